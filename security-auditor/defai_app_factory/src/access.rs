@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::get_associated_token_address, token::TokenAccount};
+
+use crate::{AppRegistration, UserAppAccess, AccessDelegation, DelegationError};
+
+// Read-only access check for backends: simulate `verify_access` and decode the
+// AccessCheck from the transaction's return data. The SFT is looked up in the
+// user's associated token account for the app's mint.
+//
+// A delegate wallet is checked by also passing the primary's AccessDelegation
+// PDA and the primary's access PDA and SFT ATA; access is granted when the
+// delegation is live and the primary's own check passes.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccessStatus {
+    Granted,
+    NoPurchase,                         // No UserAppAccess record
+    NoSft,                              // Record exists but the SFT left the wallet
+    Expired,                            // Rental past its expiry
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AccessCheck {
+    pub status: AccessStatus,
+    pub expires_at: i64,                // 0 = permanent
+    pub sft_balance: u64,
+    pub frozen: bool,                   // Soulbound, rented or under dispute
+    pub delegated_by: Option<Pubkey>,   // Primary wallet when granted via delegation
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, app_id: u64)]
+pub struct VerifyAccess<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    /// CHECK: The user's access PDA; may not exist
+    #[account(
+        seeds = [b"user_app_access".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: UncheckedAccount<'info>,
+
+    /// CHECK: The user's SFT ATA; may not exist
+    #[account(address = get_associated_token_address(&user, &app_registration.sft_mint))]
+    pub user_sft_ata: UncheckedAccount<'info>,
+
+    // Optional: a delegation naming `user` as delegate
+    #[account(
+        seeds = [b"access_delegation".as_ref(), delegation.primary.as_ref(), &app_id.to_le_bytes()],
+        bump = delegation.bump
+    )]
+    pub delegation: Option<Account<'info, AccessDelegation>>,
+
+    /// CHECK: The primary's access PDA; checked against the delegation
+    pub primary_app_access: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The primary's SFT ATA; checked against the delegation
+    pub primary_sft_ata: Option<UncheckedAccount<'info>>,
+}
+
+// Access check for one wallet from its access PDA and SFT ATA
+fn check_holder(access_info: &AccountInfo, ata_info: &AccountInfo, now: i64) -> Result<AccessCheck> {
+    let mut check = AccessCheck {
+        status: AccessStatus::NoPurchase,
+        expires_at: 0,
+        sft_balance: 0,
+        frozen: false,
+        delegated_by: None,
+    };
+
+    if access_info.data_is_empty() {
+        return Ok(check);
+    }
+    let access = UserAppAccess::try_deserialize(&mut &access_info.try_borrow_data()?[..])?;
+    check.expires_at = access.expires_at;
+
+    if !ata_info.data_is_empty() {
+        let ata = TokenAccount::try_deserialize(&mut &ata_info.try_borrow_data()?[..])?;
+        check.sft_balance = ata.amount;
+        check.frozen = ata.is_frozen();
+    }
+
+    check.status = if check.sft_balance == 0 {
+        AccessStatus::NoSft
+    } else if access.expires_at != 0 && now >= access.expires_at {
+        AccessStatus::Expired
+    } else {
+        AccessStatus::Granted
+    };
+    Ok(check)
+}
+
+pub fn verify_access(ctx: Context<VerifyAccess>, user: Pubkey, app_id: u64) -> Result<AccessCheck> {
+    let now = Clock::get()?.unix_timestamp;
+    let check = check_holder(
+        &ctx.accounts.user_app_access.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        now,
+    )?;
+    if check.status == AccessStatus::Granted {
+        return Ok(check);
+    }
+
+    // Fall back to a live delegation from the primary wallet
+    let (delegation, primary_access, primary_ata) = match (
+        &ctx.accounts.delegation,
+        &ctx.accounts.primary_app_access,
+        &ctx.accounts.primary_sft_ata,
+    ) {
+        (Some(delegation), Some(access), Some(ata)) => (delegation, access, ata),
+        _ => return Ok(check),
+    };
+    if delegation.delegate != user || !delegation.is_active(now) {
+        return Ok(check);
+    }
+
+    let primary = delegation.primary;
+    let (expected_access, _) = Pubkey::find_program_address(
+        &[b"user_app_access".as_ref(), primary.as_ref(), &app_id.to_le_bytes()],
+        &crate::ID,
+    );
+    require_keys_eq!(primary_access.key(), expected_access, DelegationError::InvalidDelegationAccounts);
+    require_keys_eq!(
+        primary_ata.key(),
+        get_associated_token_address(&primary, &ctx.accounts.app_registration.sft_mint),
+        DelegationError::InvalidDelegationAccounts
+    );
+
+    let mut delegated = check_holder(&primary_access.to_account_info(), &primary_ata.to_account_info(), now)?;
+    if delegated.status != AccessStatus::Granted {
+        return Ok(check);
+    }
+    delegated.delegated_by = Some(primary);
+    if delegation.expires_at != 0 && (delegated.expires_at == 0 || delegation.expires_at < delegated.expires_at) {
+        delegated.expires_at = delegation.expires_at;
+    }
+    Ok(delegated)
+}
@@ -0,0 +1,181 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use defai_common::{bps_of, emit_cpi_versioned, emit_versioned, EventCpi};
+
+use crate::{AppRegistration, AppFactoryError};
+
+#[constant]
+pub const MAX_AFFILIATE_BPS: u16 = 5000; // At most half of the sale
+
+#[account]
+pub struct AffiliateStats {
+    pub affiliate: Pubkey,
+    pub total_earned: u64,              // Lifetime DEFAI commissions
+    pub referral_count: u64,
+    pub bump: u8,
+}
+
+impl AffiliateStats {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct RegisterAffiliate<'info> {
+    #[account(
+        init,
+        payer = affiliate,
+        space = AffiliateStats::LEN,
+        seeds = [b"affiliate_stats".as_ref(), affiliate.key().as_ref()],
+        bump
+    )]
+    pub affiliate_stats: Account<'info, AffiliateStats>,
+
+    #[account(mut)]
+    pub affiliate: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAffiliateBps<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct AffiliateRegistered {
+    pub schema_version: u8,
+    pub affiliate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AffiliateBpsUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub affiliate_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AffiliateCommissionPaid {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub affiliate: Pubkey,
+    pub buyer: Pubkey,
+    pub commission: u64,
+    pub total_earned: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum AffiliateError {
+    #[msg("Affiliate commission cannot exceed 5000 bps")]
+    AffiliateBpsTooHigh,
+    #[msg("Affiliate stats and referrer token account must be passed together")]
+    MissingReferrerAccount,
+    #[msg("Referrer token account must be the affiliate's DEFAI account")]
+    InvalidReferrerAccount,
+    #[msg("Buyers cannot refer themselves")]
+    SelfReferral,
+}
+
+pub fn register_affiliate(ctx: Context<RegisterAffiliate>) -> Result<()> {
+    let affiliate_stats = &mut ctx.accounts.affiliate_stats;
+    affiliate_stats.affiliate = ctx.accounts.affiliate.key();
+    affiliate_stats.total_earned = 0;
+    affiliate_stats.referral_count = 0;
+    affiliate_stats.bump = ctx.bumps.affiliate_stats;
+
+    emit_versioned!(AffiliateRegistered {
+        affiliate: affiliate_stats.affiliate,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Affiliate {} registered", affiliate_stats.affiliate);
+    Ok(())
+}
+
+pub fn set_affiliate_bps(ctx: Context<SetAffiliateBps>, app_id: u64, affiliate_bps: u16) -> Result<()> {
+    require!(affiliate_bps <= MAX_AFFILIATE_BPS, AffiliateError::AffiliateBpsTooHigh);
+    ctx.accounts.app_registration.affiliate_bps = affiliate_bps;
+
+    emit_versioned!(AffiliateBpsUpdated {
+        app_id,
+        affiliate_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Affiliate commission for app {} set to {} bps", app_id, affiliate_bps);
+    Ok(())
+}
+
+// Pays the referrer their commission out of the creator share and returns
+// what is left for the creator. A no-op when no affiliate is passed.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_affiliate_commission<'info>(
+    affiliate_stats: Option<&mut AffiliateStats>,
+    referrer_defai_ata: Option<&Account<'info, TokenAccount>>,
+    user: &AccountInfo<'info>,
+    user_defai_ata: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    app_id: u64,
+    affiliate_bps: u16,
+    price: u64,
+    creator_amount: u64,
+    events: &EventCpi<'_, 'info>,
+) -> Result<u64> {
+    let (affiliate_stats, referrer_defai_ata) = match (affiliate_stats, referrer_defai_ata) {
+        (None, None) => return Ok(creator_amount),
+        (Some(stats), Some(ata)) => (stats, ata),
+        _ => return err!(AffiliateError::MissingReferrerAccount),
+    };
+    require_keys_neq!(affiliate_stats.affiliate, user.key(), AffiliateError::SelfReferral);
+    require_keys_eq!(
+        referrer_defai_ata.owner,
+        affiliate_stats.affiliate,
+        AffiliateError::InvalidReferrerAccount
+    );
+
+    let commission = bps_of(price, affiliate_bps as u64)?
+        .min(creator_amount);
+    if commission == 0 {
+        return Ok(creator_amount);
+    }
+
+    let transfer_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        Transfer {
+            from: user_defai_ata.clone(),
+            to: referrer_defai_ata.to_account_info(),
+            authority: user.clone(),
+        },
+    );
+    token::transfer(transfer_ctx, commission)?;
+
+    affiliate_stats.total_earned = affiliate_stats.total_earned
+        .checked_add(commission)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    affiliate_stats.referral_count = affiliate_stats.referral_count
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    emit_cpi_versioned!(events, AffiliateCommissionPaid {
+        app_id,
+        affiliate: affiliate_stats.affiliate,
+        buyer: user.key(),
+        commission,
+        total_earned: affiliate_stats.total_earned,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(creator_amount - commission)
+}
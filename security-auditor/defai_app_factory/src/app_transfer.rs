@@ -0,0 +1,292 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::emit_versioned;
+
+// Two-step app ownership transfer:
+// 1. The current creator proposes a new owner.
+// 2. If the factory requires it, the platform authority approves.
+// 3. The new owner accepts and becomes `app_registration.creator`, which every
+//    purchase path pays. A revenue split must be removed first so the old
+//    owner's payees don't keep receiving the creator share.
+
+#[account]
+pub struct AppTransferProposal {
+    pub app_id: u64,
+    pub from: Pubkey,                   // Creator at proposal time
+    pub to: Pubkey,                     // Proposed new creator
+    pub approved: bool,                 // Platform approval (auto-set when not required)
+    pub proposed_at: i64,
+    pub bump: u8,
+}
+
+impl AppTransferProposal {
+    pub const LEN: usize = 8 + 8 + 32 + 32 + 1 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct SetTransferApprovalRequired<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ProposeAppTransfer<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = AppTransferProposal::LEN,
+        seeds = [b"app_transfer".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub transfer_proposal: Account<'info, AppTransferProposal>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ApproveAppTransfer<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"app_transfer".as_ref(), &app_id.to_le_bytes()],
+        bump = transfer_proposal.bump
+    )]
+    pub transfer_proposal: Account<'info, AppTransferProposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct AcceptAppTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"app_transfer".as_ref(), &app_id.to_le_bytes()],
+        bump = transfer_proposal.bump,
+        constraint = transfer_proposal.to == new_creator.key() @ AppTransferError::NotProposedOwner,
+        close = previous_creator
+    )]
+    pub transfer_proposal: Account<'info, AppTransferProposal>,
+
+    /// CHECK: The app's revenue split PDA; must not exist at acceptance
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    /// CHECK: Proposing creator; receives the proposal rent
+    #[account(mut, address = transfer_proposal.from @ AppFactoryError::InvalidCreator)]
+    pub previous_creator: AccountInfo<'info>,
+
+    pub new_creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CancelAppTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_transfer".as_ref(), &app_id.to_le_bytes()],
+        bump = transfer_proposal.bump,
+        constraint = transfer_proposal.from == creator.key() @ AppFactoryError::UnauthorizedCreator,
+        close = creator
+    )]
+    pub transfer_proposal: Account<'info, AppTransferProposal>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct TransferApprovalRequirementChanged {
+    pub schema_version: u8,
+    pub required: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppTransferProposed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub needs_approval: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppTransferApproved {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppTransferred {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppTransferCancelled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum AppTransferError {
+    #[msg("Cannot transfer an app to its current creator")]
+    SameCreator,
+    #[msg("Signer is not the proposed new owner")]
+    NotProposedOwner,
+    #[msg("Transfer is awaiting platform approval")]
+    AwaitingApproval,
+    #[msg("Transfer already approved")]
+    AlreadyApproved,
+    #[msg("App creator changed since the transfer was proposed")]
+    StaleProposal,
+    #[msg("Remove the revenue split before transferring the app")]
+    RevenueSplitActive,
+}
+
+pub fn set_transfer_approval_required(
+    ctx: Context<SetTransferApprovalRequired>,
+    required: bool,
+) -> Result<()> {
+    ctx.accounts.app_factory.require_transfer_approval = required;
+
+    emit_versioned!(TransferApprovalRequirementChanged {
+        required,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App transfers {} platform approval", if required { "require" } else { "no longer require" });
+    Ok(())
+}
+
+pub fn propose_app_transfer(
+    ctx: Context<ProposeAppTransfer>,
+    app_id: u64,
+    new_creator: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(new_creator, ctx.accounts.creator.key(), AppTransferError::SameCreator);
+
+    let needs_approval = ctx.accounts.app_factory.require_transfer_approval;
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.transfer_proposal;
+    proposal.app_id = app_id;
+    proposal.from = ctx.accounts.creator.key();
+    proposal.to = new_creator;
+    proposal.approved = !needs_approval;
+    proposal.proposed_at = now;
+    proposal.bump = ctx.bumps.transfer_proposal;
+
+    emit_versioned!(AppTransferProposed {
+        app_id,
+        from: proposal.from,
+        to: new_creator,
+        needs_approval,
+        timestamp: now,
+    });
+
+    msg!("Transfer of app {} to {} proposed", app_id, new_creator);
+    Ok(())
+}
+
+pub fn approve_app_transfer(ctx: Context<ApproveAppTransfer>, app_id: u64) -> Result<()> {
+    let proposal = &mut ctx.accounts.transfer_proposal;
+    require!(!proposal.approved, AppTransferError::AlreadyApproved);
+    proposal.approved = true;
+
+    emit_versioned!(AppTransferApproved {
+        app_id,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Transfer of app {} approved", app_id);
+    Ok(())
+}
+
+pub fn accept_app_transfer(ctx: Context<AcceptAppTransfer>, app_id: u64) -> Result<()> {
+    let proposal = &ctx.accounts.transfer_proposal;
+    require!(proposal.approved, AppTransferError::AwaitingApproval);
+    require_keys_eq!(
+        proposal.from,
+        ctx.accounts.app_registration.creator,
+        AppTransferError::StaleProposal
+    );
+    require!(
+        ctx.accounts.revenue_split.data_is_empty(),
+        AppTransferError::RevenueSplitActive
+    );
+
+    let from = proposal.from;
+    let to = proposal.to;
+    ctx.accounts.app_registration.creator = to;
+
+    emit_versioned!(AppTransferred {
+        app_id,
+        from,
+        to,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} transferred from {} to {}", app_id, from, to);
+    Ok(())
+}
+
+pub fn cancel_app_transfer(_ctx: Context<CancelAppTransfer>, app_id: u64) -> Result<()> {
+    emit_versioned!(AppTransferCancelled {
+        app_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Transfer of app {} cancelled", app_id);
+    Ok(())
+}
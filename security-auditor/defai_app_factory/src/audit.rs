@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use defai_common::{audit_slot, SafeMath, AUDIT_LOG_SEED, AUDIT_LOG_SLOTS};
+
+use crate::{AppFactory, AppFactoryError};
+
+// On-chain audit trail of factory authority handovers. Each step appends an
+// entry to the `AuditLog` PDA in the same transaction, so the record exists
+// even if an indexer misses the matching event. The log is a fixed ring of
+// AUDIT_LOG_SLOTS entries; `seq` keeps counting so overwritten history shows
+// up as a gap.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    #[default]
+    None,
+    AuthorityTransferProposed,  // target: proposed authority
+    AuthorityTransferred,       // target: new authority
+    AuthorityTransferCancelled, // target: withdrawn pending authority
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub action: AuditAction,
+    pub actor: Pubkey,
+    pub subject: Pubkey,                // Account the action applied to (app_factory)
+    pub target: Pubkey,
+    pub amount: u64,
+    pub data: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl AuditEntry {
+    pub const LEN: usize = 8 + 1 + 32 + 32 + 32 + 8 + 32 + 8;
+}
+
+// AUDIT_LOG_SLOTS spelled as a literal: Anchor's IDL parser only resolves
+// array lengths from constants defined in this crate
+pub const AUDIT_LOG_LEN: usize = 32;
+const _: () = assert!(AUDIT_LOG_LEN == AUDIT_LOG_SLOTS);
+
+#[account]
+pub struct AuditLog {
+    pub total_entries: u64,
+    pub entries: [AuditEntry; AUDIT_LOG_LEN], // Ring buffer of the most recent entries
+    pub bump: u8,
+}
+
+impl AuditLog {
+    pub const LEN: usize = 8 + 8 + (AuditEntry::LEN * AUDIT_LOG_SLOTS) + 1;
+
+    pub fn record(
+        &mut self,
+        action: AuditAction,
+        actor: Pubkey,
+        subject: Pubkey,
+        target: Pubkey,
+    ) -> Result<()> {
+        let seq = self.total_entries;
+        self.entries[audit_slot(seq)] = AuditEntry {
+            seq,
+            action,
+            actor,
+            subject,
+            target,
+            amount: 0,
+            data: [0; 32],
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        self.total_entries = seq.safe_add(1)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeAuditLog<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AuditLog::LEN,
+        seeds = [AUDIT_LOG_SEED],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+    let audit_log = &mut ctx.accounts.audit_log;
+    audit_log.total_entries = 0;
+    audit_log.bump = ctx.bumps.audit_log;
+
+    msg!("Audit log initialized");
+    Ok(())
+}
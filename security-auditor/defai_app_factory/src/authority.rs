@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
-use crate::{AppFactory, AppFactoryError};
+use defai_common::{emit_versioned, AUDIT_LOG_SEED};
+
+use crate::{AppFactory, AppFactoryError, AuditAction, AuditLog};
 
 #[derive(Accounts)]
 pub struct TransferAuthority<'info> {
@@ -12,6 +14,13 @@ pub struct TransferAuthority<'info> {
     pub app_factory: Account<'info, AppFactory>,
     
     pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
@@ -26,6 +35,13 @@ pub struct AcceptAuthority<'info> {
     pub app_factory: Account<'info, AppFactory>,
     
     pub new_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
@@ -39,10 +55,18 @@ pub struct CancelAuthorityTransfer<'info> {
     pub app_factory: Account<'info, AppFactory>,
     
     pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[event]
 pub struct AuthorityTransferInitiated {
+    pub schema_version: u8,
     pub from: Pubkey,
     pub to: Pubkey,
     pub timestamp: i64,
@@ -50,12 +74,14 @@ pub struct AuthorityTransferInitiated {
 
 #[event]
 pub struct AuthorityTransferAccepted {
+    pub schema_version: u8,
     pub new_authority: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct AuthorityTransferCancelled {
+    pub schema_version: u8,
     pub timestamp: i64,
 }
 
@@ -71,7 +97,15 @@ pub fn transfer_authority(
 ) -> Result<()> {
     ctx.accounts.app_factory.pending_authority = Some(new_authority);
     
-    emit!(AuthorityTransferInitiated {
+    let app_factory_key = ctx.accounts.app_factory.key();
+    ctx.accounts.audit_log.record(
+        AuditAction::AuthorityTransferProposed,
+        ctx.accounts.authority.key(),
+        app_factory_key,
+        new_authority,
+    )?;
+    
+    emit_versioned!(AuthorityTransferInitiated {
         from: ctx.accounts.authority.key(),
         to: new_authority,
         timestamp: Clock::get()?.unix_timestamp,
@@ -93,7 +127,15 @@ pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
     app_factory.authority = ctx.accounts.new_authority.key();
     app_factory.pending_authority = None;
     
-    emit!(AuthorityTransferAccepted {
+    let app_factory_key = app_factory.key();
+    ctx.accounts.audit_log.record(
+        AuditAction::AuthorityTransferred,
+        old_authority,
+        app_factory_key,
+        ctx.accounts.new_authority.key(),
+    )?;
+    
+    emit_versioned!(AuthorityTransferAccepted {
         new_authority: ctx.accounts.new_authority.key(),
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -108,13 +150,19 @@ pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
 }
 
 pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
-    require!(
-        ctx.accounts.app_factory.pending_authority.is_some(),
-        AuthorityError::NotPendingAuthority
-    );
+    let pending_authority = ctx.accounts.app_factory.pending_authority
+        .ok_or(AuthorityError::NotPendingAuthority)?;
     ctx.accounts.app_factory.pending_authority = None;
     
-    emit!(AuthorityTransferCancelled {
+    let app_factory_key = ctx.accounts.app_factory.key();
+    ctx.accounts.audit_log.record(
+        AuditAction::AuthorityTransferCancelled,
+        ctx.accounts.authority.key(),
+        app_factory_key,
+        pending_authority,
+    )?;
+    
+    emit_versioned!(AuthorityTransferCancelled {
         timestamp: Clock::get()?.unix_timestamp,
     });
     
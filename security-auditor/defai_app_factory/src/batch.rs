@@ -0,0 +1,347 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, CreateAccount, Transfer as SystemTransfer, Allocate, Assign};
+use anchor_spl::{
+    associated_token::{self, AssociatedToken, Create, get_associated_token_address},
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased, PresaleError, VestingError, CharityError,
+    calculate_fee_split, execute_token_transfers, mint_app_sft, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, event_cpi_of};
+
+// Buys several apps at list price in one transaction. Remaining accounts come
+// in groups of ACCOUNTS_PER_APP, one group per app id, in order:
+//   0. AppRegistration (mut)
+//   1. UserAppAccess PDA (mut, uninitialized)
+//   2. SFT mint (mut)
+//   3. User's SFT ATA (mut, created if missing)
+//   4. Creator's DEFAI ATA (mut)
+//   5. Revenue split PDA (must not exist)
+// Apps with a revenue split, vested payouts or a charity share must be bought
+// individually.
+
+pub const MAX_BATCH_APPS: usize = 4;
+const ACCOUNTS_PER_APP: usize = 6;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PurchaseMultiple<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct MultipleAppsPurchased {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub app_ids: Vec<u64>,
+    pub total_price: u64,
+    pub total_platform_fee: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum BatchError {
+    #[msg("Batch must contain 1-4 apps")]
+    InvalidBatchSize,
+    #[msg("Remaining accounts do not match the app ids")]
+    InvalidBatchAccounts,
+    #[msg("Apps with a revenue split must be purchased individually")]
+    RevenueSplitActive,
+}
+
+// Creates a program-owned account at a PDA, tolerating prefunded lamports
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+    if current == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount { from: payer.clone(), to: target.clone() },
+                signer_seeds,
+            ),
+            required,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    if required > current {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                SystemTransfer { from: payer.clone(), to: target.clone() },
+            ),
+            required - current,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate { account_to_allocate: target.clone() },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign { account_to_assign: target.clone() },
+            signer_seeds,
+        ),
+        &crate::ID,
+    )
+}
+
+pub fn purchase_multiple<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PurchaseMultiple<'info>>,
+    app_ids: Vec<u64>,
+) -> Result<()> {
+    require!(
+        !app_ids.is_empty() && app_ids.len() <= MAX_BATCH_APPS,
+        BatchError::InvalidBatchSize
+    );
+    require!(
+        ctx.remaining_accounts.len() == app_ids.len() * ACCOUNTS_PER_APP,
+        BatchError::InvalidBatchAccounts
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let user_key = ctx.accounts.user.key();
+    let defai_mint = ctx.accounts.defai_mint.key();
+    let user_info = ctx.accounts.user.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+    let mut total_price: u64 = 0;
+    let mut total_platform_fee: u64 = 0;
+
+    for (i, app_id) in app_ids.iter().copied().enumerate() {
+        let group = &ctx.remaining_accounts[i * ACCOUNTS_PER_APP..(i + 1) * ACCOUNTS_PER_APP];
+        let (registration_info, access_info, mint_info, sft_ata_info, creator_ata_info, split_info) =
+            (&group[0], &group[1], &group[2], &group[3], &group[4], &group[5]);
+
+        // Validate the app
+        let mut app_registration = Box::new(Account::<AppRegistration>::try_from(registration_info)?);
+        let (expected_registration, _) = Pubkey::find_program_address(
+            &[b"app_registration".as_ref(), &app_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(registration_info.key(), expected_registration, BatchError::InvalidBatchAccounts);
+        require!(app_registration.is_active, AppFactoryError::AppNotActive);
+        require!(!app_registration.in_presale(now), PresaleError::PresaleActive);
+        require!(
+            app_registration.payout_vesting_days == 0,
+            VestingError::VestedAppUnsupportedPath
+        );
+        require!(
+            app_registration.charity_bps == 0,
+            CharityError::UnsupportedPurchasePath
+        );
+        require!(
+            app_registration.current_supply < app_registration.max_supply,
+            AppFactoryError::MaxSupplyReached
+        );
+        require_keys_eq!(mint_info.key(), app_registration.sft_mint, BatchError::InvalidBatchAccounts);
+
+        let (expected_split, _) = Pubkey::find_program_address(
+            &[b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(split_info.key(), expected_split, BatchError::InvalidBatchAccounts);
+        require!(split_info.data_is_empty(), BatchError::RevenueSplitActive);
+
+        let creator_defai_ata = Box::new(Account::<TokenAccount>::try_from(creator_ata_info)?);
+        require!(
+            creator_defai_ata.owner == app_registration.creator && creator_defai_ata.mint == defai_mint,
+            AppFactoryError::InvalidCreator
+        );
+
+        // Pay at list price
+        let price = app_registration.list_price(now);
+        let (mut platform_fee, creator_amount) = calculate_fee_split(
+            price,
+            app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+        )?;
+        require!(
+            ctx.accounts.user_defai_ata.amount >= price,
+            AppFactoryError::InsufficientBalance
+        );
+
+        // Burn the factory's share of the payment
+        let burned = burn_purchase_share(
+            &ctx.accounts.app_factory,
+            &ctx.accounts.defai_mint,
+            &ctx.accounts.user_defai_ata,
+            &user_info,
+            &ctx.accounts.token_program,
+            price,
+            &mut platform_fee,
+        )?;
+        total_price = total_price.checked_add(price).ok_or(AppFactoryError::MathOverflow)?;
+        total_platform_fee = total_platform_fee
+            .checked_add(platform_fee)
+            .ok_or(AppFactoryError::MathOverflow)?;
+
+        execute_token_transfers(
+            &ctx.accounts.user,
+            &ctx.accounts.user_defai_ata,
+            &creator_defai_ata,
+            &ctx.accounts.treasury_defai_ata,
+            &ctx.accounts.token_program,
+            platform_fee,
+            creator_amount,
+        )?;
+        // Balance checks on later apps see this payment
+        ctx.accounts.user_defai_ata.reload()?;
+
+        // Mint SFT, creating the user's ATA if needed
+        require_keys_eq!(
+            sft_ata_info.key(),
+            get_associated_token_address(&user_key, &mint_info.key()),
+            BatchError::InvalidBatchAccounts
+        );
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: user_info.clone(),
+                associated_token: sft_ata_info.clone(),
+                authority: user_info.clone(),
+                mint: mint_info.clone(),
+                system_program: system_program_info.clone(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+        let bump = app_registration.bump;
+        mint_app_sft(
+            &app_registration,
+            mint_info,
+            sft_ata_info,
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+
+        // Update supply
+        app_registration.current_supply = app_registration.current_supply
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        app_registration.record_sale(price, now)?;
+        ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+        app_registration.exit(&crate::ID)?;
+
+        // Record access
+        let (expected_access, access_bump) = Pubkey::find_program_address(
+            &[b"user_app_access".as_ref(), user_key.as_ref(), &app_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(access_info.key(), expected_access, BatchError::InvalidBatchAccounts);
+        require!(access_info.data_is_empty(), BatchError::InvalidBatchAccounts);
+        let access_seeds = &[
+            b"user_app_access".as_ref(),
+            user_key.as_ref(),
+            &app_id.to_le_bytes(),
+            &[access_bump],
+        ];
+        create_pda_account(
+            &user_info,
+            access_info,
+            &system_program_info,
+            UserAppAccess::LEN,
+            &[&access_seeds[..]],
+        )?;
+        let user_app_access = UserAppAccess {
+            user: user_key,
+            app_id,
+            sft_token_account: sft_ata_info.key(),
+            purchased_at: now,
+            purchase_price: price,
+            bump: access_bump,
+            expires_at: 0,
+            payment_mint: defai_mint,
+            rent_payer: user_key,
+            paid_out: PurchaseSplit::from_sale(&app_registration, price, burned, platform_fee, 0, creator_amount, creator_amount)?,
+            disputed: false,
+        };
+        user_app_access.try_serialize(&mut &mut access_info.try_borrow_mut_data()?[..])?;
+
+        let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+        emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+            app_id,
+            user: user_key,
+            price,
+            platform_fee,
+            creator_amount,
+            payment_mint: defai_mint,
+            timestamp: now,
+            purchase_id,
+            burned,
+            sft_mint: app_registration.sft_mint,
+            metadata_uri: app_registration.metadata_uri.clone(),
+            edition_id: None,
+        });
+    }
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), MultipleAppsPurchased {
+        user: user_key,
+        app_ids: app_ids.clone(),
+        total_price,
+        total_platform_fee,
+        timestamp: now,
+    });
+
+    msg!("User purchased {} apps for {} DEFAI", app_ids.len(), total_price);
+    Ok(())
+}
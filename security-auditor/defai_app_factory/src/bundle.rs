@@ -0,0 +1,561 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, Transfer},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased, PresaleError, VestingError, CharityError,
+    calculate_fee_split, mint_app_sft, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of, mul_div};
+
+// Bundles sell several apps at a combined price. Payment happens once in
+// `purchase_bundle`; each app's SFT is then minted by `claim_bundle_app`, one
+// app per transaction, to keep the account list (and stack) small.
+
+pub const MAX_BUNDLE_APPS: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BundleItem {
+    pub app_id: u64,
+    pub creator: Pubkey,                // Creator who approved the bundle; payouts follow the registration
+    pub weight: u64,                    // App list price when bundled; drives the payment split
+}
+
+impl BundleItem {
+    pub const LEN: usize = 8 + 32 + 8;
+}
+
+#[account]
+pub struct Bundle {
+    pub creator: Pubkey,
+    pub bundle_id: u64,
+    pub items: Vec<BundleItem>,
+    pub price: u64,                     // Combined price in DEFAI
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl Bundle {
+    pub const LEN: usize = 8 + 32 + 8 + (4 + MAX_BUNDLE_APPS * BundleItem::LEN) + 8 + 1 + 1;
+
+    pub fn total_weight(&self) -> u64 {
+        self.items.iter().map(|item| item.weight).sum()
+    }
+
+    // Portion of `amount` owed for the item at `index`; dust goes to the first item
+    pub fn share_of(&self, index: usize, amount: u64) -> Result<u64> {
+        let total_weight = self.total_weight() as u128;
+        let mut shares = [0u64; MAX_BUNDLE_APPS];
+        let mut allocated = 0u64;
+        for (i, item) in self.items.iter().enumerate() {
+            shares[i] = (amount as u128)
+                .checked_mul(item.weight as u128)
+                .ok_or(AppFactoryError::MathOverflow)?
+                .checked_div(total_weight)
+                .ok_or(AppFactoryError::MathOverflow)? as u64;
+            allocated += shares[i];
+        }
+        shares[0] += amount - allocated;
+        Ok(shares[index])
+    }
+}
+
+#[account]
+pub struct BundlePurchase {
+    pub user: Pubkey,
+    pub bundle: Pubkey,
+    pub price_paid: u64,
+    pub claimed_mask: u8,               // Bit i set once item i has been claimed
+    pub purchased_at: i64,
+    pub bump: u8,
+    pub paid_out: [PurchaseSplit; MAX_BUNDLE_APPS], // Where item i's share of the payment went, copied to its access on claim
+}
+
+impl BundlePurchase {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 8 + 1 + MAX_BUNDLE_APPS * PurchaseSplit::LEN;
+}
+
+#[derive(Accounts)]
+#[instruction(bundle_id: u64)]
+pub struct CreateBundle<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = Bundle::LEN,
+        seeds = [b"bundle".as_ref(), creator.key().as_ref(), &bundle_id.to_le_bytes()],
+        bump
+    )]
+    pub bundle: Account<'info, Bundle>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bundle_id: u64)]
+pub struct SetBundleStatus<'info> {
+    #[account(
+        mut,
+        seeds = [b"bundle".as_ref(), creator.key().as_ref(), &bundle_id.to_le_bytes()],
+        bump = bundle.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub bundle: Account<'info, Bundle>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PurchaseBundle<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"bundle".as_ref(), bundle.creator.as_ref(), &bundle.bundle_id.to_le_bytes()],
+        bump = bundle.bump
+    )]
+    pub bundle: Box<Account<'info, Bundle>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = BundlePurchase::LEN,
+        seeds = [b"bundle_purchase".as_ref(), user.key().as_ref(), bundle.key().as_ref()],
+        bump
+    )]
+    pub bundle_purchase: Box<Account<'info, BundlePurchase>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user,
+        constraint = user_defai_ata.amount >= bundle.price
+            @ AppFactoryError::InsufficientBalance
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ClaimBundleApp<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        seeds = [b"bundle".as_ref(), bundle.creator.as_ref(), &bundle.bundle_id.to_le_bytes()],
+        bump = bundle.bump
+    )]
+    pub bundle: Box<Account<'info, Bundle>>,
+
+    #[account(
+        mut,
+        seeds = [b"bundle_purchase".as_ref(), user.key().as_ref(), bundle.key().as_ref()],
+        bump = bundle_purchase.bump,
+        has_one = user
+    )]
+    pub bundle_purchase: Box<Account<'info, BundlePurchase>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct BundleCreated {
+    pub schema_version: u8,
+    pub creator: Pubkey,
+    pub bundle_id: u64,
+    pub app_ids: Vec<u64>,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BundlePurchased {
+    pub schema_version: u8,
+    pub bundle: Pubkey,
+    pub user: Pubkey,
+    pub price: u64,
+    pub platform_fee: u64,
+    pub timestamp: i64,
+    pub burned: u64,                    // DEFAI burned from the payment (taken from platform_fee)
+}
+
+#[error_code]
+pub enum BundleError {
+    #[msg("Bundle must contain 2-8 apps")]
+    InvalidBundleSize,
+    #[msg("Duplicate app in bundle")]
+    DuplicateApp,
+    #[msg("Bundle price must be below the sum of app prices")]
+    BundlePriceTooHigh,
+    #[msg("Missing or invalid app registration for bundle item")]
+    InvalidBundleApp,
+    #[msg("Every app creator must sign the bundle")]
+    MissingCreatorSignature,
+    #[msg("Missing or invalid creator token account")]
+    InvalidCreatorAccount,
+    #[msg("Bundle is not active")]
+    BundleNotActive,
+    #[msg("App is not part of this bundle")]
+    AppNotInBundle,
+    #[msg("Bundle app already claimed")]
+    AlreadyClaimed,
+    #[msg("Apps with a revenue split can't be sold in a bundle")]
+    RevenueSplitActive,
+}
+
+// Deserializes a program-owned AppRegistration passed as a remaining account
+fn load_app_registration(info: &AccountInfo, app_id: u64) -> Result<AppRegistration> {
+    require!(info.owner == &crate::ID, BundleError::InvalidBundleApp);
+    let app = AppRegistration::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(app.app_id == app_id, BundleError::InvalidBundleApp);
+    Ok(app)
+}
+
+// Remaining accounts: one AppRegistration per app, followed by the signer
+// accounts of every distinct creator other than the bundle creator.
+pub fn create_bundle(
+    ctx: Context<CreateBundle>,
+    bundle_id: u64,
+    app_ids: Vec<u64>,
+    price: u64,
+) -> Result<()> {
+    require!(
+        app_ids.len() >= 2 && app_ids.len() <= MAX_BUNDLE_APPS,
+        BundleError::InvalidBundleSize
+    );
+    require!(price > 0, AppFactoryError::InvalidPrice);
+    require!(
+        ctx.remaining_accounts.len() >= app_ids.len(),
+        BundleError::InvalidBundleApp
+    );
+
+    let (registrations, signers) = ctx.remaining_accounts.split_at(app_ids.len());
+    let creator_key = ctx.accounts.creator.key();
+    let now = Clock::get()?.unix_timestamp;
+    let mut items = Vec::with_capacity(app_ids.len());
+    let mut list_total = 0u64;
+    for (i, app_id) in app_ids.iter().enumerate() {
+        require!(!app_ids[..i].contains(app_id), BundleError::DuplicateApp);
+        let app = load_app_registration(&registrations[i], *app_id)?;
+        require!(app.is_active, AppFactoryError::AppNotActive);
+
+        // Co-creators consent to their app being sold at a bundle discount
+        if app.creator != creator_key {
+            require!(
+                signers.iter().any(|s| s.is_signer && s.key() == app.creator),
+                BundleError::MissingCreatorSignature
+            );
+        }
+
+        let list_price = app.list_price(now);
+        list_total = list_total.checked_add(list_price).ok_or(AppFactoryError::MathOverflow)?;
+        items.push(BundleItem {
+            app_id: *app_id,
+            creator: app.creator,
+            weight: list_price,
+        });
+    }
+    require!(price < list_total, BundleError::BundlePriceTooHigh);
+
+    let bundle = &mut ctx.accounts.bundle;
+    bundle.creator = creator_key;
+    bundle.bundle_id = bundle_id;
+    bundle.items = items;
+    bundle.price = price;
+    bundle.is_active = true;
+    bundle.bump = ctx.bumps.bundle;
+
+    emit_versioned!(BundleCreated {
+        creator: creator_key,
+        bundle_id,
+        app_ids,
+        price,
+        timestamp: now,
+    });
+
+    msg!("Bundle {} created by {} for {} DEFAI", bundle_id, creator_key, price);
+    Ok(())
+}
+
+pub fn set_bundle_status(ctx: Context<SetBundleStatus>, bundle_id: u64, is_active: bool) -> Result<()> {
+    ctx.accounts.bundle.is_active = is_active;
+    msg!("Bundle {} is now {}", bundle_id, if is_active { "active" } else { "inactive" });
+    Ok(())
+}
+
+// Remaining accounts: (AppRegistration, creator DEFAI ATA, revenue split PDA)
+// per bundle item, in order. The registrations are writable: each app's unit is
+// reserved at purchase, so a later sell-out can't strand a paid claim.
+pub fn purchase_bundle<'info>(ctx: Context<'_, '_, '_, 'info, PurchaseBundle<'info>>) -> Result<()> {
+    let bundle = &ctx.accounts.bundle;
+    require!(bundle.is_active, BundleError::BundleNotActive);
+    require!(
+        ctx.remaining_accounts.len() == bundle.items.len() * 3,
+        BundleError::InvalidBundleApp
+    );
+
+    // Validate every app and pay each app's current creator their weighted
+    // share, less that app's own platform fee
+    let now = Clock::get()?.unix_timestamp;
+    let defai_mint = ctx.accounts.defai_mint.key();
+    let mut platform_fee = 0u64;
+    let mut item_fees = [0u64; MAX_BUNDLE_APPS];
+    let mut item_shares = [0u64; MAX_BUNDLE_APPS];
+    for (i, item) in bundle.items.iter().enumerate() {
+        let app_info = &ctx.remaining_accounts[3 * i];
+        let mut app = load_app_registration(app_info, item.app_id)?;
+        require!(app.is_active, AppFactoryError::AppNotActive);
+        require!(!app.in_presale(now), PresaleError::PresaleActive);
+        require!(app.payout_vesting_days == 0, VestingError::VestedAppUnsupportedPath);
+        require!(app.charity_bps == 0, CharityError::UnsupportedPurchasePath);
+        require!(app.current_supply < app.max_supply, AppFactoryError::MaxSupplyReached);
+        require!(app_info.is_writable, BundleError::InvalidBundleApp);
+        app.current_supply = app.current_supply
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        app.try_serialize(&mut &mut app_info.try_borrow_mut_data()?[..])?;
+
+        // Splits only apply to individual purchases
+        let split_info = &ctx.remaining_accounts[3 * i + 2];
+        let (expected_split, _) = Pubkey::find_program_address(
+            &[b"revenue_split".as_ref(), &item.app_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(split_info.key(), expected_split, BundleError::InvalidBundleApp);
+        require!(split_info.data_is_empty(), BundleError::RevenueSplitActive);
+
+        let creator_ata_info = &ctx.remaining_accounts[3 * i + 1];
+        require!(creator_ata_info.owner == &token::ID, BundleError::InvalidCreatorAccount);
+        let creator_ata = TokenAccount::try_deserialize(&mut &creator_ata_info.try_borrow_data()?[..])?;
+        require!(
+            creator_ata.owner == app.creator && creator_ata.mint == defai_mint,
+            BundleError::InvalidCreatorAccount
+        );
+
+        let item_price = bundle.share_of(i, bundle.price)?;
+        let (item_fee, share) = calculate_fee_split(
+            item_price,
+            app.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+        )?;
+        platform_fee = platform_fee.checked_add(item_fee).ok_or(AppFactoryError::MathOverflow)?;
+        item_fees[i] = item_fee;
+        item_shares[i] = share;
+        if share == 0 {
+            continue;
+        }
+        let creator_transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_defai_ata.to_account_info(),
+                to: creator_ata_info.clone(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(creator_transfer_ctx, share)?;
+    }
+
+    // Burn the factory's share of the bundle price
+    let fee_total = platform_fee;
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.bundle.price,
+        &mut platform_fee,
+    )?;
+
+    // Transfer platform fee
+    let platform_transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.user_defai_ata.to_account_info(),
+            to: ctx.accounts.treasury_defai_ata.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::transfer(platform_transfer_ctx, platform_fee)?;
+
+    ctx.accounts.factory_stats.record_sale(ctx.accounts.bundle.price, platform_fee)?;
+
+    let bundle_purchase = &mut ctx.accounts.bundle_purchase;
+    bundle_purchase.user = ctx.accounts.user.key();
+    bundle_purchase.bundle = ctx.accounts.bundle.key();
+    bundle_purchase.price_paid = ctx.accounts.bundle.price;
+    bundle_purchase.claimed_mask = 0;
+    bundle_purchase.purchased_at = now;
+    bundle_purchase.bump = ctx.bumps.bundle_purchase;
+
+    // Attribute the burn to items by their platform fee. Rounding on the running
+    // total hands out every burned unit and never more than an item's own fee.
+    let mut fee_so_far = 0u64;
+    let mut burned_so_far = 0u64;
+    for i in 0..ctx.accounts.bundle.items.len() {
+        fee_so_far += item_fees[i];
+        let burned_through = if fee_total == 0 { 0 } else { mul_div(burned, fee_so_far, fee_total)? };
+        let item_burned = burned_through - burned_so_far;
+        burned_so_far = burned_through;
+        bundle_purchase.paid_out[i] = PurchaseSplit {
+            burned: item_burned,
+            treasury: item_fees[i] - item_burned,
+            creator: item_shares[i],
+            ..Default::default()
+        };
+    }
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), BundlePurchased {
+        bundle: bundle_purchase.bundle,
+        user: bundle_purchase.user,
+        price: bundle_purchase.price_paid,
+        platform_fee,
+        timestamp: now,
+        burned,
+    });
+
+    msg!("User purchased bundle {}", ctx.accounts.bundle.bundle_id);
+    Ok(())
+}
+
+pub fn claim_bundle_app(ctx: Context<ClaimBundleApp>, app_id: u64) -> Result<()> {
+    let index = ctx.accounts.bundle.items
+        .iter()
+        .position(|item| item.app_id == app_id)
+        .ok_or(BundleError::AppNotInBundle)?;
+    let bit = 1u8 << index;
+    require!(ctx.accounts.bundle_purchase.claimed_mask & bit == 0, BundleError::AlreadyClaimed);
+
+    // Mint SFT; purchase_bundle already counted it in the supply
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    ctx.accounts.bundle_purchase.claimed_mask |= bit;
+
+    // Attribute this app's share of the bundle price to the access record
+    let price = ctx.accounts.bundle.share_of(index, ctx.accounts.bundle_purchase.price_paid)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    let paid_out = ctx.accounts.bundle_purchase.paid_out[index];
+
+    // Record access
+    let now = Clock::get()?.unix_timestamp;
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = ctx.accounts.bundle_purchase.purchased_at;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.app_factory.defai_mint;
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = paid_out;
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee: paid_out.treasury,
+        creator_amount: paid_out.creator,
+        payment_mint: ctx.accounts.app_factory.defai_mint,
+        timestamp: now,
+        purchase_id,
+        burned: 0,                      // Burned once for the whole bundle, see BundlePurchased
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("User claimed app {} from bundle {}", app_id, ctx.accounts.bundle.bundle_id);
+    Ok(())
+}
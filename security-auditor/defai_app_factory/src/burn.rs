@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, Token, TokenAccount};
+use defai_common::{bps_of, emit_versioned};
+
+use crate::{AppFactory, AppFactoryError};
+
+// Purchase burn. A configurable share of every DEFAI app purchase is burned
+// from the buyer's payment. The burn comes out of the platform fee (and is
+// capped at it), so creator earnings and the price paid are unchanged.
+
+#[derive(Accounts)]
+pub struct SetBurnBps<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct BurnBpsSet {
+    pub schema_version: u8,
+    pub burn_bps: u16,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum BurnError {
+    #[msg("Burn cannot exceed 100%")]
+    InvalidBurnBps,
+}
+
+pub fn set_burn_bps(ctx: Context<SetBurnBps>, burn_bps: u16) -> Result<()> {
+    require!(burn_bps <= 10000, BurnError::InvalidBurnBps);
+    ctx.accounts.app_factory.burn_bps = burn_bps;
+
+    emit_versioned!(BurnBpsSet {
+        burn_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Purchase burn set to {}%", burn_bps as f64 / 100.0);
+    Ok(())
+}
+
+// Burn the factory's share of a purchase from the payer's DEFAI and take it
+// off the platform fee. Returns the amount burned.
+pub(crate) fn burn_purchase_share<'info>(
+    app_factory: &AppFactory,
+    defai_mint: &Account<'info, Mint>,
+    payer_defai_ata: &Account<'info, TokenAccount>,
+    payer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    price: u64,
+    platform_fee: &mut u64,
+) -> Result<u64> {
+    let burn_amount = bps_of(price, app_factory.burn_bps as u64)?
+        .min(*platform_fee);
+    if burn_amount == 0 {
+        return Ok(0);
+    }
+
+    token::burn(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Burn {
+                mint: defai_mint.to_account_info(),
+                from: payer_defai_ata.to_account_info(),
+                authority: payer.clone(),
+            },
+        ),
+        burn_amount,
+    )?;
+    *platform_fee -= burn_amount;
+
+    Ok(burn_amount)
+}
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::emit_versioned;
+
+pub const MAX_CATEGORIES: usize = 64;
+pub const MAX_CATEGORY_NAME_LEN: usize = 24;
+pub const MAX_APP_CATEGORIES: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Category {
+    pub name: String,
+    pub is_active: bool,                // Retired categories can't be newly attached
+}
+
+// Platform-managed category list; a category id is its index in `categories`
+#[account]
+pub struct CategoryRegistry {
+    pub categories: Vec<Category>,
+    pub bump: u8,
+}
+
+impl CategoryRegistry {
+    pub const LEN: usize = 8 + 4 + MAX_CATEGORIES * (4 + MAX_CATEGORY_NAME_LEN + 1) + 1;
+
+    // Checks a creator-supplied category list against the registry
+    pub fn validate(&self, category_ids: &[u16]) -> Result<()> {
+        require!(
+            category_ids.len() <= MAX_APP_CATEGORIES,
+            CategoryError::TooManyAppCategories
+        );
+        for (i, id) in category_ids.iter().enumerate() {
+            let category = self.categories
+                .get(*id as usize)
+                .ok_or(CategoryError::UnknownCategory)?;
+            require!(category.is_active, CategoryError::CategoryNotActive);
+            require!(!category_ids[..i].contains(id), CategoryError::DuplicateCategory);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AddCategory<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CategoryRegistry::LEN,
+        seeds = [b"category_registry"],
+        bump
+    )]
+    pub category_registry: Account<'info, CategoryRegistry>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCategoryStatus<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"category_registry"],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Account<'info, CategoryRegistry>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppCategories<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        seeds = [b"category_registry"],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Account<'info, CategoryRegistry>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct CategoryAdded {
+    pub schema_version: u8,
+    pub category_id: u16,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CategoryStatusChanged {
+    pub schema_version: u8,
+    pub category_id: u16,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppCategoriesUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub categories: Vec<u16>,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CategoryError {
+    #[msg("Category name must be 1-24 characters")]
+    InvalidCategoryName,
+    #[msg("Category registry is full")]
+    CategoryRegistryFull,
+    #[msg("Unknown category id")]
+    UnknownCategory,
+    #[msg("Category is not active")]
+    CategoryNotActive,
+    #[msg("Duplicate category id")]
+    DuplicateCategory,
+    #[msg("Too many categories for one app (max 4)")]
+    TooManyAppCategories,
+    #[msg("Category registry required to attach categories")]
+    MissingCategoryRegistry,
+}
+
+pub fn add_category(ctx: Context<AddCategory>, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_CATEGORY_NAME_LEN,
+        CategoryError::InvalidCategoryName
+    );
+
+    let registry = &mut ctx.accounts.category_registry;
+    require!(registry.categories.len() < MAX_CATEGORIES, CategoryError::CategoryRegistryFull);
+    registry.bump = ctx.bumps.category_registry;
+
+    let category_id = registry.categories.len() as u16;
+    registry.categories.push(Category {
+        name: name.clone(),
+        is_active: true,
+    });
+
+    emit_versioned!(CategoryAdded {
+        category_id,
+        name: name.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Category {} added: {}", category_id, name);
+    Ok(())
+}
+
+pub fn set_category_status(
+    ctx: Context<SetCategoryStatus>,
+    category_id: u16,
+    is_active: bool,
+) -> Result<()> {
+    let category = ctx.accounts.category_registry.categories
+        .get_mut(category_id as usize)
+        .ok_or(CategoryError::UnknownCategory)?;
+    category.is_active = is_active;
+
+    emit_versioned!(CategoryStatusChanged {
+        category_id,
+        is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Category {} is now {}",
+        category_id,
+        if is_active { "active" } else { "inactive" }
+    );
+    Ok(())
+}
+
+pub fn set_app_categories(
+    ctx: Context<SetAppCategories>,
+    app_id: u64,
+    categories: Vec<u16>,
+) -> Result<()> {
+    ctx.accounts.category_registry.validate(&categories)?;
+    ctx.accounts.app_registration.categories = categories.clone();
+    // Cached category fee no longer applies; re-run sync_app_category_fee
+    ctx.accounts.app_registration.category_fee_bps = None;
+
+    emit_versioned!(AppCategoriesUpdated {
+        app_id,
+        categories,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Categories updated for app {}", app_id);
+    Ok(())
+}
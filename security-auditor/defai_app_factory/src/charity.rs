@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use defai_common::{bps_of, emit_cpi_versioned, emit_versioned, EventCpi};
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+
+// Charity donations. The platform authority registers vetted donation
+// wallets; a creator can then dedicate a bps share of each DEFAI sale to one
+// of them. The donation comes out of the creator share and is paid by the
+// buyer as a third transfer leg. The wallet and share are mirrored on
+// AppRegistration so purchases can't skip the donation by omitting accounts.
+
+pub const MAX_CHARITY_NAME_LEN: usize = 32;
+
+#[account]
+pub struct Charity {
+    pub wallet: Pubkey,                 // Receives donations (DEFAI ATA owner)
+    pub name: String,
+    pub is_active: bool,                // Retired charities can't be newly selected
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl Charity {
+    pub const LEN: usize = 8 + 32 + (4 + MAX_CHARITY_NAME_LEN) + 1 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct RegisterCharity<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Charity::LEN,
+        seeds = [b"charity".as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub charity: Account<'info, Charity>,
+
+    /// CHECK: Donation wallet; any address may receive DEFAI
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCharityStatus<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"charity".as_ref(), charity.wallet.as_ref()],
+        bump = charity.bump
+    )]
+    pub charity: Account<'info, Charity>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppCharity<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        seeds = [b"charity".as_ref(), charity.wallet.as_ref()],
+        bump = charity.bump
+    )]
+    pub charity: Option<Account<'info, Charity>>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct CharityRegistered {
+    pub schema_version: u8,
+    pub wallet: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharityStatusChanged {
+    pub schema_version: u8,
+    pub wallet: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppCharitySet {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub charity: Option<Pubkey>,
+    pub charity_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CharityDonationPaid {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub charity: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CharityError {
+    #[msg("Charity name must be 1-32 characters")]
+    InvalidCharityName,
+    #[msg("Charity is not active")]
+    CharityNotActive,
+    #[msg("Charity share must be 1-10000 bps with a charity, 0 without")]
+    InvalidCharityBps,
+    #[msg("Pass the charity's DEFAI account for apps that donate")]
+    MissingCharityAccount,
+    #[msg("Charity token account must be owned by the app's charity")]
+    InvalidCharityAccount,
+    #[msg("Apps that donate must be bought individually with DEFAI")]
+    UnsupportedPurchasePath,
+}
+
+pub fn register_charity(ctx: Context<RegisterCharity>, name: String) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_CHARITY_NAME_LEN,
+        CharityError::InvalidCharityName
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let charity = &mut ctx.accounts.charity;
+    charity.wallet = ctx.accounts.wallet.key();
+    charity.name = name.clone();
+    charity.is_active = true;
+    charity.registered_at = now;
+    charity.bump = ctx.bumps.charity;
+
+    emit_versioned!(CharityRegistered {
+        wallet: charity.wallet,
+        name,
+        timestamp: now,
+    });
+
+    msg!("Charity {} registered", charity.wallet);
+    Ok(())
+}
+
+pub fn set_charity_status(ctx: Context<SetCharityStatus>, is_active: bool) -> Result<()> {
+    let charity = &mut ctx.accounts.charity;
+    charity.is_active = is_active;
+
+    emit_versioned!(CharityStatusChanged {
+        wallet: charity.wallet,
+        is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Charity {} is now {}", charity.wallet, if is_active { "active" } else { "retired" });
+    Ok(())
+}
+
+// Pass a charity and a non-zero share to donate; omit the charity and pass 0
+// to stop donating.
+pub fn set_app_charity(ctx: Context<SetAppCharity>, app_id: u64, charity_bps: u16) -> Result<()> {
+    let charity_wallet = match &ctx.accounts.charity {
+        Some(charity) => {
+            require!(charity.is_active, CharityError::CharityNotActive);
+            require!(charity_bps > 0 && charity_bps <= 10000, CharityError::InvalidCharityBps);
+            Some(charity.wallet)
+        }
+        None => {
+            require!(charity_bps == 0, CharityError::InvalidCharityBps);
+            None
+        }
+    };
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.charity = charity_wallet;
+    app_registration.charity_bps = charity_bps;
+
+    emit_versioned!(AppCharitySet {
+        app_id,
+        charity: charity_wallet,
+        charity_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} donates {} bps to {:?}", app_id, charity_bps, charity_wallet);
+    Ok(())
+}
+
+// Pays the app's charity share of `price` out of the creator share and
+// returns what is left for the creator. A no-op for apps without a charity.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_charity_donation<'info>(
+    app_registration: &AppRegistration,
+    charity_defai_ata: Option<&Account<'info, TokenAccount>>,
+    user: &AccountInfo<'info>,
+    user_defai_ata: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    app_id: u64,
+    price: u64,
+    creator_amount: u64,
+    events: &EventCpi<'_, 'info>,
+) -> Result<u64> {
+    let charity = match app_registration.charity {
+        Some(charity) if app_registration.charity_bps > 0 => charity,
+        _ => return Ok(creator_amount),
+    };
+    let charity_defai_ata = charity_defai_ata.ok_or(CharityError::MissingCharityAccount)?;
+    require_keys_eq!(charity_defai_ata.owner, charity, CharityError::InvalidCharityAccount);
+
+    let donation = bps_of(price, app_registration.charity_bps as u64)?
+        .min(creator_amount);
+    if donation == 0 {
+        return Ok(creator_amount);
+    }
+
+    let transfer_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        Transfer {
+            from: user_defai_ata.clone(),
+            to: charity_defai_ata.to_account_info(),
+            authority: user.clone(),
+        },
+    );
+    token::transfer(transfer_ctx, donation)?;
+
+    emit_cpi_versioned!(events, CharityDonationPaid {
+        app_id,
+        charity,
+        buyer: user.key(),
+        amount: donation,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(creator_amount - donation)
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount};
+
+use crate::{AppRegistration, UserAppAccess};
+use defai_common::emit_versioned;
+
+// Rent reclamation for dead access records. A UserAppAccess outlives the SFT
+// it was issued for when the holder sells, burns or migrates the token, or when
+// a rental's SFT is gone before `expire_access` runs. Once the recorded SFT
+// account holds nothing, anyone may close the record and its rent goes back to
+// whoever paid it. Rentals still holding their SFT go through `expire_access`;
+// withdrawn and orphaned reviews are closed by `delete_review` / `purge_review`.
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CloseAccess<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        has_one = user,
+        close = rent_payer
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: The SFT account recorded at purchase; may have been closed
+    #[account(address = user_app_access.sft_token_account)]
+    pub user_sft_ata: UncheckedAccount<'info>,
+
+    /// CHECK: Access holder; bound via has_one
+    pub user: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the rent; must be the access record's payer
+    #[account(
+        mut,
+        address = user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    // Anyone may crank a dead access record
+    pub caller: Signer<'info>,
+}
+
+#[event]
+pub struct AccessClosed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub rent_payer: Pubkey,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CleanupError {
+    #[msg("User still holds the app SFT")]
+    SftStillHeld,
+    #[msg("Rent must be returned to the account's original payer")]
+    WrongRentPayer,
+}
+
+pub fn close_access(ctx: Context<CloseAccess>, app_id: u64) -> Result<()> {
+    // A closed or emptied SFT account no longer backs the access
+    let ata_info = ctx.accounts.user_sft_ata.to_account_info();
+    if !ata_info.data_is_empty() && *ata_info.owner == token::ID {
+        let ata = TokenAccount::try_deserialize(&mut &ata_info.try_borrow_data()?[..])?;
+        require!(
+            ata.mint != ctx.accounts.app_registration.sft_mint || ata.amount == 0,
+            CleanupError::SftStillHeld
+        );
+    }
+
+    emit_versioned!(AccessClosed {
+        app_id,
+        user: ctx.accounts.user.key(),
+        rent_payer: ctx.accounts.rent_payer.key(),
+        caller: ctx.accounts.caller.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Access record of {} for app {} closed", ctx.accounts.user.key(), app_id);
+    Ok(())
+}
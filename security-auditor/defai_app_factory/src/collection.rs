@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, Mint};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{CreateV1CpiBuilder, UpdateV1CpiBuilder, VerifyCollectionV1CpiBuilder},
+    types::{Collection, Creator, Data, TokenStandard},
+};
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::emit_versioned;
+
+// Attaches an app's SFT mint to the "DEFAI APPs" master collection so wallets
+// group every app under it. The registration PDA (mint authority) creates the
+// Token Metadata account and the app_factory PDA verifies it, so the master
+// collection's update authority must be the app_factory PDA.
+//
+// The app's royalty_bps and creator are written into the metadata so
+// marketplaces pay secondary-sale royalties. Royalties are not enforced on
+// transfer: pNFT rule sets need a supply-1 mint with a master edition, which
+// the shared per-app SFT mint cannot be.
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CreateAppSftMetadata<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: App SFT metadata PDA, created by Token Metadata
+    #[account(
+        mut,
+        seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), sft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(address = app_factory.master_collection @ CollectionError::NotMasterCollection)]
+    pub collection_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Master collection metadata, validated by Token Metadata
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Master collection master edition, validated by Token Metadata
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: Token Metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by Token Metadata
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+// Every SFT of an app shares one mint and so one metadata account; pointing it
+// at the registration's current metadata_uri updates all minted SFTs at once
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct UpdateSftMetadata<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(address = app_registration.sft_mint)]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: App SFT metadata PDA, validated by Token Metadata
+    #[account(
+        mut,
+        seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), sft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: Token Metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by Token Metadata
+    #[account(address = solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppSftMetadataUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub sft_mint: Pubkey,
+    pub metadata_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppCollectionVerified {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub sft_mint: Pubkey,
+    pub collection: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CollectionError {
+    #[msg("Collection mint is not the factory's master collection")]
+    NotMasterCollection,
+    #[msg("SFT name or symbol too long")]
+    SftNameTooLong,
+    #[msg("Royalty too high (max 2000 basis points)")]
+    RoyaltyTooHigh,
+    #[msg("SFT metadata account could not be read")]
+    InvalidSftMetadata,
+}
+
+pub const MAX_SFT_NAME_LEN: usize = 32;
+pub const MAX_SFT_SYMBOL_LEN: usize = 10;
+#[constant]
+pub const MAX_ROYALTY_BPS: u16 = 2000;
+
+pub fn create_app_sft_metadata(
+    ctx: Context<CreateAppSftMetadata>,
+    app_id: u64,
+    name: String,
+    symbol: String,
+) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= MAX_SFT_NAME_LEN && symbol.len() <= MAX_SFT_SYMBOL_LEN,
+        CollectionError::SftNameTooLong
+    );
+
+    let metadata_program = ctx.accounts.token_metadata_program.to_account_info();
+    let registration_info = ctx.accounts.app_registration.to_account_info();
+    let sft_mint_info = ctx.accounts.sft_mint.to_account_info();
+    let metadata_info = ctx.accounts.metadata.to_account_info();
+    let creator_info = ctx.accounts.creator.to_account_info();
+    let system_program_info = ctx.accounts.system_program.to_account_info();
+    let sysvar_info = ctx.accounts.sysvar_instructions.to_account_info();
+    let token_program_info = ctx.accounts.token_program.to_account_info();
+    let master_collection = ctx.accounts.app_factory.master_collection;
+    let creators = vec![Creator {
+        address: ctx.accounts.creator.key(),
+        verified: false,
+        share: 100,
+    }];
+
+    // Create the metadata as the mint authority (registration PDA), pointing
+    // at the master collection unverified
+    let app_id_bytes = app_id.to_le_bytes();
+    let registration_seeds = &[
+        b"app_registration".as_ref(),
+        app_id_bytes.as_ref(),
+        &[ctx.accounts.app_registration.bump],
+    ];
+    CreateV1CpiBuilder::new(&metadata_program)
+        .metadata(&metadata_info)
+        .mint(&sft_mint_info, false)
+        .authority(&registration_info)
+        .payer(&creator_info)
+        .update_authority(&registration_info, true)
+        .system_program(&system_program_info)
+        .sysvar_instructions(&sysvar_info)
+        .spl_token_program(Some(&token_program_info))
+        .name(name)
+        .symbol(symbol)
+        .uri(ctx.accounts.app_registration.metadata_uri.clone())
+        .seller_fee_basis_points(ctx.accounts.app_registration.royalty_bps)
+        .creators(creators)
+        .is_mutable(true)
+        .token_standard(TokenStandard::FungibleAsset)
+        .decimals(0)
+        .collection(Collection { verified: false, key: master_collection })
+        .invoke_signed(&[&registration_seeds[..]])?;
+
+    // Verify membership as the collection's update authority (app_factory PDA)
+    let factory_seeds = &[b"app_factory".as_ref(), &[ctx.accounts.app_factory.bump]];
+    VerifyCollectionV1CpiBuilder::new(&metadata_program)
+        .authority(&ctx.accounts.app_factory.to_account_info())
+        .metadata(&metadata_info)
+        .collection_mint(&ctx.accounts.collection_mint.to_account_info())
+        .collection_metadata(Some(&ctx.accounts.collection_metadata.to_account_info()))
+        .collection_master_edition(Some(&ctx.accounts.collection_master_edition.to_account_info()))
+        .system_program(&system_program_info)
+        .sysvar_instructions(&sysvar_info)
+        .invoke_signed(&[&factory_seeds[..]])?;
+
+    emit_versioned!(AppCollectionVerified {
+        app_id,
+        sft_mint: sft_mint_info.key(),
+        collection: master_collection,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} SFT verified into collection {}", app_id, master_collection);
+    Ok(())
+}
+
+pub fn update_sft_metadata(ctx: Context<UpdateSftMetadata>, app_id: u64) -> Result<()> {
+    // Keep the current name, symbol, royalty and creators; only the URI moves
+    let current = Metadata::from_bytes(&ctx.accounts.metadata.try_borrow_data()?)
+        .map_err(|_| CollectionError::InvalidSftMetadata)?;
+    let metadata_uri = ctx.accounts.app_registration.metadata_uri.clone();
+    let data = Data {
+        name: current.name.trim_end_matches('\0').to_string(),
+        symbol: current.symbol.trim_end_matches('\0').to_string(),
+        uri: metadata_uri.clone(),
+        seller_fee_basis_points: current.seller_fee_basis_points,
+        creators: current.creators,
+    };
+
+    // The registration PDA is the metadata's update authority
+    let app_id_bytes = app_id.to_le_bytes();
+    let registration_seeds = &[
+        b"app_registration".as_ref(),
+        app_id_bytes.as_ref(),
+        &[ctx.accounts.app_registration.bump],
+    ];
+    UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+        .authority(&ctx.accounts.app_registration.to_account_info())
+        .mint(&ctx.accounts.sft_mint.to_account_info())
+        .metadata(&ctx.accounts.metadata.to_account_info())
+        .payer(&ctx.accounts.creator.to_account_info())
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+        .data(data)
+        .invoke_signed(&[&registration_seeds[..]])?;
+
+    emit_versioned!(AppSftMetadataUpdated {
+        app_id,
+        sft_mint: ctx.accounts.sft_mint.key(),
+        metadata_uri: metadata_uri.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} SFT metadata now points at {}", app_id, metadata_uri);
+    Ok(())
+}
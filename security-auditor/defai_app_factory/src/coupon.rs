@@ -0,0 +1,424 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+use defai_common::{bps_of, emit_cpi_versioned, emit_versioned, event_cpi_of, verify_merkle_proof};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased,
+    calculate_fee_split, execute_token_transfers, mint_app_sft, distribute_creator_amount,
+    PresaleError, CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+
+// Coupon codes are committed off-chain as a merkle tree per app. Each leaf is
+// keccak(code_hash || discount_bps (le) || max_uses (le)) where
+// code_hash = keccak(code). Buyers reveal the code itself, so publishing the
+// root does not leak redeemable codes.
+
+#[account]
+pub struct CouponRoot {
+    pub app_id: u64,
+    pub merkle_root: [u8; 32],
+    pub bump: u8,
+}
+
+impl CouponRoot {
+    pub const LEN: usize = 8 + 8 + 32 + 1;
+}
+
+#[account]
+pub struct CouponUsage {
+    pub app_id: u64,
+    pub code_hash: [u8; 32],
+    pub uses: u32,
+    pub bump: u8,
+}
+
+impl CouponUsage {
+    pub const LEN: usize = 8 + 8 + 32 + 4 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetCouponRoot<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CouponRoot::LEN,
+        seeds = [b"coupon_root".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub coupon_root: Account<'info, CouponRoot>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64, code_hash: [u8; 32])]
+pub struct PurchaseWithCoupon<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"coupon_root".as_ref(), &app_id.to_le_bytes()],
+        bump = coupon_root.bump
+    )]
+    pub coupon_root: Box<Account<'info, CouponRoot>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CouponUsage::LEN,
+        seeds = [b"coupon_usage".as_ref(), &app_id.to_le_bytes(), &code_hash],
+        bump
+    )]
+    pub coupon_usage: Box<Account<'info, CouponUsage>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct CouponRootUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CouponRedeemed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub code_hash: [u8; 32],
+    pub discount_bps: u16,
+    pub uses: u32,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CouponError {
+    #[msg("Coupon code does not match its hash")]
+    CodeHashMismatch,
+    #[msg("Invalid coupon proof")]
+    InvalidCouponProof,
+    #[msg("Invalid coupon discount")]
+    InvalidDiscount,
+    #[msg("Coupon has no uses remaining")]
+    CouponExhausted,
+}
+
+pub fn set_coupon_root(ctx: Context<SetCouponRoot>, app_id: u64, merkle_root: [u8; 32]) -> Result<()> {
+    let coupon_root = &mut ctx.accounts.coupon_root;
+    coupon_root.app_id = app_id;
+    coupon_root.merkle_root = merkle_root;
+    coupon_root.bump = ctx.bumps.coupon_root;
+
+    emit_versioned!(CouponRootUpdated {
+        app_id,
+        merkle_root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Coupon root updated for app {}", app_id);
+    Ok(())
+}
+
+pub fn purchase_with_coupon<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseWithCoupon<'info>>,
+    app_id: u64,
+    code_hash: [u8; 32],
+    code: String,
+    discount_bps: u16,
+    max_uses: u32,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    require!(
+        solana_program::keccak::hash(code.as_bytes()).0 == code_hash,
+        CouponError::CodeHashMismatch
+    );
+    require!(discount_bps > 0 && discount_bps <= 10000, CouponError::InvalidDiscount);
+
+    let mut leaf_data = Vec::with_capacity(32 + 2 + 4);
+    leaf_data.extend_from_slice(&code_hash);
+    leaf_data.extend_from_slice(&discount_bps.to_le_bytes());
+    leaf_data.extend_from_slice(&max_uses.to_le_bytes());
+    let leaf = solana_program::keccak::hash(&leaf_data).0;
+    require!(
+        verify_merkle_proof(leaf, &proof, &ctx.accounts.coupon_root.merkle_root),
+        CouponError::InvalidCouponProof
+    );
+
+    // Track uses to prevent replay beyond max_uses
+    let coupon_usage = &mut ctx.accounts.coupon_usage;
+    require!(coupon_usage.uses < max_uses, CouponError::CouponExhausted);
+    coupon_usage.app_id = app_id;
+    coupon_usage.code_hash = code_hash;
+    coupon_usage.uses += 1;
+    coupon_usage.bump = ctx.bumps.coupon_usage;
+    let uses = coupon_usage.uses;
+
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        !app_registration.in_presale(Clock::get()?.unix_timestamp),
+        PresaleError::PresaleActive
+    );
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    let price = bps_of(app_registration.list_price(Clock::get()?.unix_timestamp), (10000 - discount_bps) as u64)?;
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Mint SFT
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    // Record access
+    let now = Clock::get()?.unix_timestamp;
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), CouponRedeemed {
+        app_id,
+        user: ctx.accounts.user.key(),
+        code_hash,
+        discount_bps,
+        uses,
+        timestamp: now,
+    });
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("User purchased app {} access with a {} bps coupon", app_id, discount_bps);
+    Ok(())
+}
@@ -0,0 +1,407 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, AppFactoryError,
+    calculate_fee_split, execute_token_transfers, distribute_creator_amount,
+    CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+// Metered billing: users prepay DEFAI for credits, split like a normal sale,
+// and the app's off-chain service debits them through its registered signer.
+
+#[account]
+pub struct CreditConfig {
+    pub app_id: u64,
+    pub price_per_credit: u64,          // DEFAI per credit
+    pub service_signer: Pubkey,         // Key allowed to consume credits
+    pub is_active: bool,                // Whether new credits can be bought
+    pub bump: u8,
+}
+
+impl CreditConfig {
+    pub const LEN: usize = 8 + 8 + 8 + 32 + 1 + 1;
+}
+
+#[account]
+pub struct CreditBalance {
+    pub user: Pubkey,
+    pub app_id: u64,
+    pub balance: u64,
+    pub total_purchased: u64,
+    pub total_consumed: u64,
+    pub bump: u8,
+}
+
+impl CreditBalance {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetCreditConfig<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreditConfig::LEN,
+        seeds = [b"credit_config".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub credit_config: Account<'info, CreditConfig>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurchaseCredits<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"credit_config".as_ref(), &app_id.to_le_bytes()],
+        bump = credit_config.bump
+    )]
+    pub credit_config: Box<Account<'info, CreditConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CreditBalance::LEN,
+        seeds = [b"credit_balance".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub credit_balance: Box<Account<'info, CreditBalance>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64, user: Pubkey)]
+pub struct ConsumeCredits<'info> {
+    #[account(
+        seeds = [b"credit_config".as_ref(), &app_id.to_le_bytes()],
+        bump = credit_config.bump,
+        has_one = service_signer @ CreditError::UnauthorizedServiceSigner
+    )]
+    pub credit_config: Account<'info, CreditConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"credit_balance".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump = credit_balance.bump
+    )]
+    pub credit_balance: Account<'info, CreditBalance>,
+
+    pub service_signer: Signer<'info>,
+}
+
+#[event]
+pub struct CreditConfigUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub price_per_credit: u64,
+    pub service_signer: Pubkey,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreditsPurchased {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub credits: u64,
+    pub cost: u64,
+    pub platform_fee: u64,
+    pub creator_amount: u64,
+    pub balance: u64,
+    pub timestamp: i64,
+    pub burned: u64,                    // DEFAI burned from the payment (taken from platform_fee)
+}
+
+#[event]
+pub struct CreditsConsumed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub credits: u64,
+    pub balance: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CreditError {
+    #[msg("Credit price must be greater than 0")]
+    InvalidCreditPrice,
+    #[msg("Credit amount must be greater than 0")]
+    InvalidCreditAmount,
+    #[msg("Credit sales are not active for this app")]
+    CreditsNotActive,
+    #[msg("Insufficient credit balance")]
+    InsufficientCredits,
+    #[msg("Signer is not the app's service signer")]
+    UnauthorizedServiceSigner,
+}
+
+pub fn set_credit_config(
+    ctx: Context<SetCreditConfig>,
+    app_id: u64,
+    price_per_credit: u64,
+    service_signer: Pubkey,
+    is_active: bool,
+) -> Result<()> {
+    require!(price_per_credit > 0, CreditError::InvalidCreditPrice);
+
+    let credit_config = &mut ctx.accounts.credit_config;
+    credit_config.app_id = app_id;
+    credit_config.price_per_credit = price_per_credit;
+    credit_config.service_signer = service_signer;
+    credit_config.is_active = is_active;
+    credit_config.bump = ctx.bumps.credit_config;
+
+    emit_versioned!(CreditConfigUpdated {
+        app_id,
+        price_per_credit,
+        service_signer,
+        is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Credit config updated for app {}: {} DEFAI per credit", app_id, price_per_credit);
+    Ok(())
+}
+
+pub fn purchase_credits<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseCredits<'info>>,
+    app_id: u64,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, CreditError::InvalidCreditAmount);
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(ctx.accounts.credit_config.is_active, CreditError::CreditsNotActive);
+
+    let cost = ctx.accounts.credit_config.price_per_credit
+        .checked_mul(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    require!(
+        ctx.accounts.user_defai_ata.amount >= cost,
+        AppFactoryError::InsufficientBalance
+    );
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        cost,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        cost,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        cost,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+    ctx.accounts.factory_stats.record_sale(cost, platform_fee)?;
+
+    let credit_balance = &mut ctx.accounts.credit_balance;
+    credit_balance.user = ctx.accounts.user.key();
+    credit_balance.app_id = app_id;
+    credit_balance.balance = credit_balance.balance
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    credit_balance.total_purchased = credit_balance.total_purchased
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    credit_balance.bump = ctx.bumps.credit_balance;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), CreditsPurchased {
+        app_id,
+        user: credit_balance.user,
+        credits: amount,
+        cost,
+        platform_fee,
+        creator_amount,
+        balance: credit_balance.balance,
+        timestamp: Clock::get()?.unix_timestamp,
+        burned,
+    });
+
+    msg!("User purchased {} credits for app {}", amount, app_id);
+    Ok(())
+}
+
+pub fn consume_credits(
+    ctx: Context<ConsumeCredits>,
+    app_id: u64,
+    user: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, CreditError::InvalidCreditAmount);
+
+    let credit_balance = &mut ctx.accounts.credit_balance;
+    credit_balance.balance = credit_balance.balance
+        .checked_sub(amount)
+        .ok_or(CreditError::InsufficientCredits)?;
+    credit_balance.total_consumed = credit_balance.total_consumed
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    emit_versioned!(CreditsConsumed {
+        app_id,
+        user,
+        credits: amount,
+        balance: credit_balance.balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Consumed {} credits from {} for app {}", amount, user, app_id);
+    Ok(())
+}
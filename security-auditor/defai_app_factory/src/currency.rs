@@ -0,0 +1,539 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer as SystemTransfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased,
+    calculate_fee_split, execute_token_transfers, mint_app_sft, PresaleError, VestingError, CharityError,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+// Alternate payment currencies. DEFAI stays the primary price on
+// AppRegistration; SOL and USDC prices are optional per-app overrides.
+// Native SOL is recorded as the default pubkey in `payment_mint`.
+// These paths skip the purchase burn, since nothing paid in SOL or USDC can
+// be burned as DEFAI; their `AppPurchased` reports `burned: 0`. The USDC mint
+// can never be the DEFAI mint, so DEFAI can't be paid through here unburned.
+// Purchases made here are final: refunds, disputes and delisting escrows only
+// hold and return DEFAI, so they reject any access whose `payment_mint` isn't
+// the DEFAI mint.
+
+#[account]
+pub struct CurrencyConfig {
+    pub usdc_mint: Pubkey,              // Accepted USDC mint, set by the platform
+    pub bump: u8,
+}
+
+impl CurrencyConfig {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+#[account]
+pub struct AppCurrencyPrices {
+    pub app_id: u64,
+    pub sol_price: u64,                 // Lamports (0 = SOL not accepted)
+    pub usdc_price: u64,                // USDC base units (0 = USDC not accepted)
+    pub bump: u8,
+}
+
+impl AppCurrencyPrices {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct SetUsdcMint<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CurrencyConfig::LEN,
+        seeds = [b"currency_config"],
+        bump
+    )]
+    pub currency_config: Account<'info, CurrencyConfig>,
+
+    #[account(
+        constraint = usdc_mint.key() != app_factory.defai_mint @ CurrencyError::InvalidUsdcMint
+    )]
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppCurrencyPrices<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = AppCurrencyPrices::LEN,
+        seeds = [b"app_currency_prices".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub app_currency_prices: Account<'info, AppCurrencyPrices>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurchaseAppWithSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"app_currency_prices".as_ref(), &app_id.to_le_bytes()],
+        bump = app_currency_prices.bump
+    )]
+    pub app_currency_prices: Box<Account<'info, AppCurrencyPrices>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA; splits only cover DEFAI, so it must not exist
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(mut, address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(mut, address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurchaseAppWithUsdc<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"currency_config"],
+        bump = currency_config.bump
+    )]
+    pub currency_config: Box<Account<'info, CurrencyConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"app_currency_prices".as_ref(), &app_id.to_le_bytes()],
+        bump = app_currency_prices.bump
+    )]
+    pub app_currency_prices: Box<Account<'info, AppCurrencyPrices>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = user,
+        constraint = user_usdc_ata.amount >= app_currency_prices.usdc_price
+            @ AppFactoryError::InsufficientBalance
+    )]
+    pub user_usdc_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_usdc_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = usdc_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_usdc_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA; splits only cover DEFAI, so it must not exist
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        address = currency_config.usdc_mint @ CurrencyError::InvalidUsdcMint,
+        constraint = usdc_mint.key() != app_factory.defai_mint @ CurrencyError::InvalidUsdcMint
+    )]
+    pub usdc_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppCurrencyPricesUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub sol_price: u64,
+    pub usdc_price: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum CurrencyError {
+    #[msg("App does not accept this currency")]
+    CurrencyNotAccepted,
+    #[msg("Invalid USDC mint")]
+    InvalidUsdcMint,
+    #[msg("Apps with a revenue split can only be bought with DEFAI")]
+    RevenueSplitActive,
+}
+
+pub fn set_usdc_mint(ctx: Context<SetUsdcMint>) -> Result<()> {
+    let currency_config = &mut ctx.accounts.currency_config;
+    currency_config.usdc_mint = ctx.accounts.usdc_mint.key();
+    currency_config.bump = ctx.bumps.currency_config;
+
+    msg!("Accepted USDC mint set to {}", currency_config.usdc_mint);
+    Ok(())
+}
+
+pub fn set_app_currency_prices(
+    ctx: Context<SetAppCurrencyPrices>,
+    app_id: u64,
+    sol_price: u64,
+    usdc_price: u64,
+) -> Result<()> {
+    let prices = &mut ctx.accounts.app_currency_prices;
+    prices.app_id = app_id;
+    prices.sol_price = sol_price;
+    prices.usdc_price = usdc_price;
+    prices.bump = ctx.bumps.app_currency_prices;
+
+    emit_versioned!(AppCurrencyPricesUpdated {
+        app_id,
+        sol_price,
+        usdc_price,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} prices: {} lamports, {} USDC", app_id, sol_price, usdc_price);
+    Ok(())
+}
+
+pub fn purchase_app_with_sol(ctx: Context<PurchaseAppWithSol>, app_id: u64) -> Result<()> {
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        !app_registration.in_presale(Clock::get()?.unix_timestamp),
+        PresaleError::PresaleActive
+    );
+    require!(
+        app_registration.payout_vesting_days == 0,
+        VestingError::VestedAppUnsupportedPath
+    );
+    require!(
+        app_registration.charity_bps == 0,
+        CharityError::UnsupportedPurchasePath
+    );
+    require!(
+        ctx.accounts.revenue_split.data_is_empty(),
+        CurrencyError::RevenueSplitActive
+    );
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    let price = ctx.accounts.app_currency_prices.sol_price;
+    require!(price > 0, CurrencyError::CurrencyNotAccepted);
+    let (platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Transfer platform fee
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            SystemTransfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+            },
+        ),
+        platform_fee,
+    )?;
+
+    // Transfer creator amount
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            SystemTransfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.creator.to_account_info(),
+            },
+        ),
+        creator_amount,
+    )?;
+
+    // Mint SFT
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(0, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(0, 0)?;
+
+    // Record access
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = Clock::get()?.unix_timestamp;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = Pubkey::default();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: Pubkey::default(),
+        timestamp: Clock::get()?.unix_timestamp,
+        purchase_id,
+        burned: 0,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("User purchased app {} access for {} lamports", app_id, price);
+    Ok(())
+}
+
+pub fn purchase_app_with_usdc(ctx: Context<PurchaseAppWithUsdc>, app_id: u64) -> Result<()> {
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        !app_registration.in_presale(Clock::get()?.unix_timestamp),
+        PresaleError::PresaleActive
+    );
+    require!(
+        app_registration.payout_vesting_days == 0,
+        VestingError::VestedAppUnsupportedPath
+    );
+    require!(
+        app_registration.charity_bps == 0,
+        CharityError::UnsupportedPurchasePath
+    );
+    require!(
+        ctx.accounts.revenue_split.data_is_empty(),
+        CurrencyError::RevenueSplitActive
+    );
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    let price = ctx.accounts.app_currency_prices.usdc_price;
+    require!(price > 0, CurrencyError::CurrencyNotAccepted);
+    let (platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_usdc_ata,
+        &ctx.accounts.creator_usdc_ata,
+        &ctx.accounts.treasury_usdc_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        creator_amount,
+    )?;
+
+    // Mint SFT
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(0, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(0, 0)?;
+
+    // Record access
+    let usdc_mint = ctx.accounts.usdc_mint.key();
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = Clock::get()?.unix_timestamp;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = usdc_mint;
+    user_app_access.rent_payer = ctx.accounts.user.key();
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: usdc_mint,
+        timestamp: Clock::get()?.unix_timestamp,
+        purchase_id,
+        burned: 0,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("User purchased app {} access for {} USDC", app_id, price);
+    Ok(())
+}
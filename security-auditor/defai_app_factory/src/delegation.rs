@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+
+use crate::UserAppAccess;
+use defai_common::emit_versioned;
+
+// Access delegation. A buyer (the primary wallet, often cold storage) lets one
+// secondary wallet use an app without moving the SFT. `verify_access` honors
+// the delegation while the primary's own access is still valid, so selling or
+// refunding the SFT ends the delegate's access too. One delegate per primary
+// per app; delegating again replaces the previous delegate.
+
+#[account]
+pub struct AccessDelegation {
+    pub primary: Pubkey,                // Wallet holding the purchase
+    pub delegate: Pubkey,               // Wallet allowed to use the app
+    pub app_id: u64,
+    pub expires_at: i64,                // 0 = until revoked
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl AccessDelegation {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1;
+
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct DelegateAccess<'info> {
+    #[account(
+        seeds = [b"user_app_access".as_ref(), primary.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump
+    )]
+    pub user_app_access: Account<'info, UserAppAccess>,
+
+    #[account(
+        init_if_needed,
+        payer = primary,
+        space = AccessDelegation::LEN,
+        seeds = [b"access_delegation".as_ref(), primary.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub delegation: Account<'info, AccessDelegation>,
+
+    #[account(mut)]
+    pub primary: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RevokeAccessDelegation<'info> {
+    #[account(
+        mut,
+        seeds = [b"access_delegation".as_ref(), primary.key().as_ref(), &app_id.to_le_bytes()],
+        bump = delegation.bump,
+        close = primary
+    )]
+    pub delegation: Account<'info, AccessDelegation>,
+
+    #[account(mut)]
+    pub primary: Signer<'info>,
+}
+
+#[event]
+pub struct AccessDelegated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub primary: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessDelegationRevoked {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub primary: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum DelegationError {
+    #[msg("Cannot delegate access to yourself")]
+    SelfDelegation,
+    #[msg("Delegation expiry must be in the future")]
+    InvalidDelegationExpiry,
+    #[msg("Delegation does not match the primary's access accounts")]
+    InvalidDelegationAccounts,
+}
+
+pub fn delegate_access(
+    ctx: Context<DelegateAccess>,
+    app_id: u64,
+    delegate: Pubkey,
+    expires_at: i64,
+) -> Result<()> {
+    let primary = ctx.accounts.primary.key();
+    require_keys_neq!(delegate, primary, DelegationError::SelfDelegation);
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at == 0 || expires_at > now, DelegationError::InvalidDelegationExpiry);
+
+    let delegation = &mut ctx.accounts.delegation;
+    delegation.primary = primary;
+    delegation.delegate = delegate;
+    delegation.app_id = app_id;
+    delegation.expires_at = expires_at;
+    delegation.created_at = now;
+    delegation.bump = ctx.bumps.delegation;
+
+    emit_versioned!(AccessDelegated {
+        app_id,
+        primary,
+        delegate,
+        expires_at,
+        timestamp: now,
+    });
+
+    msg!("Access to app {} delegated from {} to {}", app_id, primary, delegate);
+    Ok(())
+}
+
+pub fn revoke_access_delegation(ctx: Context<RevokeAccessDelegation>, app_id: u64) -> Result<()> {
+    emit_versioned!(AccessDelegationRevoked {
+        app_id,
+        primary: ctx.accounts.primary.key(),
+        delegate: ctx.accounts.delegation.delegate,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Access delegation for app {} revoked", app_id);
+    Ok(())
+}
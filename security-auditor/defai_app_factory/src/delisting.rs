@@ -0,0 +1,647 @@
+use anchor_lang::prelude::*;
+use anchor_lang::AccountsClose;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer, Burn, CloseAccount};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, CleanupError, CreatorVesting, VestingError,
+    thaw_app_sft_account,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of, require_covers};
+
+// Delisting flow:
+// 1. The creator or factory authority delists the app. Purchases stop for good
+//    and the DEFAI still owed to holders (sales less refunds) is recorded as
+//    the escrow target. For apps with payout vesting, everything the creator
+//    is still owed moves from the vesting escrow into the delisting vault.
+// 2. Anyone (normally the creator) funds the rest of the target.
+// 3. Once the vault covers the target, the refund window opens; holders burn
+//    their SFT to reclaim their purchase price until it closes.
+// 4. After the window, whatever is left in the vault is released to the creator.
+
+#[constant]
+pub const MIN_REFUND_WINDOW: i64 = 86400; // 1 day
+#[constant]
+pub const MAX_REFUND_WINDOW: i64 = 2592000; // 30 days
+
+#[account]
+pub struct Delisting {
+    pub app_id: u64,
+    pub creator: Pubkey,                // Receives leftover escrow after the window
+    pub initiated_by: Pubkey,           // Creator or factory authority
+    pub delisted_at: i64,
+    pub refund_deadline: i64,           // Holders can claim refunds until this time (0 = not open yet)
+    pub escrowed: u64,                  // Total DEFAI deposited into the vault
+    pub refunded: u64,                  // Total DEFAI paid out to holders
+    pub refund_count: u64,
+    pub released: bool,                 // Leftover escrow released to the creator
+    pub bump: u8,
+    pub required_escrow: u64,           // DEFAI owed to holders at delisting; refunds open once escrowed
+    pub refund_window: i64,             // Length of the refund window once it opens
+}
+
+impl Delisting {
+    pub const LEN: usize = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 8;
+
+    pub fn refunds_open(&self) -> bool {
+        self.refund_deadline != 0
+    }
+
+    // Opens the refund window once the escrow covers every holder; returns
+    // whether this call opened it
+    pub fn open_refunds_if_covered(&mut self, now: i64) -> Result<bool> {
+        if self.refunds_open() || self.escrowed < self.required_escrow {
+            return Ok(false);
+        }
+        self.refund_deadline = now
+            .checked_add(self.refund_window)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(true)
+    }
+
+    // The vault must still hold every escrowed token not yet refunded
+    pub fn check_vault(&self, vault_balance: u64) -> Result<()> {
+        require_covers(
+            "delisting vault",
+            vault_balance,
+            self.escrowed.saturating_sub(self.refunded),
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct DelistApp<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        space = Delisting::LEN,
+        seeds = [b"delisting".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub delisting: Box<Account<'info, Delisting>>,
+
+    #[account(
+        init,
+        payer = initiator,
+        token::mint = defai_mint,
+        token::authority = delisting,
+        seeds = [b"delisting_vault".as_ref(), delisting.key().as_ref()],
+        bump
+    )]
+    pub delisting_vault: Box<Account<'info, TokenAccount>>,
+
+    // Required for apps with payout vesting; its escrow funds the refunds first
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = initiator.key() == app_registration.creator
+            || initiator.key() == app_factory.authority
+            @ DelistingError::UnauthorizedDelisting
+    )]
+    pub initiator: Signer<'info>,
+
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct FundDelistingRefunds<'info> {
+    #[account(
+        mut,
+        seeds = [b"delisting".as_ref(), &app_id.to_le_bytes()],
+        bump = delisting.bump
+    )]
+    pub delisting: Box<Account<'info, Delisting>>,
+
+    #[account(
+        mut,
+        seeds = [b"delisting_vault".as_ref(), delisting.key().as_ref()],
+        bump
+    )]
+    pub delisting_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = delisting_vault.mint,
+        token::authority = funder
+    )]
+    pub funder_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    pub funder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ClaimDelistingRefund<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"delisting".as_ref(), &app_id.to_le_bytes()],
+        bump = delisting.bump
+    )]
+    pub delisting: Box<Account<'info, Delisting>>,
+
+    #[account(
+        mut,
+        seeds = [b"delisting_vault".as_ref(), delisting.key().as_ref()],
+        bump
+    )]
+    pub delisting_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        has_one = user
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: Receives the access record's rent; must be whoever paid it
+    #[account(
+        mut,
+        address = user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user,
+        constraint = user_sft_ata.amount > 0 @ AppFactoryError::NoSftToRefund
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ReleaseDelistingFunds<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"delisting".as_ref(), &app_id.to_le_bytes()],
+        bump = delisting.bump
+    )]
+    pub delisting: Box<Account<'info, Delisting>>,
+
+    #[account(
+        mut,
+        seeds = [b"delisting_vault".as_ref(), delisting.key().as_ref()],
+        bump
+    )]
+    pub delisting_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = delisting.creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Creator wallet; receives the vault rent
+    #[account(mut, address = delisting.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    // Permissionless once the refund window has closed
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct AppDelisted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub initiated_by: Pubkey,
+    pub refund_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelistingRefundsOpened {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub escrowed: u64,
+    pub refund_deadline: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelistingRefundsFunded {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub total_escrowed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelistingRefundClaimed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelistingFundsReleased {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum DelistingError {
+    #[msg("Only the creator or factory authority can delist an app")]
+    UnauthorizedDelisting,
+    #[msg("Refund window must be between 1 and 30 days")]
+    InvalidRefundWindow,
+    #[msg("App has been delisted")]
+    AppDelisted,
+    #[msg("Delisting refund window has closed")]
+    RefundWindowClosed,
+    #[msg("Delisting refund window is still open")]
+    RefundWindowOpen,
+    #[msg("Only permanent DEFAI purchases made before delisting are refundable")]
+    NotRefundable,
+    #[msg("Not enough escrowed funds to cover this refund")]
+    InsufficientEscrow,
+    #[msg("Delisting funds already released")]
+    AlreadyReleased,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Refunds open once the escrow covers every holder")]
+    RefundsNotOpen,
+    #[msg("Disputed purchases are settled by the dispute ruling")]
+    PurchaseDisputed,
+}
+
+pub fn delist_app(ctx: Context<DelistApp>, app_id: u64, refund_window: i64) -> Result<()> {
+    require!(
+        (MIN_REFUND_WINDOW..=MAX_REFUND_WINDOW).contains(&refund_window),
+        DelistingError::InvalidRefundWindow
+    );
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    ctx.accounts.factory_stats.record_status_change(app_registration.is_active, false)?;
+    app_registration.is_active = false;
+    app_registration.delisted = true;
+
+    // Every DEFAI sale not yet refunded may come back for a refund
+    let required_escrow = app_registration.gross_revenue.saturating_sub(app_registration.refund_amount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut escrowed = 0;
+    if app_registration.payout_vesting_days > 0 {
+        // The creator's unpaid proceeds fund the refunds before anyone else
+        let (creator_vesting, vesting_vault) = match (
+            ctx.accounts.creator_vesting.as_deref_mut(),
+            ctx.accounts.vesting_vault.as_deref(),
+        ) {
+            (Some(vesting), Some(vault)) => (vesting, vault),
+            _ => return err!(VestingError::MissingVestingAccounts),
+        };
+        require_keys_eq!(vesting_vault.key(), creator_vesting.vault, VestingError::InvalidVestingVault);
+        escrowed = creator_vesting.withhold(required_escrow, now)?;
+
+        if escrowed > 0 {
+            let vesting_seeds = &[
+                b"creator_vesting".as_ref(),
+                &app_id.to_le_bytes(),
+                &[creator_vesting.bump],
+            ];
+            let signer_seeds = &[&vesting_seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: vesting_vault.to_account_info(),
+                    to: ctx.accounts.delisting_vault.to_account_info(),
+                    authority: creator_vesting.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_ctx, escrowed)?;
+        }
+    }
+
+    let delisting = &mut ctx.accounts.delisting;
+    delisting.app_id = app_id;
+    delisting.creator = app_registration.creator;
+    delisting.initiated_by = ctx.accounts.initiator.key();
+    delisting.delisted_at = now;
+    delisting.refund_deadline = 0;
+    delisting.escrowed = escrowed;
+    delisting.refunded = 0;
+    delisting.refund_count = 0;
+    delisting.released = false;
+    delisting.bump = ctx.bumps.delisting;
+    delisting.required_escrow = required_escrow;
+    delisting.refund_window = refund_window;
+    let opened = delisting.open_refunds_if_covered(now)?;
+
+    emit_versioned!(AppDelisted {
+        app_id,
+        initiated_by: delisting.initiated_by,
+        refund_deadline: delisting.refund_deadline,
+        timestamp: now,
+    });
+    if opened {
+        emit_versioned!(DelistingRefundsOpened {
+            app_id,
+            escrowed,
+            refund_deadline: delisting.refund_deadline,
+            timestamp: now,
+        });
+    }
+
+    msg!(
+        "App {} delisted; {} of {} DEFAI escrowed for refunds",
+        app_id,
+        escrowed,
+        required_escrow
+    );
+    Ok(())
+}
+
+pub fn fund_delisting_refunds(
+    ctx: Context<FundDelistingRefunds>,
+    app_id: u64,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, DelistingError::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let delisting = &ctx.accounts.delisting;
+    require!(
+        !delisting.refunds_open() || now <= delisting.refund_deadline,
+        DelistingError::RefundWindowClosed
+    );
+
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.funder_defai_ata.to_account_info(),
+            to: ctx.accounts.delisting_vault.to_account_info(),
+            authority: ctx.accounts.funder.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    let delisting = &mut ctx.accounts.delisting;
+    delisting.escrowed = delisting.escrowed
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    let opened = delisting.open_refunds_if_covered(now)?;
+    ctx.accounts.delisting_vault.reload()?;
+    delisting.check_vault(ctx.accounts.delisting_vault.amount)?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), DelistingRefundsFunded {
+        app_id,
+        funder: ctx.accounts.funder.key(),
+        amount,
+        total_escrowed: delisting.escrowed,
+        timestamp: now,
+    });
+    if opened {
+        emit_cpi_versioned!(event_cpi_of!(ctx), DelistingRefundsOpened {
+            app_id,
+            escrowed: delisting.escrowed,
+            refund_deadline: delisting.refund_deadline,
+            timestamp: now,
+        });
+    }
+
+    msg!("{} DEFAI escrowed for delisting refunds on app {}", amount, app_id);
+    Ok(())
+}
+
+pub fn claim_delisting_refund(ctx: Context<ClaimDelistingRefund>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.accounts.delisting.refunds_open(), DelistingError::RefundsNotOpen);
+    require!(now <= ctx.accounts.delisting.refund_deadline, DelistingError::RefundWindowClosed);
+
+    let access = &ctx.accounts.user_app_access;
+    require!(
+        access.expires_at == 0
+            && access.payment_mint == ctx.accounts.app_factory.defai_mint
+            && access.purchased_at <= ctx.accounts.delisting.delisted_at,
+        DelistingError::NotRefundable
+    );
+    require!(!access.disputed, DelistingError::PurchaseDisputed);
+
+    let amount = access.purchase_price;
+    require!(
+        ctx.accounts.delisting_vault.amount >= amount,
+        DelistingError::InsufficientEscrow
+    );
+
+    // Soulbound SFTs are frozen; thaw so the owner can burn
+    if ctx.accounts.user_sft_ata.is_frozen() {
+        thaw_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            ctx.accounts.app_registration.bump,
+        )?;
+    }
+
+    // Burn the SFT
+    let burn_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            from: ctx.accounts.user_sft_ata.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::burn(burn_ctx, 1)?;
+
+    // Pay the refund out of escrow
+    let delisting_bump = ctx.accounts.delisting.bump;
+    let delisting_seeds = &[
+        b"delisting".as_ref(),
+        &app_id.to_le_bytes(),
+        &[delisting_bump],
+    ];
+    let signer = &[&delisting_seeds[..]];
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.delisting_vault.to_account_info(),
+            to: ctx.accounts.user_defai_ata.to_account_info(),
+            authority: ctx.accounts.delisting.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_sub(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_refund(amount)?;
+    ctx.accounts.factory_stats.record_refund(amount)?;
+
+    let delisting = &mut ctx.accounts.delisting;
+    delisting.refunded = delisting.refunded
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    delisting.refund_count = delisting.refund_count
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.delisting_vault.reload()?;
+    ctx.accounts.delisting.check_vault(ctx.accounts.delisting_vault.amount)?;
+
+    ctx.accounts.user_app_access.close(ctx.accounts.rent_payer.to_account_info())?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), DelistingRefundClaimed {
+        app_id,
+        user: ctx.accounts.user.key(),
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Refunded {} DEFAI to {} for delisted app {}", amount, ctx.accounts.user.key(), app_id);
+    Ok(())
+}
+
+pub fn release_delisting_funds(ctx: Context<ReleaseDelistingFunds>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(!ctx.accounts.delisting.released, DelistingError::AlreadyReleased);
+    // Leftovers wait for the full refund window, which only starts once funded
+    require!(ctx.accounts.delisting.refunds_open(), DelistingError::RefundsNotOpen);
+    require!(now > ctx.accounts.delisting.refund_deadline, DelistingError::RefundWindowOpen);
+
+    let delisting_bump = ctx.accounts.delisting.bump;
+    let delisting_seeds = &[
+        b"delisting".as_ref(),
+        &app_id.to_le_bytes(),
+        &[delisting_bump],
+    ];
+    let signer = &[&delisting_seeds[..]];
+
+    let amount = ctx.accounts.delisting_vault.amount;
+    if amount > 0 {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.delisting_vault.to_account_info(),
+                to: ctx.accounts.creator_defai_ata.to_account_info(),
+                authority: ctx.accounts.delisting.to_account_info(),
+            },
+            signer,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.delisting_vault.to_account_info(),
+            destination: ctx.accounts.creator.to_account_info(),
+            authority: ctx.accounts.delisting.to_account_info(),
+        },
+        signer,
+    );
+    token::close_account(close_ctx)?;
+
+    ctx.accounts.delisting.released = true;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), DelistingFundsReleased {
+        app_id,
+        creator: ctx.accounts.delisting.creator,
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Released {} DEFAI of leftover delisting escrow for app {}", amount, app_id);
+    Ok(())
+}
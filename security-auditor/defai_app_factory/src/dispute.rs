@@ -0,0 +1,739 @@
+use anchor_lang::prelude::*;
+use anchor_lang::AccountsClose;
+use anchor_spl::token::{
+    self, Token, TokenAccount, Mint, Transfer, Approve, Burn, FreezeAccount, ThawAccount, CloseAccount,
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, CleanupError, CreatorVesting, VestingError,
+    APP_REGISTRATION_SEED, PurchaseSplit, freeze_app_sft_account, thaw_app_sft_account, remove_refunded_review,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+// Dispute flow:
+// 1. Buyer opens a dispute within the window; their SFT is frozen and a
+//    dispute vault is created. For apps with payout vesting, the disputed
+//    creator share is withheld from the creator's vesting stream into the
+//    vault right away.
+// 2. The creator escrows whatever the hold didn't cover (all of it for apps
+//    without vesting, whose creator share was paid out at purchase).
+// 3. The arbiter (or factory authority) rules refund or release.
+// If the creator never escrows, the ruling defaults against them: the buyer
+// is refunded whatever the vault holds, keeps access, and the app is
+// deactivated.
+//
+// The disputed amount is the creator's recorded net from the sale (PurchaseSplit),
+// what they received directly or into vesting after any affiliate, charity and
+// split cuts. Every ruling closes the dispute and its vault, returning the
+// rent to the buyer. The access record stays marked as disputed, so the ruling
+// is the purchase's only remedy: it can't be disputed again or refunded.
+
+#[constant]
+pub const DISPUTE_WINDOW: i64 = 604800; // 7 days after purchase
+#[constant]
+pub const CREATOR_RESPONSE_WINDOW: i64 = 259200; // 3 days to escrow
+const MAX_REASON_CID_LEN: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Open,
+    Escrowed,
+    Refunded,
+    Released,
+    CreatorDefaulted,
+}
+
+#[account]
+pub struct DisputeConfig {
+    pub arbiter: Pubkey,                // Platform-appointed arbiter
+    pub bump: u8,
+}
+
+impl DisputeConfig {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+#[account]
+pub struct Dispute {
+    pub user: Pubkey,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,                    // Creator's recorded net from the disputed purchase
+    pub status: DisputeStatus,
+    pub reason_cid: String,             // IPFS CID of the buyer's complaint
+    pub opened_at: i64,
+    pub respond_by: i64,                // Creator must escrow before this
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 8 + 1 + (4 + MAX_REASON_CID_LEN) + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct SetDisputeArbiter<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = DisputeConfig::LEN,
+        seeds = [b"dispute_config"],
+        bump
+    )]
+    pub dispute_config: Account<'info, DisputeConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct OpenDispute<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        has_one = user
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Dispute::LEN,
+        seeds = [b"dispute".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    #[account(
+        init,
+        payer = user,
+        token::mint = defai_mint,
+        token::authority = dispute,
+        seeds = [b"dispute_vault".as_ref(), dispute.key().as_ref()],
+        bump
+    )]
+    pub dispute_vault: Box<Account<'info, TokenAccount>>,
+
+    // Required for apps with payout vesting; the disputed share is withheld from it
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(address = app_registration.sft_mint)]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = user_app_access.sft_token_account,
+        constraint = user_sft_ata.amount > 0 @ AppFactoryError::NoSftToRefund
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64, user: Pubkey)]
+pub struct EscrowDisputeFunds<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump = dispute.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute_vault".as_ref(), dispute.key().as_ref()],
+        bump
+    )]
+    pub dispute_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64, user: Pubkey)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"dispute_config"],
+        bump = dispute_config.bump
+    )]
+    pub dispute_config: Box<Account<'info, DisputeConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump = dispute.bump,
+        close = buyer
+    )]
+    pub dispute: Box<Account<'info, Dispute>>,
+
+    /// CHECK: The disputing buyer; paid the dispute's rent and gets it back
+    #[account(mut, address = dispute.user)]
+    pub buyer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: The buyer's review PDA for this app; closed on a refund ruling
+    /// if it exists, as `refund_purchase` does
+    #[account(
+        mut,
+        seeds = [b"app_review", user.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_review: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute_vault".as_ref(), dispute.key().as_ref()],
+        bump
+    )]
+    pub dispute_vault: Box<Account<'info, TokenAccount>>,
+
+    // Required for apps with payout vesting; a release goes back into the stream
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = dispute.creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = user_app_access.sft_token_account
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the access record's rent; must be whoever paid it
+    #[account(
+        mut,
+        address = user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = resolver.key() == dispute_config.arbiter
+            || resolver.key() == app_factory.authority
+            @ DisputeError::UnauthorizedResolver
+    )]
+    pub resolver: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct DisputeArbiterUpdated {
+    pub schema_version: u8,
+    pub arbiter: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub reason_cid: String,
+    pub respond_by: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeFundsEscrowed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub status: DisputeStatus,
+    pub amount: u64,
+    pub resolver: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum DisputeError {
+    #[msg("Dispute window has expired (7 days)")]
+    DisputeWindowExpired,
+    #[msg("Rentals cannot be disputed")]
+    RentalNotDisputable,
+    #[msg("Only DEFAI purchases can be disputed")]
+    NonDefaiPurchase,
+    #[msg("Reason CID too long (max 64 characters)")]
+    ReasonTooLong,
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    #[msg("Creator response window has expired")]
+    ResponseWindowExpired,
+    #[msg("Creator response window has not expired yet")]
+    AwaitingCreatorEscrow,
+    #[msg("Dispute already resolved")]
+    AlreadyResolved,
+    #[msg("Dispute vault missing")]
+    MissingDisputeVault,
+    #[msg("Only the arbiter or factory authority can resolve disputes")]
+    UnauthorizedResolver,
+    #[msg("This purchase has already been disputed")]
+    AlreadyDisputed,
+    #[msg("No creator payment is recorded for this purchase")]
+    NothingToDispute,
+}
+
+pub fn set_dispute_arbiter(ctx: Context<SetDisputeArbiter>, arbiter: Pubkey) -> Result<()> {
+    let dispute_config = &mut ctx.accounts.dispute_config;
+    dispute_config.arbiter = arbiter;
+    dispute_config.bump = ctx.bumps.dispute_config;
+
+    emit_versioned!(DisputeArbiterUpdated {
+        arbiter,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Dispute arbiter set to {}", arbiter);
+    Ok(())
+}
+
+pub fn open_dispute(ctx: Context<OpenDispute>, app_id: u64, reason_cid: String) -> Result<()> {
+    require!(reason_cid.len() <= MAX_REASON_CID_LEN, DisputeError::ReasonTooLong);
+
+    let access = &ctx.accounts.user_app_access;
+    require!(access.expires_at == 0, DisputeError::RentalNotDisputable);
+    require!(
+        access.payment_mint == ctx.accounts.app_factory.defai_mint,
+        DisputeError::NonDefaiPurchase
+    );
+    require!(!access.disputed, DisputeError::AlreadyDisputed);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now - access.purchased_at <= DISPUTE_WINDOW,
+        DisputeError::DisputeWindowExpired
+    );
+
+    let PurchaseSplit { creator, vested, .. } = access.paid_out;
+    let amount = creator.checked_add(vested).ok_or(AppFactoryError::MathOverflow)?;
+    require!(amount > 0, DisputeError::NothingToDispute);
+    ctx.accounts.user_app_access.disputed = true;
+
+    // Delegate the SFT to the registration PDA so a refund ruling can burn it,
+    // then freeze it for the duration of the dispute. Soulbound SFTs are
+    // already frozen and must be thawed to approve.
+    let bump = ctx.accounts.app_registration.bump;
+    if ctx.accounts.user_sft_ata.is_frozen() {
+        thaw_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
+    let approve_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.user_sft_ata.to_account_info(),
+            delegate: ctx.accounts.app_registration.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::approve(approve_ctx, 1)?;
+
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let signer_seeds = &[&mint_seeds[..]];
+    let freeze_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        FreezeAccount {
+            account: ctx.accounts.user_sft_ata.to_account_info(),
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            authority: ctx.accounts.app_registration.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::freeze_account(freeze_ctx)?;
+
+    // Vested apps still hold the creator share: withhold it from the stream
+    // into the dispute vault so the creator can't claim it meanwhile
+    let mut held = 0;
+    if ctx.accounts.app_registration.payout_vesting_days > 0 {
+        let (creator_vesting, vesting_vault) = match (
+            ctx.accounts.creator_vesting.as_deref_mut(),
+            ctx.accounts.vesting_vault.as_deref(),
+        ) {
+            (Some(vesting), Some(vault)) => (vesting, vault),
+            _ => return err!(VestingError::MissingVestingAccounts),
+        };
+        require_keys_eq!(vesting_vault.key(), creator_vesting.vault, VestingError::InvalidVestingVault);
+        held = creator_vesting.withhold(amount, now)?;
+
+        if held > 0 {
+            let vesting_seeds = &[
+                b"creator_vesting".as_ref(),
+                &app_id.to_le_bytes(),
+                &[creator_vesting.bump],
+            ];
+            let signer_seeds = &[&vesting_seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: vesting_vault.to_account_info(),
+                    to: ctx.accounts.dispute_vault.to_account_info(),
+                    authority: creator_vesting.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_ctx, held)?;
+        }
+    }
+
+    let dispute = &mut ctx.accounts.dispute;
+    dispute.user = ctx.accounts.user.key();
+    dispute.app_id = app_id;
+    dispute.creator = ctx.accounts.app_registration.creator;
+    dispute.amount = amount;
+    // A full hold needs nothing more from the creator
+    dispute.status = if held == amount { DisputeStatus::Escrowed } else { DisputeStatus::Open };
+    dispute.reason_cid = reason_cid.clone();
+    dispute.opened_at = now;
+    dispute.respond_by = now + CREATOR_RESPONSE_WINDOW;
+    dispute.bump = ctx.bumps.dispute;
+
+    emit_versioned!(DisputeOpened {
+        app_id,
+        user: dispute.user,
+        creator: dispute.creator,
+        amount,
+        reason_cid,
+        respond_by: dispute.respond_by,
+        timestamp: now,
+    });
+
+    msg!("Dispute opened on app {} by {}; {} of {} DEFAI held", app_id, dispute.user, held, amount);
+    Ok(())
+}
+
+pub fn escrow_dispute_funds(
+    ctx: Context<EscrowDisputeFunds>,
+    app_id: u64,
+    user: Pubkey,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let dispute = &mut ctx.accounts.dispute;
+    require!(dispute.status == DisputeStatus::Open, DisputeError::DisputeNotOpen);
+    require!(now <= dispute.respond_by, DisputeError::ResponseWindowExpired);
+
+    // Only the part not already withheld from payout vesting
+    let outstanding = dispute.amount.saturating_sub(ctx.accounts.dispute_vault.amount);
+    let transfer_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.creator_defai_ata.to_account_info(),
+            to: ctx.accounts.dispute_vault.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        },
+    );
+    token::transfer(transfer_ctx, outstanding)?;
+
+    dispute.status = DisputeStatus::Escrowed;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), DisputeFundsEscrowed {
+        app_id,
+        user,
+        amount: outstanding,
+        timestamp: now,
+    });
+
+    msg!("Creator escrowed {} DEFAI for dispute on app {}", outstanding, app_id);
+    Ok(())
+}
+
+pub fn resolve_dispute<'info>(
+    ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+    app_id: u64,
+    user: Pubkey,
+    refund: bool,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let status = ctx.accounts.dispute.status;
+    let amount = ctx.accounts.dispute.amount;
+
+    let bump = ctx.accounts.app_registration.bump;
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let registration_signer = &[&mint_seeds[..]];
+
+    // Every ruling lifts the freeze first
+    let thaw_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        ThawAccount {
+            account: ctx.accounts.user_sft_ata.to_account_info(),
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            authority: ctx.accounts.app_registration.to_account_info(),
+        },
+        registration_signer,
+    );
+
+    let dispute_bump = ctx.accounts.dispute.bump;
+    let dispute_seeds = &[
+        b"dispute".as_ref(),
+        user.as_ref(),
+        &app_id.to_le_bytes(),
+        &[dispute_bump],
+    ];
+    let dispute_signer = &[&dispute_seeds[..]];
+    let pay_from_vault = |to: AccountInfo<'info>, amount: u64| {
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.dispute_vault.to_account_info(),
+                to,
+                authority: ctx.accounts.dispute.to_account_info(),
+            },
+            dispute_signer,
+        );
+        token::transfer(transfer_ctx, amount)
+    };
+
+    let new_status = match status {
+        DisputeStatus::Open => {
+            // Creator never escrowed: the buyer gets back whatever was
+            // withheld, keeps access and the app is pulled
+            require!(now > ctx.accounts.dispute.respond_by, DisputeError::AwaitingCreatorEscrow);
+            let held = ctx.accounts.dispute_vault.amount;
+            if held > 0 {
+                pay_from_vault(ctx.accounts.user_defai_ata.to_account_info(), held)?;
+                ctx.accounts.app_registration.record_refund(held)?;
+                ctx.accounts.factory_stats.record_refund(held)?;
+            }
+            token::thaw_account(thaw_ctx)?;
+            ctx.accounts.factory_stats.record_status_change(ctx.accounts.app_registration.is_active, false)?;
+            ctx.accounts.app_registration.is_active = false;
+            DisputeStatus::CreatorDefaulted
+        }
+        DisputeStatus::Escrowed => {
+            if refund {
+                pay_from_vault(ctx.accounts.user_defai_ata.to_account_info(), amount)?;
+            } else if ctx.accounts.app_registration.payout_vesting_days > 0 {
+                // Released funds resume vesting rather than paying out at once
+                let (creator_vesting, vesting_vault) = match (
+                    ctx.accounts.creator_vesting.as_deref_mut(),
+                    ctx.accounts.vesting_vault.as_deref(),
+                ) {
+                    (Some(vesting), Some(vault)) => (vesting, vault),
+                    _ => return err!(VestingError::MissingVestingAccounts),
+                };
+                require_keys_eq!(vesting_vault.key(), creator_vesting.vault, VestingError::InvalidVestingVault);
+                creator_vesting.deposit(amount, now)?;
+                pay_from_vault(vesting_vault.to_account_info(), amount)?;
+            } else {
+                pay_from_vault(ctx.accounts.creator_defai_ata.to_account_info(), amount)?;
+            }
+
+            token::thaw_account(thaw_ctx)?;
+
+            if refund {
+                // Revoke access: burn the SFT as delegate; the access record closes below
+                let burn_ctx = CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.sft_mint.to_account_info(),
+                        from: ctx.accounts.user_sft_ata.to_account_info(),
+                        authority: ctx.accounts.app_registration.to_account_info(),
+                    },
+                    registration_signer,
+                );
+                token::burn(burn_ctx, 1)?;
+
+                ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+                    .checked_sub(1)
+                    .ok_or(AppFactoryError::MathOverflow)?;
+                ctx.accounts.app_registration.record_refund(amount)?;
+                ctx.accounts.factory_stats.record_refund(amount)?;
+
+                DisputeStatus::Refunded
+            } else {
+                DisputeStatus::Released
+            }
+        }
+        _ => return err!(DisputeError::AlreadyResolved),
+    };
+
+    // Soulbound access that stays with the buyer goes back to frozen
+    if new_status != DisputeStatus::Refunded && ctx.accounts.app_registration.soulbound {
+        freeze_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
+    // Anything sent to the vault beyond the dispute goes to the buyer, then the
+    // vault closes; the dispute account itself closes to the buyer on exit
+    let leftover = match new_status {
+        DisputeStatus::CreatorDefaulted => 0,
+        _ => ctx.accounts.dispute_vault.amount.saturating_sub(amount),
+    };
+    if leftover > 0 {
+        pay_from_vault(ctx.accounts.user_defai_ata.to_account_info(), leftover)?;
+    }
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.dispute_vault.to_account_info(),
+            destination: ctx.accounts.buyer.to_account_info(),
+            authority: ctx.accounts.dispute.to_account_info(),
+        },
+        dispute_signer,
+    );
+    token::close_account(close_ctx)?;
+
+    // Close the refunded access and the buyer's review, moving lamports only
+    // once the CPIs are done
+    if new_status == DisputeStatus::Refunded {
+        ctx.accounts.user_app_access.close(ctx.accounts.rent_payer.to_account_info())?;
+        remove_refunded_review(
+            &ctx.accounts.user_review.to_account_info(),
+            &mut ctx.accounts.app_registration,
+            &ctx.accounts.buyer.to_account_info(),
+        )?;
+    }
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), DisputeResolved {
+        app_id,
+        user,
+        status: new_status,
+        amount,
+        resolver: ctx.accounts.resolver.key(),
+        timestamp: now,
+    });
+
+    msg!("Dispute on app {} by {} resolved", app_id, user);
+    Ok(())
+}
@@ -0,0 +1,497 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, SetAuthority},
+    token::spl_token::instruction::AuthorityType,
+};
+use solana_program::program_option::COption;
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased,
+    calculate_fee_split, execute_token_transfers, mint_app_sft, distribute_creator_amount,
+    PresaleError, CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+// Editions are feature tiers (basic / pro / ...) of one registered app. Each
+// edition has its own price, supply and SFT mint; mint and freeze authority
+// sit with the parent app_registration PDA like the base SFT.
+
+pub const MAX_EDITION_NAME_LEN: usize = 32;
+#[constant]
+pub const MAX_EDITIONS_PER_APP: u8 = 8;
+
+#[account]
+pub struct AppEdition {
+    pub app_id: u64,
+    pub edition_id: u8,
+    pub name: String,                   // e.g. "Pro"
+    pub sft_mint: Pubkey,
+    pub price: u64,
+    pub max_supply: u64,
+    pub current_supply: u64,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl AppEdition {
+    pub const LEN: usize = 8 + 8 + 1 + (4 + MAX_EDITION_NAME_LEN) + 32 + 8 + 8 + 8 + 1 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct AddAppEdition<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = AppEdition::LEN,
+        seeds = [
+            b"app_edition".as_ref(),
+            &app_id.to_le_bytes(),
+            &[app_registration.edition_count]
+        ],
+        bump
+    )]
+    pub app_edition: Box<Account<'info, AppEdition>>,
+
+    #[account(
+        mut,
+        constraint = sft_mint.mint_authority == COption::Some(creator.key())
+            @ AppFactoryError::InvalidMintAuthority,
+        constraint = sft_mint.freeze_authority == COption::Some(creator.key())
+            @ AppFactoryError::InvalidFreezeAuthority,
+        constraint = sft_mint.supply == 0
+            @ AppFactoryError::MintAlreadyInUse,
+        constraint = sft_mint.decimals == 0
+            @ AppFactoryError::InvalidMintDecimals,
+        constraint = sft_mint.key() != app_registration.sft_mint
+            @ AppFactoryError::MintAlreadyInUse
+    )]
+    pub sft_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64, edition_id: u8)]
+pub struct UpdateAppEdition<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"app_edition".as_ref(), &app_id.to_le_bytes(), &[edition_id]],
+        bump = app_edition.bump
+    )]
+    pub app_edition: Account<'info, AppEdition>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64, edition_id: u8)]
+pub struct PurchaseAppEdition<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_edition".as_ref(), &app_id.to_le_bytes(), &[edition_id]],
+        bump = app_edition.bump
+    )]
+    pub app_edition: Box<Account<'info, AppEdition>>,
+
+    // Edition access lives alongside base access, keyed by edition id
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [
+            b"user_app_access".as_ref(),
+            user.key().as_ref(),
+            &app_id.to_le_bytes(),
+            &[edition_id]
+        ],
+        bump
+    )]
+    pub user_edition_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_edition.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user,
+        constraint = user_defai_ata.amount >= app_edition.price
+            @ AppFactoryError::InsufficientBalance
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppEditionAdded {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub edition_id: u8,
+    pub name: String,
+    pub sft_mint: Pubkey,
+    pub price: u64,
+    pub max_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppEditionPurchased {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub edition_id: u8,
+    pub user: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum EditionError {
+    #[msg("Edition name too long (max 32 characters)")]
+    NameTooLong,
+    #[msg("Maximum editions per app reached")]
+    TooManyEditions,
+    #[msg("Edition is not active")]
+    EditionNotActive,
+    #[msg("Edition supply exhausted")]
+    EditionSoldOut,
+}
+
+pub fn add_app_edition(
+    ctx: Context<AddAppEdition>,
+    app_id: u64,
+    name: String,
+    price: u64,
+    max_supply: u64,
+) -> Result<()> {
+    require!(name.len() <= MAX_EDITION_NAME_LEN, EditionError::NameTooLong);
+    require!(price > 0, AppFactoryError::InvalidPrice);
+    require!(max_supply > 0, AppFactoryError::InvalidMaxSupply);
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    let edition_id = app_registration.edition_count;
+    require!(edition_id < MAX_EDITIONS_PER_APP, EditionError::TooManyEditions);
+    app_registration.edition_count += 1;
+
+    let app_edition = &mut ctx.accounts.app_edition;
+    app_edition.app_id = app_id;
+    app_edition.edition_id = edition_id;
+    app_edition.name = name.clone();
+    app_edition.sft_mint = ctx.accounts.sft_mint.key();
+    app_edition.price = price;
+    app_edition.max_supply = max_supply;
+    app_edition.current_supply = 0;
+    app_edition.is_active = true;
+    app_edition.bump = ctx.bumps.app_edition;
+
+    // Transfer mint and freeze authority to the app_registration PDA
+    let registration_key = ctx.accounts.app_registration.key();
+    for authority_type in [AuthorityType::MintTokens, AuthorityType::FreezeAccount] {
+        let set_auth_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.creator.to_account_info(),
+                account_or_mint: ctx.accounts.sft_mint.to_account_info(),
+            },
+        );
+        token::set_authority(set_auth_ctx, authority_type, Some(registration_key))?;
+    }
+
+    emit_versioned!(AppEditionAdded {
+        app_id,
+        edition_id,
+        name,
+        sft_mint: ctx.accounts.sft_mint.key(),
+        price,
+        max_supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} edition {} added", app_id, edition_id);
+    Ok(())
+}
+
+pub fn update_app_edition(
+    ctx: Context<UpdateAppEdition>,
+    app_id: u64,
+    edition_id: u8,
+    new_price: Option<u64>,
+    is_active: Option<bool>,
+) -> Result<()> {
+    let app_edition = &mut ctx.accounts.app_edition;
+
+    if let Some(price) = new_price {
+        require!(price > 0, AppFactoryError::InvalidPrice);
+        app_edition.price = price;
+    }
+    if let Some(active) = is_active {
+        app_edition.is_active = active;
+    }
+
+    msg!("App {} edition {} updated", app_id, edition_id);
+    Ok(())
+}
+
+pub fn purchase_app_edition<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseAppEdition<'info>>,
+    app_id: u64,
+    edition_id: u8,
+) -> Result<()> {
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        !ctx.accounts.app_registration.in_presale(Clock::get()?.unix_timestamp),
+        PresaleError::PresaleActive
+    );
+    let app_edition = &ctx.accounts.app_edition;
+    require!(app_edition.is_active, EditionError::EditionNotActive);
+    require!(
+        app_edition.current_supply < app_edition.max_supply,
+        EditionError::EditionSoldOut
+    );
+
+    let price = app_edition.price;
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Mint edition SFT; the registration PDA holds mint authority
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update edition supply
+    ctx.accounts.app_edition.current_supply = ctx.accounts.app_edition.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    // Record access
+    let now = Clock::get()?.unix_timestamp;
+    let access = &mut ctx.accounts.user_edition_access;
+    access.user = ctx.accounts.user.key();
+    access.app_id = app_id;
+    access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    access.purchased_at = now;
+    access.purchase_price = price;
+    access.bump = ctx.bumps.user_edition_access;
+    access.expires_at = 0;
+    access.payment_mint = ctx.accounts.defai_mint.key();
+    access.rent_payer = ctx.accounts.user.key();
+    access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppEditionPurchased {
+        app_id,
+        edition_id,
+        user: ctx.accounts.user.key(),
+        price,
+        timestamp: now,
+    });
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: Some(edition_id),
+    });
+
+    msg!("User purchased app {} edition {}", app_id, edition_id);
+    Ok(())
+}
@@ -0,0 +1,330 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError,
+    purchase_app_pre_validation, execute_token_transfers, mint_app_sft,
+    Sale, distribute_creator_amount, CreatorVesting, creator_payout_ata,
+    pay_charity_donation, TransferabilityError, burn_purchase_share, PurchaseSplit, ProgramRegistry,
+};
+use defai_common::{emit_cpi_versioned, event_cpi_of};
+
+// Purchases paid from a defai_estate vault. The estate program CPIs in with the
+// estate PDA as signer after checking the estate owner's signature; the DEFAI
+// comes out of an estate-owned token account and the access SFT and
+// UserAppAccess are issued to the estate PDA, so the app passes to
+// beneficiaries with the rest of the estate. Soulbound apps are rejected, and
+// affiliate commissions are not paid on this path. Backends check access for
+// the estate address. The estate must belong to the estate program linked in
+// the program registry.
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurchaseAppFromEstate<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        // Soulbound SFTs are frozen and could never be claimed from the estate
+        constraint = !app_registration.soulbound @ TransferabilityError::AppIsSoulbound
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = rent_payer,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), estate.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    // The SFT lands in the estate's ATA, where estate claims can reach it
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        associated_token::mint = sft_mint,
+        associated_token::authority = estate
+    )]
+    pub estate_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    // Estate-held DEFAI paying for the app
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = estate
+    )]
+    pub estate_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    // Optional scheduled sale
+    #[account(
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump = sale.bump
+    )]
+    pub sale: Option<Box<Account<'info, Sale>>>,
+
+    #[account(
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Box<Account<'info, ProgramRegistry>>,
+
+    // Estate PDA, signed for by the estate program
+    #[account(
+        constraint = *estate.owner == program_registry.links.estate @ EstatePurchaseError::NotAnEstate
+    )]
+    pub estate: Signer<'info>,
+
+    // Estate owner, paying rent for the new accounts
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppPurchasedByEstate {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub estate: Pubkey,
+    pub rent_payer: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum EstatePurchaseError {
+    #[msg("Payer is not an account of the linked defai_estate program")]
+    NotAnEstate,
+}
+
+pub fn purchase_app_from_estate<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseAppFromEstate<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let mut price = 0u64;
+    let mut platform_fee = 0u64;
+    let mut creator_amount = 0u64;
+
+    purchase_app_pre_validation(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.app_factory,
+        ctx.accounts.sale.as_deref().map(|sale| &**sale),
+        &mut price,
+        &mut platform_fee,
+        &mut creator_amount,
+    )?;
+    require!(
+        ctx.accounts.estate_vault.amount >= price,
+        AppFactoryError::InsufficientBalance
+    );
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.estate.to_account_info(),
+        &ctx.accounts.estate_vault.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.estate.to_account_info(),
+        &ctx.accounts.estate_vault.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.estate_vault,
+        &ctx.accounts.estate.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers out of the estate vault
+    execute_token_transfers(
+        &ctx.accounts.estate,
+        &ctx.accounts.estate_vault,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Mint SFT to the estate
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.estate_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, now)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    // Record access for the estate
+    let estate = ctx.accounts.estate.key();
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = estate;
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.estate_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.rent_payer.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+
+    // Emit events
+    emit_cpi_versioned!(event_cpi_of!(ctx), crate::AppPurchased {
+        app_id,
+        user: estate,
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchasedByEstate {
+        app_id,
+        estate,
+        rent_payer: ctx.accounts.rent_payer.key(),
+        price,
+        timestamp: now,
+    });
+
+    msg!("Estate {} purchased app {} access", estate, app_id);
+    Ok(())
+}
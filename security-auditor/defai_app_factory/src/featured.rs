@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::emit_versioned;
+
+pub const MAX_FEATURED_SLOTS: usize = 12;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct FeaturedSlot {
+    pub app_id: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+impl FeaturedSlot {
+    pub const LEN: usize = 8 + 8 + 8;
+}
+
+// Ordered home-page curation; slot 0 is shown first
+#[account]
+pub struct FeaturedList {
+    pub slots: Vec<FeaturedSlot>,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl FeaturedList {
+    pub const LEN: usize = 8 + 4 + MAX_FEATURED_SLOTS * FeaturedSlot::LEN + 8 + 1;
+
+    pub fn app_ids(&self) -> Vec<u64> {
+        self.slots.iter().map(|slot| slot.app_id).collect()
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct AddFeaturedApp<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = FeaturedList::LEN,
+        seeds = [b"featured_list"],
+        bump
+    )]
+    pub featured_list: Account<'info, FeaturedList>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeaturedList<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"featured_list"],
+        bump = featured_list.bump
+    )]
+    pub featured_list: Account<'info, FeaturedList>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct FeaturedAppAdded {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub position: u8,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeaturedAppRemoved {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeaturedListRotated {
+    pub schema_version: u8,
+    pub shift: u8,
+    pub expired_removed: u8,
+    pub app_ids: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum FeaturedError {
+    #[msg("Featured list is full")]
+    FeaturedListFull,
+    #[msg("App is already featured")]
+    AlreadyFeatured,
+    #[msg("App is not featured")]
+    NotFeatured,
+    #[msg("Slot end must be after start and in the future")]
+    InvalidSlotWindow,
+    #[msg("Position is past the end of the list")]
+    InvalidPosition,
+}
+
+pub fn add_featured_app(
+    ctx: Context<AddFeaturedApp>,
+    app_id: u64,
+    position: u8,
+    start_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(end_time > start_time && end_time > now, FeaturedError::InvalidSlotWindow);
+
+    let featured_list = &mut ctx.accounts.featured_list;
+    require!(featured_list.slots.len() < MAX_FEATURED_SLOTS, FeaturedError::FeaturedListFull);
+    require!(
+        !featured_list.slots.iter().any(|slot| slot.app_id == app_id),
+        FeaturedError::AlreadyFeatured
+    );
+    require!(
+        position as usize <= featured_list.slots.len(),
+        FeaturedError::InvalidPosition
+    );
+
+    featured_list.slots.insert(position as usize, FeaturedSlot {
+        app_id,
+        start_time,
+        end_time,
+    });
+    featured_list.updated_at = now;
+    featured_list.bump = ctx.bumps.featured_list;
+
+    emit_versioned!(FeaturedAppAdded {
+        app_id,
+        position,
+        start_time,
+        end_time,
+        timestamp: now,
+    });
+
+    msg!("App {} featured at position {}", app_id, position);
+    Ok(())
+}
+
+pub fn remove_featured_app(ctx: Context<UpdateFeaturedList>, app_id: u64) -> Result<()> {
+    let featured_list = &mut ctx.accounts.featured_list;
+    let index = featured_list.slots
+        .iter()
+        .position(|slot| slot.app_id == app_id)
+        .ok_or(FeaturedError::NotFeatured)?;
+    featured_list.slots.remove(index);
+
+    let now = Clock::get()?.unix_timestamp;
+    featured_list.updated_at = now;
+
+    emit_versioned!(FeaturedAppRemoved {
+        app_id,
+        timestamp: now,
+    });
+
+    msg!("App {} removed from featured list", app_id);
+    Ok(())
+}
+
+// Drops expired slots, then rotates the remaining order left by `shift`
+pub fn rotate_featured_apps(ctx: Context<UpdateFeaturedList>, shift: u8) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let featured_list = &mut ctx.accounts.featured_list;
+
+    let before = featured_list.slots.len();
+    featured_list.slots.retain(|slot| slot.end_time > now);
+    let expired_removed = (before - featured_list.slots.len()) as u8;
+
+    if !featured_list.slots.is_empty() {
+        let len = featured_list.slots.len();
+        featured_list.slots.rotate_left(shift as usize % len);
+    }
+    featured_list.updated_at = now;
+
+    emit_versioned!(FeaturedListRotated {
+        shift,
+        expired_removed,
+        app_ids: featured_list.app_ids(),
+        timestamp: now,
+    });
+
+    msg!(
+        "Featured list rotated by {}; {} expired slots removed",
+        shift,
+        expired_removed
+    );
+    Ok(())
+}
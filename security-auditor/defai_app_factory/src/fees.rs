@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::emit_versioned;
+
+// Platform fee overrides. The authority can pin a fee for one app or for a
+// category. Purchases read the resolved fee from AppRegistration so an
+// override can't be dodged by omitting an account:
+//   app override > lowest category override > global platform_fee_bps
+// Category fees are cached on each app by the permissionless
+// `sync_app_category_fee` crank, which must be re-run after a category fee
+// changes or the app's categories change.
+
+#[account]
+pub struct CategoryFeeOverride {
+    pub category_id: u16,
+    pub fee_bps: u16,
+    pub bump: u8,
+}
+
+impl CategoryFeeOverride {
+    pub const LEN: usize = 8 + 2 + 2 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppFeeOverride<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(category_id: u16)]
+pub struct SetCategoryFeeOverride<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CategoryFeeOverride::LEN,
+        seeds = [b"category_fee".as_ref(), &category_id.to_le_bytes()],
+        bump
+    )]
+    pub category_fee: Account<'info, CategoryFeeOverride>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(category_id: u16)]
+pub struct ClearCategoryFeeOverride<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"category_fee".as_ref(), &category_id.to_le_bytes()],
+        bump = category_fee.bump,
+        close = authority
+    )]
+    pub category_fee: Account<'info, CategoryFeeOverride>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SyncAppCategoryFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+}
+
+#[event]
+pub struct AppFeeOverrideSet {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub fee_bps: Option<u16>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CategoryFeeOverrideSet {
+    pub schema_version: u8,
+    pub category_id: u16,
+    pub fee_bps: Option<u16>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppCategoryFeeSynced {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub category_fee_bps: Option<u16>,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum FeeError {
+    #[msg("Fee override exceeds the maximum platform fee")]
+    InvalidFeeOverride,
+    #[msg("Pass one category fee account per app category, in order")]
+    CategoryFeeAccountsMismatch,
+    #[msg("Category fee account does not match the app's category")]
+    InvalidCategoryFeeAccount,
+}
+
+pub fn set_app_fee_override(
+    ctx: Context<SetAppFeeOverride>,
+    app_id: u64,
+    fee_bps: Option<u16>,
+) -> Result<()> {
+    if let Some(fee) = fee_bps {
+        require!(fee <= ctx.accounts.app_factory.max_platform_fee_bps, FeeError::InvalidFeeOverride);
+    }
+    ctx.accounts.app_registration.fee_override_bps = fee_bps;
+
+    emit_versioned!(AppFeeOverrideSet {
+        app_id,
+        fee_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Fee override for app {} set to {:?} bps", app_id, fee_bps);
+    Ok(())
+}
+
+pub fn set_category_fee_override(
+    ctx: Context<SetCategoryFeeOverride>,
+    category_id: u16,
+    fee_bps: u16,
+) -> Result<()> {
+    require!(fee_bps <= ctx.accounts.app_factory.max_platform_fee_bps, FeeError::InvalidFeeOverride);
+
+    let category_fee = &mut ctx.accounts.category_fee;
+    category_fee.category_id = category_id;
+    category_fee.fee_bps = fee_bps;
+    category_fee.bump = ctx.bumps.category_fee;
+
+    emit_versioned!(CategoryFeeOverrideSet {
+        category_id,
+        fee_bps: Some(fee_bps),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Fee override for category {} set to {} bps", category_id, fee_bps);
+    Ok(())
+}
+
+pub fn clear_category_fee_override(
+    _ctx: Context<ClearCategoryFeeOverride>,
+    category_id: u16,
+) -> Result<()> {
+    emit_versioned!(CategoryFeeOverrideSet {
+        category_id,
+        fee_bps: None,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Fee override for category {} cleared", category_id);
+    Ok(())
+}
+
+// Remaining accounts: the CategoryFeeOverride PDA for each of the app's
+// categories, in order. PDAs without an override are passed uninitialized.
+pub fn sync_app_category_fee(ctx: Context<SyncAppCategoryFee>, app_id: u64) -> Result<()> {
+    let categories = ctx.accounts.app_registration.categories.clone();
+    require!(
+        ctx.remaining_accounts.len() == categories.len(),
+        FeeError::CategoryFeeAccountsMismatch
+    );
+
+    let mut category_fee_bps: Option<u16> = None;
+    for (category_id, info) in categories.iter().zip(ctx.remaining_accounts.iter()) {
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"category_fee".as_ref(), &category_id.to_le_bytes()],
+            &crate::ID,
+        );
+        require_keys_eq!(info.key(), expected, FeeError::InvalidCategoryFeeAccount);
+        if info.data_is_empty() {
+            continue;
+        }
+
+        let override_fee = CategoryFeeOverride::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        category_fee_bps = Some(match category_fee_bps {
+            Some(current) => current.min(override_fee.fee_bps),
+            None => override_fee.fee_bps,
+        });
+    }
+    ctx.accounts.app_registration.category_fee_bps = category_fee_bps;
+
+    emit_versioned!(AppCategoryFeeSynced {
+        app_id,
+        category_fee_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} category fee synced: {:?} bps", app_id, category_fee_bps);
+    Ok(())
+}
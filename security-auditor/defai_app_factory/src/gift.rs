@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased, Sale,
+    purchase_app_pre_validation, execute_token_transfers, mint_app_sft, distribute_creator_amount,
+    CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, event_cpi_of};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64, recipient: Pubkey)]
+pub struct PurchaseAppAsGift<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    // Access is recorded for the recipient, not the payer
+    #[account(
+        init,
+        payer = payer,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), recipient.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = sft_mint,
+        associated_token::authority = recipient_wallet
+    )]
+    pub recipient_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = payer
+    )]
+    pub payer_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    // Optional scheduled sale
+    #[account(
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump = sale.bump
+    )]
+    pub sale: Option<Box<Account<'info, Sale>>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Gift recipient wallet; only used as the SFT ATA owner
+    #[account(
+        address = recipient,
+        constraint = recipient_wallet.key() != payer.key() @ GiftError::CannotGiftSelf
+    )]
+    pub recipient_wallet: AccountInfo<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppGifted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub payer: Pubkey,
+    pub recipient: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum GiftError {
+    #[msg("Use a regular purchase to buy for yourself")]
+    CannotGiftSelf,
+}
+
+pub fn purchase_app_as_gift<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseAppAsGift<'info>>,
+    app_id: u64,
+    recipient: Pubkey,
+) -> Result<()> {
+    let mut price = 0u64;
+    let mut platform_fee = 0u64;
+    let mut creator_amount = 0u64;
+
+    // Pre-validation
+    purchase_app_pre_validation(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.app_factory,
+        ctx.accounts.sale.as_deref().map(|sale| &**sale),
+        &mut price,
+        &mut platform_fee,
+        &mut creator_amount,
+    )?;
+    require!(
+        ctx.accounts.payer_defai_ata.amount >= price,
+        AppFactoryError::InsufficientBalance
+    );
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payer_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.payer_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.payer_defai_ata,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers
+    execute_token_transfers(
+        &ctx.accounts.payer,
+        &ctx.accounts.payer_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Mint SFT to the recipient
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.recipient_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    // Record access for the recipient
+    let now = Clock::get()?.unix_timestamp;
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = recipient;
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.recipient_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.payer.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppGifted {
+        app_id,
+        payer: ctx.accounts.payer.key(),
+        recipient,
+        price,
+        timestamp: now,
+    });
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: recipient,
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("User {} gifted app {} access to {}", ctx.accounts.payer.key(), app_id, recipient);
+    Ok(())
+}
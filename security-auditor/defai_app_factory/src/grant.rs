@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{AppFactory, AppRegistration, UserAppAccess, AppFactoryError, mint_app_sft};
+use defai_common::emit_versioned;
+
+#[derive(Accounts)]
+#[instruction(app_id: u64, recipient: Pubkey)]
+pub struct CreatorGrantAccess<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), recipient.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = sft_mint,
+        associated_token::authority = recipient_wallet
+    )]
+    pub recipient_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Grant recipient wallet; only used as the SFT ATA owner
+    #[account(address = recipient)]
+    pub recipient_wallet: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AccessGranted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+// Free access for giveaways, press and beta testers; counts against supply
+pub fn creator_grant_access(
+    ctx: Context<CreatorGrantAccess>,
+    app_id: u64,
+    recipient: Pubkey,
+) -> Result<()> {
+    let app_registration = &ctx.accounts.app_registration;
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    // Mint SFT to the recipient
+    let bump = app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.recipient_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    // Record access at zero price so refund paths pay nothing back
+    let now = Clock::get()?.unix_timestamp;
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = recipient;
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.recipient_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = 0;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.app_factory.defai_mint;
+    user_app_access.rent_payer = ctx.accounts.creator.key();
+
+    emit_versioned!(AccessGranted {
+        app_id,
+        creator: ctx.accounts.creator.key(),
+        recipient,
+        timestamp: now,
+    });
+
+    msg!("Creator granted app {} access to {}", app_id, recipient);
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount as TokenAccount2022;
+use defai_common::{bps_of, emit_versioned, require_linked_owner};
+use defai_swap::BonusStateV6;
+
+use crate::{AppFactory, AppFactoryError, ProgramRegistry};
+
+// Discounts for holders of defai_swap bonus NFTs. The platform authority sets a
+// discount per NFT tier; a buyer proves ownership by passing the NFT's token
+// account and its bonus_state PDA, which must belong to the swap program
+// linked in the program registry. The discount is funded from the platform
+// fee (and capped at it) so creator earnings are unaffected.
+
+pub const BONUS_NFT_TIERS: usize = 5;
+
+#[derive(Accounts)]
+pub struct SetHolderDiscounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct HolderDiscountsSet {
+    pub schema_version: u8,
+    pub discount_bps: [u16; BONUS_NFT_TIERS],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HolderDiscountApplied {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub tier: u8,
+    pub discount: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum HolderDiscountError {
+    #[msg("Discount cannot exceed 100%")]
+    InvalidDiscount,
+    #[msg("Bonus NFT token account and bonus state must be passed together")]
+    MissingBonusNftAccount,
+    #[msg("Buyer does not hold the bonus NFT")]
+    BonusNftNotHeld,
+    #[msg("The program registry must be passed with a bonus NFT")]
+    MissingProgramRegistry,
+    #[msg("Not the bonus state PDA of the linked swap program")]
+    InvalidBonusState,
+}
+
+pub fn set_holder_discounts(
+    ctx: Context<SetHolderDiscounts>,
+    discount_bps: [u16; BONUS_NFT_TIERS],
+) -> Result<()> {
+    require!(
+        discount_bps.iter().all(|bps| *bps <= 10000),
+        HolderDiscountError::InvalidDiscount
+    );
+    ctx.accounts.app_factory.holder_discount_bps = discount_bps;
+
+    emit_versioned!(HolderDiscountsSet {
+        discount_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Bonus NFT holder discounts set to {:?} bps", discount_bps);
+    Ok(())
+}
+
+// A BonusStateV6 owned by the linked swap program, at its PDA for its mint
+fn load_bonus_state(info: &AccountInfo, swap_program: &Pubkey) -> Result<BonusStateV6> {
+    require_linked_owner(info, swap_program)?;
+    let state = BonusStateV6::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    let (address, _) = Pubkey::find_program_address(&[b"bonus_v6", state.mint.as_ref()], swap_program);
+    require_keys_eq!(info.key(), address, HolderDiscountError::InvalidBonusState);
+    Ok(state)
+}
+
+// Reduce the price and platform fee by the holder's tier discount, if a bonus
+// NFT was passed. Returns the discount.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_holder_discount(
+    app_factory: &AppFactory,
+    bonus_state: Option<&AccountInfo>,
+    bonus_nft_ata: Option<&InterfaceAccount<TokenAccount2022>>,
+    program_registry: Option<&Account<ProgramRegistry>>,
+    user: Pubkey,
+    app_id: u64,
+    price: &mut u64,
+    platform_fee: &mut u64,
+) -> Result<u64> {
+    let (bonus_state, bonus_nft_ata) = match (bonus_state, bonus_nft_ata) {
+        (None, None) => return Ok(0),
+        (Some(state), Some(ata)) => (state, ata),
+        _ => return err!(HolderDiscountError::MissingBonusNftAccount),
+    };
+    let registry = program_registry.ok_or(HolderDiscountError::MissingProgramRegistry)?;
+    let bonus_state = load_bonus_state(bonus_state, &registry.links.swap)?;
+    require!(
+        bonus_nft_ata.owner == user
+            && bonus_nft_ata.mint == bonus_state.mint
+            && bonus_nft_ata.amount == 1,
+        HolderDiscountError::BonusNftNotHeld
+    );
+
+    let discount_bps = app_factory
+        .holder_discount_bps
+        .get(bonus_state.tier as usize)
+        .copied()
+        .unwrap_or(0);
+    let discount = bps_of(*price, discount_bps as u64)?;
+    let discount = discount.min(*platform_fee);
+    if discount == 0 {
+        return Ok(0);
+    }
+
+    *price -= discount;
+    *platform_fee -= discount;
+
+    emit_versioned!(HolderDiscountApplied {
+        app_id,
+        user,
+        nft_mint: bonus_state.mint,
+        tier: bonus_state.tier,
+        discount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(discount)
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::rental::{self, *};
+use crate::{listing_stake, AppFactory, AppFactoryError, AppRegistration, CreatorVesting};
+use defai_common::{emit_cpi_versioned, event_cpi_of, pay_crank_reward, EventCpi};
+
+// Keeper cranks (see defai_common::crank)
+// Permissionless versions of the factory's time-based transitions, paid from
+// the `keeper_vault` PDA: rental expiry, listing stake unlock and the end of a
+// creator vesting window. Each one fails once its transition has been applied,
+// so a keeper can't be paid twice for the same work.
+
+#[account]
+pub struct KeeperVault {
+    pub cranks: u64,          // Successful cranks
+    pub rewards_paid: u64,    // Lamports paid to keepers
+    pub bump: u8,
+}
+
+impl KeeperVault {
+    pub const LEN: usize = 8 + 8 + 1;
+
+    // Pay the keeper for a crank that just succeeded
+    pub fn reward<'info>(
+        vault: &mut Account<'info, KeeperVault>,
+        keeper: &AccountInfo<'info>,
+        crank: &str,
+        target: Pubkey,
+        events: &EventCpi<'_, 'info>,
+    ) -> Result<()> {
+        let reward = pay_crank_reward(&vault.to_account_info(), keeper, &Rent::get()?)?;
+        vault.cranks = vault.cranks.saturating_add(1);
+        vault.rewards_paid = vault.rewards_paid.saturating_add(reward);
+
+        emit_cpi_versioned!(events, KeeperCranked {
+            keeper: keeper.key(),
+            crank: crank.to_string(),
+            target,
+            reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + KeeperVault::LEN,
+        seeds = [b"keeper_vault"],
+        bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+    pub system_program: Program<'info, System>,
+}
+
+// The keeper passes itself as `expire.caller` too
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankExpireAccess<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    pub expire: ExpireAccess<'info>,
+    #[account(
+        mut,
+        seeds = [b"keeper_vault"],
+        bump = keeper_vault.bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CrankReleaseCreatorStake<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = app_factory.defai_mint,
+        token::authority = creator
+    )]
+    pub creator_defai_ata: Account<'info, TokenAccount>,
+
+    /// CHECK: Only used to bind creator_defai_ata; checked via has_one
+    pub creator: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper_vault"],
+        bump = keeper_vault.bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CrankSettleCreatorVesting<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+
+    #[account(
+        mut,
+        seeds = [b"keeper_vault"],
+        bump = keeper_vault.bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+}
+
+// Anyone may create the vault; it holds nothing but keeper rewards
+pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+    let vault = &mut ctx.accounts.keeper_vault;
+    vault.cranks = 0;
+    vault.rewards_paid = 0;
+    vault.bump = ctx.bumps.keeper_vault;
+    Ok(())
+}
+
+// `expire_access` plus the reward. The access record is closed, so a second
+// crank fails on account validation.
+pub fn crank_expire_access<'info>(
+    ctx: Context<'_, '_, '_, 'info, CrankExpireAccess<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let Context { program_id, accounts, remaining_accounts, bumps } = ctx;
+    let target = accounts.expire.user_app_access.key();
+    rental::expire_access(
+        Context::new(program_id, &mut accounts.expire, remaining_accounts, bumps.expire),
+        app_id,
+    )?;
+
+    let events = EventCpi::new(program_id, &accounts.event_authority, bumps.event_authority);
+    KeeperVault::reward(&mut accounts.keeper_vault, &accounts.keeper, "expire_access", target, &events)
+}
+
+// Return a listing stake to its creator once STAKE_LOCK_PERIOD has passed
+pub fn crank_release_creator_stake(ctx: Context<CrankReleaseCreatorStake>, app_id: u64) -> Result<()> {
+    listing_stake::release_stake(
+        &ctx.accounts.app_factory,
+        &mut ctx.accounts.app_registration,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.creator_defai_ata,
+        &ctx.accounts.token_program,
+        app_id,
+        &event_cpi_of!(ctx),
+    )?;
+
+    let target = ctx.accounts.app_registration.key();
+    KeeperVault::reward(
+        &mut ctx.accounts.keeper_vault,
+        &ctx.accounts.keeper,
+        "release_creator_stake",
+        target,
+        &event_cpi_of!(ctx),
+    )
+}
+
+// Move a finished vesting window's locked balance into `claimable`. Only
+// allowed once the window has ended with something still locked.
+pub fn crank_settle_creator_vesting(ctx: Context<CrankSettleCreatorVesting>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let creator_vesting = &mut ctx.accounts.creator_vesting;
+    require!(
+        creator_vesting.locked > 0 && now >= creator_vesting.vest_end,
+        KeeperError::NothingToSettle
+    );
+    creator_vesting.settle(now)?;
+
+    msg!("Settled vesting for app {}: {} DEFAI claimable", app_id, creator_vesting.claimable);
+
+    let target = creator_vesting.key();
+    KeeperVault::reward(
+        &mut ctx.accounts.keeper_vault,
+        &ctx.accounts.keeper,
+        "settle_creator_vesting",
+        target,
+        &event_cpi_of!(ctx),
+    )
+}
+
+#[event]
+pub struct KeeperCranked {
+    pub schema_version: u8,
+    pub keeper: Pubkey,
+    pub crank: String,
+    pub target: Pubkey,   // Account the transition was applied to
+    pub reward: u64,      // Lamports paid; 0 when the vault is empty
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum KeeperError {
+    #[msg("Vesting window has not ended or nothing is locked")]
+    NothingToSettle,
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::System;
-use anchor_spl::token::{self as token, Mint, Token, SetAuthority};
+use anchor_spl::token::{self as token, Mint, Token, TokenAccount, SetAuthority};
 use anchor_spl::token::spl_token::instruction::AuthorityType;
 use solana_program::program_option::COption;
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
 
 mod purchase_app;
 use purchase_app::*;
@@ -15,6 +16,7 @@ use update_app::*;
 
 mod refund;
 use refund::*;
+pub use refund::{PurchaseSplit, RefundError};
 
 mod reviews;
 use reviews::*;
@@ -22,14 +24,267 @@ use reviews::*;
 mod authority;
 use authority::*;
 
+mod subscription;
+use subscription::*;
+pub use subscription::{Subscription, SubscriptionError};
+
+mod rental;
+use rental::*;
+pub use rental::RentalError;
+
+mod versioning;
+use versioning::*;
+
+mod revenue_split;
+use revenue_split::*;
+pub use revenue_split::SplitRecipient;
+
+mod dispute;
+use dispute::*;
+pub use dispute::{Dispute, DisputeError, DisputeStatus};
+
+mod currency;
+use currency::*;
+pub use currency::CurrencyError;
+
+mod coupon;
+use coupon::*;
+
+mod sale;
+use sale::*;
+
+mod bundle;
+use bundle::*;
+pub use bundle::{BundleError, BundlePurchase};
+
+mod gift;
+use gift::*;
+
+mod edition;
+use edition::*;
+
+mod delisting;
+use delisting::*;
+pub use delisting::{Delisting, DelistingError};
+
+mod verification;
+use verification::*;
+
+mod category;
+use category::*;
+
+mod featured;
+use featured::*;
+
+mod affiliate;
+use affiliate::*;
+
+mod app_transfer;
+use app_transfer::*;
+
+mod presale;
+use presale::*;
+
+mod grant;
+use grant::*;
+
+mod credits;
+use credits::*;
+
+mod collection;
+use collection::*;
+
+mod transferability;
+use transferability::*;
+
+mod fees;
+use fees::*;
+
+mod vesting;
+use vesting::*;
+pub use vesting::CreatorVesting;
+
+mod access;
+use access::*;
+
+mod batch;
+use batch::*;
+
+mod supply;
+use supply::*;
+
+mod migration;
+use migration::*;
+
+mod settings;
+use settings::*;
+
+mod charity;
+use charity::*;
+
+mod delegation;
+use delegation::*;
+
+mod estate_purchase;
+use estate_purchase::*;
+
+mod holder_discount;
+use holder_discount::*;
+
+mod organization;
+use organization::*;
+
+mod burn;
+use burn::*;
+
+mod listing_stake;
+use listing_stake::*;
+
+mod cleanup;
+use cleanup::*;
+
+mod trial;
+use trial::*;
+
+mod report;
+use report::*;
+mod stats;
+use stats::*;
+pub use stats::FactoryStats;
+
+mod audit;
+use audit::*;
+
+mod security_info;
+use security_info::*;
+
+mod keeper;
+use keeper::*;
+
+mod treasury;
+use treasury::*;
+pub use treasury::{TreasuryLedger, WithdrawalPolicy};
+
+mod program_registry;
+use program_registry::*;
+pub use program_registry::{ProgramLinks, ProgramRegistry};
+
 declare_id!("FyDBGJFfviW1mqKYWueLQCW4YUm9RmUgQeEYw1izszDA");
 
+// Disclosure contact embedded in the binary's .security.txt section
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "DEFAI App Factory",
+    project_url: "https://github.com/heirlabs/audit",
+    contacts: "email:security@defai.estate,link:https://github.com/heirlabs/audit/security/advisories/new",
+    policy: "https://github.com/heirlabs/audit/blob/main/SECURITY.md",
+    preferred_languages: "en",
+    source_code: "https://github.com/heirlabs/audit",
+    expiry: "2027-10-16"
+}
+
 // ============================================================================
 // Constants
 // ============================================================================
 
-const APP_REGISTRATION_SEED: &[u8] = b"app_registration";
-const MAX_METADATA_URI_LEN: usize = 100;
+// PDA seeds. Account constraints spell these as byte literals, which is the
+// only form Anchor 0.29's IDL parser resolves into PDA derivations.
+#[constant]
+pub const ACCESS_DELEGATION_SEED: &[u8] = b"access_delegation";
+#[constant]
+pub const AFFILIATE_STATS_SEED: &[u8] = b"affiliate_stats";
+#[constant]
+pub const APP_CURRENCY_PRICES_SEED: &[u8] = b"app_currency_prices";
+#[constant]
+pub const APP_EDITION_SEED: &[u8] = b"app_edition";
+#[constant]
+pub const APP_FACTORY_SEED: &[u8] = b"app_factory";
+#[constant]
+pub const APP_MIGRATION_SEED: &[u8] = b"app_migration";
+#[constant]
+pub const APP_PRESALE_SEED: &[u8] = b"app_presale";
+#[constant]
+pub const APP_REGISTRATION_SEED: &[u8] = b"app_registration";
+#[constant]
+pub const APP_REPORT_SEED: &[u8] = b"app_report";
+#[constant]
+pub const APP_REVIEW_SEED: &[u8] = b"app_review";
+#[constant]
+pub const APP_TRANSFER_SEED: &[u8] = b"app_transfer";
+#[constant]
+pub const APP_VERSION_SEED: &[u8] = b"app_version";
+#[constant]
+pub const BUNDLE_SEED: &[u8] = b"bundle";
+#[constant]
+pub const BUNDLE_PURCHASE_SEED: &[u8] = b"bundle_purchase";
+#[constant]
+pub const CATEGORY_FEE_SEED: &[u8] = b"category_fee";
+#[constant]
+pub const CATEGORY_REGISTRY_SEED: &[u8] = b"category_registry";
+#[constant]
+pub const CHARITY_SEED: &[u8] = b"charity";
+#[constant]
+pub const COUPON_ROOT_SEED: &[u8] = b"coupon_root";
+#[constant]
+pub const COUPON_USAGE_SEED: &[u8] = b"coupon_usage";
+#[constant]
+pub const CREATOR_VESTING_SEED: &[u8] = b"creator_vesting";
+#[constant]
+pub const CREATOR_VESTING_VAULT_SEED: &[u8] = b"creator_vesting_vault";
+#[constant]
+pub const CREDIT_BALANCE_SEED: &[u8] = b"credit_balance";
+#[constant]
+pub const CREDIT_CONFIG_SEED: &[u8] = b"credit_config";
+#[constant]
+pub const CURRENCY_CONFIG_SEED: &[u8] = b"currency_config";
+#[constant]
+pub const DELISTING_SEED: &[u8] = b"delisting";
+#[constant]
+pub const DELISTING_VAULT_SEED: &[u8] = b"delisting_vault";
+#[constant]
+pub const DISPUTE_SEED: &[u8] = b"dispute";
+#[constant]
+pub const DISPUTE_CONFIG_SEED: &[u8] = b"dispute_config";
+#[constant]
+pub const DISPUTE_VAULT_SEED: &[u8] = b"dispute_vault";
+#[constant]
+pub const FACTORY_STATS_SEED: &[u8] = b"factory_stats";
+#[constant]
+pub const FEATURED_LIST_SEED: &[u8] = b"featured_list";
+#[constant]
+pub const KEEPER_VAULT_SEED: &[u8] = b"keeper_vault";
+#[constant]
+pub const PROGRAM_REGISTRY_SEED: &[u8] = b"program_registry";
+#[constant]
+pub const PURCHASER_AUTHORIZATION_SEED: &[u8] = b"purchaser_authorization";
+#[constant]
+pub const REVENUE_SPLIT_SEED: &[u8] = b"revenue_split";
+#[constant]
+pub const SALE_SEED: &[u8] = b"sale";
+#[constant]
+pub const SECURITY_INFO_SEED: &[u8] = b"security_info";
+#[constant]
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+#[constant]
+pub const SUBSCRIPTION_SEED: &[u8] = b"subscription";
+#[constant]
+pub const SUBSCRIPTION_PLAN_SEED: &[u8] = b"subscription_plan";
+#[constant]
+pub const SUPPLY_INCREASE_SEED: &[u8] = b"supply_increase";
+#[constant]
+pub const TREASURY_LEDGER_SEED: &[u8] = b"treasury_ledger";
+#[constant]
+pub const TRIAL_SEED: &[u8] = b"trial";
+#[constant]
+pub const USER_APP_ACCESS_SEED: &[u8] = b"user_app_access";
+#[constant]
+pub const VERIFIED_CREATOR_SEED: &[u8] = b"verified_creator";
+
+const MAX_METADATA_URI_LEN: usize = 256;
+
+// Protocol ceiling on any platform fee (30%); copied into AppFactory at init and never changeable
+#[constant]
+pub const MAX_PLATFORM_FEE_BPS: u16 = 3000;
 
 // ============================================================================
 // Program
@@ -43,7 +298,7 @@ pub mod defai_app_factory {
         ctx: Context<InitializeAppFactory>,
         platform_fee_bps: u16,
     ) -> Result<()> {
-        require!(platform_fee_bps <= 10000, AppFactoryError::InvalidPlatformFee);
+        require!(platform_fee_bps <= MAX_PLATFORM_FEE_BPS, AppFactoryError::InvalidPlatformFee);
 
         let app_factory = &mut ctx.accounts.app_factory;
         app_factory.authority = ctx.accounts.authority.key();
@@ -54,6 +309,20 @@ pub mod defai_app_factory {
         app_factory.total_apps = 0;
         app_factory.bump = ctx.bumps.app_factory;
         app_factory.pending_authority = None;
+        app_factory.paused = false;
+        app_factory.require_transfer_approval = false;
+        app_factory.pending_treasury = None;
+        app_factory.pending_platform_fee_bps = None;
+        app_factory.settings_change_at = 0;
+        app_factory.purchase_count = 0;
+        app_factory.price_notice_hours = 0;
+        app_factory.holder_discount_bps = [0; BONUS_NFT_TIERS];
+        app_factory.review_window_days = 0;
+        app_factory.burn_bps = 0;
+        app_factory.max_platform_fee_bps = MAX_PLATFORM_FEE_BPS;
+        app_factory.registration_fee = 0;
+        app_factory.registration_stake = 0;
+        app_factory.report_threshold = 0;
 
         msg!("AppFactory initialized with {}% platform fee", platform_fee_bps as f64 / 100.0);
         Ok(())
@@ -64,10 +333,35 @@ pub mod defai_app_factory {
         price: u64,
         max_supply: u64,
         metadata_uri: String,
+        categories: Vec<u16>,
+        royalty_bps: u16,
+        refund_policy: Option<RefundPolicy>,
     ) -> Result<()> {
         require!(price > 0, AppFactoryError::InvalidPrice);
         require!(max_supply > 0, AppFactoryError::InvalidMaxSupply);
         require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, AppFactoryError::MetadataUriTooLong);
+        require!(royalty_bps <= MAX_ROYALTY_BPS, CollectionError::RoyaltyTooHigh);
+        if let Some(policy) = &refund_policy {
+            policy.validate()?;
+        }
+        if !categories.is_empty() {
+            ctx.accounts.category_registry
+                .as_ref()
+                .ok_or(CategoryError::MissingCategoryRegistry)?
+                .validate(&categories)?;
+        }
+
+        // Anti-spam: registration fee to the treasury, stake into escrow
+        let stake_amount = collect_registration_payment(
+            &ctx.accounts.app_factory,
+            &ctx.accounts.creator.to_account_info(),
+            ctx.accounts.creator_defai_ata.as_deref(),
+            ctx.accounts.treasury_defai_ata.as_deref(),
+            ctx.accounts.stake_vault.as_deref(),
+            ctx.accounts.treasury_ledger.as_deref_mut(),
+            &ctx.accounts.token_program,
+            &event_cpi_of!(ctx),
+        )?;
 
         let app_factory = &mut ctx.accounts.app_factory;
         let app_id = app_factory.total_apps;
@@ -85,6 +379,42 @@ pub mod defai_app_factory {
         app_registration.metadata_uri = metadata_uri.clone();
         app_registration.created_at = Clock::get()?.unix_timestamp;
         app_registration.bump = ctx.bumps.app_registration;
+        app_registration.rental_price = 0;
+        app_registration.rental_days = 0;
+        app_registration.latest_version = 0;
+        app_registration.edition_count = 0;
+        app_registration.review_count = 0;
+        app_registration.rating_sum = 0;
+        app_registration.delisted = false;
+        app_registration.categories = categories;
+        app_registration.affiliate_bps = 0;
+        app_registration.units_sold = 0;
+        app_registration.gross_revenue = 0;
+        app_registration.refund_count = 0;
+        app_registration.refund_amount = 0;
+        app_registration.last_sale_at = 0;
+        app_registration.presale_start = 0;
+        app_registration.presale_end = 0;
+        app_registration.royalty_bps = royalty_bps;
+        app_registration.soulbound = false;
+        app_registration.refund_policy = refund_policy;
+        app_registration.fee_override_bps = None;
+        app_registration.category_fee_bps = None;
+        app_registration.payout_vesting_days = 0;
+        app_registration.charity = None;
+        app_registration.charity_bps = 0;
+        app_registration.pending_price = 0;
+        app_registration.price_effective_at = 0;
+        app_registration.stake_amount = stake_amount;
+        app_registration.flagged = false;
+        app_registration.trial_price = 0;
+        app_registration.trial_days = 0;
+        app_registration.trial_discount_bps = 0;
+        app_registration.trials_started = 0;
+        app_registration.trial_conversions = 0;
+        app_registration.open_reports = 0;
+        app_registration.suspended = false;
+        ctx.accounts.factory_stats.record_status_change(false, true)?;
 
         // Transfer mint and freeze authority to the app_registration PDA atomically
         {
@@ -116,12 +446,18 @@ pub mod defai_app_factory {
         }
 
         // Emit event
-        emit!(AppRegistered {
+        emit_cpi_versioned!(event_cpi_of!(ctx), AppRegistered {
             app_id,
             creator: ctx.accounts.creator.key(),
             sft_mint: ctx.accounts.sft_mint.key(),
             price,
             max_supply,
+            creator_verified: VerifiedCreator::is_verified(
+                ctx.accounts.verified_creator.as_deref()
+            ),
+            creator_kyc_hash: VerifiedCreator::kyc_hash(
+                ctx.accounts.verified_creator.as_deref()
+            ),
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -241,7 +577,10 @@ pub mod defai_app_factory {
     */
 
     // Optimized purchase function with reduced stack usage
-    pub fn purchase_app_access_v2(ctx: Context<PurchaseAppAccessOptimized>, app_id: u64) -> Result<()> {
+    pub fn purchase_app_access_v2<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppAccessOptimized<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
         let mut price = 0u64;
         let mut platform_fee = 0u64;
         let mut creator_amount = 0u64;
@@ -250,20 +589,96 @@ pub mod defai_app_factory {
         purchase_app_pre_validation(
             &ctx.accounts.app_registration,
             &ctx.accounts.app_factory,
+            ctx.accounts.sale.as_deref().map(|sale| &**sale),
             &mut price,
             &mut platform_fee,
             &mut creator_amount,
         )?;
 
+        // Bonus NFT holders get their tier discount off the platform fee
+        apply_holder_discount(
+            &ctx.accounts.app_factory,
+            ctx.accounts.bonus_state.as_deref(),
+            ctx.accounts.bonus_nft_ata.as_deref(),
+            ctx.accounts.program_registry.as_deref(),
+            ctx.accounts.user.key(),
+            app_id,
+            &mut price,
+            &mut platform_fee,
+        )?;
+        require!(
+            ctx.accounts.user_defai_ata.amount >= price,
+            AppFactoryError::InsufficientBalance
+        );
+
+        // Pay the referring affiliate, if any
+        let commission_base = creator_amount;
+        let creator_amount = pay_affiliate_commission(
+            ctx.accounts.affiliate_stats.as_deref_mut().map(|stats| &mut **stats),
+            ctx.accounts.referrer_defai_ata.as_deref(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_defai_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            ctx.accounts.app_registration.affiliate_bps,
+            price,
+            creator_amount,
+            &event_cpi_of!(ctx),
+        )?;
+
+        // Donate the app's charity share, if any
+        let creator_amount = pay_charity_donation(
+            &ctx.accounts.app_registration,
+            ctx.accounts.charity_defai_ata.as_deref(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_defai_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            price,
+            creator_amount,
+            &event_cpi_of!(ctx),
+        )?;
+
+        // Route the creator share across the revenue split, if any
+        let direct_creator_amount = distribute_creator_amount(
+            &ctx.accounts.revenue_split,
+            ctx.remaining_accounts,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_defai_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.accounts.defai_mint.key(),
+            creator_amount,
+        )?;
+
+        // Pay the creator share into vesting escrow if payouts vest
+        let creator_payout = creator_payout_ata(
+            &ctx.accounts.app_registration,
+            ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+            ctx.accounts.vesting_vault.as_deref(),
+            &ctx.accounts.creator_defai_ata,
+            direct_creator_amount,
+        )?;
+
+        // Burn the factory's share of the payment
+        let burned = burn_purchase_share(
+            &ctx.accounts.app_factory,
+            &ctx.accounts.defai_mint,
+            &ctx.accounts.user_defai_ata,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+            price,
+            &mut platform_fee,
+        )?;
+
         // Execute transfers
         execute_token_transfers(
             &ctx.accounts.user,
             &ctx.accounts.user_defai_ata,
-            &ctx.accounts.creator_defai_ata,
+            creator_payout,
             &ctx.accounts.treasury_defai_ata,
             &ctx.accounts.token_program,
             platform_fee,
-            creator_amount,
+            direct_creator_amount,
         )?;
 
         // Mint SFT
@@ -281,6 +696,8 @@ pub mod defai_app_factory {
         ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
             .checked_add(1)
             .ok_or(AppFactoryError::MathOverflow)?;
+        ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+        ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
 
         // Record access
         let user_app_access = &mut ctx.accounts.user_app_access;
@@ -288,16 +705,37 @@ pub mod defai_app_factory {
         user_app_access.app_id = app_id;
         user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
         user_app_access.purchased_at = Clock::get()?.unix_timestamp;
+        user_app_access.purchase_price = price;
         user_app_access.bump = ctx.bumps.user_app_access;
+        user_app_access.expires_at = 0;
+        user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+        user_app_access.rent_payer = ctx.accounts.user.key();
+        user_app_access.paid_out = PurchaseSplit::from_sale(
+            &ctx.accounts.app_registration,
+            price,
+            burned,
+            platform_fee,
+            commission_base - creator_amount,
+            creator_amount,
+            direct_creator_amount,
+        )?;
+
+        let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
 
         // Emit event
-        emit!(AppPurchased {
+        emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
             app_id,
             user: ctx.accounts.user.key(),
             price,
             platform_fee,
             creator_amount,
+            payment_mint: ctx.accounts.defai_mint.key(),
             timestamp: Clock::get()?.unix_timestamp,
+            purchase_id,
+            burned,
+            sft_mint: ctx.accounts.app_registration.sft_mint,
+            metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+            edition_id: None,
         });
 
         msg!("User purchased app {} access", app_id);
@@ -306,10 +744,14 @@ pub mod defai_app_factory {
 
     pub fn toggle_app_status(ctx: Context<ToggleAppStatus>, _app_id: u64) -> Result<()> {
         let app_registration = &mut ctx.accounts.app_registration;
+        require!(!app_registration.delisted, DelistingError::AppDelisted);
+        require!(!app_registration.flagged, ListingStakeError::AppFlagged);
+        require!(!app_registration.suspended, ReportError::AppSuspended);
         app_registration.is_active = !app_registration.is_active;
+        ctx.accounts.factory_stats.record_status_change(!app_registration.is_active, app_registration.is_active)?;
         
         // Emit event
-        emit!(AppStatusChanged {
+        emit_versioned!(AppStatusChanged {
             app_id: app_registration.app_id,
             is_active: app_registration.is_active,
             timestamp: Clock::get()?.unix_timestamp,
@@ -329,29 +771,55 @@ pub mod defai_app_factory {
     ) -> Result<()> {
         let app_factory = &mut ctx.accounts.app_factory;
         
+        // Fee decreases apply immediately; increases wait out the timelock
+        let mut fee_increase = None;
         if let Some(fee) = new_platform_fee_bps {
-            require!(fee <= 10000, AppFactoryError::InvalidPlatformFee);
-            app_factory.platform_fee_bps = fee;
-            msg!("Platform fee updated to {}%", fee as f64 / 100.0);
+            require!(fee <= app_factory.max_platform_fee_bps, AppFactoryError::InvalidPlatformFee);
+            if fee > app_factory.platform_fee_bps {
+                fee_increase = Some(fee);
+            } else {
+                app_factory.platform_fee_bps = fee;
+                msg!("Platform fee updated to {}%", fee as f64 / 100.0);
+                
+                emit_versioned!(PlatformSettingsUpdated {
+                    platform_fee_bps: Some(fee),
+                    treasury: None,
+                    timestamp: Clock::get()?.unix_timestamp,
+                });
+            }
         }
         
-        // Use the validated account from context
+        // Use the validated account from context; treasury changes are always timelocked
         let new_treasury_key = ctx.accounts.new_treasury.key();
-        app_factory.treasury = new_treasury_key;
-        msg!("Treasury updated to {}", new_treasury_key);
+        let treasury_change = (new_treasury_key != app_factory.treasury).then_some(new_treasury_key);
+        
+        if fee_increase.is_some() || treasury_change.is_some() {
+            settings::queue_platform_settings(app_factory, fee_increase, treasury_change)?;
+        }
+        
+        Ok(())
+    }
+
+    // Emergency stop for purchases, refunds and reviews (authority only)
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        ctx.accounts.app_factory.paused = paused;
         
         // Emit event
-        emit!(PlatformSettingsUpdated {
-            platform_fee_bps: new_platform_fee_bps,
-            treasury: Some(app_factory.treasury),
+        emit_versioned!(FactoryPauseChanged {
+            paused,
+            authority: ctx.accounts.authority.key(),
             timestamp: Clock::get()?.unix_timestamp,
         });
         
+        msg!("App factory is now {}", if paused { "paused" } else { "unpaused" });
         Ok(())
     }
 
     // Single-transaction purchase with automatic ATA initialization
-    pub fn purchase_app_with_init(ctx: Context<PurchaseAppWithInit>, app_id: u64) -> Result<()> {
+    pub fn purchase_app_with_init<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppWithInit<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
         purchase_with_init::purchase_app_with_init(ctx, app_id)
     }
 
@@ -365,6 +833,34 @@ pub mod defai_app_factory {
         update_app::update_app_metadata(ctx, app_id, new_metadata_uri, new_price)
     }
 
+    // Resize a registration created under an older layout
+    pub fn migrate_app_registration(ctx: Context<MigrateAppRegistration>, app_id: u64) -> Result<()> {
+        update_app::migrate_app_registration(ctx, app_id)
+    }
+
+    // Resize the factory account created under an older layout
+    pub fn migrate_app_factory(ctx: Context<MigrateAppFactory>) -> Result<()> {
+        update_app::migrate_app_factory(ctx)
+    }
+
+    // Resize an access record created under an older layout
+    pub fn migrate_user_app_access(
+        ctx: Context<MigrateUserAppAccess>,
+        user: Pubkey,
+        app_id: u64,
+    ) -> Result<()> {
+        update_app::migrate_user_app_access(ctx, user, app_id)
+    }
+
+    // Resize a subscription created under an older layout
+    pub fn migrate_subscription(
+        ctx: Context<MigrateSubscription>,
+        user: Pubkey,
+        app_id: u64,
+    ) -> Result<()> {
+        update_app::migrate_subscription(ctx, user, app_id)
+    }
+
     // Refund purchase
     pub fn refund_purchase(
         ctx: Context<RefundPurchase>,
@@ -374,6 +870,11 @@ pub mod defai_app_factory {
         refund::refund_purchase(ctx, app_id, reason)
     }
 
+    // Refund the unused part of a subscription pro rata
+    pub fn refund_subscription(ctx: Context<RefundSubscription>, app_id: u64) -> Result<()> {
+        refund::refund_subscription(ctx, app_id)
+    }
+
     // Submit review
     pub fn submit_review(
         ctx: Context<SubmitReview>,
@@ -387,10 +888,41 @@ pub mod defai_app_factory {
     // Update review
     pub fn update_review(
         ctx: Context<UpdateReview>,
+        app_id: u64,
         new_rating: u8,
         new_comment_cid: String,
     ) -> Result<()> {
-        reviews::update_review(ctx, new_rating, new_comment_cid)
+        reviews::update_review(ctx, app_id, new_rating, new_comment_cid)
+    }
+
+    // Delete review
+    pub fn delete_review(ctx: Context<DeleteReview>, app_id: u64) -> Result<()> {
+        reviews::delete_review(ctx, app_id)
+    }
+
+    // Hide or restore a review (platform authority)
+    pub fn moderate_review(
+        ctx: Context<ModerateReview>,
+        app_id: u64,
+        reviewer: Pubkey,
+        hide: bool,
+    ) -> Result<()> {
+        reviews::moderate_review(ctx, app_id, reviewer, hide)
+    }
+
+    // Appeal moderation of a hidden review
+    pub fn appeal_review(ctx: Context<AppealReview>, app_id: u64) -> Result<()> {
+        reviews::appeal_review(ctx, app_id)
+    }
+
+    // Close a review whose purchase was refunded, expired or replaced (permissionless)
+    pub fn purge_review(ctx: Context<PurgeReview>, app_id: u64) -> Result<()> {
+        reviews::purge_review(ctx, app_id)
+    }
+
+    // Set how long after purchase reviews may be submitted (authority only)
+    pub fn set_review_window(ctx: Context<SetReviewWindow>, days: u16) -> Result<()> {
+        reviews::set_review_window(ctx, days)
     }
 
     // Transfer authority (2-step process)
@@ -410,122 +942,1117 @@ pub mod defai_app_factory {
     pub fn cancel_authority_transfer(ctx: Context<CancelAuthorityTransfer>) -> Result<()> {
         authority::cancel_authority_transfer(ctx)
     }
-}
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+    // Create a recurring subscription plan for an app
+    pub fn create_subscription_plan(
+        ctx: Context<CreateSubscriptionPlan>,
+        app_id: u64,
+        period_seconds: i64,
+        price: u64,
+    ) -> Result<()> {
+        subscription::create_subscription_plan(ctx, app_id, period_seconds, price)
+    }
 
-#[account]
-pub struct AppFactory {
-    pub authority: Pubkey,              // Platform authority
-    pub defai_mint: Pubkey,             // DEFAI token mint
-    pub treasury: Pubkey,               // Platform treasury (receives platform fee)
-    pub master_collection: Pubkey,      // "DEFAI APPs" collection mint
-    pub platform_fee_bps: u16,         // Platform fee in basis points (2000 = 20%)
-    pub total_apps: u64,                // Total number of registered apps
-    pub bump: u8,                       // PDA bump seed
-    pub pending_authority: Option<Pubkey>, // For 2-step authority transfer
-}
+    // Update subscription plan price or availability
+    pub fn update_subscription_plan(
+        ctx: Context<UpdateSubscriptionPlan>,
+        app_id: u64,
+        new_price: Option<u64>,
+        is_active: Option<bool>,
+    ) -> Result<()> {
+        subscription::update_subscription_plan(ctx, app_id, new_price, is_active)
+    }
 
-impl AppFactory {
-    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 2 + 8 + 1 + (1 + 32);
-}
+    // Subscribe to an app for one period
+    pub fn subscribe<'info>(
+        ctx: Context<'_, '_, '_, 'info, Subscribe<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        subscription::subscribe(ctx, app_id)
+    }
 
-#[account]
-pub struct AppRegistration {
-    pub app_id: u64,                    // Unique app identifier
-    pub creator: Pubkey,                // App creator (receives creator fee)
-    pub sft_mint: Pubkey,               // SFT mint address for this app
-    pub price: u64,                     // Price in DEFAI tokens (with decimals)
-    pub max_supply: u64,                // Maximum number of SFTs that can be minted
-    pub current_supply: u64,            // Current number of SFTs minted
-    pub is_active: bool,                // Whether app purchases are enabled
-    pub metadata_uri: String,           // IPFS URI for app metadata
-    pub created_at: i64,                // Creation timestamp
-    pub bump: u8,                       // PDA bump seed
-}
+    // Pay for additional subscription periods
+    pub fn renew_subscription<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewSubscription<'info>>,
+        app_id: u64,
+        periods: u16,
+    ) -> Result<()> {
+        subscription::renew_subscription(ctx, app_id, periods)
+    }
 
-impl AppRegistration {
-    pub const LEN: usize = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 1 + (4 + 100) + 8 + 1; // ~200 bytes
-}
+    // Cancel a subscription (access remains until paid-through)
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>, app_id: u64) -> Result<()> {
+        subscription::cancel_subscription(ctx, app_id)
+    }
 
-#[account]
-pub struct UserAppAccess {
-    pub user: Pubkey,                   // User wallet
-    pub app_id: u64,                    // App they purchased
-    pub sft_token_account: Pubkey,      // Their SFT token account
-    pub purchased_at: i64,              // Purchase timestamp
-    pub purchase_price: u64,            // Price at purchase time
-    pub bump: u8,                       // PDA bump seed
-}
+    // View: whether a user's subscription currently grants access
+    pub fn is_access_valid(
+        ctx: Context<CheckSubscription>,
+        _user: Pubkey,
+        _app_id: u64,
+    ) -> Result<bool> {
+        subscription::is_access_valid(ctx)
+    }
 
-impl UserAppAccess {
-    pub const LEN: usize = 8 + 32 + 8 + 32 + 8 + 8 + 1;
-}
+    // Configure rental price and duration for an app
+    pub fn set_rental_terms(
+        ctx: Context<SetRentalTerms>,
+        app_id: u64,
+        rental_price: u64,
+        rental_days: u16,
+    ) -> Result<()> {
+        rental::set_rental_terms(ctx, app_id, rental_price, rental_days)
+    }
 
-// ============================================================================
-// Context Structures
-// ============================================================================
+    // Rent time-limited app access
+    pub fn rent_app_access<'info>(
+        ctx: Context<'_, '_, '_, 'info, RentAppAccess<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        rental::rent_app_access(ctx, app_id)
+    }
 
-#[derive(Accounts)]
-pub struct InitializeAppFactory<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = AppFactory::LEN,
-        seeds = [b"app_factory"],
-        bump
-    )]
-    pub app_factory: Account<'info, AppFactory>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    #[account(
-        constraint = defai_mint.mint_authority.is_some() 
-            @ AppFactoryError::InvalidDefaiMint,
-        constraint = defai_mint.decimals == 6 
-            @ AppFactoryError::InvalidMintDecimals
-    )]
-    pub defai_mint: Account<'info, Mint>,
-    
-    /// CHECK: Platform treasury wallet - verified to be a system account
-    #[account(
-        constraint = treasury.owner == &System::id() 
-            @ AppFactoryError::InvalidTreasury
-    )]
-    pub treasury: SystemAccount<'info>,
-    
-    #[account(
-        constraint = master_collection.supply > 0
-            @ AppFactoryError::InvalidCollection
-    )]
-    pub master_collection: Account<'info, Mint>,
-    
-    pub system_program: Program<'info, System>,
-}
+    // Permissionless: reclaim an expired rental
+    pub fn expire_access(ctx: Context<ExpireAccess>, app_id: u64) -> Result<()> {
+        rental::expire_access(ctx, app_id)
+    }
 
-#[derive(Accounts)]
-#[instruction(price: u64, max_supply: u64, metadata_uri: String)]
-pub struct RegisterApp<'info> {
-    #[account(
-        mut,
-        seeds = [b"app_factory"],
-        bump = app_factory.bump
-    )]
-    pub app_factory: Account<'info, AppFactory>,
-    
-    #[account(
-        init,
-        payer = creator,
-        space = AppRegistration::LEN,
-        seeds = [b"app_registration".as_ref(), &app_factory.total_apps.to_le_bytes()],
+    // Permissionless: close an access record whose SFT is gone, refunding rent to its payer
+    pub fn close_access(ctx: Context<CloseAccess>, app_id: u64) -> Result<()> {
+        cleanup::close_access(ctx, app_id)
+    }
+
+    // Publish a new app release with changelog
+    pub fn publish_app_version(
+        ctx: Context<PublishAppVersion>,
+        app_id: u64,
+        metadata_uri: String,
+        changelog_cid: String,
+        min_required_version: u32,
+    ) -> Result<()> {
+        versioning::publish_app_version(ctx, app_id, metadata_uri, changelog_cid, min_required_version)
+    }
+
+    // Split the creator share of purchases across team wallets
+    pub fn set_revenue_split(
+        ctx: Context<SetRevenueSplit>,
+        app_id: u64,
+        recipients: Vec<SplitRecipient>,
+    ) -> Result<()> {
+        revenue_split::set_revenue_split(ctx, app_id, recipients)
+    }
+
+    // Remove the revenue split; the creator receives the full share again
+    pub fn remove_revenue_split(ctx: Context<RemoveRevenueSplit>, app_id: u64) -> Result<()> {
+        revenue_split::remove_revenue_split(ctx, app_id)
+    }
+
+    // Appoint the platform dispute arbiter
+    pub fn set_dispute_arbiter(ctx: Context<SetDisputeArbiter>, arbiter: Pubkey) -> Result<()> {
+        dispute::set_dispute_arbiter(ctx, arbiter)
+    }
+
+    // Buyer disputes a purchase; their SFT is frozen pending a ruling
+    pub fn open_dispute(ctx: Context<OpenDispute>, app_id: u64, reason_cid: String) -> Result<()> {
+        dispute::open_dispute(ctx, app_id, reason_cid)
+    }
+
+    // Creator escrows the disputed proceeds
+    pub fn escrow_dispute_funds(
+        ctx: Context<EscrowDisputeFunds>,
+        app_id: u64,
+        user: Pubkey,
+    ) -> Result<()> {
+        dispute::escrow_dispute_funds(ctx, app_id, user)
+    }
+
+    // Arbiter or authority rules refund vs release
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveDispute<'info>>,
+        app_id: u64,
+        user: Pubkey,
+        refund: bool,
+    ) -> Result<()> {
+        dispute::resolve_dispute(ctx, app_id, user, refund)
+    }
+
+    // Set the USDC mint accepted for alternate-currency purchases
+    pub fn set_usdc_mint(ctx: Context<SetUsdcMint>) -> Result<()> {
+        currency::set_usdc_mint(ctx)
+    }
+
+    // Set SOL / USDC prices for an app (0 disables a currency)
+    pub fn set_app_currency_prices(
+        ctx: Context<SetAppCurrencyPrices>,
+        app_id: u64,
+        sol_price: u64,
+        usdc_price: u64,
+    ) -> Result<()> {
+        currency::set_app_currency_prices(ctx, app_id, sol_price, usdc_price)
+    }
+
+    // Purchase app access paying in SOL
+    pub fn purchase_app_with_sol(ctx: Context<PurchaseAppWithSol>, app_id: u64) -> Result<()> {
+        currency::purchase_app_with_sol(ctx, app_id)
+    }
+
+    // Purchase app access paying in USDC
+    pub fn purchase_app_with_usdc(ctx: Context<PurchaseAppWithUsdc>, app_id: u64) -> Result<()> {
+        currency::purchase_app_with_usdc(ctx, app_id)
+    }
+
+    // Set the merkle root of an app's coupon codes
+    pub fn set_coupon_root(
+        ctx: Context<SetCouponRoot>,
+        app_id: u64,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        coupon::set_coupon_root(ctx, app_id, merkle_root)
+    }
+
+    // Purchase app access with a discount code
+    pub fn purchase_with_coupon<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseWithCoupon<'info>>,
+        app_id: u64,
+        code_hash: [u8; 32],
+        code: String,
+        discount_bps: u16,
+        max_uses: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        coupon::purchase_with_coupon(ctx, app_id, code_hash, code, discount_bps, max_uses, proof)
+    }
+
+    // Schedule a time-boxed sale for an app
+    pub fn schedule_sale(
+        ctx: Context<ScheduleSale>,
+        app_id: u64,
+        start_time: i64,
+        end_time: i64,
+        sale_price: u64,
+        discount_bps: u16,
+    ) -> Result<()> {
+        sale::schedule_sale(ctx, app_id, start_time, end_time, sale_price, discount_bps)
+    }
+
+    // Cancel a scheduled or running sale
+    pub fn cancel_sale(ctx: Context<CancelSale>, app_id: u64) -> Result<()> {
+        sale::cancel_sale(ctx, app_id)
+    }
+
+    // Create a discounted bundle of apps
+    pub fn create_bundle(
+        ctx: Context<CreateBundle>,
+        bundle_id: u64,
+        app_ids: Vec<u64>,
+        price: u64,
+    ) -> Result<()> {
+        bundle::create_bundle(ctx, bundle_id, app_ids, price)
+    }
+
+    // Enable or disable bundle sales
+    pub fn set_bundle_status(
+        ctx: Context<SetBundleStatus>,
+        bundle_id: u64,
+        is_active: bool,
+    ) -> Result<()> {
+        bundle::set_bundle_status(ctx, bundle_id, is_active)
+    }
+
+    // Pay for a bundle; apps are claimed individually afterwards
+    pub fn purchase_bundle<'info>(ctx: Context<'_, '_, '_, 'info, PurchaseBundle<'info>>) -> Result<()> {
+        bundle::purchase_bundle(ctx)
+    }
+
+    // Mint the SFT for one app of a purchased bundle
+    pub fn claim_bundle_app(ctx: Context<ClaimBundleApp>, app_id: u64) -> Result<()> {
+        bundle::claim_bundle_app(ctx, app_id)
+    }
+
+    // Pay for app access on behalf of another wallet
+    pub fn purchase_app_as_gift<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppAsGift<'info>>,
+        app_id: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        gift::purchase_app_as_gift(ctx, app_id, recipient)
+    }
+
+    // Add a priced edition (feature tier) with its own SFT mint
+    pub fn add_app_edition(
+        ctx: Context<AddAppEdition>,
+        app_id: u64,
+        name: String,
+        price: u64,
+        max_supply: u64,
+    ) -> Result<()> {
+        edition::add_app_edition(ctx, app_id, name, price, max_supply)
+    }
+
+    // Update edition price or availability
+    pub fn update_app_edition(
+        ctx: Context<UpdateAppEdition>,
+        app_id: u64,
+        edition_id: u8,
+        new_price: Option<u64>,
+        is_active: Option<bool>,
+    ) -> Result<()> {
+        edition::update_app_edition(ctx, app_id, edition_id, new_price, is_active)
+    }
+
+    // Purchase a specific edition of an app
+    pub fn purchase_app_edition<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppEdition<'info>>,
+        app_id: u64,
+        edition_id: u8,
+    ) -> Result<()> {
+        edition::purchase_app_edition(ctx, app_id, edition_id)
+    }
+
+    // Delist an app and open a refund window (creator or authority)
+    pub fn delist_app(ctx: Context<DelistApp>, app_id: u64, refund_window: i64) -> Result<()> {
+        delisting::delist_app(ctx, app_id, refund_window)
+    }
+
+    // Deposit DEFAI to cover delisting refunds
+    pub fn fund_delisting_refunds(
+        ctx: Context<FundDelistingRefunds>,
+        app_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        delisting::fund_delisting_refunds(ctx, app_id, amount)
+    }
+
+    // Holder burns their SFT for a refund from escrow
+    pub fn claim_delisting_refund(ctx: Context<ClaimDelistingRefund>, app_id: u64) -> Result<()> {
+        delisting::claim_delisting_refund(ctx, app_id)
+    }
+
+    // Release leftover escrow to the creator after the window
+    pub fn release_delisting_funds(ctx: Context<ReleaseDelistingFunds>, app_id: u64) -> Result<()> {
+        delisting::release_delisting_funds(ctx, app_id)
+    }
+
+    // Grant or revoke a creator's verification badge (authority only)
+    pub fn set_creator_verification(
+        ctx: Context<SetCreatorVerification>,
+        creator: Pubkey,
+        verified: bool,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        verification::set_creator_verification(ctx, creator, verified, metadata_hash)
+    }
+
+    // Record or clear a creator's KYC hash (authority only)
+    pub fn set_creator_kyc(
+        ctx: Context<SetCreatorVerification>,
+        creator: Pubkey,
+        kyc_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        verification::set_creator_kyc(ctx, creator, kyc_hash)
+    }
+
+    // Add a marketplace category (authority only)
+    pub fn add_category(ctx: Context<AddCategory>, name: String) -> Result<()> {
+        category::add_category(ctx, name)
+    }
+
+    // Retire or restore a category (authority only)
+    pub fn set_category_status(
+        ctx: Context<SetCategoryStatus>,
+        category_id: u16,
+        is_active: bool,
+    ) -> Result<()> {
+        category::set_category_status(ctx, category_id, is_active)
+    }
+
+    // Replace an app's category ids (creator only)
+    pub fn set_app_categories(
+        ctx: Context<SetAppCategories>,
+        app_id: u64,
+        categories: Vec<u16>,
+    ) -> Result<()> {
+        category::set_app_categories(ctx, app_id, categories)
+    }
+
+    // Feature an app at a list position (authority only)
+    pub fn add_featured_app(
+        ctx: Context<AddFeaturedApp>,
+        app_id: u64,
+        position: u8,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        featured::add_featured_app(ctx, app_id, position, start_time, end_time)
+    }
+
+    // Remove an app from the featured list (authority only)
+    pub fn remove_featured_app(ctx: Context<UpdateFeaturedList>, app_id: u64) -> Result<()> {
+        featured::remove_featured_app(ctx, app_id)
+    }
+
+    // Prune expired slots and rotate the featured order (authority only)
+    pub fn rotate_featured_apps(ctx: Context<UpdateFeaturedList>, shift: u8) -> Result<()> {
+        featured::rotate_featured_apps(ctx, shift)
+    }
+
+    // Register as an affiliate to earn referral commissions
+    pub fn register_affiliate(ctx: Context<RegisterAffiliate>) -> Result<()> {
+        affiliate::register_affiliate(ctx)
+    }
+
+    // Set the affiliate commission for an app (creator only)
+    pub fn set_affiliate_bps(
+        ctx: Context<SetAffiliateBps>,
+        app_id: u64,
+        affiliate_bps: u16,
+    ) -> Result<()> {
+        affiliate::set_affiliate_bps(ctx, app_id, affiliate_bps)
+    }
+
+    // Require authority approval for app ownership transfers
+    pub fn set_transfer_approval_required(
+        ctx: Context<SetTransferApprovalRequired>,
+        required: bool,
+    ) -> Result<()> {
+        app_transfer::set_transfer_approval_required(ctx, required)
+    }
+
+    // Propose handing an app to a new creator (step 1)
+    pub fn propose_app_transfer(
+        ctx: Context<ProposeAppTransfer>,
+        app_id: u64,
+        new_creator: Pubkey,
+    ) -> Result<()> {
+        app_transfer::propose_app_transfer(ctx, app_id, new_creator)
+    }
+
+    // Platform approval for a pending app transfer
+    pub fn approve_app_transfer(ctx: Context<ApproveAppTransfer>, app_id: u64) -> Result<()> {
+        app_transfer::approve_app_transfer(ctx, app_id)
+    }
+
+    // New creator accepts the app (step 2)
+    pub fn accept_app_transfer(ctx: Context<AcceptAppTransfer>, app_id: u64) -> Result<()> {
+        app_transfer::accept_app_transfer(ctx, app_id)
+    }
+
+    // Withdraw a pending app transfer
+    pub fn cancel_app_transfer(ctx: Context<CancelAppTransfer>, app_id: u64) -> Result<()> {
+        app_transfer::cancel_app_transfer(ctx, app_id)
+    }
+
+    // Attach a whitelist presale window to an app (creator only)
+    pub fn set_app_presale(
+        ctx: Context<SetAppPresale>,
+        app_id: u64,
+        merkle_root: [u8; 32],
+        start_time: i64,
+        end_time: i64,
+        presale_price: u64,
+    ) -> Result<()> {
+        presale::set_app_presale(ctx, app_id, merkle_root, start_time, end_time, presale_price)
+    }
+
+    // Remove an app's presale and open sales publicly
+    pub fn cancel_app_presale(ctx: Context<CancelAppPresale>, app_id: u64) -> Result<()> {
+        presale::cancel_app_presale(ctx, app_id)
+    }
+
+    // Whitelisted purchase during the presale window
+    pub fn purchase_presale<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchasePresale<'info>>,
+        app_id: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        presale::purchase_presale(ctx, app_id, proof)
+    }
+
+    // Free promotional access minted by the creator
+    pub fn creator_grant_access(
+        ctx: Context<CreatorGrantAccess>,
+        app_id: u64,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        grant::creator_grant_access(ctx, app_id, recipient)
+    }
+
+    // Configure metered credit pricing and the service signer (creator only)
+    pub fn set_credit_config(
+        ctx: Context<SetCreditConfig>,
+        app_id: u64,
+        price_per_credit: u64,
+        service_signer: Pubkey,
+        is_active: bool,
+    ) -> Result<()> {
+        credits::set_credit_config(ctx, app_id, price_per_credit, service_signer, is_active)
+    }
+
+    // Buy usage credits for an app
+    pub fn purchase_credits<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseCredits<'info>>,
+        app_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        credits::purchase_credits(ctx, app_id, amount)
+    }
+
+    // Debit a user's credits (app service signer only)
+    pub fn consume_credits(
+        ctx: Context<ConsumeCredits>,
+        app_id: u64,
+        user: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        credits::consume_credits(ctx, app_id, user, amount)
+    }
+
+    // Create an app's SFT metadata and verify it into the master collection
+    pub fn create_app_sft_metadata(
+        ctx: Context<CreateAppSftMetadata>,
+        app_id: u64,
+        name: String,
+        symbol: String,
+    ) -> Result<()> {
+        collection::create_app_sft_metadata(ctx, app_id, name, symbol)
+    }
+
+    // Point the app's SFT metadata at the registration's current metadata_uri (creator only)
+    pub fn update_sft_metadata(ctx: Context<UpdateSftMetadata>, app_id: u64) -> Result<()> {
+        collection::update_sft_metadata(ctx, app_id)
+    }
+
+    // Make an app's access transferable or soulbound (creator only)
+    pub fn set_app_transferable(
+        ctx: Context<SetAppTransferable>,
+        app_id: u64,
+        transferable: bool,
+    ) -> Result<()> {
+        transferability::set_app_transferable(ctx, app_id, transferable)
+    }
+
+    // Thaw a holder's SFT after the app became transferable (permissionless)
+    pub fn thaw_app_sft(ctx: Context<ThawAppSft>, app_id: u64, owner: Pubkey) -> Result<()> {
+        transferability::thaw_app_sft(ctx, app_id, owner)
+    }
+
+    // Pin or clear the platform fee for one app (authority only)
+    pub fn set_app_fee_override(
+        ctx: Context<SetAppFeeOverride>,
+        app_id: u64,
+        fee_bps: Option<u16>,
+    ) -> Result<()> {
+        fees::set_app_fee_override(ctx, app_id, fee_bps)
+    }
+
+    // Set the platform fee for a category (authority only)
+    pub fn set_category_fee_override(
+        ctx: Context<SetCategoryFeeOverride>,
+        category_id: u16,
+        fee_bps: u16,
+    ) -> Result<()> {
+        fees::set_category_fee_override(ctx, category_id, fee_bps)
+    }
+
+    // Remove a category fee override (authority only)
+    pub fn clear_category_fee_override(
+        ctx: Context<ClearCategoryFeeOverride>,
+        category_id: u16,
+    ) -> Result<()> {
+        fees::clear_category_fee_override(ctx, category_id)
+    }
+
+    // Cache an app's category fee override (permissionless crank)
+    pub fn sync_app_category_fee(ctx: Context<SyncAppCategoryFee>, app_id: u64) -> Result<()> {
+        fees::sync_app_category_fee(ctx, app_id)
+    }
+
+    // Opt an app into vesting creator payouts (creator only, irreversible)
+    pub fn enable_payout_vesting(
+        ctx: Context<EnablePayoutVesting>,
+        app_id: u64,
+        vesting_days: u16,
+    ) -> Result<()> {
+        vesting::enable_payout_vesting(ctx, app_id, vesting_days)
+    }
+
+    // Freeze or unfreeze an app's vested payouts (authority only)
+    pub fn set_payout_frozen(ctx: Context<SetPayoutFrozen>, app_id: u64, frozen: bool) -> Result<()> {
+        vesting::set_payout_frozen(ctx, app_id, frozen)
+    }
+
+    // Claim vested creator proceeds
+    pub fn claim_creator_proceeds(ctx: Context<ClaimCreatorProceeds>, app_id: u64) -> Result<()> {
+        vesting::claim_creator_proceeds(ctx, app_id)
+    }
+
+    // View: typed access check (purchase record, SFT balance, rental expiry, delegation)
+    pub fn verify_access(ctx: Context<VerifyAccess>, user: Pubkey, app_id: u64) -> Result<AccessCheck> {
+        access::verify_access(ctx, user, app_id)
+    }
+
+    // Purchase up to 4 apps at list price in one transaction
+    pub fn purchase_multiple<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseMultiple<'info>>,
+        app_ids: Vec<u64>,
+    ) -> Result<()> {
+        batch::purchase_multiple(ctx, app_ids)
+    }
+
+    // Creator proposes raising an app's max supply
+    pub fn propose_supply_increase(
+        ctx: Context<ProposeSupplyIncrease>,
+        app_id: u64,
+        new_max_supply: u64,
+    ) -> Result<()> {
+        supply::propose_supply_increase(ctx, app_id, new_max_supply)
+    }
+
+    // Platform co-signs a pending supply increase (authority only)
+    pub fn approve_supply_increase(ctx: Context<ApproveSupplyIncrease>, app_id: u64) -> Result<()> {
+        supply::approve_supply_increase(ctx, app_id)
+    }
+
+    // Veto a pending supply increase (authority only)
+    pub fn reject_supply_increase(ctx: Context<RejectSupplyIncrease>, app_id: u64) -> Result<()> {
+        supply::reject_supply_increase(ctx, app_id)
+    }
+
+    // Apply an approved or timelocked supply increase (creator only)
+    pub fn execute_supply_increase(ctx: Context<ExecuteSupplyIncrease>, app_id: u64) -> Result<()> {
+        supply::execute_supply_increase(ctx, app_id)
+    }
+
+    // Withdraw a pending supply increase (creator only)
+    pub fn cancel_supply_increase(ctx: Context<CancelSupplyIncrease>, app_id: u64) -> Result<()> {
+        supply::cancel_supply_increase(ctx, app_id)
+    }
+
+    // Map a retired app to its successor (creator of both apps)
+    pub fn set_app_migration(
+        ctx: Context<SetAppMigration>,
+        old_app_id: u64,
+        new_app_id: u64,
+    ) -> Result<()> {
+        migration::set_app_migration(ctx, old_app_id, new_app_id)
+    }
+
+    // Stop migrations out of an app (creator only)
+    pub fn remove_app_migration(ctx: Context<RemoveAppMigration>, old_app_id: u64) -> Result<()> {
+        migration::remove_app_migration(ctx, old_app_id)
+    }
+
+    // Swap an old app's SFT for its successor's, keeping the purchase date
+    pub fn migrate_access(
+        ctx: Context<MigrateAccess>,
+        old_app_id: u64,
+        new_app_id: u64,
+    ) -> Result<()> {
+        migration::migrate_access(ctx, old_app_id, new_app_id)
+    }
+
+    // Apply a queued treasury change or fee increase after the timelock (authority only)
+    pub fn apply_platform_settings(ctx: Context<PendingPlatformSettings>) -> Result<()> {
+        settings::apply_platform_settings(ctx)
+    }
+
+    // Drop a queued platform settings change (authority only)
+    pub fn cancel_platform_settings_change(ctx: Context<PendingPlatformSettings>) -> Result<()> {
+        settings::cancel_platform_settings_change(ctx)
+    }
+
+    // Set the notice period for creator price increases (authority only)
+    pub fn set_price_notice_period(ctx: Context<SetPriceNoticePeriod>, hours: u16) -> Result<()> {
+        settings::set_price_notice_period(ctx, hours)
+    }
+
+    // Register a vetted donation wallet (authority only)
+    pub fn register_charity(ctx: Context<RegisterCharity>, name: String) -> Result<()> {
+        charity::register_charity(ctx, name)
+    }
+
+    // Retire or restore a charity (authority only)
+    pub fn set_charity_status(ctx: Context<SetCharityStatus>, is_active: bool) -> Result<()> {
+        charity::set_charity_status(ctx, is_active)
+    }
+
+    // Donate a share of each sale to a registered charity (creator only)
+    pub fn set_app_charity(
+        ctx: Context<SetAppCharity>,
+        app_id: u64,
+        charity_bps: u16,
+    ) -> Result<()> {
+        charity::set_app_charity(ctx, app_id, charity_bps)
+    }
+
+    // Let a secondary wallet use a purchased app without moving the SFT
+    pub fn delegate_access(
+        ctx: Context<DelegateAccess>,
+        app_id: u64,
+        delegate: Pubkey,
+        expires_at: i64,
+    ) -> Result<()> {
+        delegation::delegate_access(ctx, app_id, delegate, expires_at)
+    }
+
+    // End an access delegation (primary wallet only)
+    pub fn revoke_access_delegation(ctx: Context<RevokeAccessDelegation>, app_id: u64) -> Result<()> {
+        delegation::revoke_access_delegation(ctx, app_id)
+    }
+
+    // Purchase paid from a defai_estate vault (CPI from the estate program)
+    pub fn purchase_app_from_estate<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppFromEstate<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        estate_purchase::purchase_app_from_estate(ctx, app_id)
+    }
+
+    // Set the purchase discount per defai_swap bonus NFT tier (authority only)
+    pub fn set_holder_discounts(
+        ctx: Context<SetHolderDiscounts>,
+        discount_bps: [u16; BONUS_NFT_TIERS],
+    ) -> Result<()> {
+        holder_discount::set_holder_discounts(ctx, discount_bps)
+    }
+
+    // Let a payer wallet buy apps for an organization (signed by the organization)
+    pub fn authorize_purchaser(
+        ctx: Context<AuthorizePurchaser>,
+        payer: Pubkey,
+        max_price: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        organization::authorize_purchaser(ctx, payer, max_price, expires_at)
+    }
+
+    // Withdraw a payer's authorization (signed by the organization)
+    pub fn revoke_purchaser(ctx: Context<RevokePurchaser>, payer: Pubkey) -> Result<()> {
+        organization::revoke_purchaser(ctx, payer)
+    }
+
+    // Authorized payer buys access owned by a multisig or DAO
+    pub fn purchase_app_for_organization<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppForOrganization<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        organization::purchase_app_for_organization(ctx, app_id)
+    }
+
+    // Set the share of each DEFAI purchase that is burned (authority only)
+    pub fn set_burn_bps(ctx: Context<SetBurnBps>, burn_bps: u16) -> Result<()> {
+        burn::set_burn_bps(ctx, burn_bps)
+    }
+
+    // Set the DEFAI registration fee and refundable creator stake (authority only)
+    pub fn set_registration_requirements(
+        ctx: Context<SetRegistrationRequirements>,
+        registration_fee: u64,
+        registration_stake: u64,
+    ) -> Result<()> {
+        listing_stake::set_registration_requirements(ctx, registration_fee, registration_stake)
+    }
+
+    // Creator reclaims their listing stake once the lock period has passed
+    pub fn release_creator_stake(ctx: Context<ReleaseCreatorStake>, app_id: u64) -> Result<()> {
+        listing_stake::release_creator_stake(ctx, app_id)
+    }
+
+    // Slash a malicious listing's stake to the treasury and flag the app (authority only)
+    pub fn slash_creator_stake(
+        ctx: Context<SlashCreatorStake>,
+        app_id: u64,
+        reason_cid: String,
+    ) -> Result<()> {
+        listing_stake::slash_creator_stake(ctx, app_id, reason_cid)
+    }
+
+    // Configure a free or cheap trial and its conversion discount (creator only)
+    pub fn set_trial_terms(
+        ctx: Context<SetTrialTerms>,
+        app_id: u64,
+        trial_price: u64,
+        trial_days: u16,
+        conversion_discount_bps: u16,
+    ) -> Result<()> {
+        trial::set_trial_terms(ctx, app_id, trial_price, trial_days, conversion_discount_bps)
+    }
+
+    // Start the wallet's one trial of an app
+    pub fn start_trial<'info>(
+        ctx: Context<'_, '_, '_, 'info, StartTrial<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        trial::start_trial(ctx, app_id)
+    }
+
+    // Burn the trial SFT and buy permanent access at the conversion discount
+    pub fn convert_trial<'info>(
+        ctx: Context<'_, '_, '_, 'info, ConvertTrial<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        trial::convert_trial(ctx, app_id)
+    }
+
+    // Set how many open reports suspend an app's sales (authority only, 0 = never)
+    pub fn set_report_threshold(ctx: Context<SetReportThreshold>, report_threshold: u16) -> Result<()> {
+        report::set_report_threshold(ctx, report_threshold)
+    }
+
+    // Purchaser reports a malicious app with an evidence CID
+    pub fn report_app(ctx: Context<ReportApp>, app_id: u64, reason_cid: String) -> Result<()> {
+        report::report_app(ctx, app_id, reason_cid)
+    }
+
+    // Uphold (flag the app) or dismiss a report (authority only)
+    pub fn review_report(
+        ctx: Context<ReviewReport>,
+        app_id: u64,
+        reporter: Pubkey,
+        uphold: bool,
+    ) -> Result<()> {
+        report::review_report(ctx, app_id, reporter, uphold)
+    }
+
+    // Create the factory-wide statistics account (authority only)
+    pub fn initialize_factory_stats(ctx: Context<InitializeFactoryStats>) -> Result<()> {
+        stats::initialize_factory_stats(ctx)
+    }
+
+    // Create the ring-buffer audit log of authority handovers
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        audit::initialize_audit_log(ctx)
+    }
+
+    // Publish or rotate the on-chain security contact (authority only)
+    pub fn update_security_info(
+        ctx: Context<UpdateSecurityInfo>,
+        contacts: String,
+        policy: String,
+        auditors: String,
+        expiry: i64,
+    ) -> Result<()> {
+        security_info::update_security_info(ctx, contacts, policy, auditors, expiry)
+    }
+
+    // Keeper Crank Instructions
+
+    // Create the vault that funds keeper rewards (anyone; top up by transfer)
+    pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+        keeper::initialize_keeper_vault(ctx)
+    }
+
+    // Permissionless: expire_access, rewarded from the keeper vault
+    pub fn crank_expire_access<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankExpireAccess<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        keeper::crank_expire_access(ctx, app_id)
+    }
+
+    // Permissionless: return an unlocked listing stake to its creator
+    pub fn crank_release_creator_stake(ctx: Context<CrankReleaseCreatorStake>, app_id: u64) -> Result<()> {
+        keeper::crank_release_creator_stake(ctx, app_id)
+    }
+
+    // Permissionless: settle a creator vesting window that has ended
+    pub fn crank_settle_creator_vesting(ctx: Context<CrankSettleCreatorVesting>, app_id: u64) -> Result<()> {
+        keeper::crank_settle_creator_vesting(ctx, app_id)
+    }
+
+    // Treasury Ledger Instructions
+
+    // Create the ledger that books factory income (authority only)
+    pub fn initialize_treasury_ledger(ctx: Context<InitializeTreasuryLedger>) -> Result<()> {
+        treasury::initialize_treasury_ledger(ctx)
+    }
+
+    // Queue a new withdrawal cap and period behind the admin timelock (authority only)
+    pub fn propose_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>, policy: WithdrawalPolicy) -> Result<()> {
+        treasury::propose_withdrawal_policy(ctx, policy)
+    }
+
+    // Drop the queued withdrawal policy (authority only)
+    pub fn cancel_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+        treasury::cancel_withdrawal_policy(ctx)
+    }
+
+    // Apply the queued withdrawal policy once the timelock has elapsed (authority only)
+    pub fn execute_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+        treasury::execute_withdrawal_policy(ctx)
+    }
+
+    // Permissionless: book platform fees counted in FactoryStats since the last sync
+    pub fn sync_platform_fees(ctx: Context<SyncPlatformFees>) -> Result<()> {
+        treasury::sync_platform_fees(ctx)
+    }
+
+    // Withdraw from the ledger-owned treasury vault within the policy cap (authority only)
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        treasury::withdraw_treasury(ctx, amount)
+    }
+
+    // Program Registry Instructions
+
+    // Record the sibling program ids of this deployment; None takes the main deployment's (authority only)
+    pub fn initialize_program_registry(
+        ctx: Context<InitializeProgramRegistry>,
+        links: Option<ProgramLinks>,
+    ) -> Result<()> {
+        program_registry::initialize_program_registry(ctx, links)
+    }
+
+    // Queue new program links behind the admin timelock (authority only)
+    pub fn propose_program_links_change(ctx: Context<UpdateProgramRegistry>, links: ProgramLinks) -> Result<()> {
+        program_registry::propose_program_links_change(ctx, links)
+    }
+
+    // Drop the queued program links (authority only)
+    pub fn cancel_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::cancel_program_links_change(ctx)
+    }
+
+    // Apply the queued program links once the timelock has elapsed (authority only)
+    pub fn execute_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::execute_program_links_change(ctx)
+    }
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+pub struct AppFactory {
+    pub authority: Pubkey,              // Platform authority
+    pub defai_mint: Pubkey,             // DEFAI token mint
+    pub treasury: Pubkey,               // Platform treasury (receives platform fee)
+    pub master_collection: Pubkey,      // "DEFAI APPs" collection mint
+    pub platform_fee_bps: u16,         // Platform fee in basis points (2000 = 20%)
+    pub total_apps: u64,                // Total number of registered apps
+    pub bump: u8,                       // PDA bump seed
+    pub pending_authority: Option<Pubkey>, // For 2-step authority transfer
+    pub paused: bool,                   // Emergency stop for purchases, refunds and reviews
+    pub require_transfer_approval: bool, // App ownership transfers need authority approval
+    pub pending_treasury: Option<Pubkey>, // Timelocked treasury change
+    pub pending_platform_fee_bps: Option<u16>, // Timelocked fee increase
+    pub settings_change_at: i64,        // When the pending change can be applied
+    pub purchase_count: u64,            // Last purchase_id issued (AppPurchased events)
+    pub price_notice_hours: u16,        // Delay before creator price increases apply (0 = immediate)
+    pub holder_discount_bps: [u16; 5],  // Purchase discount per defai_swap bonus NFT tier
+    pub review_window_days: u16,        // Days after purchase a review may be submitted (0 = no limit)
+    pub burn_bps: u16,                  // Share of each DEFAI purchase burned, out of the platform fee
+    pub max_platform_fee_bps: u16,      // Fee ceiling, set from MAX_PLATFORM_FEE_BPS at init (no setter)
+    pub registration_fee: u64,          // DEFAI paid to the treasury per app registration
+    pub registration_stake: u64,        // Refundable DEFAI escrowed per app registration
+    pub report_threshold: u16,          // Open reports that suspend an app's sales (0 = never)
+}
+
+impl AppFactory {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 2 + 8 + 1 + (1 + 32) + 1 + 1 + (1 + 32) + (1 + 2) + 8 + 8 + 2 + 2 * 5 + 2 + 2 + 2 + 8 + 8 + 2;
+
+    // Issues the next sequential purchase id so indexers can order sales globally
+    pub fn next_purchase_id(&mut self) -> Result<u64> {
+        self.purchase_count = self.purchase_count
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(self.purchase_count)
+    }
+}
+
+#[account]
+pub struct AppRegistration {
+    pub app_id: u64,                    // Unique app identifier
+    pub creator: Pubkey,                // App creator (receives creator fee)
+    pub sft_mint: Pubkey,               // SFT mint address for this app
+    pub price: u64,                     // Price in DEFAI tokens (with decimals)
+    pub max_supply: u64,                // Maximum number of SFTs that can be minted
+    pub current_supply: u64,            // Current number of SFTs minted
+    pub is_active: bool,                // Whether app purchases are enabled
+    pub metadata_uri: String,           // IPFS URI for app metadata
+    pub created_at: i64,                // Creation timestamp
+    pub bump: u8,                       // PDA bump seed
+    pub rental_price: u64,              // Rental price in DEFAI (0 = rentals disabled)
+    pub rental_days: u16,               // Days of access granted by a rental
+    pub latest_version: u32,            // Latest published AppVersion (0 = none)
+    pub edition_count: u8,              // Number of AppEdition tiers
+    pub review_count: u64,              // Number of live reviews
+    pub rating_sum: u64,                // Sum of live review ratings (avg = sum / count)
+    pub delisted: bool,                 // Permanently delisted; cannot be reactivated
+    pub categories: Vec<u16>,           // CategoryRegistry ids (max MAX_APP_CATEGORIES)
+    pub affiliate_bps: u16,             // Referrer commission, taken from the creator share
+    pub units_sold: u64,                // Sales and rentals across all payment methods
+    pub gross_revenue: u64,             // DEFAI received from sales (SOL/USDC sales count as units only)
+    pub refund_count: u64,
+    pub refund_amount: u64,             // DEFAI returned to buyers
+    pub last_sale_at: i64,
+    pub presale_start: i64,             // Whitelist-only window (mirrors AppPresale)
+    pub presale_end: i64,
+    pub royalty_bps: u16,               // Secondary-sale royalty written into the SFT metadata
+    pub soulbound: bool,                // Buyer SFT ATAs are frozen at mint (not transferable)
+    pub refund_policy: Option<RefundPolicy>, // None = RefundPolicy::DEFAULT (24h, 100%)
+    pub fee_override_bps: Option<u16>,  // Authority-set platform fee for this app
+    pub category_fee_bps: Option<u16>,  // Lowest category fee override (synced by crank)
+    pub payout_vesting_days: u16,       // Creator proceeds vest over this many days (0 = paid directly)
+    pub charity: Option<Pubkey>,        // Registered Charity wallet receiving donations
+    pub charity_bps: u16,               // Share of each DEFAI sale donated, taken from the creator share
+    pub pending_price: u64,             // Scheduled price increase (0 = none)
+    pub price_effective_at: i64,        // When pending_price replaces price
+    pub stake_amount: u64,              // Creator stake held in the stake vault (0 = none or returned)
+    pub flagged: bool,                  // Stake slashed for a malicious listing; cannot be reactivated
+    pub trial_price: u64,               // DEFAI price of a trial (0 = free)
+    pub trial_days: u16,                // Trial length (0 = trials disabled)
+    pub trial_discount_bps: u16,        // Discount off the list price when converting a trial
+    pub trials_started: u64,
+    pub trial_conversions: u64,         // Trials converted to a purchase
+    pub open_reports: u16,              // Buyer reports awaiting authority review
+    pub suspended: bool,                // Sales suspended by reports; creator cannot reactivate
+}
+
+impl AppRegistration {
+    pub const LEN: usize = 8 + 8 + 32 + 32 + 8 + 8 + 8 + 1 + (4 + MAX_METADATA_URI_LEN) + 8 + 1 + 8 + 2 + 4 + 1 + 8 + 8 + 1 + (4 + 2 * MAX_APP_CATEGORIES) + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 2 + 1 + (1 + RefundPolicy::LEN) + 3 + 3 + 2 + (1 + 32) + 2 + 8 + 8 + 8 + 1 + 8 + 2 + 2 + 8 + 8 + 2 + 1; // ~565 bytes
+
+    // Platform fee for this app: app override, then category override, then global
+    pub fn effective_fee_bps(&self, platform_fee_bps: u16) -> u16 {
+        self.fee_override_bps
+            .or(self.category_fee_bps)
+            .unwrap_or(platform_fee_bps)
+    }
+
+    // List price at `now`; a scheduled increase applies from price_effective_at
+    pub fn list_price(&self, now: i64) -> u64 {
+        if self.pending_price > 0 && now >= self.price_effective_at {
+            self.pending_price
+        } else {
+            self.price
+        }
+    }
+
+    pub fn in_presale(&self, now: i64) -> bool {
+        now >= self.presale_start && now < self.presale_end
+    }
+
+    pub fn record_sale(&mut self, defai_amount: u64, now: i64) -> Result<()> {
+        self.units_sold = self.units_sold
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.gross_revenue = self.gross_revenue
+            .checked_add(defai_amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.last_sale_at = now;
+        Ok(())
+    }
+
+    pub fn record_refund(&mut self, defai_amount: u64) -> Result<()> {
+        self.refund_count = self.refund_count
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.refund_amount = self.refund_amount
+            .checked_add(defai_amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct UserAppAccess {
+    pub user: Pubkey,                   // User wallet
+    pub app_id: u64,                    // App they purchased
+    pub sft_token_account: Pubkey,      // Their SFT token account
+    pub purchased_at: i64,              // Purchase timestamp
+    pub purchase_price: u64,            // Price at purchase time
+    pub bump: u8,                       // PDA bump seed
+    pub expires_at: i64,                // Rental expiry (0 = permanent purchase)
+    pub payment_mint: Pubkey,           // Mint paid with (default pubkey = native SOL)
+    pub rent_payer: Pubkey,             // Paid this account's rent; refunded whenever it is closed
+    pub paid_out: PurchaseSplit,        // Where the DEFAI payment went; refunds return it from there
+    pub disputed: bool,                 // Set once disputed; the ruling is then the purchase's only remedy
+}
+
+impl UserAppAccess {
+    pub const LEN: usize = 8 + 32 + 8 + 32 + 8 + 8 + 1 + 8 + 32 + 32 + PurchaseSplit::LEN + 1;
+
+    // Where closing returns rent; records from before rent_payer was tracked refund the user
+    pub fn rent_recipient(&self) -> Pubkey {
+        if self.rent_payer == Pubkey::default() {
+            self.user
+        } else {
+            self.rent_payer
+        }
+    }
+}
+
+// ============================================================================
+// Context Structures
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeAppFactory<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = AppFactory::LEN,
+        seeds = [b"app_factory"],
+        bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        constraint = defai_mint.mint_authority.is_some() 
+            @ AppFactoryError::InvalidDefaiMint,
+        constraint = defai_mint.decimals == 6 
+            @ AppFactoryError::InvalidMintDecimals
+    )]
+    pub defai_mint: Account<'info, Mint>,
+    
+    /// CHECK: Platform treasury wallet - verified to be a system account
+    #[account(
+        constraint = treasury.owner == &System::id() 
+            @ AppFactoryError::InvalidTreasury
+    )]
+    pub treasury: SystemAccount<'info>,
+    
+    #[account(
+        constraint = master_collection.supply > 0
+            @ AppFactoryError::InvalidCollection
+    )]
+    pub master_collection: Account<'info, Mint>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(price: u64, max_supply: u64, metadata_uri: String)]
+pub struct RegisterApp<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = AppRegistration::LEN,
+        seeds = [b"app_registration".as_ref(), &app_factory.total_apps.to_le_bytes()],
         bump
     )]
     pub app_registration: Account<'info, AppRegistration>,
     
     #[account(
+        mut,
         constraint = sft_mint.mint_authority == COption::Some(creator.key()) 
             @ AppFactoryError::InvalidMintAuthority,
         constraint = sft_mint.freeze_authority == COption::Some(creator.key())
@@ -537,6 +2064,50 @@ pub struct RegisterApp<'info> {
     )]
     pub sft_mint: Account<'info, Mint>,
     
+    // Optional verification badge for the creator
+    #[account(
+        seeds = [b"verified_creator".as_ref(), creator.key().as_ref()],
+        bump = verified_creator.bump
+    )]
+    pub verified_creator: Option<Account<'info, VerifiedCreator>>,
+    
+    // Required when attaching categories at registration
+    #[account(
+        seeds = [b"category_registry"],
+        bump = category_registry.bump
+    )]
+    pub category_registry: Option<Account<'info, CategoryRegistry>>,
+    
+    // Required when the factory charges a registration fee or stake
+    #[account(
+        mut,
+        token::mint = app_factory.defai_mint,
+        token::authority = creator
+    )]
+    pub creator_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = app_factory.treasury
+    )]
+    pub treasury_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Option<Box<Account<'info, TokenAccount>>>,
+    
+    // Required when the factory charges a registration fee
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Option<Box<Account<'info, TreasuryLedger>>>,
+    
     #[account(mut)]
     pub creator: Signer<'info>,
     
@@ -639,6 +2210,13 @@ pub struct ToggleAppStatus<'info> {
     )]
     pub app_registration: Account<'info, AppRegistration>,
     
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
     pub creator: Signer<'info>,
 }
 
@@ -661,19 +2239,32 @@ pub struct UpdatePlatformSettings<'info> {
     pub new_treasury: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
+    pub authority: Signer<'info>,
+}
+
 // ============================================================================
 // Error Definitions
 // ============================================================================
 
 #[error_code]
 pub enum AppFactoryError {
-    #[msg("Invalid platform fee (must be <= 10000 basis points)")]
+    #[msg("Invalid platform fee (must be <= 3000 basis points)")]
     InvalidPlatformFee,
     #[msg("Invalid price (must be > 0)")]
     InvalidPrice,
     #[msg("Invalid max supply (must be > 0)")]
     InvalidMaxSupply,
-    #[msg("Metadata URI too long (max 100 characters)")]
+    #[msg("Metadata URI too long (max 256 characters)")]
     MetadataUriTooLong,
     #[msg("App is not active")]
     AppNotActive,
@@ -713,6 +2304,10 @@ pub enum AppFactoryError {
     NoSftToRefund,
     #[msg("Insufficient creator balance for refund")]
     InsufficientCreatorBalance,
+    #[msg("Account already uses the current layout")]
+    AlreadyMigrated,
+    #[msg("App factory is paused")]
+    FactoryPaused,
 }
 
 // ============================================================================
@@ -721,33 +2316,53 @@ pub enum AppFactoryError {
 
 #[event]
 pub struct AppRegistered {
+    pub schema_version: u8,
     pub app_id: u64,
     pub creator: Pubkey,
     pub sft_mint: Pubkey,
     pub price: u64,
     pub max_supply: u64,
+    pub creator_verified: bool,
+    pub creator_kyc_hash: Option<[u8; 32]>,  // Set when the creator is KYC'd
     pub timestamp: i64,
 }
 
 #[event]
 pub struct AppPurchased {
+    pub schema_version: u8,
     pub app_id: u64,
     pub user: Pubkey,
     pub price: u64,
     pub platform_fee: u64,
     pub creator_amount: u64,
+    pub payment_mint: Pubkey,           // Default pubkey = native SOL
     pub timestamp: i64,
+    pub purchase_id: u64,               // Sequential across all purchases (starts at 1)
+    pub burned: u64,                    // DEFAI burned from the payment (taken from platform_fee)
+    pub sft_mint: Pubkey,               // Snapshot of the app at purchase time
+    pub metadata_uri: String,
+    pub edition_id: Option<u8>,         // Set for edition purchases
 }
 
 #[event]
 pub struct AppStatusChanged {
+    pub schema_version: u8,
     pub app_id: u64,
     pub is_active: bool,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct FactoryPauseChanged {
+    pub schema_version: u8,
+    pub paused: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct PlatformSettingsUpdated {
+    pub schema_version: u8,
     pub platform_fee_bps: Option<u16>,
     pub treasury: Option<Pubkey>,
     pub timestamp: i64,
@@ -0,0 +1,360 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::{AppFactory, FactoryStats, AppRegistration, AppFactoryError, TreasuryLedger};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of, EventCpi, RevenueSource};
+
+// Anti-spam listing costs. The authority can charge a DEFAI registration fee
+// (paid to the treasury) and/or a refundable stake held in the factory's stake
+// vault. The stake is recorded on the app and returned to the creator after
+// STAKE_LOCK_PERIOD; until then the authority can slash it to the treasury for
+// a malicious listing, which also flags the app and stops its sales for good.
+// Both payments to the treasury are booked in the treasury ledger.
+
+#[constant]
+pub const STAKE_LOCK_PERIOD: i64 = 7776000; // 90 days
+const MAX_REASON_CID_LEN: usize = 64;
+
+#[derive(Accounts)]
+pub struct SetRegistrationRequirements<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    // Escrow for creator stakes, owned by the factory PDA
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"stake_vault"],
+        bump,
+        token::mint = defai_mint,
+        token::authority = app_factory
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(address = app_factory.defai_mint @ AppFactoryError::InvalidDefaiMint)]
+    pub defai_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ReleaseCreatorStake<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = app_factory.defai_mint,
+        token::authority = creator
+    )]
+    pub creator_defai_ata: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SlashCreatorStake<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = app_factory.defai_mint,
+        associated_token::authority = app_factory.treasury
+    )]
+    pub treasury_defai_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct RegistrationRequirementsSet {
+    pub schema_version: u8,
+    pub registration_fee: u64,
+    pub registration_stake: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorStakeReleased {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorStakeSlashed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub reason_cid: String,             // IPFS CID of the evidence
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum ListingStakeError {
+    #[msg("Creator DEFAI, treasury, treasury ledger and stake vault accounts are required to register")]
+    MissingRegistrationAccounts,
+    #[msg("No stake held for this app")]
+    NoStake,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("App has been flagged as malicious")]
+    AppFlagged,
+    #[msg("Reason CID too long (max 64 characters)")]
+    ReasonCidTooLong,
+}
+
+pub fn set_registration_requirements(
+    ctx: Context<SetRegistrationRequirements>,
+    registration_fee: u64,
+    registration_stake: u64,
+) -> Result<()> {
+    let app_factory = &mut ctx.accounts.app_factory;
+    app_factory.registration_fee = registration_fee;
+    app_factory.registration_stake = registration_stake;
+
+    emit_versioned!(RegistrationRequirementsSet {
+        registration_fee,
+        registration_stake,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "App registration now costs {} DEFAI fee plus {} DEFAI stake",
+        registration_fee,
+        registration_stake
+    );
+    Ok(())
+}
+
+// Charge the registration fee and escrow the stake. Returns the amount staked.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn collect_registration_payment<'info>(
+    app_factory: &AppFactory,
+    creator: &AccountInfo<'info>,
+    creator_defai_ata: Option<&Account<'info, TokenAccount>>,
+    treasury_defai_ata: Option<&Account<'info, TokenAccount>>,
+    stake_vault: Option<&Account<'info, TokenAccount>>,
+    treasury_ledger: Option<&mut Account<'info, TreasuryLedger>>,
+    token_program: &Program<'info, Token>,
+    events: &EventCpi<'_, 'info>,
+) -> Result<u64> {
+    let fee = app_factory.registration_fee;
+    let stake = app_factory.registration_stake;
+    if fee == 0 && stake == 0 {
+        return Ok(0);
+    }
+    let creator_defai_ata = creator_defai_ata.ok_or(ListingStakeError::MissingRegistrationAccounts)?;
+
+    if fee > 0 {
+        let treasury_defai_ata = treasury_defai_ata.ok_or(ListingStakeError::MissingRegistrationAccounts)?;
+        let treasury_ledger = treasury_ledger.ok_or(ListingStakeError::MissingRegistrationAccounts)?;
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: creator_defai_ata.to_account_info(),
+                    to: treasury_defai_ata.to_account_info(),
+                    authority: creator.clone(),
+                },
+            ),
+            fee,
+        )?;
+        treasury_ledger.record(RevenueSource::AppRegistrationFee, fee, Clock::get()?.unix_timestamp, events)?;
+    }
+
+    if stake > 0 {
+        let stake_vault = stake_vault.ok_or(ListingStakeError::MissingRegistrationAccounts)?;
+        token::transfer(
+            CpiContext::new(
+                token_program.to_account_info(),
+                Transfer {
+                    from: creator_defai_ata.to_account_info(),
+                    to: stake_vault.to_account_info(),
+                    authority: creator.clone(),
+                },
+            ),
+            stake,
+        )?;
+    }
+
+    Ok(stake)
+}
+
+// Pay out of the stake vault, signed by the factory PDA
+fn transfer_from_stake_vault<'info>(
+    app_factory: &Account<'info, AppFactory>,
+    stake_vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    let factory_seeds = &[b"app_factory".as_ref(), &[app_factory.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: stake_vault.to_account_info(),
+                to: to.to_account_info(),
+                authority: app_factory.to_account_info(),
+            },
+            &[&factory_seeds[..]],
+        ),
+        amount,
+    )
+}
+
+pub fn release_creator_stake(ctx: Context<ReleaseCreatorStake>, app_id: u64) -> Result<()> {
+    release_stake(
+        &ctx.accounts.app_factory,
+        &mut ctx.accounts.app_registration,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.creator_defai_ata,
+        &ctx.accounts.token_program,
+        app_id,
+        &event_cpi_of!(ctx),
+    )
+}
+
+// Return an unlocked stake to the creator's DEFAI account. Shared with the
+// keeper crank, which pays the same creator-owned account.
+pub(crate) fn release_stake<'info>(
+    app_factory: &Account<'info, AppFactory>,
+    app_registration: &mut Account<'info, AppRegistration>,
+    stake_vault: &Account<'info, TokenAccount>,
+    creator_defai_ata: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    app_id: u64,
+    events: &EventCpi<'_, 'info>,
+) -> Result<()> {
+    let amount = app_registration.stake_amount;
+    require!(amount > 0, ListingStakeError::NoStake);
+    require!(!app_registration.flagged, ListingStakeError::AppFlagged);
+    let now = Clock::get()?.unix_timestamp;
+    let unlock_at = app_registration.created_at
+        .checked_add(STAKE_LOCK_PERIOD)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    require!(now >= unlock_at, ListingStakeError::StakeLocked);
+
+    transfer_from_stake_vault(app_factory, stake_vault, creator_defai_ata, token_program, amount)?;
+    app_registration.stake_amount = 0;
+
+    emit_cpi_versioned!(events, CreatorStakeReleased {
+        app_id,
+        creator: app_registration.creator,
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Released {} DEFAI stake for app {}", amount, app_id);
+    Ok(())
+}
+
+// Flag a malicious listing: its stake goes to the treasury and sales stop
+pub fn slash_creator_stake(
+    ctx: Context<SlashCreatorStake>,
+    app_id: u64,
+    reason_cid: String,
+) -> Result<()> {
+    require!(reason_cid.len() <= MAX_REASON_CID_LEN, ListingStakeError::ReasonCidTooLong);
+    let amount = ctx.accounts.app_registration.stake_amount;
+    require!(amount > 0, ListingStakeError::NoStake);
+
+    transfer_from_stake_vault(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.stake_vault,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.treasury_ledger.record(RevenueSource::AppStakeSlash, amount, now, &event_cpi_of!(ctx))?;
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.stake_amount = 0;
+    app_registration.flagged = true;
+    ctx.accounts.factory_stats.record_status_change(app_registration.is_active, false)?;
+    app_registration.is_active = false;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), CreatorStakeSlashed {
+        app_id,
+        creator: app_registration.creator,
+        amount,
+        reason_cid,
+        timestamp: now,
+    });
+
+    msg!("Slashed {} DEFAI stake and flagged app {}", amount, app_id);
+    Ok(())
+}
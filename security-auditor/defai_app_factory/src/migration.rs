@@ -0,0 +1,364 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, Burn},
+};
+
+use crate::{
+    AppFactory, AppRegistration, UserAppAccess, AppFactoryError, CleanupError, Dispute, DisputeStatus,
+    mint_app_sft, thaw_app_sft_account, PurchaseSplit,
+};
+use defai_common::emit_versioned;
+
+// Migration from a retired app to its successor. The creator of both apps
+// maps the old app id to the new one; holders then call `migrate_access` to
+// burn the old SFT and receive the new one free of charge. The new access
+// record keeps the original purchase time, price and payment mint, so refund
+// windows and history carry over. Rentals are not migrated.
+
+#[account]
+pub struct AppMigration {
+    pub old_app_id: u64,
+    pub new_app_id: u64,
+    pub migrated_count: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl AppMigration {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(old_app_id: u64, new_app_id: u64)]
+pub struct SetAppMigration<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &old_app_id.to_le_bytes()],
+        bump = old_app_registration.bump,
+        constraint = old_app_registration.creator == creator.key() @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub old_app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &new_app_id.to_le_bytes()],
+        bump = new_app_registration.bump,
+        constraint = new_app_registration.creator == creator.key() @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub new_app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = AppMigration::LEN,
+        seeds = [b"app_migration".as_ref(), &old_app_id.to_le_bytes()],
+        bump
+    )]
+    pub app_migration: Account<'info, AppMigration>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(old_app_id: u64)]
+pub struct RemoveAppMigration<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &old_app_id.to_le_bytes()],
+        bump = old_app_registration.bump,
+        constraint = old_app_registration.creator == creator.key() @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub old_app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_migration".as_ref(), &old_app_id.to_le_bytes()],
+        bump = app_migration.bump,
+        close = creator
+    )]
+    pub app_migration: Account<'info, AppMigration>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(old_app_id: u64, new_app_id: u64)]
+pub struct MigrateAccess<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_migration".as_ref(), &old_app_id.to_le_bytes()],
+        bump = app_migration.bump,
+        constraint = app_migration.new_app_id == new_app_id @ MigrationError::WrongSuccessor
+    )]
+    pub app_migration: Box<Account<'info, AppMigration>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &old_app_id.to_le_bytes()],
+        bump = old_app_registration.bump
+    )]
+    pub old_app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &new_app_id.to_le_bytes()],
+        bump = new_app_registration.bump,
+        constraint = new_app_registration.is_active @ AppFactoryError::AppNotActive
+    )]
+    pub new_app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &old_app_id.to_le_bytes()],
+        bump = old_user_app_access.bump,
+        has_one = user,
+        close = rent_payer
+    )]
+    pub old_user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: Receives the access record's rent; must be whoever paid it
+    #[account(
+        mut,
+        address = old_user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &new_app_id.to_le_bytes()],
+        bump
+    )]
+    pub new_user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: The holder's dispute PDA for the old app; may not exist
+    #[account(
+        seeds = [b"dispute".as_ref(), user.key().as_ref(), &old_app_id.to_le_bytes()],
+        bump
+    )]
+    pub old_dispute: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = old_app_registration.sft_mint
+    )]
+    pub old_sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = old_sft_mint,
+        associated_token::authority = user,
+        constraint = old_user_sft_ata.amount > 0 @ MigrationError::NoSftToMigrate
+    )]
+    pub old_user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        address = new_app_registration.sft_mint
+    )]
+    pub new_sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = new_sft_mint,
+        associated_token::authority = user
+    )]
+    pub new_user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppMigrationSet {
+    pub schema_version: u8,
+    pub old_app_id: u64,
+    pub new_app_id: u64,
+    pub creator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppMigrationRemoved {
+    pub schema_version: u8,
+    pub old_app_id: u64,
+    pub migrated_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccessMigrated {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub old_app_id: u64,
+    pub new_app_id: u64,
+    pub purchased_at: i64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum MigrationError {
+    #[msg("An app cannot migrate to itself")]
+    SameApp,
+    #[msg("Migration maps this app to a different successor")]
+    WrongSuccessor,
+    #[msg("No SFT to migrate")]
+    NoSftToMigrate,
+    #[msg("Rentals cannot be migrated")]
+    RentalNotMigratable,
+    #[msg("Purchase is under dispute")]
+    DisputeOpen,
+}
+
+pub fn set_app_migration(
+    ctx: Context<SetAppMigration>,
+    old_app_id: u64,
+    new_app_id: u64,
+) -> Result<()> {
+    require!(old_app_id != new_app_id, MigrationError::SameApp);
+
+    let now = Clock::get()?.unix_timestamp;
+    let migration = &mut ctx.accounts.app_migration;
+    migration.old_app_id = old_app_id;
+    migration.new_app_id = new_app_id;
+    migration.migrated_count = 0;
+    migration.created_at = now;
+    migration.bump = ctx.bumps.app_migration;
+
+    emit_versioned!(AppMigrationSet {
+        old_app_id,
+        new_app_id,
+        creator: ctx.accounts.creator.key(),
+        timestamp: now,
+    });
+
+    msg!("App {} now migrates to app {}", old_app_id, new_app_id);
+    Ok(())
+}
+
+pub fn remove_app_migration(ctx: Context<RemoveAppMigration>, old_app_id: u64) -> Result<()> {
+    emit_versioned!(AppMigrationRemoved {
+        old_app_id,
+        migrated_count: ctx.accounts.app_migration.migrated_count,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Migration for app {} removed", old_app_id);
+    Ok(())
+}
+
+pub fn migrate_access(
+    ctx: Context<MigrateAccess>,
+    old_app_id: u64,
+    new_app_id: u64,
+) -> Result<()> {
+    let old_access = &ctx.accounts.old_user_app_access;
+    require!(old_access.expires_at == 0, MigrationError::RentalNotMigratable);
+
+    // A disputed purchase stays put until it is resolved
+    if !ctx.accounts.old_dispute.data_is_empty() {
+        let data = ctx.accounts.old_dispute.try_borrow_data()?;
+        let dispute = Dispute::try_deserialize(&mut &data[..])?;
+        require!(
+            !matches!(dispute.status, DisputeStatus::Open | DisputeStatus::Escrowed),
+            MigrationError::DisputeOpen
+        );
+    }
+
+    require!(
+        ctx.accounts.new_app_registration.current_supply < ctx.accounts.new_app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    // Soulbound SFTs are frozen; thaw so the owner can burn
+    if ctx.accounts.old_user_sft_ata.is_frozen() {
+        thaw_app_sft_account(
+            &ctx.accounts.old_app_registration.to_account_info(),
+            &ctx.accounts.old_sft_mint.to_account_info(),
+            &ctx.accounts.old_user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            old_app_id,
+            ctx.accounts.old_app_registration.bump,
+        )?;
+    }
+
+    // Burn the old SFT
+    let burn_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.old_sft_mint.to_account_info(),
+            from: ctx.accounts.old_user_sft_ata.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::burn(burn_ctx, 1)?;
+
+    // Mint the successor's SFT
+    let new_bump = ctx.accounts.new_app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.new_app_registration,
+        &ctx.accounts.new_sft_mint.to_account_info(),
+        &ctx.accounts.new_user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        new_app_id,
+        new_bump,
+    )?;
+
+    // Update supply counts
+    ctx.accounts.old_app_registration.current_supply = ctx.accounts.old_app_registration.current_supply
+        .checked_sub(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.new_app_registration.current_supply = ctx.accounts.new_app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_migration.migrated_count = ctx.accounts.app_migration.migrated_count
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    // Carry the original purchase over
+    let old_access = &ctx.accounts.old_user_app_access;
+    let new_access = &mut ctx.accounts.new_user_app_access;
+    new_access.user = ctx.accounts.user.key();
+    new_access.app_id = new_app_id;
+    new_access.sft_token_account = ctx.accounts.new_user_sft_ata.key();
+    new_access.purchased_at = old_access.purchased_at;
+    new_access.purchase_price = old_access.purchase_price;
+    new_access.bump = ctx.bumps.new_user_app_access;
+    new_access.expires_at = 0;
+    new_access.payment_mint = old_access.payment_mint;
+    new_access.rent_payer = ctx.accounts.user.key();
+    // The old app's creator was paid, so the successor's creator owes no refund
+    new_access.paid_out = PurchaseSplit::default();
+    new_access.disputed = old_access.disputed;
+
+    emit_versioned!(AccessMigrated {
+        user: ctx.accounts.user.key(),
+        old_app_id,
+        new_app_id,
+        purchased_at: new_access.purchased_at,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "User {} migrated from app {} to app {}",
+        ctx.accounts.user.key(),
+        old_app_id,
+        new_app_id
+    );
+    Ok(())
+}
@@ -0,0 +1,449 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased,
+    calculate_fee_split, execute_token_transfers, mint_app_sft, distribute_creator_amount,
+    CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{
+    emit_cpi_versioned, emit_versioned, event_cpi_of, merkle_address_leaf, verify_merkle_proof,
+};
+
+// While an app's presale window is open, every public purchase path rejects
+// and only whitelisted wallets can buy through `purchase_presale`. The
+// whitelist is a merkle tree of keccak(wallet) leaves. The window is mirrored
+// on AppRegistration so public paths can check it without this account.
+
+#[account]
+pub struct AppPresale {
+    pub app_id: u64,
+    pub merkle_root: [u8; 32],
+    pub start_time: i64,
+    pub end_time: i64,
+    pub presale_price: u64,             // Price for whitelisted buyers (0 = list price)
+    pub bump: u8,
+}
+
+impl AppPresale {
+    pub const LEN: usize = 8 + 8 + 32 + 8 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppPresale<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = AppPresale::LEN,
+        seeds = [b"app_presale".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub app_presale: Account<'info, AppPresale>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CancelAppPresale<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"app_presale".as_ref(), &app_id.to_le_bytes()],
+        bump = app_presale.bump,
+        close = creator
+    )]
+    pub app_presale: Account<'info, AppPresale>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurchasePresale<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"app_presale".as_ref(), &app_id.to_le_bytes()],
+        bump = app_presale.bump
+    )]
+    pub app_presale: Box<Account<'info, AppPresale>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppPresaleSet {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub merkle_root: [u8; 32],
+    pub start_time: i64,
+    pub end_time: i64,
+    pub presale_price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppPresaleCancelled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum PresaleError {
+    #[msg("Presale end must be after start and in the future")]
+    InvalidPresaleWindow,
+    #[msg("Presale price must be below the list price")]
+    InvalidPresalePrice,
+    #[msg("App is in its whitelist presale")]
+    PresaleActive,
+    #[msg("Presale is not running")]
+    PresaleNotActive,
+    #[msg("Wallet is not on the presale whitelist")]
+    NotWhitelisted,
+}
+
+pub fn set_app_presale(
+    ctx: Context<SetAppPresale>,
+    app_id: u64,
+    merkle_root: [u8; 32],
+    start_time: i64,
+    end_time: i64,
+    presale_price: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(end_time > start_time && end_time > now, PresaleError::InvalidPresaleWindow);
+    require!(
+        presale_price < ctx.accounts.app_registration.list_price(now),
+        PresaleError::InvalidPresalePrice
+    );
+
+    let app_presale = &mut ctx.accounts.app_presale;
+    app_presale.app_id = app_id;
+    app_presale.merkle_root = merkle_root;
+    app_presale.start_time = start_time;
+    app_presale.end_time = end_time;
+    app_presale.presale_price = presale_price;
+    app_presale.bump = ctx.bumps.app_presale;
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.presale_start = start_time;
+    app_registration.presale_end = end_time;
+
+    emit_versioned!(AppPresaleSet {
+        app_id,
+        merkle_root,
+        start_time,
+        end_time,
+        presale_price,
+        timestamp: now,
+    });
+
+    msg!("Presale set for app {} from {} to {}", app_id, start_time, end_time);
+    Ok(())
+}
+
+pub fn cancel_app_presale(ctx: Context<CancelAppPresale>, app_id: u64) -> Result<()> {
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.presale_start = 0;
+    app_registration.presale_end = 0;
+
+    emit_versioned!(AppPresaleCancelled {
+        app_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Presale cancelled for app {}; sales are public", app_id);
+    Ok(())
+}
+
+pub fn purchase_presale<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchasePresale<'info>>,
+    app_id: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let app_presale = &ctx.accounts.app_presale;
+    require!(
+        now >= app_presale.start_time && now < app_presale.end_time,
+        PresaleError::PresaleNotActive
+    );
+
+    let leaf = merkle_address_leaf(&ctx.accounts.user.key());
+    require!(
+        verify_merkle_proof(leaf, &proof, &app_presale.merkle_root),
+        PresaleError::NotWhitelisted
+    );
+
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    let price = if app_presale.presale_price > 0 {
+        app_presale.presale_price
+    } else {
+        app_registration.list_price(now)
+    };
+    require!(
+        ctx.accounts.user_defai_ata.amount >= price,
+        AppFactoryError::InsufficientBalance
+    );
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    // Execute transfers
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Mint SFT
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, now)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    // Record access
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    msg!("Whitelisted user purchased app {} access in presale", app_id);
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use defai_common::{
+    emit_versioned, validate_program_links, Timelock, ADMIN_TIMELOCK_DURATION, DEFAULT_APP_FACTORY_PROGRAM_ID,
+    DEFAULT_ESTATE_PROGRAM_ID, DEFAULT_GOVERNANCE_PROGRAM_ID, DEFAULT_SWAP_PROGRAM_ID,
+};
+
+use crate::{AppFactory, AppFactoryError};
+
+// Program registry (see defai_common::registry)
+// Ids of the sibling programs this deployment works with. Estate purchases
+// must be signed by an account of the linked estate program, and holder
+// discounts read bonus NFT state from the linked swap program. Changes go
+// through the admin timelock.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProgramLinks {
+    pub swap: Pubkey,
+    pub estate: Pubkey,
+    pub app_factory: Pubkey,
+    pub governance: Pubkey,
+}
+
+impl ProgramLinks {
+    pub const LEN: usize = 32 * 4;
+
+    pub fn defaults() -> Self {
+        Self {
+            swap: DEFAULT_SWAP_PROGRAM_ID,
+            estate: DEFAULT_ESTATE_PROGRAM_ID,
+            app_factory: DEFAULT_APP_FACTORY_PROGRAM_ID,
+            governance: DEFAULT_GOVERNANCE_PROGRAM_ID,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate_program_links(&[self.swap, self.estate, self.app_factory, self.governance], &crate::ID, &self.app_factory)
+    }
+}
+
+#[account]
+pub struct ProgramRegistry {
+    pub links: ProgramLinks,
+    pub pending_links: Option<ProgramLinks>,
+    pub links_change_timestamp: i64,
+    pub bump: u8,
+}
+
+impl ProgramRegistry {
+    pub const LEN: usize = ProgramLinks::LEN + (1 + ProgramLinks::LEN) + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramRegistry<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProgramRegistry::LEN,
+        seeds = [b"program_registry"],
+        bump
+    )]
+    pub program_registry: Box<Account<'info, ProgramRegistry>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramRegistry<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Box<Account<'info, ProgramRegistry>>,
+
+    pub authority: Signer<'info>,
+}
+
+// `None` takes the main deployment's ids
+pub fn initialize_program_registry(ctx: Context<InitializeProgramRegistry>, links: Option<ProgramLinks>) -> Result<()> {
+    let links = links.unwrap_or_else(ProgramLinks::defaults);
+    links.validate()?;
+
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    registry.links = links.clone();
+    registry.pending_links = None;
+    registry.links_change_timestamp = 0;
+    registry.bump = ctx.bumps.program_registry;
+
+    emit_versioned!(ProgramLinksChanged {
+        authority: ctx.accounts.authority.key(),
+        links,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn propose_program_links_change(ctx: Context<UpdateProgramRegistry>, links: ProgramLinks) -> Result<()> {
+    links.validate()?;
+    require!(ctx.accounts.program_registry.links != links, ProgramRegistryError::LinksUnchanged);
+
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    let execute_after = Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp)
+        .propose(links.clone(), Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+
+    emit_versioned!(ProgramLinksChangeProposed {
+        authority: ctx.accounts.authority.key(),
+        links,
+        execute_after,
+    });
+    Ok(())
+}
+
+pub fn cancel_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp).cancel()?;
+
+    emit_versioned!(ProgramLinksChangeCancelled {
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn execute_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    let links = Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp).execute(now)?;
+    registry.links = links.clone();
+
+    emit_versioned!(ProgramLinksChanged {
+        authority: ctx.accounts.authority.key(),
+        links,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct ProgramLinksChangeProposed {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub links: ProgramLinks,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct ProgramLinksChangeCancelled {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramLinksChanged {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub links: ProgramLinks,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum ProgramRegistryError {
+    #[msg("Program links are unchanged")]
+    LinksUnchanged,
+}
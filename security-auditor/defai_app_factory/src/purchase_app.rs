@@ -1,44 +1,60 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     token::{self, Token, TokenAccount, Mint, Transfer, MintTo},
+    token_interface::TokenAccount as TokenAccount2022,
 };
+use defai_common::split_bps;
 
 use crate::{
-    AppFactory, AppRegistration, UserAppAccess, AppFactoryError,
-    APP_REGISTRATION_SEED,
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, Sale, AffiliateStats,
+    APP_REGISTRATION_SEED, PresaleError, freeze_app_sft_account, thaw_app_sft_account,
+    is_token_account_frozen, CreatorVesting, ProgramRegistry,
 };
 
 // Split purchase into pre-validation and execution
 pub fn purchase_app_pre_validation(
     app_registration: &Account<AppRegistration>,
     app_factory: &Account<AppFactory>,
+    sale: Option<&Sale>,
     price: &mut u64,
     platform_fee: &mut u64,
     creator_amount: &mut u64,
 ) -> Result<()> {
     // Validate purchase
     require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        !app_registration.in_presale(now),
+        PresaleError::PresaleActive
+    );
     require!(
         app_registration.current_supply < app_registration.max_supply,
         AppFactoryError::MaxSupplyReached
     );
 
-    *price = app_registration.price;
+    *price = app_registration.list_price(now);
+
+    // A live scheduled sale overrides the list price
+    if let Some(sale) = sale {
+        if let Some(sale_price) = sale.price_at(*price, now)? {
+            *price = sale_price;
+        }
+    }
     
     // Calculate splits
-    *platform_fee = (*price)
-        .checked_mul(app_factory.platform_fee_bps as u64)
-        .ok_or(AppFactoryError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(AppFactoryError::MathOverflow)?;
-    
-    *creator_amount = (*price)
-        .checked_sub(*platform_fee)
-        .ok_or(AppFactoryError::MathOverflow)?;
+    (*platform_fee, *creator_amount) = calculate_fee_split(
+        *price,
+        app_registration.effective_fee_bps(app_factory.platform_fee_bps),
+    )?;
 
     Ok(())
 }
 
+// Split an amount into (platform_fee, creator_amount)
+pub fn calculate_fee_split(amount: u64, platform_fee_bps: u16) -> Result<(u64, u64)> {
+    split_bps(amount, platform_fee_bps as u64)
+}
+
 // Separate token transfer logic
 pub fn execute_token_transfers<'info>(
     user: &Signer<'info>,
@@ -60,7 +76,10 @@ pub fn execute_token_transfers<'info>(
     );
     token::transfer(platform_transfer_ctx, platform_fee)?;
 
-    // Transfer creator amount
+    // Transfer creator amount (already routed elsewhere when the app has a revenue split)
+    if creator_amount == 0 {
+        return Ok(());
+    }
     let creator_transfer_ctx = CpiContext::new(
         token_program.to_account_info(),
         Transfer {
@@ -90,6 +109,20 @@ pub fn mint_app_sft<'info>(
     ];
     let signer_seeds = &[&mint_seeds[..]];
 
+    // A frozen destination (soulbound holder) is thawed for the mint and
+    // frozen again afterwards
+    let was_frozen = is_token_account_frozen(user_sft_ata)?;
+    if was_frozen {
+        thaw_app_sft_account(
+            &app_registration.to_account_info(),
+            sft_mint,
+            user_sft_ata,
+            token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
     let mint_ctx = CpiContext::new_with_signer(
         token_program.to_account_info(),
         MintTo {
@@ -101,18 +134,39 @@ pub fn mint_app_sft<'info>(
     );
     token::mint_to(mint_ctx, 1)?;
 
+    if was_frozen || app_registration.soulbound {
+        freeze_app_sft_account(
+            &app_registration.to_account_info(),
+            sft_mint,
+            user_sft_ata,
+            token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
     Ok(())
 }
 
 // Optimized context with required accounts only
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct PurchaseAppAccessOptimized<'info> {
     #[account(
+        mut,
         seeds = [b"app_factory"],
-        bump
+        bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
     )]
     pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
     
     #[account(
         mut,
@@ -125,6 +179,7 @@ pub struct PurchaseAppAccessOptimized<'info> {
     pub user: Signer<'info>,
 
     #[account(
+        mut,
         constraint = defai_mint.key() == app_factory.defai_mint 
             @ AppFactoryError::InvalidDefaiMint
     )]
@@ -165,9 +220,7 @@ pub struct PurchaseAppAccessOptimized<'info> {
     #[account(
         mut,
         associated_token::mint = defai_mint,
-        associated_token::authority = user,
-        constraint = user_defai_ata.amount >= app_registration.price 
-            @ AppFactoryError::InsufficientBalance
+        associated_token::authority = user
     )]
     pub user_defai_ata: Box<Account<'info, TokenAccount>>,
     
@@ -178,6 +231,37 @@ pub struct PurchaseAppAccessOptimized<'info> {
         associated_token::authority = creator
     )]
     pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Optional defai_swap bonus NFT for a holder discount, with the program
+    // registry naming the swap program that owns its bonus state
+    /// CHECK: Owner, discriminator and PDA checked in apply_holder_discount
+    pub bonus_state: Option<UncheckedAccount<'info>>,
+
+    pub bonus_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount2022>>>,
+
+    #[account(
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Option<Box<Account<'info, ProgramRegistry>>>,
     
     // Validate treasury's DEFAI ATA; create if needed when preparing
     #[account(
@@ -187,67 +271,36 @@ pub struct PurchaseAppAccessOptimized<'info> {
         associated_token::authority = treasury
     )]
     pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
-    
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
 
-// Separate, lightweight initializer to create all needed ATAs via associated token program
-#[derive(Accounts)]
-#[instruction(app_id: u64)]
-pub struct PreparePurchaseAccounts<'info> {
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
     #[account(
-        seeds = [b"app_factory"],
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
         bump
     )]
-    pub app_factory: Box<Account<'info, AppFactory>>,
-    
-    #[account(
-        mut,
-        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
-        bump
-    )]
-    pub app_registration: Box<Account<'info, AppRegistration>>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    /// CHECK: DEFAI mint to bind ATAs
-    #[account(constraint = defai_mint.key() == app_factory.defai_mint @ AppFactoryError::InvalidDefaiMint)]
-    pub defai_mint: AccountInfo<'info>,
-    
-    /// CHECK: Creator must match registration
-    #[account(address = app_registration.creator)]
-    pub creator: AccountInfo<'info>,
-    
-    /// CHECK: Treasury must match factory
-    #[account(address = app_factory.treasury)]
-    pub treasury: AccountInfo<'info>,
-    
+    pub revenue_split: UncheckedAccount<'info>,
+
+    // Optional scheduled sale
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = defai_mint,
-        associated_token::authority = user,
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump = sale.bump
     )]
-    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
-    
+    pub sale: Option<Box<Account<'info, Sale>>>,
+
+    // Optional affiliate; commission is deducted from the creator share
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = defai_mint,
-        associated_token::authority = creator,
+        mut,
+        seeds = [b"affiliate_stats".as_ref(), affiliate_stats.affiliate.as_ref()],
+        bump = affiliate_stats.bump
     )]
-    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
-    
+    pub affiliate_stats: Option<Box<Account<'info, AffiliateStats>>>,
+
     #[account(
-        init_if_needed,
-        payer = user,
-        associated_token::mint = defai_mint,
-        associated_token::authority = treasury,
+        mut,
+        token::mint = defai_mint
     )]
-    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+    pub referrer_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
     
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, anchor_spl::associated_token::AssociatedToken>,
@@ -2,21 +2,36 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Token, TokenAccount, Mint},
+    token_interface::TokenAccount as TokenAccount2022,
 };
 
 use crate::{
-    AppFactory, AppRegistration, UserAppAccess, AppFactoryError,
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError,
     purchase_app_pre_validation, execute_token_transfers, mint_app_sft,
+    Sale, distribute_creator_amount, AffiliateStats, pay_affiliate_commission,
+    CreatorVesting, creator_payout_ata, pay_charity_donation, apply_holder_discount, burn_purchase_share,
+    PurchaseSplit, ProgramRegistry,
 };
+use defai_common::{emit_cpi_versioned, event_cpi_of};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct PurchaseAppWithInit<'info> {
     #[account(
+        mut,
         seeds = [b"app_factory"],
-        bump
+        bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
     )]
     pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
     
     #[account(
         mut,
@@ -53,9 +68,7 @@ pub struct PurchaseAppWithInit<'info> {
     #[account(
         mut,
         associated_token::mint = defai_mint,
-        associated_token::authority = user,
-        constraint = user_defai_ata.amount >= app_registration.price 
-            @ AppFactoryError::InsufficientBalance
+        associated_token::authority = user
     )]
     pub user_defai_ata: Box<Account<'info, TokenAccount>>,
     
@@ -67,6 +80,37 @@ pub struct PurchaseAppWithInit<'info> {
         associated_token::authority = creator
     )]
     pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Optional defai_swap bonus NFT for a holder discount, with the program
+    // registry naming the swap program that owns its bonus state
+    /// CHECK: Owner, discriminator and PDA checked in apply_holder_discount
+    pub bonus_state: Option<UncheckedAccount<'info>>,
+
+    pub bonus_nft_ata: Option<Box<InterfaceAccount<'info, TokenAccount2022>>>,
+
+    #[account(
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Option<Box<Account<'info, ProgramRegistry>>>,
     
     // Initialize treasury's DEFAI ATA if needed
     #[account(
@@ -76,6 +120,36 @@ pub struct PurchaseAppWithInit<'info> {
         associated_token::authority = treasury
     )]
     pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    // Optional scheduled sale
+    #[account(
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump = sale.bump
+    )]
+    pub sale: Option<Box<Account<'info, Sale>>>,
+
+    // Optional affiliate; commission is deducted from the creator share
+    #[account(
+        mut,
+        seeds = [b"affiliate_stats".as_ref(), affiliate_stats.affiliate.as_ref()],
+        bump = affiliate_stats.bump
+    )]
+    pub affiliate_stats: Option<Box<Account<'info, AffiliateStats>>>,
+
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub referrer_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -89,6 +163,7 @@ pub struct PurchaseAppWithInit<'info> {
     pub treasury: AccountInfo<'info>,
     
     #[account(
+        mut,
         constraint = defai_mint.key() == app_factory.defai_mint 
             @ AppFactoryError::InvalidDefaiMint
     )]
@@ -99,7 +174,10 @@ pub struct PurchaseAppWithInit<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn purchase_app_with_init(ctx: Context<PurchaseAppWithInit>, app_id: u64) -> Result<()> {
+pub fn purchase_app_with_init<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseAppWithInit<'info>>,
+    app_id: u64,
+) -> Result<()> {
     let mut price = 0u64;
     let mut platform_fee = 0u64;
     let mut creator_amount = 0u64;
@@ -108,20 +186,96 @@ pub fn purchase_app_with_init(ctx: Context<PurchaseAppWithInit>, app_id: u64) ->
     purchase_app_pre_validation(
         &ctx.accounts.app_registration,
         &ctx.accounts.app_factory,
+        ctx.accounts.sale.as_deref().map(|sale| &**sale),
         &mut price,
         &mut platform_fee,
         &mut creator_amount,
     )?;
 
+    // Bonus NFT holders get their tier discount off the platform fee
+    apply_holder_discount(
+        &ctx.accounts.app_factory,
+        ctx.accounts.bonus_state.as_deref(),
+        ctx.accounts.bonus_nft_ata.as_deref(),
+        ctx.accounts.program_registry.as_deref(),
+        ctx.accounts.user.key(),
+        app_id,
+        &mut price,
+        &mut platform_fee,
+    )?;
+    require!(
+        ctx.accounts.user_defai_ata.amount >= price,
+        AppFactoryError::InsufficientBalance
+    );
+
+    // Pay the referring affiliate, if any
+    let commission_base = creator_amount;
+    let creator_amount = pay_affiliate_commission(
+        ctx.accounts.affiliate_stats.as_deref_mut().map(|stats| &mut **stats),
+        ctx.accounts.referrer_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        ctx.accounts.app_registration.affiliate_bps,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
     // Execute transfers
     execute_token_transfers(
         &ctx.accounts.user,
         &ctx.accounts.user_defai_ata,
-        &ctx.accounts.creator_defai_ata,
+        creator_payout,
         &ctx.accounts.treasury_defai_ata,
         &ctx.accounts.token_program,
         platform_fee,
-        creator_amount,
+        direct_creator_amount,
     )?;
 
     // Mint SFT
@@ -139,6 +293,8 @@ pub fn purchase_app_with_init(ctx: Context<PurchaseAppWithInit>, app_id: u64) ->
     ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
         .checked_add(1)
         .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
 
     // Record access
     let user_app_access = &mut ctx.accounts.user_app_access;
@@ -146,16 +302,37 @@ pub fn purchase_app_with_init(ctx: Context<PurchaseAppWithInit>, app_id: u64) ->
     user_app_access.app_id = app_id;
     user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
     user_app_access.purchased_at = Clock::get()?.unix_timestamp;
+    user_app_access.purchase_price = price;
     user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        commission_base - creator_amount,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
 
     // Emit event
-    emit!(crate::AppPurchased {
+    emit_cpi_versioned!(event_cpi_of!(ctx), crate::AppPurchased {
         app_id,
         user: ctx.accounts.user.key(),
         price,
         platform_fee,
         creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
         timestamp: Clock::get()?.unix_timestamp,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
     });
 
     msg!("User purchased app {} access (single transaction)", app_id);
@@ -2,19 +2,48 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     token::{self, Token, TokenAccount, Mint, Transfer, Burn},
 };
+use defai_common::{bps_of, emit_cpi_versioned, event_cpi_of};
 
 use crate::{
-    AppFactory, AppRegistration, UserAppAccess, AppFactoryError,
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, CleanupError, remove_refunded_review,
+    thaw_app_sft_account, Subscription, CreatorVesting, VestingError,
 };
 
+// Refund paths. Permanent purchases refund a fixed share of the price inside
+// the app's refund window. Time-limited access (rentals, subscriptions) instead
+// refunds the unused part of the paid period pro rata, whenever it is claimed;
+// creators opt out of refunds entirely with a 0-day window.
+//
+// A purchase records where its payment went (PurchaseSplit), and a refund
+// returns that share of each part at the fee in force when it was bought. The
+// treasury returns its part and the creator the rest. That includes the
+// affiliate commission: it was paid out of the creator share to the
+// affiliate's own wallet, so the creator absorbs it and the affiliate keeps it.
+// The burned part is gone and isn't returned. Charity donations and revenue
+// split shares went to wallets that don't sign refunds, and charging them to
+// the creator would make them pay for someone else's cut, so purchases that
+// paid either can't be refunded.
+//
+// Refunds are paid in DEFAI only. SOL and USDC purchases record no split and
+// are rejected up front (NonDefaiPurchase) rather than refunded in DEFAI.
+
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct RefundPurchase<'info> {
     #[account(
         seeds = [b"app_factory"],
-        bump = app_factory.bump
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
     )]
     pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
     
     #[account(
         mut,
@@ -28,9 +57,16 @@ pub struct RefundPurchase<'info> {
         seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
         bump = user_app_access.bump,
         has_one = user,
-        close = user
+        close = rent_payer
     )]
     pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: Receives the access record's rent; must be whoever paid it
+    #[account(
+        mut,
+        address = user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
     
     #[account(
         mut,
@@ -55,6 +91,15 @@ pub struct RefundPurchase<'info> {
     )]
     pub user_defai_ata: Box<Account<'info, TokenAccount>>,
     
+    /// CHECK: The user's review PDA for this app; closed if it exists so a
+    /// refunded buyer cannot keep their review
+    #[account(
+        mut,
+        seeds = [b"app_review", user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_review: UncheckedAccount<'info>,
+    
     // Creator's DEFAI ATA (to send refund from)
     #[account(
         mut,
@@ -92,38 +137,362 @@ pub struct RefundPurchase<'info> {
     )]
     pub defai_mint: Account<'info, Mint>,
     
+    // Required when the purchase paid into payout vesting; the vested part is
+    // withheld back out of it
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RefundSubscription<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+    
+    #[account(
+        mut,
+        seeds = [b"subscription".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = subscription.bump,
+        has_one = user
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = app_registration.creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = app_factory.treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+    
+    pub user: Signer<'info>,
+    
+    #[account(
+        address = app_registration.creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub creator: Signer<'info>,
+    
+    #[account(
+        address = app_factory.treasury @ AppFactoryError::InvalidTreasury
+    )]
+    pub treasury: Signer<'info>,
+    
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint 
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+    
+    // Required when the paid span paid into payout vesting
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Platform bounds on creator-configured refund policies
+#[constant]
+pub const MAX_REFUND_WINDOW_DAYS: u16 = 30;
+#[constant]
+pub const MIN_REFUND_BPS: u16 = 5000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct RefundPolicy {
+    pub window_days: u16,               // 0 = no refunds
+    pub refund_bps: u16,                // Share of the purchase price returned
+}
+
+impl RefundPolicy {
+    pub const LEN: usize = 2 + 2;
+
+    // Applies to apps registered without a policy: 24 hours, full refund
+    pub const DEFAULT: RefundPolicy = RefundPolicy { window_days: 1, refund_bps: 10000 };
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.window_days <= MAX_REFUND_WINDOW_DAYS, RefundError::InvalidRefundPolicy);
+        require!(
+            self.window_days == 0
+                || (self.refund_bps >= MIN_REFUND_BPS && self.refund_bps <= 10000),
+            RefundError::InvalidRefundPolicy
+        );
+        Ok(())
+    }
+}
+
+// Where one DEFAI payment went
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurchaseSplit {
+    pub burned: u64,                    // Burned out of the platform fee
+    pub treasury: u64,                  // Platform fee after the burn
+    pub creator: u64,                   // Paid to the creator's ATA
+    pub vested: u64,                    // Paid into the creator's vesting vault
+    pub affiliate: u64,                 // Referrer commission
+    pub charity: u64,                   // Charity donation
+    pub split: u64,                     // Paid to revenue split recipients
+}
+
+impl PurchaseSplit {
+    pub const LEN: usize = 7 * 8;
+
+    // Split of a DEFAI sale from what the purchase path had left after each
+    // leg: `creator_amount` is the creator side after the affiliate and
+    // charity cuts, `direct_creator_amount` the part of it not routed through
+    // the revenue split. The charity donation is the rest of the price.
+    pub fn from_sale(
+        app_registration: &AppRegistration,
+        price: u64,
+        burned: u64,
+        treasury: u64,
+        affiliate: u64,
+        creator_amount: u64,
+        direct_creator_amount: u64,
+    ) -> Result<Self> {
+        let charity = price
+            .checked_sub(burned)
+            .and_then(|rest| rest.checked_sub(treasury))
+            .and_then(|rest| rest.checked_sub(affiliate))
+            .and_then(|rest| rest.checked_sub(creator_amount))
+            .ok_or(AppFactoryError::MathOverflow)?;
+        let split = creator_amount
+            .checked_sub(direct_creator_amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        let vests = app_registration.payout_vesting_days > 0;
+        Ok(Self {
+            burned,
+            treasury,
+            creator: if vests { 0 } else { direct_creator_amount },
+            vested: if vests { direct_creator_amount } else { 0 },
+            affiliate,
+            charity,
+            split,
+        })
+    }
+
+    // Each part run through `scale`, such as the policy's refund share
+    pub fn scaled(&self, scale: impl Fn(u64) -> Result<u64>) -> Result<Self> {
+        Ok(Self {
+            burned: scale(self.burned)?,
+            treasury: scale(self.treasury)?,
+            creator: scale(self.creator)?,
+            vested: scale(self.vested)?,
+            affiliate: scale(self.affiliate)?,
+            charity: scale(self.charity)?,
+            split: scale(self.split)?,
+        })
+    }
+
+    // Two splits added part by part, such as the renewals of one paid span
+    pub fn combined(&self, other: &Self) -> Result<Self> {
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or_else(|| error!(AppFactoryError::MathOverflow));
+        Ok(Self {
+            burned: add(self.burned, other.burned)?,
+            treasury: add(self.treasury, other.treasury)?,
+            creator: add(self.creator, other.creator)?,
+            vested: add(self.vested, other.vested)?,
+            affiliate: add(self.affiliate, other.affiliate)?,
+            charity: add(self.charity, other.charity)?,
+            split: add(self.split, other.split)?,
+        })
+    }
+
+    // What a refund of this split returns: everything but the burned part
+    pub fn refundable(&self) -> Result<u64> {
+        [self.treasury, self.creator, self.vested, self.affiliate, self.charity, self.split]
+            .iter()
+            .try_fold(0u64, |total, part| total.checked_add(*part))
+            .ok_or_else(|| error!(AppFactoryError::MathOverflow))
+    }
+}
+
 #[event]
 pub struct AppRefunded {
+    pub schema_version: u8,
     pub app_id: u64,
     pub user: Pubkey,
     pub refund_amount: u64,
     pub reason: String,
+    pub review_removed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionRefunded {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub refund_amount: u64,
+    pub unused_seconds: i64,
     pub timestamp: i64,
 }
 
 #[error_code]
 pub enum RefundError {
-    #[msg("Refund window has expired (24 hours)")]
+    #[msg("Refund window has expired")]
     RefundWindowExpired,
     #[msg("No SFT to refund")]
     NoSftToRefund,
     #[msg("Insufficient creator balance for refund")]
     InsufficientCreatorBalance,
+    #[msg("Only DEFAI purchases can be refunded")]
+    NonDefaiPurchase,
+    #[msg("Refund policy outside platform bounds (max 30 days, min 50%)")]
+    InvalidRefundPolicy,
+    #[msg("This app does not offer refunds")]
+    RefundsDisabled,
+    #[msg("No DEFAI payment is recorded for this access")]
+    NoRecordedPayment,
+    #[msg("Purchases that paid a charity or revenue split can't be refunded")]
+    ThirdPartyPayout,
+    #[msg("Disputed purchases are settled by the dispute ruling")]
+    PurchaseDisputed,
 }
 
-fn refund_amount(price: u64, platform_fee_bps: u16) -> u64 {
-    // Calculate the creator's portion that needs to be refunded
-    let platform_fee = price
-        .checked_mul(platform_fee_bps as u64)
-        .unwrap_or(0)
-        .checked_div(10000)
-        .unwrap_or(0);
-    
-    price.checked_sub(platform_fee).unwrap_or(price)
+// Share of `paid` covering the unused part of the span [start, end) at `now`
+pub(crate) fn pro_rata_refund(paid: u64, start: i64, end: i64, now: i64) -> Result<u64> {
+    if now >= end || end <= start {
+        return Ok(0);
+    }
+    let unused = (end - now.max(start)) as u128;
+    let refund = (paid as u128)
+        .checked_mul(unused)
+        .ok_or(AppFactoryError::MathOverflow)?
+        .checked_div((end - start) as u128)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    Ok(refund as u64)
+}
+
+// Return `refund` to the user: the treasury's part from the treasury, the
+// vested part withheld back out of the creator's vesting vault, and the rest
+// of the creator side from the creator, who also covers any vested part
+// already claimed. Returns the total returned.
+#[allow(clippy::too_many_arguments)]
+fn pay_refund<'info>(
+    creator_defai_ata: &Account<'info, TokenAccount>,
+    treasury_defai_ata: &Account<'info, TokenAccount>,
+    user_defai_ata: &Account<'info, TokenAccount>,
+    creator: &Signer<'info>,
+    treasury: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+    creator_vesting: Option<&mut Account<'info, CreatorVesting>>,
+    vesting_vault: Option<&Account<'info, TokenAccount>>,
+    app_id: u64,
+    refund: &PurchaseSplit,
+) -> Result<u64> {
+    let mut withheld = 0;
+    if refund.vested > 0 {
+        let (creator_vesting, vesting_vault) = match (creator_vesting, vesting_vault) {
+            (Some(vesting), Some(vault)) => (vesting, vault),
+            _ => return err!(VestingError::MissingVestingAccounts),
+        };
+        require_keys_eq!(vesting_vault.key(), creator_vesting.vault, VestingError::InvalidVestingVault);
+        withheld = creator_vesting.withhold(refund.vested, Clock::get()?.unix_timestamp)?;
+
+        if withheld > 0 {
+            let vesting_seeds = &[
+                b"creator_vesting".as_ref(),
+                &app_id.to_le_bytes(),
+                &[creator_vesting.bump],
+            ];
+            let signer_seeds = &[&vesting_seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: vesting_vault.to_account_info(),
+                    to: user_defai_ata.to_account_info(),
+                    authority: creator_vesting.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_ctx, withheld)?;
+        }
+    }
+
+    // The creator also covers the affiliate commission, which can't be clawed
+    // back. Callers refuse splits with charity or revenue split payouts.
+    let creator_refund = [refund.creator, refund.vested - withheld, refund.affiliate]
+        .iter()
+        .try_fold(0u64, |total, part| total.checked_add(*part))
+        .ok_or(AppFactoryError::MathOverflow)?;
+    let treasury_refund = refund.treasury;
+
+    // Validate balances before attempting transfer to avoid DoS via 0-balance accounts
+    require!(creator_defai_ata.amount >= creator_refund, AppFactoryError::InsufficientCreatorBalance);
+    require!(treasury_defai_ata.amount >= treasury_refund, AppFactoryError::InsufficientCreatorBalance);
+
+    if creator_refund > 0 {
+        let creator_transfer_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: creator_defai_ata.to_account_info(),
+                to: user_defai_ata.to_account_info(),
+                authority: creator.to_account_info(),
+            },
+        );
+        token::transfer(creator_transfer_ctx, creator_refund)?;
+    }
+
+    if treasury_refund > 0 {
+        let treasury_transfer_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: treasury_defai_ata.to_account_info(),
+                to: user_defai_ata.to_account_info(),
+                authority: treasury.to_account_info(),
+            },
+        );
+        token::transfer(treasury_transfer_ctx, treasury_refund)?;
+    }
+
+    refund.refundable()
 }
 
 pub fn refund_purchase(
@@ -131,29 +500,48 @@ pub fn refund_purchase(
     app_id: u64,
     reason: String,
 ) -> Result<()> {
-    // Check refund window (24 hours)
-    let purchase_time = ctx.accounts.user_app_access.purchased_at;
-    let current_time = Clock::get()?.unix_timestamp;
     require!(
-        current_time - purchase_time <= 86400,
-        RefundError::RefundWindowExpired
+        ctx.accounts.user_app_access.payment_mint == ctx.accounts.app_factory.defai_mint,
+        RefundError::NonDefaiPurchase
     );
+
+    let policy = ctx.accounts.app_registration.refund_policy.unwrap_or(RefundPolicy::DEFAULT);
+    let access = &ctx.accounts.user_app_access;
+    let (purchase_time, expires_at, paid_out) = (access.purchased_at, access.expires_at, access.paid_out);
+    require!(paid_out != PurchaseSplit::default(), RefundError::NoRecordedPayment);
+    require!(paid_out.charity == 0 && paid_out.split == 0, RefundError::ThirdPartyPayout);
+    require!(!access.disputed, RefundError::PurchaseDisputed);
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let refund = if expires_at > 0 {
+        // Rentals refund the unused part of the rental period
+        require!(policy.window_days > 0, RefundError::RefundsDisabled);
+        let refund = paid_out.scaled(|part| pro_rata_refund(part, purchase_time, expires_at, current_time))?;
+        require!(refund.refundable()? > 0, RefundError::RefundWindowExpired);
+        refund
+    } else {
+        // Check the app's refund window
+        require!(
+            current_time - purchase_time <= policy.window_days as i64 * 86400,
+            RefundError::RefundWindowExpired
+        );
+        
+        // The policy's refund share of each recorded part
+        paid_out.scaled(|part| bps_of(part, policy.refund_bps as u64))?
+    };
     
-    // Use the recorded purchase price for refund
-    let price = ctx.accounts.user_app_access.purchase_price;
-    let platform_fee_bps = ctx.accounts.app_factory.platform_fee_bps;
-    
-    // Calculate refund amounts
-    let platform_fee = price
-        .checked_mul(platform_fee_bps as u64)
-        .ok_or(AppFactoryError::MathOverflow)?
-        .checked_div(10000)
-        .ok_or(AppFactoryError::MathOverflow)?;
-    
-    let creator_refund = price
-        .checked_sub(platform_fee)
-        .ok_or(AppFactoryError::MathOverflow)?;
-    
+    // Soulbound SFTs are frozen; thaw so the owner can burn
+    if ctx.accounts.user_sft_ata.is_frozen() {
+        thaw_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            ctx.accounts.app_registration.bump,
+        )?;
+    }
+
     // Burn the SFT
     let burn_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
@@ -165,52 +553,104 @@ pub fn refund_purchase(
     );
     token::burn(burn_ctx, 1)?;
     
-    // Validate balances before attempting transfer to avoid DoS via 0-balance accounts
-    require!(ctx.accounts.creator_defai_ata.amount >= creator_refund, AppFactoryError::InsufficientCreatorBalance);
-    require!(ctx.accounts.treasury_defai_ata.amount >= platform_fee, AppFactoryError::InsufficientCreatorBalance);
-
-    // Refund from creator (minus platform fee)
-    let creator_transfer_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.creator_defai_ata.to_account_info(),
-            to: ctx.accounts.user_defai_ata.to_account_info(),
-            authority: ctx.accounts.creator.to_account_info(),
-        },
-    );
-    token::transfer(creator_transfer_ctx, creator_refund)?;
-    
-    // Refund platform fee from treasury
-    let treasury_transfer_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        Transfer {
-            from: ctx.accounts.treasury_defai_ata.to_account_info(),
-            to: ctx.accounts.user_defai_ata.to_account_info(),
-            authority: ctx.accounts.treasury.to_account_info(),
-        },
-    );
-    token::transfer(treasury_transfer_ctx, platform_fee)?;
+    let refund_total = pay_refund(
+        &ctx.accounts.creator_defai_ata,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.creator,
+        &ctx.accounts.treasury,
+        &ctx.accounts.token_program,
+        ctx.accounts.creator_vesting.as_deref_mut(),
+        ctx.accounts.vesting_vault.as_deref(),
+        app_id,
+        &refund,
+    )?;
     
     // Update supply count
     ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
         .checked_sub(1)
         .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_refund(refund_total)?;
+    ctx.accounts.factory_stats.record_refund(refund_total)?;
+    
+    // Remove the buyer's review, if any, and back it out of the aggregates
+    let review_removed = remove_refunded_review(
+        &ctx.accounts.user_review.to_account_info(),
+        &mut ctx.accounts.app_registration,
+        &ctx.accounts.user.to_account_info(),
+    )?;
     
     // Emit event
-    emit!(AppRefunded {
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppRefunded {
         app_id,
         user: ctx.accounts.user.key(),
-        refund_amount: price,
+        refund_amount: refund_total,
         reason,
+        review_removed,
         timestamp: Clock::get()?.unix_timestamp,
     });
     
     msg!(
         "Refunded {} DEFAI to user {} for app {}",
-        price,
+        refund_total,
         ctx.accounts.user.key(),
         app_id
     );
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Refund the unused part of a subscription's paid span and end it now. Each
+// recorded part of the span's payments comes back pro rata, from whoever
+// received it, as for purchases.
+pub fn refund_subscription(ctx: Context<RefundSubscription>, app_id: u64) -> Result<()> {
+    let policy = ctx.accounts.app_registration.refund_policy.unwrap_or(RefundPolicy::DEFAULT);
+    require!(policy.window_days > 0, RefundError::RefundsDisabled);
+
+    let now = Clock::get()?.unix_timestamp;
+    let subscription = &ctx.accounts.subscription;
+    let (period_start, paid_through, paid_out) =
+        (subscription.period_start, subscription.paid_through, subscription.paid_out);
+    require!(paid_out != PurchaseSplit::default(), RefundError::NoRecordedPayment);
+    require!(paid_out.charity == 0 && paid_out.split == 0, RefundError::ThirdPartyPayout);
+    let refund = paid_out.scaled(|part| pro_rata_refund(part, period_start, paid_through, now))?;
+    require!(refund.refundable()? > 0, RefundError::RefundWindowExpired);
+
+    let refund_total = pay_refund(
+        &ctx.accounts.creator_defai_ata,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.creator,
+        &ctx.accounts.treasury,
+        &ctx.accounts.token_program,
+        ctx.accounts.creator_vesting.as_deref_mut(),
+        ctx.accounts.vesting_vault.as_deref(),
+        app_id,
+        &refund,
+    )?;
+    ctx.accounts.app_registration.record_refund(refund_total)?;
+    ctx.accounts.factory_stats.record_refund(refund_total)?;
+
+    // Access ends with the refund
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.paid_through = now;
+    subscription.paid_amount = 0;
+    subscription.paid_out = PurchaseSplit::default();
+    subscription.cancelled = true;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), SubscriptionRefunded {
+        app_id,
+        user: subscription.user,
+        refund_amount: refund_total,
+        unused_seconds: paid_through - now,
+        timestamp: now,
+    });
+
+    msg!(
+        "Refunded {} DEFAI of unused subscription to user {} for app {}",
+        refund_total,
+        subscription.user,
+        app_id
+    );
+    Ok(())
+}
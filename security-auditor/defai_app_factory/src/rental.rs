@@ -0,0 +1,497 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, Approve, Burn, ThawAccount},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, CleanupError,
+    APP_REGISTRATION_SEED, calculate_fee_split, execute_token_transfers, mint_app_sft,
+    distribute_creator_amount, PresaleError, freeze_app_sft_account,
+    CreatorVesting, creator_payout_ata, pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+const SECONDS_PER_DAY: i64 = 86400;
+#[constant]
+pub const MAX_RENTAL_DAYS: u16 = 365;
+
+// Rental SFTs are minted with the app_registration PDA as delegate and the
+// token account frozen, so the renter cannot move the SFT elsewhere and the
+// program can burn it once the rental expires.
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetRentalTerms<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RentAppAccess<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user,
+        constraint = user_defai_ata.amount >= app_registration.rental_price
+            @ AppFactoryError::InsufficientBalance
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ExpireAccess<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        has_one = user,
+        close = rent_payer
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: Receives the access record's rent; must be whoever paid it
+    #[account(
+        mut,
+        address = user_app_access.rent_recipient() @ CleanupError::WrongRentPayer
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = user_app_access.sft_token_account
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Renter; bound via has_one
+    pub user: AccountInfo<'info>,
+
+    // Anyone may crank an expired rental
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct RentalTermsUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub rental_price: u64,
+    pub rental_days: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppRented {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub price: u64,
+    pub platform_fee: u64,
+    pub creator_amount: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+    pub burned: u64,                    // DEFAI burned from the payment (taken from platform_fee)
+}
+
+#[event]
+pub struct AccessExpired {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub caller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum RentalError {
+    #[msg("Rentals are not enabled for this app")]
+    RentalsDisabled,
+    #[msg("Rental price must be below the purchase price")]
+    RentalPriceTooHigh,
+    #[msg("Invalid rental duration (1-365 days)")]
+    InvalidRentalDuration,
+    #[msg("Access is not a rental")]
+    NotARental,
+    #[msg("Rental has not expired yet")]
+    RentalNotExpired,
+}
+
+pub fn set_rental_terms(
+    ctx: Context<SetRentalTerms>,
+    app_id: u64,
+    rental_price: u64,
+    rental_days: u16,
+) -> Result<()> {
+    let app_registration = &mut ctx.accounts.app_registration;
+
+    // A zero price disables rentals
+    if rental_price > 0 {
+        require!(
+            rental_price < app_registration.list_price(Clock::get()?.unix_timestamp),
+            RentalError::RentalPriceTooHigh
+        );
+        require!(
+            rental_days > 0 && rental_days <= MAX_RENTAL_DAYS,
+            RentalError::InvalidRentalDuration
+        );
+    }
+
+    app_registration.rental_price = rental_price;
+    app_registration.rental_days = rental_days;
+
+    emit_versioned!(RentalTermsUpdated {
+        app_id,
+        rental_price,
+        rental_days,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} rental terms: {} DEFAI for {} days", app_id, rental_price, rental_days);
+    Ok(())
+}
+
+pub fn rent_app_access<'info>(
+    ctx: Context<'_, '_, '_, 'info, RentAppAccess<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(
+        !app_registration.in_presale(Clock::get()?.unix_timestamp),
+        PresaleError::PresaleActive
+    );
+    require!(app_registration.rental_price > 0, RentalError::RentalsDisabled);
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    let price = app_registration.rental_price;
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Delegate the rented SFT to the registration PDA so it can be burned on
+    // expiry; done before minting since a soulbound mint leaves the ATA frozen
+    let approve_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.user_sft_ata.to_account_info(),
+            delegate: ctx.accounts.app_registration.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::approve(approve_ctx, 1)?;
+
+    // Mint SFT
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    // Freeze so the delegation can't be revoked and the SFT can't be moved
+    if !ctx.accounts.app_registration.soulbound {
+        freeze_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
+    // Update supply
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    ctx.accounts.app_registration.record_sale(price, Clock::get()?.unix_timestamp)?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let expires_at = now
+        .checked_add(ctx.accounts.app_registration.rental_days as i64 * SECONDS_PER_DAY)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    // Record access
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = expires_at;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppRented {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        expires_at,
+        timestamp: now,
+        burned,
+    });
+
+    msg!("User rented app {} until {}", app_id, expires_at);
+    Ok(())
+}
+
+pub fn expire_access(ctx: Context<ExpireAccess>, app_id: u64) -> Result<()> {
+    let expires_at = ctx.accounts.user_app_access.expires_at;
+    require!(expires_at > 0, RentalError::NotARental);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= expires_at, RentalError::RentalNotExpired);
+
+    let bump = ctx.accounts.app_registration.bump;
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let signer_seeds = &[&mint_seeds[..]];
+
+    // Thaw, then burn the rented SFT as delegate
+    let thaw_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        ThawAccount {
+            account: ctx.accounts.user_sft_ata.to_account_info(),
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            authority: ctx.accounts.app_registration.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::thaw_account(thaw_ctx)?;
+
+    let burn_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            from: ctx.accounts.user_sft_ata.to_account_info(),
+            authority: ctx.accounts.app_registration.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::burn(burn_ctx, 1)?;
+
+    // Release the supply slot
+    ctx.accounts.app_registration.current_supply = ctx.accounts.app_registration.current_supply
+        .checked_sub(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    emit_versioned!(AccessExpired {
+        app_id,
+        user: ctx.accounts.user.key(),
+        caller: ctx.accounts.caller.key(),
+        timestamp: now,
+    });
+
+    msg!("Rental of app {} by {} expired", app_id, ctx.accounts.user.key());
+    Ok(())
+}
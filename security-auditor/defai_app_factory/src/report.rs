@@ -0,0 +1,272 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError};
+use defai_common::emit_versioned;
+
+// Buyer reports against malicious apps. Any wallet holding an access record
+// for the app can file one report, citing an IPFS CID with the evidence. Once
+// the app's open reports reach the factory's threshold its sales are suspended
+// (deactivated, and the creator cannot reactivate) until the authority reviews
+// enough reports. Dismissing lifts the suspension once the count drops below
+// the threshold; upholding flags the app, which keeps it off sale for good and
+// exposes the creator's listing stake to `slash_creator_stake`.
+
+const MAX_REASON_CID_LEN: usize = 64;
+
+#[account]
+pub struct AppReport {
+    pub app_id: u64,
+    pub reporter: Pubkey,
+    pub reason_cid: String,             // IPFS CID of the evidence
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl AppReport {
+    pub const LEN: usize = 8 + 8 + 32 + (4 + MAX_REASON_CID_LEN) + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct SetReportThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ReportApp<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    // Only purchasers may report
+    #[account(
+        seeds = [b"user_app_access".as_ref(), reporter.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump
+    )]
+    pub user_app_access: Account<'info, UserAppAccess>,
+
+    #[account(
+        init,
+        payer = reporter,
+        space = AppReport::LEN,
+        seeds = [b"app_report".as_ref(), reporter.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub app_report: Account<'info, AppReport>,
+
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64, reporter: Pubkey)]
+pub struct ReviewReport<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    // Closed on review; rent returns to the reporter
+    #[account(
+        mut,
+        seeds = [b"app_report".as_ref(), reporter.as_ref(), &app_id.to_le_bytes()],
+        bump = app_report.bump,
+        close = reporter_account
+    )]
+    pub app_report: Account<'info, AppReport>,
+
+    /// CHECK: The report's author
+    #[account(mut, address = reporter)]
+    pub reporter_account: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct ReportThresholdSet {
+    pub schema_version: u8,
+    pub report_threshold: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppReported {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reporter: Pubkey,
+    pub reason_cid: String,
+    pub open_reports: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReportReviewed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reporter: Pubkey,
+    pub upheld: bool,
+    pub open_reports: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppSuspensionChanged {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub suspended: bool,
+    pub open_reports: u16,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum ReportError {
+    #[msg("Reason CID too long (max 64 characters)")]
+    ReasonCidTooLong,
+    #[msg("App is suspended pending review of reports")]
+    AppSuspended,
+}
+
+pub fn set_report_threshold(ctx: Context<SetReportThreshold>, report_threshold: u16) -> Result<()> {
+    ctx.accounts.app_factory.report_threshold = report_threshold;
+
+    emit_versioned!(ReportThresholdSet {
+        report_threshold,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Apps are suspended at {} open reports (0 = never)", report_threshold);
+    Ok(())
+}
+
+pub fn report_app(ctx: Context<ReportApp>, app_id: u64, reason_cid: String) -> Result<()> {
+    require!(reason_cid.len() <= MAX_REASON_CID_LEN, ReportError::ReasonCidTooLong);
+
+    let now = Clock::get()?.unix_timestamp;
+    let report = &mut ctx.accounts.app_report;
+    report.app_id = app_id;
+    report.reporter = ctx.accounts.reporter.key();
+    report.reason_cid = reason_cid.clone();
+    report.created_at = now;
+    report.bump = ctx.bumps.app_report;
+
+    let threshold = ctx.accounts.app_factory.report_threshold;
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.open_reports = app_registration.open_reports
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    let open_reports = app_registration.open_reports;
+
+    emit_versioned!(AppReported {
+        app_id,
+        reporter: report.reporter,
+        reason_cid,
+        open_reports,
+        timestamp: now,
+    });
+
+    // Suspend sales once the threshold is reached
+    if threshold > 0 && open_reports >= threshold && !app_registration.suspended {
+        app_registration.suspended = true;
+        ctx.accounts.factory_stats.record_status_change(app_registration.is_active, false)?;
+        app_registration.is_active = false;
+
+        emit_versioned!(AppSuspensionChanged {
+            app_id,
+            suspended: true,
+            open_reports,
+            timestamp: now,
+        });
+        msg!("App {} suspended after {} reports", app_id, open_reports);
+    }
+
+    msg!("App {} reported by {}", app_id, report.reporter);
+    Ok(())
+}
+
+pub fn review_report(
+    ctx: Context<ReviewReport>,
+    app_id: u64,
+    reporter: Pubkey,
+    uphold: bool,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let threshold = ctx.accounts.app_factory.report_threshold;
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.open_reports = app_registration.open_reports.saturating_sub(1);
+    let open_reports = app_registration.open_reports;
+
+    if uphold {
+        // Confirmed malicious: off sale permanently
+        app_registration.flagged = true;
+        ctx.accounts.factory_stats.record_status_change(app_registration.is_active, false)?;
+        app_registration.is_active = false;
+    } else if app_registration.suspended && (threshold == 0 || open_reports < threshold) {
+        // The creator may reactivate once enough reports are dismissed
+        app_registration.suspended = false;
+
+        emit_versioned!(AppSuspensionChanged {
+            app_id,
+            suspended: false,
+            open_reports,
+            timestamp: now,
+        });
+    }
+
+    emit_versioned!(ReportReviewed {
+        app_id,
+        reporter,
+        upheld: uphold,
+        open_reports,
+        timestamp: now,
+    });
+
+    msg!(
+        "Report by {} on app {} {}",
+        reporter,
+        app_id,
+        if uphold { "upheld" } else { "dismissed" }
+    );
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use defai_common::{bps_of, emit_versioned};
+
+use crate::{AppRegistration, AppFactoryError, VestingError};
+
+pub const MAX_SPLIT_RECIPIENTS: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SplitRecipient {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+impl SplitRecipient {
+    pub const LEN: usize = 32 + 2;
+}
+
+// Routes the creator portion of each purchase across several wallets.
+// Recipient DEFAI ATAs are passed as remaining accounts, in recipient order.
+#[account]
+pub struct RevenueSplit {
+    pub app_id: u64,
+    pub recipients: Vec<SplitRecipient>,
+    pub bump: u8,
+}
+
+impl RevenueSplit {
+    pub const LEN: usize = 8 + 8 + (4 + MAX_SPLIT_RECIPIENTS * SplitRecipient::LEN) + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetRevenueSplit<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = RevenueSplit::LEN,
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RemoveRevenueSplit<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump = revenue_split.bump,
+        close = creator
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct RevenueSplitUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub recipients: Vec<SplitRecipient>,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum RevenueSplitError {
+    #[msg("Revenue split must have 1-5 recipients")]
+    InvalidRecipientCount,
+    #[msg("Revenue split shares must sum to 10000 basis points")]
+    InvalidShareTotal,
+    #[msg("Duplicate revenue split recipient")]
+    DuplicateRecipient,
+    #[msg("Missing or invalid recipient token account")]
+    InvalidRecipientAccount,
+    #[msg("Revenue split account is not owned by the factory")]
+    InvalidRevenueSplit,
+}
+
+pub fn set_revenue_split(
+    ctx: Context<SetRevenueSplit>,
+    app_id: u64,
+    recipients: Vec<SplitRecipient>,
+) -> Result<()> {
+    require!(
+        ctx.accounts.app_registration.payout_vesting_days == 0,
+        VestingError::VestedAppUnsupportedPath
+    );
+    require!(
+        !recipients.is_empty() && recipients.len() <= MAX_SPLIT_RECIPIENTS,
+        RevenueSplitError::InvalidRecipientCount
+    );
+
+    let mut total_bps: u32 = 0;
+    for (i, recipient) in recipients.iter().enumerate() {
+        require!(recipient.share_bps > 0, RevenueSplitError::InvalidShareTotal);
+        require!(
+            !recipients[..i].iter().any(|r| r.wallet == recipient.wallet),
+            RevenueSplitError::DuplicateRecipient
+        );
+        total_bps += recipient.share_bps as u32;
+    }
+    require!(total_bps == 10000, RevenueSplitError::InvalidShareTotal);
+
+    let revenue_split = &mut ctx.accounts.revenue_split;
+    revenue_split.app_id = app_id;
+    revenue_split.recipients = recipients.clone();
+    revenue_split.bump = ctx.bumps.revenue_split;
+
+    emit_versioned!(RevenueSplitUpdated {
+        app_id,
+        recipients,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Revenue split updated for app {}", app_id);
+    Ok(())
+}
+
+pub fn remove_revenue_split(_ctx: Context<RemoveRevenueSplit>, app_id: u64) -> Result<()> {
+    emit_versioned!(RevenueSplitUpdated {
+        app_id,
+        recipients: Vec::new(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Revenue split removed for app {}; creator receives full share", app_id);
+    Ok(())
+}
+
+// Reads the app's split PDA; None when it was never set or has been removed.
+// Purchases always pass the PDA, so an active split can't be left out.
+pub fn load_revenue_split(revenue_split: &AccountInfo) -> Result<Option<RevenueSplit>> {
+    if revenue_split.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*revenue_split.owner, crate::ID, RevenueSplitError::InvalidRevenueSplit);
+    Ok(Some(RevenueSplit::try_deserialize(&mut &revenue_split.try_borrow_data()?[..])?))
+}
+
+// Pays the creator portion across split recipients. Returns the amount still
+// owed to the creator's own ATA (all of it when the app has no split).
+pub fn distribute_creator_amount<'info>(
+    revenue_split: &AccountInfo<'info>,
+    recipient_atas: &[AccountInfo<'info>],
+    user: &AccountInfo<'info>,
+    user_defai_ata: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    defai_mint: Pubkey,
+    creator_amount: u64,
+) -> Result<u64> {
+    let Some(split) = load_revenue_split(revenue_split)? else {
+        return Ok(creator_amount);
+    };
+    require!(
+        recipient_atas.len() >= split.recipients.len(),
+        RevenueSplitError::InvalidRecipientAccount
+    );
+
+    // Compute shares first; rounding dust goes to the first recipient
+    let mut shares = [0u64; MAX_SPLIT_RECIPIENTS];
+    let mut allocated = 0u64;
+    for (i, recipient) in split.recipients.iter().enumerate() {
+        shares[i] = bps_of(creator_amount, recipient.share_bps as u64)?;
+        allocated = allocated.checked_add(shares[i]).ok_or(AppFactoryError::MathOverflow)?;
+    }
+    shares[0] = shares[0]
+        .checked_add(creator_amount.checked_sub(allocated).ok_or(AppFactoryError::MathOverflow)?)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    for (i, recipient) in split.recipients.iter().enumerate() {
+        let ata_info = &recipient_atas[i];
+        require!(
+            ata_info.owner == &token::ID,
+            RevenueSplitError::InvalidRecipientAccount
+        );
+        let ata = TokenAccount::try_deserialize(&mut &ata_info.try_borrow_data()?[..])?;
+        require!(
+            ata.owner == recipient.wallet && ata.mint == defai_mint,
+            RevenueSplitError::InvalidRecipientAccount
+        );
+
+        if shares[i] == 0 {
+            continue;
+        }
+        let transfer_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from: user_defai_ata.clone(),
+                to: ata_info.clone(),
+                authority: user.clone(),
+            },
+        );
+        token::transfer(transfer_ctx, shares[i])?;
+    }
+
+    Ok(0)
+}
@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
-use crate::{UserAppAccess, AppFactoryError};
+use crate::{AppFactory, AppRegistration, UserAppAccess, AppFactoryError};
+use defai_common::emit_versioned;
+
+// Reviews are tied to the purchase they were written for: the reviewer's
+// UserAppAccess must be live to submit or edit, and `purge_review` lets anyone
+// close a review whose access was closed, expired or replaced by a newer
+// purchase. Submissions must also land within the platform's review window.
 
 #[account]
 pub struct AppReview {
@@ -9,15 +15,31 @@ pub struct AppReview {
     pub comment_cid: String, // IPFS CID for comment
     pub timestamp: i64,
     pub bump: u8,
+    pub hidden: bool, // Hidden by moderation; excluded from aggregates
+    pub appealed: bool, // Reviewer has appealed the moderation
+    pub purchased_at: i64, // purchased_at of the access this review belongs to
 }
 
 impl AppReview {
-    pub const LEN: usize = 8 + 8 + 32 + 1 + (4 + 46) + 8 + 1; // ~100 bytes
+    pub const LEN: usize = 8 + 8 + 32 + 1 + (4 + 46) + 8 + 1 + 1 + 1 + 8; // ~110 bytes
+}
+
+// Whether `access` is the live purchase `review` was written for
+fn access_backs_review(access: &UserAppAccess, review: &AppReview, now: i64) -> bool {
+    access.purchased_at == review.purchased_at
+        && (access.expires_at == 0 || now < access.expires_at)
 }
 
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct SubmitReview<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
     #[account(
         init,
         payer = user,
@@ -27,6 +49,13 @@ pub struct SubmitReview<'info> {
     )]
     pub review: Account<'info, AppReview>,
     
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+    
     #[account(
         seeds = [b"user_app_access", user.key().as_ref(), &app_id.to_le_bytes()],
         bump = user_app_access.bump,
@@ -43,6 +72,13 @@ pub struct SubmitReview<'info> {
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct UpdateReview<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
     #[account(
         mut,
         seeds = [b"app_review", reviewer.key().as_ref(), &app_id.to_le_bytes()],
@@ -51,11 +87,147 @@ pub struct UpdateReview<'info> {
     )]
     pub review: Account<'info, AppReview>,
     
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        seeds = [b"user_app_access", reviewer.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump
+    )]
+    pub user_app_access: Account<'info, UserAppAccess>,
+    
+    pub reviewer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct DeleteReview<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_review", reviewer.key().as_ref(), &app_id.to_le_bytes()],
+        bump = review.bump,
+        has_one = reviewer @ AppFactoryError::UnauthorizedReviewer,
+        close = reviewer
+    )]
+    pub review: Account<'info, AppReview>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+    
+    #[account(mut)]
     pub reviewer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(app_id: u64, reviewer: Pubkey)]
+pub struct ModerateReview<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_review", reviewer.as_ref(), &app_id.to_le_bytes()],
+        bump = review.bump
+    )]
+    pub review: Account<'info, AppReview>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct AppealReview<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
+    #[account(
+        mut,
+        seeds = [b"app_review", reviewer.key().as_ref(), &app_id.to_le_bytes()],
+        bump = review.bump,
+        has_one = reviewer @ AppFactoryError::UnauthorizedReviewer
+    )]
+    pub review: Account<'info, AppReview>,
+    
+    pub reviewer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PurgeReview<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_review", reviewer.key().as_ref(), &app_id.to_le_bytes()],
+        bump = review.bump,
+        has_one = reviewer @ AppFactoryError::UnauthorizedReviewer,
+        close = reviewer
+    )]
+    pub review: Account<'info, AppReview>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    /// CHECK: The reviewer's access PDA; may have been closed
+    #[account(
+        seeds = [b"user_app_access", reviewer.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: UncheckedAccount<'info>,
+
+    /// CHECK: Review author; receives the review's rent
+    #[account(mut)]
+    pub reviewer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReviewWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
 #[event]
 pub struct ReviewSubmitted {
+    pub schema_version: u8,
     pub app_id: u64,
     pub reviewer: Pubkey,
     pub rating: u8,
@@ -65,6 +237,7 @@ pub struct ReviewSubmitted {
 
 #[event]
 pub struct ReviewUpdated {
+    pub schema_version: u8,
     pub app_id: u64,
     pub reviewer: Pubkey,
     pub new_rating: u8,
@@ -72,6 +245,47 @@ pub struct ReviewUpdated {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ReviewModerated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reviewer: Pubkey,
+    pub hidden: bool,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewAppealed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reviewer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewDeleted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reviewer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewPurged {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub reviewer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewWindowSet {
+    pub schema_version: u8,
+    pub days: u16,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum ReviewError {
     #[msg("Invalid rating - must be between 1 and 5")]
@@ -82,6 +296,66 @@ pub enum ReviewError {
     UnauthorizedReviewer,
     #[msg("Comment CID too long (max 46 characters)")]
     CommentCidTooLong,
+    #[msg("Review is hidden by moderation")]
+    ReviewHidden,
+    #[msg("Only hidden reviews can be appealed")]
+    ReviewNotHidden,
+    #[msg("Review already appealed")]
+    AlreadyAppealed,
+    #[msg("Review already in requested moderation state")]
+    ModerationUnchanged,
+    #[msg("Review window for this purchase has closed")]
+    ReviewWindowClosed,
+    #[msg("Access is expired or belongs to a different purchase")]
+    AccessNotLive,
+    #[msg("Review is still backed by live access")]
+    ReviewStillValid,
+}
+
+pub(crate) fn remove_from_aggregates(app_registration: &mut AppRegistration, rating: u8) -> Result<()> {
+    app_registration.review_count = app_registration.review_count
+        .checked_sub(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    app_registration.rating_sum = app_registration.rating_sum
+        .checked_sub(rating as u64)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    Ok(())
+}
+
+// Closes a refunded buyer's review, if they left one, backing it out of the
+// aggregates and returning its rent to `recipient`. Returns whether one existed.
+pub(crate) fn remove_refunded_review<'info>(
+    review_info: &AccountInfo<'info>,
+    app_registration: &mut AppRegistration,
+    recipient: &AccountInfo<'info>,
+) -> Result<bool> {
+    if review_info.data_is_empty() {
+        return Ok(false);
+    }
+    require_keys_eq!(*review_info.owner, crate::ID);
+    let review = AppReview::try_deserialize(&mut &review_info.try_borrow_data()?[..])?;
+    if !review.hidden {
+        remove_from_aggregates(app_registration, review.rating)?;
+    }
+
+    let review_lamports = review_info.lamports();
+    **recipient.try_borrow_mut_lamports()? = recipient.lamports()
+        .checked_add(review_lamports)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    **review_info.try_borrow_mut_lamports()? = 0;
+    review_info.assign(&System::id());
+    review_info.realloc(0, false)?;
+    Ok(true)
+}
+
+fn add_to_aggregates(app_registration: &mut AppRegistration, rating: u8) -> Result<()> {
+    app_registration.review_count = app_registration.review_count
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    app_registration.rating_sum = app_registration.rating_sum
+        .checked_add(rating as u64)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    Ok(())
 }
 
 pub fn submit_review(
@@ -90,8 +364,22 @@ pub fn submit_review(
     rating: u8,
     comment_cid: String,
 ) -> Result<()> {
-    require!(rating >= 1 && rating <= 5, ReviewError::InvalidRating);
+    require!((1..=5).contains(&rating), ReviewError::InvalidRating);
     require!(comment_cid.len() <= 46, ReviewError::CommentCidTooLong); // IPFS CID v1 length
+
+    let now = Clock::get()?.unix_timestamp;
+    let access = &ctx.accounts.user_app_access;
+    require!(
+        access.expires_at == 0 || now < access.expires_at,
+        ReviewError::AccessNotLive
+    );
+    let window_days = ctx.accounts.app_factory.review_window_days;
+    if window_days > 0 {
+        let window_end = access.purchased_at
+            .checked_add(window_days as i64 * 86400)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        require!(now <= window_end, ReviewError::ReviewWindowClosed);
+    }
     
     // Create review
     let review = &mut ctx.accounts.review;
@@ -101,9 +389,15 @@ pub fn submit_review(
     review.comment_cid = comment_cid.clone();
     review.timestamp = Clock::get()?.unix_timestamp;
     review.bump = ctx.bumps.review;
+    review.hidden = false;
+    review.appealed = false;
+    review.purchased_at = access.purchased_at;
+    
+    // Update rating aggregates
+    add_to_aggregates(&mut ctx.accounts.app_registration, rating)?;
     
     // Emit event
-    emit!(ReviewSubmitted {
+    emit_versioned!(ReviewSubmitted {
         app_id,
         reviewer: ctx.accounts.user.key(),
         rating,
@@ -123,21 +417,36 @@ pub fn submit_review(
 
 pub fn update_review(
     ctx: Context<UpdateReview>,
+    _app_id: u64,
     new_rating: u8,
     new_comment_cid: String,
 ) -> Result<()> {
-    require!(new_rating >= 1 && new_rating <= 5, ReviewError::InvalidRating);
+    require!((1..=5).contains(&new_rating), ReviewError::InvalidRating);
     require!(new_comment_cid.len() <= 46, ReviewError::CommentCidTooLong);
     
     let review = &mut ctx.accounts.review;
+    require!(!review.hidden, ReviewError::ReviewHidden);
+    require!(
+        access_backs_review(&ctx.accounts.user_app_access, review, Clock::get()?.unix_timestamp),
+        ReviewError::AccessNotLive
+    );
     let app_id = review.app_id;
+    let old_rating = review.rating;
     
     review.rating = new_rating;
     review.comment_cid = new_comment_cid.clone();
     review.timestamp = Clock::get()?.unix_timestamp;
     
+    // Swap the old rating for the new one in the aggregate
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.rating_sum = app_registration.rating_sum
+        .checked_sub(old_rating as u64)
+        .ok_or(AppFactoryError::MathOverflow)?
+        .checked_add(new_rating as u64)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    
     // Emit event
-    emit!(ReviewUpdated {
+    emit_versioned!(ReviewUpdated {
         app_id,
         reviewer: ctx.accounts.reviewer.key(),
         new_rating,
@@ -153,4 +462,125 @@ pub fn update_review(
     );
     
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn delete_review(ctx: Context<DeleteReview>, app_id: u64) -> Result<()> {
+    // Hidden reviews were already removed from the aggregates
+    if !ctx.accounts.review.hidden {
+        remove_from_aggregates(&mut ctx.accounts.app_registration, ctx.accounts.review.rating)?;
+    }
+    
+    // Emit event
+    emit_versioned!(ReviewDeleted {
+        app_id,
+        reviewer: ctx.accounts.reviewer.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!(
+        "User {} deleted their review for app {}",
+        ctx.accounts.reviewer.key(),
+        app_id
+    );
+    
+    Ok(())
+}
+
+pub fn moderate_review(
+    ctx: Context<ModerateReview>,
+    app_id: u64,
+    reviewer: Pubkey,
+    hide: bool,
+) -> Result<()> {
+    let review = &mut ctx.accounts.review;
+    require!(review.hidden != hide, ReviewError::ModerationUnchanged);
+    
+    if hide {
+        remove_from_aggregates(&mut ctx.accounts.app_registration, review.rating)?;
+    } else {
+        // Restoring a review resolves any pending appeal
+        add_to_aggregates(&mut ctx.accounts.app_registration, review.rating)?;
+        review.appealed = false;
+    }
+    review.hidden = hide;
+    
+    // Emit event
+    emit_versioned!(ReviewModerated {
+        app_id,
+        reviewer,
+        hidden: hide,
+        moderator: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!(
+        "Review by {} for app {} is now {}",
+        reviewer,
+        app_id,
+        if hide { "hidden" } else { "visible" }
+    );
+    
+    Ok(())
+}
+
+pub fn appeal_review(ctx: Context<AppealReview>, app_id: u64) -> Result<()> {
+    let review = &mut ctx.accounts.review;
+    require!(review.hidden, ReviewError::ReviewNotHidden);
+    require!(!review.appealed, ReviewError::AlreadyAppealed);
+    review.appealed = true;
+    
+    // Emit event
+    emit_versioned!(ReviewAppealed {
+        app_id,
+        reviewer: ctx.accounts.reviewer.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!(
+        "User {} appealed moderation of their review for app {}",
+        ctx.accounts.reviewer.key(),
+        app_id
+    );
+    
+    Ok(())
+}
+
+// Close a review whose purchase is gone (permissionless cleanup)
+pub fn purge_review(ctx: Context<PurgeReview>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let access_info = ctx.accounts.user_app_access.to_account_info();
+    if !access_info.data_is_empty() {
+        require_keys_eq!(*access_info.owner, crate::ID);
+        let access = UserAppAccess::try_deserialize(&mut &access_info.try_borrow_data()?[..])?;
+        require!(
+            !access_backs_review(&access, &ctx.accounts.review, now),
+            ReviewError::ReviewStillValid
+        );
+    }
+
+    // Hidden reviews were already removed from the aggregates
+    if !ctx.accounts.review.hidden {
+        remove_from_aggregates(&mut ctx.accounts.app_registration, ctx.accounts.review.rating)?;
+    }
+
+    emit_versioned!(ReviewPurged {
+        app_id,
+        reviewer: ctx.accounts.reviewer.key(),
+        timestamp: now,
+    });
+
+    msg!("Review by {} for app {} purged", ctx.accounts.reviewer.key(), app_id);
+    Ok(())
+}
+
+pub fn set_review_window(ctx: Context<SetReviewWindow>, days: u16) -> Result<()> {
+    ctx.accounts.app_factory.review_window_days = days;
+
+    emit_versioned!(ReviewWindowSet {
+        days,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Review window set to {} days", days);
+    Ok(())
+}
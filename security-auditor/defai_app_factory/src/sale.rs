@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use defai_common::{bps_of, emit_versioned};
+
+use crate::{AppRegistration, AppFactoryError};
+
+// A scheduled sale overrides the DEFAI price of standard purchases
+// (purchase_app_access_v2 / purchase_app_with_init) while it is live.
+
+#[account]
+pub struct Sale {
+    pub app_id: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub sale_price: u64,                // Fixed sale price (0 = use discount_bps)
+    pub discount_bps: u16,              // Percentage off the list price
+    pub bump: u8,
+}
+
+impl Sale {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 2 + 1;
+
+    pub fn is_live(&self, now: i64) -> bool {
+        now >= self.start_time && now < self.end_time
+    }
+
+    // Effective price for a list price, or None when the sale isn't running
+    pub fn price_at(&self, list_price: u64, now: i64) -> Result<Option<u64>> {
+        if !self.is_live(now) {
+            return Ok(None);
+        }
+        if self.sale_price > 0 {
+            return Ok(Some(self.sale_price.min(list_price)));
+        }
+        let price = bps_of(list_price, (10000 - self.discount_bps) as u64)?;
+        Ok(Some(price))
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ScheduleSale<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = Sale::LEN,
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CancelSale<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"sale".as_ref(), &app_id.to_le_bytes()],
+        bump = sale.bump,
+        close = creator
+    )]
+    pub sale: Account<'info, Sale>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct SaleScheduled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub sale_price: u64,
+    pub discount_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SaleCancelled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum SaleError {
+    #[msg("Sale end must be after start and in the future")]
+    InvalidSaleWindow,
+    #[msg("Set either a sale price below list price or a discount (1-10000 bps)")]
+    InvalidSaleDiscount,
+}
+
+pub fn schedule_sale(
+    ctx: Context<ScheduleSale>,
+    app_id: u64,
+    start_time: i64,
+    end_time: i64,
+    sale_price: u64,
+    discount_bps: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(end_time > start_time && end_time > now, SaleError::InvalidSaleWindow);
+    require!(
+        (sale_price > 0 && sale_price < ctx.accounts.app_registration.list_price(now) && discount_bps == 0)
+            || (sale_price == 0 && discount_bps > 0 && discount_bps <= 10000),
+        SaleError::InvalidSaleDiscount
+    );
+
+    let sale = &mut ctx.accounts.sale;
+    sale.app_id = app_id;
+    sale.start_time = start_time;
+    sale.end_time = end_time;
+    sale.sale_price = sale_price;
+    sale.discount_bps = discount_bps;
+    sale.bump = ctx.bumps.sale;
+
+    emit_versioned!(SaleScheduled {
+        app_id,
+        start_time,
+        end_time,
+        sale_price,
+        discount_bps,
+        timestamp: now,
+    });
+
+    msg!("Sale scheduled for app {} from {} to {}", app_id, start_time, end_time);
+    Ok(())
+}
+
+pub fn cancel_sale(_ctx: Context<CancelSale>, app_id: u64) -> Result<()> {
+    emit_versioned!(SaleCancelled {
+        app_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Sale cancelled for app {}", app_id);
+    Ok(())
+}
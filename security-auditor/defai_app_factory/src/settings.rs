@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_versioned, schedule, require_elapsed, ADMIN_TIMELOCK_DURATION};
+
+use crate::{AppFactory, AppFactoryError, PlatformSettingsUpdated};
+
+// Timelocked platform settings. `update_platform_settings` applies fee
+// decreases immediately but only queues a treasury change or a fee increase;
+// the authority applies the queued change once SETTINGS_TIMELOCK_DURATION has
+// passed, giving creators and buyers time to react to a compromised key.
+// The notice period creators must give before raising an app's price is set
+// here too.
+
+pub const SETTINGS_TIMELOCK_DURATION: i64 = ADMIN_TIMELOCK_DURATION; // 48 hours
+
+// Upper bound on the notice creators must give before a price increase
+#[constant]
+pub const MAX_PRICE_NOTICE_HOURS: u16 = 168;
+
+#[derive(Accounts)]
+pub struct PendingPlatformSettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceNoticePeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event]
+pub struct PriceNoticePeriodSet {
+    pub schema_version: u8,
+    pub hours: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformSettingsChangeProposed {
+    pub schema_version: u8,
+    pub platform_fee_bps: Option<u16>,
+    pub treasury: Option<Pubkey>,
+    pub execute_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlatformSettingsChangeCancelled {
+    pub schema_version: u8,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum SettingsError {
+    #[msg("No pending platform settings change")]
+    NoPendingChange,
+    #[msg("Timelock not expired")]
+    TimelockNotExpired,                 // Retired: see CommonError
+    #[msg("Price notice period cannot exceed 168 hours")]
+    PriceNoticeTooLong,
+}
+
+// Queue a treasury change and/or fee increase behind the timelock. A new
+// proposal replaces any pending one and restarts the clock.
+pub(crate) fn queue_platform_settings(
+    app_factory: &mut AppFactory,
+    platform_fee_bps: Option<u16>,
+    treasury: Option<Pubkey>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    app_factory.pending_platform_fee_bps = platform_fee_bps;
+    app_factory.pending_treasury = treasury;
+    app_factory.settings_change_at = schedule(now, SETTINGS_TIMELOCK_DURATION)?;
+
+    emit_versioned!(PlatformSettingsChangeProposed {
+        platform_fee_bps,
+        treasury,
+        execute_after: app_factory.settings_change_at,
+        timestamp: now,
+    });
+
+    msg!("Platform settings change queued until {}", app_factory.settings_change_at);
+    Ok(())
+}
+
+pub fn apply_platform_settings(ctx: Context<PendingPlatformSettings>) -> Result<()> {
+    let app_factory = &mut ctx.accounts.app_factory;
+    require!(
+        app_factory.pending_platform_fee_bps.is_some() || app_factory.pending_treasury.is_some(),
+        SettingsError::NoPendingChange
+    );
+    let now = Clock::get()?.unix_timestamp;
+    require_elapsed(now, app_factory.settings_change_at)?;
+
+    let platform_fee_bps = app_factory.pending_platform_fee_bps.take();
+    let treasury = app_factory.pending_treasury.take();
+    app_factory.settings_change_at = 0;
+
+    if let Some(fee) = platform_fee_bps {
+        app_factory.platform_fee_bps = fee;
+        msg!("Platform fee updated to {}%", fee as f64 / 100.0);
+    }
+    if let Some(new_treasury) = treasury {
+        app_factory.treasury = new_treasury;
+        msg!("Treasury updated to {}", new_treasury);
+    }
+
+    emit_versioned!(PlatformSettingsUpdated {
+        platform_fee_bps,
+        treasury,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_platform_settings_change(ctx: Context<PendingPlatformSettings>) -> Result<()> {
+    let app_factory = &mut ctx.accounts.app_factory;
+    require!(
+        app_factory.pending_platform_fee_bps.is_some() || app_factory.pending_treasury.is_some(),
+        SettingsError::NoPendingChange
+    );
+    app_factory.pending_platform_fee_bps = None;
+    app_factory.pending_treasury = None;
+    app_factory.settings_change_at = 0;
+
+    emit_versioned!(PlatformSettingsChangeCancelled {
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Pending platform settings change cancelled");
+    Ok(())
+}
+
+// Changing the notice only affects increases scheduled afterwards
+pub fn set_price_notice_period(ctx: Context<SetPriceNoticePeriod>, hours: u16) -> Result<()> {
+    require!(hours <= MAX_PRICE_NOTICE_HOURS, SettingsError::PriceNoticeTooLong);
+    ctx.accounts.app_factory.price_notice_hours = hours;
+
+    emit_versioned!(PriceNoticePeriodSet {
+        hours,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Price increases now take effect after {} hours", hours);
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppFactoryError};
+
+// Factory-wide totals, updated inline by every sale, refund and app status
+// change so the platform's top-line numbers can be read straight from chain.
+// Counting starts when the account is initialized. Volume, fees and refunds
+// are in DEFAI; SOL and USDC purchases count toward total_sales only.
+
+#[account]
+pub struct FactoryStats {
+    pub total_sales: u64,               // Purchases, rentals, paid trials, renewals and credit packs
+    pub total_volume: u64,              // DEFAI paid by buyers
+    pub total_fees: u64,                // DEFAI platform fees paid to the treasury
+    pub total_refunds: u64,             // DEFAI returned to buyers
+    pub refund_count: u64,
+    pub active_apps: u64,               // Apps currently on sale
+    pub bump: u8,
+}
+
+impl FactoryStats {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn record_sale(&mut self, volume: u64, platform_fee: u64) -> Result<()> {
+        self.total_sales = self.total_sales
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.total_volume = self.total_volume
+            .checked_add(volume)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.total_fees = self.total_fees
+            .checked_add(platform_fee)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_refund(&mut self, amount: u64) -> Result<()> {
+        self.refund_count = self.refund_count
+            .checked_add(1)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.total_refunds = self.total_refunds
+            .checked_add(amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(())
+    }
+
+    // Track an app going on or off sale. Apps deactivated that went live before
+    // the stats account existed saturate at zero.
+    pub fn record_status_change(&mut self, was_active: bool, is_active: bool) -> Result<()> {
+        if is_active && !was_active {
+            self.active_apps = self.active_apps
+                .checked_add(1)
+                .ok_or(AppFactoryError::MathOverflow)?;
+        } else if was_active && !is_active {
+            self.active_apps = self.active_apps.saturating_sub(1);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeFactoryStats<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = FactoryStats::LEN,
+        seeds = [b"factory_stats"],
+        bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_factory_stats(ctx: Context<InitializeFactoryStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.factory_stats;
+    stats.total_sales = 0;
+    stats.total_volume = 0;
+    stats.total_fees = 0;
+    stats.total_refunds = 0;
+    stats.refund_count = 0;
+    stats.active_apps = 0;
+    stats.bump = ctx.bumps.factory_stats;
+
+    msg!("Factory stats initialized");
+    Ok(())
+}
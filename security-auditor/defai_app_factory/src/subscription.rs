@@ -0,0 +1,684 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Token, TokenAccount, Mint},
+};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, AppFactoryError,
+    calculate_fee_split, execute_token_transfers, CreatorVesting, creator_payout_ata, distribute_creator_amount,
+    pay_charity_donation, burn_purchase_share, PurchaseSplit,
+};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+#[constant]
+pub const MIN_SUBSCRIPTION_PERIOD: i64 = 86400; // 1 day
+#[constant]
+pub const MAX_RENEWAL_PERIODS: u16 = 36;
+
+#[account]
+pub struct SubscriptionPlan {
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub period_seconds: i64,            // Length of one billing period
+    pub price: u64,                     // Price per period in DEFAI
+    pub is_active: bool,                // Whether new subscriptions/renewals are accepted
+    pub bump: u8,
+}
+
+impl SubscriptionPlan {
+    pub const LEN: usize = 8 + 8 + 32 + 8 + 8 + 1 + 1;
+}
+
+#[account]
+pub struct Subscription {
+    pub user: Pubkey,
+    pub app_id: u64,
+    pub started_at: i64,
+    pub paid_through: i64,              // Access is valid until this timestamp
+    pub cancelled: bool,                // Cancelled subscriptions keep access until paid_through
+    pub bump: u8,
+    pub period_start: i64,              // Start of the paid span ending at paid_through
+    pub paid_amount: u64,               // DEFAI paid for that span
+    pub paid_out: PurchaseSplit,        // Where that span's payments went (basis for pro-rata refunds)
+}
+
+impl Subscription {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + PurchaseSplit::LEN;
+
+    pub fn is_access_valid(&self, now: i64) -> bool {
+        now < self.paid_through
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CreateSubscriptionPlan<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = SubscriptionPlan::LEN,
+        seeds = [b"subscription_plan".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct UpdateSubscriptionPlan<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription_plan".as_ref(), &app_id.to_le_bytes()],
+        bump = subscription_plan.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub subscription_plan: Account<'info, SubscriptionPlan>,
+
+    pub creator: Signer<'info>,
+}
+
+// Shared payment accounts for subscribe and renew
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct Subscribe<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"subscription_plan".as_ref(), &app_id.to_le_bytes()],
+        bump = subscription_plan.bump
+    )]
+    pub subscription_plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = Subscription::LEN,
+        seeds = [b"subscription".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a payment can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RenewSubscription<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        seeds = [b"subscription_plan".as_ref(), &app_id.to_le_bytes()],
+        bump = subscription_plan.bump
+    )]
+    pub subscription_plan: Box<Account<'info, SubscriptionPlan>>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = subscription.bump,
+        has_one = user
+    )]
+    pub subscription: Box<Account<'info, Subscription>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = app_registration.creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = app_factory.treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a payment can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = subscription.bump,
+        has_one = user
+    )]
+    pub subscription: Account<'info, Subscription>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, app_id: u64)]
+pub struct CheckSubscription<'info> {
+    #[account(
+        seeds = [b"subscription".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+#[event]
+pub struct SubscriptionPlanCreated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub period_seconds: i64,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionPlanUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub price: u64,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionPaid {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub periods: u16,
+    pub amount: u64,
+    pub platform_fee: u64,
+    pub creator_amount: u64,
+    pub paid_through: i64,
+    pub timestamp: i64,
+    pub burned: u64,                    // DEFAI burned from the payment (taken from platform_fee)
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub paid_through: i64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum SubscriptionError {
+    #[msg("Subscription period too short (minimum 1 day)")]
+    InvalidPeriod,
+    #[msg("Subscription plan is not active")]
+    PlanNotActive,
+    #[msg("Invalid number of renewal periods")]
+    InvalidRenewalPeriods,
+    #[msg("Subscription already cancelled")]
+    AlreadyCancelled,
+}
+
+pub fn create_subscription_plan(
+    ctx: Context<CreateSubscriptionPlan>,
+    app_id: u64,
+    period_seconds: i64,
+    price: u64,
+) -> Result<()> {
+    require!(period_seconds >= MIN_SUBSCRIPTION_PERIOD, SubscriptionError::InvalidPeriod);
+    require!(price > 0, AppFactoryError::InvalidPrice);
+
+    let plan = &mut ctx.accounts.subscription_plan;
+    plan.app_id = app_id;
+    plan.creator = ctx.accounts.creator.key();
+    plan.period_seconds = period_seconds;
+    plan.price = price;
+    plan.is_active = true;
+    plan.bump = ctx.bumps.subscription_plan;
+
+    emit_versioned!(SubscriptionPlanCreated {
+        app_id,
+        period_seconds,
+        price,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Subscription plan created for app {}", app_id);
+    Ok(())
+}
+
+pub fn update_subscription_plan(
+    ctx: Context<UpdateSubscriptionPlan>,
+    app_id: u64,
+    new_price: Option<u64>,
+    is_active: Option<bool>,
+) -> Result<()> {
+    let plan = &mut ctx.accounts.subscription_plan;
+
+    if let Some(price) = new_price {
+        require!(price > 0, AppFactoryError::InvalidPrice);
+        plan.price = price;
+    }
+    if let Some(active) = is_active {
+        plan.is_active = active;
+    }
+
+    emit_versioned!(SubscriptionPlanUpdated {
+        app_id,
+        price: plan.price,
+        is_active: plan.is_active,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+pub fn subscribe<'info>(
+    ctx: Context<'_, '_, '_, 'info, Subscribe<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(ctx.accounts.subscription_plan.is_active, SubscriptionError::PlanNotActive);
+
+    let price = ctx.accounts.subscription_plan.price;
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+    let paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let subscription = &mut ctx.accounts.subscription;
+    subscription.user = ctx.accounts.user.key();
+    subscription.app_id = app_id;
+    subscription.started_at = now;
+    subscription.paid_through = now
+        .checked_add(ctx.accounts.subscription_plan.period_seconds)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    subscription.cancelled = false;
+    subscription.bump = ctx.bumps.subscription;
+    subscription.period_start = now;
+    subscription.paid_amount = price;
+    subscription.paid_out = paid_out;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), SubscriptionPaid {
+        app_id,
+        user: subscription.user,
+        periods: 1,
+        amount: price,
+        platform_fee,
+        creator_amount,
+        paid_through: subscription.paid_through,
+        timestamp: now,
+        burned,
+    });
+
+    msg!("User subscribed to app {} until {}", app_id, subscription.paid_through);
+    Ok(())
+}
+
+pub fn renew_subscription<'info>(
+    ctx: Context<'_, '_, '_, 'info, RenewSubscription<'info>>,
+    app_id: u64,
+    periods: u16,
+) -> Result<()> {
+    require!(
+        periods > 0 && periods <= MAX_RENEWAL_PERIODS,
+        SubscriptionError::InvalidRenewalPeriods
+    );
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(ctx.accounts.subscription_plan.is_active, SubscriptionError::PlanNotActive);
+
+    let plan = &ctx.accounts.subscription_plan;
+    let amount = plan.price
+        .checked_mul(periods as u64)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    let extension = plan.period_seconds
+        .checked_mul(periods as i64)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        amount,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        amount,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        amount,
+        &mut platform_fee,
+    )?;
+
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+    ctx.accounts.factory_stats.record_sale(amount, platform_fee)?;
+    let paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        amount,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    // Lapsed subscriptions restart from now; active ones extend from paid_through
+    let now = Clock::get()?.unix_timestamp;
+    let subscription = &mut ctx.accounts.subscription;
+    // A lapsed (or never tracked) paid span restarts the refund basis
+    if subscription.paid_through <= now || subscription.paid_out == PurchaseSplit::default() {
+        subscription.period_start = now;
+        subscription.paid_amount = 0;
+        subscription.paid_out = PurchaseSplit::default();
+    }
+    subscription.paid_amount = subscription.paid_amount
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    subscription.paid_out = subscription.paid_out.combined(&paid_out)?;
+    subscription.paid_through = subscription.paid_through
+        .max(now)
+        .checked_add(extension)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    subscription.cancelled = false;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), SubscriptionPaid {
+        app_id,
+        user: subscription.user,
+        periods,
+        amount,
+        platform_fee,
+        creator_amount,
+        paid_through: subscription.paid_through,
+        timestamp: now,
+        burned,
+    });
+
+    msg!("User renewed app {} until {}", app_id, subscription.paid_through);
+    Ok(())
+}
+
+pub fn cancel_subscription(ctx: Context<CancelSubscription>, app_id: u64) -> Result<()> {
+    let subscription = &mut ctx.accounts.subscription;
+    require!(!subscription.cancelled, SubscriptionError::AlreadyCancelled);
+    subscription.cancelled = true;
+
+    emit_versioned!(SubscriptionCancelled {
+        app_id,
+        user: subscription.user,
+        paid_through: subscription.paid_through,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Subscription to app {} cancelled; access remains until {}", app_id, subscription.paid_through);
+    Ok(())
+}
+
+pub fn is_access_valid(ctx: Context<CheckSubscription>) -> Result<bool> {
+    Ok(ctx.accounts.subscription.is_access_valid(Clock::get()?.unix_timestamp))
+}
@@ -0,0 +1,304 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_versioned, schedule};
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+
+// Creator-initiated max supply increases. `max_supply` is the buyer-facing
+// scarcity promise, so the creator can't raise it alone:
+// 1. The creator proposes a new (higher) max supply; the proposal is public.
+// 2. The platform authority approves it, or SUPPLY_INCREASE_TIMELOCK elapses
+//    without the authority rejecting it.
+// 3. The creator executes the increase.
+
+// Public notice period before an unapproved increase can be executed
+#[constant]
+pub const SUPPLY_INCREASE_TIMELOCK: i64 = 604800; // 7 days
+
+#[account]
+pub struct SupplyIncreaseProposal {
+    pub app_id: u64,
+    pub creator: Pubkey,                // Creator at proposal time
+    pub current_max_supply: u64,        // max_supply when proposed
+    pub new_max_supply: u64,
+    pub approved: bool,                 // Platform co-signed
+    pub proposed_at: i64,
+    pub execute_after: i64,             // Unapproved proposals execute after this
+    pub bump: u8,
+}
+
+impl SupplyIncreaseProposal {
+    pub const LEN: usize = 8 + 8 + 32 + 8 + 8 + 1 + 8 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ProposeSupplyIncrease<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = SupplyIncreaseProposal::LEN,
+        seeds = [b"supply_increase".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub supply_proposal: Account<'info, SupplyIncreaseProposal>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ApproveSupplyIncrease<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"supply_increase".as_ref(), &app_id.to_le_bytes()],
+        bump = supply_proposal.bump
+    )]
+    pub supply_proposal: Account<'info, SupplyIncreaseProposal>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct RejectSupplyIncrease<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"supply_increase".as_ref(), &app_id.to_le_bytes()],
+        bump = supply_proposal.bump,
+        close = creator
+    )]
+    pub supply_proposal: Account<'info, SupplyIncreaseProposal>,
+
+    /// CHECK: Proposing creator; receives the proposal rent
+    #[account(mut, address = supply_proposal.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ExecuteSupplyIncrease<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        mut,
+        seeds = [b"supply_increase".as_ref(), &app_id.to_le_bytes()],
+        bump = supply_proposal.bump,
+        close = creator
+    )]
+    pub supply_proposal: Account<'info, SupplyIncreaseProposal>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct CancelSupplyIncrease<'info> {
+    #[account(
+        mut,
+        seeds = [b"supply_increase".as_ref(), &app_id.to_le_bytes()],
+        bump = supply_proposal.bump,
+        constraint = supply_proposal.creator == creator.key() @ AppFactoryError::UnauthorizedCreator,
+        close = creator
+    )]
+    pub supply_proposal: Account<'info, SupplyIncreaseProposal>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[event]
+pub struct SupplyIncreaseProposed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub current_max_supply: u64,
+    pub new_max_supply: u64,
+    pub execute_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplyIncreaseApproved {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplyIncreaseRejected {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxSupplyIncreased {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub old_max_supply: u64,
+    pub new_max_supply: u64,
+    pub approved: bool,                 // false = executed via timelock
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplyIncreaseCancelled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum SupplyError {
+    #[msg("New max supply must exceed the current max supply")]
+    SupplyNotIncreased,
+    #[msg("Supply increase already approved")]
+    AlreadyApproved,
+    #[msg("Supply increase needs platform approval or the timelock to elapse")]
+    TimelockNotExpired,
+    #[msg("App creator or max supply changed since the increase was proposed")]
+    StaleProposal,
+}
+
+pub fn propose_supply_increase(
+    ctx: Context<ProposeSupplyIncrease>,
+    app_id: u64,
+    new_max_supply: u64,
+) -> Result<()> {
+    let current_max_supply = ctx.accounts.app_registration.max_supply;
+    require!(new_max_supply > current_max_supply, SupplyError::SupplyNotIncreased);
+
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &mut ctx.accounts.supply_proposal;
+    proposal.app_id = app_id;
+    proposal.creator = ctx.accounts.creator.key();
+    proposal.current_max_supply = current_max_supply;
+    proposal.new_max_supply = new_max_supply;
+    proposal.approved = false;
+    proposal.proposed_at = now;
+    proposal.execute_after = schedule(now, SUPPLY_INCREASE_TIMELOCK)?;
+    proposal.bump = ctx.bumps.supply_proposal;
+
+    emit_versioned!(SupplyIncreaseProposed {
+        app_id,
+        creator: proposal.creator,
+        current_max_supply,
+        new_max_supply,
+        execute_after: proposal.execute_after,
+        timestamp: now,
+    });
+
+    msg!(
+        "Max supply increase for app {} proposed: {} -> {}",
+        app_id,
+        current_max_supply,
+        new_max_supply
+    );
+    Ok(())
+}
+
+pub fn approve_supply_increase(ctx: Context<ApproveSupplyIncrease>, app_id: u64) -> Result<()> {
+    let proposal = &mut ctx.accounts.supply_proposal;
+    require!(!proposal.approved, SupplyError::AlreadyApproved);
+    proposal.approved = true;
+
+    emit_versioned!(SupplyIncreaseApproved {
+        app_id,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Max supply increase for app {} approved", app_id);
+    Ok(())
+}
+
+pub fn reject_supply_increase(ctx: Context<RejectSupplyIncrease>, app_id: u64) -> Result<()> {
+    emit_versioned!(SupplyIncreaseRejected {
+        app_id,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Max supply increase for app {} rejected", app_id);
+    Ok(())
+}
+
+pub fn execute_supply_increase(ctx: Context<ExecuteSupplyIncrease>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let proposal = &ctx.accounts.supply_proposal;
+    require!(
+        proposal.approved || now >= proposal.execute_after,
+        SupplyError::TimelockNotExpired
+    );
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    require!(
+        proposal.creator == app_registration.creator
+            && proposal.current_max_supply == app_registration.max_supply,
+        SupplyError::StaleProposal
+    );
+
+    let old_max_supply = app_registration.max_supply;
+    app_registration.max_supply = proposal.new_max_supply;
+
+    emit_versioned!(MaxSupplyIncreased {
+        app_id,
+        old_max_supply,
+        new_max_supply: proposal.new_max_supply,
+        approved: proposal.approved,
+        timestamp: now,
+    });
+
+    msg!(
+        "Max supply of app {} increased from {} to {}",
+        app_id,
+        old_max_supply,
+        proposal.new_max_supply
+    );
+    Ok(())
+}
+
+pub fn cancel_supply_increase(_ctx: Context<CancelSupplyIncrease>, app_id: u64) -> Result<()> {
+    emit_versioned!(SupplyIncreaseCancelled {
+        app_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Max supply increase for app {} cancelled", app_id);
+    Ok(())
+}
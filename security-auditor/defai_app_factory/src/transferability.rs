@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, FreezeAccount, ThawAccount};
+
+use crate::{
+    AppRegistration, UserAppAccess, AppFactoryError, Dispute, DisputeStatus, APP_REGISTRATION_SEED,
+};
+use defai_common::emit_versioned;
+
+// Soulbound apps keep every buyer's SFT ATA frozen by the registration PDA
+// (the mint's freeze authority), so access can't be sold or moved. Paths that
+// need the account unfrozen (refund burns, dispute delegation) thaw it first
+// and re-freeze when the SFT stays. When a creator makes an app transferable
+// again, holders thaw their own ATA with `thaw_app_sft`; flipping to soulbound
+// only affects SFTs minted afterwards.
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetAppTransferable<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64, owner: Pubkey)]
+pub struct ThawAppSft<'info> {
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        constraint = !app_registration.soulbound @ TransferabilityError::AppIsSoulbound
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    // Rentals stay frozen until they expire
+    #[account(
+        seeds = [b"user_app_access".as_ref(), owner.as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        constraint = user_app_access.expires_at == 0 @ TransferabilityError::RentalLocked
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    /// CHECK: The holder's dispute PDA; an unresolved dispute keeps the SFT frozen
+    #[account(
+        seeds = [b"dispute".as_ref(), owner.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub dispute: UncheckedAccount<'info>,
+
+    #[account(address = app_registration.sft_mint)]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = sft_mint,
+        token::authority = owner,
+        constraint = user_sft_ata.is_frozen() @ TransferabilityError::NotFrozen
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct AppTransferabilityChanged {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub transferable: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AppSftThawed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum TransferabilityError {
+    #[msg("App access is soulbound")]
+    AppIsSoulbound,
+    #[msg("Rented access stays frozen until it expires")]
+    RentalLocked,
+    #[msg("SFT is frozen by an open dispute")]
+    DisputeLocked,
+    #[msg("SFT account is not frozen")]
+    NotFrozen,
+}
+
+pub(crate) fn freeze_app_sft_account<'info>(
+    app_registration: &AccountInfo<'info>,
+    sft_mint: &AccountInfo<'info>,
+    user_sft_ata: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    app_id: u64,
+    bump: u8,
+) -> Result<()> {
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let signer_seeds = &[&mint_seeds[..]];
+    let freeze_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        FreezeAccount {
+            account: user_sft_ata.clone(),
+            mint: sft_mint.clone(),
+            authority: app_registration.clone(),
+        },
+        signer_seeds,
+    );
+    token::freeze_account(freeze_ctx)
+}
+
+pub(crate) fn thaw_app_sft_account<'info>(
+    app_registration: &AccountInfo<'info>,
+    sft_mint: &AccountInfo<'info>,
+    user_sft_ata: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    app_id: u64,
+    bump: u8,
+) -> Result<()> {
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let signer_seeds = &[&mint_seeds[..]];
+    let thaw_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        ThawAccount {
+            account: user_sft_ata.clone(),
+            mint: sft_mint.clone(),
+            authority: app_registration.clone(),
+        },
+        signer_seeds,
+    );
+    token::thaw_account(thaw_ctx)
+}
+
+// Reads the frozen state of a token account passed as a raw AccountInfo
+pub(crate) fn is_token_account_frozen(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.is_frozen())
+}
+
+pub fn set_app_transferable(
+    ctx: Context<SetAppTransferable>,
+    app_id: u64,
+    transferable: bool,
+) -> Result<()> {
+    ctx.accounts.app_registration.soulbound = !transferable;
+
+    emit_versioned!(AppTransferabilityChanged {
+        app_id,
+        transferable,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("App {} access is now {}", app_id, if transferable { "transferable" } else { "soulbound" });
+    Ok(())
+}
+
+pub fn thaw_app_sft(ctx: Context<ThawAppSft>, app_id: u64, owner: Pubkey) -> Result<()> {
+    if !ctx.accounts.dispute.data_is_empty() {
+        let data = ctx.accounts.dispute.try_borrow_data()?;
+        let dispute = Dispute::try_deserialize(&mut &data[..])?;
+        require!(
+            !matches!(dispute.status, DisputeStatus::Open | DisputeStatus::Escrowed),
+            TransferabilityError::DisputeLocked
+        );
+    }
+
+    thaw_app_sft_account(
+        &ctx.accounts.app_registration.to_account_info(),
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        ctx.accounts.app_registration.bump,
+    )?;
+
+    emit_versioned!(AppSftThawed {
+        app_id,
+        owner,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Thawed app {} SFT for {}", app_id, owner);
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use defai_common::{
+    emit_cpi_versioned, emit_versioned, event_cpi_of, record_revenue, validate_withdrawal_period,
+    EventCpi, RevenueSource, Timelock, WithdrawalWindow, ADMIN_TIMELOCK_DURATION,
+    DEFAULT_WITHDRAWAL_PERIOD,
+};
+
+use crate::{AppFactory, AppFactoryError, FactoryStats};
+
+// Treasury ledger (see defai_common::treasury)
+// Books the factory's income by source. Registration fees and slashed stakes
+// are recorded as they are paid. Platform fees are already summed on every
+// sale in `FactoryStats.total_fees`, so `sync_platform_fees` copies new fees
+// into the ledger instead of touching each sale path. Tokens held by the
+// ledger PDA itself (set it as the factory treasury) only leave through
+// `withdraw_treasury`, within a per-period cap set through the timelock.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct WithdrawalPolicy {
+    pub cap: u64,       // DEFAI base units per period; 0 blocks withdrawals
+    pub period: i64,    // Seconds
+}
+
+impl WithdrawalPolicy {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[account]
+pub struct TreasuryLedger {
+    pub mint: Pubkey,
+    pub revenue: [u64; 8],          // Lifetime income per defai_common::RevenueSource
+    pub revenue_entries: [u64; 8],  // Payments (platform fees: syncs) booked per source
+    pub last_revenue_at: i64,
+    pub total_withdrawn: u64,
+    pub policy: WithdrawalPolicy,
+    pub window_start: i64,
+    pub withdrawn_in_window: u64,
+    pub pending_policy: Option<WithdrawalPolicy>,
+    pub policy_change_timestamp: i64,
+    pub bump: u8,
+}
+
+impl TreasuryLedger {
+    pub const LEN: usize = 32 + 64 + 64 + 8 + 8 + WithdrawalPolicy::LEN + 8 + 8 + (1 + WithdrawalPolicy::LEN) + 8 + 1;
+
+    // Book a payment that has just been transferred to the treasury
+    pub fn record(&mut self, source: RevenueSource, amount: u64, now: i64, events: &EventCpi) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let source_total = record_revenue(&mut self.revenue, &mut self.revenue_entries, source, amount)?;
+        self.last_revenue_at = now;
+
+        emit_cpi_versioned!(events, RevenueRecorded {
+            source: source as u8,
+            amount,
+            source_total,
+            timestamp: now,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasuryLedger<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TreasuryLedger::LEN,
+        seeds = [b"treasury_ledger"],
+        bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryLedger<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SyncPlatformFees<'info> {
+    #[account(
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Account<'info, FactoryStats>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury_ledger
+    )]
+    pub treasury_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    #[account(address = treasury_ledger.mint @ AppFactoryError::InvalidDefaiMint)]
+    pub mint: Account<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+// Books DEFAI income; withdrawals stay blocked until a cap is set through the timelock
+pub fn initialize_treasury_ledger(ctx: Context<InitializeTreasuryLedger>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let policy = WithdrawalPolicy { cap: 0, period: DEFAULT_WITHDRAWAL_PERIOD };
+
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    ledger.mint = ctx.accounts.app_factory.defai_mint;
+    ledger.revenue = [0; 8];
+    ledger.revenue_entries = [0; 8];
+    ledger.last_revenue_at = 0;
+    ledger.total_withdrawn = 0;
+    ledger.policy = policy;
+    ledger.window_start = 0;
+    ledger.withdrawn_in_window = 0;
+    ledger.pending_policy = None;
+    ledger.policy_change_timestamp = 0;
+    ledger.bump = ctx.bumps.treasury_ledger;
+
+    emit_versioned!(WithdrawalPolicyChanged {
+        authority: ctx.accounts.authority.key(),
+        cap: policy.cap,
+        period: policy.period,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn propose_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>, policy: WithdrawalPolicy) -> Result<()> {
+    validate_withdrawal_period(policy.period)?;
+    require!(ctx.accounts.treasury_ledger.policy != policy, TreasuryError::PolicyUnchanged);
+
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    let execute_after = Timelock::new(&mut ledger.pending_policy, &mut ledger.policy_change_timestamp)
+        .propose(policy, Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+
+    emit_versioned!(WithdrawalPolicyProposed {
+        authority: ctx.accounts.authority.key(),
+        cap: policy.cap,
+        period: policy.period,
+        execute_after,
+    });
+    Ok(())
+}
+
+pub fn cancel_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    Timelock::new(&mut ledger.pending_policy, &mut ledger.policy_change_timestamp).cancel()?;
+
+    emit_versioned!(WithdrawalPolicyCancelled {
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// The current window carries over; a lower cap only limits what is left of it
+pub fn execute_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    let policy = Timelock::new(&mut ledger.pending_policy, &mut ledger.policy_change_timestamp).execute(now)?;
+    ledger.policy = policy;
+
+    emit_versioned!(WithdrawalPolicyChanged {
+        authority: ctx.accounts.authority.key(),
+        cap: policy.cap,
+        period: policy.period,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+// Permissionless: book platform fees FactoryStats has counted since the last sync
+pub fn sync_platform_fees(ctx: Context<SyncPlatformFees>) -> Result<()> {
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    let booked = ledger.revenue[RevenueSource::AppPlatformFee.index()];
+    let unbooked = ctx.accounts.factory_stats.total_fees
+        .checked_sub(booked)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    require!(unbooked > 0, TreasuryError::NothingToSync);
+
+    ledger.record(RevenueSource::AppPlatformFee, unbooked, Clock::get()?.unix_timestamp, &event_cpi_of!(ctx))?;
+    msg!("Booked {} DEFAI of platform fees", unbooked);
+    Ok(())
+}
+
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    require!(amount > 0, TreasuryError::InvalidWithdrawalAmount);
+    let now = Clock::get()?.unix_timestamp;
+
+    let ledger: &mut TreasuryLedger = &mut ctx.accounts.treasury_ledger;
+    let WithdrawalPolicy { cap, period } = ledger.policy;
+    let mut window = WithdrawalWindow::new(&mut ledger.window_start, &mut ledger.withdrawn_in_window);
+    window.withdraw(amount, cap, period, now)?;
+    let remaining_in_window = window.remaining(cap, period, now);
+    ledger.total_withdrawn = ledger.total_withdrawn
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    let ledger_seeds = &[b"treasury_ledger".as_ref(), &[ledger.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.treasury_vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.treasury_ledger.to_account_info(),
+            },
+            &[&ledger_seeds[..]],
+        ),
+        amount,
+    )?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), TreasuryWithdrawn {
+        authority: ctx.accounts.authority.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+        remaining_in_window,
+        total_withdrawn: ctx.accounts.treasury_ledger.total_withdrawn,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct RevenueRecorded {
+    pub schema_version: u8,
+    pub source: u8,           // defai_common::RevenueSource
+    pub amount: u64,
+    pub source_total: u64,    // Lifetime income from this source
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalPolicyProposed {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub cap: u64,
+    pub period: i64,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct WithdrawalPolicyCancelled {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalPolicyChanged {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub cap: u64,
+    pub period: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub remaining_in_window: u64,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum TreasuryError {
+    #[msg("Withdrawal policy is unchanged")]
+    PolicyUnchanged,
+    #[msg("Withdrawal amount must be greater than zero")]
+    InvalidWithdrawalAmount,
+    #[msg("No platform fees to sync")]
+    NothingToSync,
+}
@@ -0,0 +1,727 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, Approve, Burn},
+};
+use defai_common::{bps_of, emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+use crate::{
+    AppFactory, FactoryStats, AppRegistration, UserAppAccess, AppFactoryError, AppPurchased,
+    APP_REGISTRATION_SEED, calculate_fee_split, execute_token_transfers, mint_app_sft,
+    distribute_creator_amount, PresaleError, freeze_app_sft_account,
+    thaw_app_sft_account, CreatorVesting, creator_payout_ata, pay_charity_donation,
+    burn_purchase_share, PurchaseSplit,
+};
+
+const SECONDS_PER_DAY: i64 = 86400;
+#[constant]
+pub const MAX_TRIAL_DAYS: u16 = 7;
+
+// Trials. A creator offers a free or cheap trial of up to 7 days; the trial SFT
+// is held like a rental (frozen, delegated to the registration PDA) and burned
+// by `expire_access` when it lapses. Each wallet gets one trial per app,
+// tracked by a TrialRecord. Before the trial is cranked away the user can
+// `convert_trial`: the trial SFT is burned and a permanent one minted at the
+// list price less the app's conversion discount.
+
+#[account]
+pub struct TrialRecord {
+    pub user: Pubkey,
+    pub app_id: u64,
+    pub started_at: i64,                // purchased_at of the trial access
+    pub converted: bool,
+    pub bump: u8,
+}
+
+impl TrialRecord {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 1 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetTrialTerms<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    pub creator: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct StartTrial<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    // One trial per wallet per app
+    #[account(
+        init,
+        payer = user,
+        space = TrialRecord::LEN,
+        seeds = [b"trial".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub trial_record: Box<Account<'info, TrialRecord>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = UserAppAccess::LEN,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = sft_mint,
+        associated_token::authority = user
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user,
+        constraint = user_defai_ata.amount >= app_registration.trial_price
+            @ AppFactoryError::InsufficientBalance
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ConvertTrial<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        constraint = !app_factory.paused @ AppFactoryError::FactoryPaused
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"factory_stats"],
+        bump = factory_stats.bump
+    )]
+    pub factory_stats: Box<Account<'info, FactoryStats>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"trial".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = trial_record.bump,
+        has_one = user,
+        constraint = !trial_record.converted @ TrialError::AlreadyConverted
+    )]
+    pub trial_record: Box<Account<'info, TrialRecord>>,
+
+    // Still the trial access: not expired away and not replaced by a purchase
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.key().as_ref(), &app_id.to_le_bytes()],
+        bump = user_app_access.bump,
+        has_one = user,
+        constraint = user_app_access.expires_at > 0
+            && user_app_access.purchased_at == trial_record.started_at
+            @ TrialError::NotATrial
+    )]
+    pub user_app_access: Box<Account<'info, UserAppAccess>>,
+
+    #[account(
+        mut,
+        address = app_registration.sft_mint
+    )]
+    pub sft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = user_app_access.sft_token_account
+    )]
+    pub user_sft_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = defai_mint,
+        associated_token::authority = user
+    )]
+    pub user_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    // Vesting escrow, required when the app's creator payouts vest
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Option<Box<Account<'info, CreatorVesting>>>,
+
+    #[account(mut)]
+    pub vesting_vault: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Charity's DEFAI account, required when the app donates a share of sales
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub charity_defai_ata: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = treasury
+    )]
+    pub treasury_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA, always passed so a purchase can't
+    /// skip the split; read only when initialized. Recipient DEFAI ATAs follow
+    /// as remaining accounts
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Creator must match registration
+    #[account(address = app_registration.creator @ AppFactoryError::InvalidCreator)]
+    pub creator: AccountInfo<'info>,
+
+    /// CHECK: Treasury must match factory
+    #[account(address = app_factory.treasury @ AppFactoryError::InvalidTreasury)]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Box<Account<'info, Mint>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct TrialTermsUpdated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub trial_price: u64,
+    pub trial_days: u16,
+    pub conversion_discount_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrialStarted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub price: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrialConverted {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub user: Pubkey,
+    pub price: u64,
+    pub discount: u64,
+    pub trial_started_at: i64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum TrialError {
+    #[msg("Trials are not enabled for this app")]
+    TrialsDisabled,
+    #[msg("Invalid trial duration (1-7 days)")]
+    InvalidTrialDuration,
+    #[msg("Trial price must be below the purchase price")]
+    TrialPriceTooHigh,
+    #[msg("Conversion discount cannot exceed 100%")]
+    InvalidConversionDiscount,
+    #[msg("Access is not this user's trial")]
+    NotATrial,
+    #[msg("Trial already converted")]
+    AlreadyConverted,
+}
+
+pub fn set_trial_terms(
+    ctx: Context<SetTrialTerms>,
+    app_id: u64,
+    trial_price: u64,
+    trial_days: u16,
+    conversion_discount_bps: u16,
+) -> Result<()> {
+    let app_registration = &mut ctx.accounts.app_registration;
+
+    // Zero days disables trials
+    if trial_days > 0 {
+        require!(trial_days <= MAX_TRIAL_DAYS, TrialError::InvalidTrialDuration);
+        require!(
+            trial_price < app_registration.list_price(Clock::get()?.unix_timestamp),
+            TrialError::TrialPriceTooHigh
+        );
+        require!(conversion_discount_bps <= 10000, TrialError::InvalidConversionDiscount);
+    }
+
+    app_registration.trial_price = trial_price;
+    app_registration.trial_days = trial_days;
+    app_registration.trial_discount_bps = conversion_discount_bps;
+
+    emit_versioned!(TrialTermsUpdated {
+        app_id,
+        trial_price,
+        trial_days,
+        conversion_discount_bps,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "App {} trial: {} days for {} DEFAI, {}% off on conversion",
+        app_id,
+        trial_days,
+        trial_price,
+        conversion_discount_bps as f64 / 100.0
+    );
+    Ok(())
+}
+
+pub fn start_trial<'info>(
+    ctx: Context<'_, '_, '_, 'info, StartTrial<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let app_registration = &ctx.accounts.app_registration;
+    require!(app_registration.is_active, AppFactoryError::AppNotActive);
+    require!(!app_registration.in_presale(now), PresaleError::PresaleActive);
+    require!(app_registration.trial_days > 0, TrialError::TrialsDisabled);
+    require!(
+        app_registration.current_supply < app_registration.max_supply,
+        AppFactoryError::MaxSupplyReached
+    );
+
+    // Free trials skip payment entirely
+    let price = app_registration.trial_price;
+    let paid_out = if price > 0 {
+        let (platform_fee, creator_amount) = calculate_fee_split(
+            price,
+            ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+        )?;
+
+        // Donate the app's charity share, if any
+        let creator_amount = pay_charity_donation(
+            &ctx.accounts.app_registration,
+            ctx.accounts.charity_defai_ata.as_deref(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_defai_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            price,
+            creator_amount,
+            &event_cpi_of!(ctx),
+        )?;
+
+        // Route the creator share across the revenue split, if any
+        let direct_creator_amount = distribute_creator_amount(
+            &ctx.accounts.revenue_split,
+            ctx.remaining_accounts,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_defai_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.accounts.defai_mint.key(),
+            creator_amount,
+        )?;
+
+        // Pay the creator share into vesting escrow if payouts vest
+        let creator_payout = creator_payout_ata(
+            &ctx.accounts.app_registration,
+            ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+            ctx.accounts.vesting_vault.as_deref(),
+            &ctx.accounts.creator_defai_ata,
+            direct_creator_amount,
+        )?;
+
+        execute_token_transfers(
+            &ctx.accounts.user,
+            &ctx.accounts.user_defai_ata,
+            creator_payout,
+            &ctx.accounts.treasury_defai_ata,
+            &ctx.accounts.token_program,
+            platform_fee,
+            direct_creator_amount,
+        )?;
+        ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+        PurchaseSplit::from_sale(
+            &ctx.accounts.app_registration,
+            price,
+            0,
+            platform_fee,
+            0,
+            creator_amount,
+            direct_creator_amount,
+        )?
+    } else {
+        PurchaseSplit::default()
+    };
+
+    // Held like a rental: delegated to the registration PDA, then frozen
+    let approve_ctx = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Approve {
+            to: ctx.accounts.user_sft_ata.to_account_info(),
+            delegate: ctx.accounts.app_registration.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+        },
+    );
+    token::approve(approve_ctx, 1)?;
+
+    let bump = ctx.accounts.app_registration.bump;
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    if !ctx.accounts.app_registration.soulbound {
+        freeze_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+    }
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.current_supply = app_registration.current_supply
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    app_registration.trials_started = app_registration.trials_started
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    let expires_at = now
+        .checked_add(app_registration.trial_days as i64 * SECONDS_PER_DAY)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    let trial_record = &mut ctx.accounts.trial_record;
+    trial_record.user = ctx.accounts.user.key();
+    trial_record.app_id = app_id;
+    trial_record.started_at = now;
+    trial_record.converted = false;
+    trial_record.bump = ctx.bumps.trial_record;
+
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.user = ctx.accounts.user.key();
+    user_app_access.app_id = app_id;
+    user_app_access.sft_token_account = ctx.accounts.user_sft_ata.key();
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.bump = ctx.bumps.user_app_access;
+    user_app_access.expires_at = expires_at;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.rent_payer = ctx.accounts.user.key();
+    user_app_access.paid_out = paid_out;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), TrialStarted {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        expires_at,
+        timestamp: now,
+    });
+
+    msg!("User started a trial of app {} until {}", app_id, expires_at);
+    Ok(())
+}
+
+pub fn convert_trial<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConvertTrial<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(ctx.accounts.app_registration.is_active, AppFactoryError::AppNotActive);
+
+    // List price less the app's conversion discount
+    let list_price = ctx.accounts.app_registration.list_price(now);
+    let discount = bps_of(list_price, ctx.accounts.app_registration.trial_discount_bps as u64)?;
+    let price = list_price - discount;
+    require!(
+        ctx.accounts.user_defai_ata.amount >= price,
+        AppFactoryError::InsufficientBalance
+    );
+
+    let (mut platform_fee, creator_amount) = calculate_fee_split(
+        price,
+        ctx.accounts.app_registration.effective_fee_bps(ctx.accounts.app_factory.platform_fee_bps),
+    )?;
+
+    // Donate the app's charity share, if any
+    let creator_amount = pay_charity_donation(
+        &ctx.accounts.app_registration,
+        ctx.accounts.charity_defai_ata.as_deref(),
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        price,
+        creator_amount,
+        &event_cpi_of!(ctx),
+    )?;
+
+    // Route the creator share across the revenue split, if any
+    let direct_creator_amount = distribute_creator_amount(
+        &ctx.accounts.revenue_split,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.user_defai_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        ctx.accounts.defai_mint.key(),
+        creator_amount,
+    )?;
+
+    // Pay the creator share into vesting escrow if payouts vest
+    let creator_payout = creator_payout_ata(
+        &ctx.accounts.app_registration,
+        ctx.accounts.creator_vesting.as_deref_mut().map(|vesting| &mut **vesting),
+        ctx.accounts.vesting_vault.as_deref(),
+        &ctx.accounts.creator_defai_ata,
+        direct_creator_amount,
+    )?;
+
+    // Burn the factory's share of the payment
+    let burned = burn_purchase_share(
+        &ctx.accounts.app_factory,
+        &ctx.accounts.defai_mint,
+        &ctx.accounts.user_defai_ata,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program,
+        price,
+        &mut platform_fee,
+    )?;
+
+    execute_token_transfers(
+        &ctx.accounts.user,
+        &ctx.accounts.user_defai_ata,
+        creator_payout,
+        &ctx.accounts.treasury_defai_ata,
+        &ctx.accounts.token_program,
+        platform_fee,
+        direct_creator_amount,
+    )?;
+
+    // Thaw, then burn the trial SFT as delegate (clearing the delegation)
+    let bump = ctx.accounts.app_registration.bump;
+    if ctx.accounts.user_sft_ata.is_frozen() {
+        thaw_app_sft_account(
+            &ctx.accounts.app_registration.to_account_info(),
+            &ctx.accounts.sft_mint.to_account_info(),
+            &ctx.accounts.user_sft_ata.to_account_info(),
+            &ctx.accounts.token_program,
+            app_id,
+            bump,
+        )?;
+    }
+    let mint_seeds = &[
+        APP_REGISTRATION_SEED,
+        &app_id.to_le_bytes(),
+        &[bump],
+    ];
+    let signer_seeds = &[&mint_seeds[..]];
+    let burn_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Burn {
+            mint: ctx.accounts.sft_mint.to_account_info(),
+            from: ctx.accounts.user_sft_ata.to_account_info(),
+            authority: ctx.accounts.app_registration.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::burn(burn_ctx, 1)?;
+
+    // Mint the permanent SFT; the trial's supply slot carries over
+    mint_app_sft(
+        &ctx.accounts.app_registration,
+        &ctx.accounts.sft_mint.to_account_info(),
+        &ctx.accounts.user_sft_ata.to_account_info(),
+        &ctx.accounts.token_program,
+        app_id,
+        bump,
+    )?;
+
+    ctx.accounts.factory_stats.record_sale(price, platform_fee)?;
+    let app_registration = &mut ctx.accounts.app_registration;
+    app_registration.record_sale(price, now)?;
+    app_registration.trial_conversions = app_registration.trial_conversions
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    let trial_started_at = ctx.accounts.trial_record.started_at;
+    ctx.accounts.trial_record.converted = true;
+
+    // The trial access becomes a permanent purchase
+    let user_app_access = &mut ctx.accounts.user_app_access;
+    user_app_access.purchased_at = now;
+    user_app_access.purchase_price = price;
+    user_app_access.expires_at = 0;
+    user_app_access.payment_mint = ctx.accounts.defai_mint.key();
+    user_app_access.paid_out = PurchaseSplit::from_sale(
+        &ctx.accounts.app_registration,
+        price,
+        burned,
+        platform_fee,
+        0,
+        creator_amount,
+        direct_creator_amount,
+    )?;
+
+    let purchase_id = ctx.accounts.app_factory.next_purchase_id()?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), AppPurchased {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        platform_fee,
+        creator_amount,
+        payment_mint: ctx.accounts.defai_mint.key(),
+        timestamp: now,
+        purchase_id,
+        burned,
+        sft_mint: ctx.accounts.app_registration.sft_mint,
+        metadata_uri: ctx.accounts.app_registration.metadata_uri.clone(),
+        edition_id: None,
+    });
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), TrialConverted {
+        app_id,
+        user: ctx.accounts.user.key(),
+        price,
+        discount,
+        trial_started_at,
+        timestamp: now,
+    });
+
+    msg!("User converted their trial of app {} for {} DEFAI", app_id, price);
+    Ok(())
+}
@@ -1,22 +1,130 @@
 use anchor_lang::prelude::*;
-use crate::{AppRegistration, AppFactoryError};
+use anchor_lang::Discriminator;
+use crate::{AppFactory, AppRegistration, UserAppAccess, Subscription, AppFactoryError, MAX_METADATA_URI_LEN};
+use defai_common::emit_versioned;
 
 #[derive(Accounts)]
 #[instruction(app_id: u64)]
 pub struct UpdateAppMetadata<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+    
     #[account(
         mut,
         seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
         bump = app_registration.bump,
-        has_one = creator @ AppFactoryError::UnauthorizedCreator
+        has_one = creator @ AppFactoryError::UnauthorizedCreator,
+        // Grow registrations created under the old 100-character limit
+        realloc = AppRegistration::LEN,
+        realloc::payer = creator,
+        realloc::zero = false
     )]
     pub app_registration: Account<'info, AppRegistration>,
     
+    #[account(mut)]
     pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct MigrateAppRegistration<'info> {
+    /// CHECK: May predate the current AppRegistration layout, so it can't be
+    /// deserialized until it has been resized; owner and discriminator are
+    /// checked in the handler
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub app_registration: UncheckedAccount<'info>,
+    
+    // Anyone can pay to migrate a registration
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAppFactory<'info> {
+    /// CHECK: May predate the current AppFactory layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"app_factory"],
+        bump
+    )]
+    pub app_factory: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, app_id: u64)]
+pub struct MigrateUserAppAccess<'info> {
+    /// CHECK: May predate the current UserAppAccess layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"user_app_access".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub user_app_access: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, app_id: u64)]
+pub struct MigrateSubscription<'info> {
+    /// CHECK: May predate the current Subscription layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"subscription".as_ref(), user.as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub subscription: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppRegistrationMigrated {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub old_len: u64,
+    pub new_len: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceChangeScheduled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub current_price: u64,
+    pub new_price: u64,
+    pub effective_at: i64,
+    pub timestamp: i64,
 }
 
 #[event]
 pub struct AppUpdated {
+    pub schema_version: u8,
     pub app_id: u64,
     pub new_price: Option<u64>,
     pub new_metadata_uri: Option<String>,
@@ -30,28 +138,53 @@ pub fn update_app_metadata(
     new_price: Option<u64>,
 ) -> Result<()> {
     let app_registration = &mut ctx.accounts.app_registration;
+    let now = Clock::get()?.unix_timestamp;
     
-    // Update price if provided
+    // Update price if provided. Decreases apply at once; increases wait out the
+    // platform notice period so pending buyers keep the old price.
+    let mut applied_price = None;
     if let Some(price) = new_price {
         require!(price > 0, AppFactoryError::InvalidPrice);
-        app_registration.price = price;
+        let current_price = app_registration.list_price(now);
+        let notice_secs = ctx.accounts.app_factory.price_notice_hours as i64 * 3600;
+        app_registration.price = current_price;
+        app_registration.pending_price = 0;
+        app_registration.price_effective_at = 0;
+        
+        if price <= current_price || notice_secs == 0 {
+            app_registration.price = price;
+            applied_price = Some(price);
+        } else {
+            app_registration.pending_price = price;
+            app_registration.price_effective_at = now
+                .checked_add(notice_secs)
+                .ok_or(AppFactoryError::MathOverflow)?;
+            
+            emit_versioned!(PriceChangeScheduled {
+                app_id,
+                current_price,
+                new_price: price,
+                effective_at: app_registration.price_effective_at,
+                timestamp: now,
+            });
+        }
     }
     
     // Update metadata URI if provided
     if let Some(metadata_uri) = &new_metadata_uri {
         require!(
-            metadata_uri.len() <= 100,
+            metadata_uri.len() <= MAX_METADATA_URI_LEN,
             AppFactoryError::MetadataUriTooLong
         );
         app_registration.metadata_uri = metadata_uri.clone();
     }
     
     // Emit event
-    emit!(AppUpdated {
+    emit_versioned!(AppUpdated {
         app_id,
-        new_price,
+        new_price: applied_price,
         new_metadata_uri,
-        timestamp: Clock::get()?.unix_timestamp,
+        timestamp: now,
     });
     
     msg!(
@@ -61,4 +194,136 @@ pub fn update_app_metadata(
     );
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Grows a program account created under an older layout to `new_len`,
+// topping up rent from `payer`. Appended bytes are zero-filled, which decodes
+// as the default for fields added since. Returns the previous length.
+fn resize_legacy_account<'info>(
+    info: &AccountInfo<'info>,
+    discriminator: [u8; 8],
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<usize> {
+    require_keys_eq!(*info.owner, crate::ID);
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == discriminator,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+    }
+    
+    let old_len = info.data_len();
+    require!(old_len < new_len, AppFactoryError::AlreadyMigrated);
+    
+    // Top up rent for the larger size
+    let rent_needed = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(info.lamports());
+    if rent_needed > 0 {
+        let transfer_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(transfer_ctx, rent_needed)?;
+    }
+    info.realloc(new_len, true)?;
+    
+    Ok(old_len)
+}
+
+// Resizes a registration to the current AppRegistration::LEN
+pub fn migrate_app_registration(ctx: Context<MigrateAppRegistration>, app_id: u64) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.app_registration.to_account_info(),
+        AppRegistration::DISCRIMINATOR,
+        AppRegistration::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    
+    // Emit event
+    emit_versioned!(AppRegistrationMigrated {
+        app_id,
+        old_len: old_len as u64,
+        new_len: AppRegistration::LEN as u64,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    
+    msg!("App {} registration migrated from {} to {} bytes", app_id, old_len, AppRegistration::LEN);
+    
+    Ok(())
+}
+
+// Resizes the factory singleton to the current AppFactory::LEN
+pub fn migrate_app_factory(ctx: Context<MigrateAppFactory>) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.app_factory.to_account_info(),
+        AppFactory::DISCRIMINATOR,
+        AppFactory::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    
+    msg!("App factory migrated from {} to {} bytes", old_len, AppFactory::LEN);
+    
+    Ok(())
+}
+
+// Resizes an access record to the current UserAppAccess::LEN. The appended
+// rent_payer stays zeroed, so closing it refunds the user, and so does
+// paid_out, so the purchase it records can't be refunded.
+pub fn migrate_user_app_access(
+    ctx: Context<MigrateUserAppAccess>,
+    user: Pubkey,
+    app_id: u64,
+) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.user_app_access.to_account_info(),
+        UserAppAccess::DISCRIMINATOR,
+        UserAppAccess::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    
+    msg!(
+        "Access of {} for app {} migrated from {} to {} bytes",
+        user,
+        app_id,
+        old_len,
+        UserAppAccess::LEN
+    );
+    
+    Ok(())
+}
+
+// Resizes a subscription to the current Subscription::LEN. Its refund basis
+// starts empty and is set again by the next renewal.
+pub fn migrate_subscription(
+    ctx: Context<MigrateSubscription>,
+    user: Pubkey,
+    app_id: u64,
+) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.subscription.to_account_info(),
+        Subscription::DISCRIMINATOR,
+        Subscription::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    
+    msg!(
+        "Subscription of {} to app {} migrated from {} to {} bytes",
+        user,
+        app_id,
+        old_len,
+        Subscription::LEN
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppFactory, AppFactoryError};
+use defai_common::emit_versioned;
+
+#[account]
+pub struct VerifiedCreator {
+    pub creator: Pubkey,
+    pub verified: bool,
+    pub metadata_hash: [u8; 32],        // Hash of the off-chain verification record (audit report, KYB, ...)
+    pub updated_at: i64,
+    pub updated_by: Pubkey,             // Authority that last changed the badge
+    pub bump: u8,
+    pub kyc_hash: Option<[u8; 32]>,     // Hash of the creator's off-chain KYC record (None = not KYC'd)
+}
+
+impl VerifiedCreator {
+    pub const LEN: usize = 8 + 32 + 1 + 32 + 8 + 32 + 1 + (1 + 32);
+
+    // Badge status for an optional registry account
+    pub fn is_verified(account: Option<&VerifiedCreator>) -> bool {
+        account.is_some_and(|verified_creator| verified_creator.verified)
+    }
+
+    // KYC hash for an optional registry account
+    pub fn kyc_hash(account: Option<&VerifiedCreator>) -> Option<[u8; 32]> {
+        account.and_then(|verified_creator| verified_creator.kyc_hash)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct SetCreatorVerification<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = VerifiedCreator::LEN,
+        seeds = [b"verified_creator".as_ref(), creator.as_ref()],
+        bump
+    )]
+    pub verified_creator: Account<'info, VerifiedCreator>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct CreatorVerificationUpdated {
+    pub schema_version: u8,
+    pub creator: Pubkey,
+    pub verified: bool,
+    pub metadata_hash: [u8; 32],
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorKycUpdated {
+    pub schema_version: u8,
+    pub creator: Pubkey,
+    pub kyc_hash: Option<[u8; 32]>,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+pub fn set_creator_verification(
+    ctx: Context<SetCreatorVerification>,
+    creator: Pubkey,
+    verified: bool,
+    metadata_hash: [u8; 32],
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let verified_creator = &mut ctx.accounts.verified_creator;
+    verified_creator.creator = creator;
+    verified_creator.verified = verified;
+    verified_creator.metadata_hash = metadata_hash;
+    verified_creator.updated_at = now;
+    verified_creator.updated_by = ctx.accounts.authority.key();
+    verified_creator.bump = ctx.bumps.verified_creator;
+
+    emit_versioned!(CreatorVerificationUpdated {
+        creator,
+        verified,
+        metadata_hash,
+        authority: verified_creator.updated_by,
+        timestamp: now,
+    });
+
+    msg!(
+        "Creator {} is now {}",
+        creator,
+        if verified { "verified" } else { "unverified" }
+    );
+    Ok(())
+}
+
+// Record or clear a creator's KYC hash; the badge is left unchanged
+pub fn set_creator_kyc(
+    ctx: Context<SetCreatorVerification>,
+    creator: Pubkey,
+    kyc_hash: Option<[u8; 32]>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let verified_creator = &mut ctx.accounts.verified_creator;
+    verified_creator.creator = creator;
+    verified_creator.kyc_hash = kyc_hash;
+    verified_creator.updated_at = now;
+    verified_creator.updated_by = ctx.accounts.authority.key();
+    verified_creator.bump = ctx.bumps.verified_creator;
+
+    emit_versioned!(CreatorKycUpdated {
+        creator,
+        kyc_hash,
+        authority: verified_creator.updated_by,
+        timestamp: now,
+    });
+
+    msg!(
+        "Creator {} KYC {}",
+        creator,
+        if kyc_hash.is_some() { "recorded" } else { "cleared" }
+    );
+    Ok(())
+}
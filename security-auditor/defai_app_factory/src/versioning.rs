@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+
+use crate::{AppRegistration, AppFactoryError, MAX_METADATA_URI_LEN};
+use defai_common::emit_versioned;
+
+const MAX_CHANGELOG_CID_LEN: usize = 64;
+
+#[account]
+pub struct AppVersion {
+    pub app_id: u64,
+    pub version: u32,                   // Sequential release number, starting at 1
+    pub metadata_uri: String,           // IPFS URI for this release's metadata
+    pub changelog_cid: String,          // IPFS CID of the release notes
+    pub min_required_version: u32,      // Oldest version that can upgrade directly (0 = any)
+    pub published_at: i64,
+    pub bump: u8,
+}
+
+impl AppVersion {
+    pub const LEN: usize = 8 + 8 + 4 + (4 + MAX_METADATA_URI_LEN) + (4 + MAX_CHANGELOG_CID_LEN) + 4 + 8 + 1;
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct PublishAppVersion<'info> {
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Account<'info, AppRegistration>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = AppVersion::LEN,
+        seeds = [
+            b"app_version".as_ref(),
+            &app_id.to_le_bytes(),
+            &(app_registration.latest_version + 1).to_le_bytes()
+        ],
+        bump
+    )]
+    pub app_version: Account<'info, AppVersion>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct AppVersionPublished {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub version: u32,
+    pub metadata_uri: String,
+    pub changelog_cid: String,
+    pub min_required_version: u32,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum VersionError {
+    #[msg("Changelog CID too long (max 64 characters)")]
+    ChangelogCidTooLong,
+    #[msg("Minimum required version must be an existing release")]
+    InvalidMinRequiredVersion,
+}
+
+pub fn publish_app_version(
+    ctx: Context<PublishAppVersion>,
+    app_id: u64,
+    metadata_uri: String,
+    changelog_cid: String,
+    min_required_version: u32,
+) -> Result<()> {
+    require!(metadata_uri.len() <= MAX_METADATA_URI_LEN, AppFactoryError::MetadataUriTooLong);
+    require!(changelog_cid.len() <= MAX_CHANGELOG_CID_LEN, VersionError::ChangelogCidTooLong);
+
+    let app_registration = &mut ctx.accounts.app_registration;
+    require!(
+        min_required_version <= app_registration.latest_version,
+        VersionError::InvalidMinRequiredVersion
+    );
+
+    let version = app_registration.latest_version
+        .checked_add(1)
+        .ok_or(AppFactoryError::MathOverflow)?;
+    app_registration.latest_version = version;
+    // The latest release becomes the app's current metadata
+    app_registration.metadata_uri = metadata_uri.clone();
+
+    let now = Clock::get()?.unix_timestamp;
+    let app_version = &mut ctx.accounts.app_version;
+    app_version.app_id = app_id;
+    app_version.version = version;
+    app_version.metadata_uri = metadata_uri.clone();
+    app_version.changelog_cid = changelog_cid.clone();
+    app_version.min_required_version = min_required_version;
+    app_version.published_at = now;
+    app_version.bump = ctx.bumps.app_version;
+
+    emit_versioned!(AppVersionPublished {
+        app_id,
+        version,
+        metadata_uri,
+        changelog_cid,
+        min_required_version,
+        timestamp: now,
+    });
+
+    msg!("App {} published version {}", app_id, version);
+    Ok(())
+}
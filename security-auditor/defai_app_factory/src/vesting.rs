@@ -0,0 +1,410 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Token, TokenAccount, Mint, Transfer},
+};
+
+use crate::{AppFactory, AppRegistration, AppFactoryError};
+use defai_common::{
+    emit_cpi_versioned, emit_versioned, event_cpi_of, require_covers, require_invariant,
+    require_vault_owner,
+};
+
+// Delayed creator payouts. Once a creator opts in, the creator share of every
+// DEFAI sale is paid into a program vault and unlocks linearly over
+// `vesting_days`. A new deposit re-vests whatever is still locked together
+// with the new amount over a fresh window, so unlocks never speed up.
+// The platform authority can freeze claims while a dispute is investigated,
+// and opening a dispute withholds the disputed share from the stream.
+// Vesting can't be switched off, and vested apps can't use revenue splits or
+// SOL/USDC pricing, which would pay the creator side directly.
+
+#[constant]
+pub const MAX_PAYOUT_VESTING_DAYS: u16 = 365;
+const SECONDS_PER_DAY: i64 = 86400;
+
+#[account]
+pub struct CreatorVesting {
+    pub app_id: u64,
+    pub vault: Pubkey,                  // DEFAI vault owned by this PDA
+    pub vesting_days: u16,
+    pub locked: u64,                    // Still vesting
+    pub vest_start: i64,
+    pub vest_end: i64,
+    pub claimable: u64,                 // Vested, not yet claimed
+    pub total_deposited: u64,
+    pub total_claimed: u64,
+    pub frozen: bool,                   // Claims blocked by the platform authority
+    pub bump: u8,
+}
+
+impl CreatorVesting {
+    pub const LEN: usize = 8 + 8 + 32 + 2 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+
+    // Moves the portion of `locked` vested by `now` into `claimable`
+    pub fn settle(&mut self, now: i64) -> Result<()> {
+        if self.locked == 0 || now <= self.vest_start {
+            return Ok(());
+        }
+        let vested = if now >= self.vest_end {
+            self.locked
+        } else {
+            (self.locked as u128)
+                .checked_mul((now - self.vest_start) as u128)
+                .ok_or(AppFactoryError::MathOverflow)?
+                .checked_div((self.vest_end - self.vest_start) as u128)
+                .ok_or(AppFactoryError::MathOverflow)? as u64
+        };
+        self.locked = self.locked
+            .checked_sub(vested)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.claimable = self.claimable
+            .checked_add(vested)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.vest_start = now.min(self.vest_end);
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64, now: i64) -> Result<()> {
+        self.settle(now)?;
+        self.locked = self.locked
+            .checked_add(amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.total_deposited = self.total_deposited
+            .checked_add(amount)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        self.vest_start = now;
+        self.vest_end = now
+            .checked_add(self.vesting_days as i64 * SECONDS_PER_DAY)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(())
+    }
+
+    // Takes up to `amount` back out of what the creator is owed, locked funds
+    // first, to hold against a dispute. Returns the amount withheld; the
+    // caller moves it out of the vault.
+    pub fn withhold(&mut self, amount: u64, now: i64) -> Result<u64> {
+        self.settle(now)?;
+        let from_locked = amount.min(self.locked);
+        let from_claimable = (amount - from_locked).min(self.claimable);
+        self.locked -= from_locked;
+        self.claimable -= from_claimable;
+
+        let withheld = from_locked + from_claimable;
+        self.total_deposited = self.total_deposited
+            .checked_sub(withheld)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        Ok(withheld)
+    }
+
+    // Deposits reconcile with claims plus what is still owed, and the vault
+    // holds at least what is owed
+    pub fn check_invariants(&self, vault_balance: u64) -> Result<()> {
+        let owed = self.locked
+            .checked_add(self.claimable)
+            .ok_or(AppFactoryError::MathOverflow)?;
+        require_invariant(
+            self.total_deposited.checked_sub(self.total_claimed) == Some(owed),
+            "creator vesting deposits reconcile with claims",
+        )?;
+        require_covers("creator vesting vault", vault_balance, owed)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct EnablePayoutVesting<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        mut,
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = CreatorVesting::LEN,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub creator_vesting: Box<Account<'info, CreatorVesting>>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = defai_mint,
+        token::authority = creator_vesting,
+        seeds = [b"creator_vesting_vault".as_ref(), creator_vesting.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: The app's revenue split PDA; must not exist
+    #[account(
+        seeds = [b"revenue_split".as_ref(), &app_id.to_le_bytes()],
+        bump
+    )]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct SetPayoutFrozen<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump,
+        has_one = authority @ AppFactoryError::UnauthorizedAuthority
+    )]
+    pub app_factory: Account<'info, AppFactory>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump
+    )]
+    pub creator_vesting: Account<'info, CreatorVesting>,
+
+    pub authority: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(app_id: u64)]
+pub struct ClaimCreatorProceeds<'info> {
+    #[account(
+        seeds = [b"app_factory"],
+        bump = app_factory.bump
+    )]
+    pub app_factory: Box<Account<'info, AppFactory>>,
+
+    #[account(
+        seeds = [b"app_registration".as_ref(), &app_id.to_le_bytes()],
+        bump = app_registration.bump,
+        has_one = creator @ AppFactoryError::UnauthorizedCreator
+    )]
+    pub app_registration: Box<Account<'info, AppRegistration>>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_vesting".as_ref(), &app_id.to_le_bytes()],
+        bump = creator_vesting.bump,
+        constraint = !creator_vesting.frozen @ VestingError::PayoutFrozen
+    )]
+    pub creator_vesting: Box<Account<'info, CreatorVesting>>,
+
+    #[account(
+        mut,
+        address = creator_vesting.vault
+    )]
+    pub vesting_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = defai_mint,
+        associated_token::authority = creator
+    )]
+    pub creator_defai_ata: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = defai_mint.key() == app_factory.defai_mint
+            @ AppFactoryError::InvalidDefaiMint
+    )]
+    pub defai_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct PayoutVestingEnabled {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub vesting_days: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutFrozenChanged {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub frozen: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorProceedsClaimed {
+    pub schema_version: u8,
+    pub app_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub still_locked: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum VestingError {
+    #[msg("Vesting period must be 1-365 days")]
+    InvalidVestingDays,
+    #[msg("Remove the revenue split before enabling payout vesting")]
+    RevenueSplitActive,
+    #[msg("App creator payouts vest; pass the vesting account and vault")]
+    MissingVestingAccounts,
+    #[msg("Vesting vault does not match the app's vesting account")]
+    InvalidVestingVault,
+    #[msg("Payouts for this app are frozen")]
+    PayoutFrozen,
+    #[msg("Nothing has vested yet")]
+    NothingToClaim,
+    #[msg("Vested apps only accept DEFAI through the vesting vault")]
+    VestedAppUnsupportedPath,
+}
+
+// Picks where the direct creator share of a DEFAI sale goes: the creator's
+// ATA, or the vesting vault (recording the deposit) for vested apps
+pub(crate) fn creator_payout_ata<'a, 'info>(
+    app_registration: &AppRegistration,
+    creator_vesting: Option<&mut CreatorVesting>,
+    vesting_vault: Option<&'a Account<'info, TokenAccount>>,
+    creator_defai_ata: &'a Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<&'a Account<'info, TokenAccount>> {
+    if app_registration.payout_vesting_days == 0 {
+        return Ok(creator_defai_ata);
+    }
+
+    let (creator_vesting, vesting_vault) = match (creator_vesting, vesting_vault) {
+        (Some(vesting), Some(vault)) => (vesting, vault),
+        _ => return err!(VestingError::MissingVestingAccounts),
+    };
+    require_keys_eq!(vesting_vault.key(), creator_vesting.vault, VestingError::InvalidVestingVault);
+    creator_vesting.deposit(amount, Clock::get()?.unix_timestamp)?;
+    Ok(vesting_vault)
+}
+
+pub fn enable_payout_vesting(
+    ctx: Context<EnablePayoutVesting>,
+    app_id: u64,
+    vesting_days: u16,
+) -> Result<()> {
+    require!(
+        vesting_days > 0 && vesting_days <= MAX_PAYOUT_VESTING_DAYS,
+        VestingError::InvalidVestingDays
+    );
+    require!(
+        ctx.accounts.revenue_split.data_is_empty(),
+        VestingError::RevenueSplitActive
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    let creator_vesting = &mut ctx.accounts.creator_vesting;
+    creator_vesting.app_id = app_id;
+    creator_vesting.vault = ctx.accounts.vesting_vault.key();
+    creator_vesting.vesting_days = vesting_days;
+    creator_vesting.locked = 0;
+    creator_vesting.vest_start = now;
+    creator_vesting.vest_end = now;
+    creator_vesting.claimable = 0;
+    creator_vesting.total_deposited = 0;
+    creator_vesting.total_claimed = 0;
+    creator_vesting.frozen = false;
+    creator_vesting.bump = ctx.bumps.creator_vesting;
+
+    ctx.accounts.app_registration.payout_vesting_days = vesting_days;
+
+    emit_versioned!(PayoutVestingEnabled {
+        app_id,
+        vesting_days,
+        timestamp: now,
+    });
+
+    msg!("Creator payouts for app {} now vest over {} days", app_id, vesting_days);
+    Ok(())
+}
+
+pub fn set_payout_frozen(ctx: Context<SetPayoutFrozen>, app_id: u64, frozen: bool) -> Result<()> {
+    ctx.accounts.creator_vesting.frozen = frozen;
+
+    emit_versioned!(PayoutFrozenChanged {
+        app_id,
+        frozen,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Payouts for app {} {}", app_id, if frozen { "frozen" } else { "unfrozen" });
+    Ok(())
+}
+
+pub fn claim_creator_proceeds(ctx: Context<ClaimCreatorProceeds>, app_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let creator_vesting = &mut ctx.accounts.creator_vesting;
+    creator_vesting.settle(now)?;
+
+    let amount = creator_vesting.claimable;
+    require!(amount > 0, VestingError::NothingToClaim);
+    creator_vesting.claimable = 0;
+    creator_vesting.total_claimed = creator_vesting.total_claimed
+        .checked_add(amount)
+        .ok_or(AppFactoryError::MathOverflow)?;
+
+    let vesting_bump = creator_vesting.bump;
+    let vesting_seeds = &[
+        b"creator_vesting".as_ref(),
+        &app_id.to_le_bytes(),
+        &[vesting_bump],
+    ];
+    let signer_seeds = &[&vesting_seeds[..]];
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vesting_vault.to_account_info(),
+            to: ctx.accounts.creator_defai_ata.to_account_info(),
+            authority: ctx.accounts.creator_vesting.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    ctx.accounts.vesting_vault.reload()?;
+    require_vault_owner(
+        "creator vesting vault",
+        &ctx.accounts.vesting_vault.owner,
+        &ctx.accounts.creator_vesting.key(),
+    )?;
+    ctx.accounts.creator_vesting.check_invariants(ctx.accounts.vesting_vault.amount)?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), CreatorProceedsClaimed {
+        app_id,
+        creator: ctx.accounts.creator.key(),
+        amount,
+        still_locked: ctx.accounts.creator_vesting.locked,
+        timestamp: now,
+    });
+
+    msg!("Creator claimed {} DEFAI of vested proceeds for app {}", amount, app_id);
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anchor_lang::{AccountDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use anyhow::{anyhow, bail, Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::from_account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+
+// Build an Anchor instruction from the generated `accounts` / `instruction` structs
+pub fn anchor_ix(program_id: Pubkey, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// PDA that signs a program's CPI events; instructions emitting them take it
+// along with the program itself
+pub fn event_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[defai_common::EVENT_AUTHORITY_SEED], program_id).0
+}
+
+// Cluster monikers accepted by --url, as in the Solana CLI
+pub fn resolve_url(url: &str) -> String {
+    match url {
+        "localnet" | "l" => "http://127.0.0.1:8899",
+        "devnet" | "d" => "https://api.devnet.solana.com",
+        "mainnet-beta" | "m" => "https://api.mainnet-beta.solana.com",
+        other => other,
+    }
+    .to_string()
+}
+
+pub struct Client {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub dry_run: bool,
+}
+
+impl Client {
+    pub fn new(url: &str, keypair: &str, dry_run: bool) -> Result<Self> {
+        let payer = read_keypair_file(keypair).map_err(|e| anyhow!("reading keypair {}: {}", keypair, e))?;
+        let rpc = RpcClient::new_with_commitment(resolve_url(url), CommitmentConfig::confirmed());
+        Ok(Self { rpc, payer, dry_run })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    // Send one transaction signed by the payer. With --dry-run it is only
+    // simulated and its logs printed.
+    pub fn send(&self, label: &str, ixs: &[Instruction]) -> Result<()> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.payer()), &[&self.payer], blockhash);
+
+        if self.dry_run {
+            let result = self.rpc.simulate_transaction(&tx)?.value;
+            for log in result.logs.unwrap_or_default() {
+                println!("  {}", log);
+            }
+            match result.err {
+                Some(err) => bail!("{}: simulation failed: {}", label, err),
+                None => println!("{}: simulation ok ({} CU)", label, result.units_consumed.unwrap_or(0)),
+            }
+            return Ok(());
+        }
+
+        let signature = self
+            .rpc
+            .send_and_confirm_transaction(&tx)
+            .with_context(|| format!("{} failed", label))?;
+        println!("{}: {}", label, signature);
+        Ok(())
+    }
+
+    pub fn exists(&self, address: &Pubkey) -> Result<bool> {
+        Ok(self.rpc.get_account_with_commitment(address, self.rpc.commitment())?.value.is_some())
+    }
+
+    pub fn account<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let data = self
+            .rpc
+            .get_account_data(address)
+            .with_context(|| format!("fetching account {}", address))?;
+        T::try_deserialize(&mut data.as_slice()).with_context(|| format!("decoding account {}", address))
+    }
+
+    // Every account of type T owned by `program_id`, matched on its discriminator
+    pub fn program_accounts<T: AccountDeserialize + Discriminator>(&self, program_id: &Pubkey) -> Result<Vec<(Pubkey, T)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, T::discriminator().to_vec()))]),
+            // The RPC default is base58, which is refused for accounts over 128 bytes
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        self.rpc
+            .get_program_accounts_with_config(program_id, config)?
+            .into_iter()
+            .map(|(address, account)| {
+                let decoded = T::try_deserialize(&mut account.data.as_slice())
+                    .with_context(|| format!("decoding account {}", address))?;
+                Ok((address, decoded))
+            })
+            .collect()
+    }
+
+    // Cluster time, which is what the programs' timelocks compare against
+    pub fn now(&self) -> Result<i64> {
+        let account = self.rpc.get_account(&sysvar::clock::ID)?;
+        let clock: Clock = from_account(&account).ok_or_else(|| anyhow!("decoding clock sysvar"))?;
+        Ok(clock.unix_timestamp)
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use defai_estate::{accounts, instruction, Estate};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::client::{anchor_ix, event_authority, Client};
+
+const PROGRAM_ID: Pubkey = defai_estate::ID;
+
+pub fn command() -> Command<'static> {
+    Command::new("estate")
+        .about("defai_estate keeper tasks")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("crank-triggers")
+                .about("Trigger inheritance on every estate whose inactivity and grace periods have run out")
+                .arg(
+                    Arg::new("estate")
+                        .long("estate")
+                        .takes_value(true)
+                        .value_parser(value_parser!(Pubkey))
+                        .help("Only this estate"),
+                ),
+        )
+}
+
+pub fn run(client: &Client, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("crank-triggers", m)) => crank_triggers(client, m.get_one::<Pubkey>("estate")),
+        _ => unreachable!("subcommand required"),
+    }
+}
+
+// Same condition `trigger_inheritance` enforces on-chain
+fn is_triggerable(estate: &Estate, now: i64) -> bool {
+    let grace_ends = estate
+        .last_active
+        .saturating_add(estate.inactivity_period)
+        .saturating_add(estate.grace_period);
+    !estate.is_locked && !estate.is_claimable && now > grace_ends
+}
+
+fn crank_triggers(client: &Client, only: Option<&Pubkey>) -> Result<()> {
+    let now = client.now()?;
+    let estates = match only {
+        Some(address) => vec![(*address, client.account::<Estate>(address)?)],
+        None => client.program_accounts::<Estate>(&PROGRAM_ID)?,
+    };
+
+    let due: Vec<Pubkey> = estates
+        .iter()
+        .filter(|(_, estate)| is_triggerable(estate, now))
+        .map(|(address, _)| *address)
+        .collect();
+    println!("{} estates scanned, {} due", estates.len(), due.len());
+
+    // The rewarded crank needs the keeper vault; fall back to the plain
+    // instruction until someone creates it
+    let keeper_vault = Pubkey::find_program_address(&[defai_estate::KEEPER_VAULT_SEED], &PROGRAM_ID).0;
+    let rewarded = client.exists(&keeper_vault)?;
+
+    // One estate per transaction so a single failure doesn't hold up the rest
+    let mut failed = 0;
+    for estate in due {
+        let ix = if rewarded {
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::CrankEstate {
+                    keeper: client.payer(),
+                    estate,
+                    keeper_vault,
+                    event_authority: event_authority(&PROGRAM_ID),
+                    program: PROGRAM_ID,
+                },
+                instruction::CrankTriggerInheritance {},
+            )
+        } else {
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::TriggerInheritance { authority: client.payer(), estate },
+                instruction::TriggerInheritance {},
+            )
+        };
+        if let Err(err) = client.send(&format!("trigger_inheritance {}", estate), &[ix]) {
+            eprintln!("{:#}", err);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        bail!("{} triggers failed", failed);
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! `defai-cli`: admin and keeper commands for the DEFAI programs.
+//!
+//! Builds instructions from the programs' own generated `accounts` /
+//! `instruction` structs and derives every PDA, so operators don't hand-craft
+//! transactions. `--dry-run` simulates instead of sending.
+
+mod client;
+mod estate;
+mod swap;
+
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgAction, Command};
+
+use client::Client;
+
+fn cli() -> Command<'static> {
+    Command::new("defai-cli")
+        .about("Admin and keeper commands for the DEFAI programs")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .global(true)
+                .takes_value(true)
+                .env("DEFAI_RPC_URL")
+                .default_value("localnet")
+                .help("RPC URL or moniker: localnet, devnet, mainnet-beta"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .short('k')
+                .global(true)
+                .takes_value(true)
+                .env("DEFAI_KEYPAIR")
+                .help("Signer and fee payer; the admin for admin commands [default: ~/.config/solana/id.json]"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Simulate transactions and print their logs instead of sending them"),
+        )
+        .subcommand(swap::command())
+        .subcommand(estate::command())
+}
+
+fn main() -> Result<()> {
+    let matches = cli().get_matches();
+    let keypair = match matches.get_one::<String>("keypair") {
+        Some(path) => path.clone(),
+        None => format!("{}/.config/solana/id.json", std::env::var("HOME")?),
+    };
+    let client = Client::new(matches.get_one::<String>("url").unwrap(), &keypair, *matches.get_one::<bool>("dry-run").unwrap())?;
+
+    match matches.subcommand() {
+        Some(("swap", m)) => swap::run(&client, m),
+        Some(("estate", m)) => estate::run(&client, m),
+        _ => unreachable!("subcommand required"),
+    }
+}
+
+// 32-byte hash given as 64 hex characters
+pub(crate) fn parse_hash(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim_start_matches("0x");
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(anyhow!("expected 64 hex characters, got {:?}", hex));
+    }
+    let mut hash = [0u8; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)?;
+    }
+    Ok(hash)
+}
+
+// "<change>: executable at <ts> (ready | in Nh Mm)"
+pub(crate) fn pending_line(change: &str, execute_after: i64, now: i64) -> String {
+    let remaining = execute_after - now;
+    let status = if remaining <= 0 {
+        "ready".to_string()
+    } else {
+        format!("in {}h {}m", remaining / 3600, remaining % 3600 / 60)
+    };
+    format!("{}: executable at {} ({})", change, execute_after, status)
+}
@@ -0,0 +1,407 @@
+use anchor_lang::system_program;
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgMatches, Command};
+use defai_common::{RevenueSource, WithdrawalWindow, AUDIT_LOG_SEED};
+use defai_swap::{
+    accounts,
+    cluster_config::{ClusterConfig, ClusterSettings},
+    instruction,
+    randomness_v2::RandomnessState,
+    treasury::TreasuryLedger,
+    AirdropVesting, Config, EscrowLedger, VestingStateV6,
+};
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::{native_token::sol_to_lamports, pubkey::Pubkey, system_instruction};
+
+use crate::client::{anchor_ix, Client};
+use crate::{parse_hash, pending_line};
+
+const PROGRAM_ID: Pubkey = defai_swap::ID;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+fn config() -> Pubkey {
+    pda(&[b"config"])
+}
+
+pub fn command() -> Command<'static> {
+    let pubkey = |name: &'static str| Arg::new(name).required(true).value_parser(value_parser!(Pubkey));
+    let cluster_args = [
+        Arg::new("randomness-program")
+            .long("randomness-program")
+            .takes_value(true)
+            .value_parser(value_parser!(Pubkey))
+            .help("Switchboard On-Demand program randomness accounts must belong to"),
+        Arg::new("whitelist-root")
+            .long("whitelist-root")
+            .takes_value(true)
+            .help("OG whitelist merkle root (64 hex chars)"),
+    ];
+
+    Command::new("swap")
+        .about("defai_swap admin and keeper tasks")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("init")
+                .about("Create the swap config accounts that do not exist yet")
+                .arg(pubkey("old-mint").long("old-mint").takes_value(true))
+                .arg(pubkey("new-mint").long("new-mint").takes_value(true))
+                .arg(pubkey("collection").long("collection").takes_value(true))
+                .arg(pubkey("treasury").long("treasury").takes_value(true))
+                .arg(
+                    Arg::new("prices")
+                        .long("prices")
+                        .required(true)
+                        .takes_value(true)
+                        .use_value_delimiter(true)
+                        .number_of_values(5)
+                        .value_name("PRICE")
+                        .value_parser(value_parser!(u64))
+                        .help("Five tier prices in base units, comma separated"),
+                )
+                .args(cluster_args.clone()),
+        )
+        .subcommand(Command::new("pending").about("Show pending timelocked changes"))
+        .subcommand(Command::new("propose-admin").about("Start the admin handover timelock").arg(pubkey("new-admin")))
+        .subcommand(Command::new("accept-admin").about("Hand over to the pending admin once its timelock has passed"))
+        .subcommand(
+            Command::new("propose-vrf-mode")
+                .about("Propose turning Switchboard randomness on (true) or off (false)")
+                .arg(Arg::new("enabled").required(true).value_parser(value_parser!(bool))),
+        )
+        .subcommand(Command::new("cancel-vrf-mode").about("Drop the pending VRF mode change"))
+        .subcommand(Command::new("execute-vrf-mode").about("Apply the pending VRF mode change"))
+        .subcommand(
+            Command::new("propose-conversion-ratio")
+                .about("OLD tokens owed per NEW token of tier price, as NUMERATOR DENOMINATOR")
+                .arg(Arg::new("numerator").required(true).value_parser(value_parser!(u64)))
+                .arg(Arg::new("denominator").required(true).value_parser(value_parser!(u64))),
+        )
+        .subcommand(Command::new("execute-conversion-ratio").about("Apply the pending conversion ratio"))
+        .subcommand(
+            Command::new("propose-cluster-config")
+                .about("Propose new cluster settings; omitted values keep their current setting")
+                .args(cluster_args),
+        )
+        .subcommand(Command::new("cancel-cluster-config").about("Drop the pending cluster config change"))
+        .subcommand(Command::new("execute-cluster-config").about("Apply the pending cluster config change"))
+        .subcommand(
+            Command::new("fund-vrf")
+                .about("Top up the randomness authority, which pays Switchboard randomness fees")
+                .arg(Arg::new("sol").required(true).value_parser(value_parser!(f64))),
+        )
+        .subcommand(Command::new("solvency").about("Compare escrowed DEFAI with outstanding vesting; exits 1 on a shortfall"))
+        .subcommand(Command::new("treasury").about("Show booked swap tax, withdrawals and the withdrawal policy"))
+}
+
+pub fn run(client: &Client, matches: &ArgMatches) -> Result<()> {
+    let admin = client.payer();
+    let config = config();
+    let update_config = || accounts::UpdateConfig { admin, config };
+    let update_cluster_config = || accounts::UpdateClusterConfig { admin, config, cluster_config: pda(&[b"cluster_config"]) };
+    let change_admin = || accounts::ChangeAdmin { admin, config, audit_log: pda(&[AUDIT_LOG_SEED]) };
+
+    match matches.subcommand() {
+        Some(("init", m)) => init(client, m),
+        Some(("pending", _)) => pending(client),
+        Some(("propose-admin", m)) => client.send(
+            "propose_admin_change",
+            &[anchor_ix(PROGRAM_ID, change_admin(), instruction::ProposeAdminChange {
+                new_admin: *m.get_one("new-admin").unwrap(),
+            })],
+        ),
+        Some(("accept-admin", _)) => client.send(
+            "accept_admin_change",
+            &[anchor_ix(PROGRAM_ID, change_admin(), instruction::AcceptAdminChange {})],
+        ),
+        Some(("propose-vrf-mode", m)) => client.send(
+            "propose_vrf_mode_change",
+            &[anchor_ix(PROGRAM_ID, update_config(), instruction::ProposeVrfModeChange {
+                enabled: *m.get_one("enabled").unwrap(),
+            })],
+        ),
+        Some(("cancel-vrf-mode", _)) => client.send(
+            "cancel_vrf_mode_change",
+            &[anchor_ix(PROGRAM_ID, update_config(), instruction::CancelVrfModeChange {})],
+        ),
+        Some(("execute-vrf-mode", _)) => client.send(
+            "execute_vrf_mode_change",
+            &[anchor_ix(
+                PROGRAM_ID,
+                accounts::ExecuteVrfModeChange { admin, config, randomness_state: pda(&[b"randomness_state"]) },
+                instruction::ExecuteVrfModeChange {},
+            )],
+        ),
+        Some(("propose-conversion-ratio", m)) => client.send(
+            "propose_conversion_ratio",
+            &[anchor_ix(PROGRAM_ID, update_config(), instruction::ProposeConversionRatio {
+                numerator: *m.get_one("numerator").unwrap(),
+                denominator: *m.get_one("denominator").unwrap(),
+            })],
+        ),
+        Some(("execute-conversion-ratio", _)) => client.send(
+            "execute_conversion_ratio_change",
+            &[anchor_ix(PROGRAM_ID, update_config(), instruction::ExecuteConversionRatioChange {})],
+        ),
+        Some(("propose-cluster-config", m)) => {
+            let current: ClusterConfig = client.account(&pda(&[b"cluster_config"]))?;
+            let settings = cluster_settings(m, current.settings)?;
+            client.send(
+                "propose_cluster_config_change",
+                &[anchor_ix(PROGRAM_ID, update_cluster_config(), instruction::ProposeClusterConfigChange { settings })],
+            )
+        }
+        Some(("cancel-cluster-config", _)) => client.send(
+            "cancel_cluster_config_change",
+            &[anchor_ix(PROGRAM_ID, update_cluster_config(), instruction::CancelClusterConfigChange {})],
+        ),
+        Some(("execute-cluster-config", _)) => {
+            // Keep the whitelist account's copy of the root in sync when it exists
+            let whitelist = pda(&[b"whitelist"]);
+            let whitelist = client.exists(&whitelist)?.then_some(whitelist);
+            client.send(
+                "execute_cluster_config_change",
+                &[anchor_ix(
+                    PROGRAM_ID,
+                    accounts::ExecuteClusterConfigChange {
+                        admin,
+                        config,
+                        cluster_config: pda(&[b"cluster_config"]),
+                        whitelist,
+                    },
+                    instruction::ExecuteClusterConfigChange {},
+                )],
+            )
+        }
+        Some(("fund-vrf", m)) => {
+            let state: RandomnessState = client.account(&pda(&[b"randomness_state"]))?;
+            let lamports = sol_to_lamports(*m.get_one::<f64>("sol").unwrap());
+            println!("funding randomness authority {} with {} lamports", state.authority, lamports);
+            client.send("fund_vrf", &[system_instruction::transfer(&admin, &state.authority, lamports)])
+        }
+        Some(("solvency", _)) => solvency(client),
+        Some(("treasury", _)) => treasury(client),
+        _ => unreachable!("subcommand required"),
+    }
+}
+
+fn cluster_settings(m: &ArgMatches, defaults: ClusterSettings) -> Result<ClusterSettings> {
+    Ok(ClusterSettings {
+        randomness_program: m.get_one("randomness-program").copied().unwrap_or(defaults.randomness_program),
+        whitelist_root: match m.get_one::<String>("whitelist-root") {
+            Some(root) => parse_hash(root)?,
+            None => defaults.whitelist_root,
+        },
+    })
+}
+
+// Each step is its own transaction and is skipped when its account already
+// exists, so an interrupted run can simply be repeated
+fn init(client: &Client, m: &ArgMatches) -> Result<()> {
+    let admin = client.payer();
+    let config = config();
+    let system_program = system_program::ID;
+
+    if !client.exists(&config)? {
+        let prices: Vec<u64> = m.get_many::<u64>("prices").unwrap().copied().collect();
+        client.send(
+            "initialize",
+            &[anchor_ix(
+                PROGRAM_ID,
+                accounts::Initialize {
+                    admin,
+                    old_mint: *m.get_one("old-mint").unwrap(),
+                    new_mint: *m.get_one("new-mint").unwrap(),
+                    collection: *m.get_one("collection").unwrap(),
+                    treasury: *m.get_one("treasury").unwrap(),
+                    config,
+                    escrow: pda(&[b"escrow"]),
+                    tax_state: pda(&[b"tax_state"]),
+                    system_program,
+                },
+                instruction::Initialize { prices },
+            )],
+        )?;
+    }
+
+    let cluster_config = pda(&[b"cluster_config"]);
+    let steps = [
+        (
+            "initialize_escrow_ledger",
+            pda(&[b"escrow_ledger"]),
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeEscrowLedger { admin, config, escrow_ledger: pda(&[b"escrow_ledger"]), system_program },
+                instruction::InitializeEscrowLedger {},
+            ),
+        ),
+        (
+            "initialize_audit_log",
+            pda(&[AUDIT_LOG_SEED]),
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeAuditLog { admin, config, audit_log: pda(&[AUDIT_LOG_SEED]), system_program },
+                instruction::InitializeAuditLog {},
+            ),
+        ),
+        (
+            "initialize_randomness_v2",
+            pda(&[b"randomness_state"]),
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeRandomness { authority: admin, randomness_state: pda(&[b"randomness_state"]), system_program },
+                instruction::InitializeRandomnessV2 {},
+            ),
+        ),
+        (
+            "initialize_cluster_config",
+            cluster_config,
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeClusterConfig { admin, config, cluster_config, system_program },
+                instruction::InitializeClusterConfig {
+                    settings: Some(cluster_settings(m, ClusterSettings::localnet())?),
+                },
+            ),
+        ),
+        (
+            "initialize_whitelist",
+            pda(&[b"whitelist"]),
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeWhitelist { admin, config, cluster_config, whitelist: pda(&[b"whitelist"]), system_program },
+                instruction::InitializeWhitelist {},
+            ),
+        ),
+        (
+            "initialize_treasury_ledger",
+            pda(&[b"treasury_ledger"]),
+            anchor_ix(
+                PROGRAM_ID,
+                accounts::InitializeTreasuryLedger {
+                    admin,
+                    config,
+                    mint: *m.get_one("new-mint").unwrap(),
+                    treasury_ledger: pda(&[b"treasury_ledger"]),
+                    system_program,
+                },
+                instruction::InitializeTreasuryLedger {},
+            ),
+        ),
+    ];
+
+    for (label, address, ix) in steps {
+        if client.exists(&address)? {
+            println!("{}: {} already exists, skipped", label, address);
+            continue;
+        }
+        client.send(label, &[ix])?;
+    }
+    Ok(())
+}
+
+fn pending(client: &Client) -> Result<()> {
+    let now = client.now()?;
+    let cfg: Config = client.account(&config())?;
+    let cluster: Option<ClusterConfig> = match client.exists(&pda(&[b"cluster_config"]))? {
+        true => Some(client.account(&pda(&[b"cluster_config"]))?),
+        false => None,
+    };
+    let ledger: Option<TreasuryLedger> = match client.exists(&pda(&[b"treasury_ledger"]))? {
+        true => Some(client.account(&pda(&[b"treasury_ledger"]))?),
+        false => None,
+    };
+
+    let mut lines = Vec::new();
+    if let Some(new_admin) = cfg.pending_admin {
+        lines.push(pending_line(&format!("admin -> {}", new_admin), cfg.admin_change_timestamp, now));
+    }
+    if let Some(enabled) = cfg.pending_vrf_enabled {
+        lines.push(pending_line(&format!("vrf_enabled -> {}", enabled), cfg.vrf_change_timestamp, now));
+    }
+    if let Some(ratio) = &cfg.pending_old_conversion_ratio {
+        let change = format!("conversion ratio -> {}:{}", ratio.numerator, ratio.denominator);
+        lines.push(pending_line(&change, cfg.conversion_ratio_change_timestamp, now));
+    }
+    if let Some(ClusterConfig { pending_settings: Some(settings), settings_change_timestamp, .. }) = &cluster {
+        let change = format!("cluster config -> randomness program {}", settings.randomness_program);
+        lines.push(pending_line(&change, *settings_change_timestamp, now));
+    }
+
+    if let Some(TreasuryLedger { pending_policy: Some(policy), policy_change_timestamp, .. }) = &ledger {
+        let change = format!("withdrawal policy -> {} per {}s", policy.cap, policy.period);
+        lines.push(pending_line(&change, *policy_change_timestamp, now));
+    }
+
+    if lines.is_empty() {
+        println!("no pending changes");
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// Escrowed DEFAI against what the program still owes: unreleased bonus
+// vesting and unreleased airdrop vesting. The ledger's `reserved_airdrop`
+// is shown next to the airdrop sum it is meant to track.
+fn solvency(client: &Client) -> Result<()> {
+    let cfg: Config = client.account(&config())?;
+    let escrow = pda(&[b"escrow"]);
+
+    let mut escrowed: u64 = 0;
+    for account in client.rpc.get_token_accounts_by_owner(&escrow, TokenAccountsFilter::Mint(cfg.new_mint))? {
+        let balance = client.rpc.get_token_account_balance(&account.pubkey.parse()?)?;
+        escrowed = escrowed.saturating_add(balance.amount.parse()?);
+    }
+
+    let vesting: u64 = client
+        .program_accounts::<VestingStateV6>(&PROGRAM_ID)?
+        .iter()
+        .map(|(_, v)| v.total_amount.saturating_sub(v.released_amount))
+        .sum();
+    let airdrop: u64 = client
+        .program_accounts::<AirdropVesting>(&PROGRAM_ID)?
+        .iter()
+        .map(|(_, v)| v.total_amount.saturating_sub(v.released_amount))
+        .sum();
+    let ledger: EscrowLedger = client.account(&pda(&[b"escrow_ledger"]))?;
+
+    let owed = vesting.saturating_add(airdrop);
+    println!("escrowed DEFAI          {:>20}", escrowed);
+    println!("bonus vesting owed      {:>20}", vesting);
+    println!("airdrop vesting owed    {:>20}", airdrop);
+    println!("ledger reserved_airdrop {:>20}", ledger.reserved_airdrop);
+    println!("total owed              {:>20}", owed);
+
+    if escrowed < owed {
+        bail!("escrow is short by {}", owed - escrowed);
+    }
+    println!("surplus                 {:>20}", escrowed - owed);
+    Ok(())
+}
+
+// The swap side of the DAO's revenue books. Income is tax only; the other
+// revenue sources belong to the other programs' ledgers.
+fn treasury(client: &Client) -> Result<()> {
+    let now = client.now()?;
+    let ledger: TreasuryLedger = client.account(&pda(&[b"treasury_ledger"]))?;
+    let tax = RevenueSource::SwapTax.index();
+    let (mut window_start, mut withdrawn) = (ledger.window_start, ledger.withdrawn_in_window);
+    let available = WithdrawalWindow::new(&mut window_start, &mut withdrawn).remaining(
+        ledger.policy.cap,
+        ledger.policy.period,
+        now,
+    );
+
+    println!("mint                    {:>20}", ledger.mint);
+    println!("swap tax booked         {:>20}", ledger.revenue[tax]);
+    println!("swap tax payments       {:>20}", ledger.revenue_entries[tax]);
+    println!("total withdrawn         {:>20}", ledger.total_withdrawn);
+    println!("withdrawal cap          {:>20}", ledger.policy.cap);
+    println!("withdrawal period (s)   {:>20}", ledger.policy.period);
+    println!("available this period   {:>20}", available);
+    Ok(())
+}
@@ -0,0 +1,76 @@
+#![no_main]
+
+// Cross-checks `verify_merkle_proof` against the reference tree shared with
+// the proptest suite. Each input is a set of allowlist entries, a member whose
+// proof gets mangled, and a raw (leaf, proof, root) triple.
+
+use anchor_lang::prelude::Pubkey;
+use arbitrary::Arbitrary;
+use defai_common::{merkle_leaf, verify_merkle_proof};
+use libfuzzer_sys::fuzz_target;
+
+#[path = "../../tests/common/mod.rs"]
+mod reference;
+
+#[derive(Arbitrary, Debug)]
+enum Mangle {
+    None,
+    FlipBit { element: u8, bit: u8 },
+    Truncate,
+    Append([u8; 32]),
+    Swap { a: u8, b: u8 },
+    WrongAmount(u64),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    entries: Vec<([u8; 32], u64)>,
+    member: u8,
+    mangle: Mangle,
+    raw_leaf: [u8; 32],
+    raw_proof: Vec<[u8; 32]>,
+    raw_root: [u8; 32],
+}
+
+fuzz_target!(|input: Input| {
+    // Malformed proofs against arbitrary roots must agree with the reference
+    assert_eq!(
+        verify_merkle_proof(input.raw_leaf, &input.raw_proof, &input.raw_root),
+        reference::verify(input.raw_leaf, &input.raw_proof, &input.raw_root),
+    );
+
+    if input.entries.is_empty() {
+        return;
+    }
+    let leaves: Vec<_> = input.entries.iter().map(|(user, amount)| reference::leaf(user, *amount)).collect();
+    let levels = reference::levels(&leaves);
+    let root = reference::root(&levels);
+    let index = input.member as usize % input.entries.len();
+    let (user, amount) = input.entries[index];
+    let mut leaf = merkle_leaf(&Pubkey::new_from_array(user), amount);
+    let mut proof = reference::proof(&levels, index);
+    assert!(verify_merkle_proof(leaf, &proof, &root));
+
+    match input.mangle {
+        Mangle::None => return,
+        Mangle::FlipBit { element, bit } if !proof.is_empty() => {
+            let element = element as usize % proof.len();
+            proof[element][bit as usize / 8] ^= 1 << (bit % 8);
+        }
+        Mangle::Truncate if !proof.is_empty() => {
+            proof.pop();
+        }
+        Mangle::Append(extra) => proof.push(extra),
+        Mangle::Swap { a, b } if !proof.is_empty() => {
+            let len = proof.len();
+            proof.swap(a as usize % len, b as usize % len);
+        }
+        Mangle::WrongAmount(other) if other != amount => {
+            leaf = merkle_leaf(&Pubkey::new_from_array(user), other);
+        }
+        _ => return,
+    }
+    // A mangled proof may still verify only if the reference agrees, e.g. a
+    // swap of equal siblings or a duplicate leaf in the entry list
+    assert_eq!(verify_merkle_proof(leaf, &proof, &root), reference::verify(leaf, &proof, &root));
+});
@@ -0,0 +1,12 @@
+// Layout shared by every program's `AuditLog` PDA. The account types live in
+// each program (so they appear in its IDL); these constants keep the seed and
+// ring size identical so one indexer or script can read all three logs.
+
+pub const AUDIT_LOG_SEED: &[u8] = b"audit_log";
+
+// Entries retained per log; entry `seq` is stored at `seq % AUDIT_LOG_SLOTS`
+pub const AUDIT_LOG_SLOTS: usize = 32;
+
+pub fn audit_slot(seq: u64) -> usize {
+    (seq % AUDIT_LOG_SLOTS as u64) as usize
+}
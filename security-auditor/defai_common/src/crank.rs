@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::CommonError;
+
+// Keeper cranks
+// Time-based transitions (inheritance triggers, tax resets, rental expiries,
+// stake unlocks, ...) are exposed by each program as permissionless `crank_*`
+// instructions, so one keeper bot can service the whole protocol. A crank
+// fails unless its transition is due and not yet applied; repeating one is an
+// error and pays nothing. A successful crank pays the keeper from the
+// program's `keeper_vault` PDA (seed "keeper_vault"). Anyone can top the
+// vault up with a plain SOL transfer. An empty vault pays nothing but doesn't
+// block the crank.
+
+// Per successful crank; two base signature fees
+pub const CRANK_REWARD_LAMPORTS: u64 = 10_000;
+
+// Move up to CRANK_REWARD_LAMPORTS from `vault` (owned by the calling program)
+// to `keeper`, keeping the vault rent-exempt. Returns the amount paid.
+pub fn pay_crank_reward(vault: &AccountInfo, keeper: &AccountInfo, rent: &Rent) -> Result<u64> {
+    let reserve = rent.minimum_balance(vault.data_len());
+    let reward = vault.lamports().saturating_sub(reserve).min(CRANK_REWARD_LAMPORTS);
+    if reward == 0 {
+        return Ok(0);
+    }
+    let vault_lamports = vault.lamports().checked_sub(reward).ok_or(CommonError::MathOverflow)?;
+    let keeper_lamports = keeper.lamports().checked_add(reward).ok_or(CommonError::MathOverflow)?;
+    **vault.try_borrow_mut_lamports()? = vault_lamports;
+    **keeper.try_borrow_mut_lamports()? = keeper_lamports;
+    Ok(reward)
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+// Offset past every program-local enum (which all start at 6000) so a shared
+// error is recognisable by its code alone, whichever program returned it.
+#[error_code(offset = 9000)]
+pub enum CommonError {
+    #[msg("Overflow in maths operation")]
+    MathOverflow,
+    #[msg("Timelock not expired")]
+    TimelockNotExpired,
+    #[msg("No pending change")]
+    NoPendingChange,
+    #[msg("Security contact or policy is empty or too long")]
+    InvalidSecurityInfo,
+    #[msg("Security info expiry must be in the future")]
+    SecurityInfoExpired,
+    #[msg("Withdrawal period must be between 1 second and 365 days")]
+    InvalidWithdrawalPolicy,
+    #[msg("Withdrawal exceeds what the treasury policy allows this period")]
+    WithdrawalLimitExceeded,
+    #[msg("Receipt log is full")]
+    ReceiptLogFull,
+    #[msg("Program links must be distinct, set, and include this program under its own id")]
+    InvalidProgramLinks,
+    #[msg("Account or program is not the one linked in the program registry")]
+    UnlinkedProgram,
+    #[msg("Accounting invariant violated")]
+    InvariantViolated,
+}
@@ -0,0 +1,90 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+// Event schema versioning. Every event in the DEFAI programs starts with a
+// `schema_version: u8` field (`version` is already an app release number in
+// defai_app_factory). It is declared first so it is the byte right after the
+// 8-byte discriminator: an indexer can read it before decoding the rest and
+// pick the matching layout instead of silently misreading a changed event.
+//
+// Events are emitted through `emit_versioned!`, which fills the field in. A
+// struct literal without `schema_version` doesn't compile, so an event can't be
+// emitted unversioned.
+//
+// All events are at EVENT_VERSION. When an event's fields change, give that
+// event its own version with the second form, `emit_versioned!(Event { .. }, 2)`,
+// and note it in the program's README.
+
+pub const EVENT_VERSION: u8 = 1;
+
+#[macro_export]
+macro_rules! emit_versioned {
+    ($($event:ident)::+ { $($fields:tt)* }) => {
+        $crate::emit_versioned!($($event)::+ { $($fields)* }, $crate::EVENT_VERSION)
+    };
+    ($($event:ident)::+ { $($fields:tt)* }, $version:expr) => {
+        ::anchor_lang::prelude::emit!($($event)::+ { schema_version: $version, $($fields)* })
+    };
+}
+
+// CPI events
+// Busy transactions can have their logs truncated, and `emit!` events go with
+// them without an error. Events that record value moving (payments, claims,
+// payouts, refunds, withdrawals) are instead emitted the way Anchor's
+// `emit_cpi!` does it: a self-CPI signed by the program's event authority PDA
+// (seed "__event_authority"), which lands in the transaction's inner
+// instructions. The instruction's accounts take `#[event_cpi]`, which appends
+// `event_authority` and `program`.
+//
+// `emit_cpi!` needs a variable named `ctx` and can't be nested in another
+// macro, so `emit_cpi_versioned!` sends the same instruction through
+// `EventCpi`. Handlers build one with `event_cpi_of!(ctx)`; shared payment
+// helpers take it as an argument.
+
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub struct EventCpi<'a, 'info> {
+    program_id: &'a Pubkey,
+    event_authority: &'a AccountInfo<'info>,
+    bump: u8,
+}
+
+impl<'a, 'info> EventCpi<'a, 'info> {
+    pub fn new(program_id: &'a Pubkey, event_authority: &'a AccountInfo<'info>, bump: u8) -> Self {
+        Self { program_id, event_authority, bump }
+    }
+
+    pub fn emit<E: anchor_lang::Event>(&self, event: &E) -> Result<()> {
+        let data: Vec<u8> = EVENT_IX_TAG_LE.into_iter().chain(event.data()).collect();
+        let ix = Instruction::new_with_bytes(
+            *self.program_id,
+            &data,
+            vec![AccountMeta::new_readonly(*self.event_authority.key, true)],
+        );
+        invoke_signed(
+            &ix,
+            std::slice::from_ref(self.event_authority),
+            &[&[EVENT_AUTHORITY_SEED, &[self.bump]]],
+        )
+        .map_err(Into::into)
+    }
+}
+
+#[macro_export]
+macro_rules! event_cpi_of {
+    ($ctx:expr) => {
+        $crate::event::EventCpi::new($ctx.program_id, &$ctx.accounts.event_authority, $ctx.bumps.event_authority)
+    };
+}
+
+#[macro_export]
+macro_rules! emit_cpi_versioned {
+    ($events:expr, $($event:ident)::+ { $($fields:tt)* }) => {
+        $crate::emit_cpi_versioned!($events, $($event)::+ { $($fields)* }, $crate::EVENT_VERSION)
+    };
+    ($events:expr, $($event:ident)::+ { $($fields:tt)* }, $version:expr) => {
+        $events.emit(&$($event)::+ { schema_version: $version, $($fields)* })?
+    };
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::CommonError;
+
+// Invariant checks
+// Value-moving instructions end by checking the accounting they touched
+// against the accounts it describes: a vault still covers what is booked
+// against it, shares still add up, a vault still belongs to its PDA. A
+// violation logs which invariant broke and the values involved, then fails
+// with `InvariantViolated`, so drift stops the instruction that caused it
+// instead of surfacing later as a shortfall nobody can trace.
+
+// Fail unless `holds`, logging `name`
+pub fn require_invariant(holds: bool, name: &str) -> Result<()> {
+    if !holds {
+        msg!("Invariant violated: {}", name);
+        return err!(CommonError::InvariantViolated);
+    }
+    Ok(())
+}
+
+// A balance covers what is booked against it
+pub fn require_covers(name: &str, balance: u64, liabilities: u64) -> Result<()> {
+    if balance < liabilities {
+        msg!("Invariant violated: {}: balance {} < liabilities {}", name, balance, liabilities);
+        return err!(CommonError::InvariantViolated);
+    }
+    Ok(())
+}
+
+// Shares add up to exactly `total` (100 for percentages, 10,000 for bps)
+pub fn require_shares_total<I>(name: &str, shares: I, total: u64) -> Result<()>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let sum = shares
+        .into_iter()
+        .try_fold(0u64, |sum, share| sum.checked_add(share));
+    if sum != Some(total) {
+        match sum {
+            Some(sum) => msg!("Invariant violated: {}: shares sum to {}, not {}", name, sum, total),
+            None => msg!("Invariant violated: {}: shares overflow", name),
+        }
+        return err!(CommonError::InvariantViolated);
+    }
+    Ok(())
+}
+
+// A token account still belongs to the PDA that signs for it
+pub fn require_vault_owner(name: &str, vault_owner: &Pubkey, expected: &Pubkey) -> Result<()> {
+    if vault_owner != expected {
+        msg!("Invariant violated: {}: owned by {}, expected {}", name, vault_owner, expected);
+        return err!(CommonError::InvariantViolated);
+    }
+    Ok(())
+}
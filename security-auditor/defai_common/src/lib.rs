@@ -0,0 +1,32 @@
+// Code shared by the DEFAI programs: error codes, checked math, merkle
+// proofs, the admin timelock, the audit log and security info layouts, keeper
+// crank rewards, treasury accounting, compressed receipts, the program
+// registry, invariant checks, event versioning and CPI events. This is a
+// plain library crate with no program id or instructions; each program links
+// it and keeps its own accounts and events.
+
+pub mod audit;
+pub mod crank;
+pub mod error;
+pub mod event;
+pub mod invariant;
+pub mod math;
+pub mod merkle;
+pub mod receipts;
+pub mod registry;
+pub mod security;
+pub mod timelock;
+pub mod treasury;
+
+pub use audit::*;
+pub use crank::*;
+pub use error::CommonError;
+pub use event::{EventCpi, EVENT_AUTHORITY_SEED, EVENT_VERSION};
+pub use invariant::*;
+pub use math::*;
+pub use merkle::*;
+pub use receipts::*;
+pub use registry::*;
+pub use security::*;
+pub use timelock::*;
+pub use treasury::*;
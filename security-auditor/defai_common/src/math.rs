@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::CommonError;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const PERCENT_DENOMINATOR: u64 = 100;
+
+// Checked arithmetic that fails with CommonError::MathOverflow instead of
+// returning an Option, so call sites read `a.safe_add(b)?`.
+pub trait SafeMath: Sized {
+    fn safe_add(self, rhs: Self) -> Result<Self>;
+    fn safe_sub(self, rhs: Self) -> Result<Self>;
+    fn safe_mul(self, rhs: Self) -> Result<Self>;
+    fn safe_div(self, rhs: Self) -> Result<Self>;
+}
+
+macro_rules! impl_safe_math {
+    ($($t:ty),*) => {$(
+        impl SafeMath for $t {
+            fn safe_add(self, rhs: Self) -> Result<Self> {
+                self.checked_add(rhs).ok_or(error!(CommonError::MathOverflow))
+            }
+            fn safe_sub(self, rhs: Self) -> Result<Self> {
+                self.checked_sub(rhs).ok_or(error!(CommonError::MathOverflow))
+            }
+            fn safe_mul(self, rhs: Self) -> Result<Self> {
+                self.checked_mul(rhs).ok_or(error!(CommonError::MathOverflow))
+            }
+            fn safe_div(self, rhs: Self) -> Result<Self> {
+                self.checked_div(rhs).ok_or(error!(CommonError::MathOverflow))
+            }
+        }
+    )*};
+}
+
+impl_safe_math!(u8, u16, u32, u64, u128, i64);
+
+// amount * numerator / denominator, computed in u128 so the product can't
+// overflow. Fails if the denominator is zero or the result exceeds u64.
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let result = (amount as u128)
+        .safe_mul(numerator as u128)?
+        .safe_div(denominator as u128)?;
+    u64::try_from(result).map_err(|_| error!(CommonError::MathOverflow))
+}
+
+// The basis-point share of an amount, rounded down
+pub fn bps_of(amount: u64, bps: u64) -> Result<u64> {
+    mul_div(amount, bps, BPS_DENOMINATOR)
+}
+
+// The percentage share of an amount, rounded down
+pub fn percent_of(amount: u64, percent: u64) -> Result<u64> {
+    mul_div(amount, percent, PERCENT_DENOMINATOR)
+}
+
+// Split an amount into (bps share, remainder); the remainder keeps the dust
+pub fn split_bps(amount: u64, bps: u64) -> Result<(u64, u64)> {
+    let share = bps_of(amount, bps)?;
+    Ok((share, amount.safe_sub(share)?))
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+// Sorted-pair keccak merkle trees, as produced by the allowlist scripts: each
+// level hashes the smaller node first, so proofs carry no left/right flags.
+
+// Leaf for an allowlist entry that carries an amount (OG tier 0, airdrop)
+pub fn merkle_leaf(user: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[user.as_ref(), &amount.to_le_bytes()]).0
+}
+
+// Leaf for an address-only allowlist (presale phases)
+pub fn merkle_address_leaf(user: &Pubkey) -> [u8; 32] {
+    keccak::hash(user.as_ref()).0
+}
+
+// Parent of two sibling nodes
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[a, b]).0
+    } else {
+        keccak::hashv(&[b, a]).0
+    }
+}
+
+// Fold the proof from the leaf up and compare against the root. An empty
+// proof is only valid for a single-leaf tree whose root is the leaf itself.
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    proof.iter().fold(leaf, |acc, sibling| hash_pair(&acc, sibling)) == *root
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use crate::{hash_pair, CommonError, SafeMath};
+
+// Compressed receipts
+// Finished records nobody writes to again (completed estate claims, bonus
+// rerolls) don't need an account each. A program appends them as leaves of
+// an append-only merkle tree kept in its `ReceiptLog` PDA (seed
+// "receipt_log") and publishes the receipt bytes in a CPI event. Indexers
+// rebuild the tree from those events, and anyone can prove a receipt against
+// the on-chain root with `verify_merkle_proof`. Active or mutable state stays
+// in regular PDAs.
+//
+// The log stores only the rightmost path of the tree (the frontier), so an
+// append needs no proof and appends from different transactions can't go
+// stale against each other. Hashing is sorted-pair keccak, as for the
+// allowlists; empty subtrees hash from a zero leaf. The account types live in
+// each program (so they appear in its IDL), like the treasury ledger.
+
+pub const RECEIPT_LOG_SEED: &[u8] = b"receipt_log";
+
+// Up to 2^20 - 1 (about a million) receipts per log
+pub const RECEIPT_TREE_DEPTH: usize = 20;
+pub const MAX_RECEIPTS: u64 = (1 << RECEIPT_TREE_DEPTH) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReceiptKind {
+    EstateClaim = 0,  // defai_estate: a completed claim
+    BonusReroll = 1,  // defai_swap: a reroll of a bonus NFT
+}
+
+// Leaf for the receipt at `index`. The index keeps two identical receipts
+// apart.
+pub fn receipt_leaf(kind: ReceiptKind, index: u64, data: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[&[kind as u8], &index.to_le_bytes(), data]).0
+}
+
+// Root of a tree holding `leaf_count` leaves with the given frontier
+pub fn receipt_root(frontier: &[[u8; 32]; RECEIPT_TREE_DEPTH], leaf_count: u64) -> [u8; 32] {
+    let mut node = [0u8; 32];
+    let mut zero = [0u8; 32];
+    let mut size = leaf_count;
+    for sibling in frontier {
+        node = if size & 1 == 1 { hash_pair(sibling, &node) } else { hash_pair(&node, &zero) };
+        zero = hash_pair(&zero, &zero);
+        size >>= 1;
+    }
+    node
+}
+
+// Append a leaf and return the new root. Fails with ReceiptLogFull after
+// MAX_RECEIPTS leaves; the last slot is never used, since a full tree has no
+// frontier node left to hold it.
+pub fn append_receipt(
+    frontier: &mut [[u8; 32]; RECEIPT_TREE_DEPTH],
+    leaf_count: &mut u64,
+    leaf: [u8; 32],
+) -> Result<[u8; 32]> {
+    require!(*leaf_count < MAX_RECEIPTS, CommonError::ReceiptLogFull);
+    *leaf_count = leaf_count.safe_add(1)?;
+
+    let mut node = leaf;
+    let mut size = *leaf_count;
+    for slot in frontier.iter_mut() {
+        if size & 1 == 1 {
+            *slot = node;
+            break;
+        }
+        node = hash_pair(slot, &node);
+        size >>= 1;
+    }
+    Ok(receipt_root(frontier, *leaf_count))
+}
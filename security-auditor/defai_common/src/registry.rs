@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey;
+
+use crate::CommonError;
+
+// Program registry
+// The programs call into and read from each other: swap reads estate claim
+// records, the app factory checks estates and swap bonus NFTs, and estate
+// CPIs into the app factory. Instead of compiling those ids in, each program
+// keeps the ids of its deployment's siblings in its own `ProgramRegistry` PDA
+// (seed "program_registry"), so a second deployment under other ids only
+// needs its registries pointed at each other. The ids change through the
+// admin timelock. The account types live in each program, like the cluster
+// config, so they appear in its IDL.
+
+pub const PROGRAM_REGISTRY_SEED: &[u8] = b"program_registry";
+
+// Ids of the main deployment, taken when a registry is initialized without links
+pub const DEFAULT_SWAP_PROGRAM_ID: Pubkey = pubkey!("DB9Zvhdp5xh853d2Tr2HBkRDDaCSioD7vwchhcGaXCw3");
+pub const DEFAULT_ESTATE_PROGRAM_ID: Pubkey = pubkey!("HvyyPrXbrhNEiGhttDUGMsYjKDPkYER2uFaLo7Bkei92");
+pub const DEFAULT_APP_FACTORY_PROGRAM_ID: Pubkey = pubkey!("FyDBGJFfviW1mqKYWueLQCW4YUm9RmUgQeEYw1izszDA");
+pub const DEFAULT_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("95PcYsWP8ezUP65mMe9Pw8juqsVpqnphzXDvhWwJfsmY");
+
+// A deployment links distinct, non-default program ids, and a program links
+// itself under its own id
+pub fn validate_program_links(links: &[Pubkey], own: &Pubkey, own_link: &Pubkey) -> Result<()> {
+    require_keys_eq!(*own_link, *own, CommonError::InvalidProgramLinks);
+    for (i, id) in links.iter().enumerate() {
+        require!(*id != Pubkey::default(), CommonError::InvalidProgramLinks);
+        require!(!links[..i].contains(id), CommonError::InvalidProgramLinks);
+    }
+    Ok(())
+}
+
+// The account belongs to the linked program
+pub fn require_linked_owner(account: &AccountInfo, linked: &Pubkey) -> Result<()> {
+    require_keys_eq!(*account.owner, *linked, CommonError::UnlinkedProgram);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::CommonError;
+
+// Supplemental security contact published by each program in a
+// `SecurityInfo` PDA. The security.txt embedded in the binary is fixed at
+// deploy time; the PDA lets an admin rotate contacts or point at a new policy
+// without an upgrade. The account types live in each program (so they appear
+// in its IDL); these limits keep the layout identical across all three.
+
+pub const MAX_SECURITY_CONTACTS_LEN: usize = 256; // Same "<type>:<value>,..." list as security.txt
+pub const MAX_SECURITY_POLICY_LEN: usize = 256; // Link to the disclosure policy
+pub const MAX_SECURITY_AUDITORS_LEN: usize = 128;
+
+// Space for a `SecurityInfo` account, discriminator included
+pub const SECURITY_INFO_SPACE: usize = 8 + // discriminator
+    32 + // updated_by
+    4 + MAX_SECURITY_CONTACTS_LEN + // contacts
+    4 + MAX_SECURITY_POLICY_LEN + // policy
+    4 + MAX_SECURITY_AUDITORS_LEN + // auditors
+    8 + // expiry
+    8 + // updated_at
+    1; // bump
+
+pub fn validate_security_info(contacts: &str, policy: &str, auditors: &str, expiry: i64, now: i64) -> Result<()> {
+    require!(
+        !contacts.is_empty() && contacts.len() <= MAX_SECURITY_CONTACTS_LEN,
+        CommonError::InvalidSecurityInfo
+    );
+    require!(
+        !policy.is_empty() && policy.len() <= MAX_SECURITY_POLICY_LEN,
+        CommonError::InvalidSecurityInfo
+    );
+    require!(auditors.len() <= MAX_SECURITY_AUDITORS_LEN, CommonError::InvalidSecurityInfo);
+    require!(expiry > now, CommonError::SecurityInfoExpired);
+    Ok(())
+}
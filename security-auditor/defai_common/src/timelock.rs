@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{CommonError, SafeMath};
+
+// Admin timelock shared by the programs. A change is proposed into a pending
+// slot together with the time it may execute; it can be cancelled at any time
+// and executed once the delay has elapsed. The state lives in the programs' own
+// accounts as an `Option<T>` plus an `i64` timestamp, so existing layouts are
+// unchanged; `Timelock` borrows that pair and drives it:
+//
+//   Idle --propose--> Pending --(delay elapses)--> Ready --execute--> Idle
+//     ^                  |                           |
+//     +-----cancel-------+-----------cancel----------+
+
+pub const ADMIN_TIMELOCK_DURATION: i64 = 48 * 60 * 60; // 48 hours for admin actions
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelockStatus {
+    Idle,
+    Pending,
+    Ready,
+}
+
+pub struct Timelock<'a, T> {
+    pending: &'a mut Option<T>,
+    execute_after: &'a mut i64,
+}
+
+impl<'a, T> Timelock<'a, T> {
+    pub fn new(pending: &'a mut Option<T>, execute_after: &'a mut i64) -> Self {
+        Self { pending, execute_after }
+    }
+
+    pub fn status(&self, now: i64) -> TimelockStatus {
+        match self.pending {
+            None => TimelockStatus::Idle,
+            Some(_) if now >= *self.execute_after => TimelockStatus::Ready,
+            Some(_) => TimelockStatus::Pending,
+        }
+    }
+
+    // Queue a change, replacing any pending one and restarting the clock.
+    // Returns the time it may execute.
+    pub fn propose(&mut self, value: T, now: i64, delay: i64) -> Result<i64> {
+        let execute_after = schedule(now, delay)?;
+        *self.pending = Some(value);
+        *self.execute_after = execute_after;
+        Ok(execute_after)
+    }
+
+    pub fn cancel(&mut self) -> Result<T> {
+        let value = self.pending.take().ok_or(CommonError::NoPendingChange)?;
+        *self.execute_after = 0;
+        Ok(value)
+    }
+
+    // Take the pending change once its delay has elapsed
+    pub fn execute(&mut self, now: i64) -> Result<T> {
+        require!(self.pending.is_some(), CommonError::NoPendingChange);
+        require_elapsed(now, *self.execute_after)?;
+        self.cancel()
+    }
+}
+
+// The time a change proposed now may execute
+pub fn schedule(now: i64, delay: i64) -> Result<i64> {
+    now.safe_add(delay)
+}
+
+pub fn require_elapsed(now: i64, execute_after: i64) -> Result<()> {
+    require!(now >= execute_after, CommonError::TimelockNotExpired);
+    Ok(())
+}
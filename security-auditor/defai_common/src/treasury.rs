@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::{CommonError, SafeMath};
+
+// Treasury accounting
+// Protocol income lands in plain token accounts, so each program that earns
+// fees keeps a `TreasuryLedger` PDA (seed "treasury_ledger") that books every
+// payment by source and caps withdrawals per period. The ledger PDA also owns
+// the program's treasury vault. Pointing the program's treasury wallet at the
+// ledger puts that income under the withdrawal policy. The account types
+// live in each program (so they appear in its IDL). The source indices below
+// are shared, so the DAO can add up the books of all programs by product line.
+
+pub const TREASURY_LEDGER_SEED: &[u8] = b"treasury_ledger";
+
+// Revenue counters per ledger. Sources added later take the free slots, so
+// existing ledgers keep their layout.
+pub const REVENUE_SOURCE_SLOTS: usize = 8;
+
+// Withdrawal windows start closed: no withdrawals until a cap is set through
+// the admin timelock
+pub const DEFAULT_WITHDRAWAL_PERIOD: i64 = 30 * 24 * 60 * 60; // 30 days
+pub const MAX_WITHDRAWAL_PERIOD: i64 = 365 * 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RevenueSource {
+    SwapTax = 0,            // defai_swap: tax on DEFAI → NFT swaps and compounding
+    AppPlatformFee = 1,     // defai_app_factory: platform fee on sales
+    AppRegistrationFee = 2, // defai_app_factory: listing fee
+    AppStakeSlash = 3,      // defai_app_factory: slashed listing stakes
+    EstateFee = 4,          // defai_estate: not charged yet
+    RwaFee = 5,             // defai_estate: not charged yet
+}
+
+impl RevenueSource {
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// Add one payment to a ledger's per-source totals and entry counts
+pub fn record_revenue(
+    totals: &mut [u64; REVENUE_SOURCE_SLOTS],
+    entries: &mut [u64; REVENUE_SOURCE_SLOTS],
+    source: RevenueSource,
+    amount: u64,
+) -> Result<u64> {
+    let i = source.index();
+    totals[i] = totals[i].safe_add(amount)?;
+    entries[i] = entries[i].safe_add(1)?;
+    Ok(totals[i])
+}
+
+// Any cap is valid; 0 blocks withdrawals
+pub fn validate_withdrawal_period(period: i64) -> Result<()> {
+    require!(
+        period > 0 && period <= MAX_WITHDRAWAL_PERIOD,
+        CommonError::InvalidWithdrawalPolicy
+    );
+    Ok(())
+}
+
+// Fixed withdrawal windows. Like `Timelock`, it borrows the window start and
+// running total from the program's own account. A window opens with the first
+// withdrawal after the previous one has run for `period` seconds.
+pub struct WithdrawalWindow<'a> {
+    start: &'a mut i64,
+    withdrawn: &'a mut u64,
+}
+
+impl<'a> WithdrawalWindow<'a> {
+    pub fn new(start: &'a mut i64, withdrawn: &'a mut u64) -> Self {
+        Self { start, withdrawn }
+    }
+
+    fn expired(&self, period: i64, now: i64) -> bool {
+        now.saturating_sub(*self.start) >= period
+    }
+
+    // What may still be withdrawn at `now`
+    pub fn remaining(&self, cap: u64, period: i64, now: i64) -> u64 {
+        if self.expired(period, now) {
+            cap
+        } else {
+            cap.saturating_sub(*self.withdrawn)
+        }
+    }
+
+    // Book `amount` against the current window, opening a new one if due
+    pub fn withdraw(&mut self, amount: u64, cap: u64, period: i64, now: i64) -> Result<()> {
+        require!(amount <= self.remaining(cap, period, now), CommonError::WithdrawalLimitExceeded);
+        if self.expired(period, now) {
+            *self.start = now;
+            *self.withdrawn = 0;
+        }
+        *self.withdrawn = self.withdrawn.safe_add(amount)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+// Reference sorted-pair keccak merkle tree for cross-checking
+// `defai_common::merkle`. Hashing goes through `sha3` rather than
+// `solana_program::keccak`, and nodes are concatenated into a Vec the way
+// the original on-chain code did, so a shortcut in the shared implementation
+// shows up as a mismatch. Also included by the cargo-fuzz target.
+
+#![allow(dead_code)]
+
+use sha3::{Digest, Keccak256};
+
+pub type Node = [u8; 32];
+
+pub fn keccak(data: &[u8]) -> Node {
+    Keccak256::digest(data).into()
+}
+
+pub fn leaf(user: &[u8; 32], amount: u64) -> Node {
+    let mut data = user.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    keccak(&data)
+}
+
+pub fn parent(a: &Node, b: &Node) -> Node {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    let mut combined = lo.to_vec();
+    combined.extend_from_slice(hi);
+    keccak(&combined)
+}
+
+pub fn verify(leaf: Node, proof: &[Node], root: &Node) -> bool {
+    let mut acc = leaf;
+    for sibling in proof {
+        acc = parent(&acc, sibling);
+    }
+    acc == *root
+}
+
+// Every level of the tree, leaves first. An unpaired last node is carried up
+// unchanged, matching the allowlist generator.
+pub fn levels(leaves: &[Node]) -> Vec<Vec<Node>> {
+    assert!(!leaves.is_empty());
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => parent(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+pub fn root(levels: &[Vec<Node>]) -> Node {
+    levels.last().unwrap()[0]
+}
+
+pub fn proof(levels: &[Vec<Node>], mut index: usize) -> Vec<Node> {
+    let mut proof = vec![];
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        index /= 2;
+    }
+    proof
+}
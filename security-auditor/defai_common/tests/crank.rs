@@ -0,0 +1,51 @@
+// Keeper reward payouts: capped at CRANK_REWARD_LAMPORTS and never dipping
+// the keeper vault below rent exemption.
+
+use anchor_lang::prelude::*;
+use defai_common::{pay_crank_reward, CRANK_REWARD_LAMPORTS};
+
+const VAULT_DATA_LEN: usize = 8 + 17;
+
+// Runs one payout from a vault holding `vault_lamports` and returns
+// (paid, vault balance after, keeper balance after)
+fn pay(vault_lamports: u64) -> (u64, u64, u64) {
+    let (vault_key, keeper_key, owner) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (mut vault_balance, mut keeper_balance) = (vault_lamports, 5_000u64);
+    let mut vault_data = vec![0u8; VAULT_DATA_LEN];
+    let mut keeper_data = vec![];
+    let vault = AccountInfo::new(&vault_key, false, true, &mut vault_balance, &mut vault_data, &owner, false, 0);
+    let keeper = AccountInfo::new(&keeper_key, true, true, &mut keeper_balance, &mut keeper_data, &owner, false, 0);
+
+    let paid = pay_crank_reward(&vault, &keeper, &Rent::default()).unwrap();
+    let (vault_after, keeper_after) = (vault.lamports(), keeper.lamports());
+    (paid, vault_after, keeper_after)
+}
+
+fn reserve() -> u64 {
+    Rent::default().minimum_balance(VAULT_DATA_LEN)
+}
+
+#[test]
+fn pays_the_full_reward_from_a_funded_vault() {
+    let funded = reserve() + 10 * CRANK_REWARD_LAMPORTS;
+    let (paid, vault_after, keeper_after) = pay(funded);
+    assert_eq!(paid, CRANK_REWARD_LAMPORTS);
+    assert_eq!(vault_after, funded - CRANK_REWARD_LAMPORTS);
+    assert_eq!(keeper_after, 5_000 + CRANK_REWARD_LAMPORTS);
+}
+
+#[test]
+fn pays_only_the_surplus_above_rent() {
+    let (paid, vault_after, _) = pay(reserve() + 1_234);
+    assert_eq!(paid, 1_234);
+    assert_eq!(vault_after, reserve());
+}
+
+#[test]
+fn empty_vault_pays_nothing() {
+    let (paid, vault_after, keeper_after) = pay(reserve());
+    assert_eq!((paid, vault_after, keeper_after), (0, reserve(), 5_000));
+
+    // Below rent exemption (e.g. a vault that was never topped up): still no error
+    assert_eq!(pay(reserve() - 1).0, 0);
+}
@@ -0,0 +1,52 @@
+// Invariant checks: coverage, share totals and vault ownership pass on
+// consistent books and fail with InvariantViolated otherwise.
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::*;
+use defai_common::{
+    require_covers, require_invariant, require_shares_total, require_vault_owner, CommonError,
+};
+
+fn violated() -> Error {
+    Error::from(CommonError::InvariantViolated)
+}
+
+#[test]
+fn coverage_allows_surplus_but_not_shortfall() {
+    assert!(require_covers("vault", 100, 100).is_ok());
+    assert!(require_covers("vault", 101, 100).is_ok());
+    assert_eq!(require_covers("vault", 99, 100).unwrap_err(), violated());
+}
+
+#[test]
+fn shares_must_hit_the_total_exactly() {
+    assert!(require_shares_total("shares", [50, 30, 20], 100).is_ok());
+    assert!(require_shares_total("bps", [2_500, 7_500], 10_000).is_ok());
+    assert_eq!(require_shares_total("shares", [50, 30], 100).unwrap_err(), violated());
+    assert_eq!(require_shares_total("shares", [60, 50], 100).unwrap_err(), violated());
+    assert_eq!(require_shares_total("shares", [], 100).unwrap_err(), violated());
+}
+
+#[test]
+fn shares_that_overflow_are_rejected() {
+    assert_eq!(
+        require_shares_total("shares", [u64::MAX, 101], 100).unwrap_err(),
+        violated()
+    );
+}
+
+#[test]
+fn vault_must_belong_to_its_pda() {
+    let pda = Pubkey::new_unique();
+    assert!(require_vault_owner("vault", &pda, &pda).is_ok());
+    assert_eq!(
+        require_vault_owner("vault", &Pubkey::new_unique(), &pda).unwrap_err(),
+        violated()
+    );
+}
+
+#[test]
+fn plain_invariants_fail_when_false() {
+    assert!(require_invariant(true, "holds").is_ok());
+    assert_eq!(require_invariant(false, "broken").unwrap_err(), violated());
+}
@@ -0,0 +1,63 @@
+// Shared fixed-point helpers: basis-point and percentage shares, and the
+// u128 intermediate in `mul_div` that keeps u64::MAX amounts from overflowing.
+
+use anchor_lang::error::Error;
+use defai_common::{
+    bps_of, mul_div, percent_of, split_bps, CommonError, SafeMath, BPS_DENOMINATOR,
+};
+
+#[test]
+fn split_bps_handles_zero() {
+    assert_eq!(split_bps(0, 2_500).unwrap(), (0, 0));
+    assert_eq!(split_bps(1_000, 0).unwrap(), (0, 1_000));
+}
+
+#[test]
+fn split_bps_at_the_denominator_takes_everything() {
+    assert_eq!(split_bps(1_000, BPS_DENOMINATOR).unwrap(), (1_000, 0));
+    assert_eq!(split_bps(u64::MAX, BPS_DENOMINATOR).unwrap(), (u64::MAX, 0));
+}
+
+#[test]
+fn split_bps_leaves_rounding_dust_in_the_remainder() {
+    // 3 * 3333 / 10_000 rounds down to 0, so nothing is lost
+    assert_eq!(split_bps(3, 3_333).unwrap(), (0, 3));
+    let (share, rest) = split_bps(u64::MAX, 3_333).unwrap();
+    assert_eq!(share, (u64::MAX as u128 * 3_333 / 10_000) as u64);
+    assert_eq!(share + rest, u64::MAX);
+}
+
+#[test]
+fn split_bps_above_the_denominator_fails() {
+    // A share above the amount can't leave a remainder
+    assert_eq!(split_bps(1_000, BPS_DENOMINATOR + 1).unwrap(), (1_000, 0));
+    assert_eq!(
+        split_bps(10_000, BPS_DENOMINATOR + 1).unwrap_err(),
+        Error::from(CommonError::MathOverflow)
+    );
+}
+
+#[test]
+fn mul_div_does_not_overflow_in_the_product() {
+    assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX).unwrap(), u64::MAX);
+    assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR).unwrap(), u64::MAX);
+    assert_eq!(percent_of(u64::MAX, 100).unwrap(), u64::MAX);
+    assert_eq!(percent_of(199, 50).unwrap(), 99);
+}
+
+#[test]
+fn mul_div_rejects_a_zero_denominator_and_oversized_results() {
+    assert_eq!(mul_div(1, 1, 0).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(mul_div(u64::MAX, 2, 1).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR + 1).unwrap_err(), Error::from(CommonError::MathOverflow));
+}
+
+#[test]
+fn safe_math_fails_at_the_bounds() {
+    assert_eq!(u64::MAX.safe_add(0).unwrap(), u64::MAX);
+    assert_eq!(u64::MAX.safe_add(1).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(0u64.safe_sub(1).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(u64::MAX.safe_mul(2).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(1u64.safe_div(0).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(i64::MIN.safe_sub(1).unwrap_err(), Error::from(CommonError::MathOverflow));
+}
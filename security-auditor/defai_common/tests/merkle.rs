@@ -0,0 +1,93 @@
+// Property tests for the shared merkle verification against the reference
+// tree in `common`. Run with `cargo test -p defai_common`; the cargo-fuzz
+// target in `fuzz/` covers the same checks over unstructured input.
+
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use defai_common::{hash_pair, merkle_address_leaf, merkle_leaf, verify_merkle_proof};
+use proptest::prelude::*;
+
+fn entries() -> impl Strategy<Value = Vec<([u8; 32], u64)>> {
+    prop::collection::vec((any::<[u8; 32]>(), any::<u64>()), 1..64)
+}
+
+fn tree(entries: &[([u8; 32], u64)]) -> Vec<Vec<common::Node>> {
+    let leaves: Vec<_> = entries.iter().map(|(user, amount)| common::leaf(user, *amount)).collect();
+    common::levels(&leaves)
+}
+
+proptest! {
+    #[test]
+    fn leaves_match_reference(user in any::<[u8; 32]>(), amount in any::<u64>()) {
+        let key = Pubkey::new_from_array(user);
+        prop_assert_eq!(merkle_leaf(&key, amount), common::leaf(&user, amount));
+        prop_assert_eq!(merkle_address_leaf(&key), common::keccak(&user));
+    }
+
+    #[test]
+    fn hash_pair_matches_reference_and_commutes(a in any::<[u8; 32]>(), b in any::<[u8; 32]>()) {
+        prop_assert_eq!(hash_pair(&a, &b), common::parent(&a, &b));
+        prop_assert_eq!(hash_pair(&a, &b), hash_pair(&b, &a));
+    }
+
+    #[test]
+    fn every_member_verifies(entries in entries()) {
+        let levels = tree(&entries);
+        let root = common::root(&levels);
+        for (index, (user, amount)) in entries.iter().enumerate() {
+            let leaf = merkle_leaf(&Pubkey::new_from_array(*user), *amount);
+            prop_assert!(verify_merkle_proof(leaf, &common::proof(&levels, index), &root));
+        }
+    }
+
+    #[test]
+    fn wrong_amount_is_rejected(entries in entries(), pick in any::<prop::sample::Index>(), delta in 1..u64::MAX) {
+        let levels = tree(&entries);
+        let index = pick.index(entries.len());
+        let (user, amount) = entries[index];
+        let leaf = merkle_leaf(&Pubkey::new_from_array(user), amount.wrapping_add(delta));
+        prop_assert!(!verify_merkle_proof(leaf, &common::proof(&levels, index), &common::root(&levels)));
+    }
+
+    #[test]
+    fn tampered_proof_is_rejected(
+        entries in prop::collection::vec((any::<[u8; 32]>(), any::<u64>()), 2..64),
+        pick in any::<prop::sample::Index>(),
+        element in any::<prop::sample::Index>(),
+        bit in 0..256usize,
+    ) {
+        let levels = tree(&entries);
+        let root = common::root(&levels);
+        let index = pick.index(entries.len());
+        let leaf = levels[0][index];
+        let proof = common::proof(&levels, index);
+
+        let mut flipped = proof.clone();
+        flipped[element.index(proof.len())][bit / 8] ^= 1 << (bit % 8);
+        prop_assert!(!verify_merkle_proof(leaf, &flipped, &root));
+
+        prop_assert!(!verify_merkle_proof(leaf, &proof[..proof.len() - 1], &root));
+
+        let mut extended = proof.clone();
+        extended.push(root);
+        prop_assert!(!verify_merkle_proof(leaf, &extended, &root));
+    }
+
+    // Arbitrary, mostly malformed, inputs must agree with the reference
+    #[test]
+    fn agrees_with_reference_on_arbitrary_input(
+        leaf in any::<[u8; 32]>(),
+        proof in prop::collection::vec(any::<[u8; 32]>(), 0..16),
+        root in any::<[u8; 32]>(),
+    ) {
+        prop_assert_eq!(verify_merkle_proof(leaf, &proof, &root), common::verify(leaf, &proof, &root));
+    }
+}
+
+#[test]
+fn single_leaf_tree_has_empty_proof() {
+    let leaf = merkle_leaf(&Pubkey::new_unique(), 42);
+    assert!(verify_merkle_proof(leaf, &[], &leaf));
+    assert!(!verify_merkle_proof(leaf, &[], &[0; 32]));
+}
@@ -0,0 +1,107 @@
+// Receipt log appends checked against the reference tree in `common`, padded
+// with zero leaves to the full RECEIPT_TREE_DEPTH.
+
+mod common;
+
+use anchor_lang::error::Error;
+use defai_common::{
+    append_receipt, receipt_leaf, receipt_root, verify_merkle_proof, CommonError, ReceiptKind, MAX_RECEIPTS,
+    RECEIPT_TREE_DEPTH,
+};
+use proptest::prelude::*;
+
+type Frontier = [[u8; 32]; RECEIPT_TREE_DEPTH];
+
+// Levels of the leaves padded to a power of two, plus the zero subtree hashes
+// needed to carry that tree up to RECEIPT_TREE_DEPTH
+fn reference(leaves: &[common::Node]) -> (Vec<Vec<common::Node>>, Vec<common::Node>) {
+    let width = leaves.len().max(1).next_power_of_two();
+    let mut padded = leaves.to_vec();
+    padded.resize(width, [0u8; 32]);
+
+    let mut zeros = vec![[0u8; 32]];
+    for _ in 0..RECEIPT_TREE_DEPTH {
+        let z = *zeros.last().unwrap();
+        zeros.push(common::parent(&z, &z));
+    }
+    (common::levels(&padded), zeros)
+}
+
+fn reference_root(leaves: &[common::Node]) -> common::Node {
+    let (levels, zeros) = reference(leaves);
+    let height = levels.len() - 1;
+    (height..RECEIPT_TREE_DEPTH).fold(common::root(&levels), |node, h| common::parent(&node, &zeros[h]))
+}
+
+fn reference_proof(leaves: &[common::Node], index: usize) -> Vec<common::Node> {
+    let (levels, zeros) = reference(leaves);
+    let mut proof = common::proof(&levels, index);
+    proof.extend_from_slice(&zeros[levels.len() - 1..RECEIPT_TREE_DEPTH]);
+    proof
+}
+
+fn append_all(leaves: &[common::Node]) -> (Frontier, u64, [u8; 32]) {
+    let (mut frontier, mut count) = ([[0u8; 32]; RECEIPT_TREE_DEPTH], 0);
+    let mut root = receipt_root(&frontier, count);
+    for leaf in leaves {
+        root = append_receipt(&mut frontier, &mut count, *leaf).unwrap();
+    }
+    (frontier, count, root)
+}
+
+#[test]
+fn empty_log_has_the_zero_tree_root() {
+    assert_eq!(receipt_root(&[[0u8; 32]; RECEIPT_TREE_DEPTH], 0), reference_root(&[]));
+}
+
+#[test]
+fn leaf_binds_kind_index_and_data() {
+    let leaf = receipt_leaf(ReceiptKind::EstateClaim, 7, b"receipt");
+    let mut data = vec![ReceiptKind::EstateClaim as u8];
+    data.extend_from_slice(&7u64.to_le_bytes());
+    data.extend_from_slice(b"receipt");
+    assert_eq!(leaf, common::keccak(&data));
+
+    assert_ne!(leaf, receipt_leaf(ReceiptKind::BonusReroll, 7, b"receipt"));
+    assert_ne!(leaf, receipt_leaf(ReceiptKind::EstateClaim, 8, b"receipt"));
+}
+
+#[test]
+fn full_log_rejects_appends() {
+    let mut frontier = [[0u8; 32]; RECEIPT_TREE_DEPTH];
+    let mut count = MAX_RECEIPTS;
+    assert_eq!(
+        append_receipt(&mut frontier, &mut count, [1u8; 32]).unwrap_err(),
+        Error::from(CommonError::ReceiptLogFull)
+    );
+    assert_eq!(count, MAX_RECEIPTS);
+}
+
+proptest! {
+    #[test]
+    fn root_matches_reference(leaves in prop::collection::vec(any::<[u8; 32]>(), 1..80)) {
+        let (frontier, count, root) = append_all(&leaves);
+        prop_assert_eq!(count, leaves.len() as u64);
+        prop_assert_eq!(root, reference_root(&leaves));
+        prop_assert_eq!(receipt_root(&frontier, count), root);
+    }
+
+    #[test]
+    fn every_receipt_proves_against_the_root(leaves in prop::collection::vec(any::<[u8; 32]>(), 1..40)) {
+        let (_, _, root) = append_all(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = reference_proof(&leaves, index);
+            prop_assert_eq!(proof.len(), RECEIPT_TREE_DEPTH);
+            prop_assert!(verify_merkle_proof(*leaf, &proof, &root));
+        }
+    }
+
+    #[test]
+    fn proofs_follow_the_current_root(leaves in prop::collection::vec(any::<[u8; 32]>(), 2..40)) {
+        // A proof taken right after the first append is stale once more
+        // receipts land; the indexer has to prove against the current tree
+        let (_, _, root) = append_all(&leaves);
+        prop_assert!(!verify_merkle_proof(leaves[0], &reference_proof(&leaves[..1], 0), &root));
+        prop_assert!(verify_merkle_proof(leaves[0], &reference_proof(&leaves, 0), &root));
+    }
+}
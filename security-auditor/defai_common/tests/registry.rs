@@ -0,0 +1,54 @@
+// Program link validation: every link set and distinct, with the program
+// itself linked under its own id; and the owner check against a link.
+
+use anchor_lang::error::Error;
+use anchor_lang::prelude::*;
+use defai_common::{require_linked_owner, validate_program_links, CommonError};
+
+fn links() -> [Pubkey; 4] {
+    [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()]
+}
+
+#[test]
+fn accepts_distinct_links_including_the_program() {
+    let links = links();
+    assert!(validate_program_links(&links, &links[1], &links[1]).is_ok());
+}
+
+#[test]
+fn rejects_a_program_linked_under_another_id() {
+    let links = links();
+    assert_eq!(
+        validate_program_links(&links, &Pubkey::new_unique(), &links[1]).unwrap_err(),
+        Error::from(CommonError::InvalidProgramLinks)
+    );
+}
+
+#[test]
+fn rejects_unset_and_repeated_links() {
+    let mut unset = links();
+    unset[3] = Pubkey::default();
+    assert_eq!(
+        validate_program_links(&unset, &unset[0], &unset[0]).unwrap_err(),
+        Error::from(CommonError::InvalidProgramLinks)
+    );
+
+    let mut repeated = links();
+    repeated[2] = repeated[0];
+    assert_eq!(
+        validate_program_links(&repeated, &repeated[0], &repeated[0]).unwrap_err(),
+        Error::from(CommonError::InvalidProgramLinks)
+    );
+}
+
+#[test]
+fn owner_must_be_the_linked_program() {
+    let (key, linked) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (mut lamports, mut data) = (0u64, vec![]);
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &linked, false, 0);
+    assert!(require_linked_owner(&info, &linked).is_ok());
+    assert_eq!(
+        require_linked_owner(&info, &Pubkey::new_unique()).unwrap_err(),
+        Error::from(CommonError::UnlinkedProgram)
+    );
+}
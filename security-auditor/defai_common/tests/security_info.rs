@@ -0,0 +1,49 @@
+// Bounds on the supplemental security contact each program stores in its
+// `SecurityInfo` PDA.
+
+use anchor_lang::error::Error;
+use defai_common::{
+    validate_security_info, CommonError, MAX_SECURITY_AUDITORS_LEN, MAX_SECURITY_CONTACTS_LEN,
+    MAX_SECURITY_POLICY_LEN,
+};
+
+const CONTACTS: &str = "email:security@defai.estate";
+const POLICY: &str = "https://github.com/heirlabs/audit/blob/main/SECURITY.md";
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn accepts_fields_at_their_limits() {
+    let contacts = "c".repeat(MAX_SECURITY_CONTACTS_LEN);
+    let policy = "p".repeat(MAX_SECURITY_POLICY_LEN);
+    let auditors = "a".repeat(MAX_SECURITY_AUDITORS_LEN);
+    assert!(validate_security_info(&contacts, &policy, &auditors, NOW + 1, NOW).is_ok());
+    assert!(validate_security_info(CONTACTS, POLICY, "", NOW + 1, NOW).is_ok());
+}
+
+#[test]
+fn rejects_empty_or_oversized_fields() {
+    let invalid = Error::from(CommonError::InvalidSecurityInfo);
+    let too_long = |max: usize| "x".repeat(max + 1);
+
+    assert_eq!(validate_security_info("", POLICY, "", NOW + 1, NOW).unwrap_err(), invalid);
+    assert_eq!(validate_security_info(CONTACTS, "", "", NOW + 1, NOW).unwrap_err(), invalid);
+    assert_eq!(
+        validate_security_info(&too_long(MAX_SECURITY_CONTACTS_LEN), POLICY, "", NOW + 1, NOW).unwrap_err(),
+        invalid
+    );
+    assert_eq!(
+        validate_security_info(CONTACTS, &too_long(MAX_SECURITY_POLICY_LEN), "", NOW + 1, NOW).unwrap_err(),
+        invalid
+    );
+    assert_eq!(
+        validate_security_info(CONTACTS, POLICY, &too_long(MAX_SECURITY_AUDITORS_LEN), NOW + 1, NOW).unwrap_err(),
+        invalid
+    );
+}
+
+#[test]
+fn rejects_expiry_not_in_future() {
+    let expired = Error::from(CommonError::SecurityInfoExpired);
+    assert_eq!(validate_security_info(CONTACTS, POLICY, "", NOW, NOW).unwrap_err(), expired);
+    assert_eq!(validate_security_info(CONTACTS, POLICY, "", NOW - 1, NOW).unwrap_err(), expired);
+}
@@ -0,0 +1,81 @@
+// Admin timelock state machine: Idle -> Pending -> Ready -> Idle, with the
+// Ready edge landing exactly on `execute_after`.
+
+use anchor_lang::error::Error;
+use defai_common::{
+    require_elapsed, schedule, CommonError, Timelock, TimelockStatus, ADMIN_TIMELOCK_DURATION,
+};
+
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn proposal_becomes_ready_exactly_at_the_deadline() {
+    let (mut pending, mut execute_after) = (None::<u64>, 0i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    assert_eq!(lock.status(NOW), TimelockStatus::Idle);
+
+    let deadline = lock.propose(7, NOW, ADMIN_TIMELOCK_DURATION).unwrap();
+    assert_eq!(deadline, NOW + ADMIN_TIMELOCK_DURATION);
+    assert_eq!(lock.status(deadline - 1), TimelockStatus::Pending);
+    assert_eq!(lock.status(deadline), TimelockStatus::Ready);
+
+    assert_eq!(lock.execute(deadline - 1).unwrap_err(), Error::from(CommonError::TimelockNotExpired));
+    assert_eq!(lock.execute(deadline).unwrap(), 7);
+    assert_eq!(lock.status(deadline), TimelockStatus::Idle);
+    assert_eq!((pending, execute_after), (None, 0));
+}
+
+#[test]
+fn zero_delay_is_ready_immediately() {
+    let (mut pending, mut execute_after) = (None::<u64>, 0i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    lock.propose(1, NOW, 0).unwrap();
+    assert_eq!(lock.status(NOW), TimelockStatus::Ready);
+    assert_eq!(lock.execute(NOW).unwrap(), 1);
+}
+
+#[test]
+fn nothing_pending_cannot_be_cancelled_or_executed() {
+    let (mut pending, mut execute_after) = (None::<u64>, 0i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    assert_eq!(lock.cancel().unwrap_err(), Error::from(CommonError::NoPendingChange));
+    assert_eq!(lock.execute(i64::MAX).unwrap_err(), Error::from(CommonError::NoPendingChange));
+}
+
+#[test]
+fn cancel_clears_the_slot() {
+    let (mut pending, mut execute_after) = (None::<u64>, 0i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    lock.propose(3, NOW, ADMIN_TIMELOCK_DURATION).unwrap();
+    assert_eq!(lock.cancel().unwrap(), 3);
+    assert_eq!(lock.status(i64::MAX), TimelockStatus::Idle);
+    assert_eq!((pending, execute_after), (None, 0));
+}
+
+#[test]
+fn reproposing_restarts_the_clock() {
+    let (mut pending, mut execute_after) = (None::<u64>, 0i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    let first = lock.propose(1, NOW, ADMIN_TIMELOCK_DURATION).unwrap();
+    let second = lock.propose(2, first - 1, ADMIN_TIMELOCK_DURATION).unwrap();
+    assert_eq!(lock.status(first), TimelockStatus::Pending);
+    assert_eq!(lock.execute(second).unwrap(), 2);
+}
+
+#[test]
+fn overflowing_deadline_is_rejected_and_leaves_state_alone() {
+    assert_eq!(schedule(i64::MAX, 1).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!(schedule(i64::MAX, 0).unwrap(), i64::MAX);
+
+    let (mut pending, mut execute_after) = (Some(9u64), 42i64);
+    let mut lock = Timelock::new(&mut pending, &mut execute_after);
+    assert_eq!(lock.propose(1, i64::MAX, 1).unwrap_err(), Error::from(CommonError::MathOverflow));
+    assert_eq!((pending, execute_after), (Some(9), 42));
+}
+
+#[test]
+fn require_elapsed_is_inclusive() {
+    assert_eq!(require_elapsed(NOW - 1, NOW).unwrap_err(), Error::from(CommonError::TimelockNotExpired));
+    assert!(require_elapsed(NOW, NOW).is_ok());
+    assert!(require_elapsed(NOW + 1, NOW).is_ok());
+}
@@ -0,0 +1,86 @@
+// Treasury ledger bookkeeping: per-source revenue counters and the fixed
+// withdrawal windows behind each program's withdrawal cap.
+
+use anchor_lang::error::Error;
+use defai_common::{
+    record_revenue, validate_withdrawal_period, CommonError, RevenueSource, WithdrawalWindow,
+    MAX_WITHDRAWAL_PERIOD, REVENUE_SOURCE_SLOTS,
+};
+
+const DAY: i64 = 24 * 60 * 60;
+const NOW: i64 = 1_700_000_000;
+
+#[test]
+fn revenue_is_booked_per_source() {
+    let (mut totals, mut entries) = ([0u64; REVENUE_SOURCE_SLOTS], [0u64; REVENUE_SOURCE_SLOTS]);
+    record_revenue(&mut totals, &mut entries, RevenueSource::SwapTax, 500).unwrap();
+    record_revenue(&mut totals, &mut entries, RevenueSource::SwapTax, 250).unwrap();
+    let fee_total = record_revenue(&mut totals, &mut entries, RevenueSource::AppPlatformFee, 40).unwrap();
+
+    assert_eq!(fee_total, 40);
+    assert_eq!(totals[RevenueSource::SwapTax.index()], 750);
+    assert_eq!(entries[RevenueSource::SwapTax.index()], 2);
+    assert_eq!(entries[RevenueSource::AppPlatformFee.index()], 1);
+    assert_eq!(totals.iter().sum::<u64>(), 790);
+
+    // Overflow fails and leaves the counters alone
+    totals[RevenueSource::AppStakeSlash.index()] = u64::MAX;
+    assert_eq!(
+        record_revenue(&mut totals, &mut entries, RevenueSource::AppStakeSlash, 1).unwrap_err(),
+        Error::from(CommonError::MathOverflow)
+    );
+    assert_eq!(entries[RevenueSource::AppStakeSlash.index()], 0);
+}
+
+#[test]
+fn sources_fit_in_the_ledger() {
+    assert!(RevenueSource::RwaFee.index() < REVENUE_SOURCE_SLOTS);
+}
+
+#[test]
+fn withdrawal_period_bounds() {
+    assert!(validate_withdrawal_period(1).is_ok());
+    assert!(validate_withdrawal_period(MAX_WITHDRAWAL_PERIOD).is_ok());
+    for period in [0, -DAY, MAX_WITHDRAWAL_PERIOD + 1] {
+        assert_eq!(
+            validate_withdrawal_period(period).unwrap_err(),
+            Error::from(CommonError::InvalidWithdrawalPolicy)
+        );
+    }
+}
+
+#[test]
+fn withdrawals_are_capped_within_a_window() {
+    let (mut start, mut withdrawn) = (0i64, 0u64);
+    let mut window = WithdrawalWindow::new(&mut start, &mut withdrawn);
+
+    window.withdraw(600, 1_000, DAY, NOW).unwrap();
+    window.withdraw(400, 1_000, DAY, NOW + DAY - 1).unwrap();
+    assert_eq!(window.remaining(1_000, DAY, NOW + DAY - 1), 0);
+    assert_eq!(
+        window.withdraw(1, 1_000, DAY, NOW + DAY - 1).unwrap_err(),
+        Error::from(CommonError::WithdrawalLimitExceeded)
+    );
+    assert_eq!((start, withdrawn), (NOW, 1_000));
+}
+
+#[test]
+fn a_new_window_opens_after_the_period() {
+    let (mut start, mut withdrawn) = (NOW, 1_000u64);
+    let mut window = WithdrawalWindow::new(&mut start, &mut withdrawn);
+
+    assert_eq!(window.remaining(1_000, DAY, NOW + DAY), 1_000);
+    window.withdraw(300, 1_000, DAY, NOW + 3 * DAY).unwrap();
+    assert_eq!((start, withdrawn), (NOW + 3 * DAY, 300));
+}
+
+#[test]
+fn a_zero_cap_blocks_withdrawals() {
+    let (mut start, mut withdrawn) = (0i64, 0u64);
+    let mut window = WithdrawalWindow::new(&mut start, &mut withdrawn);
+    assert_eq!(
+        window.withdraw(1, 0, DAY, NOW).unwrap_err(),
+        Error::from(CommonError::WithdrawalLimitExceeded)
+    );
+    assert_eq!((start, withdrawn), (0, 0));
+}
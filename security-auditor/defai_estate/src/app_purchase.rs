@@ -0,0 +1,182 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::{Estate, EstateError, ProgramRegistry, ESTATE_SEED};
+use defai_common::{emit_cpi_versioned, event_cpi_of, CommonError};
+
+// Buy a DEFAI App Factory app with DEFAI held in the estate vault.
+// The owner signs; the estate PDA signs the app factory CPI, so the access
+// SFT is minted to the estate's ATA and passes to beneficiaries through
+// claim_token like any other estate-held token. The app factory called is the
+// one linked in the estate's program registry; it checks the estate against
+// its own registry.
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct PurchaseAppWithEstateFunds<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        bump,
+        constraint = !estate.is_locked @ EstateError::EstateLocked,
+        constraint = !estate.is_claimable @ EstateError::EstateClaimable,
+    )]
+    pub estate: Account<'info, Estate>,
+
+    #[account(
+        mut,
+        seeds = [b"estate_vault", estate.key().as_ref(), defai_mint.key().as_ref()],
+        bump,
+    )]
+    pub estate_vault: Account<'info, TokenAccount>,
+
+    // App factory accounts, validated by the app factory program
+    /// CHECK: App factory config PDA
+    #[account(mut)]
+    pub app_factory: UncheckedAccount<'info>,
+    /// CHECK: Factory statistics PDA
+    #[account(mut)]
+    pub factory_stats: UncheckedAccount<'info>,
+    /// CHECK: App registration PDA
+    #[account(mut)]
+    pub app_registration: UncheckedAccount<'info>,
+    /// CHECK: Estate's access PDA, created by the app factory
+    #[account(mut)]
+    pub user_app_access: UncheckedAccount<'info>,
+    /// CHECK: App SFT mint
+    #[account(mut)]
+    pub sft_mint: UncheckedAccount<'info>,
+    /// CHECK: Estate's SFT ATA, created if missing
+    #[account(mut)]
+    pub estate_sft_ata: UncheckedAccount<'info>,
+    /// CHECK: Creator's DEFAI ATA
+    #[account(mut)]
+    pub creator_defai_ata: UncheckedAccount<'info>,
+    /// CHECK: Creator vesting escrow, for apps with vested payouts
+    #[account(mut)]
+    pub creator_vesting: Option<UncheckedAccount<'info>>,
+    /// CHECK: Creator vesting vault
+    #[account(mut)]
+    pub vesting_vault: Option<UncheckedAccount<'info>>,
+    /// CHECK: Charity DEFAI account, for apps donating a share of sales
+    #[account(mut)]
+    pub charity_defai_ata: Option<UncheckedAccount<'info>>,
+    /// CHECK: Treasury DEFAI ATA
+    #[account(mut)]
+    pub treasury_defai_ata: UncheckedAccount<'info>,
+    /// CHECK: App revenue split PDA, validated by the app factory
+    pub revenue_split: UncheckedAccount<'info>,
+    /// CHECK: Scheduled sale
+    pub sale: Option<UncheckedAccount<'info>>,
+    /// CHECK: App creator
+    pub creator: UncheckedAccount<'info>,
+    /// CHECK: Platform treasury
+    pub treasury: UncheckedAccount<'info>,
+    /// CHECK: DEFAI mint, checked against the app factory config; written by
+    /// the purchase burn
+    #[account(mut)]
+    pub defai_mint: UncheckedAccount<'info>,
+    /// CHECK: App factory's CPI event authority PDA
+    pub app_factory_event_authority: UncheckedAccount<'info>,
+    /// CHECK: App factory's program registry PDA
+    pub app_factory_program_registry: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Box<Account<'info, ProgramRegistry>>,
+    /// CHECK: The app factory linked in the program registry
+    #[account(
+        executable,
+        address = program_registry.links.app_factory @ CommonError::UnlinkedProgram
+    )]
+    pub app_factory_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct EstateAppPurchased {
+    pub schema_version: u8,
+    pub estate_id: Pubkey,
+    pub app_id: u64,
+    pub amount_spent: u64,
+    pub timestamp: i64,
+}
+
+pub fn purchase_app_with_estate_funds<'info>(
+    ctx: Context<'_, '_, '_, 'info, PurchaseAppWithEstateFunds<'info>>,
+    app_id: u64,
+) -> Result<()> {
+    let balance_before = ctx.accounts.estate_vault.amount;
+
+    let estate = &ctx.accounts.estate;
+    let estate_number_bytes = estate.estate_number.to_le_bytes();
+    let seeds = &[
+        ESTATE_SEED,
+        estate.owner.as_ref(),
+        estate_number_bytes.as_ref(),
+        &[ctx.bumps.estate]
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = defai_app_factory::cpi::accounts::PurchaseAppFromEstate {
+        app_factory: ctx.accounts.app_factory.to_account_info(),
+        factory_stats: ctx.accounts.factory_stats.to_account_info(),
+        app_registration: ctx.accounts.app_registration.to_account_info(),
+        user_app_access: ctx.accounts.user_app_access.to_account_info(),
+        sft_mint: ctx.accounts.sft_mint.to_account_info(),
+        estate_sft_ata: ctx.accounts.estate_sft_ata.to_account_info(),
+        estate_vault: ctx.accounts.estate_vault.to_account_info(),
+        creator_defai_ata: ctx.accounts.creator_defai_ata.to_account_info(),
+        creator_vesting: ctx.accounts.creator_vesting.as_ref().map(|a| a.to_account_info()),
+        vesting_vault: ctx.accounts.vesting_vault.as_ref().map(|a| a.to_account_info()),
+        charity_defai_ata: ctx.accounts.charity_defai_ata.as_ref().map(|a| a.to_account_info()),
+        treasury_defai_ata: ctx.accounts.treasury_defai_ata.to_account_info(),
+        revenue_split: ctx.accounts.revenue_split.to_account_info(),
+        sale: ctx.accounts.sale.as_ref().map(|a| a.to_account_info()),
+        program_registry: ctx.accounts.app_factory_program_registry.to_account_info(),
+        estate: ctx.accounts.estate.to_account_info(),
+        rent_payer: ctx.accounts.owner.to_account_info(),
+        creator: ctx.accounts.creator.to_account_info(),
+        treasury: ctx.accounts.treasury.to_account_info(),
+        defai_mint: ctx.accounts.defai_mint.to_account_info(),
+        token_program: ctx.accounts.token_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+        event_authority: ctx.accounts.app_factory_event_authority.to_account_info(),
+        program: ctx.accounts.app_factory_program.to_account_info(),
+    };
+    // Revenue split recipients are forwarded as remaining accounts
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.app_factory_program.to_account_info(),
+        cpi_accounts,
+        signer,
+    )
+    .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+    defai_app_factory::cpi::purchase_app_from_estate(cpi_ctx, app_id)?;
+
+    ctx.accounts.estate_vault.reload()?;
+    let amount_spent = balance_before.saturating_sub(ctx.accounts.estate_vault.amount);
+
+    // Spending from the vault reduces the tracked trading value
+    let estate = &mut ctx.accounts.estate;
+    estate.trading_value = estate.trading_value.saturating_sub(amount_spent);
+    estate.check_in()?;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), EstateAppPurchased {
+        estate_id: estate.estate_id,
+        app_id,
+        amount_spent,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Estate #{} bought app {} for {} DEFAI", estate.estate_number, app_id, amount_spent);
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use defai_common::{audit_slot, SafeMath, AUDIT_LOG_SEED, AUDIT_LOG_SLOTS};
+
+// On-chain audit trail
+// Multisig admin handovers and recovery executions append an entry to the
+// program-wide `AuditLog` PDA in the same transaction, so the record exists
+// even if an indexer misses the matching event. The log is a fixed ring of
+// AUDIT_LOG_SLOTS entries; `seq` keeps counting so overwritten history shows
+// up as a gap.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum AuditAction {
+    #[default]
+    None,
+    AdminChangeProposed, // subject: multisig, target: proposed admin
+    AdminChanged,        // subject: multisig, target: new admin
+    RecoveryExecuted,    // subject: estate, target: recovery address
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub action: AuditAction,
+    pub actor: Pubkey,
+    pub subject: Pubkey,
+    pub target: Pubkey,
+    pub amount: u64,
+    pub data: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl AuditEntry {
+    pub const LEN: usize = 8 + // seq
+        1 + // action
+        32 + // actor
+        32 + // subject
+        32 + // target
+        8 + // amount
+        32 + // data
+        8; // timestamp
+}
+
+// AUDIT_LOG_SLOTS spelled as a literal: Anchor's IDL parser only resolves
+// array lengths from constants defined in this crate
+pub const AUDIT_LOG_LEN: usize = 32;
+const _: () = assert!(AUDIT_LOG_LEN == AUDIT_LOG_SLOTS);
+
+#[account]
+pub struct AuditLog {
+    pub total_entries: u64,
+    pub entries: [AuditEntry; AUDIT_LOG_LEN], // Ring buffer of the most recent entries
+    pub bump: u8,
+}
+
+impl AuditLog {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // total_entries
+        AuditEntry::LEN * AUDIT_LOG_LEN + // entries
+        1; // bump
+
+    pub fn record(
+        &mut self,
+        action: AuditAction,
+        actor: Pubkey,
+        subject: Pubkey,
+        target: Pubkey,
+    ) -> Result<()> {
+        let seq = self.total_entries;
+        self.entries[audit_slot(seq)] = AuditEntry {
+            seq,
+            action,
+            actor,
+            subject,
+            target,
+            amount: 0,
+            data: [0; 32],
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        self.total_entries = seq.safe_add(1)?;
+        Ok(())
+    }
+}
+
+// The log only ever holds what the program writes, so anyone may pay to
+// create it (as with the global counter).
+#[derive(Accounts)]
+pub struct InitializeAuditLog<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = AuditLog::LEN,
+        seeds = [AUDIT_LOG_SEED],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+    let audit_log = &mut ctx.accounts.audit_log;
+    audit_log.total_entries = 0;
+    audit_log.bump = ctx.bumps.audit_log;
+
+    msg!("Audit log initialized");
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use anchor_lang::prelude::*;
+use defai_common::emit_versioned;
+
+use crate::{
+    Beneficiary, BeneficiaryAdded, BeneficiaryRemoved, Estate, EstateError, RiskManagementSettings,
+    MAX_BENEFICIARIES,
+};
+
+// Granular beneficiary edits
+// update_beneficiaries replaces the whole list, which must total 100%.
+// add_beneficiary, remove_beneficiary and update_beneficiary_share change one
+// entry at a time, each moving share between two beneficiaries so the list
+// still totals 100% afterwards. An owner who stops checking in partway
+// through a set of edits leaves an estate that can still be triggered.
+
+// Growing beneficiary lists
+// An estate is created with room for INITIAL_BENEFICIARY_SLOTS beneficiaries.
+// add_beneficiary and update_beneficiaries grow the account past that, up to
+// MAX_BENEFICIARIES, with the signer paying the extra rent. The list stays in
+// the Estate account, so claims keep indexing it directly.
+
+impl Beneficiary {
+    pub const LEN: usize = 32 + 32 + 1 + 1 + 1;
+}
+
+impl Estate {
+    pub fn space(beneficiaries: usize) -> usize {
+        8 + // discriminator
+            32 + // estate_id
+            32 + // owner
+            32 + // owner_email_hash
+            8 + // last_active
+            8 + // inactivity_period
+            8 + // grace_period
+            (4 + beneficiaries * Beneficiary::LEN) + // beneficiaries vector
+            1 + // total_beneficiaries
+            8 + // creation_time
+            8 + // estate_value
+            1 + // is_locked
+            1 + // is_claimable
+            4 + // total_rwas
+            8 + // estate_number
+            1 + // total_claims
+            // Trading fields
+            1 + // trading_enabled
+            (1 + 32) + // ai_agent Option<Pubkey>
+            (1 + 32) + // trading_strategy Option<TradingStrategy>
+            8 + // human_contribution
+            8 + // ai_contribution
+            8 + // trading_value
+            8 + // trading_profit
+            8 + // high_water_mark
+            1 + // human_share
+            1 + // ai_share
+            (1 + 1) + // stop_loss Option<u8>
+            4 + // emergency_delay_hours
+            1 + // emergency_withdrawal_initiated
+            8 + // emergency_withdrawal_time
+            8 + // last_trading_update
+            (1 + 32) + // multisig Option<Pubkey>
+            (1 + RiskManagementSettings::LEN) + // risk_settings Option
+            100 // buffer
+    }
+
+    // Size for a list of `beneficiaries`; never shrinks an account with free slots
+    pub fn grown_space(beneficiaries: usize, current_len: usize) -> usize {
+        Self::space(beneficiaries).max(current_len)
+    }
+
+    pub fn share_total(&self) -> u64 {
+        self.beneficiaries.iter().map(|b| b.share_percentage as u64).sum()
+    }
+
+    // Beneficiaries can be edited by the owner or the attached multisig until
+    // the estate is locked or claimable
+    pub fn require_beneficiary_editor(&self, signer: &Pubkey) -> Result<()> {
+        require!(!self.is_locked, EstateError::EstateLocked);
+        require!(!self.is_claimable, EstateError::EstateClaimable);
+        require!(
+            *signer == self.owner || self.multisig == Some(*signer),
+            EstateError::UnauthorizedAccess
+        );
+        Ok(())
+    }
+
+    fn beneficiary_share(&self, index: usize) -> Result<u8> {
+        self.beneficiaries
+            .get(index)
+            .map(|b| b.share_percentage)
+            .ok_or_else(|| error!(EstateError::InvalidBeneficiaryIndex))
+    }
+}
+
+#[derive(Accounts)]
+pub struct AddBeneficiary<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        realloc = Estate::grown_space(estate.beneficiaries.len() + 1, estate.to_account_info().data_len()),
+        realloc::payer = owner,
+        realloc::zero = false,
+    )]
+    pub estate: Box<Account<'info, Estate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EditBeneficiary<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub estate: Box<Account<'info, Estate>>,
+}
+
+#[event]
+pub struct BeneficiaryShareUpdated {
+    pub schema_version: u8,
+    pub estate_id: Pubkey,
+    pub beneficiary_address: Pubkey,
+    pub old_share_percentage: u8,
+    pub new_share_percentage: u8,
+    pub timestamp: i64,
+}
+
+fn emit_share_updated(estate: &Estate, index: usize, old_share: u8, timestamp: i64) {
+    let beneficiary = &estate.beneficiaries[index];
+    emit_versioned!(BeneficiaryShareUpdated {
+        estate_id: estate.estate_id,
+        beneficiary_address: beneficiary.address,
+        old_share_percentage: old_share,
+        new_share_percentage: beneficiary.share_percentage,
+        timestamp,
+    });
+}
+
+// The first beneficiary takes 100%; later ones take their share from
+// `from_index`, who must keep at least 1%
+pub fn add_beneficiary(
+    ctx: Context<AddBeneficiary>,
+    address: Pubkey,
+    email_hash: [u8; 32],
+    share_percentage: u8,
+    from_index: Option<u8>,
+) -> Result<()> {
+    let estate = &mut ctx.accounts.estate;
+    estate.require_beneficiary_editor(&ctx.accounts.owner.key())?;
+    require!(
+        estate.beneficiaries.len() < MAX_BENEFICIARIES as usize,
+        EstateError::TooManyBeneficiaries
+    );
+    require!(
+        estate.beneficiaries.iter().all(|b| b.address != address),
+        EstateError::DuplicateBeneficiary
+    );
+    require!(share_percentage > 0, EstateError::ZeroBeneficiaryShare);
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    let giver = match from_index {
+        None => {
+            require!(estate.beneficiaries.is_empty(), EstateError::InvalidBeneficiaryIndex);
+            require!(share_percentage == 100, EstateError::InvalidBeneficiaryShares);
+            None
+        }
+        Some(from) => {
+            let from = from as usize;
+            let old_share = estate.beneficiary_share(from)?;
+            require!(share_percentage < old_share, EstateError::ZeroBeneficiaryShare);
+            estate.beneficiaries[from].share_percentage = old_share - share_percentage;
+            Some((from, old_share))
+        }
+    };
+
+    estate.beneficiaries.push(Beneficiary {
+        address,
+        email_hash,
+        share_percentage,
+        claimed: false,
+        notification_sent: false,
+    });
+    estate.total_beneficiaries = estate.beneficiaries.len() as u8;
+
+    emit_versioned!(BeneficiaryAdded {
+        estate_id: estate.estate_id,
+        beneficiary_address: address,
+        share_percentage,
+        total_beneficiaries: estate.total_beneficiaries,
+        timestamp,
+    });
+    if let Some((from, old_share)) = giver {
+        emit_share_updated(estate, from, old_share, timestamp);
+    }
+
+    msg!("Added beneficiary {} with {}%", address, share_percentage);
+    Ok(())
+}
+
+// The removed share goes to `heir_index` (counted before removal), and later
+// beneficiaries move up one index
+pub fn remove_beneficiary(ctx: Context<EditBeneficiary>, index: u8, heir_index: u8) -> Result<()> {
+    let estate = &mut ctx.accounts.estate;
+    estate.require_beneficiary_editor(&ctx.accounts.owner.key())?;
+    let (index, heir_index) = (index as usize, heir_index as usize);
+    let removed_share = estate.beneficiary_share(index)?;
+    let heir_share = estate.beneficiary_share(heir_index)?;
+    require!(index != heir_index, EstateError::InvalidBeneficiaryIndex);
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    estate.beneficiaries[heir_index].share_percentage = heir_share + removed_share;
+    let removed = estate.beneficiaries.remove(index);
+    estate.total_beneficiaries = estate.beneficiaries.len() as u8;
+    let heir_index = if heir_index > index { heir_index - 1 } else { heir_index };
+
+    emit_versioned!(BeneficiaryRemoved {
+        estate_id: estate.estate_id,
+        beneficiary_address: removed.address,
+        index: index as u8,
+        timestamp,
+    });
+    emit_share_updated(estate, heir_index, heir_share, timestamp);
+
+    msg!(
+        "Removed beneficiary {}; their {}% went to {}",
+        removed.address,
+        removed_share,
+        estate.beneficiaries[heir_index].address
+    );
+    Ok(())
+}
+
+// Set one beneficiary's share, moving the difference to or from `balance_index`
+pub fn update_beneficiary_share(
+    ctx: Context<EditBeneficiary>,
+    index: u8,
+    share_percentage: u8,
+    balance_index: u8,
+) -> Result<()> {
+    let estate = &mut ctx.accounts.estate;
+    estate.require_beneficiary_editor(&ctx.accounts.owner.key())?;
+    let (index, balance_index) = (index as usize, balance_index as usize);
+    let old_share = estate.beneficiary_share(index)?;
+    let old_balance = estate.beneficiary_share(balance_index)?;
+    require!(index != balance_index, EstateError::InvalidBeneficiaryIndex);
+    require!(share_percentage > 0, EstateError::ZeroBeneficiaryShare);
+
+    // The pair's combined share is unchanged, so the estate stays at 100%
+    let pair = old_share + old_balance;
+    require!(share_percentage < pair, EstateError::ZeroBeneficiaryShare);
+    estate.beneficiaries[index].share_percentage = share_percentage;
+    estate.beneficiaries[balance_index].share_percentage = pair - share_percentage;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    emit_share_updated(estate, index, old_share, timestamp);
+    emit_share_updated(estate, balance_index, old_balance, timestamp);
+
+    msg!(
+        "Beneficiary {} share set to {}%, beneficiary {} now holds {}%",
+        index,
+        share_percentage,
+        balance_index,
+        pair - share_percentage
+    );
+    Ok(())
+}
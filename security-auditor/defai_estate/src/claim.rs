@@ -0,0 +1,156 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenInterface, TransferChecked};
+
+use crate::{Beneficiary, ClaimRecord, ClaimStage, Estate, EstateError, ESTATE_SEED};
+
+// Split claim paths into validation and execution
+// claim_inheritance, claim_token and claim_nft run next to ATA creation and
+// priority-fee instructions, so each reads the estate once through these
+// helpers instead of repeating the checks inline.
+
+pub fn validate_claimant<'a>(
+    estate: &'a Estate,
+    beneficiary_index: u8,
+    claimant: &Pubkey,
+) -> Result<&'a Beneficiary> {
+    require!(estate.is_claimable, EstateError::NotClaimable);
+    require!(
+        beneficiary_index < estate.total_beneficiaries,
+        EstateError::InvalidBeneficiaryIndex
+    );
+
+    let beneficiary = &estate.beneficiaries[beneficiary_index as usize];
+    require!(
+        beneficiary.address == *claimant,
+        EstateError::UnauthorizedBeneficiary
+    );
+    Ok(beneficiary)
+}
+
+// Token and NFT claims are only open once the SOL share has been claimed
+pub fn validate_asset_claimant<'a>(
+    estate: &'a Estate,
+    beneficiary_index: u8,
+    claimant: &Pubkey,
+) -> Result<&'a Beneficiary> {
+    let beneficiary = validate_claimant(estate, beneficiary_index, claimant)?;
+    require!(beneficiary.claimed, EstateError::MustClaimInheritanceFirst);
+    Ok(beneficiary)
+}
+
+// Resumable claims
+// SOL, ten token mints, NFTs and RWAs don't fit in one transaction, so a claim
+// runs as a sequence of instructions checkpointed on the ClaimRecord.
+// claim_inheritance pays the SOL share and opens the Tokens stage, and each
+// advance_claim opens the next kind of asset. The program can't see every mint
+// or NFT the estate holds, so advancing doesn't close a stage: an asset
+// instruction runs from its own stage until the claim is Complete, and refuses
+// assets already listed on the record. A failed transaction leaves the record
+// untouched, so a client resumes from the stage and lists it reads back.
+
+impl ClaimRecord {
+    pub fn space(tokens: usize, nfts: usize) -> usize {
+        8 + 32 + 32 + 8 + 8 + 1 + (4 + tokens * (32 + 8)) + (4 + nfts * 32) + 1 + 4 + 4 + 8
+    }
+
+    // Size after adding claim entries; never shrinks a record with free slots
+    pub fn grown_space(&self, extra_tokens: usize, extra_nfts: usize, current_len: usize) -> usize {
+        Self::space(
+            self.tokens_claimed.len() + extra_tokens,
+            self.nfts_claimed.len() + extra_nfts,
+        )
+        .max(current_len)
+    }
+
+    // An asset kind stays claimable from its own stage until the claim is complete
+    pub fn require_stage(&self, stage: ClaimStage) -> Result<()> {
+        require!(self.stage >= stage, EstateError::WrongClaimStage);
+        require!(self.stage != ClaimStage::Complete, EstateError::ClaimAlreadyComplete);
+        Ok(())
+    }
+
+    pub fn checkpoint(&mut self, now: i64) {
+        self.steps = self.steps.saturating_add(1);
+        self.last_step_at = now;
+    }
+
+    // Open the next stage; there is no way back, and Complete ends the claim
+    pub fn advance(&mut self, now: i64) -> Result<ClaimStage> {
+        self.stage = match self.stage {
+            ClaimStage::Tokens => ClaimStage::Nfts,
+            ClaimStage::Nfts => ClaimStage::Rwas,
+            ClaimStage::Rwas => ClaimStage::Complete,
+            ClaimStage::Complete => return err!(EstateError::ClaimAlreadyComplete),
+        };
+        self.checkpoint(now);
+        Ok(self.stage)
+    }
+}
+
+// Transfer out of an estate-owned token account, signed by the estate PDA
+pub fn transfer_from_estate<'info>(
+    estate: &Account<'info, Estate>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+    bump: u8,
+) -> Result<()> {
+    let estate_number_bytes = estate.estate_number.to_le_bytes();
+    let seeds = &[
+        ESTATE_SEED,
+        estate.owner.as_ref(),
+        estate_number_bytes.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                authority: estate.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )
+}
+
+// Move one NFT out of the estate with transfer_checked, which Token-2022 mints
+// such as DEFAI Swap's bonus NFTs require
+pub fn transfer_nft_from_estate<'info>(
+    estate: &Account<'info, Estate>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    bump: u8,
+) -> Result<()> {
+    let estate_number_bytes = estate.estate_number.to_le_bytes();
+    let seeds = &[
+        ESTATE_SEED,
+        estate.owner.as_ref(),
+        estate_number_bytes.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.clone(),
+                mint: mint.to_account_info(),
+                to: to.clone(),
+                authority: estate.to_account_info(),
+            },
+            signer,
+        ),
+        1,
+        mint.decimals,
+    )
+}
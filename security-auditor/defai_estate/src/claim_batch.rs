@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer as SystemTransfer};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address, AssociatedToken, Create},
+    token::{Mint, Token, TokenAccount},
+};
+use defai_common::{percent_of, require_invariant, require_vault_owner};
+
+use crate::{transfer_from_estate, validate_asset_claimant, ClaimRecord, ClaimStage, Estate, EstateError, TokenClaim};
+
+// Claims the beneficiary's share of several SPL token mints in one
+// transaction. Remaining accounts come in groups of ACCOUNTS_PER_MINT, one
+// group per mint, in order:
+//   0. Token mint
+//   1. Estate's ATA for the mint (mut)
+//   2. Beneficiary's ATA for the mint (mut, created if missing)
+// Each mint is paid as claim_token would pay it. The claim record grows once
+// for the whole batch and takes a single checkpoint. Past about eight mints
+// the accounts only fit with an address lookup table.
+
+pub const MAX_BATCH_TOKEN_CLAIMS: usize = 10;
+const ACCOUNTS_PER_MINT: usize = 3;
+
+#[derive(Accounts)]
+pub struct ClaimTokensBatch<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub estate: Box<Account<'info, Estate>>,
+
+    #[account(
+        mut,
+        has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
+        has_one = estate @ EstateError::InvalidClaimRecord,
+    )]
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// Make room for `extra_tokens` more token claims, the beneficiary paying the rent
+fn grow_claim_record<'info>(
+    claim_record: &Account<'info, ClaimRecord>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    extra_tokens: usize,
+) -> Result<()> {
+    let info = claim_record.to_account_info();
+    let new_len = claim_record.grown_space(extra_tokens, 0, info.data_len());
+    if new_len == info.data_len() {
+        return Ok(());
+    }
+
+    let rent_due = Rent::get()?.minimum_balance(new_len).saturating_sub(info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                SystemTransfer { from: payer.to_account_info(), to: info.clone() },
+            ),
+            rent_due,
+        )?;
+    }
+    info.realloc(new_len, false)?;
+    Ok(())
+}
+
+pub fn claim_tokens_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimTokensBatch<'info>>,
+    beneficiary_index: u8,
+) -> Result<()> {
+    let share_percentage = validate_asset_claimant(
+        &ctx.accounts.estate,
+        beneficiary_index,
+        &ctx.accounts.beneficiary.key(),
+    )?
+    .share_percentage;
+    ctx.accounts.claim_record.require_stage(ClaimStage::Tokens)?;
+
+    let mint_count = ctx.remaining_accounts.len() / ACCOUNTS_PER_MINT;
+    require!(
+        mint_count > 0 && mint_count <= MAX_BATCH_TOKEN_CLAIMS,
+        EstateError::InvalidBatchSize
+    );
+    require!(
+        ctx.remaining_accounts.len() == mint_count * ACCOUNTS_PER_MINT,
+        EstateError::InvalidBatchAccounts
+    );
+
+    let estate_key = ctx.accounts.estate.key();
+    let beneficiary_key = ctx.accounts.beneficiary.key();
+    let mut claims: Vec<TokenClaim> = Vec::with_capacity(mint_count);
+
+    for group in ctx.remaining_accounts.chunks(ACCOUNTS_PER_MINT) {
+        let (mint_info, estate_ata_info, beneficiary_ata_info) = (&group[0], &group[1], &group[2]);
+
+        // Mints already on the record or earlier in this batch are refused
+        let mint = Account::<Mint>::try_from(mint_info)?.key();
+        require!(
+            ctx.accounts.claim_record.tokens_claimed.iter().chain(claims.iter()).all(|c| c.mint != mint),
+            EstateError::TokenAlreadyClaimed
+        );
+
+        require_keys_eq!(
+            estate_ata_info.key(),
+            get_associated_token_address(&estate_key, &mint),
+            EstateError::InvalidBatchAccounts
+        );
+        require_keys_eq!(
+            beneficiary_ata_info.key(),
+            get_associated_token_address(&beneficiary_key, &mint),
+            EstateError::InvalidBatchAccounts
+        );
+        let balance_before = Account::<TokenAccount>::try_from(estate_ata_info)?.amount;
+        let token_share = percent_of(balance_before, share_percentage as u64)?;
+        if token_share == 0 {
+            continue;
+        }
+
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            Create {
+                payer: ctx.accounts.beneficiary.to_account_info(),
+                associated_token: beneficiary_ata_info.clone(),
+                authority: ctx.accounts.beneficiary.to_account_info(),
+                mint: mint_info.clone(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+        ))?;
+        transfer_from_estate(
+            &ctx.accounts.estate,
+            estate_ata_info,
+            beneficiary_ata_info,
+            &ctx.accounts.token_program,
+            token_share,
+            ctx.bumps.estate,
+        )?;
+
+        // Same checks as claim_token: the estate's account paid exactly the share
+        let estate_ata = Account::<TokenAccount>::try_from(estate_ata_info)?;
+        require_vault_owner("estate token account", &estate_ata.owner, &estate_key)?;
+        require_invariant(
+            estate_ata.amount == balance_before - token_share,
+            "estate token account debited by the claimed share",
+        )?;
+
+        claims.push(TokenClaim { mint, amount: token_share });
+    }
+
+    // One record update for the whole batch
+    let claimed = claims.len();
+    if claimed > 0 {
+        grow_claim_record(
+            &ctx.accounts.claim_record,
+            &ctx.accounts.beneficiary,
+            &ctx.accounts.system_program,
+            claimed,
+        )?;
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.tokens_claimed.extend(claims);
+        claim_record.checkpoint(Clock::get()?.unix_timestamp);
+    }
+
+    msg!("Claimed {} of {} token mints", claimed, mint_count);
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{Mint, Token, TokenAccount},
+};
+use defai_common::{require_invariant, require_vault_owner};
+use mpl_token_metadata::{accounts::Metadata, instructions::TransferV1CpiBuilder, types::TokenStandard};
+
+use crate::{validate_asset_claimant, ClaimRecord, ClaimStage, Estate, EstateError, ESTATE_SEED};
+
+// Programmable NFT claims
+// A pNFT's token account stays frozen, so claim_nft's plain transfer can't
+// move it. claim_pnft has Token Metadata transfer it instead, signed by the
+// estate PDA as owner, which thaws and refreezes the token and moves its token
+// record to the beneficiary. Token Metadata creates the beneficiary's ATA and
+// token record, paid for by the beneficiary. A pNFT whose rule set rejects
+// transfers out of a program-owned account can't be claimed.
+
+#[derive(Accounts)]
+pub struct ClaimPnft<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub estate: Box<Account<'info, Estate>>,
+
+    #[account(
+        mut,
+        has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
+        has_one = estate @ EstateError::InvalidClaimRecord,
+        realloc = claim_record.grown_space(0, 1, claim_record.to_account_info().data_len()),
+        realloc::payer = beneficiary,
+        realloc::zero = false,
+    )]
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
+
+    pub nft_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = estate,
+    )]
+    pub estate_nft_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Beneficiary's ATA for the pNFT, created by Token Metadata if missing
+    #[account(
+        mut,
+        address = get_associated_token_address(&beneficiary.key(), &nft_mint.key()) @ EstateError::InvalidPnftAccounts
+    )]
+    pub beneficiary_nft_account: UncheckedAccount<'info>,
+
+    /// CHECK: pNFT metadata PDA, parsed in the handler
+    #[account(
+        mut,
+        seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), nft_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: pNFT master edition PDA, validated by Token Metadata
+    #[account(
+        seeds = [b"metadata".as_ref(), token_metadata_program.key().as_ref(), nft_mint.key().as_ref(), b"edition".as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub edition: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of the estate's token account, validated by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata".as_ref(),
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record".as_ref(),
+            estate_nft_account.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub owner_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Token record of the beneficiary's token account, created by Token Metadata
+    #[account(
+        mut,
+        seeds = [
+            b"metadata".as_ref(),
+            token_metadata_program.key().as_ref(),
+            nft_mint.key().as_ref(),
+            b"token_record".as_ref(),
+            beneficiary_nft_account.key().as_ref(),
+        ],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub destination_token_record: UncheckedAccount<'info>,
+
+    /// CHECK: Token Auth Rules program, only needed when the pNFT has a rule set
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: The pNFT's rule set, checked against its metadata by Token Metadata
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Token Metadata program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar required by Token Metadata
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_pnft(ctx: Context<ClaimPnft>, beneficiary_index: u8) -> Result<()> {
+    validate_asset_claimant(
+        &ctx.accounts.estate,
+        beneficiary_index,
+        &ctx.accounts.beneficiary.key(),
+    )?;
+    ctx.accounts.claim_record.require_stage(ClaimStage::Nfts)?;
+
+    let nft_mint = ctx.accounts.nft_mint.key();
+    require!(
+        !ctx.accounts.claim_record.nfts_claimed.contains(&nft_mint),
+        EstateError::NFTAlreadyClaimed
+    );
+    require!(
+        ctx.accounts.estate_nft_account.amount == 1,
+        EstateError::InvalidNFTAmount
+    );
+
+    // Plain NFTs go through claim_nft
+    let metadata = Metadata::from_bytes(&ctx.accounts.metadata.try_borrow_data()?)
+        .map_err(|_| EstateError::NotProgrammableNft)?;
+    require!(
+        matches!(
+            metadata.token_standard,
+            Some(TokenStandard::ProgrammableNonFungible | TokenStandard::ProgrammableNonFungibleEdition)
+        ),
+        EstateError::NotProgrammableNft
+    );
+
+    let estate = &ctx.accounts.estate;
+    let estate_number_bytes = estate.estate_number.to_le_bytes();
+    let seeds = &[
+        ESTATE_SEED,
+        estate.owner.as_ref(),
+        estate_number_bytes.as_ref(),
+        &[ctx.bumps.estate],
+    ];
+
+    let estate_info = estate.to_account_info();
+    let beneficiary_info = ctx.accounts.beneficiary.to_account_info();
+    let estate_nft_info = ctx.accounts.estate_nft_account.to_account_info();
+    let mint_info = ctx.accounts.nft_mint.to_account_info();
+    let edition_info = ctx.accounts.edition.to_account_info();
+    let owner_record_info = ctx.accounts.owner_token_record.to_account_info();
+    let destination_record_info = ctx.accounts.destination_token_record.to_account_info();
+    let rules_program_info = ctx.accounts.authorization_rules_program.as_ref().map(|a| a.to_account_info());
+    let rules_info = ctx.accounts.authorization_rules.as_ref().map(|a| a.to_account_info());
+
+    // The estate PDA owns the token and signs as its authority
+    TransferV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+        .token(&estate_nft_info)
+        .token_owner(&estate_info)
+        .destination_token(&ctx.accounts.beneficiary_nft_account.to_account_info())
+        .destination_owner(&beneficiary_info)
+        .mint(&mint_info)
+        .metadata(&ctx.accounts.metadata.to_account_info())
+        .edition(Some(&edition_info))
+        .token_record(Some(&owner_record_info))
+        .destination_token_record(Some(&destination_record_info))
+        .authority(&estate_info)
+        .payer(&beneficiary_info)
+        .system_program(&ctx.accounts.system_program.to_account_info())
+        .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+        .spl_token_program(&ctx.accounts.token_program.to_account_info())
+        .spl_ata_program(&ctx.accounts.associated_token_program.to_account_info())
+        .authorization_rules_program(rules_program_info.as_ref())
+        .authorization_rules(rules_info.as_ref())
+        .amount(1)
+        .invoke_signed(&[&seeds[..]])?;
+
+    // Same checks as claim_nft: the pNFT left the estate's account for the beneficiary's
+    ctx.accounts.estate_nft_account.reload()?;
+    let beneficiary_nft_account =
+        TokenAccount::try_deserialize(&mut &ctx.accounts.beneficiary_nft_account.try_borrow_data()?[..])?;
+    require_vault_owner(
+        "estate NFT account",
+        &ctx.accounts.estate_nft_account.owner,
+        &ctx.accounts.estate.key(),
+    )?;
+    require_invariant(
+        ctx.accounts.estate_nft_account.amount == 0 && beneficiary_nft_account.amount >= 1,
+        "pNFT moved from estate to beneficiary",
+    )?;
+
+    ctx.accounts.claim_record.nfts_claimed.push(nft_mint);
+    ctx.accounts.claim_record.checkpoint(Clock::get()?.unix_timestamp);
+
+    msg!("Claimed pNFT {}", nft_mint);
+    Ok(())
+}
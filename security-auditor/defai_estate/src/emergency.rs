@@ -2,7 +2,9 @@ use anchor_lang::prelude::*;
 use crate::{Estate, EstateError};
 
 // Constants for emergency lock
+#[constant]
 pub const EMERGENCY_LOCK_COOLDOWN: i64 = 3600; // 1 hour cooldown between locks
+#[constant]
 pub const MIN_UNLOCK_DELAY: i64 = 300; // 5 minutes minimum before unlock
 
 #[account]
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::{Estate, EstateError};
+use defai_common::emit_versioned;
 
 // Simple emergency lock - no verification codes needed
 // Owner proves identity via signature
@@ -81,7 +82,7 @@ pub fn emergency_lock_impl(
     }
     
     // Emit event
-    emit!(EstateLocked {
+    emit_versioned!(EstateLocked {
         estate_id: estate.key(),
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -100,7 +101,7 @@ pub fn emergency_unlock_impl(
     estate.is_locked = false;
     
     // Emit event
-    emit!(EstateUnlocked {
+    emit_versioned!(EstateUnlocked {
         estate_id: estate.key(),
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -117,7 +118,7 @@ pub fn force_unlock_by_multisig(ctx: Context<ForceUnlockByMultisig>) -> Result<(
     // Just unlock the estate
     estate.is_locked = false;
     
-    emit!(EstateUnlocked {
+    emit_versioned!(EstateUnlocked {
         estate_id: estate.key(),
         timestamp: Clock::get()?.unix_timestamp,
     });
@@ -130,12 +131,14 @@ pub fn force_unlock_by_multisig(ctx: Context<ForceUnlockByMultisig>) -> Result<(
 // Events
 #[event]
 pub struct EstateLocked {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct EstateUnlocked {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of, pay_crank_reward, EventCpi};
+
+use crate::{Estate, EstateError, EstateLocked, RISK_RESET_INTERVAL};
+
+// Keeper cranks (see defai_common::crank)
+// Permissionless versions of the estate's time-based transitions, paid from
+// the `keeper_vault` PDA.
+
+#[account]
+pub struct KeeperVault {
+    pub cranks: u64,          // Successful cranks
+    pub rewards_paid: u64,    // Lamports paid to keepers
+    pub bump: u8,
+}
+
+impl KeeperVault {
+    pub const LEN: usize = 8 + 8 + 1;
+
+    // Pay the keeper for a crank that just succeeded
+    pub fn reward<'info>(
+        vault: &mut Account<'info, KeeperVault>,
+        keeper: &AccountInfo<'info>,
+        crank: &str,
+        target: Pubkey,
+        events: &EventCpi<'_, 'info>,
+    ) -> Result<()> {
+        let reward = pay_crank_reward(&vault.to_account_info(), keeper, &Rent::get()?)?;
+        vault.cranks = vault.cranks.saturating_add(1);
+        vault.rewards_paid = vault.rewards_paid.saturating_add(reward);
+
+        emit_cpi_versioned!(events, KeeperCranked {
+            keeper: keeper.key(),
+            crank: crank.to_string(),
+            target,
+            reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + KeeperVault::LEN,
+        seeds = [b"keeper_vault"],
+        bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankEstate<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(mut)]
+    pub estate: Account<'info, Estate>,
+    #[account(
+        mut,
+        seeds = [b"keeper_vault"],
+        bump = keeper_vault.bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+}
+
+// Anyone may create the vault; it holds nothing but keeper rewards
+pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+    let vault = &mut ctx.accounts.keeper_vault;
+    vault.cranks = 0;
+    vault.rewards_paid = 0;
+    vault.bump = ctx.bumps.keeper_vault;
+    Ok(())
+}
+
+// Same transition as `trigger_inheritance`; a second crank fails with
+// AlreadyClaimable.
+pub fn crank_trigger_inheritance(ctx: Context<CrankEstate>) -> Result<()> {
+    let estate = &mut ctx.accounts.estate;
+    let now = Clock::get()?.unix_timestamp;
+    estate.trigger_inheritance(now)?;
+
+    emit_versioned!(EstateLocked {
+        estate_id: estate.estate_id,
+        timestamp: now,
+    });
+
+    let target = estate.key();
+    KeeperVault::reward(
+        &mut ctx.accounts.keeper_vault,
+        &ctx.accounts.keeper,
+        "trigger_inheritance",
+        target,
+        &event_cpi_of!(ctx),
+    )
+}
+
+// Clear the estate's daily loss counter once RISK_RESET_INTERVAL has passed
+// since the last reset. Only estates with a non-zero counter can be cranked.
+pub fn crank_reset_daily_risk(ctx: Context<CrankEstate>) -> Result<()> {
+    let estate = &mut ctx.accounts.estate;
+    let clock = Clock::get()?;
+    let settings = estate.risk_settings.as_mut().ok_or(EstateError::NoRiskToReset)?;
+    require!(settings.daily_loss_bps > 0, EstateError::NoRiskToReset);
+    require!(
+        clock.unix_timestamp >= settings.last_risk_reset.saturating_add(RISK_RESET_INTERVAL),
+        EstateError::RiskResetNotDue
+    );
+    settings.reset_daily_metrics(&clock);
+
+    let target = estate.key();
+    KeeperVault::reward(
+        &mut ctx.accounts.keeper_vault,
+        &ctx.accounts.keeper,
+        "reset_daily_risk",
+        target,
+        &event_cpi_of!(ctx),
+    )
+}
+
+#[event]
+pub struct KeeperCranked {
+    pub schema_version: u8,
+    pub keeper: Pubkey,
+    pub crank: String,
+    pub target: Pubkey,   // Account the transition was applied to
+    pub reward: u64,      // Lamports paid; 0 when the vault is empty
+    pub timestamp: i64,
+}
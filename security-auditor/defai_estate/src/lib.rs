@@ -4,44 +4,133 @@ use anchor_lang::accounts::interface::Interface;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use anchor_spl::token_interface::{TokenInterface, Mint as MintInterface, TokenAccount as TokenAccountInterface};
 use anchor_spl::associated_token::AssociatedToken;
+use defai_common::{
+    emit_cpi_versioned, emit_versioned, event_cpi_of, mul_div, percent_of, require_covers,
+    require_invariant, require_shares_total, require_vault_owner, Timelock, AUDIT_LOG_SEED,
+};
 
 mod emergency_simple;
 use emergency_simple::*;
 
+mod app_purchase;
+use app_purchase::*;
+
+mod audit;
+use audit::*;
+
+mod claim;
+use claim::*;
+
+mod claim_batch;
+use claim_batch::*;
+pub use claim_batch::MAX_BATCH_TOKEN_CLAIMS;
+
+mod claim_pnft;
+use claim_pnft::*;
+
+mod beneficiaries;
+use beneficiaries::*;
+pub use beneficiaries::BeneficiaryShareUpdated;
+
+mod security_info;
+use security_info::*;
+
+mod keeper;
+use keeper::*;
+
+mod receipts;
+use receipts::*;
+pub use receipts::{ClaimReceipt, ReceiptLog};
+
+mod program_registry;
+use program_registry::*;
+pub use program_registry::{ProgramLinks, ProgramRegistry};
+
 mod risk_management;
 #[allow(ambiguous_glob_reexports)]
 pub use risk_management::*;
 
 declare_id!("HvyyPrXbrhNEiGhttDUGMsYjKDPkYER2uFaLo7Bkei92");
 
-// Estate Seeds
+// Disclosure contact embedded in the binary's .security.txt section
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "DEFAI Estate",
+    project_url: "https://github.com/heirlabs/audit",
+    contacts: "email:security@defai.estate,link:https://github.com/heirlabs/audit/security/advisories/new",
+    policy: "https://github.com/heirlabs/audit/blob/main/SECURITY.md",
+    preferred_languages: "en",
+    source_code: "https://github.com/heirlabs/audit",
+    expiry: "2027-10-16"
+}
+
+// Estate Seeds. Account constraints spell these as byte literals, which is
+// the only form Anchor 0.29's IDL parser resolves into PDA derivations.
+#[constant]
 pub const ESTATE_SEED: &[u8] = b"estate";
+#[constant]
 pub const RWA_SEED: &[u8] = b"rwa";
+#[constant]
 pub const COUNTER_SEED: &[u8] = b"counter";
+#[constant]
 pub const CLAIM_SEED: &[u8] = b"claim";
+#[constant]
 pub const ASSET_SUMMARY_SEED: &[u8] = b"asset_summary";
+#[constant]
 pub const RECOVERY_SEED: &[u8] = b"recovery";
+#[constant]
+pub const EMERGENCY_LOCK_SEED: &[u8] = b"emergency_lock";
+#[constant]
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+#[constant]
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+#[constant]
+pub const SECURITY_INFO_SEED: &[u8] = b"security_info";
+#[constant]
+pub const KEEPER_VAULT_SEED: &[u8] = b"keeper_vault";
+#[constant]
+pub const RECEIPT_LOG_SEED: &[u8] = b"receipt_log";
+#[constant]
+pub const PROGRAM_REGISTRY_SEED: &[u8] = b"program_registry";
 
 // Trading Seeds
+#[constant]
 pub const ESTATE_VAULT_SEED: &[u8] = b"estate_vault";
 
-// Estate Constants
-pub const MIN_INACTIVITY_PERIOD: i64 = 24 * 60 * 60; // 24 hours in seconds
-pub const MAX_INACTIVITY_PERIOD: i64 = 300 * 365 * 24 * 60 * 60; // 300 years in seconds
-pub const MIN_GRACE_PERIOD: i64 = 24 * 60 * 60; // 24 hours in seconds
-pub const MAX_GRACE_PERIOD: i64 = 90 * 24 * 60 * 60; // 90 days in seconds
-pub const MAX_BENEFICIARIES: u8 = 10;
-pub const ESTATE_FEE: u64 = 100_000_000; // 0.1 SOL
-pub const RWA_FEE: u64 = 10_000_000; // 0.01 SOL
+// Estate Constants. Exported values are plain literals so IDL clients can
+// parse them as numbers.
+#[constant]
+pub const MIN_INACTIVITY_PERIOD: i64 = 86400; // 24 hours in seconds
+#[constant]
+pub const MAX_INACTIVITY_PERIOD: i64 = 9460800000; // 300 years in seconds
+#[constant]
+pub const MIN_GRACE_PERIOD: i64 = 86400; // 24 hours in seconds
+#[constant]
+pub const MAX_GRACE_PERIOD: i64 = 7776000; // 90 days in seconds
+#[constant]
+pub const MAX_BENEFICIARIES: u8 = 100; // Shares are whole percentages of at least 1%
+#[constant]
+pub const ESTATE_FEE: u64 = 100000000; // 0.1 SOL
+#[constant]
+pub const RWA_FEE: u64 = 10000000; // 0.01 SOL
 pub const MIN_RENT_BALANCE: u64 = 890880; // Minimum rent-exempt balance for a basic account
+// Beneficiary entries an Estate is allocated with; adding more grows it
+pub const INITIAL_BENEFICIARY_SLOTS: usize = 10;
+// Claim entries a ClaimRecord is allocated with; the single-asset claims grow it past this
+pub const INITIAL_CLAIM_SLOTS: usize = 10;
 
 // Joint Account Constants
+#[constant]
 pub const MAX_PROFIT_SHARE: u8 = 50; // Maximum AI agent profit share (50%)
+#[constant]
 pub const MIN_EMERGENCY_DELAY: u32 = 24; // 24 hours minimum
+#[constant]
 pub const MAX_EMERGENCY_DELAY: u32 = 168; // 7 days maximum
+#[constant]
+pub const RISK_RESET_INTERVAL: i64 = 86400; // Daily risk metrics window
 
 // Admin Constants
-pub const ADMIN_TIMELOCK_DURATION: i64 = 48 * 60 * 60; // 48 hours for admin actions
+pub use defai_common::ADMIN_TIMELOCK_DURATION;
 pub const MAX_SIGNERS: usize = 10;
 pub const MIN_SIGNERS: usize = 2;
 pub const MAX_PROPOSALS: usize = 20;
@@ -86,7 +175,7 @@ pub mod defai_estate {
         
         msg!("Multisig initialized with {} signers, threshold: {}", signers.len(), threshold);
         
-        emit!(MultisigCreated {
+        emit_versioned!(MultisigCreated {
             multisig_address: multisig_key,
             signers,
             threshold,
@@ -100,19 +189,27 @@ pub mod defai_estate {
         ctx: Context<ProposeAdminChange>,
         new_admin: Pubkey,
     ) -> Result<()> {
-        let multisig = &mut ctx.accounts.multisig;
+        let multisig_key = ctx.accounts.multisig.key();
+        let multisig: &mut Multisig = &mut ctx.accounts.multisig;
         
         require!(
             ctx.accounts.signer.key() == multisig.admin,
             EstateError::UnauthorizedAccess
         );
         
-        multisig.pending_admin = Some(new_admin);
-        multisig.admin_change_timestamp = Clock::get()?.unix_timestamp + ADMIN_TIMELOCK_DURATION;
+        Timelock::new(&mut multisig.pending_admin, &mut multisig.admin_change_timestamp)
+            .propose(new_admin, Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
         
         msg!("Admin change proposed. Can be executed after {}", multisig.admin_change_timestamp);
         
-        emit!(AdminChangeProposed {
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminChangeProposed,
+            ctx.accounts.signer.key(),
+            multisig_key,
+            new_admin,
+        )?;
+        
+        emit_versioned!(AdminChangeProposed {
             old_admin: multisig.admin,
             new_admin,
             execute_after: multisig.admin_change_timestamp,
@@ -123,26 +220,25 @@ pub mod defai_estate {
     }
     
     pub fn accept_admin_change(ctx: Context<AcceptAdminChange>) -> Result<()> {
-        let multisig = &mut ctx.accounts.multisig;
+        let multisig: &mut Multisig = &mut ctx.accounts.multisig;
         
-        require!(
-            multisig.pending_admin.is_some(),
-            EstateError::NoPendingAdminChange
-        );
-        require!(
-            Clock::get()?.unix_timestamp >= multisig.admin_change_timestamp,
-            EstateError::TimelockNotExpired
-        );
+        let new_admin = Timelock::new(&mut multisig.pending_admin, &mut multisig.admin_change_timestamp)
+            .execute(Clock::get()?.unix_timestamp)?;
         
         let old_admin = multisig.admin;
-        let new_admin = multisig.pending_admin.unwrap();
         multisig.admin = new_admin;
-        multisig.pending_admin = None;
-        multisig.admin_change_timestamp = 0;
         
         msg!("Admin changed from {} to {}", old_admin, new_admin);
         
-        emit!(AdminChangeExecuted {
+        let multisig_key = ctx.accounts.multisig.key();
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminChanged,
+            old_admin,
+            multisig_key,
+            new_admin,
+        )?;
+        
+        emit_versioned!(AdminChangeExecuted {
             old_admin,
             new_admin,
             timestamp: Clock::get()?.unix_timestamp,
@@ -186,7 +282,7 @@ pub mod defai_estate {
         
         msg!("Proposal {} created by {}", proposal.proposal_id, ctx.accounts.proposer.key());
         
-        emit!(ProposalCreated {
+        emit_versioned!(ProposalCreated {
             proposal_id: proposal.proposal_id,
             proposer: proposal.proposer,
             target_estate,
@@ -227,7 +323,7 @@ pub mod defai_estate {
             multisig.threshold
         );
         
-        emit!(ProposalApproved {
+        emit_versioned!(ProposalApproved {
             proposal_id: proposal.proposal_id,
             approver: ctx.accounts.signer.key(),
             total_approvals: proposal.approvals.len() as u8,
@@ -255,7 +351,7 @@ pub mod defai_estate {
         
         msg!("Proposal {} executed", proposal.proposal_id);
         
-        emit!(ProposalExecuted {
+        emit_versioned!(ProposalExecuted {
             proposal_id: proposal.proposal_id,
             executor: ctx.accounts.executor.key(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -274,6 +370,37 @@ pub mod defai_estate {
         Ok(())
     }
 
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        audit::initialize_audit_log(ctx)
+    }
+
+    // Publish or rotate the on-chain security contact (upgrade authority only)
+    pub fn update_security_info(
+        ctx: Context<UpdateSecurityInfo>,
+        contacts: String,
+        policy: String,
+        auditors: String,
+        expiry: i64,
+    ) -> Result<()> {
+        security_info::update_security_info(ctx, contacts, policy, auditors, expiry)
+    }
+
+    // ===== Keeper Cranks =====
+
+    pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+        keeper::initialize_keeper_vault(ctx)
+    }
+
+    // Permissionless trigger_inheritance that pays the caller from the keeper vault
+    pub fn crank_trigger_inheritance(ctx: Context<CrankEstate>) -> Result<()> {
+        keeper::crank_trigger_inheritance(ctx)
+    }
+
+    // Clear an estate's daily loss counter once a day has passed
+    pub fn crank_reset_daily_risk(ctx: Context<CrankEstate>) -> Result<()> {
+        keeper::crank_reset_daily_risk(ctx)
+    }
+
     pub fn create_estate(
         ctx: Context<CreateEstate>,
         inactivity_period: i64,
@@ -281,11 +408,11 @@ pub mod defai_estate {
         owner_email_hash: [u8; 32],
     ) -> Result<()> {
         require!(
-            inactivity_period >= MIN_INACTIVITY_PERIOD && inactivity_period <= MAX_INACTIVITY_PERIOD,
+            (MIN_INACTIVITY_PERIOD..=MAX_INACTIVITY_PERIOD).contains(&inactivity_period),
             EstateError::InvalidInactivityPeriod
         );
         require!(
-            grace_period >= MIN_GRACE_PERIOD && grace_period <= MAX_GRACE_PERIOD,
+            (MIN_GRACE_PERIOD..=MAX_GRACE_PERIOD).contains(&grace_period),
             EstateError::InvalidGracePeriod
         );
 
@@ -332,7 +459,7 @@ pub mod defai_estate {
         msg!("Estate #{} created", estate.estate_number);
         
         // Emit estate created event
-        emit!(EstateCreated {
+        emit_versioned!(EstateCreated {
             estate_id: estate.estate_id,
             owner: estate.owner,
             estate_number: estate.estate_number,
@@ -364,11 +491,11 @@ pub mod defai_estate {
         );
         require!(!estate.trading_enabled, EstateError::TradingAlreadyEnabled);
         require!(
-            human_share >= 50 && human_share <= 100,
+            (50..=100).contains(&human_share),
             EstateError::InvalidProfitShare
         );
         require!(
-            emergency_delay_hours >= MIN_EMERGENCY_DELAY && emergency_delay_hours <= MAX_EMERGENCY_DELAY,
+            (MIN_EMERGENCY_DELAY..=MAX_EMERGENCY_DELAY).contains(&emergency_delay_hours),
             EstateError::InvalidEmergencyDelay
         );
         
@@ -407,7 +534,7 @@ pub mod defai_estate {
         );
         
         // Emit trading enabled event
-        emit!(TradingEnabled {
+        emit_versioned!(TradingEnabled {
             estate_id: estate.estate_id,
             ai_agent,
             human_share,
@@ -435,7 +562,7 @@ pub mod defai_estate {
         
         msg!("Trading paused for Estate #{}", estate.estate_number);
         
-        emit!(TradingPaused {
+        emit_versioned!(TradingPaused {
             estate_id: estate.estate_id,
             timestamp: Clock::get()?.unix_timestamp,
         });
@@ -463,7 +590,7 @@ pub mod defai_estate {
         
         msg!("Trading resumed for Estate #{}", estate.estate_number);
         
-        emit!(TradingResumed {
+        emit_versioned!(TradingResumed {
             estate_id: estate.estate_id,
             timestamp: Clock::get()?.unix_timestamp,
         });
@@ -586,7 +713,7 @@ pub mod defai_estate {
         );
         
         // Emit trading contribution event
-        emit!(TradingContribution {
+        emit_cpi_versioned!(event_cpi_of!(ctx), TradingContribution {
             estate_id: estate.estate_id,
             contributor: ctx.accounts.contributor.key(),
             amount,
@@ -637,6 +764,14 @@ pub mod defai_estate {
     
     Ok(())
 }
+
+    // Buy a DEFAI App Factory app with DEFAI from the estate vault
+    pub fn purchase_app_with_estate_funds<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseAppWithEstateFunds<'info>>,
+        app_id: u64,
+    ) -> Result<()> {
+        app_purchase::purchase_app_with_estate_funds(ctx, app_id)
+    }
     
     pub fn update_trading_value(
         ctx: Context<UpdateTradingValue>,
@@ -676,7 +811,7 @@ pub mod defai_estate {
         );
         
         // Emit trading value updated event
-        emit!(TradingValueUpdated {
+        emit_versioned!(TradingValueUpdated {
             estate_id: estate.estate_id,
             old_value,
             new_value: new_total_value,
@@ -699,20 +834,12 @@ pub mod defai_estate {
         require!(estate.trading_profit > 0, EstateError::NoProfitsToDistribute);
         
         // Calculate distributable profit (above high water mark)
-        let distributable_profit = if estate.trading_value > estate.high_water_mark {
-            estate.trading_value - estate.high_water_mark
-        } else {
-            0
-        };
+        let distributable_profit = estate.trading_value.saturating_sub(estate.high_water_mark);
         
         require!(distributable_profit > 0, EstateError::NoProfitsToDistribute);
         
         // Calculate shares
-        let human_profit_share = (distributable_profit as u128)
-            .checked_mul(estate.human_share as u128)
-            .unwrap()
-            .checked_div(100)
-            .unwrap() as u64;
+        let human_profit_share = percent_of(distributable_profit, estate.human_share as u64)?;
         let ai_profit_share = distributable_profit - human_profit_share;
         
         // Extract values before transfer to avoid borrow issues
@@ -776,7 +903,7 @@ pub mod defai_estate {
         );
         
         // Emit profits distributed event
-        emit!(ProfitsDistributed {
+        emit_cpi_versioned!(event_cpi_of!(ctx), ProfitsDistributed {
             estate_id: estate.estate_id,
             human_withdrawal: human_profit_share,
             ai_withdrawal: ai_profit_share,
@@ -813,7 +940,7 @@ pub mod defai_estate {
         );
         
         // Emit emergency withdrawal initiated event
-        emit!(EmergencyWithdrawalInitiated {
+        emit_versioned!(EmergencyWithdrawalInitiated {
             estate_id: estate.estate_id,
             initiator: ctx.accounts.owner.key(),
             execute_after: estate.emergency_withdrawal_time,
@@ -848,11 +975,7 @@ pub mod defai_estate {
         // Calculate human's proportional share
         let total_contributions = estate.human_contribution + estate.ai_contribution;
         let human_proportion = if total_contributions > 0 {
-            (estate.human_contribution as u128)
-                .checked_mul(estate.trading_value as u128)
-                .unwrap()
-                .checked_div(total_contributions as u128)
-                .unwrap() as u64
+            mul_div(estate.human_contribution, estate.trading_value, total_contributions)?
         } else {
             0
         };
@@ -918,7 +1041,7 @@ pub mod defai_estate {
         msg!("Estate check-in successful. Timer reset.");
         
         // Emit check-in event
-        emit!(EstateCheckedIn {
+        emit_versioned!(EstateCheckedIn {
             estate_id: estate.estate_id,
             owner: estate.owner,
             timestamp: clock.unix_timestamp,
@@ -933,25 +1056,15 @@ pub mod defai_estate {
     ) -> Result<()> {
         let estate = &mut ctx.accounts.estate;
 
-        require!(!estate.is_locked, EstateError::EstateLocked);
-        require!(!estate.is_claimable, EstateError::EstateClaimable);
-        
-        // Check authorization - either owner or multisig
-        let is_owner = ctx.accounts.owner.key() == estate.owner;
-        let is_multisig = estate.multisig.is_some() && 
-            ctx.accounts.owner.key() == estate.multisig.unwrap();
-        
-        require!(
-            is_owner || is_multisig,
-            EstateError::UnauthorizedAccess
-        );
+        // Owner or multisig, before the estate locks or opens to claims
+        estate.require_beneficiary_editor(&ctx.accounts.owner.key())?;
         require!(
             beneficiaries.len() <= MAX_BENEFICIARIES as usize,
             EstateError::TooManyBeneficiaries
         );
 
         // Validate percentages sum to 100
-        let total_percentage: u8 = beneficiaries.iter().map(|b| b.share_percentage).sum();
+        let total_percentage: u64 = beneficiaries.iter().map(|b| b.share_percentage as u64).sum();
         require!(
             total_percentage == 100,
             EstateError::InvalidBeneficiaryShares
@@ -965,6 +1078,30 @@ pub mod defai_estate {
         Ok(())
     }
 
+    // Add one beneficiary, taking their share from `from_index`; the first takes 100%
+    pub fn add_beneficiary(
+        ctx: Context<AddBeneficiary>,
+        address: Pubkey,
+        email_hash: [u8; 32],
+        share_percentage: u8,
+        from_index: Option<u8>,
+    ) -> Result<()> {
+        beneficiaries::add_beneficiary(ctx, address, email_hash, share_percentage, from_index)
+    }
+
+    pub fn remove_beneficiary(ctx: Context<EditBeneficiary>, index: u8, heir_index: u8) -> Result<()> {
+        beneficiaries::remove_beneficiary(ctx, index, heir_index)
+    }
+
+    pub fn update_beneficiary_share(
+        ctx: Context<EditBeneficiary>,
+        index: u8,
+        share_percentage: u8,
+        balance_index: u8,
+    ) -> Result<()> {
+        beneficiaries::update_beneficiary_share(ctx, index, share_percentage, balance_index)
+    }
+
     // Additional estate functions continue here...
     
     pub fn create_rwa(
@@ -1008,7 +1145,7 @@ pub mod defai_estate {
         msg!("RWA #{} created for Estate #{}", rwa.rwa_number, estate.estate_number);
         
         // Emit RWA added event
-        emit!(RWAAdded {
+        emit_versioned!(RWAAdded {
             estate_id: estate.estate_id,
             rwa_id: ctx.accounts.rwa.key(),
             metadata_uri,
@@ -1040,7 +1177,7 @@ pub mod defai_estate {
         msg!("RWA #{} deleted from Estate #{}", rwa.rwa_number, estate.estate_number);
         
         // Emit RWA deleted event
-        emit!(RWADeleted {
+        emit_versioned!(RWADeleted {
             estate_id: estate.estate_id,
             rwa_id: ctx.accounts.rwa.key(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -1076,23 +1213,12 @@ pub mod defai_estate {
         let estate = &mut ctx.accounts.estate;
         let clock = Clock::get()?;
 
-        require!(!estate.is_locked, EstateError::EstateLocked);
-        require!(!estate.is_claimable, EstateError::AlreadyClaimable);
-
-        let inactive_since = estate.last_active + estate.inactivity_period;
-        let grace_ends = inactive_since + estate.grace_period;
-
-        require!(
-            clock.unix_timestamp > grace_ends,
-            EstateError::NotYetClaimable
-        );
-
-        estate.is_claimable = true;
+        estate.trigger_inheritance(clock.unix_timestamp)?;
 
         msg!("Estate is now claimable by beneficiaries");
         
         // Emit estate locked event
-        emit!(EstateLocked {
+        emit_versioned!(EstateLocked {
             estate_id: estate.estate_id,
             timestamp: clock.unix_timestamp,
         });
@@ -1104,41 +1230,25 @@ pub mod defai_estate {
         ctx: Context<ClaimInheritance>,
         beneficiary_index: u8,
     ) -> Result<()> {
-        // First, validate the estate state and get needed values
         let estate_key = ctx.accounts.estate.key();
         let beneficiary_key = ctx.accounts.beneficiary.key();
-        
-        {
-            let estate = &ctx.accounts.estate;
-            require!(estate.is_claimable, EstateError::NotClaimable);
-            require!(
-                beneficiary_index < estate.total_beneficiaries,
-                EstateError::InvalidBeneficiaryIndex
-            );
-            
-            let beneficiary = &estate.beneficiaries[beneficiary_index as usize];
-            require!(
-                beneficiary.address == beneficiary_key,
-                EstateError::UnauthorizedBeneficiary
-            );
-            require!(!beneficiary.claimed, EstateError::AlreadyClaimed);
-        }
+        let now = Clock::get()?.unix_timestamp;
 
-        // Get share percentage before mutable borrow
-        let share_percentage = ctx.accounts.estate.beneficiaries[beneficiary_index as usize].share_percentage;
+        let share_percentage = {
+            let beneficiary = validate_claimant(&ctx.accounts.estate, beneficiary_index, &beneficiary_key)?;
+            require!(!beneficiary.claimed, EstateError::AlreadyClaimed);
+            beneficiary.share_percentage
+        };
 
-        // Calculate SOL to transfer
-        let estate_balance = ctx.accounts.estate.to_account_info().lamports();
-        let transferable_balance = estate_balance.saturating_sub(MIN_RENT_BALANCE);
-        let sol_share = (transferable_balance as u128)
-            .checked_mul(share_percentage as u128)
-            .unwrap()
-            .checked_div(100)
-            .unwrap() as u64;
+        // Calculate SOL to transfer, keeping rent for the estate at its current size
+        let estate_info = ctx.accounts.estate.to_account_info();
+        let rent_reserve = Rent::get()?.minimum_balance(estate_info.data_len());
+        let transferable_balance = estate_info.lamports().saturating_sub(rent_reserve);
+        let sol_share = percent_of(transferable_balance, share_percentage as u64)?;
 
         // Transfer SOL to beneficiary
         if sol_share > 0 {
-            **ctx.accounts.estate.to_account_info().try_borrow_mut_lamports()? -= sol_share;
+            **estate_info.try_borrow_mut_lamports()? -= sol_share;
             **ctx.accounts.beneficiary.to_account_info().try_borrow_mut_lamports()? += sol_share;
         }
 
@@ -1146,31 +1256,42 @@ pub mod defai_estate {
         let claim_record = &mut ctx.accounts.claim_record;
         claim_record.estate = estate_key;
         claim_record.beneficiary = beneficiary_key;
-        claim_record.claim_time = Clock::get()?.unix_timestamp;
+        claim_record.claim_time = now;
         claim_record.sol_amount = sol_share;
         claim_record.share_percentage = share_percentage;
         claim_record.tokens_claimed = Vec::new();
         claim_record.nfts_claimed = Vec::new();
+        claim_record.stage = ClaimStage::Tokens;
+        claim_record.steps = 0;
+        claim_record.rwas_claimed = 0;
+        claim_record.checkpoint(now);
 
         // Mark as claimed
         let estate = &mut ctx.accounts.estate;
         estate.beneficiaries[beneficiary_index as usize].claimed = true;
         estate.total_claims += 1;
 
-        msg!(
-            "Beneficiary {} claimed {}% of estate. SOL transferred: {}",
-            beneficiary_key,
-            share_percentage,
-            sol_share
-        );
+        // The shares being paid out still add up, and the estate keeps its rent reserve
+        require_shares_total(
+            "estate beneficiary shares",
+            estate.beneficiaries.iter().map(|b| b.share_percentage as u64),
+            100,
+        )?;
+        require_invariant(
+            estate.total_claims <= estate.total_beneficiaries,
+            "estate claims within beneficiaries",
+        )?;
+        require_covers("estate rent reserve", estate.to_account_info().lamports(), rent_reserve)?;
+
+        msg!("Claimed {}% of estate, {} lamports", share_percentage, sol_share);
         
         // Emit inheritance claimed event
-        emit!(InheritanceClaimed {
+        emit_cpi_versioned!(event_cpi_of!(ctx), InheritanceClaimed {
             estate_id: estate.estate_id,
             beneficiary: beneficiary_key,
             share_percentage,
             claim_number: estate.total_claims as u64,
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: now,
         });
 
         Ok(())
@@ -1182,7 +1303,7 @@ pub mod defai_estate {
     ) -> Result<()> {
         let estate = &ctx.accounts.estate;
         let rwa = &mut ctx.accounts.rwa;
-        let claim_record = &ctx.accounts.claim_record;
+        let claim_record = &mut ctx.accounts.claim_record;
         
         require!(estate.is_claimable, EstateError::NotClaimable);
         require!(
@@ -1202,9 +1323,16 @@ pub mod defai_estate {
             EstateError::InvalidRWA
         );
         require!(rwa.is_active, EstateError::RWAAlreadyDeleted);
+        claim_record.require_stage(ClaimStage::Rwas)?;
+        require!(
+            rwa.current_owner != ctx.accounts.beneficiary.key(),
+            EstateError::RWAAlreadyClaimed
+        );
 
         // Transfer ownership
         rwa.current_owner = ctx.accounts.beneficiary.key();
+        claim_record.rwas_claimed += 1;
+        claim_record.checkpoint(Clock::get()?.unix_timestamp);
 
         msg!(
             "RWA #{} ownership transferred to {}",
@@ -1219,107 +1347,90 @@ pub mod defai_estate {
         ctx: Context<ClaimToken>,
         beneficiary_index: u8,
     ) -> Result<()> {
-        let estate = &ctx.accounts.estate;
-        let claim_record = &mut ctx.accounts.claim_record;
-        
-        require!(estate.is_claimable, EstateError::NotClaimable);
-        require!(
-            beneficiary_index < estate.total_beneficiaries,
-            EstateError::InvalidBeneficiaryIndex
-        );
-        
-        let beneficiary = &estate.beneficiaries[beneficiary_index as usize];
-        require!(
-            beneficiary.address == ctx.accounts.beneficiary.key(),
-            EstateError::UnauthorizedBeneficiary
-        );
-        require!(beneficiary.claimed, EstateError::MustClaimInheritanceFirst);
+        let beneficiary = validate_asset_claimant(
+            &ctx.accounts.estate,
+            beneficiary_index,
+            &ctx.accounts.beneficiary.key(),
+        )?;
         
+        ctx.accounts.claim_record.require_stage(ClaimStage::Tokens)?;
+
         // Check if this token was already claimed
         let token_mint = ctx.accounts.token_mint.key();
-        for token_claim in &claim_record.tokens_claimed {
-            require!(
-                token_claim.mint != token_mint,
-                EstateError::TokenAlreadyClaimed
-            );
-        }
+        require!(
+            ctx.accounts.claim_record.tokens_claimed.iter().all(|c| c.mint != token_mint),
+            EstateError::TokenAlreadyClaimed
+        );
         
         // Calculate share
-        let estate_token_balance = ctx.accounts.estate_token_account.amount;
-        let token_share = (estate_token_balance as u128)
-            .checked_mul(beneficiary.share_percentage as u128)
-            .unwrap()
-            .checked_div(100)
-            .unwrap() as u64;
+        let token_share = percent_of(
+            ctx.accounts.estate_token_account.amount,
+            beneficiary.share_percentage as u64,
+        )?;
         
+        let balance_before = ctx.accounts.estate_token_account.amount;
         if token_share > 0 {
-            // Transfer tokens
-            let estate_number_bytes = estate.estate_number.to_le_bytes();
-            let seeds = &[
-                ESTATE_SEED,
-                estate.owner.as_ref(),
-                estate_number_bytes.as_ref(),
-                &[ctx.bumps.estate]
-            ];
-            let signer = &[&seeds[..]];
-            
-            let cpi_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.estate_token_account.to_account_info(),
-                    to: ctx.accounts.beneficiary_token_account.to_account_info(),
-                    authority: ctx.accounts.estate.to_account_info(),
-                },
-                signer,
-            );
-            
-            token::transfer(cpi_ctx, token_share)?;
+            transfer_from_estate(
+                &ctx.accounts.estate,
+                &ctx.accounts.estate_token_account.to_account_info(),
+                &ctx.accounts.beneficiary_token_account.to_account_info(),
+                &ctx.accounts.token_program,
+                token_share,
+                ctx.bumps.estate,
+            )?;
             
             // Record the claim
-            claim_record.tokens_claimed.push(TokenClaim {
+            ctx.accounts.claim_record.tokens_claimed.push(TokenClaim {
                 mint: token_mint,
                 amount: token_share,
             });
+            ctx.accounts.claim_record.checkpoint(Clock::get()?.unix_timestamp);
         }
+
+        // The estate's account paid exactly the share and is still the estate's
+        ctx.accounts.estate_token_account.reload()?;
+        require_vault_owner(
+            "estate token account",
+            &ctx.accounts.estate_token_account.owner,
+            &ctx.accounts.estate.key(),
+        )?;
+        require_invariant(
+            ctx.accounts.estate_token_account.amount == balance_before - token_share,
+            "estate token account debited by the claimed share",
+        )?;
         
-        msg!(
-            "Beneficiary {} claimed {} tokens of mint {}",
-            beneficiary.address,
-            token_share,
-            token_mint
-        );
+        msg!("Claimed {} tokens", token_share);
         
         Ok(())
     }
 
+    // Claim several SPL token mints at once; remaining accounts are
+    // (mint, estate ATA, beneficiary ATA) per mint
+    pub fn claim_tokens_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimTokensBatch<'info>>,
+        beneficiary_index: u8,
+    ) -> Result<()> {
+        claim_batch::claim_tokens_batch(ctx, beneficiary_index)
+    }
+
     pub fn claim_nft(
         ctx: Context<ClaimNFT>,
         beneficiary_index: u8,
     ) -> Result<()> {
-        let estate = &ctx.accounts.estate;
-        let claim_record = &mut ctx.accounts.claim_record;
-        
-        require!(estate.is_claimable, EstateError::NotClaimable);
-        require!(
-            beneficiary_index < estate.total_beneficiaries,
-            EstateError::InvalidBeneficiaryIndex
-        );
-        
-        let beneficiary = &estate.beneficiaries[beneficiary_index as usize];
-        require!(
-            beneficiary.address == ctx.accounts.beneficiary.key(),
-            EstateError::UnauthorizedBeneficiary
-        );
-        require!(beneficiary.claimed, EstateError::MustClaimInheritanceFirst);
+        validate_asset_claimant(
+            &ctx.accounts.estate,
+            beneficiary_index,
+            &ctx.accounts.beneficiary.key(),
+        )?;
         
+        ctx.accounts.claim_record.require_stage(ClaimStage::Nfts)?;
+
         // Check if this NFT was already claimed
         let nft_mint = ctx.accounts.nft_mint.key();
-        for nft_claimed in &claim_record.nfts_claimed {
-            require!(
-                *nft_claimed != nft_mint,
-                EstateError::NFTAlreadyClaimed
-            );
-        }
+        require!(
+            !ctx.accounts.claim_record.nfts_claimed.contains(&nft_mint),
+            EstateError::NFTAlreadyClaimed
+        );
         
         // Verify estate owns exactly 1 of this NFT
         require!(
@@ -1327,40 +1438,96 @@ pub mod defai_estate {
             EstateError::InvalidNFTAmount
         );
         
-        // Transfer NFT
-        let estate_number_bytes = estate.estate_number.to_le_bytes();
-        let seeds = &[
-            ESTATE_SEED,
-            estate.owner.as_ref(),
-            estate_number_bytes.as_ref(),
-            &[ctx.bumps.estate]
-        ];
-        let signer = &[&seeds[..]];
-        
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.estate_nft_account.to_account_info(),
-                to: ctx.accounts.beneficiary_nft_account.to_account_info(),
-                authority: ctx.accounts.estate.to_account_info(),
-            },
-            signer,
-        );
+        transfer_nft_from_estate(
+            &ctx.accounts.estate,
+            &ctx.accounts.estate_nft_account.to_account_info(),
+            &ctx.accounts.beneficiary_nft_account.to_account_info(),
+            &ctx.accounts.nft_mint,
+            &ctx.accounts.token_program,
+            ctx.bumps.estate,
+        )?;
         
-        token::transfer(cpi_ctx, 1)?;
+        // The NFT left the estate's account for the beneficiary's
+        ctx.accounts.estate_nft_account.reload()?;
+        ctx.accounts.beneficiary_nft_account.reload()?;
+        require_vault_owner(
+            "estate NFT account",
+            &ctx.accounts.estate_nft_account.owner,
+            &ctx.accounts.estate.key(),
+        )?;
+        require_invariant(
+            ctx.accounts.estate_nft_account.amount == 0
+                && ctx.accounts.beneficiary_nft_account.amount >= 1,
+            "NFT moved from estate to beneficiary",
+        )?;
         
         // Record the claim
-        claim_record.nfts_claimed.push(nft_mint);
+        ctx.accounts.claim_record.nfts_claimed.push(nft_mint);
+        ctx.accounts.claim_record.checkpoint(Clock::get()?.unix_timestamp);
         
-        msg!(
-            "Beneficiary {} claimed NFT {}",
-            beneficiary.address,
-            nft_mint
-        );
+        msg!("Claimed NFT");
         
         Ok(())
     }
 
+    // Claim a programmable NFT through Token Metadata's transfer
+    pub fn claim_pnft(ctx: Context<ClaimPnft>, beneficiary_index: u8) -> Result<()> {
+        claim_pnft::claim_pnft(ctx, beneficiary_index)
+    }
+
+    // Open the claim's next stage; earlier kinds stay claimable until Complete
+    pub fn advance_claim(ctx: Context<AdvanceClaim>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let claim_record = &mut ctx.accounts.claim_record;
+        let stage = claim_record.advance(now)?;
+
+        emit_versioned!(ClaimStageAdvanced {
+            estate_id: ctx.accounts.estate.estate_id,
+            beneficiary: claim_record.beneficiary,
+            stage,
+            tokens_claimed: claim_record.tokens_claimed.len() as u32,
+            nfts_claimed: claim_record.nfts_claimed.len() as u32,
+            rwas_claimed: claim_record.rwas_claimed,
+            steps: claim_record.steps,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // ===== Compressed Claim Receipts =====
+
+    pub fn initialize_receipt_log(ctx: Context<InitializeReceiptLog>) -> Result<()> {
+        receipts::initialize_receipt_log(ctx)
+    }
+
+    // Fold a completed claim record into the receipt log and refund its rent
+    pub fn compress_claim_record(ctx: Context<CompressClaimRecord>) -> Result<()> {
+        receipts::compress_claim_record(ctx)
+    }
+
+    // ===== Program Registry =====
+
+    // Record the sibling program ids of this deployment; None takes the main deployment's
+    pub fn initialize_program_registry(
+        ctx: Context<InitializeProgramRegistry>,
+        links: Option<ProgramLinks>,
+    ) -> Result<()> {
+        program_registry::initialize_program_registry(ctx, links)
+    }
+
+    pub fn propose_program_links_change(ctx: Context<UpdateProgramRegistry>, links: ProgramLinks) -> Result<()> {
+        program_registry::propose_program_links_change(ctx, links)
+    }
+
+    pub fn cancel_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::cancel_program_links_change(ctx)
+    }
+
+    pub fn execute_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::execute_program_links_change(ctx)
+    }
+
     pub fn close_estate(ctx: Context<CloseEstate>) -> Result<()> {
         let estate = &ctx.accounts.estate;
         let asset_summary = &ctx.accounts.asset_summary;
@@ -1471,6 +1638,14 @@ pub mod defai_estate {
         
         msg!("Estate #{} recovered to {}", estate.estate_number, ctx.accounts.recovery_address.key());
         
+        let estate_key = ctx.accounts.estate.key();
+        ctx.accounts.audit_log.record(
+            AuditAction::RecoveryExecuted,
+            ctx.accounts.admin.key(),
+            estate_key,
+            ctx.accounts.recovery_address.key(),
+        )?;
+        
         Ok(())
     }
     
@@ -1494,7 +1669,7 @@ pub mod defai_estate {
         
         msg!("Multisig attached to Estate #{}", estate.estate_number);
         
-        emit!(MultisigAttached {
+        emit_versioned!(MultisigAttached {
             estate_id: estate.estate_id,
             multisig_address: ctx.accounts.multisig.key(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -1567,6 +1742,25 @@ impl Estate {
         self.is_claimable = false;
         Ok(())
     }
+
+    // Open the estate to beneficiary claims once inactivity and grace have run out
+    pub fn trigger_inheritance(&mut self, now: i64) -> Result<()> {
+        require!(!self.is_locked, EstateError::EstateLocked);
+        require!(!self.is_claimable, EstateError::AlreadyClaimable);
+        // Every edit keeps the list at 100%; claims divide by it, so never open one that isn't
+        require!(
+            self.beneficiaries.is_empty() || self.share_total() == 100,
+            EstateError::InvalidBeneficiaryShares
+        );
+
+        let inactive_since = self.last_active + self.inactivity_period;
+        let grace_ends = inactive_since + self.grace_period;
+
+        require!(now > grace_ends, EstateError::NotYetClaimable);
+
+        self.is_claimable = true;
+        Ok(())
+    }
 }
 
 // JointAccount struct removed - all functionality merged into Estate
@@ -1599,6 +1793,22 @@ pub struct ClaimRecord {
     pub share_percentage: u8,
     pub tokens_claimed: Vec<TokenClaim>,
     pub nfts_claimed: Vec<Pubkey>,
+    // Checkpoint for claims that span several transactions (see claim.rs).
+    // Appended after the claim lists so readers of the fields above, such as
+    // defai_swap's inheritance check, keep parsing the record.
+    pub stage: ClaimStage,
+    pub steps: u32,          // Transactions that moved this claim forward
+    pub rwas_claimed: u32,
+    pub last_step_at: i64,
+}
+
+// Stages run in order; claim_inheritance pays the SOL share and opens Tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClaimStage {
+    Tokens,     // claim_token
+    Nfts,       // claim_nft
+    Rwas,       // transfer_rwa_ownership
+    Complete,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -1689,6 +1899,13 @@ pub struct ProposeAdminChange<'info> {
         constraint = multisig.admin == signer.key()
     )]
     pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
@@ -1702,6 +1919,13 @@ pub struct AcceptAdminChange<'info> {
             @ EstateError::UnauthorizedAccess
     )]
     pub multisig: Account<'info, Multisig>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 #[derive(Accounts)]
@@ -1773,7 +1997,7 @@ pub struct InitializeGlobalCounter<'info> {
         init,
         payer = admin,
         space = 8 + 8,
-        seeds = [COUNTER_SEED],
+        seeds = [b"counter"],
         bump
     )]
     pub global_counter: Account<'info, GlobalCounter>,
@@ -1789,42 +2013,8 @@ pub struct CreateEstate<'info> {
     #[account(
         init,
         payer = owner,
-        space = 8 + // discriminator
-            32 + // estate_id
-            32 + // owner
-            32 + // owner_email_hash
-            8 + // last_active
-            8 + // inactivity_period
-            8 + // grace_period
-            (4 + 10 * (32 + 32 + 1 + 1 + 1)) + // beneficiaries vector
-            1 + // total_beneficiaries
-            8 + // creation_time
-            8 + // estate_value
-            1 + // is_locked
-            1 + // is_claimable
-            4 + // total_rwas
-            8 + // estate_number
-            1 + // total_claims
-            // Trading fields
-            1 + // trading_enabled
-            (1 + 32) + // ai_agent Option<Pubkey>
-            (1 + 32) + // trading_strategy Option<TradingStrategy>
-            8 + // human_contribution
-            8 + // ai_contribution
-            8 + // trading_value
-            8 + // trading_profit
-            8 + // high_water_mark
-            1 + // human_share
-            1 + // ai_share
-            (1 + 1) + // stop_loss Option<u8>
-            4 + // emergency_delay_hours
-            1 + // emergency_withdrawal_initiated
-            8 + // emergency_withdrawal_time
-            8 + // last_trading_update
-            (1 + 32) + // multisig Option<Pubkey>
-            (1 + RiskManagementSettings::LEN) + // risk_settings Option
-            100, // buffer
-        seeds = [ESTATE_SEED, owner.key().as_ref(), global_counter.count.to_le_bytes().as_ref()],
+        space = Estate::space(INITIAL_BENEFICIARY_SLOTS),
+        seeds = [b"estate", owner.key().as_ref(), global_counter.count.to_le_bytes().as_ref()],
         bump
     )]
     pub estate: Account<'info, Estate>,
@@ -1878,6 +2068,7 @@ pub struct ResumeTrading<'info> {
     pub estate: Account<'info, Estate>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ContributeToTrading<'info> {
     #[account(mut)]
@@ -1895,7 +2086,7 @@ pub struct ContributeToTrading<'info> {
     #[account(
         mut,
         seeds = [
-            ESTATE_VAULT_SEED,
+            b"estate_vault",
             estate.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -1914,7 +2105,7 @@ pub struct InitEstateVault<'info> {
     #[account(
         mut,
         has_one = owner,
-        seeds = [ESTATE_SEED, estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
         bump
     )]
     pub estate: Account<'info, Estate>,
@@ -1922,7 +2113,7 @@ pub struct InitEstateVault<'info> {
     #[account(
         mut,
         seeds = [
-            ESTATE_VAULT_SEED,
+            b"estate_vault",
             estate.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -1946,6 +2137,7 @@ pub struct UpdateTradingValue<'info> {
     pub estate: Account<'info, Estate>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DistributeTradingProfits<'info> {
     pub authority: Signer<'info>,
@@ -1955,7 +2147,7 @@ pub struct DistributeTradingProfits<'info> {
         constraint = estate.trading_enabled @ EstateError::TradingNotEnabled,
         constraint = estate.trading_profit > 0 @ EstateError::NoProfitsToDistribute,
         seeds = [
-            ESTATE_SEED,
+            b"estate",
             estate.owner.as_ref(),
             estate.estate_number.to_le_bytes().as_ref(),
         ],
@@ -1968,7 +2160,7 @@ pub struct DistributeTradingProfits<'info> {
         token::mint = token_mint,
         token::authority = estate,
         seeds = [
-            ESTATE_VAULT_SEED,
+            b"estate_vault",
             estate.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -2015,7 +2207,7 @@ pub struct ExecuteTradingEmergencyWithdrawal<'info> {
         has_one = owner,
         constraint = estate.emergency_withdrawal_initiated @ EstateError::EmergencyWithdrawalNotInitiated,
         seeds = [
-            ESTATE_SEED,
+            b"estate",
             estate.owner.as_ref(),
             estate.estate_number.to_le_bytes().as_ref(),
         ],
@@ -2028,7 +2220,7 @@ pub struct ExecuteTradingEmergencyWithdrawal<'info> {
         token::mint = token_mint,
         token::authority = estate,
         seeds = [
-            ESTATE_VAULT_SEED,
+            b"estate_vault",
             estate.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -2050,7 +2242,7 @@ pub struct DepositTokenToEstate<'info> {
     #[account(
         mut,
         seeds = [
-            ESTATE_SEED,
+            b"estate",
             estate.owner.as_ref(),
             estate.estate_number.to_le_bytes().as_ref(),
         ],
@@ -2062,7 +2254,7 @@ pub struct DepositTokenToEstate<'info> {
     #[account(
         mut,
         seeds = [
-            ESTATE_VAULT_SEED,
+            b"estate_vault",
             estate.key().as_ref(),
             token_mint.key().as_ref(),
         ],
@@ -2086,14 +2278,20 @@ pub struct CheckIn<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(beneficiaries: Vec<Beneficiary>)]
 pub struct UpdateBeneficiaries<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
     #[account(
         mut,
+        realloc = Estate::grown_space(beneficiaries.len(), estate.to_account_info().data_len()),
+        realloc::payer = owner,
+        realloc::zero = false,
     )]
     pub estate: Account<'info, Estate>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -2111,7 +2309,7 @@ pub struct CreateRWA<'info> {
         init,
         payer = owner,
         space = 8 + 32 + (4 + 32) + (4 + 128) + (4 + 256) + (4 + 64) + (4 + 256) + 8 + 1 + 4 + 32,
-        seeds = [RWA_SEED, estate.key().as_ref(), estate.total_rwas.to_le_bytes().as_ref()],
+        seeds = [b"rwa", estate.key().as_ref(), estate.total_rwas.to_le_bytes().as_ref()],
         bump
     )]
     pub rwa: Account<'info, RWA>,
@@ -2144,7 +2342,7 @@ pub struct ScanEstateAssets<'info> {
         init_if_needed,
         payer = authority,
         space = 8 + 32 + 8 + 8 + 4 + 4,
-        seeds = [ASSET_SUMMARY_SEED, estate.key().as_ref()],
+        seeds = [b"asset_summary", estate.key().as_ref()],
         bump
     )]
     pub asset_summary: Account<'info, AssetSummary>,
@@ -2160,6 +2358,7 @@ pub struct TriggerInheritance<'info> {
     pub estate: Account<'info, Estate>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimInheritance<'info> {
     #[account(mut)]
@@ -2167,19 +2366,19 @@ pub struct ClaimInheritance<'info> {
     
     #[account(
         mut,
-        seeds = [ESTATE_SEED, estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
         bump
     )]
-    pub estate: Account<'info, Estate>,
+    pub estate: Box<Account<'info, Estate>>,
     
     #[account(
         init,
         payer = beneficiary,
-        space = 8 + 32 + 32 + 8 + 8 + 1 + (4 + 10 * (32 + 8)) + (4 + 10 * 32),
-        seeds = [CLAIM_SEED, estate.key().as_ref(), beneficiary.key().as_ref()],
+        space = ClaimRecord::space(INITIAL_CLAIM_SLOTS, INITIAL_CLAIM_SLOTS),
+        seeds = [b"claim", estate.key().as_ref(), beneficiary.key().as_ref()],
         bump
     )]
-    pub claim_record: Account<'info, ClaimRecord>,
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
     
     pub system_program: Program<'info, System>,
 }
@@ -2189,6 +2388,7 @@ pub struct TransferRWAOwnership<'info> {
     #[account(mut)]
     pub beneficiary: Signer<'info>,
     
+    #[account(mut)]
     pub claim_record: Account<'info, ClaimRecord>,
     
     pub estate: Account<'info, Estate>,
@@ -2203,26 +2403,29 @@ pub struct ClaimToken<'info> {
     pub beneficiary: Signer<'info>,
     
     #[account(
-        seeds = [ESTATE_SEED, estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
         bump
     )]
-    pub estate: Account<'info, Estate>,
+    pub estate: Box<Account<'info, Estate>>,
     
     #[account(
         mut,
         has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
         has_one = estate @ EstateError::InvalidClaimRecord,
+        realloc = claim_record.grown_space(1, 0, claim_record.to_account_info().data_len()),
+        realloc::payer = beneficiary,
+        realloc::zero = false,
     )]
-    pub claim_record: Account<'info, ClaimRecord>,
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
     
-    pub token_mint: InterfaceAccount<'info, MintInterface>,
+    pub token_mint: Box<InterfaceAccount<'info, MintInterface>>,
     
     #[account(
         mut,
         associated_token::mint = token_mint,
         associated_token::authority = estate,
     )]
-    pub estate_token_account: InterfaceAccount<'info, TokenAccountInterface>,
+    pub estate_token_account: Box<InterfaceAccount<'info, TokenAccountInterface>>,
     
     #[account(
         init_if_needed,
@@ -2230,7 +2433,7 @@ pub struct ClaimToken<'info> {
         associated_token::mint = token_mint,
         associated_token::authority = beneficiary,
     )]
-    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccountInterface>,
+    pub beneficiary_token_account: Box<InterfaceAccount<'info, TokenAccountInterface>>,
     
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -2243,40 +2446,64 @@ pub struct ClaimNFT<'info> {
     pub beneficiary: Signer<'info>,
     
     #[account(
-        seeds = [ESTATE_SEED, estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
         bump
     )]
-    pub estate: Account<'info, Estate>,
+    pub estate: Box<Account<'info, Estate>>,
     
     #[account(
         mut,
         has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
         has_one = estate @ EstateError::InvalidClaimRecord,
+        realloc = claim_record.grown_space(0, 1, claim_record.to_account_info().data_len()),
+        realloc::payer = beneficiary,
+        realloc::zero = false,
     )]
-    pub claim_record: Account<'info, ClaimRecord>,
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
     
-    pub nft_mint: InterfaceAccount<'info, MintInterface>,
+    pub nft_mint: Box<InterfaceAccount<'info, MintInterface>>,
     
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = estate,
+        associated_token::token_program = token_program,
     )]
-    pub estate_nft_account: InterfaceAccount<'info, TokenAccountInterface>,
+    pub estate_nft_account: Box<InterfaceAccount<'info, TokenAccountInterface>>,
     
     #[account(
         init_if_needed,
         payer = beneficiary,
         associated_token::mint = nft_mint,
         associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
     )]
-    pub beneficiary_nft_account: InterfaceAccount<'info, TokenAccountInterface>,
+    pub beneficiary_nft_account: Box<InterfaceAccount<'info, TokenAccountInterface>>,
     
-    pub token_program: Program<'info, Token>,
+    // SPL Token or Token-2022 (DEFAI Swap bonus NFTs)
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct AdvanceClaim<'info> {
+    pub beneficiary: Signer<'info>,
+    
+    #[account(
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub estate: Box<Account<'info, Estate>>,
+    
+    #[account(
+        mut,
+        has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
+        has_one = estate @ EstateError::InvalidClaimRecord,
+    )]
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
+}
+
 #[derive(Accounts)]
 pub struct CloseEstate<'info> {
     #[account(mut)]
@@ -2290,7 +2517,7 @@ pub struct CloseEstate<'info> {
     pub estate: Account<'info, Estate>,
 
     #[account(
-        seeds = [ASSET_SUMMARY_SEED, estate.key().as_ref()],
+        seeds = [b"asset_summary", estate.key().as_ref()],
         bump
     )]
     pub asset_summary: Account<'info, AssetSummary>,
@@ -2309,7 +2536,7 @@ pub struct InitiateRecovery<'info> {
         init,
         payer = admin,
         space = 8 + 32 + 32 + 8 + 8 + (4 + 256) + 1,
-        seeds = [RECOVERY_SEED, estate.key().as_ref()],
+        seeds = [b"recovery", estate.key().as_ref()],
         bump
     )]
     pub recovery: Account<'info, Recovery>,
@@ -2328,13 +2555,20 @@ pub struct ExecuteRecovery<'info> {
     #[account(
         mut,
         has_one = estate,
-        seeds = [RECOVERY_SEED, estate.key().as_ref()],
+        seeds = [b"recovery", estate.key().as_ref()],
         bump
     )]
     pub recovery: Account<'info, Recovery>,
     
     /// CHECK: The new owner address for the recovered estate
     pub recovery_address: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
 }
 
 // ===== Events =====
@@ -2342,6 +2576,7 @@ pub struct ExecuteRecovery<'info> {
 // Multi-sig Events
 #[event]
 pub struct MultisigCreated {
+    pub schema_version: u8,
     pub multisig_address: Pubkey,
     pub signers: Vec<Pubkey>,
     pub threshold: u8,
@@ -2350,6 +2585,7 @@ pub struct MultisigCreated {
 
 #[event]
 pub struct AdminChangeProposed {
+    pub schema_version: u8,
     pub old_admin: Pubkey,
     pub new_admin: Pubkey,
     pub execute_after: i64,
@@ -2358,6 +2594,7 @@ pub struct AdminChangeProposed {
 
 #[event]
 pub struct AdminChangeExecuted {
+    pub schema_version: u8,
     pub old_admin: Pubkey,
     pub new_admin: Pubkey,
     pub timestamp: i64,
@@ -2365,6 +2602,7 @@ pub struct AdminChangeExecuted {
 
 #[event]
 pub struct ProposalCreated {
+    pub schema_version: u8,
     pub proposal_id: u64,
     pub proposer: Pubkey,
     pub target_estate: Pubkey,
@@ -2374,6 +2612,7 @@ pub struct ProposalCreated {
 
 #[event]
 pub struct ProposalApproved {
+    pub schema_version: u8,
     pub proposal_id: u64,
     pub approver: Pubkey,
     pub total_approvals: u8,
@@ -2382,6 +2621,7 @@ pub struct ProposalApproved {
 
 #[event]
 pub struct ProposalExecuted {
+    pub schema_version: u8,
     pub proposal_id: u64,
     pub executor: Pubkey,
     pub timestamp: i64,
@@ -2389,6 +2629,7 @@ pub struct ProposalExecuted {
 
 #[event]
 pub struct MultisigAttached {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub multisig_address: Pubkey,
     pub timestamp: i64,
@@ -2397,6 +2638,7 @@ pub struct MultisigAttached {
 // Estate Events
 #[event]
 pub struct EstateCreated {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub owner: Pubkey,
     pub estate_number: u64,
@@ -2407,6 +2649,7 @@ pub struct EstateCreated {
 
 #[event]
 pub struct BeneficiaryAdded {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub beneficiary_address: Pubkey,
     pub share_percentage: u8,
@@ -2416,6 +2659,7 @@ pub struct BeneficiaryAdded {
 
 #[event]
 pub struct BeneficiaryRemoved {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub beneficiary_address: Pubkey,
     pub index: u8,
@@ -2424,6 +2668,7 @@ pub struct BeneficiaryRemoved {
 
 #[event]
 pub struct EstateCheckedIn {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub owner: Pubkey,
     pub timestamp: i64,
@@ -2431,18 +2676,21 @@ pub struct EstateCheckedIn {
 
 #[event]
 pub struct EstateLocked {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct EstateUnlocked {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct InheritanceClaimed {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub beneficiary: Pubkey,
     pub share_percentage: u8,
@@ -2450,8 +2698,22 @@ pub struct InheritanceClaimed {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ClaimStageAdvanced {
+    pub schema_version: u8,
+    pub estate_id: Pubkey,
+    pub beneficiary: Pubkey,
+    pub stage: ClaimStage,      // Stage the claim is now in
+    pub tokens_claimed: u32,
+    pub nfts_claimed: u32,
+    pub rwas_claimed: u32,
+    pub steps: u32,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RWAAdded {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub rwa_id: Pubkey,
     pub metadata_uri: String,
@@ -2460,6 +2722,7 @@ pub struct RWAAdded {
 
 #[event]
 pub struct RWADeleted {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub rwa_id: Pubkey,
     pub timestamp: i64,
@@ -2467,6 +2730,7 @@ pub struct RWADeleted {
 
 #[event]
 pub struct RecoveryInitiated {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub admin: Pubkey,
     pub recovery_address: Pubkey,
@@ -2476,6 +2740,7 @@ pub struct RecoveryInitiated {
 
 #[event]
 pub struct RecoveryExecuted {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub old_owner: Pubkey,
     pub new_owner: Pubkey,
@@ -2484,6 +2749,7 @@ pub struct RecoveryExecuted {
 
 #[event]
 pub struct TradingEnabled {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub ai_agent: Pubkey,
     pub human_share: u8,
@@ -2494,18 +2760,21 @@ pub struct TradingEnabled {
 
 #[event]
 pub struct TradingPaused {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct TradingResumed {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
 pub struct TradingContribution {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub contributor: Pubkey,
     pub amount: u64,
@@ -2516,6 +2785,7 @@ pub struct TradingContribution {
 
 #[event]
 pub struct TradingValueUpdated {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub old_value: u64,
     pub new_value: u64,
@@ -2525,6 +2795,7 @@ pub struct TradingValueUpdated {
 
 #[event]
 pub struct ProfitsDistributed {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub human_withdrawal: u64,
     pub ai_withdrawal: u64,
@@ -2534,6 +2805,7 @@ pub struct ProfitsDistributed {
 
 #[event]
 pub struct EmergencyWithdrawalInitiated {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub initiator: Pubkey,
     pub execute_after: i64,
@@ -2542,6 +2814,7 @@ pub struct EmergencyWithdrawalInitiated {
 
 #[event]
 pub struct EmergencyWithdrawalExecuted {
+    pub schema_version: u8,
     pub estate_id: Pubkey,
     pub human_withdrawal: u64,
     pub ai_withdrawal: u64,
@@ -2562,7 +2835,7 @@ pub enum EstateError {
     UnauthorizedAccess,
     #[msg("Estate is already claimable")]
     EstateClaimable,
-    #[msg("Too many beneficiaries. Maximum is 10")]
+    #[msg("Too many beneficiaries. Maximum is 100")]
     TooManyBeneficiaries,
     #[msg("Beneficiary shares must sum to 100%")]
     InvalidBeneficiaryShares,
@@ -2656,6 +2929,7 @@ pub enum EstateError {
     InsufficientApprovals,
     #[msg("Multisig already attached to this estate")]
     MultisigAlreadyAttached,
+    // Retired: admin timelocks now fail with defai_common::CommonError
     #[msg("No pending admin change")]
     NoPendingAdminChange,
     #[msg("Timelock not expired")]
@@ -2692,4 +2966,32 @@ pub enum EstateError {
     MaxUnlockAttemptsExceeded,
     #[msg("Invalid verification code")]
     InvalidVerificationCode,
+    #[msg("Daily risk metrics were reset less than a day ago")]
+    RiskResetNotDue,
+    #[msg("Estate has no daily risk metrics to reset")]
+    NoRiskToReset,
+    #[msg("Claim has not reached the stage for this asset")]
+    WrongClaimStage,
+    #[msg("Claim is already complete")]
+    ClaimAlreadyComplete,
+    #[msg("RWA already belongs to this beneficiary")]
+    RWAAlreadyClaimed,
+    #[msg("Only completed claims can be compressed")]
+    ClaimNotComplete,
+    #[msg("Program links are unchanged")]
+    ProgramLinksUnchanged,
+    #[msg("Beneficiary shares would exceed 100%")]
+    BeneficiarySharesExceeded,
+    #[msg("Beneficiary share must be greater than 0")]
+    ZeroBeneficiaryShare,
+    #[msg("Beneficiary is already on the estate")]
+    DuplicateBeneficiary,
+    #[msg("Batch must claim between 1 and 10 token mints")]
+    InvalidBatchSize,
+    #[msg("Batch accounts must be (mint, estate ATA, beneficiary ATA) per mint")]
+    InvalidBatchAccounts,
+    #[msg("NFT is not a programmable NFT - use claim_nft")]
+    NotProgrammableNft,
+    #[msg("Beneficiary pNFT account must be the beneficiary's ATA")]
+    InvalidPnftAccounts,
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use defai_common::{
+    append_receipt, emit_cpi_versioned, event_cpi_of, receipt_leaf, receipt_root, EventCpi, ReceiptKind,
+};
+
+use crate::{ClaimRecord, ClaimStage, Estate, EstateError, TokenClaim};
+
+// Compressed claim receipts (see defai_common::receipts)
+// A claim record is only written while its claim runs. Once it is Complete
+// the beneficiary can fold it into the estate program's receipt log and close
+// it, taking back its rent. The `claimed` flag on the estate's beneficiary
+// entry still blocks a second claim_inheritance. defai_swap's
+// claim_inherited_vesting reads the record itself, so heirs of bonus NFTs
+// should take over the vesting before compressing.
+
+#[account]
+pub struct ReceiptLog {
+    pub root: [u8; 32],
+    pub leaf_count: u64,
+    pub frontier: [[u8; 32]; 20],  // defai_common::RECEIPT_TREE_DEPTH
+    pub bump: u8,
+}
+
+impl ReceiptLog {
+    pub const LEN: usize = 32 + 8 + 32 * 20 + 1;
+
+    // Append a receipt and publish its bytes so the tree can be rebuilt
+    pub fn append(&mut self, kind: ReceiptKind, data: Vec<u8>, now: i64, events: &EventCpi) -> Result<u64> {
+        let index = self.leaf_count;
+        let leaf = receipt_leaf(kind, index, &data);
+        self.root = append_receipt(&mut self.frontier, &mut self.leaf_count, leaf)?;
+
+        emit_cpi_versioned!(events, ReceiptLogged {
+            kind: kind as u8,
+            index,
+            leaf,
+            root: self.root,
+            data,
+            timestamp: now,
+        });
+        Ok(index)
+    }
+}
+
+// Leaf data of a ReceiptKind::EstateClaim receipt: the closed ClaimRecord
+// plus when it was compressed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimReceipt {
+    pub estate: Pubkey,
+    pub beneficiary: Pubkey,
+    pub claim_time: i64,
+    pub sol_amount: u64,
+    pub share_percentage: u8,
+    pub tokens_claimed: Vec<TokenClaim>,
+    pub nfts_claimed: Vec<Pubkey>,
+    pub rwas_claimed: u32,
+    pub steps: u32,
+    pub completed_at: i64,
+    pub compressed_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReceiptLog<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ReceiptLog::LEN,
+        seeds = [b"receipt_log"],
+        bump
+    )]
+    pub receipt_log: Box<Account<'info, ReceiptLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CompressClaimRecord<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        seeds = [b"estate", estate.owner.as_ref(), estate.estate_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub estate: Box<Account<'info, Estate>>,
+
+    #[account(
+        mut,
+        close = beneficiary,
+        has_one = beneficiary @ EstateError::UnauthorizedBeneficiary,
+        has_one = estate @ EstateError::InvalidClaimRecord,
+        constraint = claim_record.stage == ClaimStage::Complete @ EstateError::ClaimNotComplete,
+    )]
+    pub claim_record: Box<Account<'info, ClaimRecord>>,
+
+    #[account(
+        mut,
+        seeds = [b"receipt_log"],
+        bump = receipt_log.bump
+    )]
+    pub receipt_log: Box<Account<'info, ReceiptLog>>,
+}
+
+// Anyone may create the log; it only holds the tree
+pub fn initialize_receipt_log(ctx: Context<InitializeReceiptLog>) -> Result<()> {
+    let log = &mut ctx.accounts.receipt_log;
+    log.frontier = [[0; 32]; 20];
+    log.leaf_count = 0;
+    log.root = receipt_root(&log.frontier, 0);
+    log.bump = ctx.bumps.receipt_log;
+    Ok(())
+}
+
+pub fn compress_claim_record(ctx: Context<CompressClaimRecord>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let record = &ctx.accounts.claim_record;
+    let receipt = ClaimReceipt {
+        estate: record.estate,
+        beneficiary: record.beneficiary,
+        claim_time: record.claim_time,
+        sol_amount: record.sol_amount,
+        share_percentage: record.share_percentage,
+        tokens_claimed: record.tokens_claimed.clone(),
+        nfts_claimed: record.nfts_claimed.clone(),
+        rwas_claimed: record.rwas_claimed,
+        steps: record.steps,
+        completed_at: record.last_step_at,
+        compressed_at: now,
+    };
+
+    let index = ctx.accounts.receipt_log.append(
+        ReceiptKind::EstateClaim,
+        receipt.try_to_vec()?,
+        now,
+        &event_cpi_of!(ctx),
+    )?;
+
+    msg!("Claim record of {} compressed as receipt {}", record.beneficiary, index);
+    Ok(())
+}
+
+#[event]
+pub struct ReceiptLogged {
+    pub schema_version: u8,
+    pub kind: u8,          // defai_common::ReceiptKind
+    pub index: u64,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],    // Root after this append
+    pub data: Vec<u8>,     // Borsh-encoded receipt, e.g. ClaimReceipt
+    pub timestamp: i64,
+}
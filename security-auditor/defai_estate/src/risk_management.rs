@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use defai_common::emit_versioned;
 
 // Risk Management Settings for Trading
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
@@ -295,6 +296,7 @@ pub struct UpdateStrategyMix<'info> {
 // Events
 #[event]
 pub struct RiskSettingsUpdated {
+    pub schema_version: u8,
     pub estate: Pubkey,
     pub max_drawdown_bps: u16,
     pub max_daily_loss_bps: u16,
@@ -304,6 +306,7 @@ pub struct RiskSettingsUpdated {
 
 #[event]
 pub struct StrategyMixUpdated {
+    pub schema_version: u8,
     pub estate: Pubkey,
     pub strategy_mix: StrategyMix,
     pub timestamp: i64,
@@ -311,6 +314,7 @@ pub struct StrategyMixUpdated {
 
 #[event]
 pub struct RiskLimitTriggered {
+    pub schema_version: u8,
     pub estate: Pubkey,
     pub limit_type: RiskLimitType,
     pub current_value_bps: u16,
@@ -336,7 +340,7 @@ pub fn update_risk_settings(
     let estate = &mut ctx.accounts.estate;
     estate.risk_settings = Some(settings.clone());
     
-    emit!(RiskSettingsUpdated {
+    emit_versioned!(RiskSettingsUpdated {
         estate: estate.key(),
         max_drawdown_bps: settings.max_drawdown_bps,
         max_daily_loss_bps: settings.max_daily_loss_bps,
@@ -371,7 +375,7 @@ pub fn update_strategy_mix(
     risk_settings.strategy_mix = strategy_mix.clone();
     estate.risk_settings = Some(risk_settings);
     
-    emit!(StrategyMixUpdated {
+    emit_versioned!(StrategyMixUpdated {
         estate: estate.key(),
         strategy_mix,
         timestamp: Clock::get()?.unix_timestamp,
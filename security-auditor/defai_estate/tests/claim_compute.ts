@@ -0,0 +1,218 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { DefaiEstate, IDL } from "../target/types/defai_estate";
+import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL, Transaction } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  MINT_SIZE,
+  ACCOUNT_SIZE,
+  MintLayout,
+  AccountLayout,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { start, ProgramTestContext } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { assert } from "chai";
+
+// Compute-unit ceilings for the claim paths, ATA creation included. A
+// beneficiary's transaction also carries compute-budget instructions, so a
+// change that pushes a path over its ceiling should be looked at before merge.
+const CLAIM_INHERITANCE_BUDGET = 30_000;
+const CLAIM_TOKEN_BUDGET = 60_000;
+const CLAIM_NFT_BUDGET = 60_000;
+const ADVANCE_CLAIM_BUDGET = 10_000;
+
+const PROGRAM_ID = new PublicKey("HvyyPrXbrhNEiGhttDUGMsYjKDPkYER2uFaLo7Bkei92");
+
+// Run with SBF_OUT_DIR pointing at the built defai_estate.so
+describe("Claim compute budget", () => {
+  let context: ProgramTestContext;
+  let program: Program<DefaiEstate>;
+
+  const owner = Keypair.generate();
+  const beneficiary = Keypair.generate();
+  const estateNumber = new anchor.BN(0);
+  let estate: PublicKey;
+  let claimRecord: PublicKey;
+
+  const setAccount = (address: PublicKey, programOwner: PublicKey, data: Buffer, lamports = LAMPORTS_PER_SOL) =>
+    context.setAccount(address, { lamports, data, owner: programOwner, executable: false });
+
+  const setMint = (mint: PublicKey, supply: bigint, decimals: number) => {
+    const data = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode({
+      mintAuthorityOption: 0,
+      mintAuthority: PublicKey.default,
+      supply,
+      decimals,
+      isInitialized: true,
+      freezeAuthorityOption: 0,
+      freezeAuthority: PublicKey.default,
+    }, data);
+    setAccount(mint, TOKEN_PROGRAM_ID, data);
+  };
+
+  const setTokenAccount = (mint: PublicKey, authority: PublicKey, amount: bigint) => {
+    const address = getAssociatedTokenAddressSync(mint, authority, true);
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode({
+      mint,
+      owner: authority,
+      amount,
+      delegateOption: 0,
+      delegate: PublicKey.default,
+      state: 1,
+      isNativeOption: 0,
+      isNative: BigInt(0),
+      delegatedAmount: BigInt(0),
+      closeAuthorityOption: 0,
+      closeAuthority: PublicKey.default,
+    }, data);
+    setAccount(address, TOKEN_PROGRAM_ID, data);
+    return address;
+  };
+
+  const unitsConsumed = async (tx: Transaction) => {
+    tx.recentBlockhash = context.lastBlockhash;
+    tx.feePayer = beneficiary.publicKey;
+    tx.sign(beneficiary);
+    const meta = await context.banksClient.processTransaction(tx);
+    return Number(meta.computeUnitsConsumed);
+  };
+
+  before(async () => {
+    context = await start([{ name: "defai_estate", programId: PROGRAM_ID }], []);
+    program = new Program<DefaiEstate>(IDL, PROGRAM_ID, new BankrunProvider(context));
+
+    [estate] = PublicKey.findProgramAddressSync(
+      [Buffer.from("estate"), owner.publicKey.toBuffer(), estateNumber.toArrayLike(Buffer, "le", 8)],
+      PROGRAM_ID
+    );
+    [claimRecord] = PublicKey.findProgramAddressSync(
+      [Buffer.from("claim"), estate.toBuffer(), beneficiary.publicKey.toBuffer()],
+      PROGRAM_ID
+    );
+
+    setAccount(beneficiary.publicKey, SystemProgram.programId, Buffer.alloc(0), 10 * LAMPORTS_PER_SOL);
+
+    // Inject an estate that has already passed its grace period, with the
+    // maximum number of beneficiaries so the estate deserializes at full size
+    const beneficiaries = [beneficiary.publicKey, ...Array.from({ length: 9 }, () => Keypair.generate().publicKey)]
+      .map((address) => ({
+        address,
+        emailHash: Array(32).fill(0),
+        sharePercentage: 10,
+        claimed: false,
+        notificationSent: true,
+      }));
+    const estateData = await program.coder.accounts.encode("Estate", {
+      estateId: estate,
+      owner: owner.publicKey,
+      ownerEmailHash: Array(32).fill(0),
+      lastActive: new anchor.BN(0),
+      inactivityPeriod: new anchor.BN(24 * 60 * 60),
+      gracePeriod: new anchor.BN(24 * 60 * 60),
+      beneficiaries,
+      totalBeneficiaries: beneficiaries.length,
+      creationTime: new anchor.BN(0),
+      estateValue: new anchor.BN(0),
+      isLocked: true,
+      isClaimable: true,
+      totalRwas: 0,
+      estateNumber,
+      totalClaims: 0,
+      tradingEnabled: false,
+      aiAgent: null,
+      tradingStrategy: null,
+      humanContribution: new anchor.BN(0),
+      aiContribution: new anchor.BN(0),
+      tradingValue: new anchor.BN(0),
+      tradingProfit: new anchor.BN(0),
+      highWaterMark: new anchor.BN(0),
+      humanShare: 0,
+      aiShare: 0,
+      stopLoss: null,
+      emergencyDelayHours: 0,
+      emergencyWithdrawalInitiated: false,
+      emergencyWithdrawalTime: new anchor.BN(0),
+      lastTradingUpdate: new anchor.BN(0),
+      multisig: null,
+      riskSettings: null,
+    });
+    setAccount(estate, PROGRAM_ID, Buffer.concat([estateData, Buffer.alloc(100)]), 5 * LAMPORTS_PER_SOL);
+  });
+
+  it("claim_inheritance stays within budget", async () => {
+    const tx = await program.methods
+      .claimInheritance(0)
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        estate,
+        claimRecord,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+
+    const units = await unitsConsumed(tx);
+    assert.isAtMost(units, CLAIM_INHERITANCE_BUDGET, `claim_inheritance used ${units} CU`);
+  });
+
+  it("claim_token with ATA creation stays within budget", async () => {
+    const tokenMint = Keypair.generate().publicKey;
+    setMint(tokenMint, BigInt(1_000_000_000), 6);
+    const estateTokenAccount = setTokenAccount(tokenMint, estate, BigInt(1_000_000_000));
+
+    const tx = await program.methods
+      .claimToken(0)
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        estate,
+        claimRecord,
+        tokenMint,
+        estateTokenAccount,
+        beneficiaryTokenAccount: getAssociatedTokenAddressSync(tokenMint, beneficiary.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+
+    const units = await unitsConsumed(tx);
+    assert.isAtMost(units, CLAIM_TOKEN_BUDGET, `claim_token used ${units} CU`);
+  });
+
+  it("advance_claim stays within budget", async () => {
+    const tx = await program.methods
+      .advanceClaim()
+      .accounts({ beneficiary: beneficiary.publicKey, estate, claimRecord })
+      .transaction();
+
+    const units = await unitsConsumed(tx);
+    assert.isAtMost(units, ADVANCE_CLAIM_BUDGET, `advance_claim used ${units} CU`);
+  });
+
+  it("claim_nft with ATA creation stays within budget", async () => {
+    const nftMint = Keypair.generate().publicKey;
+    setMint(nftMint, BigInt(1), 0);
+    const estateNftAccount = setTokenAccount(nftMint, estate, BigInt(1));
+
+    const tx = await program.methods
+      .claimNft(0)
+      .accounts({
+        beneficiary: beneficiary.publicKey,
+        estate,
+        claimRecord,
+        nftMint,
+        estateNftAccount,
+        beneficiaryNftAccount: getAssociatedTokenAddressSync(nftMint, beneficiary.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .transaction();
+
+    const units = await unitsConsumed(tx);
+    assert.isAtMost(units, CLAIM_NFT_BUDGET, `claim_nft used ${units} CU`);
+  });
+});
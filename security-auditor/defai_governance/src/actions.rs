@@ -0,0 +1,369 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+
+use defai_swap::cluster_config::ClusterSettings;
+use defai_swap::treasury::WithdrawalPolicy as SwapWithdrawalPolicy;
+use defai_app_factory::WithdrawalPolicy as AppFactoryWithdrawalPolicy;
+
+use crate::GovernanceError;
+
+// Per-program admin actions a proposal can carry. Each variant maps to one
+// admin instruction of the target program; the governance authority PDA signs
+// the CPI in place of the admin key. The target program performs all of its
+// usual validation, so governance only decides *whether* an action may run.
+//
+// Accounts are passed to `execute_proposal` as remaining accounts: the target
+// program first, then the instruction's accounts in declaration order, leaving
+// out the admin signer (the authority PDA is supplied by governance).
+// Instructions that emit CPI events take the target program's event authority
+// PDA last; the target program itself fills their `program` account.
+
+pub const MAX_SWAP_PRICES: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum SwapAction {
+    UpdatePrices { prices: Vec<u64> },                      // config
+    UpdateTreasury { new_treasury: Pubkey },                // config
+    Pause,                                                  // config
+    Unpause,                                                // config
+    ProposeAdminChange { new_admin: Pubkey },               // config, audit_log
+    AcceptAdminChange,                                      // config, audit_log
+    ProposeVrfModeChange { enabled: bool },                 // config
+    CancelVrfModeChange,                                    // config
+    ExecuteVrfModeChange,                                   // config, randomness_state
+    ProposeConversionRatio { numerator: u64, denominator: u64 }, // config
+    ExecuteConversionRatioChange,                           // config
+    ProposeClusterConfigChange { settings: ClusterSettings }, // config, cluster_config
+    CancelClusterConfigChange,                              // config, cluster_config
+    ExecuteClusterConfigChange,                             // config, cluster_config, whitelist (optional)
+    ProposeWithdrawalPolicy { policy: SwapWithdrawalPolicy }, // config, treasury_ledger
+    CancelWithdrawalPolicy,                                 // config, treasury_ledger
+    ExecuteWithdrawalPolicy,                                // config, treasury_ledger
+    WithdrawTreasury { amount: u64 },                       // config, treasury_ledger, treasury_vault, destination, mint, token_program_2022, event_authority
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum AppFactoryAction {
+    UpdatePlatformSettings { new_platform_fee_bps: Option<u16> }, // app_factory, new_treasury
+    ApplyPlatformSettings,                                  // app_factory
+    CancelPlatformSettingsChange,                           // app_factory
+    SetPaused { paused: bool },                             // app_factory
+    SetBurnBps { burn_bps: u16 },                           // app_factory
+    SetReportThreshold { report_threshold: u16 },           // app_factory
+    ReviewReport { app_id: u64, reporter: Pubkey, uphold: bool }, // app_factory, factory_stats, app_registration, app_report, reporter_account
+    TransferAuthority { new_authority: Pubkey },            // app_factory, audit_log
+    AcceptAuthority,                                        // app_factory, audit_log
+    CancelAuthorityTransfer,                                // app_factory, audit_log
+    ProposeWithdrawalPolicy { policy: AppFactoryWithdrawalPolicy }, // app_factory, treasury_ledger
+    CancelWithdrawalPolicy,                                 // app_factory, treasury_ledger
+    ExecuteWithdrawalPolicy,                                // app_factory, treasury_ledger
+    WithdrawTreasury { amount: u64 },                       // app_factory, treasury_ledger, treasury_vault, destination, mint, token_program, event_authority
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum EstateAction {
+    ProposeAdminChange { new_admin: Pubkey },               // multisig, audit_log
+    AcceptAdminChange,                                      // multisig, audit_log
+}
+
+impl SwapAction {
+    pub fn validate(&self) -> Result<()> {
+        if let SwapAction::UpdatePrices { prices } = self {
+            require!(prices.len() == MAX_SWAP_PRICES, GovernanceError::InvalidAction);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn execute<'info>(
+        &self,
+        authority: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        use defai_swap::cpi::{
+            self,
+            accounts::{
+                ChangeAdmin, ExecuteClusterConfigChange, ExecuteVrfModeChange, UpdateClusterConfig, UpdateConfig,
+                UpdateTreasuryLedger, WithdrawTreasury,
+            },
+        };
+
+        let iter = &mut accounts.iter();
+        let program = next_target_program(iter, defai_swap::ID)?;
+        let config = next_account_info(iter)?;
+        let update_config = || signed(
+            program,
+            signer,
+            UpdateConfig { admin: authority.clone(), config: config.clone() },
+        );
+        let update_cluster_config = |cluster_config: &AccountInfo<'info>| signed(
+            program,
+            signer,
+            UpdateClusterConfig { admin: authority.clone(), config: config.clone(), cluster_config: cluster_config.clone() },
+        );
+        let update_treasury_ledger = |treasury_ledger: &AccountInfo<'info>| signed(
+            program,
+            signer,
+            UpdateTreasuryLedger { admin: authority.clone(), config: config.clone(), treasury_ledger: treasury_ledger.clone() },
+        );
+
+        match self {
+            SwapAction::UpdatePrices { prices } => cpi::update_prices(update_config(), prices.clone()),
+            SwapAction::UpdateTreasury { new_treasury } => cpi::update_treasury(update_config(), *new_treasury),
+            SwapAction::Pause => cpi::pause(update_config()),
+            SwapAction::Unpause => cpi::unpause(update_config()),
+            SwapAction::ProposeAdminChange { new_admin } => {
+                let audit_log = next_account_info(iter)?;
+                cpi::propose_admin_change(
+                    signed(program, signer, ChangeAdmin {
+                        admin: authority.clone(),
+                        config: config.clone(),
+                        audit_log: audit_log.clone(),
+                    }),
+                    *new_admin,
+                )
+            }
+            SwapAction::AcceptAdminChange => {
+                let audit_log = next_account_info(iter)?;
+                cpi::accept_admin_change(signed(program, signer, ChangeAdmin {
+                    admin: authority.clone(),
+                    config: config.clone(),
+                    audit_log: audit_log.clone(),
+                }))
+            }
+            SwapAction::ProposeVrfModeChange { enabled } => cpi::propose_vrf_mode_change(update_config(), *enabled),
+            SwapAction::CancelVrfModeChange => cpi::cancel_vrf_mode_change(update_config()),
+            SwapAction::ExecuteVrfModeChange => {
+                let randomness_state = next_account_info(iter)?;
+                cpi::execute_vrf_mode_change(signed(program, signer, ExecuteVrfModeChange {
+                    admin: authority.clone(),
+                    config: config.clone(),
+                    randomness_state: randomness_state.clone(),
+                }))
+            }
+            SwapAction::ProposeConversionRatio { numerator, denominator } => {
+                cpi::propose_conversion_ratio(update_config(), *numerator, *denominator)
+            }
+            SwapAction::ExecuteConversionRatioChange => cpi::execute_conversion_ratio_change(update_config()),
+            SwapAction::ProposeClusterConfigChange { settings } => {
+                let cluster_config = next_account_info(iter)?;
+                cpi::propose_cluster_config_change(update_cluster_config(cluster_config), settings.clone())
+            }
+            SwapAction::CancelClusterConfigChange => {
+                let cluster_config = next_account_info(iter)?;
+                cpi::cancel_cluster_config_change(update_cluster_config(cluster_config))
+            }
+            SwapAction::ExecuteClusterConfigChange => {
+                let cluster_config = next_account_info(iter)?;
+                cpi::execute_cluster_config_change(signed(program, signer, ExecuteClusterConfigChange {
+                    admin: authority.clone(),
+                    config: config.clone(),
+                    cluster_config: cluster_config.clone(),
+                    whitelist: iter.next().cloned(),
+                }))
+            }
+            SwapAction::ProposeWithdrawalPolicy { policy } => {
+                let treasury_ledger = next_account_info(iter)?;
+                cpi::propose_withdrawal_policy(update_treasury_ledger(treasury_ledger), *policy)
+            }
+            SwapAction::CancelWithdrawalPolicy => {
+                let treasury_ledger = next_account_info(iter)?;
+                cpi::cancel_withdrawal_policy(update_treasury_ledger(treasury_ledger))
+            }
+            SwapAction::ExecuteWithdrawalPolicy => {
+                let treasury_ledger = next_account_info(iter)?;
+                cpi::execute_withdrawal_policy(update_treasury_ledger(treasury_ledger))
+            }
+            SwapAction::WithdrawTreasury { amount } => {
+                let treasury_ledger = next_account_info(iter)?;
+                let treasury_vault = next_account_info(iter)?;
+                let destination = next_account_info(iter)?;
+                let mint = next_account_info(iter)?;
+                let token_program_2022 = next_account_info(iter)?;
+                let event_authority = next_account_info(iter)?;
+                cpi::withdraw_treasury(
+                    signed(program, signer, WithdrawTreasury {
+                        admin: authority.clone(),
+                        config: config.clone(),
+                        treasury_ledger: treasury_ledger.clone(),
+                        treasury_vault: treasury_vault.clone(),
+                        destination: destination.clone(),
+                        mint: mint.clone(),
+                        token_program_2022: token_program_2022.clone(),
+                        event_authority: event_authority.clone(),
+                        program: program.clone(),
+                    }),
+                    *amount,
+                )
+            }
+        }
+    }
+}
+
+impl AppFactoryAction {
+    pub(crate) fn execute<'info>(
+        &self,
+        authority: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        use defai_app_factory::cpi::{self, accounts::*};
+
+        let iter = &mut accounts.iter();
+        let program = next_target_program(iter, defai_app_factory::ID)?;
+        let app_factory = next_account_info(iter)?.clone();
+        let authority = authority.clone();
+
+        match self {
+            AppFactoryAction::UpdatePlatformSettings { new_platform_fee_bps } => {
+                let new_treasury = next_account_info(iter)?.clone();
+                cpi::update_platform_settings(
+                    signed(program, signer, UpdatePlatformSettings { app_factory, authority, new_treasury }),
+                    *new_platform_fee_bps,
+                )
+            }
+            AppFactoryAction::ApplyPlatformSettings => {
+                cpi::apply_platform_settings(signed(program, signer, PendingPlatformSettings { app_factory, authority }))
+            }
+            AppFactoryAction::CancelPlatformSettingsChange => {
+                cpi::cancel_platform_settings_change(signed(program, signer, PendingPlatformSettings { app_factory, authority }))
+            }
+            AppFactoryAction::SetPaused { paused } => {
+                cpi::set_paused(signed(program, signer, SetPaused { app_factory, authority }), *paused)
+            }
+            AppFactoryAction::SetBurnBps { burn_bps } => {
+                cpi::set_burn_bps(signed(program, signer, SetBurnBps { app_factory, authority }), *burn_bps)
+            }
+            AppFactoryAction::SetReportThreshold { report_threshold } => {
+                cpi::set_report_threshold(signed(program, signer, SetReportThreshold { app_factory, authority }), *report_threshold)
+            }
+            AppFactoryAction::ReviewReport { app_id, reporter, uphold } => {
+                let factory_stats = next_account_info(iter)?.clone();
+                let app_registration = next_account_info(iter)?.clone();
+                let app_report = next_account_info(iter)?.clone();
+                let reporter_account = next_account_info(iter)?.clone();
+                cpi::review_report(
+                    signed(program, signer, ReviewReport {
+                        app_factory,
+                        factory_stats,
+                        app_registration,
+                        app_report,
+                        reporter_account,
+                        authority,
+                    }),
+                    *app_id,
+                    *reporter,
+                    *uphold,
+                )
+            }
+            AppFactoryAction::TransferAuthority { new_authority } => {
+                let audit_log = next_account_info(iter)?.clone();
+                cpi::transfer_authority(
+                    signed(program, signer, TransferAuthority { app_factory, authority, audit_log }),
+                    *new_authority,
+                )
+            }
+            AppFactoryAction::AcceptAuthority => {
+                let audit_log = next_account_info(iter)?.clone();
+                cpi::accept_authority(signed(program, signer, AcceptAuthority {
+                    app_factory,
+                    new_authority: authority,
+                    audit_log,
+                }))
+            }
+            AppFactoryAction::CancelAuthorityTransfer => {
+                let audit_log = next_account_info(iter)?.clone();
+                cpi::cancel_authority_transfer(
+                    signed(program, signer, CancelAuthorityTransfer { app_factory, authority, audit_log }),
+                )
+            }
+            AppFactoryAction::ProposeWithdrawalPolicy { policy } => {
+                let treasury_ledger = next_account_info(iter)?.clone();
+                cpi::propose_withdrawal_policy(
+                    signed(program, signer, UpdateTreasuryLedger { app_factory, treasury_ledger, authority }),
+                    *policy,
+                )
+            }
+            AppFactoryAction::CancelWithdrawalPolicy => {
+                let treasury_ledger = next_account_info(iter)?.clone();
+                cpi::cancel_withdrawal_policy(
+                    signed(program, signer, UpdateTreasuryLedger { app_factory, treasury_ledger, authority }),
+                )
+            }
+            AppFactoryAction::ExecuteWithdrawalPolicy => {
+                let treasury_ledger = next_account_info(iter)?.clone();
+                cpi::execute_withdrawal_policy(
+                    signed(program, signer, UpdateTreasuryLedger { app_factory, treasury_ledger, authority }),
+                )
+            }
+            AppFactoryAction::WithdrawTreasury { amount } => {
+                let treasury_ledger = next_account_info(iter)?.clone();
+                let treasury_vault = next_account_info(iter)?.clone();
+                let destination = next_account_info(iter)?.clone();
+                let mint = next_account_info(iter)?.clone();
+                let token_program = next_account_info(iter)?.clone();
+                let event_authority = next_account_info(iter)?.clone();
+                cpi::withdraw_treasury(
+                    signed(program, signer, WithdrawTreasury {
+                        app_factory,
+                        treasury_ledger,
+                        treasury_vault,
+                        destination,
+                        mint,
+                        authority,
+                        token_program,
+                        event_authority,
+                        program: program.clone(),
+                    }),
+                    *amount,
+                )
+            }
+        }
+    }
+}
+
+impl EstateAction {
+    pub(crate) fn execute<'info>(
+        &self,
+        authority: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        use defai_estate::cpi::{self, accounts::{AcceptAdminChange, ProposeAdminChange}};
+
+        let iter = &mut accounts.iter();
+        let program = next_target_program(iter, defai_estate::ID)?;
+        let multisig = next_account_info(iter)?.clone();
+        let audit_log = next_account_info(iter)?.clone();
+        let authority = authority.clone();
+
+        match self {
+            EstateAction::ProposeAdminChange { new_admin } => cpi::propose_admin_change(
+                signed(program, signer, ProposeAdminChange { signer: authority, multisig, audit_log }),
+                *new_admin,
+            ),
+            EstateAction::AcceptAdminChange => cpi::accept_admin_change(
+                signed(program, signer, AcceptAdminChange { signer: authority, multisig, audit_log }),
+            ),
+        }
+    }
+}
+
+// CPI context signed by the governance authority PDA
+fn signed<'a, 'b, 'c, 'info, T: ToAccountMetas + ToAccountInfos<'info>>(
+    program: &AccountInfo<'info>,
+    signer: &'a [&'b [&'c [u8]]],
+    accounts: T,
+) -> CpiContext<'a, 'b, 'c, 'info, T> {
+    CpiContext::new_with_signer(program.clone(), accounts, signer)
+}
+
+fn next_target_program<'a, 'info>(
+    iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+    program_id: Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    let program = next_account_info(iter)?;
+    require_keys_eq!(program.key(), program_id, GovernanceError::InvalidTargetProgram);
+    require!(program.executable, GovernanceError::InvalidTargetProgram);
+    Ok(program)
+}
@@ -0,0 +1,502 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_versioned, require_elapsed, SafeMath};
+
+pub mod actions;
+use actions::*;
+pub mod upgrade;
+use upgrade::*;
+
+declare_id!("95PcYsWP8ezUP65mMe9Pw8juqsVpqnphzXDvhWwJfsmY");
+
+// Unified governance for the DEFAI programs. A single member multisig owns the
+// governance authority PDA, and that PDA is installed as the admin of
+// defai_swap (`config.admin`), defai_app_factory (`app_factory.authority`) and
+// any defai_estate multisig (`multisig.admin`) through each program's existing
+// two-step admin handover. From then on admin instructions only run when a
+// proposal reaches the approval threshold and governance signs the CPI.
+//
+// Handover, per program:
+// 1. The current admin proposes the authority PDA as the new admin.
+// 2. A governance proposal carrying the program's `AcceptAdminChange` /
+//    `AcceptAuthority` action is executed (after the target's admin timelock,
+//    where it has one). defai_swap's accept is signed by the current admin.
+//
+// The authority PDA can also hold the programs' upgrade authority; upgrades
+// then go through timelocked `Upgrade` proposals (see `upgrade`).
+
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+pub const AUTHORITY_SEED: &[u8] = b"governance_authority";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const MAX_MEMBERS: usize = 10;
+// Borsh size of the largest action (UpdateMembers with MAX_MEMBERS members)
+pub const MAX_ACTION_LEN: usize = 1 + 4 + MAX_MEMBERS * 32 + 1;
+
+#[program]
+pub mod defai_governance {
+    use super::*;
+
+    pub fn initialize_governance(
+        ctx: Context<InitializeGovernance>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_members(&members, threshold)?;
+
+        let governance = &mut ctx.accounts.governance;
+        governance.members = members.clone();
+        governance.threshold = threshold;
+        governance.proposal_count = 0;
+        governance.members_version = 0;
+        governance.authority_bump = ctx.bumps.authority;
+        governance.bump = ctx.bumps.governance;
+
+        emit_versioned!(GovernanceInitialized {
+            authority: ctx.accounts.authority.key(),
+            members,
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Governance initialized; admin authority is {}", ctx.accounts.authority.key());
+        Ok(())
+    }
+
+    // Propose an action; the proposer's approval is counted
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: GovernanceAction) -> Result<()> {
+        let governance = &mut ctx.accounts.governance;
+        let proposer = ctx.accounts.proposer.key();
+        require!(governance.is_member(&proposer), GovernanceError::NotMember);
+        action.validate()?;
+        if let GovernanceAction::Upgrade(upgrade) = &action {
+            upgrade.validate_buffer(ctx.remaining_accounts, &governance.authority()?)?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = governance.proposal_count;
+        proposal.proposer = proposer;
+        proposal.action = action.clone();
+        proposal.approvals = vec![proposer];
+        proposal.members_version = governance.members_version;
+        proposal.created_at = now;
+        proposal.executed = false;
+        proposal.cancelled = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        governance.proposal_count = governance.proposal_count.safe_add(1)?;
+
+        if let GovernanceAction::Upgrade(upgrade) = &action {
+            emit_versioned!(UpgradeProposed {
+                id: proposal.id,
+                program: upgrade.program,
+                buffer: upgrade.buffer,
+                executable_after: upgrade_executable_after(now)?,
+            });
+        }
+        emit_versioned!(ProposalCreated {
+            id: proposal.id,
+            proposer,
+            action,
+            timestamp: now,
+        });
+
+        msg!("Proposal {} created by {}", proposal.id, proposer);
+        Ok(())
+    }
+
+    pub fn approve_proposal(ctx: Context<ApproveProposal>, id: u64) -> Result<()> {
+        let governance = &ctx.accounts.governance;
+        let member = ctx.accounts.member.key();
+        require!(governance.is_member(&member), GovernanceError::NotMember);
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.require_open(governance)?;
+        require!(!proposal.approvals.contains(&member), GovernanceError::AlreadyApproved);
+        proposal.approvals.push(member);
+
+        emit_versioned!(ProposalApproved {
+            id,
+            member,
+            approvals: proposal.approvals.len() as u8,
+            threshold: governance.threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposal {} approved by {} ({}/{})", id, member, proposal.approvals.len(), governance.threshold);
+        Ok(())
+    }
+
+    // Withdraw a proposal that hasn't executed (proposer only)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>, id: u64) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.executed, GovernanceError::AlreadyExecuted);
+        require!(!proposal.cancelled, GovernanceError::ProposalCancelled);
+        proposal.cancelled = true;
+
+        emit_versioned!(ProposalCancelled {
+            id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposal {} cancelled", id);
+        Ok(())
+    }
+
+    // Run an approved proposal. Target program accounts go in remaining
+    // accounts (see `actions`); any member may trigger execution.
+    pub fn execute_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteProposal<'info>>,
+        id: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.governance.is_member(&ctx.accounts.executor.key()),
+            GovernanceError::NotMember
+        );
+        {
+            let governance = &ctx.accounts.governance;
+            let proposal = &ctx.accounts.proposal;
+            proposal.require_open(governance)?;
+            require!(
+                proposal.approvals.len() >= governance.threshold as usize,
+                GovernanceError::InsufficientApprovals
+            );
+        }
+        ctx.accounts.proposal.executed = true;
+
+        let action = ctx.accounts.proposal.action.clone();
+        let authority_seeds = &[AUTHORITY_SEED, &[ctx.accounts.governance.authority_bump]];
+        let signer = &[&authority_seeds[..]];
+        let authority = ctx.accounts.authority.to_account_info();
+
+        match &action {
+            GovernanceAction::Swap(swap_action) => {
+                swap_action.execute(&authority, ctx.remaining_accounts, signer)?
+            }
+            GovernanceAction::AppFactory(app_factory_action) => {
+                app_factory_action.execute(&authority, ctx.remaining_accounts, signer)?
+            }
+            GovernanceAction::Estate(estate_action) => {
+                estate_action.execute(&authority, ctx.remaining_accounts, signer)?
+            }
+            GovernanceAction::Upgrade(upgrade) => {
+                let executable_after = upgrade_executable_after(ctx.accounts.proposal.created_at)?;
+                require_elapsed(Clock::get()?.unix_timestamp, executable_after)?;
+                upgrade.execute(&authority, ctx.remaining_accounts, signer)?
+            }
+            GovernanceAction::UpdateMembers { members, threshold } => {
+                let governance = &mut ctx.accounts.governance;
+                governance.members = members.clone();
+                governance.threshold = *threshold;
+                // Approvals gathered under the old membership no longer count
+                governance.members_version = governance.members_version.safe_add(1)?;
+            }
+        }
+
+        emit_versioned!(ProposalExecuted {
+            id,
+            executor: ctx.accounts.executor.key(),
+            action,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposal {} executed", id);
+        Ok(())
+    }
+}
+
+fn validate_members(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty() && members.len() <= MAX_MEMBERS,
+        GovernanceError::InvalidMemberCount
+    );
+    let mut unique = std::collections::HashSet::new();
+    require!(members.iter().all(|m| unique.insert(*m)), GovernanceError::DuplicateMember);
+    require!(
+        threshold > 0 && threshold as usize <= members.len(),
+        GovernanceError::InvalidThreshold
+    );
+    Ok(())
+}
+
+// ===== Accounts =====
+
+#[account]
+pub struct Governance {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub proposal_count: u64,
+    pub members_version: u64,           // Bumped on every membership change
+    pub authority_bump: u8,
+    pub bump: u8,
+}
+
+impl Governance {
+    pub const LEN: usize = 8 + (4 + MAX_MEMBERS * 32) + 1 + 8 + 8 + 1 + 1;
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+
+    // Address of the signing PDA, from the stored bump
+    pub fn authority(&self) -> Result<Pubkey> {
+        Pubkey::create_program_address(&[AUTHORITY_SEED, &[self.authority_bump]], &crate::ID)
+            .map_err(|_| error!(GovernanceError::InvalidAction))
+    }
+}
+
+#[account]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub approvals: Vec<Pubkey>,
+    pub members_version: u64,           // Governance membership it was proposed under
+    pub created_at: i64,
+    pub executed: bool,
+    pub cancelled: bool,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + 8 + 32 + MAX_ACTION_LEN + (4 + MAX_MEMBERS * 32) + 8 + 8 + 1 + 1 + 1;
+
+    fn require_open(&self, governance: &Governance) -> Result<()> {
+        require!(!self.executed, GovernanceError::AlreadyExecuted);
+        require!(!self.cancelled, GovernanceError::ProposalCancelled);
+        require!(
+            self.members_version == governance.members_version,
+            GovernanceError::StaleProposal
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum GovernanceAction {
+    Swap(SwapAction),
+    AppFactory(AppFactoryAction),
+    Estate(EstateAction),
+    UpdateMembers { members: Vec<Pubkey>, threshold: u8 },
+    Upgrade(UpgradeAction),
+}
+
+impl GovernanceAction {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            GovernanceAction::Swap(action) => action.validate(),
+            GovernanceAction::UpdateMembers { members, threshold } => validate_members(members, *threshold),
+            GovernanceAction::Upgrade(action) => action.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ===== Contexts =====
+
+// Only the program's upgrade authority can initialize, so the first caller
+// after deployment can't install their own members
+#[derive(Accounts)]
+pub struct InitializeGovernance<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Governance::LEN,
+        seeds = [GOVERNANCE_SEED],
+        bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    /// CHECK: Signing PDA installed as admin of the governed programs; holds no data
+    #[account(seeds = [AUTHORITY_SEED], bump)]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ GovernanceError::NotUpgradeAuthority
+    )]
+    pub program: Program<'info, program::DefaiGovernance>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ GovernanceError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [PROPOSAL_SEED, &governance.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ApproveProposal<'info> {
+    #[account(
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub member: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CancelProposal<'info> {
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &id.to_le_bytes()],
+        bump = proposal.bump,
+        has_one = proposer @ GovernanceError::NotProposer
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [GOVERNANCE_SEED],
+        bump = governance.bump
+    )]
+    pub governance: Account<'info, Governance>,
+
+    #[account(
+        mut,
+        seeds = [PROPOSAL_SEED, &id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Governance signing PDA; signs the CPI as the target's admin
+    #[account(
+        mut,
+        seeds = [AUTHORITY_SEED],
+        bump = governance.authority_bump
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    pub executor: Signer<'info>,
+}
+
+// ===== Events =====
+
+#[event]
+pub struct GovernanceInitialized {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub schema_version: u8,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub timestamp: i64,
+}
+
+// Published alongside ProposalCreated so watchers can track pending upgrades
+#[event]
+pub struct UpgradeProposed {
+    pub schema_version: u8,
+    pub id: u64,
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+    pub executable_after: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub schema_version: u8,
+    pub id: u64,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCancelled {
+    pub schema_version: u8,
+    pub id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub schema_version: u8,
+    pub id: u64,
+    pub executor: Pubkey,
+    pub action: GovernanceAction,
+    pub timestamp: i64,
+}
+
+// ===== Errors =====
+
+#[error_code]
+pub enum GovernanceError {
+    #[msg("Member count must be between 1 and 10")]
+    InvalidMemberCount,
+    #[msg("Duplicate member")]
+    DuplicateMember,
+    #[msg("Threshold must be between 1 and the member count")]
+    InvalidThreshold,
+    #[msg("Signer is not a governance member")]
+    NotMember,
+    #[msg("Only the proposer can cancel a proposal")]
+    NotProposer,
+    #[msg("Member already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Not enough approvals to execute")]
+    InsufficientApprovals,
+    #[msg("Proposal already executed")]
+    AlreadyExecuted,
+    #[msg("Proposal was cancelled")]
+    ProposalCancelled,
+    #[msg("Governance membership changed since this proposal was created")]
+    StaleProposal,
+    #[msg("Invalid action parameters")]
+    InvalidAction,
+    #[msg("Target program account missing or incorrect")]
+    InvalidTargetProgram,
+    #[msg("Upgrade buffer missing, or not held by the governance authority")]
+    InvalidUpgradeBuffer,
+    #[msg("Only the program's upgrade authority can initialize governance")]
+    NotUpgradeAuthority,
+}
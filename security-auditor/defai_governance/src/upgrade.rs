@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    account_info::next_account_info,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    program::invoke_signed,
+};
+use defai_common::schedule;
+
+use crate::GovernanceError;
+
+// Program upgrades
+// The governance authority PDA holds the upgrade authority of the governed
+// programs (handed over with `solana program set-upgrade-authority`). An
+// `Upgrade` proposal needs the usual approval threshold and, on top of that,
+// must stay published for UPGRADE_TIMELOCK_DURATION after creation before it
+// can execute, so users can review the new code or exit first.
+//
+// The buffer's authority must already be the authority PDA when the upgrade
+// is proposed. Only that authority can write to, close or re-assign a buffer,
+// and governance does none of those, so the bytes reviewed during the
+// timelock are the bytes that get deployed.
+
+pub const UPGRADE_TIMELOCK_DURATION: i64 = 7 * 24 * 60 * 60; // 7 days
+
+// Programs governance may upgrade
+pub const UPGRADEABLE_PROGRAMS: [Pubkey; 3] = [defai_swap::ID, defai_estate::ID, defai_app_factory::ID];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct UpgradeAction {
+    pub program: Pubkey,
+    pub buffer: Pubkey,
+}
+
+impl UpgradeAction {
+    pub fn validate(&self) -> Result<()> {
+        require!(UPGRADEABLE_PROGRAMS.contains(&self.program), GovernanceError::InvalidTargetProgram);
+        Ok(())
+    }
+
+    // Accounts when proposing: buffer
+    pub(crate) fn validate_buffer(&self, accounts: &[AccountInfo], authority: &Pubkey) -> Result<()> {
+        let buffer = next_account_info(&mut accounts.iter())?;
+        require_keys_eq!(buffer.key(), self.buffer, GovernanceError::InvalidUpgradeBuffer);
+        require_keys_eq!(*buffer.owner, bpf_loader_upgradeable::ID, GovernanceError::InvalidUpgradeBuffer);
+
+        let state = UpgradeableLoaderState::try_deserialize(&mut &buffer.try_borrow_data()?[..])?;
+        require!(
+            state == UpgradeableLoaderState::Buffer { authority_address: Some(*authority) },
+            GovernanceError::InvalidUpgradeBuffer
+        );
+        Ok(())
+    }
+
+    // Accounts when executing: program, program_data, buffer, spill, rent,
+    // clock, bpf_loader_upgradeable. The loader checks that program_data
+    // belongs to the program and that both it and the buffer list the
+    // authority PDA; the buffer's lamports go to spill.
+    pub(crate) fn execute<'info>(
+        &self,
+        authority: &AccountInfo<'info>,
+        accounts: &[AccountInfo<'info>],
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let iter = &mut accounts.iter();
+        let program = next_account_info(iter)?;
+        let program_data = next_account_info(iter)?;
+        let buffer = next_account_info(iter)?;
+        let spill = next_account_info(iter)?;
+        let rent = next_account_info(iter)?;
+        let clock = next_account_info(iter)?;
+        let loader = next_account_info(iter)?;
+        require_keys_eq!(program.key(), self.program, GovernanceError::InvalidTargetProgram);
+        require_keys_eq!(buffer.key(), self.buffer, GovernanceError::InvalidUpgradeBuffer);
+        require_keys_eq!(loader.key(), bpf_loader_upgradeable::ID, GovernanceError::InvalidTargetProgram);
+
+        let ix = bpf_loader_upgradeable::upgrade(&self.program, &self.buffer, authority.key, spill.key);
+        invoke_signed(
+            &ix,
+            &[
+                program_data.clone(),
+                program.clone(),
+                buffer.clone(),
+                spill.clone(),
+                rent.clone(),
+                clock.clone(),
+                authority.clone(),
+                loader.clone(),
+            ],
+            signer,
+        )?;
+        Ok(())
+    }
+}
+
+// Earliest time an upgrade proposed at `created_at` may execute
+pub fn upgrade_executable_after(created_at: i64) -> Result<i64> {
+    schedule(created_at, UPGRADE_TIMELOCK_DURATION)
+}
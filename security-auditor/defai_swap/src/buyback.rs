@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{self as token22, Token2022},
+    token_interface::{Mint, TokenAccount as TokenAccount2022, Burn},
+};
+
+use defai_common::{bps_of, emit_cpi_versioned, emit_versioned, event_cpi_of, schedule, require_elapsed, BPS_DENOMINATOR};
+
+use crate::{Config, Escrow};
+
+// Buyback-and-Burn
+// Swap tax is collected in DEFAI, so the buyback leg needs no DEX swap: tax
+// routed into the buyback vault is burned directly. The swap instructions
+// send `tax_share_bps` of every tax payment to the vault and the rest to the
+// treasury. Every burn is proposed first and can only execute after the
+// timelock, and is capped per execution.
+
+// Timelock between proposing and executing a burn
+const BUYBACK_TIMELOCK_DURATION: i64 = 48 * 60 * 60; // 48 hours
+// Maximum amount that can be burned in a single execution
+const MAX_BUYBACK_BURN_AMOUNT: u64 = 1_000_000 * 1_000_000; // 1M DEFAI (6 decimals)
+
+#[account]
+pub struct BuybackState {
+    pub bump: u8,
+    pub vault: Pubkey,
+    pub pending_amount: u64,
+    pub execute_after: i64,
+    pub total_burned: u64,
+    pub burn_count: u32,
+    pub tax_share_bps: u16,     // Share of swap tax routed into the vault
+}
+
+impl BuybackState {
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 4 + 2;
+}
+
+// Part of `tax_amount` that goes to the buyback vault. Before the buyback is
+// initialized the state PDA is empty and all tax goes to the treasury.
+pub(crate) fn buyback_tax_share(
+    buyback_state: &AccountInfo,
+    buyback_vault: &AccountInfo,
+    tax_amount: u64,
+) -> Result<u64> {
+    if buyback_state.data_is_empty() {
+        return Ok(0);
+    }
+    require_keys_eq!(*buyback_state.owner, crate::ID, BuybackError::InvalidBuybackState);
+    let state = BuybackState::try_deserialize(&mut &buyback_state.try_borrow_data()?[..])?;
+    require_keys_eq!(buyback_vault.key(), state.vault, BuybackError::InvalidVault);
+    bps_of(tax_amount, state.tax_share_bps as u64)
+}
+
+#[derive(Accounts)]
+pub struct InitializeBuyback<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ BuybackError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + BuybackState::LEN,
+        seeds = [b"buyback_state"],
+        bump
+    )]
+    pub buyback_state: Account<'info, BuybackState>,
+    #[account(
+        constraint = defai_mint.key() == config.new_mint @ BuybackError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    // Sub-treasury that receives the share of swap tax earmarked for burning
+    #[account(
+        init,
+        payer = admin,
+        token::mint = defai_mint,
+        token::authority = escrow,
+        token::token_program = token_program_2022,
+        seeds = [b"buyback_vault"],
+        bump
+    )]
+    pub buyback_vault: InterfaceAccount<'info, TokenAccount2022>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeBuyback<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ BuybackError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"buyback_state"],
+        bump = buyback_state.bump
+    )]
+    pub buyback_state: Account<'info, BuybackState>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteBuybackAndBurn<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ BuybackError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"buyback_state"],
+        bump = buyback_state.bump
+    )]
+    pub buyback_state: Account<'info, BuybackState>,
+    #[account(
+        mut,
+        address = buyback_state.vault @ BuybackError::InvalidVault
+    )]
+    pub buyback_vault: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        constraint = defai_mint.key() == config.new_mint @ BuybackError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn initialize_buyback(ctx: Context<InitializeBuyback>, tax_share_bps: u16) -> Result<()> {
+    require!(tax_share_bps as u64 <= BPS_DENOMINATOR, BuybackError::InvalidTaxShare);
+
+    let state = &mut ctx.accounts.buyback_state;
+    state.bump = ctx.bumps.buyback_state;
+    state.vault = ctx.accounts.buyback_vault.key();
+    state.pending_amount = 0;
+    state.execute_after = 0;
+    state.total_burned = 0;
+    state.burn_count = 0;
+    state.tax_share_bps = tax_share_bps;
+
+    msg!("Buyback vault initialized, receiving {} bps of swap tax", tax_share_bps);
+    Ok(())
+}
+
+pub fn propose_buyback_and_burn(ctx: Context<ProposeBuyback>, amount: u64) -> Result<()> {
+    require!(amount > 0, BuybackError::InvalidAmount);
+    require!(amount <= MAX_BUYBACK_BURN_AMOUNT, BuybackError::ExceedsBurnCap);
+
+    let state = &mut ctx.accounts.buyback_state;
+    let now = Clock::get()?.unix_timestamp;
+    state.pending_amount = amount;
+    state.execute_after = schedule(now, BUYBACK_TIMELOCK_DURATION)?;
+
+    emit_versioned!(BuybackProposed {
+        admin: ctx.accounts.admin.key(),
+        amount,
+        execute_after: state.execute_after,
+    });
+    Ok(())
+}
+
+// Changes the share of swap tax routed into the buyback vault from the next
+// swap on
+pub fn set_buyback_tax_share(ctx: Context<ProposeBuyback>, tax_share_bps: u16) -> Result<()> {
+    require!(tax_share_bps as u64 <= BPS_DENOMINATOR, BuybackError::InvalidTaxShare);
+
+    let state = &mut ctx.accounts.buyback_state;
+    let old_tax_share_bps = state.tax_share_bps;
+    state.tax_share_bps = tax_share_bps;
+
+    emit_versioned!(BuybackTaxShareUpdated {
+        admin: ctx.accounts.admin.key(),
+        old_tax_share_bps,
+        new_tax_share_bps: tax_share_bps,
+    });
+    Ok(())
+}
+
+pub fn cancel_buyback_and_burn(ctx: Context<ProposeBuyback>) -> Result<()> {
+    let state = &mut ctx.accounts.buyback_state;
+    require!(state.pending_amount > 0, BuybackError::NoPendingBuyback);
+    state.pending_amount = 0;
+    state.execute_after = 0;
+
+    msg!("Pending buyback cancelled");
+    Ok(())
+}
+
+pub fn buyback_and_burn(ctx: Context<ExecuteBuybackAndBurn>) -> Result<()> {
+    let state = &mut ctx.accounts.buyback_state;
+    let now = Clock::get()?.unix_timestamp;
+    require!(state.pending_amount > 0, BuybackError::NoPendingBuyback);
+    require_elapsed(now, state.execute_after)?;
+
+    let amount = state.pending_amount;
+    require!(
+        ctx.accounts.buyback_vault.amount >= amount,
+        BuybackError::InsufficientVaultBalance
+    );
+
+    let escrow_seeds = &[b"escrow" as &[u8], &[ctx.accounts.escrow.bump][..]];
+    let signer_seeds = &[&escrow_seeds[..]];
+    let burn_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program_2022.to_account_info(),
+        Burn {
+            mint: ctx.accounts.defai_mint.to_account_info(),
+            from: ctx.accounts.buyback_vault.to_account_info(),
+            authority: ctx.accounts.escrow.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token22::burn(burn_ctx, amount)?;
+
+    ctx.accounts.defai_mint.reload()?;
+
+    state.pending_amount = 0;
+    state.execute_after = 0;
+    state.total_burned = state.total_burned
+        .checked_add(amount)
+        .ok_or(BuybackError::MathOverflow)?;
+    state.burn_count += 1;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), BuybackBurned {
+        admin: ctx.accounts.admin.key(),
+        amount,
+        total_burned: state.total_burned,
+        supply_after: ctx.accounts.defai_mint.supply,
+        burn_index: state.burn_count,
+        timestamp: now,
+    });
+
+    msg!("Burned {} DEFAI from buyback vault", amount);
+    Ok(())
+}
+
+#[event]
+pub struct BuybackProposed {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct BuybackTaxShareUpdated {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub old_tax_share_bps: u16,
+    pub new_tax_share_bps: u16,
+}
+
+#[event]
+pub struct BuybackBurned {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub total_burned: u64,
+    pub supply_after: u64,
+    pub burn_index: u32,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum BuybackError {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Invalid mint")]
+    InvalidMint,
+    #[msg("Buyback vault does not match state")]
+    InvalidVault,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Amount exceeds per-execution burn cap")]
+    ExceedsBurnCap,
+    #[msg("No pending buyback")]
+    NoPendingBuyback,
+    #[msg("Timelock not expired")]
+    TimelockNotExpired,                 // Retired: see CommonError
+    #[msg("Buyback vault balance too low")]
+    InsufficientVaultBalance,
+    #[msg("Overflow in maths operation")]
+    MathOverflow,
+    #[msg("Tax share exceeds 10000 bps")]
+    InvalidTaxShare,
+    #[msg("Buyback state is not owned by this program")]
+    InvalidBuybackState,
+}
@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_versioned, Timelock, ADMIN_TIMELOCK_DURATION};
+
+use crate::switchboard::SWITCHBOARD_ON_DEMAND_MAINNET_PID;
+use crate::{Config, ErrorCode, Whitelist};
+
+// Cluster configuration
+// Values that differ between localnet, devnet and mainnet live in the
+// `cluster_config` PDA instead of the binary, so one build deploys everywhere.
+// They are set once at initialization and afterwards only change through the
+// admin timelock, like the other config changes.
+
+// OG NFT whitelist merkle root used when none is given at initialization
+pub const DEFAULT_WHITELIST_ROOT: [u8; 32] = [75, 45, 118, 95, 221, 195, 106, 5, 187, 186, 56, 74, 112, 138, 19, 108, 59, 243, 44, 140, 228, 10, 199, 125, 41, 242, 223, 102, 191, 115, 73, 142];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ClusterSettings {
+    pub randomness_program: Pubkey, // Switchboard On-Demand program randomness accounts must belong to
+    pub whitelist_root: [u8; 32],
+}
+
+impl ClusterSettings {
+    pub const LEN: usize = 32 + 32;
+
+    // Localnet defaults: the mainnet Switchboard program, which Anchor.toml
+    // clones into the test validator, and the OG whitelist root
+    pub fn localnet() -> Self {
+        Self {
+            randomness_program: SWITCHBOARD_ON_DEMAND_MAINNET_PID,
+            whitelist_root: DEFAULT_WHITELIST_ROOT,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require_keys_neq!(self.randomness_program, Pubkey::default(), ErrorCode::InvalidInput);
+        require!(self.whitelist_root != [0u8; 32], ErrorCode::InvalidInput);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct ClusterConfig {
+    pub settings: ClusterSettings,
+    pub pending_settings: Option<ClusterSettings>,
+    pub settings_change_timestamp: i64,
+    pub bump: u8,
+}
+
+impl ClusterConfig {
+    pub const LEN: usize = ClusterSettings::LEN + (1 + ClusterSettings::LEN) + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializeClusterConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ClusterConfig::LEN,
+        seeds = [b"cluster_config"],
+        bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateClusterConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"cluster_config"],
+        bump = cluster_config.bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteClusterConfigChange<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"cluster_config"],
+        bump = cluster_config.bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
+    // Kept in sync with the new root when it has been initialized
+    #[account(
+        mut,
+        seeds = [b"whitelist"],
+        bump
+    )]
+    pub whitelist: Option<Account<'info, Whitelist>>,
+}
+
+// `None` takes the localnet defaults
+pub fn initialize_cluster_config(ctx: Context<InitializeClusterConfig>, settings: Option<ClusterSettings>) -> Result<()> {
+    let settings = settings.unwrap_or_else(ClusterSettings::localnet);
+    settings.validate()?;
+
+    let cluster_config: &mut ClusterConfig = &mut ctx.accounts.cluster_config;
+    cluster_config.settings = settings.clone();
+    cluster_config.pending_settings = None;
+    cluster_config.settings_change_timestamp = 0;
+    cluster_config.bump = ctx.bumps.cluster_config;
+
+    emit_versioned!(ClusterConfigChanged {
+        admin: ctx.accounts.admin.key(),
+        randomness_program: settings.randomness_program,
+        whitelist_root: settings.whitelist_root,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn propose_cluster_config_change(ctx: Context<UpdateClusterConfig>, settings: ClusterSettings) -> Result<()> {
+    settings.validate()?;
+    require!(ctx.accounts.cluster_config.settings != settings, ErrorCode::InvalidInput);
+
+    let cluster_config: &mut ClusterConfig = &mut ctx.accounts.cluster_config;
+    let execute_after = Timelock::new(&mut cluster_config.pending_settings, &mut cluster_config.settings_change_timestamp)
+        .propose(settings.clone(), Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+
+    emit_versioned!(ClusterConfigChangeProposed {
+        admin: ctx.accounts.admin.key(),
+        randomness_program: settings.randomness_program,
+        whitelist_root: settings.whitelist_root,
+        execute_after,
+    });
+    Ok(())
+}
+
+pub fn cancel_cluster_config_change(ctx: Context<UpdateClusterConfig>) -> Result<()> {
+    let cluster_config: &mut ClusterConfig = &mut ctx.accounts.cluster_config;
+    Timelock::new(&mut cluster_config.pending_settings, &mut cluster_config.settings_change_timestamp).cancel()?;
+
+    emit_versioned!(ClusterConfigChangeCancelled {
+        admin: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn execute_cluster_config_change(ctx: Context<ExecuteClusterConfigChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let cluster_config: &mut ClusterConfig = &mut ctx.accounts.cluster_config;
+    let settings = Timelock::new(&mut cluster_config.pending_settings, &mut cluster_config.settings_change_timestamp)
+        .execute(now)?;
+    cluster_config.settings = settings.clone();
+
+    if let Some(whitelist) = ctx.accounts.whitelist.as_mut() {
+        whitelist.root = settings.whitelist_root;
+    }
+
+    emit_versioned!(ClusterConfigChanged {
+        admin: ctx.accounts.admin.key(),
+        randomness_program: settings.randomness_program,
+        whitelist_root: settings.whitelist_root,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct ClusterConfigChangeProposed {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub randomness_program: Pubkey,
+    pub whitelist_root: [u8; 32],
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct ClusterConfigChangeCancelled {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClusterConfigChanged {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub randomness_program: Pubkey,
+    pub whitelist_root: [u8; 32],
+    pub timestamp: i64,
+}
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{self as token22, Token2022},
+    token_interface::{Mint as Mint2022, TokenAccount as TokenAccount2022, TransferChecked},
+};
+use defai_common::{emit_cpi_versioned, event_cpi_of, require_covers};
+
+use crate::math::vested_after_cliff;
+use crate::{Config, ErrorCode, Escrow, ProgramRegistry, VestingStateV6};
+
+// Inherited vesting positions
+// A bonus NFT's vesting follows the NFT, but the deceased holder may have
+// routed claims to a payout wallet nobody controls any more. A beneficiary
+// who received the NFT through a defai_estate claim proves it with their
+// estate `ClaimRecord`, which clears the payout wallet and settles whatever
+// has vested to them. Vesting then continues for the heir as the holder.
+//
+// defai_estate can't be a dependency (it links defai_app_factory, which links
+// this crate), so the ClaimRecord layout is vendored below and only read
+// after its owner and discriminator check out. The owner must be the estate
+// program linked in the program registry.
+
+// `defai_estate::ClaimRecord`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct EstateClaimRecord {
+    pub estate: Pubkey,
+    pub beneficiary: Pubkey,
+    pub claim_time: i64,
+    pub sol_amount: u64,
+    pub share_percentage: u8,
+    pub tokens_claimed: Vec<EstateTokenClaim>,
+    pub nfts_claimed: Vec<Pubkey>,
+}
+
+// `defai_estate::TokenClaim`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct EstateTokenClaim {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl EstateClaimRecord {
+    pub const DISCRIMINATOR: [u8; 8] = [57, 229, 0, 9, 65, 62, 96, 7];
+
+    pub fn parse(account: &AccountInfo, estate_program: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, *estate_program, InheritanceError::InvalidClaimRecordOwner);
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == Self::DISCRIMINATOR,
+            InheritanceError::InvalidClaimRecord
+        );
+        // Only the leading fields are read; the claim checkpoint and any free slots follow
+        Self::deserialize(&mut &data[8..]).map_err(|_| error!(InheritanceError::InvalidClaimRecord))
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimInheritedVesting<'info> {
+    #[account(mut)]
+    pub heir: Signer<'info>,
+    /// CHECK: NFT mint
+    pub nft_mint: AccountInfo<'info>,
+    #[account(
+        constraint = heir_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
+        constraint = heir_nft_ata.owner == heir.key() @ ErrorCode::NoNft,
+        constraint = heir_nft_ata.amount == 1 @ ErrorCode::NoNft
+    )]
+    pub heir_nft_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    /// CHECK: The heir's defai_estate ClaimRecord; owner, discriminator and contents checked in the handler
+    pub claim_record: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = heir,
+        associated_token::mint = defai_mint,
+        associated_token::authority = heir,
+        associated_token::token_program = token_program_2022
+    )]
+    pub heir_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = escrow
+    )]
+    pub escrow_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    pub defai_mint: Box<InterfaceAccount<'info, Mint2022>>,
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"vesting_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub vesting_state: Account<'info, VestingStateV6>,
+    #[account(
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Account<'info, ProgramRegistry>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn claim_inherited_vesting(ctx: Context<ClaimInheritedVesting>) -> Result<()> {
+    require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+
+    let heir = ctx.accounts.heir.key();
+    let nft_mint = ctx.accounts.nft_mint.key();
+    let record = EstateClaimRecord::parse(&ctx.accounts.claim_record, &ctx.accounts.program_registry.links.estate)?;
+    require_keys_eq!(record.beneficiary, heir, InheritanceError::InvalidClaimRecord);
+    require!(record.nfts_claimed.contains(&nft_mint), InheritanceError::NftNotInherited);
+
+    let now = Clock::get()?.unix_timestamp;
+    let vesting_state = &mut ctx.accounts.vesting_state;
+    let previous_payout_wallet = vesting_state.payout_wallet.take();
+
+    let vested_amount = vested_after_cliff(
+        vesting_state.total_amount,
+        vesting_state.start_timestamp,
+        vesting_state.end_timestamp,
+        now,
+    )?;
+    let claimable = vested_amount.saturating_sub(vesting_state.released_amount);
+
+    // Taking over the position is allowed before anything has vested
+    if claimable > 0 {
+        let escrow_seeds = &[b"escrow" as &[u8], &[ctx.accounts.escrow.bump][..]];
+        let signer_seeds = &[&escrow_seeds[..]];
+        token22::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_2022.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_defai_ata.to_account_info(),
+                    to: ctx.accounts.heir_defai_ata.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            claimable,
+            ctx.accounts.defai_mint.decimals,
+        )?;
+
+        let vesting_state = &mut ctx.accounts.vesting_state;
+        vesting_state.released_amount = vesting_state.released_amount
+            .checked_add(claimable)
+            .ok_or(ErrorCode::MathOverflow)?;
+        vesting_state.last_claimed_timestamp = now;
+        require_covers("vesting total", vesting_state.total_amount, vesting_state.released_amount)?;
+    }
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), VestingInherited {
+        heir,
+        estate: record.estate,
+        nft_mint,
+        previous_payout_wallet,
+        amount_claimed: claimable,
+        total_vested: vested_amount,
+        timestamp: now,
+    });
+
+    msg!("Heir {} took over vesting for {}; claimed {}", heir, nft_mint, claimable);
+    Ok(())
+}
+
+#[event]
+pub struct VestingInherited {
+    pub schema_version: u8,
+    pub heir: Pubkey,
+    pub estate: Pubkey,
+    pub nft_mint: Pubkey,
+    pub previous_payout_wallet: Option<Pubkey>,   // Cleared; claims now go to the holder
+    pub amount_claimed: u64,
+    pub total_vested: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum InheritanceError {
+    #[msg("Claim record is not owned by the linked DEFAI Estate program")]
+    InvalidClaimRecordOwner,
+    #[msg("Not a DEFAI Estate claim record for this heir")]
+    InvalidClaimRecord,
+    #[msg("This NFT was not claimed from the estate by this heir")]
+    NftNotInherited,
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of, pay_crank_reward, EventCpi};
+
+use crate::{ErrorCode, TaxReset, UserTaxState, INITIAL_TAX_BPS};
+
+// Keeper cranks (see defai_common::crank)
+// Permissionless versions of the swap's time-based transitions, paid from
+// the `keeper_vault` PDA.
+
+#[account]
+pub struct KeeperVault {
+    pub cranks: u64,          // Successful cranks
+    pub rewards_paid: u64,    // Lamports paid to keepers
+    pub bump: u8,
+}
+
+impl KeeperVault {
+    pub const LEN: usize = 8 + 8 + 1;
+
+    // Pay the keeper for a crank that just succeeded
+    pub fn reward<'info>(
+        vault: &mut Account<'info, KeeperVault>,
+        keeper: &AccountInfo<'info>,
+        crank: &str,
+        target: Pubkey,
+        events: &EventCpi<'_, 'info>,
+    ) -> Result<()> {
+        let reward = pay_crank_reward(&vault.to_account_info(), keeper, &Rent::get()?)?;
+        vault.cranks = vault.cranks.saturating_add(1);
+        vault.rewards_paid = vault.rewards_paid.saturating_add(reward);
+
+        emit_cpi_versioned!(events, KeeperCranked {
+            keeper: keeper.key(),
+            crank: crank.to_string(),
+            target,
+            reward,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeKeeperVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + KeeperVault::LEN,
+        seeds = [b"keeper_vault"],
+        bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CrankResetUserTax<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"user_tax", user_tax_state.user.as_ref()],
+        bump
+    )]
+    pub user_tax_state: Account<'info, UserTaxState>,
+    #[account(
+        mut,
+        seeds = [b"keeper_vault"],
+        bump = keeper_vault.bump
+    )]
+    pub keeper_vault: Account<'info, KeeperVault>,
+}
+
+// Anyone may create the vault; it holds nothing but keeper rewards
+pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+    let vault = &mut ctx.accounts.keeper_vault;
+    vault.cranks = 0;
+    vault.rewards_paid = 0;
+    vault.bump = ctx.bumps.keeper_vault;
+    Ok(())
+}
+
+// Reset a user's tax once TAX_RESET_DURATION has passed since their last
+// swap. Rejected if the tax is already at the initial rate, so a state can
+// only be cranked once per swap streak.
+pub fn crank_reset_user_tax(ctx: Context<CrankResetUserTax>) -> Result<()> {
+    let user_tax_state = &mut ctx.accounts.user_tax_state;
+    require!(
+        user_tax_state.tax_rate_bps != INITIAL_TAX_BPS || user_tax_state.swap_count != 0,
+        ErrorCode::TaxAlreadyReset
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let old_rate = user_tax_state.reset_tax(now)?;
+
+    emit_versioned!(TaxReset {
+        user: user_tax_state.user,
+        old_rate_bps: old_rate,
+        new_rate_bps: INITIAL_TAX_BPS,
+        timestamp: now,
+    });
+
+    let target = user_tax_state.key();
+    KeeperVault::reward(
+        &mut ctx.accounts.keeper_vault,
+        &ctx.accounts.keeper,
+        "reset_user_tax",
+        target,
+        &event_cpi_of!(ctx),
+    )
+}
+
+#[event]
+pub struct KeeperCranked {
+    pub schema_version: u8,
+    pub keeper: Pubkey,
+    pub crank: String,
+    pub target: Pubkey,   // Account the transition was applied to
+    pub reward: u64,      // Lamports paid; 0 when the vault is empty
+    pub timestamp: i64,
+}
@@ -1,48 +1,182 @@
+// initialize_collection takes one argument per tier field, and the CPI helper
+// Anchor generates for it inherits the count
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::{
+    associated_token::AssociatedToken,
     token::{self, Token, TokenAccount, Transfer},
     token_2022::{self as token22, Token2022},
-    token_interface::{TokenAccount as TokenAccount2022, TransferChecked, Burn, CloseAccount},
+    token_interface::{Mint as Mint2022, TokenAccount as TokenAccount2022, TransferChecked, Burn, CloseAccount},
 };
 use anchor_lang::prelude::InterfaceAccount;
+use defai_common::{
+    bps_of, emit_cpi_versioned, emit_versioned, event_cpi_of, merkle_address_leaf, merkle_leaf, require_covers,
+    verify_merkle_proof,
+    ReceiptKind, RevenueSource, Timelock, ADMIN_TIMELOCK_DURATION, AUDIT_LOG_SEED,
+};
 
 // Old VRF modules removed - using randomness_v2 only
 pub mod randomness_v2;
 use randomness_v2::*;
+pub mod nft_staking;
+use nft_staking::*;
+pub mod buyback;
+use buyback::*;
+pub mod math;
+use math::*;
+pub mod switchboard;
+pub mod audit;
+use audit::*;
+pub mod security_info;
+use security_info::*;
+pub mod cluster_config;
+use cluster_config::*;
+pub mod program_registry;
+use program_registry::*;
+pub mod keeper;
+use keeper::*;
+pub mod inheritance;
+use inheritance::*;
+pub mod treasury;
+use treasury::*;
+pub mod receipts;
+use receipts::*;
+pub mod migration;
+use migration::*;
 
 declare_id!("DB9Zvhdp5xh853d2Tr2HBkRDDaCSioD7vwchhcGaXCw3");
 
-// Tax configuration constants (basis points = parts per 10_000)
-const INITIAL_TAX_BPS: u16 = 500;     // 5%
-const TAX_INCREMENT_BPS: u16 = 100;    // 1% each swap
-const TAX_CAP_BPS: u16 = 3000;        // 30% maximum tax
-const TAX_RESET_DURATION: i64 = 24 * 60 * 60; // 24 hours in seconds
-
-// Timelock constants
-const ADMIN_TIMELOCK_DURATION: i64 = 48 * 60 * 60; // 48 hours for admin actions
+// Disclosure contact embedded in the binary's .security.txt section. The
+// SecurityInfo PDA supplements it between upgrades.
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "DEFAI Swap",
+    project_url: "https://github.com/heirlabs/audit",
+    contacts: "email:security@defai.estate,link:https://github.com/heirlabs/audit/security/advisories/new",
+    policy: "https://github.com/heirlabs/audit/blob/main/SECURITY.md",
+    preferred_languages: "en",
+    source_code: "https://github.com/heirlabs/audit",
+    expiry: "2027-10-16"
+}
 
-// OG NFT Whitelist Merkle Root
-const WHITELIST_ROOT: [u8; 32] = [75, 45, 118, 95, 221, 195, 106, 5, 187, 186, 56, 74, 112, 138, 19, 108, 59, 243, 44, 140, 228, 10, 199, 125, 41, 242, 223, 102, 191, 115, 73, 142];
+// PDA seeds. Account constraints spell these as byte literals, which is the
+// only form Anchor 0.29's IDL parser resolves into PDA derivations.
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+#[constant]
+pub const ESCROW_SEED: &[u8] = b"escrow";
+#[constant]
+pub const ESCROW_OLD_SEED: &[u8] = b"escrow_old";
+#[constant]
+pub const ESCROW_LEDGER_SEED: &[u8] = b"escrow_ledger";
+#[constant]
+pub const TAX_STATE_SEED: &[u8] = b"tax_state";
+#[constant]
+pub const USER_TAX_SEED: &[u8] = b"user_tax";
+#[constant]
+pub const COLLECTION_CONFIG_SEED: &[u8] = b"collection_config";
+#[constant]
+pub const TIER_PHASE_SEED: &[u8] = b"tier_phase";
+#[constant]
+pub const RANDOMNESS_STATE_SEED: &[u8] = b"randomness_state";
+#[constant]
+pub const BONUS_SEED: &[u8] = b"bonus_v6";
+#[constant]
+pub const VESTING_SEED: &[u8] = b"vesting_v6";
+#[constant]
+pub const REROLL_HISTORY_SEED: &[u8] = b"reroll_history";
+#[constant]
+pub const OG_TIER0_CLAIM_SEED: &[u8] = b"og_tier0_claim";
+#[constant]
+pub const AIRDROP_VESTING_SEED: &[u8] = b"airdrop_vesting";
+#[constant]
+pub const WHITELIST_SEED: &[u8] = b"whitelist";
+#[constant]
+pub const NFT_STAKING_POOL_SEED: &[u8] = b"nft_staking_pool";
+#[constant]
+pub const NFT_STAKING_REWARDS_SEED: &[u8] = b"nft_staking_rewards";
+#[constant]
+pub const NFT_STAKE_SEED: &[u8] = b"nft_stake";
+#[constant]
+pub const NFT_STAKE_VAULT_SEED: &[u8] = b"nft_stake_vault";
+#[constant]
+pub const BUYBACK_STATE_SEED: &[u8] = b"buyback_state";
+#[constant]
+pub const BUYBACK_VAULT_SEED: &[u8] = b"buyback_vault";
+#[constant]
+pub const SECURITY_INFO_SEED: &[u8] = b"security_info";
+#[constant]
+pub const CLUSTER_CONFIG_SEED: &[u8] = b"cluster_config";
+#[constant]
+pub const KEEPER_VAULT_SEED: &[u8] = b"keeper_vault";
+#[constant]
+pub const TREASURY_LEDGER_SEED: &[u8] = b"treasury_ledger";
+#[constant]
+pub const RECEIPT_LOG_SEED: &[u8] = b"receipt_log";
+#[constant]
+pub const PROGRAM_REGISTRY_SEED: &[u8] = b"program_registry";
 
-// Vesting constants
-const VESTING_DURATION: i64 = 90 * 24 * 60 * 60; // 90 days in seconds
-const CLIFF_DURATION: i64 = 2 * 24 * 60 * 60;    // 2 days in seconds
+// Tax configuration constants (basis points = parts per 10_000)
+#[constant]
+pub const INITIAL_TAX_BPS: u16 = 500;     // 5%
+#[constant]
+pub const TAX_INCREMENT_BPS: u16 = 100;    // 1% each swap
+#[constant]
+pub const TAX_CAP_BPS: u16 = 3000;        // 30% maximum tax
+#[constant]
+pub const TAX_RESET_DURATION: i64 = 86400; // 24 hours in seconds
+
+// Rate limiting: swaps a single user may execute within one slot
+const MAX_SWAPS_PER_SLOT: u8 = 1;
+
+// Remaining supply (basis points of tier supply) below which TierLowSupply is emitted
+const LOW_SUPPLY_THRESHOLD_BPS: u32 = 500; // 5%
+
+// Number of rerolls retained in each NFT's history log
+const REROLL_HISTORY_LEN: usize = 10;
+
+// Fixed tier metadata sizes in CollectionConfig. Names and symbols take
+// Metaplex's limits. URI prefixes get well past the 200 bytes the Borsh
+// layout reserved, so moving a tier to a longer gateway URL still fits.
+const MAX_TIER_NAME_LEN: usize = 32;
+const MAX_TIER_SYMBOL_LEN: usize = 10;
+const MAX_TIER_URI_PREFIX_LEN: usize = 512;
+
+// Vesting constants. Exported values are plain literals so IDL clients can
+// parse them as numbers.
+#[constant]
+pub const VESTING_DURATION: i64 = 7776000; // 90 days in seconds
+#[constant]
+pub const CLIFF_DURATION: i64 = 172800;    // 2 days in seconds
+#[constant]
+pub const DUST_VESTING_THRESHOLD: u64 = 10000000; // 10 DEFAI (6 decimals) - paid out without cliff
 
 // ============================================
 // LOCKED CONTEXT - DO NOT CHANGE THESE BONUS RANGES EVER
 // These bonus ranges are FINAL and IMMUTABLE
 // ============================================
 // Bonus ranges per tier (basis points)
-const TIER_0_MIN_BONUS: u16 = 0;     // 0%  - OG tier (No bonus)
-const TIER_0_MAX_BONUS: u16 = 0;     // 0%  - OG tier (No bonus)
-const TIER_1_MIN_BONUS: u16 = 0;     // 0%  - Train
-const TIER_1_MAX_BONUS: u16 = 1500;  // 15% - Train
-const TIER_2_MIN_BONUS: u16 = 1500;  // 15% - Boat
-const TIER_2_MAX_BONUS: u16 = 5000;  // 50% - Boat
-const TIER_3_MIN_BONUS: u16 = 2000;  // 20% - Plane
-const TIER_3_MAX_BONUS: u16 = 10000; // 100% - Plane
-const TIER_4_MIN_BONUS: u16 = 5000;  // 50% - Rocket
-const TIER_4_MAX_BONUS: u16 = 30000; // 300% - Rocket
+#[constant]
+pub const TIER_0_MIN_BONUS: u16 = 0;     // 0%  - OG tier (No bonus)
+#[constant]
+pub const TIER_0_MAX_BONUS: u16 = 0;     // 0%  - OG tier (No bonus)
+#[constant]
+pub const TIER_1_MIN_BONUS: u16 = 0;     // 0%  - Train
+#[constant]
+pub const TIER_1_MAX_BONUS: u16 = 1500;  // 15% - Train
+#[constant]
+pub const TIER_2_MIN_BONUS: u16 = 1500;  // 15% - Boat
+#[constant]
+pub const TIER_2_MAX_BONUS: u16 = 5000;  // 50% - Boat
+#[constant]
+pub const TIER_3_MIN_BONUS: u16 = 2000;  // 20% - Plane
+#[constant]
+pub const TIER_3_MAX_BONUS: u16 = 10000; // 100% - Plane
+#[constant]
+pub const TIER_4_MIN_BONUS: u16 = 5000;  // 50% - Rocket
+#[constant]
+pub const TIER_4_MAX_BONUS: u16 = 30000; // 300% - Rocket
 // ============================================
 // END LOCKED CONTEXT
 // ============================================
@@ -69,6 +203,12 @@ pub mod defai_swap {
         cfg.admin_change_timestamp = 0;
         // Auto-enable VRF by default; ensure VRF state is initialized and randomness consumed before swaps
         cfg.vrf_enabled = true; 
+        cfg.pending_vrf_enabled = None;
+        cfg.vrf_change_timestamp = 0;
+        // OLD tokens are exchanged 1:1 against tier prices until changed via timelock
+        cfg.old_conversion_ratio = ConversionRatio { numerator: 1, denominator: 1 };
+        cfg.pending_old_conversion_ratio = None;
+        cfg.conversion_ratio_change_timestamp = 0;
 
         // Persist escrow bump for later signer seeds
         let escrow = &mut ctx.accounts.escrow;
@@ -116,7 +256,7 @@ pub mod defai_swap {
         ctx.accounts.config.paused = true;
         
         // Emit admin action event
-        emit!(AdminAction {
+        emit_versioned!(AdminAction {
             admin: ctx.accounts.admin.key(),
             action: "Pause protocol".to_string(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -132,7 +272,7 @@ pub mod defai_swap {
         ctx.accounts.config.paused = false;
         
         // Emit admin action event
-        emit!(AdminAction {
+        emit_versioned!(AdminAction {
             admin: ctx.accounts.admin.key(),
             action: "Unpause protocol".to_string(),
             timestamp: Clock::get()?.unix_timestamp,
@@ -141,26 +281,45 @@ pub mod defai_swap {
         Ok(())
     }
 
+    pub fn initialize_escrow_ledger(ctx: Context<InitializeEscrowLedger>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let ledger = &mut ctx.accounts.escrow_ledger;
+        ledger.reserved_airdrop = 0;
+        ledger.bump = ctx.bumps.escrow_ledger;
+        Ok(())
+    }
+
     pub fn initialize_whitelist(ctx: Context<InitializeWhitelist>) -> Result<()> {
         require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
         
         let whitelist = &mut ctx.accounts.whitelist;
-        whitelist.root = WHITELIST_ROOT;
+        whitelist.root = ctx.accounts.cluster_config.settings.whitelist_root;
         whitelist.claimed_count = 0;
         Ok(())
     }
     
-    pub fn propose_admin_change(ctx: Context<UpdateConfig>, new_admin: Pubkey) -> Result<()> {
+    pub fn propose_admin_change(ctx: Context<ChangeAdmin>, new_admin: Pubkey) -> Result<()> {
         require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
         
-        let cfg = &mut ctx.accounts.config;
-        cfg.pending_admin = Some(new_admin);
-        cfg.admin_change_timestamp = Clock::get()?.unix_timestamp + ADMIN_TIMELOCK_DURATION;
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        Timelock::new(&mut cfg.pending_admin, &mut cfg.admin_change_timestamp)
+            .propose(new_admin, Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
         
         msg!("Admin change proposed. Can be executed after {}", cfg.admin_change_timestamp);
         
+        let config_key = ctx.accounts.config.key();
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminChangeProposed,
+            ctx.accounts.admin.key(),
+            config_key,
+            new_admin,
+            0,
+            [0; 32],
+        )?;
+        
         // Emit admin action event
-        emit!(AdminAction {
+        emit_versioned!(AdminAction {
             admin: ctx.accounts.admin.key(),
             action: format!("Propose admin change to {}", new_admin),
             timestamp: Clock::get()?.unix_timestamp,
@@ -169,26 +328,30 @@ pub mod defai_swap {
         Ok(())
     }
     
-    pub fn accept_admin_change(ctx: Context<UpdateConfig>) -> Result<()> {
+    pub fn accept_admin_change(ctx: Context<ChangeAdmin>) -> Result<()> {
         require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
         
-        let cfg = &mut ctx.accounts.config;
-        require!(cfg.pending_admin.is_some(), ErrorCode::NoPendingAdminChange);
-        require!(
-            Clock::get()?.unix_timestamp >= cfg.admin_change_timestamp,
-            ErrorCode::TimelockNotExpired
-        );
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        let new_admin = Timelock::new(&mut cfg.pending_admin, &mut cfg.admin_change_timestamp)
+            .execute(Clock::get()?.unix_timestamp)?;
         
         let old_admin = cfg.admin;
-        let new_admin = cfg.pending_admin.unwrap();
         cfg.admin = new_admin;
-        cfg.pending_admin = None;
-        cfg.admin_change_timestamp = 0;
         
         msg!("Admin changed from {} to {}", old_admin, new_admin);
         
+        let config_key = ctx.accounts.config.key();
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminChanged,
+            old_admin,
+            config_key,
+            new_admin,
+            0,
+            [0; 32],
+        )?;
+        
         // Emit admin action event
-        emit!(AdminAction {
+        emit_versioned!(AdminAction {
             admin: old_admin,
             action: format!("Admin changed to {}", new_admin),
             timestamp: Clock::get()?.unix_timestamp,
@@ -197,6 +360,103 @@ pub mod defai_swap {
         Ok(())
     }
     
+    pub fn propose_vrf_mode_change(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        require!(ctx.accounts.config.vrf_enabled != enabled, ErrorCode::InvalidInput);
+        
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        Timelock::new(&mut cfg.pending_vrf_enabled, &mut cfg.vrf_change_timestamp)
+            .propose(enabled, Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+        
+        msg!("VRF mode change proposed. Can be executed after {}", cfg.vrf_change_timestamp);
+        
+        emit_versioned!(VrfModeChangeProposed {
+            admin: ctx.accounts.admin.key(),
+            enabled,
+            execute_after: cfg.vrf_change_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn cancel_vrf_mode_change(ctx: Context<UpdateConfig>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        Timelock::new(&mut cfg.pending_vrf_enabled, &mut cfg.vrf_change_timestamp).cancel()?;
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.admin.key(),
+            action: "Cancel VRF mode change".to_string(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn execute_vrf_mode_change(ctx: Context<ExecuteVrfModeChange>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+        let enabled = Timelock::new(&mut cfg.pending_vrf_enabled, &mut cfg.vrf_change_timestamp)
+            .execute(now)?;
+        cfg.vrf_enabled = enabled;
+        
+        // Invalidate any result produced under the previous trust model so swaps
+        // must wait for fresh randomness generated under the new mode
+        let randomness_state = &mut ctx.accounts.randomness_state;
+        randomness_state.revealed_value = [0u8; 32];
+        randomness_state.is_pending = false;
+        randomness_state.last_update = now;
+        
+        msg!("VRF mode changed to {}", if enabled { "enabled" } else { "disabled" });
+        
+        emit_versioned!(VrfModeChanged {
+            admin: ctx.accounts.admin.key(),
+            enabled,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn propose_conversion_ratio(ctx: Context<UpdateConfig>, numerator: u64, denominator: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        require!(numerator > 0 && denominator > 0, ErrorCode::InvalidInput);
+        
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        Timelock::new(&mut cfg.pending_old_conversion_ratio, &mut cfg.conversion_ratio_change_timestamp)
+            .propose(ConversionRatio { numerator, denominator }, Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+        
+        msg!("Conversion ratio change proposed. Can be executed after {}", cfg.conversion_ratio_change_timestamp);
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.admin.key(),
+            action: format!("Propose OLD:NEW conversion ratio {}:{}", numerator, denominator),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn execute_conversion_ratio_change(ctx: Context<UpdateConfig>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let cfg: &mut Config = &mut ctx.accounts.config;
+        let ratio = Timelock::new(&mut cfg.pending_old_conversion_ratio, &mut cfg.conversion_ratio_change_timestamp)
+            .execute(Clock::get()?.unix_timestamp)?;
+        cfg.old_conversion_ratio = ratio.clone();
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.admin.key(),
+            action: format!("OLD:NEW conversion ratio changed to {}:{}", ratio.numerator, ratio.denominator),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
     // Old VRF functions removed - use randomness_v2 functions instead
 
     // New Switchboard On-Demand Randomness Instructions
@@ -216,30 +476,209 @@ pub mod defai_swap {
         randomness_v2::generate_simple_randomness(ctx)
     }
 
+    // Bonus-NFT Staking Instructions
+    pub fn initialize_nft_staking(
+        ctx: Context<InitializeNftStaking>,
+        reward_rate_per_second: u64,
+        tier_weights: [u16; 5],
+    ) -> Result<()> {
+        nft_staking::initialize_nft_staking(ctx, reward_rate_per_second, tier_weights)
+    }
+
+    pub fn update_nft_staking(
+        ctx: Context<UpdateNftStaking>,
+        reward_rate_per_second: u64,
+        tier_weights: [u16; 5],
+    ) -> Result<()> {
+        nft_staking::update_nft_staking(ctx, reward_rate_per_second, tier_weights)
+    }
+
+    pub fn stake_bonus_nft(ctx: Context<StakeBonusNft>) -> Result<()> {
+        nft_staking::stake_bonus_nft(ctx)
+    }
+
+    pub fn unstake_bonus_nft(ctx: Context<UnstakeBonusNft>) -> Result<()> {
+        nft_staking::unstake_bonus_nft(ctx)
+    }
+
+    pub fn claim_staking_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+        nft_staking::claim_staking_rewards(ctx)
+    }
+
+    // Audit Log Instructions
+    pub fn initialize_audit_log(ctx: Context<InitializeAuditLog>) -> Result<()> {
+        audit::initialize_audit_log(ctx)
+    }
+
+    // Security Contact Instructions
+    pub fn update_security_info(
+        ctx: Context<UpdateSecurityInfo>,
+        contacts: String,
+        policy: String,
+        auditors: String,
+        expiry: i64,
+    ) -> Result<()> {
+        security_info::update_security_info(ctx, contacts, policy, auditors, expiry)
+    }
+
+    // Cluster Configuration Instructions
+    pub fn initialize_cluster_config(
+        ctx: Context<InitializeClusterConfig>,
+        settings: Option<ClusterSettings>,
+    ) -> Result<()> {
+        cluster_config::initialize_cluster_config(ctx, settings)
+    }
+
+    pub fn propose_cluster_config_change(ctx: Context<UpdateClusterConfig>, settings: ClusterSettings) -> Result<()> {
+        cluster_config::propose_cluster_config_change(ctx, settings)
+    }
+
+    pub fn cancel_cluster_config_change(ctx: Context<UpdateClusterConfig>) -> Result<()> {
+        cluster_config::cancel_cluster_config_change(ctx)
+    }
+
+    pub fn execute_cluster_config_change(ctx: Context<ExecuteClusterConfigChange>) -> Result<()> {
+        cluster_config::execute_cluster_config_change(ctx)
+    }
+
+    // Program Registry Instructions
+    pub fn initialize_program_registry(
+        ctx: Context<InitializeProgramRegistry>,
+        links: Option<ProgramLinks>,
+    ) -> Result<()> {
+        program_registry::initialize_program_registry(ctx, links)
+    }
+
+    pub fn propose_program_links_change(ctx: Context<UpdateProgramRegistry>, links: ProgramLinks) -> Result<()> {
+        program_registry::propose_program_links_change(ctx, links)
+    }
+
+    pub fn cancel_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::cancel_program_links_change(ctx)
+    }
+
+    pub fn execute_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+        program_registry::execute_program_links_change(ctx)
+    }
+
+    // Keeper Crank Instructions
+    pub fn initialize_keeper_vault(ctx: Context<InitializeKeeperVault>) -> Result<()> {
+        keeper::initialize_keeper_vault(ctx)
+    }
+
+    pub fn crank_reset_user_tax(ctx: Context<CrankResetUserTax>) -> Result<()> {
+        keeper::crank_reset_user_tax(ctx)
+    }
+
+    // Treasury Ledger Instructions
+    pub fn initialize_treasury_ledger(ctx: Context<InitializeTreasuryLedger>) -> Result<()> {
+        treasury::initialize_treasury_ledger(ctx)
+    }
+
+    pub fn propose_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>, policy: WithdrawalPolicy) -> Result<()> {
+        treasury::propose_withdrawal_policy(ctx, policy)
+    }
+
+    pub fn cancel_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+        treasury::cancel_withdrawal_policy(ctx)
+    }
+
+    pub fn execute_withdrawal_policy(ctx: Context<UpdateTreasuryLedger>) -> Result<()> {
+        treasury::execute_withdrawal_policy(ctx)
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        treasury::withdraw_treasury(ctx, amount)
+    }
+
+    // Receipt Log Instructions
+    pub fn initialize_receipt_log(ctx: Context<InitializeReceiptLog>) -> Result<()> {
+        receipts::initialize_receipt_log(ctx)
+    }
+
+    pub fn close_reroll_history(ctx: Context<CloseRerollHistory>) -> Result<()> {
+        receipts::close_reroll_history(ctx)
+    }
+
+    // Buyback-and-Burn Instructions
+    pub fn initialize_buyback(ctx: Context<InitializeBuyback>, tax_share_bps: u16) -> Result<()> {
+        buyback::initialize_buyback(ctx, tax_share_bps)
+    }
+
+    pub fn set_buyback_tax_share(ctx: Context<ProposeBuyback>, tax_share_bps: u16) -> Result<()> {
+        buyback::set_buyback_tax_share(ctx, tax_share_bps)
+    }
+
+    pub fn propose_buyback_and_burn(ctx: Context<ProposeBuyback>, amount: u64) -> Result<()> {
+        buyback::propose_buyback_and_burn(ctx, amount)
+    }
+
+    pub fn cancel_buyback_and_burn(ctx: Context<ProposeBuyback>) -> Result<()> {
+        buyback::cancel_buyback_and_burn(ctx)
+    }
+
+    pub fn buyback_and_burn(ctx: Context<ExecuteBuybackAndBurn>) -> Result<()> {
+        buyback::buyback_and_burn(ctx)
+    }
+
+    pub fn configure_tier_phase(
+        ctx: Context<ConfigureTierPhase>,
+        tier: u8,
+        presale_start: i64,
+        presale_end: i64,
+        merkle_root: [u8; 32],
+        max_per_wallet: u16,
+    ) -> Result<()> {
+        require!(tier < 5, ErrorCode::InvalidTier);
+        require!(presale_start <= presale_end, ErrorCode::InvalidInput);
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let phase = &mut ctx.accounts.tier_phase;
+        phase.tier = tier;
+        phase.presale_start = presale_start;
+        phase.presale_end = presale_end;
+        phase.merkle_root = merkle_root;
+        phase.max_per_wallet = max_per_wallet;
+        phase.bump = ctx.bumps.tier_phase;
+        
+        let phase_key = phase.key();
+        ctx.accounts.audit_log.record(
+            AuditAction::TierRootUpdate,
+            ctx.accounts.admin.key(),
+            phase_key,
+            Pubkey::default(),
+            tier as u64,
+            merkle_root,
+        )?;
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.admin.key(),
+            action: format!("Configure tier {} presale {}..{}", tier, presale_start, presale_end),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     pub fn initialize_user_tax(ctx: Context<InitializeUserTax>) -> Result<()> {
         let user_tax_state = &mut ctx.accounts.user_tax_state;
         user_tax_state.user = ctx.accounts.user.key();
         user_tax_state.tax_rate_bps = INITIAL_TAX_BPS;
         user_tax_state.last_swap_timestamp = Clock::get()?.unix_timestamp;
         user_tax_state.swap_count = 0;
+        user_tax_state.last_swap_slot = 0;
+        user_tax_state.slot_swap_count = 0;
+        user_tax_state.presale_purchases = [0; 5];
         Ok(())
     }
 
     pub fn reset_user_tax(ctx: Context<ResetUserTax>) -> Result<()> {
         let user_tax_state = &mut ctx.accounts.user_tax_state;
         let now = Clock::get()?.unix_timestamp;
-        
-        require!(
-            now >= user_tax_state.last_swap_timestamp + TAX_RESET_DURATION,
-            ErrorCode::TaxResetTooEarly
-        );
-        
-        let old_rate = user_tax_state.tax_rate_bps;
-        user_tax_state.tax_rate_bps = INITIAL_TAX_BPS;
-        user_tax_state.swap_count = 0;
+        let old_rate = user_tax_state.reset_tax(now)?;
         
         // Emit tax reset event
-        emit!(TaxReset {
+        emit_versioned!(TaxReset {
             user: ctx.accounts.user.key(),
             old_rate_bps: old_rate,
             new_rate_bps: INITIAL_TAX_BPS,
@@ -260,7 +699,7 @@ pub mod defai_swap {
         airdrop_merkle_root: [u8; 32],    // For 10_1AIR-Sheet1.csv - Pure vesting, no NFT
         og_tier_0_supply: u16,            // Reserved supply for OG holders
     ) -> Result<()> {
-        let collection_config = &mut ctx.accounts.collection_config;
+        let mut collection_config = ctx.accounts.collection_config.load_init()?;
         collection_config.authority = ctx.accounts.authority.key();
         collection_config.collection_mint = ctx.accounts.collection_mint.key();
         collection_config.treasury = ctx.accounts.treasury.key();
@@ -268,9 +707,9 @@ pub mod defai_swap {
         collection_config.old_defai_mint = ctx.accounts.old_defai_mint.key();
         
         for i in 0..5 {
-            collection_config.tier_names[i] = tier_names.get(i).cloned().unwrap_or_default();
-            collection_config.tier_symbols[i] = tier_symbols.get(i).cloned().unwrap_or_default();
-            collection_config.tier_uri_prefixes[i] = tier_uri_prefixes.get(i).cloned().unwrap_or_default();
+            collection_config.tier_names[i] = fixed_bytes(tier_names.get(i).map_or("", String::as_str))?;
+            collection_config.tier_symbols[i] = fixed_bytes(tier_symbols.get(i).map_or("", String::as_str))?;
+            collection_config.tier_uri_prefixes[i] = fixed_bytes(tier_uri_prefixes.get(i).map_or("", String::as_str))?;
         }
         
         collection_config.tier_prices = tier_prices;
@@ -284,6 +723,93 @@ pub mod defai_swap {
         Ok(())
     }
 
+    /// Convert a CollectionConfig created before the zero-copy layout in place.
+    /// Fails if a stored tier string is longer than its fixed field.
+    pub fn migrate_collection_config(ctx: Context<MigrateCollectionConfig>) -> Result<()> {
+        let info = ctx.accounts.collection_config.to_account_info();
+        // Same discriminator, so the old Borsh account is told apart by size: it
+        // was created at 8 + 1550 bytes, about half the fixed layout's size.
+        require!(info.data_len() != 8 + CollectionConfig::LEN, ErrorCode::CollectionConfigMigrated);
+        let legacy = LegacyCollectionConfig::deserialize(&mut &info.try_borrow_data()?[8..])?;
+        require_keys_eq!(legacy.authority, ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        
+        info.realloc(8 + CollectionConfig::LEN, false)?;
+        info.try_borrow_mut_data()?[8..].fill(0);
+        
+        let mut collection_config = ctx.accounts.collection_config.load_mut()?;
+        collection_config.authority = legacy.authority;
+        collection_config.collection_mint = legacy.collection_mint;
+        collection_config.treasury = legacy.treasury;
+        collection_config.defai_mint = legacy.defai_mint;
+        collection_config.old_defai_mint = legacy.old_defai_mint;
+        for i in 0..5 {
+            collection_config.tier_names[i] = fixed_bytes(&legacy.tier_names[i])?;
+            collection_config.tier_symbols[i] = fixed_bytes(&legacy.tier_symbols[i])?;
+            collection_config.tier_uri_prefixes[i] = fixed_bytes(&legacy.tier_uri_prefixes[i])?;
+        }
+        collection_config.tier_prices = legacy.tier_prices;
+        collection_config.tier_supplies = legacy.tier_supplies;
+        collection_config.tier_minted = legacy.tier_minted;
+        collection_config.og_tier_0_merkle_root = legacy.og_tier_0_merkle_root;
+        collection_config.airdrop_merkle_root = legacy.airdrop_merkle_root;
+        collection_config.og_tier_0_supply = legacy.og_tier_0_supply;
+        collection_config.og_tier_0_minted = legacy.og_tier_0_minted;
+        
+        msg!("CollectionConfig migrated to zero-copy layout");
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.authority.key(),
+            action: "Migrate collection config to zero-copy".to_string(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Grow a Config created before the VRF timelock and conversion ratio
+    /// to the current layout
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        migration::migrate_config(ctx)
+    }
+
+    /// Grow a vesting created before payout wallets; permissionless, payer funds the rent
+    pub fn migrate_vesting_state(ctx: Context<MigrateVestingState>, nft_mint: Pubkey) -> Result<()> {
+        migration::migrate_vesting_state(ctx, nft_mint)
+    }
+
+    /// Grow an airdrop vesting created before payout wallets; permissionless
+    pub fn migrate_airdrop_vesting(ctx: Context<MigrateAirdropVesting>, beneficiary: Pubkey) -> Result<()> {
+        migration::migrate_airdrop_vesting(ctx, beneficiary)
+    }
+
+    /// Grow a user's tax state created before slot limits and presale counters
+    pub fn migrate_user_tax_state(ctx: Context<MigrateUserTaxState>, user: Pubkey) -> Result<()> {
+        migration::migrate_user_tax_state(ctx, user)
+    }
+
+    pub fn update_tier_metadata(
+        ctx: Context<UpdateTierMetadata>,
+        tier: u8,
+        name: String,
+        symbol: String,
+        uri_prefix: String,
+    ) -> Result<()> {
+        require!(tier < 5, ErrorCode::InvalidTier);
+        
+        let mut collection_config = ctx.accounts.collection_config.load_mut()?;
+        collection_config.tier_names[tier as usize] = fixed_bytes(&name)?;
+        collection_config.tier_symbols[tier as usize] = fixed_bytes(&symbol)?;
+        collection_config.tier_uri_prefixes[tier as usize] = fixed_bytes(&uri_prefix)?;
+        
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.authority.key(),
+            action: format!("Update tier {} metadata", tier),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     /// Function 1: For MAY20DEFAIHolders.csv - Mints NFT and provides 1:1 vesting from Quantity column
     pub fn swap_og_tier0_for_pnft_v6(
         ctx: Context<SwapOgTier0ForPnftV6>,
@@ -296,7 +822,7 @@ pub mod defai_swap {
         msg!("=== SWAP OG TIER 0 FOR PNFT V6 START ===");
         require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
         
-        let config = &ctx.accounts.collection_config;
+        let mut config = ctx.accounts.collection_config.load_mut()?;
         let og_claim = &mut ctx.accounts.og_tier0_claim;
         let clock = Clock::get()?;
         
@@ -305,24 +831,11 @@ pub mod defai_swap {
         require!(!og_claim.claimed, ErrorCode::OgTier0AlreadyClaimed);
         
         // Verify merkle proof for OG tier 0 whitelist
-        let user_key = ctx.accounts.user.key();
-        let amount_bytes = vesting_amount.to_le_bytes();
-        let leaf_data = [user_key.as_ref(), &amount_bytes].concat();
-        let leaf = solana_program::keccak::hash(&leaf_data);
-        
-        let is_valid = merkle_proof.iter().fold(leaf.0, |acc, proof_elem| {
-            let mut combined = vec![];
-            if acc <= *proof_elem {
-                combined.extend_from_slice(&acc);
-                combined.extend_from_slice(proof_elem);
-            } else {
-                combined.extend_from_slice(proof_elem);
-                combined.extend_from_slice(&acc);
-            }
-            solana_program::keccak::hash(&combined).0
-        }) == config.og_tier_0_merkle_root;
-        
-        require!(is_valid, ErrorCode::NotOnOgWhitelist);
+        let leaf = merkle_leaf(&ctx.accounts.user.key(), vesting_amount);
+        require!(
+            verify_merkle_proof(leaf, &merkle_proof, &config.og_tier_0_merkle_root),
+            ErrorCode::NotOnOgWhitelist
+        );
         
         // Check OG tier 0 supply (separate from regular tier 0)
         require!(
@@ -335,6 +848,12 @@ pub mod defai_swap {
         let (min_bonus, max_bonus) = get_tier_bonus_range(0);
         let random_value = if ctx.accounts.config.vrf_enabled {
             require!(!ctx.accounts.randomness_state.is_pending && ctx.accounts.randomness_state.revealed_value != [0u8; 32], ErrorCode::RandomnessNotReady);
+            emit_randomness_consumed(
+                &ctx.accounts.randomness_state,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+            );
             generate_vrf_random(
                 &ctx.accounts.randomness_state.revealed_value,
                 &ctx.accounts.user.key(),
@@ -370,17 +889,17 @@ pub mod defai_swap {
         vesting_state.start_timestamp = clock.unix_timestamp;
         vesting_state.end_timestamp = clock.unix_timestamp + VESTING_DURATION;
         vesting_state.last_claimed_timestamp = clock.unix_timestamp;
+        vesting_state.payout_wallet = None;
         
         // Mark as claimed for this user
         og_claim.claimer = ctx.accounts.user.key();
         og_claim.claimed = true;
         
         // Update OG tier 0 minted count (separate from regular tier 0)
-        let config = &mut ctx.accounts.collection_config;
         config.og_tier_0_minted += 1;
         
         // Emit swap event
-        emit!(SwapExecuted {
+        emit_cpi_versioned!(event_cpi_of!(ctx), SwapExecuted {
             user: ctx.accounts.user.key(),
             tier: 0,
             price: 0, // Free for OG holders
@@ -400,15 +919,29 @@ pub mod defai_swap {
         _metadata_uri: String,
         _name: String,
         _symbol: String,
+        presale_proof: Vec<[u8; 32]>,  // Only checked while the tier is in its presale window
     ) -> Result<()> {
         msg!("=== SWAP DEFAI FOR PNFT V6 START ===");
         require!(tier < 5, ErrorCode::InvalidTier);
         require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
         
-        let config = &mut ctx.accounts.collection_config;
+        let mut config = ctx.accounts.collection_config.load_mut()?;
         let user_tax = &mut ctx.accounts.user_tax_state;
         let clock = Clock::get()?;
         
+        // Limit swaps per slot to blunt result-sniping right after a randomness reveal
+        user_tax.record_swap_in_slot(clock.slot)?;
+        
+        // Enforce presale phase (whitelist + per-wallet cap) for this tier
+        enforce_tier_phase(
+            &ctx.accounts.tier_phase,
+            &ctx.accounts.user.key(),
+            &presale_proof,
+            user_tax,
+            tier,
+            clock.unix_timestamp,
+        )?;
+        
         // Check supply - for tier 0, check remaining supply after reserving for OG holders
         if tier == 0 {
             let remaining_supply = config.tier_supplies[0].saturating_sub(config.og_tier_0_supply);
@@ -424,21 +957,19 @@ pub mod defai_swap {
         }
         
         // Check and reset tax if 24 hours passed
-        if clock.unix_timestamp - user_tax.last_swap_timestamp >= TAX_RESET_DURATION {
+        if tax_reset_due(user_tax.last_swap_timestamp, clock.unix_timestamp) {
             user_tax.tax_rate_bps = INITIAL_TAX_BPS;
             user_tax.swap_count = 0;
         }
         
         // Calculate amounts
         let price = config.tier_prices[tier as usize];
-        let tax_amount = (price as u128)
-            .checked_mul(user_tax.tax_rate_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let tax_amount = bps_of(price, user_tax.tax_rate_bps as u64)?;
         let net_amount = price.checked_sub(tax_amount).ok_or(ErrorCode::MathOverflow)?;
         
-        // Transfer tax to treasury
+        // Transfer tax to treasury, less the share routed into the buyback vault
+        let buyback_amount = buyback_tax_share(&ctx.accounts.buyback_state, &ctx.accounts.buyback_vault, tax_amount)?;
+        let treasury_amount = tax_amount.checked_sub(buyback_amount).ok_or(ErrorCode::MathOverflow)?;
         let cpi_ctx_tax = CpiContext::new(
             ctx.accounts.token_program_2022.to_account_info(),
             TransferChecked {
@@ -448,7 +979,20 @@ pub mod defai_swap {
                 mint: ctx.accounts.defai_mint.to_account_info(),
             },
         );
-        token22::transfer_checked(cpi_ctx_tax, tax_amount, 6)?;
+        token22::transfer_checked(cpi_ctx_tax, treasury_amount, 6)?;
+        ctx.accounts.treasury_ledger.record(RevenueSource::SwapTax, treasury_amount, clock.unix_timestamp, &event_cpi_of!(ctx))?;
+        if buyback_amount > 0 {
+            let cpi_ctx_buyback = CpiContext::new(
+                ctx.accounts.token_program_2022.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_defai_ata.to_account_info(),
+                    to: ctx.accounts.buyback_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+            );
+            token22::transfer_checked(cpi_ctx_buyback, buyback_amount, 6)?;
+        }
         
         // Transfer net to escrow
         let cpi_ctx_net = CpiContext::new(
@@ -466,6 +1010,12 @@ pub mod defai_swap {
         let (min_bonus, max_bonus) = get_tier_bonus_range(tier);
         let random_value = if ctx.accounts.config.vrf_enabled {
             require!(!ctx.accounts.randomness_state.is_pending && ctx.accounts.randomness_state.revealed_value != [0u8; 32], ErrorCode::RandomnessNotReady);
+            emit_randomness_consumed(
+                &ctx.accounts.randomness_state,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+            );
             generate_vrf_random(
                 &ctx.accounts.randomness_state.revealed_value,
                 &ctx.accounts.user.key(),
@@ -495,11 +1045,7 @@ pub mod defai_swap {
         
         // Set up vesting state
         let vesting_state = &mut ctx.accounts.vesting_state;
-        let vesting_amount = (price as u128)
-            .checked_mul(bonus_state.bonus_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let vesting_amount = bps_of(price, bonus_state.bonus_bps as u64)?;
         
         vesting_state.mint = ctx.accounts.nft_mint.key();
         vesting_state.total_amount = vesting_amount;
@@ -507,18 +1053,42 @@ pub mod defai_swap {
         vesting_state.start_timestamp = clock.unix_timestamp;
         vesting_state.end_timestamp = clock.unix_timestamp + VESTING_DURATION;
         vesting_state.last_claimed_timestamp = clock.unix_timestamp;
+        vesting_state.payout_wallet = None;
+        
+        // Dust-sized bonuses are paid out immediately instead of vesting behind
+        // the cliff, and their vesting account is closed again
+        if is_dust_vesting(vesting_amount) {
+            pay_out_dust_vesting(
+                &ctx.accounts.escrow,
+                &ctx.accounts.escrow_defai_ata,
+                &ctx.accounts.user_defai_ata,
+                &ctx.accounts.defai_mint,
+                &ctx.accounts.token_program_2022,
+                vesting_amount,
+            )?;
+            
+            emit_cpi_versioned!(event_cpi_of!(ctx), VestingClaimed {
+                user: ctx.accounts.user.key(),
+                nft_mint: ctx.accounts.nft_mint.key(),
+                amount_claimed: vesting_amount,
+                total_vested: vesting_amount,
+                timestamp: clock.unix_timestamp,
+            });
+            ctx.accounts.vesting_state.close(ctx.accounts.user.to_account_info())?;
+            
+            msg!("Dust vesting of {} paid out immediately", vesting_amount);
+        }
         
         // Update user tax for next swap
-        user_tax.tax_rate_bps = user_tax.tax_rate_bps
-            .saturating_add(TAX_INCREMENT_BPS)
-            .min(TAX_CAP_BPS);
+        user_tax.tax_rate_bps = next_tax_bps(user_tax.tax_rate_bps);
         user_tax.swap_count += 1;
         user_tax.last_swap_timestamp = clock.unix_timestamp;
         
         config.tier_minted[tier as usize] += 1;
+        emit_tier_supply_events(&config, tier, clock.unix_timestamp);
         
         // Emit swap event
-        emit!(SwapExecuted {
+        emit_cpi_versioned!(event_cpi_of!(ctx), SwapExecuted {
             user: ctx.accounts.user.key(),
             tier,
             price,
@@ -538,15 +1108,29 @@ pub mod defai_swap {
         _metadata_uri: String,
         _name: String,
         _symbol: String,
+        presale_proof: Vec<[u8; 32]>,  // Only checked while the tier is in its presale window
     ) -> Result<()> {
         msg!("=== SWAP OLD DEFAI FOR PNFT V6 START ===");
         require!(tier < 5, ErrorCode::InvalidTier);
         require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
         
-        let config = &mut ctx.accounts.collection_config;
+        let mut config = ctx.accounts.collection_config.load_mut()?;
         let user_tax = &mut ctx.accounts.user_tax_state;
         let clock = Clock::get()?;
         
+        // Limit swaps per slot to blunt result-sniping right after a randomness reveal
+        user_tax.record_swap_in_slot(clock.slot)?;
+        
+        // Enforce presale phase (whitelist + per-wallet cap) for this tier
+        enforce_tier_phase(
+            &ctx.accounts.tier_phase,
+            &ctx.accounts.user.key(),
+            &presale_proof,
+            user_tax,
+            tier,
+            clock.unix_timestamp,
+        )?;
+        
         // Check supply - for tier 0, check remaining supply after reserving for OG holders
         if tier == 0 {
             let remaining_supply = config.tier_supplies[0].saturating_sub(config.og_tier_0_supply);
@@ -562,6 +1146,8 @@ pub mod defai_swap {
         }
         
         let price = config.tier_prices[tier as usize];
+        // Tier prices are denominated in NEW DEFAI; convert to the OLD amount owed
+        let old_amount = ctx.accounts.config.old_amount_for(price)?;
         
         // Transfer OLD tokens into program-controlled escrow (not burn)
         // This enables the team to later sell on DEX and route liquidity into the new token.
@@ -573,12 +1159,18 @@ pub mod defai_swap {
                 authority: ctx.accounts.user.to_account_info(),
             },
         );
-        token::transfer(cpi_ctx_old, price)?;
+        token::transfer(cpi_ctx_old, old_amount)?;
         
         // Generate random bonus using VRF when enabled; otherwise fallback
         let (min_bonus, max_bonus) = get_tier_bonus_range(tier);
         let random_value = if ctx.accounts.config.vrf_enabled {
             require!(!ctx.accounts.randomness_state.is_pending && ctx.accounts.randomness_state.revealed_value != [0u8; 32], ErrorCode::RandomnessNotReady);
+            emit_randomness_consumed(
+                &ctx.accounts.randomness_state,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+            );
             generate_vrf_random(
                 &ctx.accounts.randomness_state.revealed_value,
                 &ctx.accounts.user.key(),
@@ -608,11 +1200,7 @@ pub mod defai_swap {
         
         // Set up vesting state
         let vesting_state = &mut ctx.accounts.vesting_state;
-        let vesting_amount = (price as u128)
-            .checked_mul(bonus_state.bonus_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let vesting_amount = bps_of(price, bonus_state.bonus_bps as u64)?;
         
         vesting_state.mint = ctx.accounts.nft_mint.key();
         vesting_state.total_amount = vesting_amount;
@@ -620,6 +1208,31 @@ pub mod defai_swap {
         vesting_state.start_timestamp = clock.unix_timestamp;
         vesting_state.end_timestamp = clock.unix_timestamp + VESTING_DURATION;
         vesting_state.last_claimed_timestamp = clock.unix_timestamp;
+        vesting_state.payout_wallet = None;
+        
+        // Dust-sized bonuses are paid out immediately instead of vesting behind
+        // the cliff, and their vesting account is closed again
+        if is_dust_vesting(vesting_amount) {
+            pay_out_dust_vesting(
+                &ctx.accounts.escrow,
+                &ctx.accounts.escrow_defai_ata,
+                &ctx.accounts.user_defai_ata,
+                &ctx.accounts.defai_mint,
+                &ctx.accounts.token_program_2022,
+                vesting_amount,
+            )?;
+            
+            emit_cpi_versioned!(event_cpi_of!(ctx), VestingClaimed {
+                user: ctx.accounts.user.key(),
+                nft_mint: ctx.accounts.nft_mint.key(),
+                amount_claimed: vesting_amount,
+                total_vested: vesting_amount,
+                timestamp: clock.unix_timestamp,
+            });
+            ctx.accounts.vesting_state.close(ctx.accounts.user.to_account_info())?;
+            
+            msg!("Dust vesting of {} paid out immediately", vesting_amount);
+        }
         
         // OLD DEFAI swaps are tax-free and should not affect tax state
         // Only increment swap count for tracking purposes
@@ -627,9 +1240,10 @@ pub mod defai_swap {
         // Do NOT update last_swap_timestamp to avoid breaking the tax reset mechanism
         
         config.tier_minted[tier as usize] += 1;
+        emit_tier_supply_events(&config, tier, clock.unix_timestamp);
         
         // Emit swap event
-        emit!(SwapExecuted {
+        emit_cpi_versioned!(event_cpi_of!(ctx), SwapExecuted {
             user: ctx.accounts.user.key(),
             tier,
             price,
@@ -644,22 +1258,16 @@ pub mod defai_swap {
     }
 
     pub fn redeem_v6(ctx: Context<RedeemV6>) -> Result<()> {
-        msg!("=== REDEEM V6 START ===");
         require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
         
         let bonus_state = &mut ctx.accounts.bonus_state;
-        let vesting_state = &ctx.accounts.vesting_state;
-        let cfg = &ctx.accounts.config;
         
         // Verify NFT not already redeemed
         require!(!bonus_state.claimed, ErrorCode::NftAlreadyRedeemed);
         
-        // Get base price from tier
-        let base_price = cfg.prices[bonus_state.tier as usize];
-        
-        // Calculate total amount (base + bonus)
-        let bonus_amount = vesting_state.total_amount;
-        let _total_amount = base_price + bonus_amount;
+        // Get base price from tier. The vested bonus stays with vesting_state
+        // and is not paid out here.
+        let base_price = ctx.accounts.config.prices[bonus_state.tier as usize];
         
         // Deduct accumulated fees from base price
         let amount_to_transfer = base_price.saturating_sub(bonus_state.fee_deducted);
@@ -707,7 +1315,7 @@ pub mod defai_swap {
         bonus_state.claimed = true;
         
         // Emit redemption event
-        emit!(RedemptionExecuted {
+        emit_cpi_versioned!(event_cpi_of!(ctx), RedemptionExecuted {
             user: ctx.accounts.user.key(),
             nft_mint: ctx.accounts.nft_mint.key(),
             amount_returned: amount_to_transfer,
@@ -720,8 +1328,6 @@ pub mod defai_swap {
             bonus_state.fee_deducted, 
             amount_to_transfer
         );
-        msg!("NFT burned and account closed - redemption complete and irreversible");
-        msg!("=== REDEEM V6 COMPLETE ===");
         Ok(())
     }
 
@@ -737,30 +1343,17 @@ pub mod defai_swap {
         
         let airdrop_vesting = &mut ctx.accounts.airdrop_vesting;
         let clock = Clock::get()?;
-        let config = &ctx.accounts.collection_config;
+        let config = ctx.accounts.collection_config.load()?;
         
         // Verify user hasn't already claimed
         require!(airdrop_vesting.beneficiary == Pubkey::default(), ErrorCode::AlreadyClaimed);
         
         // Verify merkle proof
-        let user_key = ctx.accounts.user.key();
-        let amount_bytes = amount.to_le_bytes();
-        let leaf_data = [user_key.as_ref(), &amount_bytes].concat();
-        let leaf = solana_program::keccak::hash(&leaf_data);
-        
-        let is_valid = merkle_proof.iter().fold(leaf.0, |acc, proof_elem| {
-            let mut combined = vec![];
-            if acc <= *proof_elem {
-                combined.extend_from_slice(&acc);
-                combined.extend_from_slice(proof_elem);
-            } else {
-                combined.extend_from_slice(proof_elem);
-                combined.extend_from_slice(&acc);
-            }
-            solana_program::keccak::hash(&combined).0
-        }) == config.airdrop_merkle_root;
-        
-        require!(is_valid, ErrorCode::InvalidMerkleProof);
+        let leaf = merkle_leaf(&ctx.accounts.user.key(), amount);
+        require!(
+            verify_merkle_proof(leaf, &merkle_proof, &config.airdrop_merkle_root),
+            ErrorCode::InvalidMerkleProof
+        );
         
         // Initialize vesting state
         airdrop_vesting.beneficiary = ctx.accounts.user.key();
@@ -769,9 +1362,26 @@ pub mod defai_swap {
         airdrop_vesting.start_timestamp = clock.unix_timestamp;
         airdrop_vesting.end_timestamp = clock.unix_timestamp + VESTING_DURATION;
         airdrop_vesting.last_claimed_timestamp = clock.unix_timestamp;
+        airdrop_vesting.payout_wallet = None;
+        
+        // Reserve the allocation against escrow so admins can see commitments outrunning funding
+        let ledger = &mut ctx.accounts.escrow_ledger;
+        ledger.reserved_airdrop = ledger.reserved_airdrop
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let escrow_balance = ctx.accounts.escrow_token_account.amount;
+        if escrow_balance < ledger.reserved_airdrop {
+            msg!("WARNING: escrow holds {} but {} is reserved for airdrops", escrow_balance, ledger.reserved_airdrop);
+            emit_versioned!(EscrowUnderfunded {
+                escrow_balance,
+                reserved: ledger.reserved_airdrop,
+                shortfall: ledger.reserved_airdrop - escrow_balance,
+                timestamp: clock.unix_timestamp,
+            });
+        }
         
         // Emit event
-        emit!(AirdropClaimed {
+        emit_cpi_versioned!(event_cpi_of!(ctx), AirdropClaimed {
             user: ctx.accounts.user.key(),
             amount,
             vesting_start: clock.unix_timestamp,
@@ -789,23 +1399,13 @@ pub mod defai_swap {
         let airdrop_vesting = &mut ctx.accounts.airdrop_vesting;
         let now = Clock::get()?.unix_timestamp;
         
-        // Check cliff period
-        let cliff_end = airdrop_vesting.start_timestamp + CLIFF_DURATION;
-        require!(now >= cliff_end, ErrorCode::StillInCliff);
-        
-        // Calculate vested amount
-        let elapsed = now - airdrop_vesting.start_timestamp;
-        let duration = airdrop_vesting.end_timestamp - airdrop_vesting.start_timestamp;
-        
-        let vested_amount = if elapsed >= duration {
-            airdrop_vesting.total_amount
-        } else {
-            (airdrop_vesting.total_amount as u128)
-                .checked_mul(elapsed as u128)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(duration as u128)
-                .ok_or(ErrorCode::MathOverflow)? as u64
-        };
+        // Cliff applies unless the allocation is dust
+        let vested_amount = vested_after_cliff(
+            airdrop_vesting.total_amount,
+            airdrop_vesting.start_timestamp,
+            airdrop_vesting.end_timestamp,
+            now,
+        )?;
         
         let claimable = vested_amount.saturating_sub(airdrop_vesting.released_amount);
         require!(claimable > 0, ErrorCode::NothingToClaim);
@@ -829,9 +1429,14 @@ pub mod defai_swap {
         // Update released amount
         airdrop_vesting.released_amount += claimable;
         airdrop_vesting.last_claimed_timestamp = now;
+        require_covers("airdrop vesting total", airdrop_vesting.total_amount, airdrop_vesting.released_amount)?;
+        
+        // Release the paid portion of the reservation
+        let ledger = &mut ctx.accounts.escrow_ledger;
+        ledger.reserved_airdrop = ledger.reserved_airdrop.saturating_sub(claimable);
         
         // Emit event
-        emit!(AirdropVestingClaimed {
+        emit_cpi_versioned!(event_cpi_of!(ctx), AirdropVestingClaimed {
             user: ctx.accounts.user.key(),
             amount_claimed: claimable,
             total_vested: vested_amount,
@@ -851,23 +1456,13 @@ pub mod defai_swap {
         let vesting_state = &mut ctx.accounts.vesting_state;
         let clock = Clock::get()?;
         
-        // Check cliff period
-        let cliff_end = vesting_state.start_timestamp + CLIFF_DURATION;
-        require!(clock.unix_timestamp >= cliff_end, ErrorCode::StillInCliff);
-        
-        // Calculate vested amount
-        let elapsed = clock.unix_timestamp.saturating_sub(vesting_state.start_timestamp);
-        let duration = vesting_state.end_timestamp.saturating_sub(vesting_state.start_timestamp);
-        
-        let vested_amount = if elapsed >= duration {
-            vesting_state.total_amount
-        } else {
-            vesting_state.total_amount
-                .checked_mul(elapsed as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(duration as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-        };
+        // Cliff applies unless the allocation is dust
+        let vested_amount = vested_after_cliff(
+            vesting_state.total_amount,
+            vesting_state.start_timestamp,
+            vesting_state.end_timestamp,
+            clock.unix_timestamp,
+        )?;
         
         let claimable = vested_amount.saturating_sub(vesting_state.released_amount);
         require!(claimable > 0, ErrorCode::NothingToClaim);
@@ -890,9 +1485,10 @@ pub mod defai_swap {
         // Update state
         vesting_state.released_amount += claimable;
         vesting_state.last_claimed_timestamp = clock.unix_timestamp;
+        require_covers("vesting total", vesting_state.total_amount, vesting_state.released_amount)?;
         
         // Emit vesting claim event
-        emit!(VestingClaimed {
+        emit_cpi_versioned!(event_cpi_of!(ctx), VestingClaimed {
             user: ctx.accounts.user.key(),
             nft_mint: ctx.accounts.nft_mint.key(),
             amount_claimed: claimable,
@@ -905,32 +1501,282 @@ pub mod defai_swap {
         Ok(())
     }
 
-    pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
-        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+    /// Use the claimable vested balance of an existing NFT as payment for a new tier swap.
+    /// The tokens never leave escrow: tax is paid from escrow to treasury and the net stays put.
+    pub fn compound_vested_into_swap(
+        ctx: Context<CompoundVestedIntoSwap>,
+        tier: u8,
+    ) -> Result<()> {
+        msg!("=== COMPOUND VESTED INTO SWAP START ===");
+        require!(tier < 5, ErrorCode::InvalidTier);
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
         
-        let escrow_seeds = &[b"escrow" as &[u8], &[ctx.accounts.escrow.bump][..]];
-        let signer_seeds = &[&escrow_seeds[..]];
+        let mut config = ctx.accounts.collection_config.load_mut()?;
+        let user_tax = &mut ctx.accounts.user_tax_state;
+        let source_vesting = &mut ctx.accounts.source_vesting_state;
+        let clock = Clock::get()?;
         
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.source_vault.to_account_info(),
-                to: ctx.accounts.dest.to_account_info(),
-                authority: ctx.accounts.escrow.to_account_info(),
-            },
-            signer_seeds,
-        );
-        token::transfer(cpi_ctx, amount)?;
+        // Limit swaps per slot to blunt result-sniping right after a randomness reveal
+        user_tax.record_swap_in_slot(clock.slot)?;
         
-        // Emit admin action event
-        emit!(AdminAction {
-            admin: ctx.accounts.admin.key(),
-            action: format!("Withdraw {} tokens", amount),
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        // Compounding is only available once the tier is open to the public
+        enforce_tier_phase(
+            &ctx.accounts.tier_phase,
+            &ctx.accounts.user.key(),
+            &[],
+            user_tax,
+            tier,
+            clock.unix_timestamp,
+        )?;
         
-        Ok(())
-    }
+        // Check supply - for tier 0, check remaining supply after reserving for OG holders
+        if tier == 0 {
+            let remaining_supply = config.tier_supplies[0].saturating_sub(config.og_tier_0_supply);
+            require!(
+                config.tier_minted[0] < remaining_supply,
+                ErrorCode::NoLiquidity
+            );
+        } else {
+            require!(
+                config.tier_minted[tier as usize] < config.tier_supplies[tier as usize],
+                ErrorCode::NoLiquidity
+            );
+        }
+        
+        // Compute the claimable balance of the source NFT exactly as claim_vested_v6 does
+        let vested_amount = vested_after_cliff(
+            source_vesting.total_amount,
+            source_vesting.start_timestamp,
+            source_vesting.end_timestamp,
+            clock.unix_timestamp,
+        )?;
+        let claimable = vested_amount.saturating_sub(source_vesting.released_amount);
+        
+        let price = config.tier_prices[tier as usize];
+        require!(claimable >= price, ErrorCode::InsufficientVestedForCompound);
+        
+        // Check and reset tax if 24 hours passed
+        if tax_reset_due(user_tax.last_swap_timestamp, clock.unix_timestamp) {
+            user_tax.tax_rate_bps = INITIAL_TAX_BPS;
+            user_tax.swap_count = 0;
+        }
+        
+        let tax_amount = bps_of(price, user_tax.tax_rate_bps as u64)?;
+        
+        // Pay tax out of escrow, splitting off the buyback share; the net
+        // portion simply stays escrowed
+        let buyback_amount = buyback_tax_share(&ctx.accounts.buyback_state, &ctx.accounts.buyback_vault, tax_amount)?;
+        let treasury_amount = tax_amount.checked_sub(buyback_amount).ok_or(ErrorCode::MathOverflow)?;
+        let escrow_seeds = &[b"escrow" as &[u8], &[ctx.accounts.escrow.bump][..]];
+        let signer_seeds = &[&escrow_seeds[..]];
+        if treasury_amount > 0 {
+            let cpi_ctx_tax = CpiContext::new_with_signer(
+                ctx.accounts.token_program_2022.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_defai_ata.to_account_info(),
+                    to: ctx.accounts.treasury_defai_ata.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token22::transfer_checked(cpi_ctx_tax, treasury_amount, 6)?;
+            ctx.accounts.treasury_ledger.record(RevenueSource::SwapTax, treasury_amount, clock.unix_timestamp, &event_cpi_of!(ctx))?;
+        }
+        if buyback_amount > 0 {
+            let cpi_ctx_buyback = CpiContext::new_with_signer(
+                ctx.accounts.token_program_2022.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_defai_ata.to_account_info(),
+                    to: ctx.accounts.buyback_vault.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token22::transfer_checked(cpi_ctx_buyback, buyback_amount, 6)?;
+        }
+        
+        // Consume the compounded amount from the source vesting
+        source_vesting.released_amount = source_vesting.released_amount
+            .checked_add(price)
+            .ok_or(ErrorCode::MathOverflow)?;
+        source_vesting.last_claimed_timestamp = clock.unix_timestamp;
+        require_covers("source vesting total", source_vesting.total_amount, source_vesting.released_amount)?;
+        
+        // Generate random bonus using VRF when enabled; otherwise fallback
+        let (min_bonus, max_bonus) = get_tier_bonus_range(tier);
+        let random_value = if ctx.accounts.config.vrf_enabled {
+            require!(!ctx.accounts.randomness_state.is_pending && ctx.accounts.randomness_state.revealed_value != [0u8; 32], ErrorCode::RandomnessNotReady);
+            emit_randomness_consumed(
+                &ctx.accounts.randomness_state,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+            );
+            generate_vrf_random(
+                &ctx.accounts.randomness_state.revealed_value,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+            )
+        } else {
+            let recent_blockhash = ctx.accounts.recent_blockhashes.data.borrow();
+            let blockhash_bytes: [u8; 32] = recent_blockhash[8..40].try_into().unwrap();
+            generate_secure_random(
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+                &blockhash_bytes,
+            )
+        };
+        let random_bonus = calculate_random_bonus(random_value, min_bonus, max_bonus);
+        
+        // Set up bonus state
+        let bonus_state = &mut ctx.accounts.bonus_state;
+        bonus_state.mint = ctx.accounts.nft_mint.key();
+        bonus_state.tier = tier;
+        bonus_state.bonus_bps = random_bonus;
+        bonus_state.vesting_start = clock.unix_timestamp;
+        bonus_state.vesting_duration = VESTING_DURATION;
+        bonus_state.claimed = false;
+        bonus_state.fee_deducted = 0;
+        
+        // Set up vesting state
+        let vesting_state = &mut ctx.accounts.vesting_state;
+        let vesting_amount = bps_of(price, bonus_state.bonus_bps as u64)?;
+        
+        vesting_state.mint = ctx.accounts.nft_mint.key();
+        vesting_state.total_amount = vesting_amount;
+        vesting_state.released_amount = 0;
+        vesting_state.start_timestamp = clock.unix_timestamp;
+        vesting_state.end_timestamp = clock.unix_timestamp + VESTING_DURATION;
+        vesting_state.last_claimed_timestamp = clock.unix_timestamp;
+        vesting_state.payout_wallet = None;
+        
+        // Dust-sized bonuses are paid out immediately instead of vesting behind
+        // the cliff, and their vesting account is closed again
+        if is_dust_vesting(vesting_amount) {
+            pay_out_dust_vesting(
+                &ctx.accounts.escrow,
+                &ctx.accounts.escrow_defai_ata,
+                &ctx.accounts.user_defai_ata,
+                &ctx.accounts.defai_mint,
+                &ctx.accounts.token_program_2022,
+                vesting_amount,
+            )?;
+            
+            emit_cpi_versioned!(event_cpi_of!(ctx), VestingClaimed {
+                user: ctx.accounts.user.key(),
+                nft_mint: ctx.accounts.nft_mint.key(),
+                amount_claimed: vesting_amount,
+                total_vested: vesting_amount,
+                timestamp: clock.unix_timestamp,
+            });
+            ctx.accounts.vesting_state.close(ctx.accounts.user.to_account_info())?;
+            
+            msg!("Dust vesting of {} paid out immediately", vesting_amount);
+        }
+        
+        // Update user tax for next swap
+        user_tax.tax_rate_bps = next_tax_bps(user_tax.tax_rate_bps);
+        user_tax.swap_count += 1;
+        user_tax.last_swap_timestamp = clock.unix_timestamp;
+        
+        config.tier_minted[tier as usize] += 1;
+        emit_tier_supply_events(&config, tier, clock.unix_timestamp);
+        
+        emit_cpi_versioned!(event_cpi_of!(ctx), VestedCompounded {
+            user: ctx.accounts.user.key(),
+            source_nft_mint: ctx.accounts.source_nft_mint.key(),
+            amount: price,
+            remaining_claimable: claimable - price,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        emit_cpi_versioned!(event_cpi_of!(ctx), SwapExecuted {
+            user: ctx.accounts.user.key(),
+            tier,
+            price,
+            tax_amount,
+            bonus_bps: bonus_state.bonus_bps,
+            nft_mint: ctx.accounts.nft_mint.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("=== COMPOUND VESTED INTO SWAP COMPLETE ===");
+        Ok(())
+    }
+
+    /// Route future vesting claims for an NFT to a separate wallet (None resets to the holder)
+    pub fn set_vesting_payout_wallet(ctx: Context<SetVestingPayoutWallet>, payout_wallet: Option<Pubkey>) -> Result<()> {
+        let vesting_state = &mut ctx.accounts.vesting_state;
+        vesting_state.payout_wallet = payout_wallet;
+        
+        emit_versioned!(PayoutWalletUpdated {
+            owner: ctx.accounts.user.key(),
+            vesting_account: vesting_state.key(),
+            payout_wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    /// Estate heir of a bonus NFT takes over its vesting: clears the payout wallet and claims what has vested
+    pub fn claim_inherited_vesting(ctx: Context<ClaimInheritedVesting>) -> Result<()> {
+        inheritance::claim_inherited_vesting(ctx)
+    }
+
+    /// Route future airdrop vesting claims to a separate wallet (None resets to the beneficiary)
+    pub fn set_airdrop_payout_wallet(ctx: Context<SetAirdropPayoutWallet>, payout_wallet: Option<Pubkey>) -> Result<()> {
+        let airdrop_vesting = &mut ctx.accounts.airdrop_vesting;
+        airdrop_vesting.payout_wallet = payout_wallet;
+        
+        emit_versioned!(PayoutWalletUpdated {
+            owner: ctx.accounts.user.key(),
+            vesting_account: airdrop_vesting.key(),
+            payout_wallet,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn admin_withdraw(ctx: Context<AdminWithdraw>, amount: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
+        
+        let escrow_seeds = &[b"escrow" as &[u8], &[ctx.accounts.escrow.bump][..]];
+        let signer_seeds = &[&escrow_seeds[..]];
+        
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source_vault.to_account_info(),
+                to: ctx.accounts.dest.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(cpi_ctx, amount)?;
+        
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminWithdrawal,
+            ctx.accounts.admin.key(),
+            ctx.accounts.source_vault.key(),
+            ctx.accounts.dest.key(),
+            amount,
+            [0; 32],
+        )?;
+        
+        // Emit admin action event
+        emit_versioned!(AdminAction {
+            admin: ctx.accounts.admin.key(),
+            action: format!("Withdraw {} tokens", amount),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
 
     pub fn admin_withdraw_token2022(ctx: Context<AdminWithdrawToken2022>, amount: u64) -> Result<()> {
         require_keys_eq!(ctx.accounts.admin.key(), ctx.accounts.config.admin, ErrorCode::Unauthorized);
@@ -952,8 +1798,17 @@ pub mod defai_swap {
         // Transfer with 6 decimals (standard for DEFAI tokens)
         token22::transfer_checked(cpi_ctx, amount, 6)?;
         
+        ctx.accounts.audit_log.record(
+            AuditAction::AdminWithdrawal,
+            ctx.accounts.admin.key(),
+            ctx.accounts.source_vault.key(),
+            ctx.accounts.dest.key(),
+            amount,
+            [0; 32],
+        )?;
+        
         // Emit admin action event
-        emit!(AdminAction {
+        emit_versioned!(AdminAction {
             admin: ctx.accounts.admin.key(),
             action: format!("Withdraw {} Token-2022 tokens", amount),
             timestamp: Clock::get()?.unix_timestamp,
@@ -988,29 +1843,19 @@ pub mod defai_swap {
         let clock = Clock::get()?;
         
         // Calculate vested amount
-        let elapsed = clock.unix_timestamp.saturating_sub(vesting_state.start_timestamp);
-        let duration = vesting_state.end_timestamp.saturating_sub(vesting_state.start_timestamp);
-        
-        let vested_amount = if elapsed >= duration {
-            vesting_state.total_amount
-        } else {
-            vesting_state.total_amount
-                .checked_mul(elapsed as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(duration as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-        };
+        let vested_amount = vested_amount(
+            vesting_state.total_amount,
+            vesting_state.start_timestamp,
+            vesting_state.end_timestamp,
+            clock.unix_timestamp,
+        );
         
         // Get unreleased amount
         let unreleased = vested_amount.saturating_sub(vesting_state.released_amount);
         require!(unreleased > 0, ErrorCode::NothingToClaim);
         
         // Calculate tax based on base price (not including bonus)
-        let tax_amount = (base_price as u128)
-            .checked_mul(user_tax.tax_rate_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let tax_amount = bps_of(base_price, user_tax.tax_rate_bps as u64)?;
         
         // Store old bonus for logging
         let old_bonus_bps = bonus_state.bonus_bps;
@@ -1022,6 +1867,12 @@ pub mod defai_swap {
         // Use VRF randomness when enabled; otherwise fallback
         let random_value = if ctx.accounts.config.vrf_enabled {
             require!(!ctx.accounts.randomness_state.is_pending && ctx.accounts.randomness_state.revealed_value != [0u8; 32], ErrorCode::RandomnessNotReady);
+            emit_randomness_consumed(
+                &ctx.accounts.randomness_state,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.nft_mint.key(),
+                &clock,
+            );
             generate_vrf_random(
                 &ctx.accounts.randomness_state.revealed_value,
                 &ctx.accounts.user.key(),
@@ -1048,11 +1899,7 @@ pub mod defai_swap {
             .ok_or(ErrorCode::MathOverflow)?;
         
         // Update vesting state
-        let new_vesting_amount = (base_price as u128)
-            .checked_mul(random_bonus as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let new_vesting_amount = bps_of(base_price, random_bonus as u64)?;
         
         vesting_state.total_amount = new_vesting_amount;
         vesting_state.released_amount = 0;
@@ -1061,9 +1908,7 @@ pub mod defai_swap {
         vesting_state.last_claimed_timestamp = clock.unix_timestamp;
         
         // Increment user's tax rate for next time (max 3000 bps = 30%)
-        user_tax.tax_rate_bps = user_tax.tax_rate_bps
-            .saturating_add(TAX_INCREMENT_BPS)
-            .min(TAX_CAP_BPS);
+        user_tax.tax_rate_bps = next_tax_bps(user_tax.tax_rate_bps);
         
         msg!("Rerolled NFT {} from {}% to {}% bonus (fee: {} DEFAI deducted from future redemption)", 
             ctx.accounts.nft_mint.key(), 
@@ -1074,8 +1919,24 @@ pub mod defai_swap {
         
         msg!("User tax rate increased to {}%", user_tax.tax_rate_bps as f64 / 100.0);
         
+        // Log the reroll as a compressed receipt so secondary buyers can inspect it
+        let receipt = RerollReceipt {
+            nft_mint: ctx.accounts.nft_mint.key(),
+            user: ctx.accounts.user.key(),
+            old_bonus_bps,
+            new_bonus_bps: random_bonus,
+            fee: tax_amount,
+            timestamp: clock.unix_timestamp,
+        };
+        ctx.accounts.receipt_log.append(
+            ReceiptKind::BonusReroll,
+            receipt.try_to_vec()?,
+            clock.unix_timestamp,
+            &event_cpi_of!(ctx),
+        )?;
+        
         // Emit reroll event
-        emit!(BonusRerolled {
+        emit_cpi_versioned!(event_cpi_of!(ctx), BonusRerolled {
             user: ctx.accounts.user.key(),
             nft_mint: ctx.accounts.nft_mint.key(),
             old_bonus_bps,
@@ -1088,6 +1949,37 @@ pub mod defai_swap {
         Ok(())
     }
 
+    /// Read-only preview of a DEFAI swap for the given tier and user, returned via return data
+    pub fn quote_swap(ctx: Context<QuoteSwap>, tier: u8, user: Pubkey) -> Result<SwapQuote> {
+        require!(tier < 5, ErrorCode::InvalidTier);
+        
+        let config = ctx.accounts.collection_config.load()?;
+        let now = Clock::get()?.unix_timestamp;
+        
+        // Mirror the tax reset logic applied in swap_defai_for_pnft_v6
+        let tax_bps = match &ctx.accounts.user_tax_state {
+            Some(user_tax) => effective_tax_bps(user_tax.tax_rate_bps, user_tax.last_swap_timestamp, now),
+            None => INITIAL_TAX_BPS,
+        };
+        
+        let price = config.tier_prices[tier as usize];
+        let tax_amount = bps_of(price, tax_bps as u64)?;
+        let net_amount = price.checked_sub(tax_amount).ok_or(ErrorCode::MathOverflow)?;
+        
+        let (min_bonus_bps, max_bonus_bps) = get_tier_bonus_range(tier);
+        
+        Ok(SwapQuote {
+            user,
+            tier,
+            price,
+            tax_bps,
+            tax_amount,
+            net_amount,
+            min_bonus_bps,
+            max_bonus_bps,
+        })
+    }
+
     pub fn update_nft_metadata_v6(ctx: Context<UpdateNftMetadataV6>) -> Result<()> {
         msg!("=== UPDATE NFT METADATA V6 START ===");
         
@@ -1099,15 +1991,12 @@ pub mod defai_swap {
         let elapsed = clock.unix_timestamp.saturating_sub(vesting_state.start_timestamp);
         let duration = vesting_state.end_timestamp.saturating_sub(vesting_state.start_timestamp);
         
-        let vested_amount = if elapsed >= duration {
-            vesting_state.total_amount
-        } else {
-            vesting_state.total_amount
-                .checked_mul(elapsed as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(duration as u64)
-                .ok_or(ErrorCode::MathOverflow)?
-        };
+        let vested_amount = vested_amount(
+            vesting_state.total_amount,
+            vesting_state.start_timestamp,
+            vesting_state.end_timestamp,
+            clock.unix_timestamp,
+        );
         
         let remaining_vested = vested_amount.saturating_sub(vesting_state.released_amount);
         let days_remaining = if elapsed >= duration {
@@ -1134,18 +2023,101 @@ pub mod defai_swap {
     }
 }
 
-// Helper function to get bonus range for a tier
-fn get_tier_bonus_range(tier: u8) -> (u16, u16) {
-    match tier {
-        0 => (TIER_0_MIN_BONUS, TIER_0_MAX_BONUS),
-        1 => (TIER_1_MIN_BONUS, TIER_1_MAX_BONUS),
-        2 => (TIER_2_MIN_BONUS, TIER_2_MAX_BONUS),
-        3 => (TIER_3_MIN_BONUS, TIER_3_MAX_BONUS),
-        4 => (TIER_4_MIN_BONUS, TIER_4_MAX_BONUS),
-        _ => (0, 0),
+// Helper function to emit TierLowSupply / TierSoldOut when a mint crosses a threshold
+// Helper function to pay a dust-sized bonus out of escrow in one go
+fn pay_out_dust_vesting<'info>(
+    escrow: &Account<'info, Escrow>,
+    escrow_defai_ata: &InterfaceAccount<'info, TokenAccount2022>,
+    user_defai_ata: &InterfaceAccount<'info, TokenAccount2022>,
+    defai_mint: &AccountInfo<'info>,
+    token_program_2022: &Program<'info, Token2022>,
+    amount: u64,
+) -> Result<()> {
+    let escrow_seeds = &[b"escrow" as &[u8], &[escrow.bump][..]];
+    let signer_seeds = &[&escrow_seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program_2022.to_account_info(),
+        TransferChecked {
+            from: escrow_defai_ata.to_account_info(),
+            to: user_defai_ata.to_account_info(),
+            authority: escrow.to_account_info(),
+            mint: defai_mint.clone(),
+        },
+        signer_seeds,
+    );
+    token22::transfer_checked(cpi_ctx, amount, 6)
+}
+
+fn emit_tier_supply_events(config: &CollectionConfig, tier: u8, timestamp: i64) {
+    // Tier 0 public supply excludes the reserve held back for OG holders
+    let supply = if tier == 0 {
+        config.tier_supplies[0].saturating_sub(config.og_tier_0_supply)
+    } else {
+        config.tier_supplies[tier as usize]
+    };
+    let minted = config.tier_minted[tier as usize];
+    let remaining = supply.saturating_sub(minted);
+    
+    if remaining == 0 {
+        emit_versioned!(TierSoldOut {
+            tier,
+            supply,
+            timestamp,
+        });
+        return;
+    }
+    
+    let is_low = |left: u16| (left as u32) * 10000 < (supply as u32) * LOW_SUPPLY_THRESHOLD_BPS;
+    if is_low(remaining) && !is_low(remaining + 1) {
+        emit_versioned!(TierLowSupply {
+            tier,
+            remaining,
+            supply,
+            timestamp,
+        });
     }
 }
 
+// Helper function to store a tier string in a fixed, zero-padded field
+fn fixed_bytes<const N: usize>(value: &str) -> Result<[u8; N]> {
+    require!(value.len() <= N, ErrorCode::TierMetadataTooLong);
+    let mut bytes = [0u8; N];
+    bytes[..value.len()].copy_from_slice(value.as_bytes());
+    Ok(bytes)
+}
+
+// Helper function to enforce a tier's presale phase. Tiers without a TierPhase
+// account are public; otherwise the tier is closed before presale_start,
+// whitelist-only until presale_end, and public afterwards.
+fn enforce_tier_phase(
+    tier_phase_info: &AccountInfo,
+    user: &Pubkey,
+    proof: &[[u8; 32]],
+    user_tax: &mut UserTaxState,
+    tier: u8,
+    now: i64,
+) -> Result<()> {
+    if tier_phase_info.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(*tier_phase_info.owner, crate::ID, ErrorCode::InvalidInput);
+    let phase = TierPhase::try_deserialize(&mut &tier_phase_info.try_borrow_data()?[..])?;
+    require!(now >= phase.presale_start, ErrorCode::TierNotOpen);
+    if now >= phase.presale_end {
+        return Ok(());
+    }
+    
+    let leaf = merkle_address_leaf(user);
+    require!(verify_merkle_proof(leaf, proof, &phase.merkle_root), ErrorCode::NotOnPresaleWhitelist);
+    
+    let purchased = &mut user_tax.presale_purchases[tier as usize];
+    if phase.max_per_wallet > 0 {
+        require!(*purchased < phase.max_per_wallet, ErrorCode::PresaleWalletLimitReached);
+    }
+    *purchased = purchased.saturating_add(1);
+    Ok(())
+}
+
 // Account structures
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -1227,6 +2199,62 @@ pub struct UpdateConfig<'info> {
     pub config: Account<'info, Config>,
 }
 
+#[derive(Accounts)]
+pub struct ChangeAdmin<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteVrfModeChange<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"randomness_state"],
+        bump = randomness_state.bump
+    )]
+    pub randomness_state: Account<'info, RandomnessState>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEscrowLedger<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + EscrowLedger::LEN,
+        seeds = [b"escrow_ledger"],
+        bump,
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeWhitelist<'info> {
     #[account(mut)]
@@ -1236,6 +2264,11 @@ pub struct InitializeWhitelist<'info> {
         bump
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"cluster_config"],
+        bump = cluster_config.bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
     #[account(
         init,
         payer = admin,
@@ -1247,6 +2280,33 @@ pub struct InitializeWhitelist<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(tier: u8)]
+pub struct ConfigureTierPhase<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + TierPhase::LEN,
+        seeds = [b"tier_phase".as_ref(), &[tier]],
+        bump
+    )]
+    pub tier_phase: Account<'info, TierPhase>,
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeUserTax<'info> {
     #[account(mut)]
@@ -1293,10 +2353,35 @@ pub struct InitializeCollection<'info> {
         seeds = [b"collection_config"],
         bump
     )]
-    pub collection_config: Account<'info, CollectionConfig>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct MigrateCollectionConfig<'info> {
+    pub authority: Signer<'info>,
+    // Authority is checked against the legacy data in the handler
+    #[account(
+        mut,
+        seeds = [b"collection_config"],
+        bump
+    )]
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTierMetadata<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"collection_config"],
+        bump,
+        constraint = collection_config.load()?.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SwapOgTier0ForPnftV6<'info> {
     #[account(mut)]
@@ -1309,7 +2394,7 @@ pub struct SwapOgTier0ForPnftV6<'info> {
     )]
     pub randomness_state: Box<Account<'info, RandomnessState>>,
     #[account(mut)]
-    pub collection_config: Box<Account<'info, CollectionConfig>>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     /// CHECK: NFT mint to be created
     pub nft_mint: AccountInfo<'info>,
     #[account(mut)]
@@ -1350,10 +2435,18 @@ pub struct SwapOgTier0ForPnftV6<'info> {
     pub recent_blockhashes: AccountInfo<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(tier: u8)]
 pub struct SwapDefaiForPnftV6<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: Optional presale phase for the tier; validated by seeds and deserialized when initialized
+    #[account(
+        seeds = [b"tier_phase".as_ref(), &[tier]],
+        bump
+    )]
+    pub tier_phase: AccountInfo<'info>,
     #[account(mut)]
     pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
     #[account(
@@ -1366,9 +2459,28 @@ pub struct SwapDefaiForPnftV6<'info> {
         mut,
         // Validate treasury ATA matches the configured treasury
         token::mint = defai_mint,
-        token::authority = collection_config.treasury
+        token::authority = collection_config.load()?.treasury
     )]
     pub treasury_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
+    )]
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+    /// CHECK: Buyback state; empty until the buyback is initialized, deserialized in the handler
+    #[account(
+        seeds = [b"buyback_state"],
+        bump
+    )]
+    pub buyback_state: AccountInfo<'info>,
+    /// CHECK: Buyback vault, checked against the buyback state when it is initialized
+    #[account(
+        mut,
+        seeds = [b"buyback_vault"],
+        bump
+    )]
+    pub buyback_vault: AccountInfo<'info>,
     #[account(
         mut,
         // Validate escrow ATA is owned by the escrow PDA
@@ -1380,7 +2492,7 @@ pub struct SwapDefaiForPnftV6<'info> {
     pub defai_mint: AccountInfo<'info>,
     pub config: Account<'info, Config>,
     #[account(mut)]
-    pub collection_config: Box<Account<'info, CollectionConfig>>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     /// CHECK: NFT mint to be created
     pub nft_mint: AccountInfo<'info>,
     #[account(mut)]
@@ -1419,37 +2531,284 @@ pub struct SwapDefaiForPnftV6<'info> {
     pub recent_blockhashes: AccountInfo<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(tier: u8)]
 pub struct SwapOldDefaiForPnftV6<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: Optional presale phase for the tier; validated by seeds and deserialized when initialized
+    #[account(
+        seeds = [b"tier_phase".as_ref(), &[tier]],
+        bump
+    )]
+    pub tier_phase: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = user_old.owner == user.key() @ ErrorCode::Unauthorized,
+        constraint = user_old.mint == old_defai_mint.key() @ ErrorCode::InvalidMint
+    )]
+    pub user_old: Box<Account<'info, TokenAccount>>,
+    /// CHECK: The legacy OLD DEFAI mint; used to verify burn
+    #[account(
+        constraint = old_defai_mint.key() == collection_config.load()?.old_defai_mint @ ErrorCode::InvalidMint
+    )]
+    pub old_defai_mint: AccountInfo<'info>,
+    #[account(
+        mut,
+        // Escrow for OLD tokens held by the program; must be owned by escrow PDA and match OLD mint
+        constraint = escrow_old.owner == escrow.key() @ ErrorCode::Unauthorized,
+        constraint = escrow_old.mint == old_defai_mint.key() @ ErrorCode::InvalidMint
+    )]
+    pub escrow_old: Box<Account<'info, TokenAccount>>,
+    // DEFAI accounts used to pay out a dust-sized bonus at once
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = user
+    )]
+    pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = escrow
+    )]
+    pub escrow_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    /// CHECK: DEFAI mint
+    #[account(
+        constraint = defai_mint.key() == collection_config.load()?.defai_mint @ ErrorCode::InvalidMint
+    )]
+    pub defai_mint: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"randomness_state"],
+        bump = randomness_state.bump
+    )]
+    pub randomness_state: Account<'info, RandomnessState>,
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
+    /// CHECK: NFT mint to be created
+    pub nft_mint: AccountInfo<'info>,
+    #[account(mut)]
+    pub nft_token_account: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + BonusStateV6::LEN,
+        seeds = [b"bonus_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub bonus_state: Box<Account<'info, BonusStateV6>>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + VestingStateV6::LEN,
+        seeds = [b"vesting_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub vesting_state: Box<Account<'info, VestingStateV6>>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"user_tax", user.key().as_ref()],
+        bump
+    )]
+    pub user_tax_state: Box<Account<'info, UserTaxState>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+    /// CHECK: Sysvar for recent blockhashes
+    #[account(address = solana_program::sysvar::recent_blockhashes::ID)]
+    pub recent_blockhashes: AccountInfo<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RedeemV6<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: NFT mint - needs to be mutable for burn operation
+    #[account(mut)]
+    pub nft_mint: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = user_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
+        constraint = user_nft_ata.owner == user.key() @ ErrorCode::NoNft,
+        constraint = user_nft_ata.amount == 1 @ ErrorCode::NoNft
+    )]
+    pub user_nft_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = user
+    )]
+    pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = escrow
+    )]
+    pub escrow_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    /// CHECK: DEFAI mint
+    pub defai_mint: AccountInfo<'info>,
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"escrow"],
+        bump = escrow.bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    #[account(
+        mut,
+        seeds = [b"bonus_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub bonus_state: Box<Account<'info, BonusStateV6>>,
+    /// CHECK: Not read; a dust bonus's vesting account was closed at the swap
+    #[account(
+        seeds = [b"vesting_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub vesting_state: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimVestedV6<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: NFT mint
+    pub nft_mint: AccountInfo<'info>,
+    #[account(
+        constraint = user_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
+        constraint = user_nft_ata.owner == user.key() @ ErrorCode::NoNft,
+        constraint = user_nft_ata.amount == 1 @ ErrorCode::NoNft
+    )]
+    pub user_nft_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    /// CHECK: Wallet receiving the claim - the payout wallet when one is set, otherwise the NFT holder
+    #[account(
+        constraint = payout_owner.key() == vesting_state.payout_wallet.unwrap_or(user.key()) @ ErrorCode::InvalidPayoutWallet
+    )]
+    pub payout_owner: AccountInfo<'info>,
+    // Created on first claim so fresh wallets can claim in a single transaction
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = payout_owner,
+        associated_token::token_program = token_program_2022
+    )]
+    pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = escrow
+    )]
+    pub escrow_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    pub defai_mint: Box<InterfaceAccount<'info, Mint2022>>,
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [b"vesting_v6", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub vesting_state: Account<'info, VestingStateV6>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(tier: u8)]
+pub struct CompoundVestedIntoSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: Optional presale phase for the tier; validated by seeds and deserialized when initialized
+    #[account(
+        seeds = [b"tier_phase".as_ref(), &[tier]],
+        bump
+    )]
+    pub tier_phase: AccountInfo<'info>,
+    /// CHECK: NFT mint whose vested balance is compounded
+    pub source_nft_mint: AccountInfo<'info>,
+    #[account(
+        constraint = source_nft_ata.mint == source_nft_mint.key() @ ErrorCode::InvalidNft,
+        constraint = source_nft_ata.owner == user.key() @ ErrorCode::NoNft,
+        constraint = source_nft_ata.amount == 1 @ ErrorCode::NoNft
+    )]
+    pub source_nft_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    #[account(
+        mut,
+        seeds = [b"vesting_v6", source_nft_mint.key().as_ref()],
+        bump
+    )]
+    pub source_vesting_state: Box<Account<'info, VestingStateV6>>,
+    #[account(
+        mut,
+        seeds = [b"randomness_state"],
+        bump = randomness_state.bump
+    )]
+    pub randomness_state: Box<Account<'info, RandomnessState>>,
+    #[account(
+        mut,
+        // Validate treasury ATA matches the configured treasury
+        token::mint = defai_mint,
+        token::authority = collection_config.load()?.treasury
+    )]
+    pub treasury_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
     #[account(
         mut,
-        constraint = user_old.owner == user.key() @ ErrorCode::Unauthorized,
-        constraint = user_old.mint == old_defai_mint.key() @ ErrorCode::InvalidMint
+        seeds = [b"treasury_ledger"],
+        bump = treasury_ledger.bump
     )]
-    pub user_old: Box<Account<'info, TokenAccount>>,
-    /// CHECK: The legacy OLD DEFAI mint; used to verify burn
+    pub treasury_ledger: Box<Account<'info, TreasuryLedger>>,
+    /// CHECK: Buyback state; empty until the buyback is initialized, deserialized in the handler
     #[account(
-        constraint = old_defai_mint.key() == collection_config.old_defai_mint @ ErrorCode::InvalidMint
+        seeds = [b"buyback_state"],
+        bump
     )]
-    pub old_defai_mint: AccountInfo<'info>,
+    pub buyback_state: AccountInfo<'info>,
+    /// CHECK: Buyback vault, checked against the buyback state when it is initialized
     #[account(
         mut,
-        // Escrow for OLD tokens held by the program; must be owned by escrow PDA and match OLD mint
-        constraint = escrow_old.owner == escrow.key() @ ErrorCode::Unauthorized,
-        constraint = escrow_old.mint == old_defai_mint.key() @ ErrorCode::InvalidMint
+        seeds = [b"buyback_vault"],
+        bump
     )]
-    pub escrow_old: Box<Account<'info, TokenAccount>>,
+    pub buyback_vault: AccountInfo<'info>,
     #[account(
         mut,
-        seeds = [b"randomness_state"],
-        bump = randomness_state.bump
+        // Validate escrow ATA is owned by the escrow PDA
+        token::mint = defai_mint,
+        token::authority = escrow
     )]
-    pub randomness_state: Account<'info, RandomnessState>,
-    pub config: Account<'info, Config>,
+    pub escrow_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    // Receives a dust-sized bonus, paid out at once
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = user
+    )]
+    pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    /// CHECK: DEFAI mint
+    #[account(
+        constraint = defai_mint.key() == collection_config.load()?.defai_mint @ ErrorCode::InvalidMint
+    )]
+    pub defai_mint: AccountInfo<'info>,
+    pub config: Box<Account<'info, Config>>,
     #[account(mut)]
-    pub collection_config: Box<Account<'info, CollectionConfig>>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     /// CHECK: NFT mint to be created
     pub nft_mint: AccountInfo<'info>,
     #[account(mut)]
@@ -1474,7 +2833,7 @@ pub struct SwapOldDefaiForPnftV6<'info> {
         seeds = [b"escrow"],
         bump
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub escrow: Box<Account<'info, Escrow>>,
     #[account(
         mut,
         seeds = [b"user_tax", user.key().as_ref()],
@@ -1482,7 +2841,6 @@ pub struct SwapOldDefaiForPnftV6<'info> {
     )]
     pub user_tax_state: Box<Account<'info, UserTaxState>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
     pub token_program_2022: Program<'info, Token2022>,
     /// CHECK: Sysvar for recent blockhashes
     #[account(address = solana_program::sysvar::recent_blockhashes::ID)]
@@ -1490,14 +2848,11 @@ pub struct SwapOldDefaiForPnftV6<'info> {
 }
 
 #[derive(Accounts)]
-pub struct RedeemV6<'info> {
-    #[account(mut)]
+pub struct SetVestingPayoutWallet<'info> {
     pub user: Signer<'info>,
-    /// CHECK: NFT mint - needs to be mutable for burn operation
-    #[account(mut)]
+    /// CHECK: NFT mint
     pub nft_mint: AccountInfo<'info>,
     #[account(
-        mut,
         constraint = user_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
         constraint = user_nft_ata.owner == user.key() @ ErrorCode::NoNft,
         constraint = user_nft_ata.amount == 1 @ ErrorCode::NoNft
@@ -1505,78 +2860,22 @@ pub struct RedeemV6<'info> {
     pub user_nft_ata: InterfaceAccount<'info, TokenAccount2022>,
     #[account(
         mut,
-        token::mint = defai_mint,
-        token::authority = user
-    )]
-    pub user_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
-    #[account(
-        mut,
-        token::mint = defai_mint,
-        token::authority = escrow
-    )]
-    pub escrow_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
-    /// CHECK: DEFAI mint
-    pub defai_mint: AccountInfo<'info>,
-    pub config: Account<'info, Config>,
-    #[account(
-        seeds = [b"escrow"],
-        bump
-    )]
-    pub escrow: Account<'info, Escrow>,
-    #[account(
-        mut,
-        seeds = [b"bonus_v6", nft_mint.key().as_ref()],
-        bump
-    )]
-    pub bonus_state: Account<'info, BonusStateV6>,
-    #[account(
         seeds = [b"vesting_v6", nft_mint.key().as_ref()],
         bump
     )]
     pub vesting_state: Account<'info, VestingStateV6>,
-    pub system_program: Program<'info, System>,
-    pub token_program_2022: Program<'info, Token2022>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimVestedV6<'info> {
-    #[account(mut)]
+pub struct SetAirdropPayoutWallet<'info> {
     pub user: Signer<'info>,
-    /// CHECK: NFT mint
-    pub nft_mint: AccountInfo<'info>,
-    #[account(
-        constraint = user_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
-        constraint = user_nft_ata.owner == user.key() @ ErrorCode::NoNft,
-        constraint = user_nft_ata.amount == 1 @ ErrorCode::NoNft
-    )]
-    pub user_nft_ata: InterfaceAccount<'info, TokenAccount2022>,
-    #[account(
-        mut,
-        token::mint = defai_mint,
-        token::authority = user
-    )]
-    pub user_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
-    #[account(
-        mut,
-        token::mint = defai_mint,
-        token::authority = escrow
-    )]
-    pub escrow_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
-    /// CHECK: DEFAI mint
-    pub defai_mint: AccountInfo<'info>,
-    pub config: Account<'info, Config>,
-    #[account(
-        seeds = [b"escrow"],
-        bump
-    )]
-    pub escrow: Account<'info, Escrow>,
     #[account(
         mut,
-        seeds = [b"vesting_v6", nft_mint.key().as_ref()],
-        bump
+        seeds = [b"airdrop_vesting", user.key().as_ref()],
+        bump,
+        constraint = airdrop_vesting.beneficiary == user.key()
     )]
-    pub vesting_state: Account<'info, VestingStateV6>,
-    pub token_program_2022: Program<'info, Token2022>,
+    pub airdrop_vesting: Account<'info, AirdropVesting>,
 }
 
 #[derive(Accounts)]
@@ -1593,6 +2892,12 @@ pub struct AdminWithdraw<'info> {
         bump
     )]
     pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -1612,9 +2917,16 @@ pub struct AdminWithdrawToken2022<'info> {
         bump
     )]
     pub escrow: Account<'info, Escrow>,
+    #[account(
+        mut,
+        seeds = [AUDIT_LOG_SEED],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
     pub token_program_2022: Program<'info, Token2022>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RerollBonusV6<'info> {
     #[account(mut)]
@@ -1653,6 +2965,12 @@ pub struct RerollBonusV6<'info> {
         bump
     )]
     pub user_tax_state: Account<'info, UserTaxState>,
+    #[account(
+        mut,
+        seeds = [b"receipt_log"],
+        bump = receipt_log.bump
+    )]
+    pub receipt_log: Box<Account<'info, ReceiptLog>>,
     pub system_program: Program<'info, System>,
     #[account(
         mut,
@@ -1665,6 +2983,22 @@ pub struct RerollBonusV6<'info> {
     pub recent_blockhashes: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(tier: u8, user: Pubkey)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [b"collection_config"],
+        bump
+    )]
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
+    // Optional: users who have never swapped have no tax state yet
+    #[account(
+        seeds = [b"user_tax", user.as_ref()],
+        bump
+    )]
+    pub user_tax_state: Option<Account<'info, UserTaxState>>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateNftMetadataV6<'info> {
     /// CHECK: NFT mint
@@ -1681,6 +3015,7 @@ pub struct UpdateNftMetadataV6<'info> {
     pub vesting_state: Account<'info, VestingStateV6>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimAirdrop<'info> {
     #[account(mut)]
@@ -1697,15 +3032,33 @@ pub struct ClaimAirdrop<'info> {
         seeds = [b"collection_config"],
         bump
     )]
-    pub collection_config: Account<'info, CollectionConfig>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     #[account(
         seeds = [b"config"],
         bump
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"escrow"],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        // Escrow DEFAI account the airdrop will be paid from
+        token::authority = escrow,
+        token::mint = collection_config.load()?.defai_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger"],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimVestedAirdrop<'info> {
     #[account(mut)]
@@ -1717,21 +3070,28 @@ pub struct ClaimVestedAirdrop<'info> {
         constraint = airdrop_vesting.beneficiary == user.key()
     )]
     pub airdrop_vesting: Account<'info, AirdropVesting>,
+    /// CHECK: Wallet receiving the claim - the payout wallet when one is set, otherwise the beneficiary
     #[account(
-        mut,
-        token::mint = defai_mint,
-        token::authority = user
+        constraint = payout_owner.key() == airdrop_vesting.payout_wallet.unwrap_or(user.key()) @ ErrorCode::InvalidPayoutWallet
     )]
-    pub user_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
+    pub payout_owner: AccountInfo<'info>,
+    // Created on first claim so fresh wallets can claim in a single transaction
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = defai_mint,
+        associated_token::authority = payout_owner,
+        associated_token::token_program = token_program
+    )]
+    pub user_defai_ata: Box<InterfaceAccount<'info, TokenAccount2022>>,
     #[account(
         mut,
         // Ensure escrow token account is owned by escrow PDA and is the DEFAI mint
         token::authority = escrow,
         token::mint = defai_mint
     )]
-    pub escrow_token_account: InterfaceAccount<'info, TokenAccount2022>,
-    /// CHECK: DEFAI mint
-    pub defai_mint: AccountInfo<'info>,
+    pub escrow_token_account: Box<InterfaceAccount<'info, TokenAccount2022>>,
+    pub defai_mint: Box<InterfaceAccount<'info, Mint2022>>,
     #[account(
         seeds = [b"escrow"],
         bump
@@ -1741,13 +3101,21 @@ pub struct ClaimVestedAirdrop<'info> {
         seeds = [b"collection_config"],
         bump
     )]
-    pub collection_config: Account<'info, CollectionConfig>,
+    pub collection_config: AccountLoader<'info, CollectionConfig>,
     #[account(
         seeds = [b"config"],
         bump
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"escrow_ledger"],
+        bump = escrow_ledger.bump
+    )]
+    pub escrow_ledger: Account<'info, EscrowLedger>,
     pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 // State structs
@@ -1763,10 +3131,33 @@ pub struct Config {
     pub pending_admin: Option<Pubkey>,
     pub admin_change_timestamp: i64,
     pub vrf_enabled: bool,
+    pub pending_vrf_enabled: Option<bool>,
+    pub vrf_change_timestamp: i64,
+    pub old_conversion_ratio: ConversionRatio,              // OLD tokens owed per NEW token of tier price
+    pub pending_old_conversion_ratio: Option<ConversionRatio>,
+    pub conversion_ratio_change_timestamp: i64,
 }
 
 impl Config {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + (8 * 5) + 1 + 33 + 8 + 1;
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + (8 * 5) + 1 + 33 + 8 + 1 + 2 + 8 + 16 + 17 + 8;
+
+    // initialize and migrate_config start at 1:1 and proposals reject a zero
+    // term, so the denominator is never 0
+    pub fn old_amount_for(&self, price: u64) -> Result<u64> {
+        let ratio = &self.old_conversion_ratio;
+        let amount = (price as u128)
+            .checked_mul(ratio.numerator as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(ratio.denominator as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        u64::try_from(amount).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConversionRatio {
+    pub numerator: u64,
+    pub denominator: u64,
 }
 
 #[account]
@@ -1778,6 +3169,16 @@ impl Escrow {
     pub const LEN: usize = 1;
 }
 
+#[account]
+pub struct EscrowLedger {
+    pub reserved_airdrop: u64,  // Booked airdrop allocations not yet paid out
+    pub bump: u8,
+}
+
+impl EscrowLedger {
+    pub const LEN: usize = 8 + 1;
+}
+
 #[account]
 pub struct TaxState {
     pub current_bps: u16,
@@ -1808,29 +3209,55 @@ pub enum ProposalType {
     UpdateTreasury { new_treasury: Pubkey },
 }
 
-#[account]
+// Zero-copy: swaps read and bump the counters in place instead of decoding the
+// whole account. Tier strings are zero-padded UTF-8 in fixed fields (see
+// MAX_TIER_*_LEN). Fields are ordered by alignment so #[repr(C)] adds no
+// padding of its own.
+#[account(zero_copy)]
 pub struct CollectionConfig {
+    pub tier_prices: [u64; 5],
     pub authority: Pubkey,
     pub collection_mint: Pubkey,
     pub treasury: Pubkey,
     pub defai_mint: Pubkey,
     pub old_defai_mint: Pubkey,
-    pub tier_names: [String; 5],
-    pub tier_symbols: [String; 5],
-    pub tier_prices: [u64; 5],
-    pub tier_supplies: [u16; 5],
-    pub tier_minted: [u16; 5],
-    pub tier_uri_prefixes: [String; 5],
     // MAY20DEFAIHolders.csv: OG Tier 0 holders who can mint NFT + get 1:1 vesting from Quantity column
     pub og_tier_0_merkle_root: [u8; 32],
     // 10_1AIR-Sheet1.csv: Airdrop recipients who get vesting only (NO NFT) from AIRDROP column
     pub airdrop_merkle_root: [u8; 32],
+    pub tier_supplies: [u16; 5],
+    pub tier_minted: [u16; 5],
     pub og_tier_0_supply: u16,      // Reserved supply for OG holders
     pub og_tier_0_minted: u16,      // Counter for OG claims
+    pub tier_names: [[u8; MAX_TIER_NAME_LEN]; 5],
+    pub tier_symbols: [[u8; MAX_TIER_SYMBOL_LEN]; 5],
+    pub tier_uri_prefixes: [[u8; MAX_TIER_URI_PREFIX_LEN]; 5],
+    pub _padding: [u8; 6],          // Rounds the size up to a multiple of 8
 }
 
 impl CollectionConfig {
-    pub const LEN: usize = 32 + 32 + 32 + 32 + 32 + (64 * 5) + (10 * 5) + (8 * 5) + (2 * 5) + (2 * 5) + (200 * 5) + 32 + 32 + 2 + 2;  // Added 4 bytes for og_tier_0_supply and og_tier_0_minted
+    pub const LEN: usize = std::mem::size_of::<CollectionConfig>();
+}
+
+// Borsh layout CollectionConfig had before it became zero-copy; only read by
+// `migrate_collection_config`
+#[derive(AnchorDeserialize)]
+pub struct LegacyCollectionConfig {
+    pub authority: Pubkey,
+    pub collection_mint: Pubkey,
+    pub treasury: Pubkey,
+    pub defai_mint: Pubkey,
+    pub old_defai_mint: Pubkey,
+    pub tier_names: [String; 5],
+    pub tier_symbols: [String; 5],
+    pub tier_prices: [u64; 5],
+    pub tier_supplies: [u16; 5],
+    pub tier_minted: [u16; 5],
+    pub tier_uri_prefixes: [String; 5],
+    pub og_tier_0_merkle_root: [u8; 32],
+    pub airdrop_merkle_root: [u8; 32],
+    pub og_tier_0_supply: u16,
+    pub og_tier_0_minted: u16,
 }
 
 #[account]
@@ -1856,10 +3283,37 @@ pub struct VestingStateV6 {
     pub start_timestamp: i64,
     pub end_timestamp: i64,
     pub last_claimed_timestamp: i64,
+    pub payout_wallet: Option<Pubkey>,  // Optional destination for vesting claims
 }
 
 impl VestingStateV6 {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8;
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 33;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RerollEntry {
+    pub old_bonus_bps: u16,
+    pub new_bonus_bps: u16,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+impl RerollEntry {
+    pub const LEN: usize = 2 + 2 + 8 + 8;
+}
+
+// Per-NFT reroll log written before rerolls moved to the receipt log. No
+// longer created; existing accounts can be closed with close_reroll_history.
+#[account]
+pub struct RerollHistory {
+    pub mint: Pubkey,
+    pub total_rerolls: u32,
+    pub total_fees: u64,
+    pub entries: [RerollEntry; REROLL_HISTORY_LEN],  // Ring buffer of the most recent rerolls
+}
+
+impl RerollHistory {
+    pub const LEN: usize = 32 + 4 + 8 + (RerollEntry::LEN * REROLL_HISTORY_LEN);
 }
 
 #[account]
@@ -1868,10 +3322,47 @@ pub struct UserTaxState {
     pub tax_rate_bps: u16,
     pub last_swap_timestamp: i64,
     pub swap_count: u32,
+    pub last_swap_slot: u64,
+    pub slot_swap_count: u8,
+    pub presale_purchases: [u16; 5],  // Swaps made during each tier's presale window
 }
 
 impl UserTaxState {
-    pub const LEN: usize = 32 + 2 + 8 + 4;
+    pub const LEN: usize = 32 + 2 + 8 + 4 + 8 + 1 + (2 * 5);
+
+    // Back to the initial rate once TAX_RESET_DURATION has passed since the
+    // last swap. Returns the rate it replaced.
+    pub fn reset_tax(&mut self, now: i64) -> Result<u16> {
+        require!(tax_reset_due(self.last_swap_timestamp, now), ErrorCode::TaxResetTooEarly);
+        let old_rate = self.tax_rate_bps;
+        self.tax_rate_bps = INITIAL_TAX_BPS;
+        self.swap_count = 0;
+        Ok(old_rate)
+    }
+
+    pub fn record_swap_in_slot(&mut self, slot: u64) -> Result<()> {
+        if self.last_swap_slot != slot {
+            self.last_swap_slot = slot;
+            self.slot_swap_count = 0;
+        }
+        require!(self.slot_swap_count < MAX_SWAPS_PER_SLOT, ErrorCode::SlotRateLimited);
+        self.slot_swap_count += 1;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct TierPhase {
+    pub tier: u8,
+    pub presale_start: i64,
+    pub presale_end: i64,
+    pub merkle_root: [u8; 32],  // Leaves are keccak(wallet)
+    pub max_per_wallet: u16,    // 0 = unlimited
+    pub bump: u8,
+}
+
+impl TierPhase {
+    pub const LEN: usize = 1 + 8 + 8 + 32 + 2 + 1;
 }
 
 #[account]
@@ -1902,10 +3393,23 @@ pub struct AirdropVesting {
     pub start_timestamp: i64,
     pub end_timestamp: i64,
     pub last_claimed_timestamp: i64,
+    pub payout_wallet: Option<Pubkey>,  // Optional destination for vesting claims
 }
 
 impl AirdropVesting {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8;
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 8 + 8 + 33;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SwapQuote {
+    pub user: Pubkey,
+    pub tier: u8,
+    pub price: u64,
+    pub tax_bps: u16,
+    pub tax_amount: u64,
+    pub net_amount: u64,
+    pub min_bonus_bps: u16,
+    pub max_bonus_bps: u16,
 }
 
 #[error_code]
@@ -1952,6 +3456,8 @@ pub enum ErrorCode {
     InvalidMint,
     #[msg("Insufficient DEFAI balance for reroll. Must hold base tier amount.")]
     InsufficientDefaiForReroll,
+    // Retired variants stay so later codes keep their numbers; admin
+    // timelocks now fail with defai_common::CommonError
     #[msg("No pending admin change")]
     NoPendingAdminChange,
     #[msg("Timelock not expired")]
@@ -1964,12 +3470,37 @@ pub enum ErrorCode {
     InvalidNft,
     #[msg("Randomness not ready - generate randomness first")]
     RandomnessNotReady,
+    #[msg("No pending VRF mode change")]
+    NoPendingVrfModeChange,             // Retired
+    #[msg("Destination token account is not owned by the payout wallet")]
+    InvalidPayoutWallet,
+    #[msg("Claimable vested amount does not cover the tier price")]
+    InsufficientVestedForCompound,
+    #[msg("No pending conversion ratio change")]
+    NoPendingConversionRatio,           // Retired
+    #[msg("Too many swaps in this slot - try again shortly")]
+    SlotRateLimited,
+    #[msg("Tier is not open yet")]
+    TierNotOpen,
+    #[msg("User not on presale whitelist for this tier")]
+    NotOnPresaleWhitelist,
+    #[msg("Presale per-wallet limit reached")]
+    PresaleWalletLimitReached,
+    #[msg("Tier name, symbol or URI prefix is too long")]
+    TierMetadataTooLong,
+    #[msg("CollectionConfig already uses the zero-copy layout")]
+    CollectionConfigMigrated,
+    #[msg("Tax is already at the initial rate")]
+    TaxAlreadyReset,
+    #[msg("Account already uses the current layout")]
+    AccountAlreadyMigrated,
 }
 
 // ===== Events =====
 
 #[event]
 pub struct SwapExecuted {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub tier: u8,
     pub price: u64,
@@ -1981,6 +3512,7 @@ pub struct SwapExecuted {
 
 #[event]
 pub struct RedemptionExecuted {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub nft_mint: Pubkey,
     pub amount_returned: u64,
@@ -1990,6 +3522,7 @@ pub struct RedemptionExecuted {
 
 #[event]
 pub struct VestingClaimed {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub nft_mint: Pubkey,
     pub amount_claimed: u64,
@@ -1999,6 +3532,7 @@ pub struct VestingClaimed {
 
 #[event]
 pub struct BonusRerolled {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub nft_mint: Pubkey,
     pub old_bonus_bps: u16,
@@ -2009,6 +3543,7 @@ pub struct BonusRerolled {
 
 #[event]
 pub struct AdminAction {
+    pub schema_version: u8,
     pub admin: Pubkey,
     pub action: String,
     pub timestamp: i64,
@@ -2016,6 +3551,7 @@ pub struct AdminAction {
 
 #[event]
 pub struct TaxReset {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub old_rate_bps: u16,
     pub new_rate_bps: u16,
@@ -2024,6 +3560,7 @@ pub struct TaxReset {
 
 #[event]
 pub struct AirdropClaimed {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub amount: u64,
     pub vesting_start: i64,
@@ -2032,8 +3569,70 @@ pub struct AirdropClaimed {
 
 #[event]
 pub struct AirdropVestingClaimed {
+    pub schema_version: u8,
     pub user: Pubkey,
     pub amount_claimed: u64,
     pub total_vested: u64,
     pub timestamp: i64,
 }
+
+#[event]
+pub struct VrfModeChangeProposed {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub enabled: bool,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct VrfModeChanged {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayoutWalletUpdated {
+    pub schema_version: u8,
+    pub owner: Pubkey,
+    pub vesting_account: Pubkey,
+    pub payout_wallet: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedCompounded {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub source_nft_mint: Pubkey,
+    pub amount: u64,
+    pub remaining_claimable: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierLowSupply {
+    pub schema_version: u8,
+    pub tier: u8,
+    pub remaining: u16,
+    pub supply: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierSoldOut {
+    pub schema_version: u8,
+    pub tier: u8,
+    pub supply: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowUnderfunded {
+    pub schema_version: u8,
+    pub escrow_balance: u64,
+    pub reserved: u64,
+    pub shortfall: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    ErrorCode, CLIFF_DURATION, DUST_VESTING_THRESHOLD, INITIAL_TAX_BPS, TAX_CAP_BPS, TAX_INCREMENT_BPS,
+    TAX_RESET_DURATION, TIER_0_MAX_BONUS, TIER_0_MIN_BONUS, TIER_1_MAX_BONUS, TIER_1_MIN_BONUS, TIER_2_MAX_BONUS,
+    TIER_2_MIN_BONUS, TIER_3_MAX_BONUS, TIER_3_MIN_BONUS, TIER_4_MAX_BONUS, TIER_4_MIN_BONUS,
+};
+
+// Payout math shared by the swap, claim, compound, reroll and view
+// instructions. Everything here is pure so the proptest suite in
+// tests/payout_math.rs can pin it down.
+
+// Whether a vesting allocation is small enough to skip the cliff
+pub fn is_dust_vesting(amount: u64) -> bool {
+    amount > 0 && amount <= DUST_VESTING_THRESHOLD
+}
+
+// Linearly vested part of `total` at `now`: nothing before `start`, all of it
+// from `end` on (including schedules where end <= start). Computed in u128 so
+// large totals cannot overflow, and never exceeds `total`.
+pub fn vested_amount(total: u64, start: i64, end: i64, now: i64) -> u64 {
+    if now >= end {
+        return total;
+    }
+    if now <= start {
+        return 0;
+    }
+    let elapsed = now.abs_diff(start) as u128;
+    let duration = end.abs_diff(start) as u128;
+    (total as u128 * elapsed / duration) as u64
+}
+
+// Vested total a claim may draw against at `now`. Fails inside the cliff
+// unless the allocation is dust, which vests in full immediately.
+pub fn vested_after_cliff(total: u64, start: i64, end: i64, now: i64) -> Result<u64> {
+    if is_dust_vesting(total) {
+        return Ok(total);
+    }
+    require!(now >= start.saturating_add(CLIFF_DURATION), ErrorCode::StillInCliff);
+    Ok(vested_amount(total, start, end, now))
+}
+
+// Bonus range (basis points of the tier price) for a tier; unknown tiers get none
+pub fn get_tier_bonus_range(tier: u8) -> (u16, u16) {
+    match tier {
+        0 => (TIER_0_MIN_BONUS, TIER_0_MAX_BONUS),
+        1 => (TIER_1_MIN_BONUS, TIER_1_MAX_BONUS),
+        2 => (TIER_2_MIN_BONUS, TIER_2_MAX_BONUS),
+        3 => (TIER_3_MIN_BONUS, TIER_3_MAX_BONUS),
+        4 => (TIER_4_MIN_BONUS, TIER_4_MAX_BONUS),
+        _ => (0, 0),
+    }
+}
+
+// Whether a full reset period has passed since the user's last swap
+pub fn tax_reset_due(last_swap_timestamp: i64, now: i64) -> bool {
+    now.saturating_sub(last_swap_timestamp) >= TAX_RESET_DURATION
+}
+
+// Tax rate a swap at `now` is charged
+pub fn effective_tax_bps(rate_bps: u16, last_swap_timestamp: i64, now: i64) -> u16 {
+    if tax_reset_due(last_swap_timestamp, now) {
+        INITIAL_TAX_BPS
+    } else {
+        rate_bps
+    }
+}
+
+// Rate after a completed swap: one increment up, capped
+pub fn next_tax_bps(rate_bps: u16) -> u16 {
+    rate_bps.saturating_add(TAX_INCREMENT_BPS).min(TAX_CAP_BPS)
+}
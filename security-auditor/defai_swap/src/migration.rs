@@ -0,0 +1,258 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use defai_common::emit_versioned;
+
+use crate::{AdminAction, AirdropVesting, Config, ConversionRatio, ErrorCode, UserTaxState, VestingStateV6};
+
+// Account migrations
+// Accounts created before a struct gained fields are shorter than its current
+// LEN and no longer deserialize, so every instruction that loads them fails.
+// These instructions grow them in place to the current layout.
+
+// Grows a program account created under an older layout to `new_len`,
+// topping up rent from `payer`. Appended bytes are zero-filled, which decodes
+// as the default for fields added since. Returns the previous length.
+fn resize_legacy_account<'info>(
+    info: &AccountInfo<'info>,
+    discriminator: [u8; 8],
+    new_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<usize> {
+    require_keys_eq!(*info.owner, crate::ID);
+    {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == discriminator,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+    }
+
+    let old_len = info.data_len();
+    require!(old_len < new_len, ErrorCode::AccountAlreadyMigrated);
+
+    // Top up rent for the larger size
+    let rent_needed = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(info.lamports());
+    if rent_needed > 0 {
+        let transfer_ctx = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(transfer_ctx, rent_needed)?;
+    }
+    info.realloc(new_len, true)?;
+
+    Ok(old_len)
+}
+
+// Borsh layout Config had before the VRF timelock and the conversion ratio;
+// only read by `migrate_config`
+#[derive(AnchorDeserialize)]
+pub struct LegacyConfig {
+    pub admin: Pubkey,
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub collection: Pubkey,
+    pub treasury: Pubkey,
+    pub prices: [u64; 5],
+    pub paused: bool,
+    pub pending_admin: Option<Pubkey>,
+    pub admin_change_timestamp: i64,
+    pub vrf_enabled: bool,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: May predate the current Config layout; owner, discriminator and
+    /// admin are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Config holds an Option before the new fields, so bytes past the old
+// contents may be stale rather than zero. The legacy fields are decoded and
+// the whole account rewritten instead of relying on zero-fill.
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let info = ctx.accounts.config.to_account_info();
+    let legacy = {
+        let data = info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == Config::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        LegacyConfig::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(legacy.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+
+    let old_len = resize_legacy_account(
+        &info,
+        Config::DISCRIMINATOR,
+        8 + Config::LEN,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )?;
+
+    let config = Config {
+        admin: legacy.admin,
+        old_mint: legacy.old_mint,
+        new_mint: legacy.new_mint,
+        collection: legacy.collection,
+        treasury: legacy.treasury,
+        prices: legacy.prices,
+        paused: legacy.paused,
+        pending_admin: legacy.pending_admin,
+        admin_change_timestamp: legacy.admin_change_timestamp,
+        vrf_enabled: legacy.vrf_enabled,
+        pending_vrf_enabled: None,
+        vrf_change_timestamp: 0,
+        // OLD tokens were exchanged 1:1 before the ratio existed
+        old_conversion_ratio: ConversionRatio { numerator: 1, denominator: 1 },
+        pending_old_conversion_ratio: None,
+        conversion_ratio_change_timestamp: 0,
+    };
+    {
+        let mut data = info.try_borrow_mut_data()?;
+        data.fill(0);
+        config.try_serialize(&mut &mut data[..])?;
+    }
+
+    msg!("Config migrated from {} to {} bytes", old_len, 8 + Config::LEN);
+
+    emit_versioned!(AdminAction {
+        admin: ctx.accounts.admin.key(),
+        action: "Migrate config to the current layout".to_string(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(nft_mint: Pubkey)]
+pub struct MigrateVestingState<'info> {
+    /// CHECK: May predate the current VestingStateV6 layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"vesting_v6", nft_mint.as_ref()],
+        bump
+    )]
+    pub vesting_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct MigrateAirdropVesting<'info> {
+    /// CHECK: May predate the current AirdropVesting layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"airdrop_vesting", beneficiary.as_ref()],
+        bump
+    )]
+    pub airdrop_vesting: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Resizes an NFT's vesting to the current VestingStateV6::LEN. Anyone can pay
+// for it; the appended payout_wallet stays zeroed (None), so claims keep going
+// to the NFT holder.
+pub fn migrate_vesting_state(ctx: Context<MigrateVestingState>, nft_mint: Pubkey) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.vesting_state.to_account_info(),
+        VestingStateV6::DISCRIMINATOR,
+        8 + VestingStateV6::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    msg!(
+        "Vesting of NFT {} migrated from {} to {} bytes",
+        nft_mint,
+        old_len,
+        8 + VestingStateV6::LEN
+    );
+    Ok(())
+}
+
+// Resizes an airdrop vesting to the current AirdropVesting::LEN; as above, the
+// payout wallet starts unset
+pub fn migrate_airdrop_vesting(ctx: Context<MigrateAirdropVesting>, beneficiary: Pubkey) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.airdrop_vesting.to_account_info(),
+        AirdropVesting::DISCRIMINATOR,
+        8 + AirdropVesting::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    msg!(
+        "Airdrop vesting of {} migrated from {} to {} bytes",
+        beneficiary,
+        old_len,
+        8 + AirdropVesting::LEN
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct MigrateUserTaxState<'info> {
+    /// CHECK: May predate the current UserTaxState layout; owner and
+    /// discriminator are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"user_tax", user.as_ref()],
+        bump
+    )]
+    pub user_tax_state: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Resizes a user's tax state to the current UserTaxState::LEN, covering both
+// the slot rate limit and the presale counters. They start zeroed: no swaps
+// in the current slot and no presale purchases.
+pub fn migrate_user_tax_state(ctx: Context<MigrateUserTaxState>, user: Pubkey) -> Result<()> {
+    let old_len = resize_legacy_account(
+        &ctx.accounts.user_tax_state.to_account_info(),
+        UserTaxState::DISCRIMINATOR,
+        8 + UserTaxState::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    msg!(
+        "Tax state of {} migrated from {} to {} bytes",
+        user,
+        old_len,
+        8 + UserTaxState::LEN
+    );
+    Ok(())
+}
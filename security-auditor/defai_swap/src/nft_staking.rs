@@ -0,0 +1,530 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{self as token22, Token2022},
+    token_interface::{Mint, TokenAccount as TokenAccount2022, TransferChecked, CloseAccount},
+};
+
+use crate::{BonusStateV6, Config};
+use defai_common::{emit_cpi_versioned, emit_versioned, event_cpi_of};
+
+// Bonus-NFT Staking
+// Un-redeemed bonus NFTs can be locked in a program vault to accrue DEFAI
+// rewards from an admin-funded reward pool. Rewards are distributed per
+// second in proportion to each position's tier weight.
+
+// Scaling factor for the accumulated reward-per-weight value
+const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+#[account]
+pub struct NftStakingPool {
+    pub bump: u8,
+    pub authority: Pubkey,
+    pub defai_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_rate_per_second: u64,  // DEFAI emitted per second across all stakers
+    pub tier_weights: [u16; 5],       // Relative weight of each bonus tier
+    pub total_weight: u64,
+    pub acc_reward_per_weight: u128,  // Scaled by REWARD_PRECISION
+    pub last_update_ts: i64,
+    pub total_staked: u32,
+}
+
+impl NftStakingPool {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + (2 * 5) + 8 + 16 + 8 + 4;
+
+    // Bring the reward accumulator up to date before any weight change or payout
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_update_ts {
+            return Ok(());
+        }
+        if self.total_weight > 0 {
+            let elapsed = (now - self.last_update_ts) as u128;
+            let increment = elapsed
+                .checked_mul(self.reward_rate_per_second as u128)
+                .ok_or(StakingError::MathOverflow)?
+                .checked_mul(REWARD_PRECISION)
+                .ok_or(StakingError::MathOverflow)?
+                .checked_div(self.total_weight as u128)
+                .ok_or(StakingError::MathOverflow)?;
+            self.acc_reward_per_weight = self.acc_reward_per_weight
+                .checked_add(increment)
+                .ok_or(StakingError::MathOverflow)?;
+        }
+        self.last_update_ts = now;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct NftStakePosition {
+    pub owner: Pubkey,
+    pub nft_mint: Pubkey,
+    pub tier: u8,
+    pub weight: u16,
+    pub reward_debt: u128,
+    pub unpaid_rewards: u64,  // Rewards earned but not paid because the vault ran dry
+    pub staked_at: i64,
+    pub bump: u8,
+}
+
+impl NftStakePosition {
+    pub const LEN: usize = 32 + 32 + 1 + 2 + 16 + 8 + 8 + 1;
+
+    fn accrued(&self, acc_reward_per_weight: u128) -> Result<u128> {
+        (self.weight as u128)
+            .checked_mul(acc_reward_per_weight)
+            .ok_or(StakingError::MathOverflow.into())
+            .map(|v| v / REWARD_PRECISION)
+    }
+
+    // Move everything earned since the last checkpoint into unpaid_rewards
+    pub fn settle(&mut self, acc_reward_per_weight: u128) -> Result<()> {
+        let accrued = self.accrued(acc_reward_per_weight)?;
+        let pending = accrued.saturating_sub(self.reward_debt);
+        self.unpaid_rewards = self.unpaid_rewards
+            .checked_add(u64::try_from(pending).map_err(|_| StakingError::MathOverflow)?)
+            .ok_or(StakingError::MathOverflow)?;
+        self.reward_debt = accrued;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeNftStaking<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ StakingError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + NftStakingPool::LEN,
+        seeds = [b"nft_staking_pool"],
+        bump
+    )]
+    pub staking_pool: Account<'info, NftStakingPool>,
+    #[account(
+        constraint = defai_mint.key() == config.new_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = defai_mint,
+        token::authority = staking_pool,
+        token::token_program = token_program_2022,
+        seeds = [b"nft_staking_rewards"],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount2022>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateNftStaking<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ StakingError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"nft_staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, NftStakingPool>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeBonusNft<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"nft_staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, NftStakingPool>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = user_nft_ata.mint == nft_mint.key() @ StakingError::InvalidNft,
+        constraint = user_nft_ata.owner == user.key() @ StakingError::InvalidNft,
+        constraint = user_nft_ata.amount == 1 @ StakingError::InvalidNft
+    )]
+    pub user_nft_ata: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        seeds = [b"bonus_v6", nft_mint.key().as_ref()],
+        bump,
+        constraint = !bonus_state.claimed @ StakingError::NftAlreadyRedeemed
+    )]
+    pub bonus_state: Account<'info, BonusStateV6>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + NftStakePosition::LEN,
+        seeds = [b"nft_stake", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub stake_position: Account<'info, NftStakePosition>,
+    #[account(
+        init,
+        payer = user,
+        token::mint = nft_mint,
+        token::authority = staking_pool,
+        token::token_program = token_program_2022,
+        seeds = [b"nft_stake_vault", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_vault: InterfaceAccount<'info, TokenAccount2022>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnstakeBonusNft<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"nft_staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, NftStakingPool>,
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = user
+    )]
+    pub user_nft_ata: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"nft_stake", nft_mint.key().as_ref()],
+        bump = stake_position.bump,
+        constraint = stake_position.owner == user.key() @ StakingError::Unauthorized
+    )]
+    pub stake_position: Account<'info, NftStakePosition>,
+    #[account(
+        mut,
+        seeds = [b"nft_stake_vault", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub nft_vault: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        address = staking_pool.reward_vault @ StakingError::InvalidRewardVault
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = user
+    )]
+    pub user_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        address = staking_pool.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimStakingRewards<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"nft_staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, NftStakingPool>,
+    #[account(
+        mut,
+        seeds = [b"nft_stake", stake_position.nft_mint.as_ref()],
+        bump = stake_position.bump,
+        constraint = stake_position.owner == user.key() @ StakingError::Unauthorized
+    )]
+    pub stake_position: Account<'info, NftStakePosition>,
+    #[account(
+        mut,
+        address = staking_pool.reward_vault @ StakingError::InvalidRewardVault
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        token::mint = defai_mint,
+        token::authority = user
+    )]
+    pub user_defai_ata: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        address = staking_pool.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn initialize_nft_staking(
+    ctx: Context<InitializeNftStaking>,
+    reward_rate_per_second: u64,
+    tier_weights: [u16; 5],
+) -> Result<()> {
+    let pool = &mut ctx.accounts.staking_pool;
+    pool.bump = ctx.bumps.staking_pool;
+    pool.authority = ctx.accounts.admin.key();
+    pool.defai_mint = ctx.accounts.defai_mint.key();
+    pool.reward_vault = ctx.accounts.reward_vault.key();
+    pool.reward_rate_per_second = reward_rate_per_second;
+    pool.tier_weights = tier_weights;
+    pool.total_weight = 0;
+    pool.acc_reward_per_weight = 0;
+    pool.last_update_ts = Clock::get()?.unix_timestamp;
+    pool.total_staked = 0;
+
+    msg!("NFT staking pool initialized");
+    Ok(())
+}
+
+// Tier weight changes only apply to positions staked afterwards
+pub fn update_nft_staking(
+    ctx: Context<UpdateNftStaking>,
+    reward_rate_per_second: u64,
+    tier_weights: [u16; 5],
+) -> Result<()> {
+    let pool = &mut ctx.accounts.staking_pool;
+    let now = Clock::get()?.unix_timestamp;
+    pool.update(now)?;
+    pool.reward_rate_per_second = reward_rate_per_second;
+    pool.tier_weights = tier_weights;
+
+    emit_versioned!(NftStakingConfigUpdated {
+        admin: ctx.accounts.admin.key(),
+        reward_rate_per_second,
+        tier_weights,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn stake_bonus_nft(ctx: Context<StakeBonusNft>) -> Result<()> {
+    require!(!ctx.accounts.config.paused, StakingError::ProtocolPaused);
+
+    let now = Clock::get()?.unix_timestamp;
+    let tier = ctx.accounts.bonus_state.tier;
+    let pool = &mut ctx.accounts.staking_pool;
+    let weight = pool.tier_weights[tier as usize];
+    require!(weight > 0, StakingError::TierNotStakeable);
+
+    pool.update(now)?;
+
+    // Lock the NFT in the program vault
+    let cpi_ctx = CpiContext::new(
+        ctx.accounts.token_program_2022.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.user_nft_ata.to_account_info(),
+            to: ctx.accounts.nft_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.nft_mint.to_account_info(),
+        },
+    );
+    token22::transfer_checked(cpi_ctx, 1, 0)?;
+
+    let position = &mut ctx.accounts.stake_position;
+    position.owner = ctx.accounts.user.key();
+    position.nft_mint = ctx.accounts.nft_mint.key();
+    position.tier = tier;
+    position.weight = weight;
+    position.reward_debt = (weight as u128)
+        .checked_mul(pool.acc_reward_per_weight)
+        .ok_or(StakingError::MathOverflow)?
+        / REWARD_PRECISION;
+    position.unpaid_rewards = 0;
+    position.staked_at = now;
+    position.bump = ctx.bumps.stake_position;
+
+    pool.total_weight = pool.total_weight
+        .checked_add(weight as u64)
+        .ok_or(StakingError::MathOverflow)?;
+    pool.total_staked += 1;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), BonusNftStaked {
+        user: position.owner,
+        nft_mint: position.nft_mint,
+        tier,
+        weight,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn unstake_bonus_nft(ctx: Context<UnstakeBonusNft>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pool = &mut ctx.accounts.staking_pool;
+    let position = &mut ctx.accounts.stake_position;
+
+    pool.update(now)?;
+    position.settle(pool.acc_reward_per_weight)?;
+
+    let pool_seeds = &[b"nft_staking_pool" as &[u8], &[pool.bump][..]];
+    let signer_seeds = &[&pool_seeds[..]];
+
+    // Pay out whatever the vault can cover; anything left is forfeited on unstake
+    let reward_paid = position.unpaid_rewards.min(ctx.accounts.reward_vault.amount);
+    if reward_paid > 0 {
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program_2022.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.reward_vault.to_account_info(),
+                to: ctx.accounts.user_defai_ata.to_account_info(),
+                authority: pool.to_account_info(),
+                mint: ctx.accounts.defai_mint.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token22::transfer_checked(cpi_ctx, reward_paid, ctx.accounts.defai_mint.decimals)?;
+    }
+
+    // Return the NFT and close the vault, reclaiming rent to the user
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program_2022.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.nft_vault.to_account_info(),
+            to: ctx.accounts.user_nft_ata.to_account_info(),
+            authority: pool.to_account_info(),
+            mint: ctx.accounts.nft_mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token22::transfer_checked(cpi_ctx, 1, 0)?;
+
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program_2022.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.nft_vault.to_account_info(),
+            destination: ctx.accounts.user.to_account_info(),
+            authority: pool.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token22::close_account(close_ctx)?;
+
+    pool.total_weight = pool.total_weight.saturating_sub(position.weight as u64);
+    pool.total_staked = pool.total_staked.saturating_sub(1);
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), BonusNftUnstaked {
+        user: ctx.accounts.user.key(),
+        nft_mint: position.nft_mint,
+        reward_paid,
+        reward_forfeited: position.unpaid_rewards - reward_paid,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn claim_staking_rewards(ctx: Context<ClaimStakingRewards>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pool = &mut ctx.accounts.staking_pool;
+    let position = &mut ctx.accounts.stake_position;
+
+    pool.update(now)?;
+    position.settle(pool.acc_reward_per_weight)?;
+
+    let amount = position.unpaid_rewards.min(ctx.accounts.reward_vault.amount);
+    require!(amount > 0, StakingError::NothingToClaim);
+
+    let pool_seeds = &[b"nft_staking_pool" as &[u8], &[pool.bump][..]];
+    let signer_seeds = &[&pool_seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program_2022.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            to: ctx.accounts.user_defai_ata.to_account_info(),
+            authority: pool.to_account_info(),
+            mint: ctx.accounts.defai_mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token22::transfer_checked(cpi_ctx, amount, ctx.accounts.defai_mint.decimals)?;
+
+    // Keep any shortfall on the position so it can be claimed once the vault is refilled
+    position.unpaid_rewards -= amount;
+
+    emit_cpi_versioned!(event_cpi_of!(ctx), StakingRewardsClaimed {
+        user: ctx.accounts.user.key(),
+        nft_mint: position.nft_mint,
+        amount,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct NftStakingConfigUpdated {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub reward_rate_per_second: u64,
+    pub tier_weights: [u16; 5],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusNftStaked {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub tier: u8,
+    pub weight: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusNftUnstaked {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub reward_paid: u64,
+    pub reward_forfeited: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakingRewardsClaimed {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum StakingError {
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Protocol paused")]
+    ProtocolPaused,
+    #[msg("Invalid mint")]
+    InvalidMint,
+    #[msg("Invalid NFT or NFT not held by user")]
+    InvalidNft,
+    #[msg("NFT already redeemed")]
+    NftAlreadyRedeemed,
+    #[msg("Tier has no staking weight")]
+    TierNotStakeable,
+    #[msg("Reward vault does not match staking pool")]
+    InvalidRewardVault,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Overflow in maths operation")]
+    MathOverflow,
+}
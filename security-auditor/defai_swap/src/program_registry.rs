@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use defai_common::{
+    emit_versioned, validate_program_links, Timelock, ADMIN_TIMELOCK_DURATION, DEFAULT_APP_FACTORY_PROGRAM_ID,
+    DEFAULT_ESTATE_PROGRAM_ID, DEFAULT_GOVERNANCE_PROGRAM_ID, DEFAULT_SWAP_PROGRAM_ID,
+};
+
+use crate::{Config, ErrorCode};
+
+// Program registry (see defai_common::registry)
+// Ids of the sibling programs this deployment works with. Swap reads estate
+// claim records for inherited vesting from the linked estate program. Set
+// once at initialization and afterwards only changed through the admin
+// timelock, like the cluster config.
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProgramLinks {
+    pub swap: Pubkey,
+    pub estate: Pubkey,
+    pub app_factory: Pubkey,
+    pub governance: Pubkey,
+}
+
+impl ProgramLinks {
+    pub const LEN: usize = 32 * 4;
+
+    pub fn defaults() -> Self {
+        Self {
+            swap: DEFAULT_SWAP_PROGRAM_ID,
+            estate: DEFAULT_ESTATE_PROGRAM_ID,
+            app_factory: DEFAULT_APP_FACTORY_PROGRAM_ID,
+            governance: DEFAULT_GOVERNANCE_PROGRAM_ID,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        validate_program_links(&[self.swap, self.estate, self.app_factory, self.governance], &crate::ID, &self.swap)
+    }
+}
+
+#[account]
+pub struct ProgramRegistry {
+    pub links: ProgramLinks,
+    pub pending_links: Option<ProgramLinks>,
+    pub links_change_timestamp: i64,
+    pub bump: u8,
+}
+
+impl ProgramRegistry {
+    pub const LEN: usize = ProgramLinks::LEN + (1 + ProgramLinks::LEN) + 8 + 1;
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramRegistry::LEN,
+        seeds = [b"program_registry"],
+        bump
+    )]
+    pub program_registry: Account<'info, ProgramRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramRegistry<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"program_registry"],
+        bump = program_registry.bump
+    )]
+    pub program_registry: Account<'info, ProgramRegistry>,
+}
+
+// `None` takes the main deployment's ids
+pub fn initialize_program_registry(ctx: Context<InitializeProgramRegistry>, links: Option<ProgramLinks>) -> Result<()> {
+    let links = links.unwrap_or_else(ProgramLinks::defaults);
+    links.validate()?;
+
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    registry.links = links.clone();
+    registry.pending_links = None;
+    registry.links_change_timestamp = 0;
+    registry.bump = ctx.bumps.program_registry;
+
+    emit_versioned!(ProgramLinksChanged {
+        admin: ctx.accounts.admin.key(),
+        links,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn propose_program_links_change(ctx: Context<UpdateProgramRegistry>, links: ProgramLinks) -> Result<()> {
+    links.validate()?;
+    require!(ctx.accounts.program_registry.links != links, ErrorCode::InvalidInput);
+
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    let execute_after = Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp)
+        .propose(links.clone(), Clock::get()?.unix_timestamp, ADMIN_TIMELOCK_DURATION)?;
+
+    emit_versioned!(ProgramLinksChangeProposed {
+        admin: ctx.accounts.admin.key(),
+        links,
+        execute_after,
+    });
+    Ok(())
+}
+
+pub fn cancel_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp).cancel()?;
+
+    emit_versioned!(ProgramLinksChangeCancelled {
+        admin: ctx.accounts.admin.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn execute_program_links_change(ctx: Context<UpdateProgramRegistry>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let registry: &mut ProgramRegistry = &mut ctx.accounts.program_registry;
+    let links = Timelock::new(&mut registry.pending_links, &mut registry.links_change_timestamp).execute(now)?;
+    registry.links = links.clone();
+
+    emit_versioned!(ProgramLinksChanged {
+        admin: ctx.accounts.admin.key(),
+        links,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct ProgramLinksChangeProposed {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub links: ProgramLinks,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct ProgramLinksChangeCancelled {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramLinksChanged {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub links: ProgramLinks,
+    pub timestamp: i64,
+}
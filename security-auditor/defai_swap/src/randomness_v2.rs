@@ -1,16 +1,12 @@
 use anchor_lang::prelude::*;
+use defai_common::emit_versioned;
+
+use crate::cluster_config::ClusterConfig;
+use crate::switchboard::RandomnessAccountData;
 
 // Switchboard On-Demand Randomness Implementation
 // This replaces the deprecated VRF Lite approach
 
-#[derive(Debug, Clone, Copy, AnchorSerialize, AnchorDeserialize)]
-pub struct RandomnessAccountData {
-    pub seed: [u8; 32],
-    pub value: [u8; 32],
-    pub slot: u64,
-    pub timestamp: i64,
-}
-
 #[account]
 pub struct RandomnessState {
     pub bump: u8,
@@ -56,8 +52,14 @@ pub struct CommitRandomness<'info> {
     )]
     pub randomness_state: Account<'info, RandomnessState>,
     
-    /// CHECK: Switchboard randomness account
+    /// CHECK: Switchboard On-Demand randomness account, validated by RandomnessAccountData::parse
     pub randomness_account: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"cluster_config"],
+        bump = cluster_config.bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
 }
 
 #[derive(Accounts)]
@@ -73,8 +75,14 @@ pub struct RevealRandomness<'info> {
     )]
     pub randomness_state: Account<'info, RandomnessState>,
     
-    /// CHECK: Switchboard randomness account
+    /// CHECK: Switchboard On-Demand randomness account, validated by RandomnessAccountData::parse
     pub randomness_account: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"cluster_config"],
+        bump = cluster_config.bump
+    )]
+    pub cluster_config: Account<'info, ClusterConfig>,
 }
 
 // Simple fallback randomness using recent blockhashes
@@ -113,11 +121,29 @@ pub fn commit_randomness(ctx: Context<CommitRandomness>) -> Result<()> {
     let randomness_state = &mut ctx.accounts.randomness_state;
     let clock = Clock::get()?;
     
+    // Only accept a commitment Switchboard seeded in the previous slot, so its
+    // value cannot already be known
+    let randomness = RandomnessAccountData::parse(
+        &ctx.accounts.randomness_account,
+        &ctx.accounts.cluster_config.settings.randomness_program,
+    )?;
+    require!(
+        randomness.seed_slot == clock.slot.saturating_sub(1),
+        RandomnessError::RandomnessAlreadyRevealed
+    );
+    
     // Store the randomness account for later reveal
     randomness_state.randomness_account = ctx.accounts.randomness_account.key();
-    randomness_state.committed_slot = clock.slot;
+    randomness_state.committed_slot = randomness.seed_slot;
     randomness_state.is_pending = true;
     
+    emit_versioned!(RandomnessRequested {
+        requester: ctx.accounts.authority.key(),
+        randomness_account: randomness_state.randomness_account,
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+    });
+    
     msg!("Randomness committed at slot {}", clock.slot);
     Ok(())
 }
@@ -136,19 +162,30 @@ pub fn reveal_randomness(ctx: Context<RevealRandomness>) -> Result<()> {
         RandomnessError::InvalidRandomnessAccount
     );
     
-    // Parse the randomness account data
-    let data = ctx.accounts.randomness_account.try_borrow_data()?;
-    if data.len() >= 104 {
-        // Extract the random value (32 bytes at offset 40)
-        randomness_state.revealed_value.copy_from_slice(&data[40..72]);
-        randomness_state.last_update = clock.unix_timestamp;
-        randomness_state.is_pending = false;
-        
-        msg!("Randomness revealed successfully");
-    } else {
-        return Err(RandomnessError::InvalidAccountData.into());
-    }
+    // The account must still hold the commitment seen at commit time
+    let randomness = RandomnessAccountData::parse(
+        &ctx.accounts.randomness_account,
+        &ctx.accounts.cluster_config.settings.randomness_program,
+    )?;
+    require!(
+        randomness.seed_slot == randomness_state.committed_slot,
+        RandomnessError::InvalidRandomnessAccount
+    );
+    
+    randomness_state.revealed_value = randomness.get_value(clock.slot)?;
+    randomness_state.last_update = clock.unix_timestamp;
+    randomness_state.is_pending = false;
+    
+    emit_versioned!(RandomnessRevealed {
+        authority: ctx.accounts.authority.key(),
+        randomness_account: randomness_state.randomness_account,
+        committed_slot: randomness_state.committed_slot,
+        slot: clock.slot,
+        result_hash: result_hash(&randomness_state.revealed_value),
+        timestamp: clock.unix_timestamp,
+    });
     
+    msg!("Randomness revealed successfully");
     Ok(())
 }
 
@@ -173,10 +210,42 @@ pub fn generate_simple_randomness(ctx: Context<SimpleRandomness>) -> Result<()>
     randomness_state.last_update = clock.unix_timestamp;
     randomness_state.is_pending = false;
     
+    emit_versioned!(RandomnessRevealed {
+        authority: ctx.accounts.authority.key(),
+        randomness_account: Pubkey::default(),
+        committed_slot: clock.slot,
+        slot: clock.slot,
+        result_hash: result_hash(&randomness_state.revealed_value),
+        timestamp: clock.unix_timestamp,
+    });
+    
     msg!("Simple randomness generated");
     Ok(())
 }
 
+// Hash of a randomness result, published in events so indexers can correlate
+// each bonus roll with the reveal that produced it
+pub fn result_hash(value: &[u8; 32]) -> [u8; 32] {
+    solana_program::keccak::hash(value).to_bytes()
+}
+
+// Emit a consumption event for a bonus roll that used the stored randomness
+pub fn emit_randomness_consumed(
+    randomness_state: &RandomnessState,
+    user: &Pubkey,
+    nft_mint: &Pubkey,
+    clock: &Clock,
+) {
+    emit_versioned!(RandomnessConsumed {
+        user: *user,
+        nft_mint: *nft_mint,
+        randomness_account: randomness_state.randomness_account,
+        result_hash: result_hash(&randomness_state.revealed_value),
+        slot: clock.slot,
+        timestamp: clock.unix_timestamp,
+    });
+}
+
 // Helper function to get a random number in range [min, max]
 pub fn get_random_in_range(random_bytes: &[u8; 32], min: u64, max: u64) -> u64 {
     let range = max - min + 1;
@@ -254,6 +323,37 @@ pub fn generate_secure_random(
     u64::from_le_bytes(bytes)
 }
 
+#[event]
+pub struct RandomnessRequested {
+    pub schema_version: u8,
+    pub requester: Pubkey,
+    pub randomness_account: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RandomnessRevealed {
+    pub schema_version: u8,
+    pub authority: Pubkey,
+    pub randomness_account: Pubkey,  // Default pubkey for blockhash-based fallback randomness
+    pub committed_slot: u64,
+    pub slot: u64,
+    pub result_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RandomnessConsumed {
+    pub schema_version: u8,
+    pub user: Pubkey,
+    pub nft_mint: Pubkey,
+    pub randomness_account: Pubkey,
+    pub result_hash: [u8; 32],
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
 #[error_code]
 pub enum RandomnessError {
     #[msg("No randomness commitment found")]
@@ -266,4 +366,12 @@ pub enum RandomnessError {
     RandomnessNotResolved,
     #[msg("Unauthorized")]
     Unauthorized,
+    #[msg("Randomness account is not owned by the configured Switchboard On-Demand program")]
+    InvalidRandomnessOwner,
+    #[msg("Randomness account discriminator mismatch")]
+    InvalidDiscriminator,
+    #[msg("Randomness was not seeded in the previous slot")]
+    RandomnessAlreadyRevealed,
+    #[msg("Randomness must be consumed in the slot it was revealed")]
+    RandomnessExpired,
 }
\ No newline at end of file
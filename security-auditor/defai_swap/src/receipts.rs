@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount as TokenAccount2022;
+use defai_common::{append_receipt, emit_cpi_versioned, receipt_leaf, receipt_root, EventCpi, ReceiptKind};
+
+use crate::{ErrorCode, RerollHistory};
+
+// Compressed reroll receipts (see defai_common::receipts)
+// Each reroll is appended to the swap program's receipt log instead of a
+// per-NFT `RerollHistory` account, so a first reroll no longer pays that
+// account's rent. Secondary buyers read an NFT's rerolls from the indexed
+// `ReceiptLogged` events and can prove them against the log's root. Holders
+// of NFTs that already have a history account can close it for the rent.
+
+#[account]
+pub struct ReceiptLog {
+    pub root: [u8; 32],
+    pub leaf_count: u64,
+    pub frontier: [[u8; 32]; 20],  // defai_common::RECEIPT_TREE_DEPTH
+    pub bump: u8,
+}
+
+impl ReceiptLog {
+    pub const LEN: usize = 32 + 8 + 32 * 20 + 1;
+
+    // Append a receipt and publish its bytes so the tree can be rebuilt
+    pub fn append(&mut self, kind: ReceiptKind, data: Vec<u8>, now: i64, events: &EventCpi) -> Result<u64> {
+        let index = self.leaf_count;
+        let leaf = receipt_leaf(kind, index, &data);
+        self.root = append_receipt(&mut self.frontier, &mut self.leaf_count, leaf)?;
+
+        emit_cpi_versioned!(events, ReceiptLogged {
+            kind: kind as u8,
+            index,
+            leaf,
+            root: self.root,
+            data,
+            timestamp: now,
+        });
+        Ok(index)
+    }
+}
+
+// Leaf data of a ReceiptKind::BonusReroll receipt
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RerollReceipt {
+    pub nft_mint: Pubkey,
+    pub user: Pubkey,
+    pub old_bonus_bps: u16,
+    pub new_bonus_bps: u16,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReceiptLog<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ReceiptLog::LEN,
+        seeds = [b"receipt_log"],
+        bump
+    )]
+    pub receipt_log: Box<Account<'info, ReceiptLog>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRerollHistory<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    /// CHECK: NFT mint
+    pub nft_mint: AccountInfo<'info>,
+    #[account(
+        constraint = holder_nft_ata.mint == nft_mint.key() @ ErrorCode::InvalidNft,
+        constraint = holder_nft_ata.owner == holder.key() @ ErrorCode::NoNft,
+        constraint = holder_nft_ata.amount == 1 @ ErrorCode::NoNft
+    )]
+    pub holder_nft_ata: InterfaceAccount<'info, TokenAccount2022>,
+    #[account(
+        mut,
+        close = holder,
+        seeds = [b"reroll_history", nft_mint.key().as_ref()],
+        bump
+    )]
+    pub reroll_history: Box<Account<'info, RerollHistory>>,
+}
+
+// Anyone may create the log; it only holds the tree
+pub fn initialize_receipt_log(ctx: Context<InitializeReceiptLog>) -> Result<()> {
+    let log = &mut ctx.accounts.receipt_log;
+    log.frontier = [[0; 32]; 20];
+    log.leaf_count = 0;
+    log.root = receipt_root(&log.frontier, 0);
+    log.bump = ctx.bumps.receipt_log;
+    Ok(())
+}
+
+// The rent goes to whoever holds the NFT now, as the history went with it
+pub fn close_reroll_history(ctx: Context<CloseRerollHistory>) -> Result<()> {
+    msg!(
+        "Closed reroll history of {} ({} rerolls)",
+        ctx.accounts.nft_mint.key(),
+        ctx.accounts.reroll_history.total_rerolls
+    );
+    Ok(())
+}
+
+#[event]
+pub struct ReceiptLogged {
+    pub schema_version: u8,
+    pub kind: u8,          // defai_common::ReceiptKind
+    pub index: u64,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],    // Root after this append
+    pub data: Vec<u8>,     // Borsh-encoded receipt, e.g. RerollReceipt
+    pub timestamp: i64,
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use defai_common::{emit_versioned, validate_security_info, SECURITY_INFO_SPACE};
+
+use crate::{Config, ErrorCode};
+
+// Supplemental security contact
+// The security.txt in the program binary carries the disclosure channel as of
+// deploy. The admin keeps this PDA current between upgrades, so a researcher
+// who finds the binary's contact stale can read the live one from chain.
+
+#[account]
+pub struct SecurityInfo {
+    pub updated_by: Pubkey,
+    pub contacts: String,
+    pub policy: String,
+    pub auditors: String,
+    pub expiry: i64, // Unix timestamp after which the contact should not be trusted
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl SecurityInfo {
+    pub const LEN: usize = SECURITY_INFO_SPACE;
+}
+
+#[derive(Accounts)]
+pub struct UpdateSecurityInfo<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = SecurityInfo::LEN,
+        seeds = [b"security_info"],
+        bump
+    )]
+    pub security_info: Box<Account<'info, SecurityInfo>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_security_info(
+    ctx: Context<UpdateSecurityInfo>,
+    contacts: String,
+    policy: String,
+    auditors: String,
+    expiry: i64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    validate_security_info(&contacts, &policy, &auditors, expiry, now)?;
+
+    let info = &mut ctx.accounts.security_info;
+    info.updated_by = ctx.accounts.admin.key();
+    info.contacts = contacts;
+    info.policy = policy;
+    info.auditors = auditors;
+    info.expiry = expiry;
+    info.updated_at = now;
+    info.bump = ctx.bumps.security_info;
+
+    emit_versioned!(SecurityInfoUpdated {
+        updated_by: info.updated_by,
+        expiry,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+#[event]
+pub struct SecurityInfoUpdated {
+    pub schema_version: u8,
+    pub updated_by: Pubkey,
+    pub expiry: i64,
+    pub timestamp: i64,
+}
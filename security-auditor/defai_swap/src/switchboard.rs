@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use bytemuck::{Pod, Zeroable};
+
+use crate::randomness_v2::RandomnessError;
+
+// Vendored Switchboard On-Demand account layouts, matching
+// `switchboard_on_demand::RandomnessAccountData` (0.3.x). The SDK itself is
+// not a dependency: its open-ended solana-program requirement does not
+// resolve alongside Anchor 0.29. Accounts are only read after their owner,
+// discriminator and length check out, so a foreign or truncated account is
+// rejected instead of handing back whatever bytes sit at a fixed offset.
+// Which Switchboard deployment is trusted comes from the cluster config.
+
+pub const SWITCHBOARD_ON_DEMAND_MAINNET_PID: Pubkey =
+    anchor_lang::solana_program::pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+pub const SWITCHBOARD_ON_DEMAND_DEVNET_PID: Pubkey =
+    anchor_lang::solana_program::pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2");
+
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+#[repr(C)]
+pub struct RandomnessAccountData {
+    pub authority: Pubkey,
+    pub queue: Pubkey,
+    pub seed_slothash: [u8; 32],
+    pub seed_slot: u64,
+    pub oracle: Pubkey,
+    pub reveal_slot: u64,
+    pub value: [u8; 32],
+    _ebuf2: [u8; 96],
+    _ebuf1: [u8; 128],
+}
+
+impl RandomnessAccountData {
+    pub const DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+
+    pub fn parse(account: &AccountInfo, program_id: &Pubkey) -> Result<Self> {
+        require_keys_eq!(*account.owner, *program_id, RandomnessError::InvalidRandomnessOwner);
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= Self::LEN, RandomnessError::InvalidAccountData);
+        require!(data[..8] == Self::DISCRIMINATOR, RandomnessError::InvalidDiscriminator);
+        Ok(bytemuck::pod_read_unaligned(&data[8..Self::LEN]))
+    }
+
+    // The revealed value, only readable in the slot the oracle revealed it so a
+    // caller cannot wait and pick between known outcomes
+    pub fn get_value(&self, slot: u64) -> Result<[u8; 32]> {
+        require!(self.reveal_slot > self.seed_slot, RandomnessError::RandomnessNotResolved);
+        require!(self.reveal_slot == slot, RandomnessError::RandomnessExpired);
+        Ok(self.value)
+    }
+}
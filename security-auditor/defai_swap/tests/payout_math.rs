@@ -0,0 +1,250 @@
+// Property tests for the payout math in src/math.rs: linear vesting and its
+// cliff, tier bonus ranges and per-user tax accumulation. Run with
+// `cargo test -p defai_swap`.
+
+use anchor_lang::error::Error;
+use defai_common::bps_of;
+use defai_swap::{
+    math::*, randomness_v2::calculate_random_bonus, ErrorCode, CLIFF_DURATION, DUST_VESTING_THRESHOLD,
+    INITIAL_TAX_BPS, TAX_CAP_BPS, TAX_INCREMENT_BPS, TAX_RESET_DURATION, VESTING_DURATION,
+};
+use proptest::prelude::*;
+
+// Start times far enough from the i64 edges that start + VESTING_DURATION fits
+fn start_time() -> impl Strategy<Value = i64> {
+    -(1i64 << 40)..(1i64 << 40)
+}
+
+fn non_dust() -> impl Strategy<Value = u64> {
+    DUST_VESTING_THRESHOLD + 1..=u64::MAX
+}
+
+fn reference_vested(total: u64, start: i64, end: i64, now: i64) -> u64 {
+    if end <= start {
+        return if now >= end { total } else { 0 };
+    }
+    let duration = end as i128 - start as i128;
+    let elapsed = (now as i128 - start as i128).clamp(0, duration);
+    (total as u128 * elapsed as u128 / duration as u128) as u64
+}
+
+proptest! {
+    #[test]
+    fn vested_never_exceeds_total(total in any::<u64>(), start in any::<i64>(), end in any::<i64>(), now in any::<i64>()) {
+        prop_assert!(vested_amount(total, start, end, now) <= total);
+    }
+
+    #[test]
+    fn vested_matches_reference(total in any::<u64>(), start in any::<i64>(), end in any::<i64>(), now in any::<i64>()) {
+        prop_assert_eq!(vested_amount(total, start, end, now), reference_vested(total, start, end, now));
+    }
+
+    #[test]
+    fn vested_is_monotonic(total in any::<u64>(), start in start_time(), a in any::<i64>(), b in any::<i64>()) {
+        let end = start + VESTING_DURATION;
+        let (earlier, later) = if a <= b { (a, b) } else { (b, a) };
+        prop_assert!(vested_amount(total, start, end, earlier) <= vested_amount(total, start, end, later));
+    }
+
+    #[test]
+    fn schedule_endpoints(total in any::<u64>(), start in start_time(), before in 0..(1i64 << 40), after in 0..(1i64 << 40)) {
+        let end = start + VESTING_DURATION;
+        prop_assert_eq!(vested_amount(total, start, end, start - before), 0);
+        prop_assert_eq!(vested_amount(total, start, end, end + after), total);
+    }
+
+    // A zero-length schedule vests everything at its start and nothing before
+    #[test]
+    fn zero_length_schedule(total in any::<u64>(), start in any::<i64>(), offset in 1..(1i64 << 40)) {
+        prop_assert_eq!(vested_amount(total, start, start, start), total);
+        prop_assert_eq!(vested_amount(total, start, start, start.saturating_add(offset)), total);
+        if let Some(before) = start.checked_sub(offset) {
+            prop_assert_eq!(vested_amount(total, start, start, before), 0);
+        }
+    }
+
+    #[test]
+    fn cliff_boundary(total in non_dust(), start in start_time()) {
+        let end = start + VESTING_DURATION;
+        let cliff_end = start + CLIFF_DURATION;
+        let err = vested_after_cliff(total, start, end, cliff_end - 1).unwrap_err();
+        prop_assert_eq!(err, Error::from(ErrorCode::StillInCliff));
+        prop_assert_eq!(vested_after_cliff(total, start, end, cliff_end).unwrap(), vested_amount(total, start, end, cliff_end));
+    }
+
+    #[test]
+    fn dust_skips_cliff(total in 1..=DUST_VESTING_THRESHOLD, start in start_time(), now in any::<i64>()) {
+        prop_assert_eq!(vested_after_cliff(total, start, start + VESTING_DURATION, now).unwrap(), total);
+    }
+
+    // Claiming at arbitrary points releases exactly the total by the end, with
+    // rounding dust carried into later claims rather than lost
+    #[test]
+    fn successive_claims_release_total(total in any::<u64>(), start in start_time(), mut offsets in prop::collection::vec(0..VESTING_DURATION, 0..20)) {
+        let end = start + VESTING_DURATION;
+        offsets.sort_unstable();
+        let mut released = 0u64;
+        for offset in offsets.into_iter().chain([VESTING_DURATION]) {
+            let now = start + offset.max(CLIFF_DURATION);
+            let claim = vested_after_cliff(total, start, end, now).unwrap().checked_sub(released);
+            prop_assert!(claim.is_some());
+            released += claim.unwrap();
+        }
+        prop_assert_eq!(released, total);
+    }
+
+    #[test]
+    fn random_bonus_stays_in_tier_range(tier in 0u8..5, random_value in any::<u64>()) {
+        let (min, max) = get_tier_bonus_range(tier);
+        let bonus = calculate_random_bonus(random_value, min, max);
+        prop_assert!((min..=max).contains(&bonus));
+    }
+
+    // Every value in a tier's range is reachable, including both ends
+    #[test]
+    fn random_bonus_covers_range(tier in 0u8..5, pick in any::<prop::sample::Index>()) {
+        let (min, max) = get_tier_bonus_range(tier);
+        let offset = pick.index(max as usize - min as usize + 1) as u64;
+        prop_assert_eq!(calculate_random_bonus(offset, min, max), min + offset as u16);
+    }
+
+    #[test]
+    fn bonus_vesting_bounded_by_tier_max(tier in 0u8..5, price in 0..=u64::MAX / 3, random_value in any::<u64>()) {
+        let (min, max) = get_tier_bonus_range(tier);
+        let bonus = calculate_random_bonus(random_value, min, max);
+        let vesting = bps_of(price, bonus as u64).unwrap();
+        prop_assert!(vesting <= bps_of(price, max as u64).unwrap());
+        prop_assert!(vesting >= bps_of(price, min as u64).unwrap());
+    }
+
+    #[test]
+    fn tax_rate_stays_between_initial_and_cap(swaps in 0usize..64) {
+        let rate = (0..swaps).fold(INITIAL_TAX_BPS, |rate, _| next_tax_bps(rate));
+        let expected = (INITIAL_TAX_BPS as usize + swaps * TAX_INCREMENT_BPS as usize).min(TAX_CAP_BPS as usize);
+        prop_assert_eq!(rate as usize, expected);
+    }
+
+    #[test]
+    fn tax_never_decreases_on_swap(rate in any::<u16>()) {
+        let next = next_tax_bps(rate);
+        prop_assert!(next >= rate.min(TAX_CAP_BPS));
+        prop_assert!(next <= TAX_CAP_BPS);
+    }
+
+    #[test]
+    fn tax_resets_after_quiet_period(rate in any::<u16>(), last in any::<i64>(), gap in any::<i64>()) {
+        let now = last.saturating_add(gap);
+        let expected = if now.saturating_sub(last) >= TAX_RESET_DURATION { INITIAL_TAX_BPS } else { rate };
+        prop_assert_eq!(effective_tax_bps(rate, last, now), expected);
+    }
+
+    // Model a user's swap history: the rate climbs by one increment per swap,
+    // resets after a quiet day, and every charge stays within the cap
+    #[test]
+    fn tax_accumulation_over_swap_history(
+        swaps in prop::collection::vec((0..2 * TAX_RESET_DURATION, 0..1_000_000_000_000u64), 1..40),
+    ) {
+        let mut rate = INITIAL_TAX_BPS;
+        let mut last = 0i64;
+        let mut now = 0i64;
+        let mut streak = 0u16;
+        for (gap, price) in swaps {
+            now += gap;
+            if tax_reset_due(last, now) {
+                streak = 0;
+            }
+            let charged = effective_tax_bps(rate, last, now);
+            prop_assert_eq!(charged, (INITIAL_TAX_BPS + streak * TAX_INCREMENT_BPS).min(TAX_CAP_BPS));
+
+            let tax = bps_of(price, charged as u64).unwrap();
+            prop_assert!(tax <= bps_of(price, TAX_CAP_BPS as u64).unwrap());
+
+            rate = next_tax_bps(charged);
+            last = now;
+            streak += 1;
+        }
+    }
+}
+
+#[test]
+fn tax_reset_boundary() {
+    assert!(!tax_reset_due(0, TAX_RESET_DURATION - 1));
+    assert!(tax_reset_due(0, TAX_RESET_DURATION));
+    assert!(!tax_reset_due(i64::MAX, i64::MIN));
+    assert!(tax_reset_due(i64::MIN, i64::MAX));
+}
+
+#[test]
+fn vesting_handles_u64_max() {
+    let start = 1_700_000_000;
+    let end = start + VESTING_DURATION;
+    let half = vested_amount(u64::MAX, start, end, start + VESTING_DURATION / 2);
+    assert_eq!(half, u64::MAX / 2);
+    assert_eq!(vested_after_cliff(u64::MAX, start, end, end).unwrap(), u64::MAX);
+}
+
+// These ranges are fixed for minted NFTs; a change here is a payout change
+#[test]
+fn tier_bonus_ranges_are_locked() {
+    let ranges: Vec<_> = (0..6).map(get_tier_bonus_range).collect();
+    assert_eq!(ranges, [(0, 0), (0, 1_500), (1_500, 5_000), (2_000, 10_000), (5_000, 30_000), (0, 0)]);
+}
+
+#[test]
+fn dust_threshold_boundary() {
+    assert!(!is_dust_vesting(0));
+    assert!(is_dust_vesting(1));
+    assert!(is_dust_vesting(DUST_VESTING_THRESHOLD));
+    assert!(!is_dust_vesting(DUST_VESTING_THRESHOLD + 1));
+    assert!(!is_dust_vesting(u64::MAX));
+}
+
+#[test]
+fn vesting_with_end_at_or_before_start() {
+    let start = 1_700_000_000;
+    // Inverted schedules vest everything once `end` has passed, nothing before
+    assert_eq!(vested_amount(1_000, start, start - 10, start - 11), 0);
+    assert_eq!(vested_amount(1_000, start, start - 10, start - 10), 1_000);
+    assert_eq!(vested_amount(1_000, start, start - 10, start), 1_000);
+    assert_eq!(vested_amount(1_000, start, start, start - 1), 0);
+    assert_eq!(vested_amount(1_000, start, start, start), 1_000);
+    assert_eq!(vested_amount(u64::MAX, i64::MAX, i64::MIN, i64::MIN), u64::MAX);
+    assert_eq!(vested_amount(u64::MAX, i64::MIN, i64::MAX, i64::MIN), 0);
+}
+
+#[test]
+fn zero_total_vests_nothing_and_is_held_to_the_cliff() {
+    let start = 1_700_000_000;
+    let end = start + VESTING_DURATION;
+    assert_eq!(vested_amount(0, start, end, end), 0);
+    assert_eq!(
+        vested_after_cliff(0, start, end, start).unwrap_err(),
+        Error::from(ErrorCode::StillInCliff)
+    );
+    assert_eq!(vested_after_cliff(0, start, end, start + CLIFF_DURATION).unwrap(), 0);
+}
+
+#[test]
+fn cliff_edge_at_the_dust_threshold() {
+    let start = 1_700_000_000;
+    let end = start + VESTING_DURATION;
+    let cliff_end = start + CLIFF_DURATION;
+    assert_eq!(
+        vested_after_cliff(DUST_VESTING_THRESHOLD, start, end, start).unwrap(),
+        DUST_VESTING_THRESHOLD
+    );
+    assert_eq!(
+        vested_after_cliff(DUST_VESTING_THRESHOLD + 1, start, end, cliff_end - 1).unwrap_err(),
+        Error::from(ErrorCode::StillInCliff)
+    );
+    assert_eq!(
+        vested_after_cliff(DUST_VESTING_THRESHOLD + 1, start, end, cliff_end).unwrap(),
+        vested_amount(DUST_VESTING_THRESHOLD + 1, start, end, cliff_end)
+    );
+    // The cliff deadline saturates instead of overflowing near i64::MAX
+    assert_eq!(
+        vested_after_cliff(u64::MAX, i64::MAX - 1, i64::MAX, i64::MAX - 1).unwrap_err(),
+        Error::from(ErrorCode::StillInCliff)
+    );
+    assert_eq!(vested_after_cliff(u64::MAX, i64::MAX - 1, i64::MAX, i64::MAX).unwrap(), u64::MAX);
+}
@@ -0,0 +1,156 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { DefaiSwap, IDL } from "../target/types/defai_swap";
+import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  TOKEN_2022_PROGRAM_ID,
+  MINT_SIZE,
+  ACCOUNT_SIZE,
+  MintLayout,
+  AccountLayout,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { start, ProgramTestContext } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { assert } from "chai";
+
+// Compute-unit ceiling for redeem_v6 (transfer, burn and close in one
+// instruction). Holders usually add priority fees on top, so a change that
+// pushes it over should be looked at before merge.
+const REDEEM_V6_BUDGET = 60_000;
+
+const PROGRAM_ID = new PublicKey("DB9Zvhdp5xh853d2Tr2HBkRDDaCSioD7vwchhcGaXCw3");
+
+// Run with SBF_OUT_DIR pointing at the built defai_swap.so
+describe("Redeem compute budget", () => {
+  let context: ProgramTestContext;
+  let program: Program<DefaiSwap>;
+
+  const user = Keypair.generate();
+  const defaiMint = Keypair.generate().publicKey;
+  const nftMint = Keypair.generate().publicKey;
+  const config = Keypair.generate().publicKey;
+  const pda = (seed: string, key?: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      key ? [Buffer.from(seed), key.toBuffer()] : [Buffer.from(seed)],
+      PROGRAM_ID
+    );
+
+  const setAccount = (address: PublicKey, programOwner: PublicKey, data: Buffer, lamports = LAMPORTS_PER_SOL) =>
+    context.setAccount(address, { lamports, data, owner: programOwner, executable: false });
+
+  const setMint = (mint: PublicKey, supply: bigint, decimals: number) => {
+    const data = Buffer.alloc(MINT_SIZE);
+    MintLayout.encode({
+      mintAuthorityOption: 0,
+      mintAuthority: PublicKey.default,
+      supply,
+      decimals,
+      isInitialized: true,
+      freezeAuthorityOption: 0,
+      freezeAuthority: PublicKey.default,
+    }, data);
+    setAccount(mint, TOKEN_2022_PROGRAM_ID, data);
+  };
+
+  const setTokenAccount = (mint: PublicKey, authority: PublicKey, amount: bigint) => {
+    const address = getAssociatedTokenAddressSync(mint, authority, true, TOKEN_2022_PROGRAM_ID);
+    const data = Buffer.alloc(ACCOUNT_SIZE);
+    AccountLayout.encode({
+      mint,
+      owner: authority,
+      amount,
+      delegateOption: 0,
+      delegate: PublicKey.default,
+      state: 1,
+      isNativeOption: 0,
+      isNative: BigInt(0),
+      delegatedAmount: BigInt(0),
+      closeAuthorityOption: 0,
+      closeAuthority: PublicKey.default,
+    }, data);
+    setAccount(address, TOKEN_2022_PROGRAM_ID, data);
+    return address;
+  };
+
+  before(async () => {
+    context = await start([{ name: "defai_swap", programId: PROGRAM_ID }], []);
+    program = new Program<DefaiSwap>(IDL, PROGRAM_ID, new BankrunProvider(context));
+
+    setAccount(user.publicKey, SystemProgram.programId, Buffer.alloc(0), 10 * LAMPORTS_PER_SOL);
+    setMint(defaiMint, BigInt(1_000_000_000_000), 6);
+    setMint(nftMint, BigInt(1), 0);
+
+    const [escrow, escrowBump] = pda("escrow");
+    const tierPrice = new anchor.BN(1_000_000_000);
+
+    // Inject the state a holder has after swapping for a tier 0 NFT
+    setAccount(config, PROGRAM_ID, await program.coder.accounts.encode("Config", {
+      admin: PublicKey.default,
+      oldMint: PublicKey.default,
+      newMint: defaiMint,
+      collection: PublicKey.default,
+      treasury: PublicKey.default,
+      prices: Array(5).fill(tierPrice),
+      paused: false,
+      pendingAdmin: null,
+      adminChangeTimestamp: new anchor.BN(0),
+      vrfEnabled: false,
+      pendingVrfEnabled: null,
+      vrfChangeTimestamp: new anchor.BN(0),
+      oldConversionRatio: { numerator: new anchor.BN(10), denominator: new anchor.BN(1) },
+      pendingOldConversionRatio: null,
+      conversionRatioChangeTimestamp: new anchor.BN(0),
+    }));
+    setAccount(escrow, PROGRAM_ID, await program.coder.accounts.encode("Escrow", { bump: escrowBump }));
+    setAccount(pda("bonus_v6", nftMint)[0], PROGRAM_ID, await program.coder.accounts.encode("BonusStateV6", {
+      mint: nftMint,
+      tier: 0,
+      bonusBps: 500,
+      vestingStart: new anchor.BN(0),
+      vestingDuration: new anchor.BN(0),
+      claimed: false,
+      feeDeducted: new anchor.BN(0),
+    }));
+    setAccount(pda("vesting_v6", nftMint)[0], PROGRAM_ID, await program.coder.accounts.encode("VestingStateV6", {
+      mint: nftMint,
+      totalAmount: new anchor.BN(50_000_000),
+      releasedAmount: new anchor.BN(0),
+      startTimestamp: new anchor.BN(0),
+      endTimestamp: new anchor.BN(0),
+      lastClaimedTimestamp: new anchor.BN(0),
+      payoutWallet: null,
+    }));
+
+    setTokenAccount(nftMint, user.publicKey, BigInt(1));
+    setTokenAccount(defaiMint, user.publicKey, BigInt(0));
+    setTokenAccount(defaiMint, escrow, BigInt(1_000_000_000_000));
+  });
+
+  it("redeem_v6 stays within budget", async () => {
+    const tx = await program.methods
+      .redeemV6()
+      .accounts({
+        user: user.publicKey,
+        nftMint,
+        userNftAta: getAssociatedTokenAddressSync(nftMint, user.publicKey, false, TOKEN_2022_PROGRAM_ID),
+        userDefaiAta: getAssociatedTokenAddressSync(defaiMint, user.publicKey, false, TOKEN_2022_PROGRAM_ID),
+        escrowDefaiAta: getAssociatedTokenAddressSync(defaiMint, pda("escrow")[0], true, TOKEN_2022_PROGRAM_ID),
+        defaiMint,
+        config,
+        escrow: pda("escrow")[0],
+        bonusState: pda("bonus_v6", nftMint)[0],
+        vestingState: pda("vesting_v6", nftMint)[0],
+        systemProgram: SystemProgram.programId,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .transaction();
+
+    tx.recentBlockhash = context.lastBlockhash;
+    tx.feePayer = user.publicKey;
+    tx.sign(user);
+    const meta = await context.banksClient.processTransaction(tx);
+    const units = Number(meta.computeUnitsConsumed);
+    assert.isAtMost(units, REDEEM_V6_BUDGET, `redeem_v6 used ${units} CU`);
+  });
+});
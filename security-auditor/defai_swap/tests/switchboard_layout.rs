@@ -0,0 +1,93 @@
+// Pins the vendored Switchboard On-Demand randomness layout to the byte
+// offsets of the on-chain account and checks the owner, discriminator and
+// length guards in `RandomnessAccountData::parse`. Accounts are parsed against
+// the mainnet program unless a test configures another.
+
+use anchor_lang::{error::Error, prelude::*};
+use defai_swap::{
+    randomness_v2::RandomnessError,
+    switchboard::{RandomnessAccountData, SWITCHBOARD_ON_DEMAND_DEVNET_PID, SWITCHBOARD_ON_DEMAND_MAINNET_PID},
+};
+
+// Offsets from the start of account data, discriminator included
+const SEED_SLOT_OFFSET: usize = 104;
+const REVEAL_SLOT_OFFSET: usize = 144;
+const VALUE_OFFSET: usize = 152;
+
+fn account_bytes(seed_slot: u64, reveal_slot: u64, value: [u8; 32]) -> Vec<u8> {
+    let mut data = vec![0u8; RandomnessAccountData::LEN];
+    data[..8].copy_from_slice(&RandomnessAccountData::DISCRIMINATOR);
+    data[8..40].copy_from_slice(&[1; 32]); // authority
+    data[40..72].copy_from_slice(&[2; 32]); // queue
+    data[SEED_SLOT_OFFSET..SEED_SLOT_OFFSET + 8].copy_from_slice(&seed_slot.to_le_bytes());
+    data[REVEAL_SLOT_OFFSET..REVEAL_SLOT_OFFSET + 8].copy_from_slice(&reveal_slot.to_le_bytes());
+    data[VALUE_OFFSET..VALUE_OFFSET + 32].copy_from_slice(&value);
+    data
+}
+
+fn parse_for(program_id: Pubkey, owner: Pubkey, mut data: Vec<u8>) -> Result<RandomnessAccountData> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    RandomnessAccountData::parse(&info, &program_id)
+}
+
+fn parse(owner: Pubkey, data: Vec<u8>) -> Result<RandomnessAccountData> {
+    parse_for(SWITCHBOARD_ON_DEMAND_MAINNET_PID, owner, data)
+}
+
+#[test]
+fn layout_matches_on_chain_offsets() {
+    assert_eq!(RandomnessAccountData::LEN, 408);
+
+    let parsed = parse(SWITCHBOARD_ON_DEMAND_MAINNET_PID, account_bytes(10, 11, [7; 32])).unwrap();
+    assert_eq!(parsed.authority, Pubkey::new_from_array([1; 32]));
+    assert_eq!(parsed.queue, Pubkey::new_from_array([2; 32]));
+    assert_eq!(parsed.seed_slot, 10);
+    assert_eq!(parsed.reveal_slot, 11);
+    assert_eq!(parsed.value, [7; 32]);
+}
+
+#[test]
+fn accepts_configured_devnet_program() {
+    let devnet = SWITCHBOARD_ON_DEMAND_DEVNET_PID;
+    assert!(parse_for(devnet, devnet, account_bytes(10, 11, [7; 32])).is_ok());
+}
+
+#[test]
+fn rejects_other_cluster_program() {
+    let err = parse(SWITCHBOARD_ON_DEMAND_DEVNET_PID, account_bytes(10, 11, [7; 32])).unwrap_err();
+    assert_eq!(err, Error::from(RandomnessError::InvalidRandomnessOwner));
+}
+
+#[test]
+fn rejects_foreign_owner() {
+    let err = parse(Pubkey::new_unique(), account_bytes(10, 11, [7; 32])).unwrap_err();
+    assert_eq!(err, Error::from(RandomnessError::InvalidRandomnessOwner));
+}
+
+#[test]
+fn rejects_wrong_discriminator() {
+    let mut data = account_bytes(10, 11, [7; 32]);
+    data[0] ^= 1;
+    let err = parse(SWITCHBOARD_ON_DEMAND_MAINNET_PID, data).unwrap_err();
+    assert_eq!(err, Error::from(RandomnessError::InvalidDiscriminator));
+}
+
+#[test]
+fn rejects_truncated_account() {
+    let mut data = account_bytes(10, 11, [7; 32]);
+    data.truncate(RandomnessAccountData::LEN - 1);
+    let err = parse(SWITCHBOARD_ON_DEMAND_MAINNET_PID, data).unwrap_err();
+    assert_eq!(err, Error::from(RandomnessError::InvalidAccountData));
+}
+
+#[test]
+fn value_only_readable_in_reveal_slot() {
+    let revealed = parse(SWITCHBOARD_ON_DEMAND_MAINNET_PID, account_bytes(10, 12, [7; 32])).unwrap();
+    assert_eq!(revealed.get_value(12).unwrap(), [7; 32]);
+    assert_eq!(revealed.get_value(13).unwrap_err(), Error::from(RandomnessError::RandomnessExpired));
+
+    let pending = parse(SWITCHBOARD_ON_DEMAND_MAINNET_PID, account_bytes(10, 0, [0; 32])).unwrap();
+    assert_eq!(pending.get_value(10).unwrap_err(), Error::from(RandomnessError::RandomnessNotResolved));
+}
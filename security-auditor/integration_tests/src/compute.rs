@@ -0,0 +1,49 @@
+//! Compute-unit budgets for the instructions the lifecycle tests drive.
+//!
+//! Each budget is a ceiling for one instruction sent alone, ATA creation
+//! inside the instruction included. `TestEnv::send_metered` fails the test
+//! when an instruction goes over its ceiling, so a regression on the claim,
+//! swap or purchase paths shows up in `cargo test` instead of on mainnet.
+//! Raise a ceiling only together with the change that needs it.
+
+use std::io::Write;
+
+// Per-instruction limit without a compute-budget instruction
+pub const DEFAULT_INSTRUCTION_LIMIT: u64 = 200_000;
+
+pub const BUDGETS: &[(&str, u64)] = &[
+    // defai_estate
+    ("claim_inheritance", 30_000),
+    ("claim_token", 60_000),
+    ("claim_nft", 60_000),
+    ("advance_claim", 10_000),
+    ("compress_claim_record", 40_000),
+    // defai_swap
+    ("swap_defai_for_pnft_v6", 120_000),
+    ("claim_vested_v6", 60_000),
+    ("claim_inherited_vesting", 70_000),
+    ("redeem_v6", 60_000),
+    // defai_app_factory
+    ("purchase_app_access_v2", 150_000),
+    ("refund_purchase", 80_000),
+];
+
+pub fn budget(instruction: &str) -> u64 {
+    BUDGETS
+        .iter()
+        .find(|(name, _)| *name == instruction)
+        .map(|(_, units)| *units)
+        .unwrap_or_else(|| panic!("no compute budget for {instruction}"))
+}
+
+// Fails when `units` is over the instruction's budget. With COMPUTE_REPORT
+// set, each measurement is also appended to that file as
+// `instruction units budget`, so CI can keep the numbers per run.
+pub fn check(instruction: &str, units: u64) {
+    let ceiling = budget(instruction);
+    if let Some(path) = std::env::var_os("COMPUTE_REPORT") {
+        let mut report = std::fs::OpenOptions::new().create(true).append(true).open(path).unwrap();
+        writeln!(report, "{instruction} {units} {ceiling}").unwrap();
+    }
+    assert!(units <= ceiling, "{instruction} used {units} CU, over its budget of {ceiling}");
+}
@@ -0,0 +1,320 @@
+//! Shared fixtures for the workspace integration tests.
+//!
+//! `TestEnv` boots a `solana-program-test` bank with the swap, estate and
+//! app factory programs loaded, plus helpers to mint SPL and Token-2022 test
+//! tokens, move the clock and read accounts back. After `anchor build` the
+//! `.so` files are picked up from `target/deploy` unless `SBF_OUT_DIR` points
+//! elsewhere. Without them the programs are linked in and run natively, so
+//! `cargo test` works without the SBF toolchain; compute-unit budgets are
+//! only meaningful against the built binaries.
+//!
+//! `compute` holds the per-instruction compute-unit budgets checked by
+//! `TestEnv::send_metered`.
+
+pub mod compute;
+
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_utils::StateMut,
+    bpf_loader,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as TokenAccount};
+
+pub use spl_associated_token_account::get_associated_token_address_with_program_id as ata;
+pub use spl_token::ID as TOKEN_PROGRAM_ID;
+pub use spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+
+pub const SOL: u64 = 1_000_000_000;
+
+// Build an Anchor instruction from the generated `accounts` / `instruction` structs
+pub fn anchor_ix(program_id: Pubkey, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+// PDA that signs a program's CPI events; instructions emitting them take it
+// along with the program itself
+pub fn event_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[defai_common::EVENT_AUTHORITY_SEED], program_id).0
+}
+
+// Anchor custom error code carried by a failed transaction, if any
+pub fn error_code(err: &BanksClientError) -> Option<u32> {
+    use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))
+        | BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::Custom(code)),
+            ..
+        } => Some(*code),
+        _ => None,
+    }
+}
+
+fn program_path(name: &str) -> std::path::PathBuf {
+    let dir = std::env::var_os("SBF_OUT_DIR")
+        .or_else(|| std::env::var_os("BPF_OUT_DIR"))
+        .unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/../target/deploy").into());
+    std::path::Path::new(&dir).join(format!("{name}.so"))
+}
+
+// Whether the deploy directory holds the named program's binary. Tests that
+// can't run natively, such as upgrades or Token Metadata CPIs, skip without it
+pub fn has_binary(name: &str) -> bool {
+    program_path(name).exists()
+}
+
+// Built program binary from the deploy directory
+pub fn program_binary(name: &str) -> Vec<u8> {
+    let path = program_path(name);
+    std::fs::read(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()))
+}
+
+// Executable account for a program the workspace doesn't build, such as Token
+// Metadata, loaded from its binary in the deploy directory
+pub fn external_program(name: &str) -> Account {
+    let data = program_binary(name);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader::ID,
+        executable: true,
+        rent_epoch: 0,
+    }
+}
+
+// Native entrypoints for running the programs without their binaries. Anchor's
+// `entry` ties the account slice to the accounts' own lifetime, which the
+// program-test processor signature doesn't, so the slice is leaked for the
+// duration of the test.
+mod native {
+    use solana_sdk::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+    macro_rules! native_entry {
+        ($($name:ident => $program:ident),*) => {$(
+            pub fn $name(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+                let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+                $program::entry(program_id, accounts, data)
+            }
+        )*};
+    }
+
+    native_entry!(swap => defai_swap, estate => defai_estate, app_factory => defai_app_factory);
+}
+
+// Upgradeable loader account: `state` in its first `metadata_len` bytes, then `data`
+pub fn loader_account(state: UpgradeableLoaderState, metadata_len: usize, data: &[u8]) -> Account {
+    let mut account = Account {
+        lamports: Rent::default().minimum_balance(metadata_len + data.len()),
+        data: vec![0; metadata_len + data.len()],
+        owner: bpf_loader_upgradeable::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    account.set_state(&state).unwrap();
+    account.data[metadata_len..].copy_from_slice(data);
+    account
+}
+
+pub struct TestEnv {
+    pub ctx: ProgramTestContext,
+}
+
+impl TestEnv {
+    pub async fn start() -> Self {
+        Self::start_with(&[], |_| {}).await
+    }
+
+    // Like `start`, but each `(name, program id, upgrade authority)` in
+    // `upgradeable` is deployed through the upgradeable loader, and `setup`
+    // can add further accounts before the bank starts
+    pub async fn start_with(upgradeable: &[(&str, Pubkey, Pubkey)], setup: impl FnOnce(&mut ProgramTest)) -> Self {
+        if std::env::var_os("SBF_OUT_DIR").is_none() && std::env::var_os("BPF_OUT_DIR").is_none() {
+            std::env::set_var("SBF_OUT_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/../target/deploy"));
+        }
+
+        // Use the built binaries when all of them are there, otherwise run natively
+        let mut program_test = ProgramTest::default();
+        let built = ["defai_swap", "defai_estate", "defai_app_factory"].iter().all(|name| has_binary(name));
+        program_test.prefer_bpf(built);
+        let deployed = |program_id: Pubkey| upgradeable.iter().any(|(_, id, _)| *id == program_id);
+        if !deployed(defai_swap::ID) {
+            program_test.add_program("defai_swap", defai_swap::ID, processor!(native::swap));
+        }
+        if !deployed(defai_estate::ID) {
+            program_test.add_program("defai_estate", defai_estate::ID, processor!(native::estate));
+        }
+        if !deployed(defai_app_factory::ID) {
+            program_test.add_program("defai_app_factory", defai_app_factory::ID, processor!(native::app_factory));
+        }
+        for (name, program_id, upgrade_authority) in upgradeable {
+            let (programdata_address, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+            let program = loader_account(
+                UpgradeableLoaderState::Program { programdata_address },
+                UpgradeableLoaderState::size_of_program(),
+                &[],
+            );
+            let programdata = loader_account(
+                UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: Some(*upgrade_authority) },
+                UpgradeableLoaderState::size_of_programdata_metadata(),
+                &program_binary(name),
+            );
+            program_test.add_account(*program_id, Account { executable: true, ..program });
+            program_test.add_account(programdata_address, programdata);
+        }
+        setup(&mut program_test);
+
+        Self { ctx: program_test.start_with_context().await }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    // Send instructions paid for by the test payer; returns compute units consumed
+    pub async fn send(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<u64, BanksClientError> {
+        let blockhash = self.ctx.get_new_latest_blockhash().await?;
+        let mut all_signers = vec![&self.ctx.payer];
+        all_signers.extend(signers.iter().copied().filter(|s| s.pubkey() != self.ctx.payer.pubkey()));
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.ctx.payer.pubkey()), &all_signers, blockhash);
+
+        let outcome = self.ctx.banks_client.process_transaction_with_metadata(tx).await?;
+        outcome.result?;
+        Ok(outcome.metadata.map_or(0, |meta| meta.compute_units_consumed))
+    }
+
+    // Send a single instruction and fail if it goes over its budget in `compute::BUDGETS`
+    pub async fn send_metered(
+        &mut self,
+        instruction: &str,
+        ix: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<u64, BanksClientError> {
+        let units = self.send(&[ix], signers).await?;
+        compute::check(instruction, units);
+        Ok(units)
+    }
+
+    pub async fn transfer_sol(&mut self, to: &Pubkey, lamports: u64) {
+        let ix = system_instruction::transfer(&self.payer(), to, lamports);
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    pub async fn funded_keypair(&mut self, lamports: u64) -> Keypair {
+        let keypair = Keypair::new();
+        self.transfer_sol(&keypair.pubkey(), lamports).await;
+        keypair
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.ctx.banks_client.get_balance(*address).await.unwrap()
+    }
+
+    // Mint under `token_program` (SPL Token or Token-2022) with the payer as mint and freeze authority
+    pub async fn create_mint(&mut self, token_program: &Pubkey, decimals: u8) -> Pubkey {
+        let payer = self.payer();
+        self.create_mint_with_authority(token_program, decimals, &payer).await
+    }
+
+    pub async fn create_mint_with_authority(&mut self, token_program: &Pubkey, decimals: u8, authority: &Pubkey) -> Pubkey {
+        let mint = Keypair::new();
+        self.create_mint_at(&mint, token_program, decimals, authority).await;
+        mint.pubkey()
+    }
+
+    // Mint at a chosen address, for tests that seed accounts derived from it at genesis
+    pub async fn create_mint_at(&mut self, mint: &Keypair, token_program: &Pubkey, decimals: u8, authority: &Pubkey) {
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let space = spl_token_2022::state::Mint::LEN;
+        let ixs = [
+            system_instruction::create_account(
+                &self.payer(),
+                &mint.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                token_program,
+            ),
+            spl_token_2022::instruction::initialize_mint2(token_program, &mint.pubkey(), authority, Some(authority), decimals)
+                .unwrap(),
+        ];
+        self.send(&ixs, &[mint]).await.unwrap();
+    }
+
+    // Associated token account for `owner`, which may be a PDA
+    pub async fn create_ata(&mut self, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        let ix = spl_associated_token_account::instruction::create_associated_token_account(
+            &self.payer(),
+            owner,
+            mint,
+            token_program,
+        );
+        self.send(&[ix], &[]).await.unwrap();
+        ata(owner, mint, token_program)
+    }
+
+    // Mint from a payer-controlled mint
+    pub async fn mint_to(&mut self, token_program: &Pubkey, mint: &Pubkey, destination: &Pubkey, amount: u64) {
+        let ix = spl_token_2022::instruction::mint_to(token_program, mint, destination, &self.payer(), &[], amount)
+            .unwrap();
+        self.send(&[ix], &[]).await.unwrap();
+    }
+
+    // Create `owner`'s ATA and mint `amount` into it
+    pub async fn fund_ata(&mut self, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey, amount: u64) -> Pubkey {
+        let account = self.create_ata(owner, mint, token_program).await;
+        if amount > 0 {
+            self.mint_to(token_program, mint, &account, amount).await;
+        }
+        account
+    }
+
+    pub async fn token_balance(&mut self, account: &Pubkey) -> u64 {
+        let data = self.ctx.banks_client.get_account(*account).await.unwrap().expect("token account").data;
+        StateWithExtensions::<TokenAccount>::unpack(&data).unwrap().base.amount
+    }
+
+    pub async fn token_frozen(&mut self, account: &Pubkey) -> bool {
+        let data = self.ctx.banks_client.get_account(*account).await.unwrap().expect("token account").data;
+        StateWithExtensions::<TokenAccount>::unpack(&data).unwrap().base.is_frozen()
+    }
+
+    pub async fn exists(&mut self, address: &Pubkey) -> bool {
+        self.ctx.banks_client.get_account(*address).await.unwrap().is_some()
+    }
+
+    pub async fn account_data_len(&mut self, address: &Pubkey) -> usize {
+        self.ctx.banks_client.get_account(*address).await.unwrap().expect("account").data.len()
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let data = self.ctx.banks_client.get_account(*address).await.unwrap().expect("account").data;
+        T::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.ctx.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+    }
+
+    // Move the bank clock forward; slots advance too so blockhashes stay fresh
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        self.ctx.warp_to_slot(clock.slot + 2).unwrap();
+        clock.slot += 2;
+        clock.unix_timestamp += seconds;
+        self.ctx.set_sysvar(&clock);
+    }
+}
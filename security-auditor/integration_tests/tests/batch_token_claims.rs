@@ -0,0 +1,161 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_estate::{
+    accounts, instruction, Beneficiary, ClaimRecord, EstateError, GlobalCounter, CLAIM_SEED, COUNTER_SEED,
+    ESTATE_SEED, MIN_GRACE_PERIOD, MIN_INACTIVITY_PERIOD,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::AccountMeta,
+    signature::{Keypair, Signer},
+};
+
+// A beneficiary claims their share of three SPL mints in one transaction.
+// The claim record takes all three in a single checkpoint, and mints already
+// claimed, repeated in the batch or passed with the wrong accounts are refused.
+#[tokio::test]
+async fn batch_token_claims() {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_estate::ID;
+
+    let (global_counter, _) = Pubkey::find_program_address(&[COUNTER_SEED], &program_id);
+    let ix = anchor_ix(
+        program_id,
+        accounts::InitializeGlobalCounter { admin: env.payer(), global_counter, system_program: system_program::ID },
+        instruction::InitializeGlobalCounter {},
+    );
+    env.send(&[ix], &[]).await.unwrap();
+
+    let owner = env.funded_keypair(5 * SOL).await;
+    let estate_number = env.account::<GlobalCounter>(&global_counter).await.count;
+    let (estate, _) = Pubkey::find_program_address(
+        &[ESTATE_SEED, owner.pubkey().as_ref(), &estate_number.to_le_bytes()],
+        &program_id,
+    );
+    let ix = anchor_ix(
+        program_id,
+        accounts::CreateEstate {
+            owner: owner.pubkey(),
+            estate,
+            global_counter,
+            estate_mint: Keypair::new().pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateEstate {
+            inactivity_period: MIN_INACTIVITY_PERIOD,
+            grace_period: MIN_GRACE_PERIOD,
+            owner_email_hash: [1; 32],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+
+    let first = env.funded_keypair(SOL).await;
+    let second = env.funded_keypair(SOL).await;
+    let beneficiary = |address: Pubkey, share_percentage: u8| Beneficiary {
+        address,
+        email_hash: [0; 32],
+        share_percentage,
+        claimed: false,
+        notification_sent: false,
+    };
+    let ix = anchor_ix(
+        program_id,
+        accounts::UpdateBeneficiaries { owner: owner.pubkey(), estate, system_program: system_program::ID },
+        instruction::UpdateBeneficiaries {
+            beneficiaries: vec![beneficiary(first.pubkey(), 60), beneficiary(second.pubkey(), 40)],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+
+    // Three mints held by the estate
+    env.transfer_sol(&estate, SOL).await;
+    let mut mints = Vec::new();
+    for _ in 0..3 {
+        let mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+        env.fund_ata(&estate, &mint, &TOKEN_PROGRAM_ID, 1_000_000).await;
+        mints.push(mint);
+    }
+
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    let ix = anchor_ix(
+        program_id,
+        accounts::TriggerInheritance { authority: first.pubkey(), estate },
+        instruction::TriggerInheritance {},
+    );
+    env.send(&[ix], &[&first]).await.unwrap();
+
+    let claim_record = |beneficiary: &Pubkey| {
+        Pubkey::find_program_address(&[CLAIM_SEED, estate.as_ref(), beneficiary.as_ref()], &program_id).0
+    };
+    for (index, heir) in [&first, &second].into_iter().enumerate() {
+        let ix = anchor_ix(
+            program_id,
+            accounts::ClaimInheritance {
+                beneficiary: heir.pubkey(),
+                estate,
+                claim_record: claim_record(&heir.pubkey()),
+                system_program: system_program::ID,
+                event_authority: event_authority(&program_id),
+                program: program_id,
+            },
+            instruction::ClaimInheritance { beneficiary_index: index as u8 },
+        );
+        env.send(&[ix], &[heir]).await.unwrap();
+    }
+
+    // (mint, estate ATA, beneficiary ATA) per mint, after the named accounts
+    let batch = |heir: &Keypair, index: u8, mints: &[Pubkey]| {
+        let mut ix = anchor_ix(
+            program_id,
+            accounts::ClaimTokensBatch {
+                beneficiary: heir.pubkey(),
+                estate,
+                claim_record: claim_record(&heir.pubkey()),
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            instruction::ClaimTokensBatch { beneficiary_index: index },
+        );
+        for mint in mints {
+            ix.accounts.push(AccountMeta::new_readonly(*mint, false));
+            ix.accounts.push(AccountMeta::new(ata(&estate, mint, &TOKEN_PROGRAM_ID), false));
+            ix.accounts.push(AccountMeta::new(ata(&heir.pubkey(), mint, &TOKEN_PROGRAM_ID), false));
+        }
+        ix
+    };
+
+    // First beneficiary takes 60% of all three mints in one go
+    let steps_before = env.account::<ClaimRecord>(&claim_record(&first.pubkey())).await.steps;
+    env.send(&[batch(&first, 0, &mints)], &[&first]).await.unwrap();
+    for mint in &mints {
+        assert_eq!(env.token_balance(&ata(&first.pubkey(), mint, &TOKEN_PROGRAM_ID)).await, 600_000);
+        assert_eq!(env.token_balance(&ata(&estate, mint, &TOKEN_PROGRAM_ID)).await, 400_000);
+    }
+    let record: ClaimRecord = env.account(&claim_record(&first.pubkey())).await;
+    assert_eq!(record.tokens_claimed.iter().map(|c| c.mint).collect::<Vec<_>>(), mints);
+    assert!(record.tokens_claimed.iter().all(|c| c.amount == 600_000));
+    assert_eq!(record.steps, steps_before + 1);
+
+    let err = env.send(&[batch(&first, 0, &mints[..1])], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::TokenAlreadyClaimed.into()));
+
+    // A mint can't be listed twice, and the triples must be complete
+    let err = env.send(&[batch(&second, 1, &[mints[0], mints[0]])], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::TokenAlreadyClaimed.into()));
+    let mut short = batch(&second, 1, &mints[..2]);
+    short.accounts.pop();
+    let err = env.send(&[short], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBatchAccounts.into()));
+    let err = env.send(&[batch(&second, 1, &[])], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBatchSize.into()));
+
+    // Swapping in someone else's ATA is caught
+    let mut wrong_ata = batch(&second, 1, &mints[..1]);
+    wrong_ata.accounts.last_mut().unwrap().pubkey = ata(&first.pubkey(), &mints[0], &TOKEN_PROGRAM_ID);
+    let err = env.send(&[wrong_ata], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBatchAccounts.into()));
+
+    env.send(&[batch(&second, 1, &mints)], &[&second]).await.unwrap();
+    let record: ClaimRecord = env.account(&claim_record(&second.pubkey())).await;
+    assert_eq!(record.tokens_claimed.len(), 3);
+}
@@ -0,0 +1,203 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_estate::{
+    accounts, instruction, ClaimRecord, Estate, EstateError, GlobalCounter, CLAIM_SEED, COUNTER_SEED, ESTATE_SEED,
+    INITIAL_BENEFICIARY_SLOTS, MAX_BENEFICIARIES, MIN_GRACE_PERIOD, MIN_INACTIVITY_PERIOD,
+};
+use defai_integration_tests::{anchor_ix, error_code, event_authority, TestEnv, SOL};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+// Create an estate for a fresh owner, setting up the global counter on first use
+async fn create_estate(env: &mut TestEnv) -> (Keypair, Pubkey) {
+    let program_id = defai_estate::ID;
+    let (global_counter, _) = Pubkey::find_program_address(&[COUNTER_SEED], &program_id);
+    if !env.exists(&global_counter).await {
+        let ix = anchor_ix(
+            program_id,
+            accounts::InitializeGlobalCounter { admin: env.payer(), global_counter, system_program: system_program::ID },
+            instruction::InitializeGlobalCounter {},
+        );
+        env.send(&[ix], &[]).await.unwrap();
+    }
+
+    let owner = env.funded_keypair(5 * SOL).await;
+    let estate_number = env.account::<GlobalCounter>(&global_counter).await.count;
+    let (estate, _) = Pubkey::find_program_address(
+        &[ESTATE_SEED, owner.pubkey().as_ref(), &estate_number.to_le_bytes()],
+        &program_id,
+    );
+    let ix = anchor_ix(
+        program_id,
+        accounts::CreateEstate {
+            owner: owner.pubkey(),
+            estate,
+            global_counter,
+            estate_mint: Keypair::new().pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateEstate {
+            inactivity_period: MIN_INACTIVITY_PERIOD,
+            grace_period: MIN_GRACE_PERIOD,
+            owner_email_hash: [1; 32],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+    (owner, estate)
+}
+
+fn add(estate: Pubkey, signer: &Pubkey, address: Pubkey, share_percentage: u8, from_index: Option<u8>) -> Instruction {
+    anchor_ix(
+        defai_estate::ID,
+        accounts::AddBeneficiary { owner: *signer, estate, system_program: system_program::ID },
+        instruction::AddBeneficiary { address, email_hash: [0; 32], share_percentage, from_index },
+    )
+}
+
+fn trigger(estate: Pubkey, authority: &Pubkey) -> Instruction {
+    anchor_ix(
+        defai_estate::ID,
+        accounts::TriggerInheritance { authority: *authority, estate },
+        instruction::TriggerInheritance {},
+    )
+}
+
+fn claim(estate: Pubkey, heir: &Pubkey, beneficiary_index: u8) -> Instruction {
+    let program_id = defai_estate::ID;
+    anchor_ix(
+        program_id,
+        accounts::ClaimInheritance {
+            beneficiary: *heir,
+            estate,
+            claim_record: Pubkey::find_program_address(&[CLAIM_SEED, estate.as_ref(), heir.as_ref()], &program_id).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(&program_id),
+            program: program_id,
+        },
+        instruction::ClaimInheritance { beneficiary_index },
+    )
+}
+
+// Build an estate's beneficiary list one entry at a time. Every edit moves
+// share between two beneficiaries, so the list totals 100% after each one and
+// an owner who lapses partway through their edits leaves a claimable estate.
+#[tokio::test]
+async fn beneficiary_edits() {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_estate::ID;
+    let (owner, estate) = create_estate(&mut env).await;
+    env.transfer_sol(&estate, SOL).await;
+
+    let editor = || accounts::EditBeneficiary { owner: owner.pubkey(), estate };
+    let remove = |index: u8, heir_index: u8| {
+        anchor_ix(program_id, editor(), instruction::RemoveBeneficiary { index, heir_index })
+    };
+    let set_share = |index: u8, share_percentage: u8, balance_index: u8| {
+        anchor_ix(program_id, editor(), instruction::UpdateBeneficiaryShare { index, share_percentage, balance_index })
+    };
+    let shares = |estate: &Estate| -> Vec<(Pubkey, u8)> {
+        estate.beneficiaries.iter().map(|b| (b.address, b.share_percentage)).collect()
+    };
+
+    // The first beneficiary takes everything, later ones take from an existing share
+    let heir = env.funded_keypair(SOL).await;
+    let (first, second, third) = (Pubkey::new_unique(), Pubkey::new_unique(), heir.pubkey());
+    let err = env.send(&[add(estate, &owner.pubkey(), first, 50, None)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryShares.into()));
+    env.send(&[add(estate, &owner.pubkey(), first, 100, None)], &[&owner]).await.unwrap();
+    let err = env.send(&[add(estate, &owner.pubkey(), second, 30, None)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryIndex.into()));
+    env.send(&[add(estate, &owner.pubkey(), second, 30, Some(0))], &[&owner]).await.unwrap();
+    assert_eq!(shares(&env.account::<Estate>(&estate).await), vec![(first, 70), (second, 30)]);
+
+    // Only the owner edits, nobody is listed twice, and no edit leaves a
+    // beneficiary with nothing
+    let stranger = env.funded_keypair(SOL).await;
+    let err = env.send(&[add(estate, &stranger.pubkey(), third, 20, Some(0))], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::UnauthorizedAccess.into()));
+    let err = env.send(&[add(estate, &owner.pubkey(), third, 70, Some(0))], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ZeroBeneficiaryShare.into()));
+    let err = env.send(&[add(estate, &owner.pubkey(), first, 10, Some(1))], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::DuplicateBeneficiary.into()));
+    let err = env.send(&[add(estate, &owner.pubkey(), third, 0, Some(0))], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ZeroBeneficiaryShare.into()));
+    let err = env.send(&[add(estate, &owner.pubkey(), third, 10, Some(2))], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryIndex.into()));
+
+    // A share change is balanced against another beneficiary, who must keep some
+    let err = env.send(&[set_share(1, 100, 0)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ZeroBeneficiaryShare.into()));
+    let err = env.send(&[set_share(1, 40, 1)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryIndex.into()));
+    env.send(&[set_share(1, 40, 0)], &[&owner]).await.unwrap();
+    env.send(&[add(estate, &owner.pubkey(), third, 10, Some(0))], &[&owner]).await.unwrap();
+    let state = env.account::<Estate>(&estate).await;
+    assert_eq!(shares(&state), vec![(first, 50), (second, 40), (third, 10)]);
+    assert_eq!(state.total_beneficiaries, 3);
+
+    // Removing hands the share to a named heir and shifts later entries up
+    let err = env.send(&[remove(0, 0)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryIndex.into()));
+    let err = env.send(&[remove(3, 0)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::InvalidBeneficiaryIndex.into()));
+    env.send(&[remove(0, 2)], &[&owner]).await.unwrap();
+    let state = env.account::<Estate>(&estate).await;
+    assert_eq!(shares(&state), vec![(second, 40), (third, 60)]);
+    assert_eq!(state.total_beneficiaries, 2);
+
+    // The owner lapses after that partial edit; the estate still opens and
+    // the heir who took the removed share claims it
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    env.send(&[trigger(estate, &stranger.pubkey())], &[&stranger]).await.unwrap();
+    assert!(env.account::<Estate>(&estate).await.is_claimable);
+
+    let estate_lamports = env.lamports(&estate).await;
+    let rent_reserve = env.ctx.banks_client.get_rent().await.unwrap().minimum_balance(env.account_data_len(&estate).await);
+    env.send(&[claim(estate, &third, 1)], &[&heir]).await.unwrap();
+    let record: ClaimRecord =
+        env.account(&Pubkey::find_program_address(&[CLAIM_SEED, estate.as_ref(), third.as_ref()], &program_id).0).await;
+    assert_eq!(record.sol_amount, (estate_lamports - rent_reserve) * 60 / 100);
+
+    // Once claimable the list is frozen
+    let err = env.send(&[set_share(1, 50, 0)], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::EstateClaimable.into()));
+}
+
+// Fill an estate to MAX_BENEFICIARIES at 1% each. The account grows past its
+// initial slots as beneficiaries are added, and the last one claims by index.
+#[tokio::test]
+async fn estate_grows_to_max_beneficiaries() {
+    let mut env = TestEnv::start().await;
+    let (owner, estate) = create_estate(&mut env).await;
+    let initial_len = env.account_data_len(&estate).await;
+
+    let heirs: Vec<Keypair> = (0..MAX_BENEFICIARIES).map(|_| Keypair::new()).collect();
+    for (added, heir) in heirs.iter().enumerate() {
+        // The first heir starts with everything and hands 1% to each one after
+        let (share, from_index) = if added == 0 { (100, None) } else { (1, Some(0)) };
+        env.send(&[add(estate, &owner.pubkey(), heir.pubkey(), share, from_index)], &[&owner]).await.unwrap();
+        if added + 1 == INITIAL_BENEFICIARY_SLOTS {
+            assert_eq!(env.account_data_len(&estate).await, initial_len);
+        }
+    }
+    assert_eq!(env.account_data_len(&estate).await, Estate::space(MAX_BENEFICIARIES as usize));
+    let state = env.account::<Estate>(&estate).await;
+    assert_eq!(state.total_beneficiaries, MAX_BENEFICIARIES);
+    assert_eq!(state.share_total(), 100);
+
+    let err = env.send(&[add(estate, &owner.pubkey(), Pubkey::new_unique(), 1, Some(0))], &[&owner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::TooManyBeneficiaries.into()));
+
+    env.transfer_sol(&estate, 10 * SOL).await;
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    env.send(&[trigger(estate, &owner.pubkey())], &[&owner]).await.unwrap();
+
+    let last = heirs.last().unwrap();
+    env.transfer_sol(&last.pubkey(), SOL).await;
+    let index = MAX_BENEFICIARIES - 1;
+    env.send(&[claim(estate, &last.pubkey(), index)], &[last]).await.unwrap();
+    let state = env.account::<Estate>(&estate).await;
+    assert!(state.beneficiaries[index as usize].claimed);
+    assert_eq!(state.total_claims, 1);
+}
@@ -0,0 +1,328 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{
+    accounts, instruction, AppFactoryError, AppRegistration, BundleError, BundlePurchase, PurchaseSplit, UserAppAccess,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u16 = 1_000;
+const BURN_BPS: u16 = 400;
+const USER_FUNDS: u64 = 200 * DEFAI;
+const BUNDLE_ID: u64 = 7;
+const BUNDLE_PRICE: u64 = 80 * DEFAI;
+// App 0 at the factory fee, app 1 with a 20% override; app 1 has two units
+const PRICES: [u64; 2] = [60 * DEFAI, 40 * DEFAI];
+const FEE_BPS: [u64; 2] = [1_000, 2_000];
+const MAX_SUPPLY: [u64; 2] = [100, 2];
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+fn registration(app_id: u64) -> Pubkey {
+    pda(&[b"app_registration", &app_id.to_le_bytes()])
+}
+
+fn access(user: &Pubkey, app_id: u64) -> Pubkey {
+    pda(&[b"user_app_access", user.as_ref(), &app_id.to_le_bytes()])
+}
+
+struct Bundled {
+    env: TestEnv,
+    treasury: Pubkey,
+    creators: [Keypair; 2],
+    defai_mint: Pubkey,
+    sft_mints: [Pubkey; 2],
+    creator_atas: [Pubkey; 2],
+}
+
+impl Bundled {
+    fn bundle(&self) -> Pubkey {
+        pda(&[b"bundle", self.creators[0].pubkey().as_ref(), &BUNDLE_ID.to_le_bytes()])
+    }
+
+    fn bundle_purchase(&self, user: &Pubkey) -> Pubkey {
+        pda(&[b"bundle_purchase", user.as_ref(), self.bundle().as_ref()])
+    }
+
+    // Created by app 0's creator; app 1's creator consents by signing
+    fn create(&self, price: u64, co_signed: bool) -> Instruction {
+        let mut ix = anchor_ix(
+            defai_app_factory::ID,
+            accounts::CreateBundle {
+                bundle: self.bundle(),
+                creator: self.creators[0].pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::CreateBundle { bundle_id: BUNDLE_ID, app_ids: vec![0, 1], price },
+        );
+        ix.accounts.extend((0..2).map(|app_id| AccountMeta::new_readonly(registration(app_id), false)));
+        ix.accounts.push(AccountMeta::new_readonly(self.creators[1].pubkey(), co_signed));
+        ix
+    }
+
+    fn purchase(&self, user: &Pubkey) -> Instruction {
+        let mut ix = anchor_ix(
+            defai_app_factory::ID,
+            accounts::PurchaseBundle {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                bundle: self.bundle(),
+                bundle_purchase: self.bundle_purchase(user),
+                user_defai_ata: ata(user, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                treasury_defai_ata: ata(&self.treasury, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                user: *user,
+                treasury: self.treasury,
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::PurchaseBundle {},
+        );
+        for app_id in 0..2u64 {
+            ix.accounts.extend([
+                AccountMeta::new(registration(app_id), false),
+                AccountMeta::new(self.creator_atas[app_id as usize], false),
+                AccountMeta::new_readonly(pda(&[b"revenue_split", &app_id.to_le_bytes()]), false),
+            ]);
+        }
+        ix
+    }
+
+    fn claim(&self, user: &Pubkey, app_id: u64) -> Instruction {
+        let sft_mint = self.sft_mints[app_id as usize];
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::ClaimBundleApp {
+                app_factory: pda(&[b"app_factory"]),
+                bundle: self.bundle(),
+                bundle_purchase: self.bundle_purchase(user),
+                app_registration: registration(app_id),
+                user_app_access: access(user, app_id),
+                sft_mint,
+                user_sft_ata: ata(user, &sft_mint, &TOKEN_PROGRAM_ID),
+                user: *user,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::ClaimBundleApp { app_id },
+        )
+    }
+
+    // A single purchase of `app_id` outside the bundle
+    fn purchase_app(&self, user: &Pubkey, app_id: u64) -> Instruction {
+        let sft_mint = self.sft_mints[app_id as usize];
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::PurchaseAppAccessOptimized {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: registration(app_id),
+                user: *user,
+                defai_mint: self.defai_mint,
+                creator: self.creators[app_id as usize].pubkey(),
+                treasury: self.treasury,
+                user_app_access: access(user, app_id),
+                sft_mint,
+                user_sft_ata: ata(user, &sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(user, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                creator_defai_ata: self.creator_atas[app_id as usize],
+                creator_vesting: None,
+                vesting_vault: None,
+                charity_defai_ata: None,
+                bonus_state: None,
+                bonus_nft_ata: None,
+                program_registry: None,
+                treasury_defai_ata: ata(&self.treasury, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                revenue_split: pda(&[b"revenue_split", &app_id.to_le_bytes()]),
+                sale: None,
+                affiliate_stats: None,
+                referrer_defai_ata: None,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::PurchaseAppAccessV2 { app_id },
+        )
+    }
+
+    // A wallet holding USER_FUNDS DEFAI and an SFT account for each app
+    async fn buyer(&mut self) -> Keypair {
+        let buyer = self.env.funded_keypair(5 * SOL).await;
+        self.env.fund_ata(&buyer.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+        for sft_mint in self.sft_mints {
+            self.env.create_ata(&buyer.pubkey(), &sft_mint, &TOKEN_PROGRAM_ID).await;
+        }
+        buyer
+    }
+}
+
+// Factory with a purchase burn and two apps from different creators
+async fn registered() -> Bundled {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = Keypair::new().pubkey();
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury,
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetBurnBps { app_factory, authority },
+            instruction::SetBurnBps { burn_bps: BURN_BPS },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creators = [env.funded_keypair(5 * SOL).await, env.funded_keypair(5 * SOL).await];
+    let mut sft_mints = [Pubkey::default(); 2];
+    let mut creator_atas = [Pubkey::default(); 2];
+    for (app_id, creator) in creators.iter().enumerate() {
+        sft_mints[app_id] = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+        creator_atas[app_id] = env.create_ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+        let ix = anchor_ix(
+            program_id,
+            accounts::RegisterApp {
+                app_factory,
+                factory_stats,
+                app_registration: registration(app_id as u64),
+                sft_mint: sft_mints[app_id],
+                verified_creator: None,
+                category_registry: None,
+                creator_defai_ata: None,
+                treasury_defai_ata: None,
+                stake_vault: None,
+                treasury_ledger: None,
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RegisterApp {
+                price: PRICES[app_id],
+                max_supply: MAX_SUPPLY[app_id],
+                metadata_uri: "ipfs://app".into(),
+                categories: vec![],
+                royalty_bps: 0,
+                refund_policy: None,
+            },
+        );
+        env.send(&[ix], &[creator]).await.unwrap();
+    }
+
+    let ix = anchor_ix(
+        program_id,
+        accounts::SetAppFeeOverride { app_factory, app_registration: registration(1), authority },
+        instruction::SetAppFeeOverride { app_id: 1, fee_bps: Some(FEE_BPS[1] as u16) },
+    );
+    env.send(&[ix], &[]).await.unwrap();
+
+    Bundled { env, treasury, creators, defai_mint, sft_mints, creator_atas }
+}
+
+// A bundle sold once and claimed app by app. Each creator is paid their
+// weighted share less their app's own fee, the burn comes out of the fees in
+// proportion, and the buyer's units are counted against supply at purchase so
+// a sell-out before the claims doesn't strand them.
+#[tokio::test]
+async fn bundle_split_and_supply() {
+    let mut b = registered().await;
+    let [first_creator, second_creator] = [b.creators[0].insecure_clone(), b.creators[1].insecure_clone()];
+
+    // The bundle is discounted and every creator signs for their app
+    let err = b.env.send(&[b.create(100 * DEFAI, true)], &[&first_creator, &second_creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(BundleError::BundlePriceTooHigh.into()));
+    let err = b.env.send(&[b.create(BUNDLE_PRICE, false)], &[&first_creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(BundleError::MissingCreatorSignature.into()));
+    b.env.send(&[b.create(BUNDLE_PRICE, true)], &[&first_creator, &second_creator]).await.unwrap();
+
+    // The price splits 60:40 by list price, then each part pays its app's fee
+    let item_prices = PRICES.map(|price| BUNDLE_PRICE * price / (PRICES[0] + PRICES[1]));
+    let fees = [0, 1].map(|i| item_prices[i] * FEE_BPS[i] / 10_000);
+    let fee_total = fees[0] + fees[1];
+    let burned = BUNDLE_PRICE * BURN_BPS as u64 / 10_000;
+    let first_burned = burned * fees[0] / fee_total;
+    let expected = [
+        PurchaseSplit { burned: first_burned, treasury: fees[0] - first_burned, creator: item_prices[0] - fees[0], ..Default::default() },
+        PurchaseSplit {
+            burned: burned - first_burned,
+            treasury: fees[1] - (burned - first_burned),
+            creator: item_prices[1] - fees[1],
+            ..Default::default()
+        },
+    ];
+
+    let buyer = b.buyer().await;
+    b.env.send(&[b.purchase(&buyer.pubkey())], &[&buyer]).await.unwrap();
+    assert_eq!(b.env.token_balance(&ata(&buyer.pubkey(), &b.defai_mint, &TOKEN_PROGRAM_ID)).await, USER_FUNDS - BUNDLE_PRICE);
+    assert_eq!(b.env.token_balance(&b.creator_atas[0]).await, expected[0].creator);
+    assert_eq!(b.env.token_balance(&b.creator_atas[1]).await, expected[1].creator);
+    assert_eq!(b.env.token_balance(&ata(&b.treasury, &b.defai_mint, &TOKEN_PROGRAM_ID)).await, fee_total - burned);
+    let purchase: BundlePurchase = b.env.account(&b.bundle_purchase(&buyer.pubkey())).await;
+    assert!(purchase.paid_out[..2] == expected);
+
+    // Both units are reserved before anything is claimed
+    for app_id in 0..2 {
+        assert_eq!(b.env.account::<AppRegistration>(&registration(app_id)).await.current_supply, 1);
+    }
+
+    // App 1 sells its last unit on its own, so no further bundle can be bought
+    let other = b.buyer().await;
+    b.env.send(&[b.purchase_app(&other.pubkey(), 1)], &[&other]).await.unwrap();
+    let late = b.buyer().await;
+    let err = b.env.send(&[b.purchase(&late.pubkey())], &[&late]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(AppFactoryError::MaxSupplyReached.into()));
+
+    // The first buyer still claims app 1 from their reserved unit, once
+    b.env.send(&[b.claim(&buyer.pubkey(), 1)], &[&buyer]).await.unwrap();
+    assert_eq!(b.env.token_balance(&ata(&buyer.pubkey(), &b.sft_mints[1], &TOKEN_PROGRAM_ID)).await, 1);
+    let registration_1: AppRegistration = b.env.account(&registration(1)).await;
+    assert_eq!(registration_1.current_supply, MAX_SUPPLY[1]);
+    assert!(b.env.send(&[b.claim(&buyer.pubkey(), 1)], &[&buyer]).await.is_err());
+
+    // Each access records its item's price and where that part went
+    b.env.send(&[b.claim(&buyer.pubkey(), 0)], &[&buyer]).await.unwrap();
+    for app_id in 0..2 {
+        let record: UserAppAccess = b.env.account(&access(&buyer.pubkey(), app_id)).await;
+        assert_eq!(record.purchase_price, item_prices[app_id as usize]);
+        assert!(record.paid_out == expected[app_id as usize]);
+    }
+    let registration_0: AppRegistration = b.env.account(&registration(0)).await;
+    assert_eq!((registration_0.current_supply, registration_0.gross_revenue), (1, item_prices[0]));
+    assert_eq!(b.env.account::<BundlePurchase>(&b.bundle_purchase(&buyer.pubkey())).await.claimed_mask, 0b11);
+}
@@ -0,0 +1,297 @@
+use anchor_lang::{error::ErrorCode as AnchorErrorCode, prelude::Pubkey, system_program, AnchorSerialize};
+use defai_estate::{
+    accounts, instruction, Beneficiary, ClaimRecord, EstateError, GlobalCounter, CLAIM_SEED, COUNTER_SEED,
+    ESTATE_SEED, MIN_GRACE_PERIOD, MIN_INACTIVITY_PERIOD,
+};
+use defai_integration_tests::{
+    anchor_ix, ata, error_code, event_authority, external_program, has_binary, TestEnv, SOL, TOKEN_PROGRAM_ID,
+};
+use mpl_token_metadata::{
+    accounts::{MasterEdition, Metadata, TokenRecord},
+    instructions::{CreateV1Builder, MintV1Builder},
+    types::{Key, PrintSupply, TokenStandard},
+};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    sysvar,
+};
+
+// Programmable NFT claims through Token Metadata. The rejected cases all fail
+// before the transfer CPI, so they run without Token Metadata loaded; the
+// successful claim needs its binary in the deploy directory (see the README).
+
+struct Heirs {
+    estate: Pubkey,
+    first: Keypair,
+    second: Keypair,
+}
+
+fn claim_record(estate: &Pubkey, beneficiary: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CLAIM_SEED, estate.as_ref(), beneficiary.as_ref()], &defai_estate::ID).0
+}
+
+// An estate split 60/40 between two beneficiaries, not yet claimable
+async fn create_estate(env: &mut TestEnv) -> Heirs {
+    let program_id = defai_estate::ID;
+    let (global_counter, _) = Pubkey::find_program_address(&[COUNTER_SEED], &program_id);
+    let ix = anchor_ix(
+        program_id,
+        accounts::InitializeGlobalCounter { admin: env.payer(), global_counter, system_program: system_program::ID },
+        instruction::InitializeGlobalCounter {},
+    );
+    env.send(&[ix], &[]).await.unwrap();
+
+    let owner = env.funded_keypair(5 * SOL).await;
+    let estate_number = env.account::<GlobalCounter>(&global_counter).await.count;
+    let (estate, _) = Pubkey::find_program_address(
+        &[ESTATE_SEED, owner.pubkey().as_ref(), &estate_number.to_le_bytes()],
+        &program_id,
+    );
+    let ix = anchor_ix(
+        program_id,
+        accounts::CreateEstate {
+            owner: owner.pubkey(),
+            estate,
+            global_counter,
+            estate_mint: Keypair::new().pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateEstate {
+            inactivity_period: MIN_INACTIVITY_PERIOD,
+            grace_period: MIN_GRACE_PERIOD,
+            owner_email_hash: [1; 32],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+
+    let first = env.funded_keypair(SOL).await;
+    let second = env.funded_keypair(SOL).await;
+    let beneficiary = |address: Pubkey, share_percentage: u8| Beneficiary {
+        address,
+        email_hash: [0; 32],
+        share_percentage,
+        claimed: false,
+        notification_sent: false,
+    };
+    let ix = anchor_ix(
+        program_id,
+        accounts::UpdateBeneficiaries { owner: owner.pubkey(), estate, system_program: system_program::ID },
+        instruction::UpdateBeneficiaries {
+            beneficiaries: vec![beneficiary(first.pubkey(), 60), beneficiary(second.pubkey(), 40)],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+    env.transfer_sol(&estate, SOL).await;
+
+    Heirs { estate, first, second }
+}
+
+// Lapse the estate, trigger it and have both beneficiaries claim their SOL
+async fn unlock(env: &mut TestEnv, heirs: &Heirs) {
+    let program_id = defai_estate::ID;
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    let ix = anchor_ix(
+        program_id,
+        accounts::TriggerInheritance { authority: heirs.first.pubkey(), estate: heirs.estate },
+        instruction::TriggerInheritance {},
+    );
+    env.send(&[ix], &[&heirs.first]).await.unwrap();
+
+    for (index, heir) in [&heirs.first, &heirs.second].into_iter().enumerate() {
+        let ix = anchor_ix(
+            program_id,
+            accounts::ClaimInheritance {
+                beneficiary: heir.pubkey(),
+                estate: heirs.estate,
+                claim_record: claim_record(&heirs.estate, &heir.pubkey()),
+                system_program: system_program::ID,
+                event_authority: event_authority(&program_id),
+                program: program_id,
+            },
+            instruction::ClaimInheritance { beneficiary_index: index as u8 },
+        );
+        env.send(&[ix], &[heir]).await.unwrap();
+    }
+}
+
+// Close the token stage so NFT claims are open
+async fn advance_to_nfts(env: &mut TestEnv, estate: Pubkey, heir: &Keypair) {
+    let ix = anchor_ix(
+        defai_estate::ID,
+        accounts::AdvanceClaim { beneficiary: heir.pubkey(), estate, claim_record: claim_record(&estate, &heir.pubkey()) },
+        instruction::AdvanceClaim {},
+    );
+    env.send(&[ix], &[heir]).await.unwrap();
+}
+
+fn claim_pnft(estate: Pubkey, heir: &Pubkey, record: Pubkey, mint: Pubkey, beneficiary_index: u8) -> Instruction {
+    let estate_nft_account = ata(&estate, &mint, &TOKEN_PROGRAM_ID);
+    let beneficiary_nft_account = ata(heir, &mint, &TOKEN_PROGRAM_ID);
+    anchor_ix(
+        defai_estate::ID,
+        accounts::ClaimPnft {
+            beneficiary: *heir,
+            estate,
+            claim_record: record,
+            nft_mint: mint,
+            estate_nft_account,
+            beneficiary_nft_account,
+            metadata: Metadata::find_pda(&mint).0,
+            edition: MasterEdition::find_pda(&mint).0,
+            owner_token_record: TokenRecord::find_pda(&mint, &estate_nft_account).0,
+            destination_token_record: TokenRecord::find_pda(&mint, &beneficiary_nft_account).0,
+            authorization_rules_program: None,
+            authorization_rules: None,
+            token_metadata_program: mpl_token_metadata::ID,
+            sysvar_instructions: sysvar::instructions::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimPnft { beneficiary_index },
+    )
+}
+
+// Claims before the estate unlocks, out of stage, by the wrong beneficiary or
+// for an NFT that isn't programmable are refused before Token Metadata is called
+#[tokio::test]
+async fn claim_pnft_rejections() {
+    // A plain NFT, with Token Metadata's record of it seeded at genesis
+    let mint = Keypair::new();
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: Pubkey::new_unique(),
+        mint: mint.pubkey(),
+        name: "Heirloom".into(),
+        symbol: String::new(),
+        uri: String::new(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    let data = metadata.try_to_vec().unwrap();
+    let account = Account { lamports: SOL, data, owner: mpl_token_metadata::ID, executable: false, rent_epoch: 0 };
+    let mut env = TestEnv::start_with(&[], |program_test| {
+        program_test.add_account(Metadata::find_pda(&mint.pubkey()).0, account);
+    })
+    .await;
+    let heirs = create_estate(&mut env).await;
+    let (estate, first) = (heirs.estate, &heirs.first);
+
+    // The estate holds the NFT
+    let payer = env.payer();
+    env.create_mint_at(&mint, &TOKEN_PROGRAM_ID, 0, &payer).await;
+    let mint = mint.pubkey();
+    env.fund_ata(&estate, &mint, &TOKEN_PROGRAM_ID, 1).await;
+
+    // Before the estate unlocks there is no claim record to claim against
+    let first_record = claim_record(&estate, &first.pubkey());
+    let ix = claim_pnft(estate, &first.pubkey(), first_record, mint, 0);
+    let err = env.send(&[ix], &[first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(AnchorErrorCode::AccountNotInitialized.into()));
+
+    unlock(&mut env, &heirs).await;
+
+    // NFTs wait for their stage to open
+    let ix = claim_pnft(estate, &first.pubkey(), first_record, mint, 0);
+    let err = env.send(&[ix], &[first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::WrongClaimStage.into()));
+    advance_to_nfts(&mut env, estate, first).await;
+
+    // The other beneficiary can't use the first one's record or index
+    let second = &heirs.second;
+    let ix = claim_pnft(estate, &second.pubkey(), first_record, mint, 0);
+    let err = env.send(&[ix], &[second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::UnauthorizedBeneficiary.into()));
+    let ix = claim_pnft(estate, &second.pubkey(), claim_record(&estate, &second.pubkey()), mint, 0);
+    let err = env.send(&[ix], &[second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::UnauthorizedBeneficiary.into()));
+
+    // Plain NFTs go through claim_nft
+    let ix = claim_pnft(estate, &first.pubkey(), first_record, mint, 0);
+    let err = env.send(&[ix], &[first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::NotProgrammableNft.into()));
+    assert!(env.account::<ClaimRecord>(&first_record).await.nfts_claimed.is_empty());
+}
+
+// A pNFT minted to the estate moves to the beneficiary through Token
+// Metadata's transfer and is recorded on their claim; it can't be claimed twice
+#[tokio::test]
+async fn claim_pnft_transfers_through_token_metadata() {
+    if !has_binary("mpl_token_metadata") {
+        eprintln!("skipping: mpl_token_metadata.so is not in the deploy directory");
+        return;
+    }
+    let mut env = TestEnv::start_with(&[], |program_test| {
+        program_test.add_account(mpl_token_metadata::ID, external_program("mpl_token_metadata"));
+    })
+    .await;
+    let heirs = create_estate(&mut env).await;
+    let (estate, first) = (heirs.estate, &heirs.first);
+
+    let mint = Keypair::new();
+    let (metadata, _) = Metadata::find_pda(&mint.pubkey());
+    let (master_edition, _) = MasterEdition::find_pda(&mint.pubkey());
+    let estate_nft_account = ata(&estate, &mint.pubkey(), &TOKEN_PROGRAM_ID);
+    let create = CreateV1Builder::new()
+        .metadata(metadata)
+        .master_edition(Some(master_edition))
+        .mint(mint.pubkey(), true)
+        .authority(env.payer())
+        .payer(env.payer())
+        .update_authority(env.payer(), true)
+        .spl_token_program(Some(TOKEN_PROGRAM_ID))
+        .name("Heirloom".into())
+        .uri("https://example.com/heirloom.json".into())
+        .seller_fee_basis_points(0)
+        .token_standard(TokenStandard::ProgrammableNonFungible)
+        .print_supply(PrintSupply::Zero)
+        .instruction();
+    env.send(&[create], &[&mint]).await.unwrap();
+    let mint_to_estate = MintV1Builder::new()
+        .token(estate_nft_account)
+        .token_owner(Some(estate))
+        .metadata(metadata)
+        .master_edition(Some(master_edition))
+        .token_record(Some(TokenRecord::find_pda(&mint.pubkey(), &estate_nft_account).0))
+        .mint(mint.pubkey())
+        .authority(env.payer())
+        .payer(env.payer())
+        .amount(1)
+        .instruction();
+    env.send(&[mint_to_estate], &[]).await.unwrap();
+    assert_eq!(env.token_balance(&estate_nft_account).await, 1);
+
+    unlock(&mut env, &heirs).await;
+    advance_to_nfts(&mut env, estate, first).await;
+
+    // Headroom for Token Metadata's pNFT transfer on top of the claim itself
+    let first_record = claim_record(&estate, &first.pubkey());
+    let claim = || {
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(400_000),
+            claim_pnft(estate, &first.pubkey(), first_record, mint.pubkey(), 0),
+        ]
+    };
+    env.send(&claim(), &[first]).await.unwrap();
+
+    let beneficiary_nft_account = ata(&first.pubkey(), &mint.pubkey(), &TOKEN_PROGRAM_ID);
+    assert_eq!(env.token_balance(&beneficiary_nft_account).await, 1);
+    assert_eq!(env.token_balance(&estate_nft_account).await, 0);
+    assert!(env.exists(&TokenRecord::find_pda(&mint.pubkey(), &beneficiary_nft_account).0).await);
+    let record: ClaimRecord = env.account(&first_record).await;
+    assert_eq!(record.nfts_claimed, [mint.pubkey()]);
+
+    let err = env.send(&claim(), &[first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::NFTAlreadyClaimed.into()));
+}
@@ -0,0 +1,27 @@
+// The budget table itself; needs no program binaries. The measurements are
+// taken by the lifecycle tests through `TestEnv::send_metered`.
+
+use defai_integration_tests::compute::{budget, BUDGETS, DEFAULT_INSTRUCTION_LIMIT};
+use std::collections::HashSet;
+
+#[test]
+fn every_instruction_has_one_budget() {
+    let mut seen = HashSet::new();
+    for (instruction, _) in BUDGETS {
+        assert!(seen.insert(instruction), "{instruction} is budgeted twice");
+    }
+}
+
+#[test]
+fn budgets_fit_without_a_compute_budget_instruction() {
+    // Wallets that don't add SetComputeUnitLimit still get 200k per instruction
+    for (instruction, units) in BUDGETS {
+        assert!(*units <= DEFAULT_INSTRUCTION_LIMIT, "{instruction} is budgeted {units} CU");
+    }
+}
+
+#[test]
+#[should_panic(expected = "no compute budget for unknown_instruction")]
+fn unbudgeted_instructions_fail() {
+    budget("unknown_instruction");
+}
@@ -0,0 +1,346 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{
+    accounts, instruction, AppRegistration, CurrencyError, DisputeError, PurchaseSplit, RefundError, SplitRecipient,
+    UserAppAccess,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+const USDC: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u64 = 1_000;
+const BURN_BPS: u64 = 400;
+const PRICE: u64 = 50_000_000;
+const SOL_PRICE: u64 = SOL / 2;
+const USDC_PRICE: u64 = 25 * USDC;
+const APP_ID: u64 = 0;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+fn access(user: &Pubkey) -> Pubkey {
+    pda(&[b"user_app_access", user.as_ref(), &APP_ID.to_le_bytes()])
+}
+
+struct Priced {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    defai_mint: Pubkey,
+    usdc_mint: Pubkey,
+    sft_mint: Pubkey,
+    app_registration: Pubkey,
+}
+
+impl Priced {
+    fn set_usdc_mint(&self, usdc_mint: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::SetUsdcMint {
+                app_factory: pda(&[b"app_factory"]),
+                currency_config: pda(&[b"currency_config"]),
+                usdc_mint: *usdc_mint,
+                authority: self.env.payer(),
+                system_program: system_program::ID,
+            },
+            instruction::SetUsdcMint {},
+        )
+    }
+
+    fn set_prices(&self, sol_price: u64, usdc_price: u64) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::SetAppCurrencyPrices {
+                app_registration: self.app_registration,
+                app_currency_prices: pda(&[b"app_currency_prices", &APP_ID.to_le_bytes()]),
+                creator: self.creator.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::SetAppCurrencyPrices { app_id: APP_ID, sol_price, usdc_price },
+        )
+    }
+
+    fn set_split(&self, wallet: Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::SetRevenueSplit {
+                app_registration: self.app_registration,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                creator: self.creator.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::SetRevenueSplit { app_id: APP_ID, recipients: vec![SplitRecipient { wallet, share_bps: 10_000 }] },
+        )
+    }
+
+    fn buy_with_sol(&self, user: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::PurchaseAppWithSol {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                app_currency_prices: pda(&[b"app_currency_prices", &APP_ID.to_le_bytes()]),
+                user_app_access: access(user),
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                user: *user,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::PurchaseAppWithSol { app_id: APP_ID },
+        )
+    }
+
+    fn buy_with_usdc(&self, user: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::PurchaseAppWithUsdc {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                currency_config: pda(&[b"currency_config"]),
+                app_registration: self.app_registration,
+                app_currency_prices: pda(&[b"app_currency_prices", &APP_ID.to_le_bytes()]),
+                user_app_access: access(user),
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_usdc_ata: ata(user, &self.usdc_mint, &TOKEN_PROGRAM_ID),
+                creator_usdc_ata: ata(&self.creator.pubkey(), &self.usdc_mint, &TOKEN_PROGRAM_ID),
+                treasury_usdc_ata: ata(&self.treasury.pubkey(), &self.usdc_mint, &TOKEN_PROGRAM_ID),
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                user: *user,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                usdc_mint: self.usdc_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::PurchaseAppWithUsdc { app_id: APP_ID },
+        )
+    }
+
+    fn refund(&self, user: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::RefundPurchase {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                rent_payer: *user,
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(user, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                user_review: pda(&[b"app_review", user.as_ref(), &APP_ID.to_le_bytes()]),
+                creator_defai_ata: ata(&self.creator.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID),
+                treasury_defai_ata: ata(&self.treasury.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID),
+                creator_vesting: None,
+                vesting_vault: None,
+                user: *user,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RefundPurchase { app_id: APP_ID, reason: "Changed my mind".into() },
+        )
+    }
+
+    fn open_dispute(&self, user: &Pubkey) -> Instruction {
+        let dispute = pda(&[b"dispute", user.as_ref(), &APP_ID.to_le_bytes()]);
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::OpenDispute {
+                app_factory: pda(&[b"app_factory"]),
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                dispute,
+                dispute_vault: pda(&[b"dispute_vault", dispute.as_ref()]),
+                creator_vesting: None,
+                vesting_vault: None,
+                sft_mint: self.sft_mint,
+                defai_mint: self.defai_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user: *user,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::OpenDispute { app_id: APP_ID, reason_cid: "bafy-complaint".into() },
+        )
+    }
+
+    // A wallet with SOL, USDC_PRICE USDC and an empty DEFAI account to be refunded into
+    async fn buyer(&mut self) -> Keypair {
+        let user = self.env.funded_keypair(5 * SOL).await;
+        self.env.fund_ata(&user.pubkey(), &self.usdc_mint, &TOKEN_PROGRAM_ID, USDC_PRICE).await;
+        self.env.create_ata(&user.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID).await;
+        user
+    }
+}
+
+// Factory with a purchase burn, an accepted USDC mint and one app, before
+// its alternate prices are set
+async fn registered() -> Priced {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let usdc_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS as u16 },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetBurnBps { app_factory, authority },
+            instruction::SetBurnBps { burn_bps: BURN_BPS as u16 },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let ix = anchor_ix(
+        program_id,
+        accounts::RegisterApp {
+            app_factory,
+            factory_stats,
+            app_registration,
+            sft_mint,
+            verified_creator: None,
+            category_registry: None,
+            creator_defai_ata: None,
+            treasury_defai_ata: None,
+            stake_vault: None,
+            treasury_ledger: None,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RegisterApp {
+            price: PRICE,
+            max_supply: 100,
+            metadata_uri: "ipfs://app".into(),
+            categories: vec![],
+            royalty_bps: 0,
+            refund_policy: None,
+        },
+    );
+    env.send(&[ix], &[&creator]).await.unwrap();
+
+    // Refund and dispute accounts are checked before the handler runs
+    env.create_ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+    env.create_ata(&treasury.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+
+    let mut priced = Priced { env, creator, treasury, defai_mint, usdc_mint, sft_mint, app_registration };
+    let ix = priced.set_usdc_mint(&usdc_mint);
+    priced.env.send(&[ix], &[]).await.unwrap();
+    priced
+}
+
+// SOL and USDC purchases pay the creator and treasury directly, burn
+// nothing, and are final: neither refunds nor disputes pay them back
+#[tokio::test]
+async fn sol_and_usdc_purchases() {
+    let mut p = registered().await;
+    let (creator, treasury) = (p.creator.insecure_clone(), p.treasury.insecure_clone());
+
+    // The USDC mint can't be DEFAI
+    let err = p.env.send(&[p.set_usdc_mint(&p.defai_mint)], &[]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(CurrencyError::InvalidUsdcMint.into()));
+
+    // A 0 price turns a currency off
+    p.env.send(&[p.set_prices(SOL_PRICE, 0)], &[&creator]).await.unwrap();
+    let usdc_buyer = p.buyer().await;
+    let err = p.env.send(&[p.buy_with_usdc(&usdc_buyer.pubkey())], &[&usdc_buyer]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(CurrencyError::CurrencyNotAccepted.into()));
+
+    let fee = SOL_PRICE * PLATFORM_FEE_BPS / 10_000;
+    let creator_lamports = p.env.lamports(&creator.pubkey()).await;
+    let treasury_lamports = p.env.lamports(&treasury.pubkey()).await;
+    let sol_buyer = p.buyer().await;
+    p.env.send(&[p.buy_with_sol(&sol_buyer.pubkey())], &[&sol_buyer]).await.unwrap();
+    assert_eq!(p.env.lamports(&creator.pubkey()).await, creator_lamports + SOL_PRICE - fee);
+    assert_eq!(p.env.lamports(&treasury.pubkey()).await, treasury_lamports + fee);
+    let record: UserAppAccess = p.env.account(&access(&sol_buyer.pubkey())).await;
+    assert_eq!(record.payment_mint, Pubkey::default());
+    assert_eq!(record.purchase_price, SOL_PRICE);
+    assert!(record.paid_out == PurchaseSplit::default());
+
+    p.env.send(&[p.set_prices(SOL_PRICE, USDC_PRICE)], &[&creator]).await.unwrap();
+    p.env.send(&[p.buy_with_usdc(&usdc_buyer.pubkey())], &[&usdc_buyer]).await.unwrap();
+    let fee = USDC_PRICE * PLATFORM_FEE_BPS / 10_000;
+    assert_eq!(p.env.token_balance(&ata(&usdc_buyer.pubkey(), &p.usdc_mint, &TOKEN_PROGRAM_ID)).await, 0);
+    assert_eq!(p.env.token_balance(&ata(&creator.pubkey(), &p.usdc_mint, &TOKEN_PROGRAM_ID)).await, USDC_PRICE - fee);
+    assert_eq!(p.env.token_balance(&ata(&treasury.pubkey(), &p.usdc_mint, &TOKEN_PROGRAM_ID)).await, fee);
+    let record: UserAppAccess = p.env.account(&access(&usdc_buyer.pubkey())).await;
+    assert_eq!(record.payment_mint, p.usdc_mint);
+    assert_eq!(record.purchase_price, USDC_PRICE);
+    assert_eq!(p.env.account::<AppRegistration>(&p.app_registration).await.current_supply, 2);
+
+    // Refunds and disputes only pay back DEFAI, so both purchases stand
+    for buyer in [&sol_buyer, &usdc_buyer] {
+        let err = p.env.send(&[p.refund(&buyer.pubkey())], &[buyer, &creator, &treasury]).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(RefundError::NonDefaiPurchase.into()));
+        let err = p.env.send(&[p.open_dispute(&buyer.pubkey())], &[buyer]).await.unwrap_err();
+        assert_eq!(error_code(&err), Some(DisputeError::NonDefaiPurchase.into()));
+        assert_eq!(p.env.token_balance(&ata(&buyer.pubkey(), &p.sft_mint, &TOKEN_PROGRAM_ID)).await, 1);
+        assert!(p.env.exists(&access(&buyer.pubkey())).await);
+    }
+}
+
+// Revenue splits only cover DEFAI, so an app with one can't be bought in SOL
+// or USDC
+#[tokio::test]
+async fn revenue_split_requires_defai() {
+    let mut p = registered().await;
+    let creator = p.creator.insecure_clone();
+    p.env.send(&[p.set_prices(SOL_PRICE, USDC_PRICE)], &[&creator]).await.unwrap();
+    p.env.send(&[p.set_split(Pubkey::new_unique())], &[&creator]).await.unwrap();
+
+    let user = p.buyer().await;
+    let err = p.env.send(&[p.buy_with_sol(&user.pubkey())], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(CurrencyError::RevenueSplitActive.into()));
+    let err = p.env.send(&[p.buy_with_usdc(&user.pubkey())], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(CurrencyError::RevenueSplitActive.into()));
+    assert!(!p.env.exists(&access(&user.pubkey())).await);
+}
@@ -0,0 +1,390 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{
+    accounts, instruction, AppFactoryError, AppRegistration, CreatorVesting, Delisting, DelistingError,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u16 = 1_000;
+const PRICE: u64 = 50 * DEFAI;
+const CREATOR_NET: u64 = PRICE - PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+const USER_FUNDS: u64 = 100 * DEFAI;
+const APP_ID: u64 = 0;
+const DAY: i64 = 24 * 60 * 60;
+const REFUND_WINDOW: i64 = 3 * DAY;
+const VESTING_DAYS: u16 = 10;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+fn access(buyer: &Pubkey) -> Pubkey {
+    pda(&[b"user_app_access", buyer.as_ref(), &APP_ID.to_le_bytes()])
+}
+
+struct Delistable {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    buyers: [Keypair; 2],
+    defai_mint: Pubkey,
+    sft_mint: Pubkey,
+    app_registration: Pubkey,
+    creator_defai_ata: Pubkey,
+    // (creator_vesting, vesting_vault) for apps with payout vesting
+    vesting: Option<(Pubkey, Pubkey)>,
+}
+
+impl Delistable {
+    fn delisting(&self) -> Pubkey {
+        pda(&[b"delisting", &APP_ID.to_le_bytes()])
+    }
+
+    fn vault(&self) -> Pubkey {
+        pda(&[b"delisting_vault", self.delisting().as_ref()])
+    }
+
+    fn purchase(&self, buyer: &Pubkey) -> Instruction {
+        let (app_factory, factory_stats) = (pda(&[b"app_factory"]), pda(&[b"factory_stats"]));
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::PurchaseAppAccessOptimized {
+                app_factory,
+                factory_stats,
+                app_registration: self.app_registration,
+                user: *buyer,
+                defai_mint: self.defai_mint,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                user_app_access: access(buyer),
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(buyer, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(buyer, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                creator_defai_ata: self.creator_defai_ata,
+                creator_vesting: self.vesting.map(|(vesting, _)| vesting),
+                vesting_vault: self.vesting.map(|(_, vault)| vault),
+                charity_defai_ata: None,
+                bonus_state: None,
+                bonus_nft_ata: None,
+                program_registry: None,
+                treasury_defai_ata: ata(&self.treasury.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID),
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                sale: None,
+                affiliate_stats: None,
+                referrer_defai_ata: None,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::PurchaseAppAccessV2 { app_id: APP_ID },
+        )
+    }
+
+    fn delist(&self, initiator: &Pubkey, refund_window: i64) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::DelistApp {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                delisting: self.delisting(),
+                delisting_vault: self.vault(),
+                creator_vesting: self.vesting.map(|(vesting, _)| vesting),
+                vesting_vault: self.vesting.map(|(_, vault)| vault),
+                initiator: *initiator,
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::DelistApp { app_id: APP_ID, refund_window },
+        )
+    }
+
+    fn fund(&self, amount: u64) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::FundDelistingRefunds {
+                delisting: self.delisting(),
+                delisting_vault: self.vault(),
+                funder_defai_ata: self.creator_defai_ata,
+                funder: self.creator.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::FundDelistingRefunds { app_id: APP_ID, amount },
+        )
+    }
+
+    fn claim(&self, buyer: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::ClaimDelistingRefund {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                delisting: self.delisting(),
+                delisting_vault: self.vault(),
+                user_app_access: access(buyer),
+                rent_payer: *buyer,
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(buyer, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(buyer, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                user: *buyer,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::ClaimDelistingRefund { app_id: APP_ID },
+        )
+    }
+
+    fn release(&self, caller: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::ReleaseDelistingFunds {
+                app_factory: pda(&[b"app_factory"]),
+                delisting: self.delisting(),
+                delisting_vault: self.vault(),
+                creator_defai_ata: self.creator_defai_ata,
+                creator: self.creator.pubkey(),
+                caller: *caller,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::ReleaseDelistingFunds { app_id: APP_ID },
+        )
+    }
+}
+
+// Factory with one registered app bought by two buyers, optionally with the
+// creator's payouts vesting over VESTING_DAYS
+async fn purchased(vesting: bool) -> Delistable {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let ix = anchor_ix(
+        program_id,
+        accounts::RegisterApp {
+            app_factory,
+            factory_stats,
+            app_registration,
+            sft_mint,
+            verified_creator: None,
+            category_registry: None,
+            creator_defai_ata: None,
+            treasury_defai_ata: None,
+            stake_vault: None,
+            treasury_ledger: None,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RegisterApp {
+            price: PRICE,
+            max_supply: 100,
+            metadata_uri: "ipfs://app".into(),
+            categories: vec![],
+            royalty_bps: 0,
+            refund_policy: None,
+        },
+    );
+    env.send(&[ix], &[&creator]).await.unwrap();
+
+    let vesting = if vesting {
+        let creator_vesting = pda(&[b"creator_vesting", &APP_ID.to_le_bytes()]);
+        let vesting_vault = pda(&[b"creator_vesting_vault", creator_vesting.as_ref()]);
+        let ix = anchor_ix(
+            program_id,
+            accounts::EnablePayoutVesting {
+                app_factory,
+                app_registration,
+                creator_vesting,
+                vesting_vault,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                defai_mint,
+                creator: creator.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::EnablePayoutVesting { app_id: APP_ID, vesting_days: VESTING_DAYS },
+        );
+        env.send(&[ix], &[&creator]).await.unwrap();
+        Some((creator_vesting, vesting_vault))
+    } else {
+        None
+    };
+
+    let creator_defai_ata = env.create_ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+    let buyers = [env.funded_keypair(5 * SOL).await, env.funded_keypair(5 * SOL).await];
+    let mut app = Delistable {
+        env,
+        creator,
+        treasury,
+        buyers,
+        defai_mint,
+        sft_mint,
+        app_registration,
+        creator_defai_ata,
+        vesting,
+    };
+    for index in 0..app.buyers.len() {
+        let buyer = app.buyers[index].insecure_clone();
+        app.env.fund_ata(&buyer.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+        app.env.create_ata(&buyer.pubkey(), &sft_mint, &TOKEN_PROGRAM_ID).await;
+        let ix = app.purchase(&buyer.pubkey());
+        app.env.send(&[ix], &[&buyer]).await.unwrap();
+    }
+    app
+}
+
+// The creator delists, funds the escrow that opens the refund window, one
+// holder refunds themselves and the other lets the window pass; the leftover
+// then goes back to the creator
+#[tokio::test]
+async fn delisting_refunds_then_release() {
+    let mut app = purchased(false).await;
+    let creator = app.creator.insecure_clone();
+    let [first, second] = [app.buyers[0].insecure_clone(), app.buyers[1].insecure_clone()];
+    let stranger = app.env.funded_keypair(SOL).await;
+
+    // Only the creator or the factory authority delists, for a window of 1 to 30 days
+    let err = app.env.send(&[app.delist(&stranger.pubkey(), REFUND_WINDOW)], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::UnauthorizedDelisting.into()));
+    let err = app.env.send(&[app.delist(&creator.pubkey(), DAY - 1)], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::InvalidRefundWindow.into()));
+
+    // Delisting stops sales for good and owes both holders their price back
+    app.env.send(&[app.delist(&creator.pubkey(), REFUND_WINDOW)], &[&creator]).await.unwrap();
+    let registration: AppRegistration = app.env.account(&app.app_registration).await;
+    assert!(registration.delisted && !registration.is_active);
+    let delisting: Delisting = app.env.account(&app.delisting()).await;
+    assert_eq!(delisting.required_escrow, 2 * PRICE);
+    assert!(!delisting.refunds_open());
+
+    let late_buyer = app.env.funded_keypair(5 * SOL).await;
+    app.env.fund_ata(&late_buyer.pubkey(), &app.defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+    app.env.create_ata(&late_buyer.pubkey(), &app.sft_mint, &TOKEN_PROGRAM_ID).await;
+    let err = app.env.send(&[app.purchase(&late_buyer.pubkey())], &[&late_buyer]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(AppFactoryError::AppNotActive.into()));
+
+    // Refunds and release wait for the escrow to cover every holder
+    let err = app.env.send(&[app.claim(&first.pubkey())], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::RefundsNotOpen.into()));
+    let err = app.env.send(&[app.release(&stranger.pubkey())], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::RefundsNotOpen.into()));
+
+    // The creator holds their net from both sales and tops up the rest
+    let creator_defai_ata = app.creator_defai_ata;
+    app.env.mint_to(&TOKEN_PROGRAM_ID, &app.defai_mint, &creator_defai_ata, 2 * (PRICE - CREATOR_NET)).await;
+    app.env.send(&[app.fund(PRICE)], &[&creator]).await.unwrap();
+    assert!(!app.env.account::<Delisting>(&app.delisting()).await.refunds_open());
+    app.env.send(&[app.fund(PRICE)], &[&creator]).await.unwrap();
+    let delisting: Delisting = app.env.account(&app.delisting()).await;
+    assert_eq!(delisting.escrowed, 2 * PRICE);
+    assert!(delisting.refunds_open());
+
+    // A holder burns their SFT for the full price, once
+    let first_sft = ata(&first.pubkey(), &app.sft_mint, &TOKEN_PROGRAM_ID);
+    app.env.send(&[app.claim(&first.pubkey())], &[&first]).await.unwrap();
+    assert_eq!(app.env.token_balance(&ata(&first.pubkey(), &app.defai_mint, &TOKEN_PROGRAM_ID)).await, USER_FUNDS);
+    assert_eq!(app.env.token_balance(&first_sft).await, 0);
+    assert!(!app.env.exists(&access(&first.pubkey())).await);
+    assert!(app.env.send(&[app.claim(&first.pubkey())], &[&first]).await.is_err());
+
+    let registration: AppRegistration = app.env.account(&app.app_registration).await;
+    assert_eq!((registration.current_supply, registration.refund_count, registration.refund_amount), (1, 1, PRICE));
+    let delisting: Delisting = app.env.account(&app.delisting()).await;
+    assert_eq!((delisting.refunded, delisting.refund_count), (PRICE, 1));
+
+    // Leftovers stay in escrow until the window closes, and late refunds are refused
+    let err = app.env.send(&[app.release(&stranger.pubkey())], &[&stranger]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::RefundWindowOpen.into()));
+    app.env.warp_forward(REFUND_WINDOW + 1).await;
+    let err = app.env.send(&[app.claim(&second.pubkey())], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::RefundWindowClosed.into()));
+    let err = app.env.send(&[app.fund(1)], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DelistingError::RefundWindowClosed.into()));
+
+    // Anyone releases the unclaimed escrow to the creator, closing the vault
+    app.env.send(&[app.release(&stranger.pubkey())], &[&stranger]).await.unwrap();
+    assert_eq!(app.env.token_balance(&creator_defai_ata).await, PRICE);
+    assert!(!app.env.exists(&app.vault()).await);
+    assert!(app.env.account::<Delisting>(&app.delisting()).await.released);
+    assert_eq!(app.env.token_balance(&ata(&second.pubkey(), &app.sft_mint, &TOKEN_PROGRAM_ID)).await, 1);
+}
+
+// With payout vesting, delisting moves the creator's unpaid proceeds into the
+// escrow first, so the creator only funds the part they were already paid
+#[tokio::test]
+async fn delisting_escrows_unvested_proceeds() {
+    let mut app = purchased(true).await;
+    let creator = app.creator.insecure_clone();
+    let first = app.buyers[0].insecure_clone();
+    let (creator_vesting, vesting_vault) = app.vesting.unwrap();
+
+    // The factory authority can delist too
+    let authority = app.env.payer();
+    app.env.send(&[app.delist(&authority, REFUND_WINDOW)], &[]).await.unwrap();
+    assert_eq!(app.env.token_balance(&app.vault()).await, 2 * CREATOR_NET);
+    assert_eq!(app.env.token_balance(&vesting_vault).await, 0);
+    let vesting: CreatorVesting = app.env.account(&creator_vesting).await;
+    assert_eq!((vesting.locked, vesting.claimable), (0, 0));
+
+    let delisting: Delisting = app.env.account(&app.delisting()).await;
+    assert_eq!(delisting.initiated_by, authority);
+    assert_eq!((delisting.escrowed, delisting.required_escrow), (2 * CREATOR_NET, 2 * PRICE));
+    assert!(!delisting.refunds_open());
+
+    // The platform fees are the only shortfall
+    let creator_defai_ata = app.creator_defai_ata;
+    let shortfall = 2 * (PRICE - CREATOR_NET);
+    app.env.mint_to(&TOKEN_PROGRAM_ID, &app.defai_mint, &creator_defai_ata, shortfall).await;
+    app.env.send(&[app.fund(shortfall)], &[&creator]).await.unwrap();
+    assert!(app.env.account::<Delisting>(&app.delisting()).await.refunds_open());
+
+    app.env.send(&[app.claim(&first.pubkey())], &[&first]).await.unwrap();
+    assert_eq!(app.env.token_balance(&ata(&first.pubkey(), &app.defai_mint, &TOKEN_PROGRAM_ID)).await, USER_FUNDS);
+    assert_eq!(app.env.token_balance(&app.vault()).await, PRICE);
+}
@@ -0,0 +1,479 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{
+    accounts, instruction, AppRegistration, CreatorVesting, Dispute, DisputeError, DisputeStatus, RefundError,
+    UserAppAccess,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u16 = 1_000;
+const PRICE: u64 = 50 * DEFAI;
+const CREATOR_NET: u64 = PRICE - PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+const USER_FUNDS: u64 = 100 * DEFAI;
+const APP_ID: u64 = 0;
+const DAY: i64 = 24 * 60 * 60;
+const VESTING_DAYS: u16 = 10;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+struct Disputable {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    user: Keypair,
+    arbiter: Keypair,
+    defai_mint: Pubkey,
+    sft_mint: Pubkey,
+    app_registration: Pubkey,
+    user_app_access: Pubkey,
+    user_defai_ata: Pubkey,
+    user_sft_ata: Pubkey,
+    creator_defai_ata: Pubkey,
+    // (creator_vesting, vesting_vault) for apps with payout vesting
+    vesting: Option<(Pubkey, Pubkey)>,
+}
+
+impl Disputable {
+    fn dispute(&self) -> Pubkey {
+        pda(&[b"dispute", self.user.pubkey().as_ref(), &APP_ID.to_le_bytes()])
+    }
+
+    fn dispute_vault(&self) -> Pubkey {
+        pda(&[b"dispute_vault", self.dispute().as_ref()])
+    }
+
+    fn open(&self) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::OpenDispute {
+                app_factory: pda(&[b"app_factory"]),
+                app_registration: self.app_registration,
+                user_app_access: self.user_app_access,
+                dispute: self.dispute(),
+                dispute_vault: self.dispute_vault(),
+                creator_vesting: self.vesting.map(|(vesting, _)| vesting),
+                vesting_vault: self.vesting.map(|(_, vault)| vault),
+                sft_mint: self.sft_mint,
+                defai_mint: self.defai_mint,
+                user_sft_ata: self.user_sft_ata,
+                user: self.user.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::OpenDispute { app_id: APP_ID, reason_cid: "bafy-complaint".into() },
+        )
+    }
+
+    fn escrow(&self) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::EscrowDisputeFunds {
+                app_factory: pda(&[b"app_factory"]),
+                dispute: self.dispute(),
+                dispute_vault: self.dispute_vault(),
+                creator_defai_ata: self.creator_defai_ata,
+                creator: self.creator.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::EscrowDisputeFunds { app_id: APP_ID, user: self.user.pubkey() },
+        )
+    }
+
+    fn resolve(&self, refund: bool) -> Instruction {
+        self.resolve_as(&self.arbiter.pubkey(), refund)
+    }
+
+    fn resolve_as(&self, resolver: &Pubkey, refund: bool) -> Instruction {
+        let user = self.user.pubkey();
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::ResolveDispute {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                dispute_config: pda(&[b"dispute_config"]),
+                app_registration: self.app_registration,
+                dispute: self.dispute(),
+                buyer: user,
+                user_app_access: self.user_app_access,
+                user_review: pda(&[b"app_review", user.as_ref(), &APP_ID.to_le_bytes()]),
+                dispute_vault: self.dispute_vault(),
+                creator_vesting: self.vesting.map(|(vesting, _)| vesting),
+                vesting_vault: self.vesting.map(|(_, vault)| vault),
+                user_defai_ata: self.user_defai_ata,
+                creator_defai_ata: self.creator_defai_ata,
+                sft_mint: self.sft_mint,
+                user_sft_ata: self.user_sft_ata,
+                rent_payer: user,
+                resolver: *resolver,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::ResolveDispute { app_id: APP_ID, user, refund },
+        )
+    }
+
+    // Every ruling closes the dispute and its vault
+    async fn assert_closed(&mut self) {
+        let (dispute, dispute_vault) = (self.dispute(), self.dispute_vault());
+        assert!(!self.env.exists(&dispute).await);
+        assert!(!self.env.exists(&dispute_vault).await);
+    }
+}
+
+// Factory with an arbiter and one registered app that `user` has bought
+// access to, optionally with the creator's payouts vesting over VESTING_DAYS
+async fn purchase(vesting: bool) -> Disputable {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+    let arbiter = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetDisputeArbiter {
+                app_factory,
+                dispute_config: pda(&[b"dispute_config"]),
+                authority,
+                system_program: system_program::ID,
+            },
+            instruction::SetDisputeArbiter { arbiter: arbiter.pubkey() },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let ix = anchor_ix(
+        program_id,
+        accounts::RegisterApp {
+            app_factory,
+            factory_stats,
+            app_registration,
+            sft_mint,
+            verified_creator: None,
+            category_registry: None,
+            creator_defai_ata: None,
+            treasury_defai_ata: None,
+            stake_vault: None,
+            treasury_ledger: None,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RegisterApp {
+            price: PRICE,
+            max_supply: 100,
+            metadata_uri: "ipfs://app".into(),
+            categories: vec![],
+            royalty_bps: 0,
+            refund_policy: None,
+        },
+    );
+    env.send(&[ix], &[&creator]).await.unwrap();
+
+    let vesting = if vesting {
+        let creator_vesting = pda(&[b"creator_vesting", &APP_ID.to_le_bytes()]);
+        let vesting_vault = pda(&[b"creator_vesting_vault", creator_vesting.as_ref()]);
+        let ix = anchor_ix(
+            program_id,
+            accounts::EnablePayoutVesting {
+                app_factory,
+                app_registration,
+                creator_vesting,
+                vesting_vault,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                defai_mint,
+                creator: creator.pubkey(),
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::EnablePayoutVesting { app_id: APP_ID, vesting_days: VESTING_DAYS },
+        );
+        env.send(&[ix], &[&creator]).await.unwrap();
+        Some((creator_vesting, vesting_vault))
+    } else {
+        None
+    };
+
+    let creator_defai_ata = env.create_ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+    let user = env.funded_keypair(5 * SOL).await;
+    let user_defai_ata = env.fund_ata(&user.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+    let user_sft_ata = env.create_ata(&user.pubkey(), &sft_mint, &TOKEN_PROGRAM_ID).await;
+    let user_app_access = pda(&[b"user_app_access", user.pubkey().as_ref(), &APP_ID.to_le_bytes()]);
+
+    let ix = anchor_ix(
+        program_id,
+        accounts::PurchaseAppAccessOptimized {
+            app_factory,
+            factory_stats,
+            app_registration,
+            user: user.pubkey(),
+            defai_mint,
+            creator: creator.pubkey(),
+            treasury: treasury.pubkey(),
+            user_app_access,
+            sft_mint,
+            user_sft_ata,
+            user_defai_ata,
+            creator_defai_ata,
+            creator_vesting: vesting.map(|(vesting, _)| vesting),
+            vesting_vault: vesting.map(|(_, vault)| vault),
+            charity_defai_ata: None,
+            bonus_state: None,
+            bonus_nft_ata: None,
+            program_registry: None,
+            treasury_defai_ata: ata(&treasury.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID),
+            revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+            sale: None,
+            affiliate_stats: None,
+            referrer_defai_ata: None,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::PurchaseAppAccessV2 { app_id: APP_ID },
+    );
+    env.send(&[ix], &[&user]).await.unwrap();
+
+    Disputable {
+        env,
+        creator,
+        treasury,
+        user,
+        arbiter,
+        defai_mint,
+        sft_mint,
+        app_registration,
+        user_app_access,
+        user_defai_ata,
+        user_sft_ata,
+        creator_defai_ata,
+        vesting,
+    }
+}
+
+fn review(d: &Disputable, rating: u8) -> Instruction {
+    let user = d.user.pubkey();
+    anchor_ix(
+        defai_app_factory::ID,
+        accounts::SubmitReview {
+            app_factory: pda(&[b"app_factory"]),
+            review: pda(&[b"app_review", user.as_ref(), &APP_ID.to_le_bytes()]),
+            app_registration: d.app_registration,
+            user_app_access: d.user_app_access,
+            user,
+            system_program: system_program::ID,
+        },
+        instruction::SubmitReview { app_id: APP_ID, rating, comment_cid: "bafy-review".into() },
+    )
+}
+
+// The buyer disputes, the creator escrows their recorded net and the arbiter
+// rules for the buyer: the escrow is refunded, access and the buyer's review
+// are removed, and the dispute closes with its rent back to the buyer
+#[tokio::test]
+async fn refund_ruling() {
+    let mut d = purchase(false).await;
+    let (user, creator, arbiter) = (d.user.insecure_clone(), d.creator.insecure_clone(), d.arbiter.insecure_clone());
+
+    d.env.send(&[review(&d, 1)], &[&user]).await.unwrap();
+    assert_eq!(d.env.account::<AppRegistration>(&d.app_registration).await.review_count, 1);
+
+    // Opening freezes the SFT and records the creator's net from the sale
+    d.env.send(&[d.open()], &[&user]).await.unwrap();
+    assert!(d.env.token_frozen(&d.user_sft_ata).await);
+    let dispute: Dispute = d.env.account(&d.dispute()).await;
+    assert_eq!(dispute.amount, CREATOR_NET);
+    assert!(dispute.status == DisputeStatus::Open);
+    assert!(d.env.account::<UserAppAccess>(&d.user_app_access).await.disputed);
+
+    // Nothing to rule on until the creator escrows or their window passes
+    let err = d.env.send(&[d.resolve(true)], &[&arbiter]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DisputeError::AwaitingCreatorEscrow.into()));
+    let err = d.env.send(&[d.resolve_as(&creator.pubkey(), true)], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DisputeError::UnauthorizedResolver.into()));
+
+    d.env.send(&[d.escrow()], &[&creator]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.dispute_vault()).await, CREATOR_NET);
+    assert_eq!(d.env.token_balance(&d.creator_defai_ata).await, 0);
+    assert!(d.env.account::<Dispute>(&d.dispute()).await.status == DisputeStatus::Escrowed);
+
+    let lamports_before = d.env.lamports(&user.pubkey()).await;
+    d.env.send(&[d.resolve(true)], &[&arbiter]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.user_defai_ata).await, USER_FUNDS - PRICE + CREATOR_NET);
+    assert_eq!(d.env.token_balance(&d.user_sft_ata).await, 0);
+    assert!(!d.env.exists(&d.user_app_access).await);
+    assert!(!d.env.exists(&pda(&[b"app_review", user.pubkey().as_ref(), &APP_ID.to_le_bytes()])).await);
+    let registration: AppRegistration = d.env.account(&d.app_registration).await;
+    assert_eq!((registration.review_count, registration.rating_sum), (0, 0));
+    assert_eq!(registration.current_supply, 0);
+    assert_eq!(registration.refund_amount, CREATOR_NET);
+    d.assert_closed().await;
+    assert!(d.env.lamports(&user.pubkey()).await > lamports_before);
+}
+
+// A release pays the escrow back to the creator and the buyer keeps access,
+// but the ruling is final: no second dispute and no refund afterwards
+#[tokio::test]
+async fn release_ruling() {
+    let mut d = purchase(false).await;
+    let (user, creator, arbiter) = (d.user.insecure_clone(), d.creator.insecure_clone(), d.arbiter.insecure_clone());
+
+    d.env.send(&[d.open()], &[&user]).await.unwrap();
+    d.env.send(&[d.escrow()], &[&creator]).await.unwrap();
+    d.env.send(&[d.resolve(false)], &[&arbiter]).await.unwrap();
+
+    assert_eq!(d.env.token_balance(&d.creator_defai_ata).await, CREATOR_NET);
+    assert_eq!(d.env.token_balance(&d.user_defai_ata).await, USER_FUNDS - PRICE);
+    assert_eq!(d.env.token_balance(&d.user_sft_ata).await, 1);
+    assert!(!d.env.token_frozen(&d.user_sft_ata).await);
+    d.assert_closed().await;
+
+    let err = d.env.send(&[d.open()], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DisputeError::AlreadyDisputed.into()));
+
+    let refund = anchor_ix(
+        defai_app_factory::ID,
+        accounts::RefundPurchase {
+            app_factory: pda(&[b"app_factory"]),
+            factory_stats: pda(&[b"factory_stats"]),
+            app_registration: d.app_registration,
+            user_app_access: d.user_app_access,
+            rent_payer: user.pubkey(),
+            sft_mint: d.sft_mint,
+            user_sft_ata: d.user_sft_ata,
+            user_defai_ata: d.user_defai_ata,
+            user_review: pda(&[b"app_review", user.pubkey().as_ref(), &APP_ID.to_le_bytes()]),
+            creator_defai_ata: d.creator_defai_ata,
+            treasury_defai_ata: ata(&d.treasury.pubkey(), &d.defai_mint, &TOKEN_PROGRAM_ID),
+            creator_vesting: None,
+            vesting_vault: None,
+            user: user.pubkey(),
+            creator: creator.pubkey(),
+            treasury: d.treasury.pubkey(),
+            defai_mint: d.defai_mint,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RefundPurchase { app_id: APP_ID, reason: "Lost the dispute".into() },
+    );
+    let treasury = d.treasury.insecure_clone();
+    let err = d.env.send(&[refund], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::PurchaseDisputed.into()));
+}
+
+// A creator who never escrows loses by default once their window passes:
+// the buyer keeps access and the app is pulled
+#[tokio::test]
+async fn creator_default() {
+    let mut d = purchase(false).await;
+    let (user, creator, arbiter) = (d.user.insecure_clone(), d.creator.insecure_clone(), d.arbiter.insecure_clone());
+
+    d.env.send(&[d.open()], &[&user]).await.unwrap();
+    d.env.warp_forward(3 * DAY + 1).await;
+    let err = d.env.send(&[d.escrow()], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DisputeError::ResponseWindowExpired.into()));
+
+    // A refund ruling isn't needed; the default applies either way
+    d.env.send(&[d.resolve(true)], &[&arbiter]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.user_sft_ata).await, 1);
+    assert!(!d.env.token_frozen(&d.user_sft_ata).await);
+    assert!(d.env.exists(&d.user_app_access).await);
+    assert!(!d.env.account::<AppRegistration>(&d.app_registration).await.is_active);
+    d.assert_closed().await;
+}
+
+// With payout vesting the disputed net is withheld from the stream at once.
+// Whatever the creator already claimed can't be withheld, so they escrow that
+// part, and a refund ruling returns both.
+#[tokio::test]
+async fn vested_partial_withhold() {
+    let mut d = purchase(true).await;
+    let (user, creator, arbiter) = (d.user.insecure_clone(), d.creator.insecure_clone(), d.arbiter.insecure_clone());
+    let (creator_vesting, vesting_vault) = d.vesting.unwrap();
+    assert_eq!(d.env.token_balance(&vesting_vault).await, CREATOR_NET);
+
+    // Half the stream vests and the creator claims it
+    d.env.warp_forward(VESTING_DAYS as i64 / 2 * DAY).await;
+    let ix = anchor_ix(
+        defai_app_factory::ID,
+        accounts::ClaimCreatorProceeds {
+            app_factory: pda(&[b"app_factory"]),
+            app_registration: d.app_registration,
+            creator_vesting,
+            vesting_vault,
+            creator_defai_ata: d.creator_defai_ata,
+            defai_mint: d.defai_mint,
+            creator: creator.pubkey(),
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::ClaimCreatorProceeds { app_id: APP_ID },
+    );
+    d.env.send(&[ix], &[&creator]).await.unwrap();
+    let claimed = d.env.token_balance(&d.creator_defai_ata).await;
+    assert!(claimed > 0 && claimed < CREATOR_NET);
+
+    // Opening withholds the rest of the stream; the claimed part is outstanding
+    d.env.send(&[d.open()], &[&user]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.dispute_vault()).await, CREATOR_NET - claimed);
+    assert_eq!(d.env.token_balance(&vesting_vault).await, 0);
+    let vesting: CreatorVesting = d.env.account(&creator_vesting).await;
+    assert_eq!((vesting.locked, vesting.claimable), (0, 0));
+    let dispute: Dispute = d.env.account(&d.dispute()).await;
+    assert_eq!(dispute.amount, CREATOR_NET);
+    assert!(dispute.status == DisputeStatus::Open);
+
+    d.env.send(&[d.escrow()], &[&creator]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.creator_defai_ata).await, 0);
+    assert_eq!(d.env.token_balance(&d.dispute_vault()).await, CREATOR_NET);
+
+    d.env.send(&[d.resolve(true)], &[&arbiter]).await.unwrap();
+    assert_eq!(d.env.token_balance(&d.user_defai_ata).await, USER_FUNDS - PRICE + CREATOR_NET);
+    assert!(!d.env.exists(&d.user_app_access).await);
+    d.assert_closed().await;
+}
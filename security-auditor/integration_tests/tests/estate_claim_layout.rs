@@ -0,0 +1,114 @@
+// Pins defai_swap's vendored copy of the defai_estate `ClaimRecord` layout to
+// the real account type. Swap can't link defai_estate, so this crate, which
+// links both, is where a layout drift would show up. Runs without program
+// binaries.
+
+use anchor_lang::{error::Error, prelude::*, Discriminator};
+use defai_estate::{ClaimRecord, ClaimStage, TokenClaim, INITIAL_CLAIM_SLOTS};
+use defai_swap::inheritance::{EstateClaimRecord, InheritanceError};
+
+// Allocation used by claim_inheritance: room for 10 token and 10 NFT claims
+// plus the claim checkpoint
+const CLAIM_RECORD_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 1 + (4 + 10 * (32 + 8)) + (4 + 10 * 32) + 1 + 4 + 4 + 8;
+
+fn record(nfts: Vec<Pubkey>) -> ClaimRecord {
+    ClaimRecord {
+        estate: Pubkey::new_unique(),
+        beneficiary: Pubkey::new_unique(),
+        claim_time: 1_700_000_000,
+        sol_amount: 6 * 1_000_000_000,
+        share_percentage: 60,
+        tokens_claimed: vec![TokenClaim { mint: Pubkey::new_unique(), amount: 600 }],
+        nfts_claimed: nfts,
+        stage: ClaimStage::Nfts,
+        steps: 4,
+        rwas_claimed: 0,
+        last_step_at: 1_700_000_600,
+    }
+}
+
+fn account_bytes(record: &ClaimRecord) -> Vec<u8> {
+    let mut data = Vec::with_capacity(CLAIM_RECORD_SPACE);
+    record.try_serialize(&mut data).unwrap();
+    data.resize(data.len().max(CLAIM_RECORD_SPACE), 0);
+    data
+}
+
+fn parse(owner: Pubkey, mut data: Vec<u8>) -> Result<EstateClaimRecord> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+    EstateClaimRecord::parse(&info, &defai_estate::ID)
+}
+
+#[test]
+fn discriminator_matches_estate() {
+    assert_eq!(EstateClaimRecord::DISCRIMINATOR, ClaimRecord::discriminator());
+}
+
+#[test]
+fn claim_record_space_matches_the_allocation() {
+    assert_eq!(ClaimRecord::space(INITIAL_CLAIM_SLOTS, INITIAL_CLAIM_SLOTS), CLAIM_RECORD_SPACE);
+
+    // A full record grows by exactly one entry; one with free bytes keeps its size
+    let full = ClaimRecord {
+        tokens_claimed: vec![TokenClaim { mint: Pubkey::new_unique(), amount: 1 }; INITIAL_CLAIM_SLOTS],
+        ..record(vec![Pubkey::new_unique(); INITIAL_CLAIM_SLOTS])
+    };
+    assert_eq!(full.grown_space(0, 1, CLAIM_RECORD_SPACE), CLAIM_RECORD_SPACE + 32);
+    assert_eq!(full.grown_space(1, 0, CLAIM_RECORD_SPACE), CLAIM_RECORD_SPACE + 32 + 8);
+    assert_eq!(record(vec![]).grown_space(1, 0, CLAIM_RECORD_SPACE), CLAIM_RECORD_SPACE);
+}
+
+#[test]
+fn parses_a_padded_estate_claim_record() {
+    let nfts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let original = record(nfts.clone());
+    let parsed = parse(defai_estate::ID, account_bytes(&original)).unwrap();
+
+    assert_eq!(parsed.estate, original.estate);
+    assert_eq!(parsed.beneficiary, original.beneficiary);
+    assert_eq!(parsed.claim_time, original.claim_time);
+    assert_eq!(parsed.sol_amount, original.sol_amount);
+    assert_eq!(parsed.share_percentage, 60);
+    assert_eq!(parsed.tokens_claimed.len(), 1);
+    assert_eq!(parsed.tokens_claimed[0].amount, 600);
+    assert_eq!(parsed.nfts_claimed, nfts);
+}
+
+// Records grown past the initial slots carry the checkpoint after the NFT
+// list; the vendored parse stops before it
+#[test]
+fn parses_a_grown_estate_claim_record() {
+    let nfts: Vec<Pubkey> = (0..INITIAL_CLAIM_SLOTS + 3).map(|_| Pubkey::new_unique()).collect();
+    let original = ClaimRecord {
+        tokens_claimed: vec![TokenClaim { mint: Pubkey::new_unique(), amount: 1 }; INITIAL_CLAIM_SLOTS],
+        ..record(nfts.clone())
+    };
+    let data = account_bytes(&original);
+    assert_eq!(data.len(), CLAIM_RECORD_SPACE + 3 * 32);
+
+    let parsed = parse(defai_estate::ID, data.clone()).unwrap();
+    assert_eq!(parsed.nfts_claimed, nfts);
+    let full = ClaimRecord::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!((full.stage, full.steps), (ClaimStage::Nfts, 4));
+}
+
+#[test]
+fn rejects_foreign_owner_and_other_accounts() {
+    let data = account_bytes(&record(vec![]));
+    assert_eq!(
+        parse(defai_swap::ID, data.clone()).unwrap_err(),
+        Error::from(InheritanceError::InvalidClaimRecordOwner)
+    );
+
+    // An estate-owned account of another type
+    let mut other = data.clone();
+    other[..8].copy_from_slice(&defai_estate::Estate::discriminator());
+    assert_eq!(parse(defai_estate::ID, other).unwrap_err(), Error::from(InheritanceError::InvalidClaimRecord));
+
+    // Truncated inside the NFT list
+    let mut truncated = data;
+    truncated.truncate(8 + 32 + 32 + 8 + 8 + 1 + 4 + (32 + 8) + 2);
+    assert_eq!(parse(defai_estate::ID, truncated).unwrap_err(), Error::from(InheritanceError::InvalidClaimRecord));
+}
@@ -0,0 +1,241 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_common::receipt_root;
+use defai_estate::{
+    accounts, instruction, Beneficiary, ClaimRecord, ClaimStage, Estate, EstateError, GlobalCounter, ReceiptLog, CLAIM_SEED,
+    COUNTER_SEED, ESTATE_SEED, MIN_GRACE_PERIOD, MIN_INACTIVITY_PERIOD, RECEIPT_LOG_SEED,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::signature::{Keypair, Signer};
+
+// Create an estate, let it lapse, then have two beneficiaries claim SOL,
+// SPL tokens and an NFT from it, one claim stage per transaction. The
+// finished claim is then compressed into the receipt log.
+#[tokio::test]
+async fn estate_lifecycle() {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_estate::ID;
+
+    let (global_counter, _) = Pubkey::find_program_address(&[COUNTER_SEED], &program_id);
+    let ix = anchor_ix(
+        program_id,
+        accounts::InitializeGlobalCounter { admin: env.payer(), global_counter, system_program: system_program::ID },
+        instruction::InitializeGlobalCounter {},
+    );
+    env.send(&[ix], &[]).await.unwrap();
+
+    // Owner creates estate #0
+    let owner = env.funded_keypair(5 * SOL).await;
+    let estate_number = env.account::<GlobalCounter>(&global_counter).await.count;
+    let (estate, _) = Pubkey::find_program_address(
+        &[ESTATE_SEED, owner.pubkey().as_ref(), &estate_number.to_le_bytes()],
+        &program_id,
+    );
+    let ix = anchor_ix(
+        program_id,
+        accounts::CreateEstate {
+            owner: owner.pubkey(),
+            estate,
+            global_counter,
+            estate_mint: Keypair::new().pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreateEstate {
+            inactivity_period: MIN_INACTIVITY_PERIOD,
+            grace_period: MIN_GRACE_PERIOD,
+            owner_email_hash: [1; 32],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+
+    let first = env.funded_keypair(SOL).await;
+    let second = env.funded_keypair(SOL).await;
+    let beneficiary = |address: Pubkey, share_percentage: u8| Beneficiary {
+        address,
+        email_hash: [0; 32],
+        share_percentage,
+        claimed: false,
+        notification_sent: false,
+    };
+    let ix = anchor_ix(
+        program_id,
+        accounts::UpdateBeneficiaries { owner: owner.pubkey(), estate, system_program: system_program::ID },
+        instruction::UpdateBeneficiaries {
+            beneficiaries: vec![beneficiary(first.pubkey(), 60), beneficiary(second.pubkey(), 40)],
+        },
+    );
+    env.send(&[ix], &[&owner]).await.unwrap();
+
+    // Fund the estate with SOL, an SPL token balance and an NFT
+    env.transfer_sol(&estate, 10 * SOL).await;
+    let token_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let estate_token_account = env.fund_ata(&estate, &token_mint, &TOKEN_PROGRAM_ID, 1_000_000_000).await;
+    let nft_mint = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    let estate_nft_account = env.fund_ata(&estate, &nft_mint, &TOKEN_PROGRAM_ID, 1).await;
+
+    // Not claimable until inactivity and grace have both passed
+    let trigger = anchor_ix(
+        program_id,
+        accounts::TriggerInheritance { authority: first.pubkey(), estate },
+        instruction::TriggerInheritance {},
+    );
+    let err = env.send(std::slice::from_ref(&trigger), &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::NotYetClaimable.into()));
+
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    env.send(&[trigger], &[&first]).await.unwrap();
+    assert!(env.account::<Estate>(&estate).await.is_claimable);
+
+    let claim_record = |beneficiary: &Pubkey| {
+        Pubkey::find_program_address(&[CLAIM_SEED, estate.as_ref(), beneficiary.as_ref()], &program_id).0
+    };
+    let claim_inheritance = |beneficiary: &Keypair, index: u8| {
+        anchor_ix(
+            program_id,
+            accounts::ClaimInheritance {
+                beneficiary: beneficiary.pubkey(),
+                estate,
+                claim_record: claim_record(&beneficiary.pubkey()),
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_estate::ID),
+                program: defai_estate::ID,
+            },
+            instruction::ClaimInheritance { beneficiary_index: index },
+        )
+    };
+    let claim_token = |beneficiary: &Keypair, index: u8| {
+        anchor_ix(
+            program_id,
+            accounts::ClaimToken {
+                beneficiary: beneficiary.pubkey(),
+                estate,
+                claim_record: claim_record(&beneficiary.pubkey()),
+                token_mint,
+                estate_token_account,
+                beneficiary_token_account: ata(&beneficiary.pubkey(), &token_mint, &TOKEN_PROGRAM_ID),
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            instruction::ClaimToken { beneficiary_index: index },
+        )
+    };
+
+    // Token claims are closed until the SOL share is claimed
+    let err = env.send(&[claim_token(&first, 0)], &[&first]).await.unwrap_err();
+    assert!(error_code(&err).is_some());
+
+    // A beneficiary cannot claim someone else's share
+    let err = env.send(&[claim_inheritance(&second, 0)], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::UnauthorizedBeneficiary.into()));
+
+    // First beneficiary: 60% of the SOL above the rent reserve, then tokens and the NFT
+    let estate_lamports = env.lamports(&estate).await;
+    let rent_reserve = env.ctx.banks_client.get_rent().await.unwrap().minimum_balance(env.account_data_len(&estate).await);
+    let before = env.lamports(&first.pubkey()).await;
+    env.send_metered("claim_inheritance", claim_inheritance(&first, 0), &[&first]).await.unwrap();
+    let record: ClaimRecord = env.account(&claim_record(&first.pubkey())).await;
+    assert_eq!(record.sol_amount, (estate_lamports - rent_reserve) * 60 / 100);
+    assert_eq!(record.share_percentage, 60);
+    assert!(env.lamports(&first.pubkey()).await > before);
+
+    let advance_claim = |beneficiary: &Keypair| {
+        anchor_ix(
+            program_id,
+            accounts::AdvanceClaim {
+                beneficiary: beneficiary.pubkey(),
+                estate,
+                claim_record: claim_record(&beneficiary.pubkey()),
+            },
+            instruction::AdvanceClaim {},
+        )
+    };
+    let ix = anchor_ix(
+        program_id,
+        accounts::ClaimNFT {
+            beneficiary: first.pubkey(),
+            estate,
+            claim_record: claim_record(&first.pubkey()),
+            nft_mint,
+            estate_nft_account,
+            beneficiary_nft_account: ata(&first.pubkey(), &nft_mint, &TOKEN_PROGRAM_ID),
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimNft { beneficiary_index: 0 },
+    );
+
+    // NFTs wait for their stage to open
+    let err = env.send(std::slice::from_ref(&ix), &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::WrongClaimStage.into()));
+    env.send_metered("advance_claim", advance_claim(&first), &[&first]).await.unwrap();
+
+    // Opening the NFT stage leaves tokens claimable, so advancing early strands nothing
+    env.send_metered("claim_token", claim_token(&first, 0), &[&first]).await.unwrap();
+    assert_eq!(env.token_balance(&ata(&first.pubkey(), &token_mint, &TOKEN_PROGRAM_ID)).await, 600_000_000);
+
+    let err = env.send(&[claim_token(&first, 0)], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::TokenAlreadyClaimed.into()));
+
+    env.send_metered("claim_nft", ix, &[&first]).await.unwrap();
+    assert_eq!(env.token_balance(&ata(&first.pubkey(), &nft_mint, &TOKEN_PROGRAM_ID)).await, 1);
+    assert_eq!(env.token_balance(&estate_nft_account).await, 0);
+
+    // The record shows where the claim stands; no RWAs, so finish it
+    let record: ClaimRecord = env.account(&claim_record(&first.pubkey())).await;
+    assert_eq!(record.stage, ClaimStage::Nfts);
+    assert_eq!((record.tokens_claimed.len(), record.nfts_claimed.len()), (1, 1));
+    assert_eq!(record.steps, 4);
+    env.send(&[advance_claim(&first), advance_claim(&first)], &[&first]).await.unwrap();
+    assert_eq!(env.account::<ClaimRecord>(&claim_record(&first.pubkey())).await.stage, ClaimStage::Complete);
+    let err = env.send(&[advance_claim(&first)], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ClaimAlreadyComplete.into()));
+    let err = env.send(&[claim_token(&first, 0)], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ClaimAlreadyComplete.into()));
+
+    // Second beneficiary claims from what is left
+    env.send(&[claim_inheritance(&second, 1), claim_token(&second, 1)], &[&second]).await.unwrap();
+    assert!(env.token_balance(&ata(&second.pubkey(), &token_mint, &TOKEN_PROGRAM_ID)).await > 0);
+
+    // The finished claim folds into the receipt log and its rent comes back
+    let (receipt_log, _) = Pubkey::find_program_address(&[RECEIPT_LOG_SEED], &program_id);
+    let ix = anchor_ix(
+        program_id,
+        accounts::InitializeReceiptLog { payer: env.payer(), receipt_log, system_program: system_program::ID },
+        instruction::InitializeReceiptLog {},
+    );
+    env.send(&[ix], &[]).await.unwrap();
+    let compress = |beneficiary: &Keypair| {
+        anchor_ix(
+            program_id,
+            accounts::CompressClaimRecord {
+                beneficiary: beneficiary.pubkey(),
+                estate,
+                claim_record: claim_record(&beneficiary.pubkey()),
+                receipt_log,
+                event_authority: event_authority(&program_id),
+                program: program_id,
+            },
+            instruction::CompressClaimRecord {},
+        )
+    };
+    let err = env.send(&[compress(&second)], &[&second]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::ClaimNotComplete.into()));
+
+    let record_rent = env.lamports(&claim_record(&first.pubkey())).await;
+    let before = env.lamports(&first.pubkey()).await;
+    env.send_metered("compress_claim_record", compress(&first), &[&first]).await.unwrap();
+    assert!(!env.exists(&claim_record(&first.pubkey())).await);
+    assert_eq!(env.lamports(&first.pubkey()).await, before + record_rent);
+    let log: ReceiptLog = env.account(&receipt_log).await;
+    assert_eq!(log.leaf_count, 1);
+    assert_eq!(log.root, receipt_root(&log.frontier, 1));
+
+    // Closing the record doesn't reopen the claim
+    let err = env.send(&[claim_inheritance(&first, 0)], &[&first]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(EstateError::AlreadyClaimed.into()));
+
+    let estate_state: Estate = env.account(&estate).await;
+    assert_eq!(estate_state.total_claims, 2);
+    assert!(estate_state.beneficiaries.iter().all(|b| b.claimed));
+}
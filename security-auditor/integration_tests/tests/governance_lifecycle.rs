@@ -0,0 +1,184 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_common::CommonError;
+use defai_governance::{
+    accounts, instruction,
+    upgrade::{UpgradeAction, UPGRADE_TIMELOCK_DURATION},
+    Governance, GovernanceAction, GovernanceError, Proposal, AUTHORITY_SEED, GOVERNANCE_SEED, PROPOSAL_SEED,
+};
+use defai_integration_tests::{anchor_ix, error_code, has_binary, loader_account, program_binary, TestEnv, SOL};
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::{AccountMeta, Instruction},
+    signature::{Keypair, Signer},
+    sysvar,
+};
+
+// Governance is initialized by its upgrade authority, not whoever calls first.
+// A membership change goes through propose, approve and execute, and leaves
+// proposals made under the old members stale. An upgrade of defai_swap from a
+// buffer held by the governance authority waits out the upgrade timelock.
+#[tokio::test]
+async fn governance_lifecycle() {
+    if !has_binary("defai_governance") || !has_binary("defai_swap") {
+        eprintln!("skipping: the upgradeable loader needs the built defai_governance and defai_swap");
+        return;
+    }
+    let program_id = defai_governance::ID;
+    let deployer = Keypair::new();
+    let (authority, _) = Pubkey::find_program_address(&[AUTHORITY_SEED], &program_id);
+
+    // defai_swap's upgrade authority was handed to governance; the new build
+    // sits in a buffer that governance also holds
+    let buffer = Keypair::new().pubkey();
+    let foreign_buffer = Keypair::new().pubkey();
+    let buffer_account = |authority_address| {
+        loader_account(
+            UpgradeableLoaderState::Buffer { authority_address: Some(authority_address) },
+            UpgradeableLoaderState::size_of_buffer_metadata(),
+            &program_binary("defai_swap"),
+        )
+    };
+    let mut env = TestEnv::start_with(
+        &[("defai_governance", program_id, deployer.pubkey()), ("defai_swap", defai_swap::ID, authority)],
+        |program_test| {
+            program_test.add_account(buffer, buffer_account(authority));
+            program_test.add_account(foreign_buffer, buffer_account(deployer.pubkey()));
+        },
+    )
+    .await;
+    env.transfer_sol(&deployer.pubkey(), 5 * SOL).await;
+
+    let program_data = |program: &Pubkey| {
+        Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::ID).0
+    };
+    let (governance, _) = Pubkey::find_program_address(&[GOVERNANCE_SEED], &program_id);
+    let proposal = |id: u64| Pubkey::find_program_address(&[PROPOSAL_SEED, &id.to_le_bytes()], &program_id).0;
+
+    let members: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for member in &members {
+        env.transfer_sol(&member.pubkey(), SOL).await;
+    }
+    let initialize = |payer: &Keypair| {
+        anchor_ix(
+            program_id,
+            accounts::InitializeGovernance {
+                governance,
+                authority,
+                payer: payer.pubkey(),
+                program: program_id,
+                program_data: program_data(&program_id),
+                system_program: system_program::ID,
+            },
+            instruction::InitializeGovernance {
+                members: members.iter().map(|m| m.pubkey()).collect(),
+                threshold: 2,
+            },
+        )
+    };
+
+    // Anyone but the upgrade authority is refused
+    let front_runner = env.funded_keypair(SOL).await;
+    let err = env.send(&[initialize(&front_runner)], &[&front_runner]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::NotUpgradeAuthority.into()));
+    env.send(&[initialize(&deployer)], &[&deployer]).await.unwrap();
+    let state: Governance = env.account(&governance).await;
+    assert_eq!(state.threshold, 2);
+    assert_eq!(state.members_version, 0);
+
+    let create = |proposer: &Keypair, id: u64, action: GovernanceAction| {
+        anchor_ix(
+            program_id,
+            accounts::CreateProposal {
+                governance,
+                proposal: proposal(id),
+                proposer: proposer.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::CreateProposal { action },
+        )
+    };
+    let approve = |member: &Keypair, id: u64| {
+        anchor_ix(
+            program_id,
+            accounts::ApproveProposal { governance, proposal: proposal(id), member: member.pubkey() },
+            instruction::ApproveProposal { id },
+        )
+    };
+    let execute = |executor: &Keypair, id: u64, remaining: Vec<AccountMeta>| {
+        let mut ix: Instruction = anchor_ix(
+            program_id,
+            accounts::ExecuteProposal { governance, proposal: proposal(id), authority, executor: executor.pubkey() },
+            instruction::ExecuteProposal { id },
+        );
+        ix.accounts.extend(remaining);
+        ix
+    };
+
+    // Proposal 0 drops the third member and lowers the threshold; proposal 1
+    // is opened under the same membership
+    let remaining = vec![members[0].pubkey(), members[1].pubkey()];
+    let update = GovernanceAction::UpdateMembers { members: remaining.clone(), threshold: 1 };
+    env.send(&[create(&members[0], 0, update)], &[&members[0]]).await.unwrap();
+    let competing = GovernanceAction::UpdateMembers { members: remaining.clone(), threshold: 2 };
+    env.send(&[create(&members[2], 1, competing)], &[&members[2]]).await.unwrap();
+
+    let outsider = env.funded_keypair(SOL).await;
+    let err = env.send(&[approve(&outsider, 0)], &[&outsider]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::NotMember.into()));
+    let err = env.send(&[approve(&members[0], 0)], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::AlreadyApproved.into()));
+    let err = env.send(&[execute(&members[0], 0, vec![])], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::InsufficientApprovals.into()));
+
+    env.send(&[approve(&members[1], 0)], &[&members[1]]).await.unwrap();
+    env.send(&[execute(&members[1], 0, vec![])], &[&members[1]]).await.unwrap();
+    let state: Governance = env.account(&governance).await;
+    assert_eq!(state.members, remaining);
+    assert_eq!(state.threshold, 1);
+    assert_eq!(state.members_version, 1);
+    assert!(env.account::<Proposal>(&proposal(0)).await.executed);
+    let err = env.send(&[execute(&members[0], 0, vec![])], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::AlreadyExecuted.into()));
+
+    // Proposal 1 predates the membership change
+    let err = env.send(&[approve(&members[0], 1)], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::StaleProposal.into()));
+    let err = env.send(&[execute(&members[0], 1, vec![])], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::StaleProposal.into()));
+
+    // Upgrades need a buffer governance already holds
+    let upgrade = |buffer: Pubkey| GovernanceAction::Upgrade(UpgradeAction { program: defai_swap::ID, buffer });
+    let mut foreign = create(&members[0], 2, upgrade(foreign_buffer));
+    foreign.accounts.push(AccountMeta::new_readonly(foreign_buffer, false));
+    let err = env.send(&[foreign], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(GovernanceError::InvalidUpgradeBuffer.into()));
+
+    let mut propose_upgrade = create(&members[0], 2, upgrade(buffer));
+    propose_upgrade.accounts.push(AccountMeta::new_readonly(buffer, false));
+    env.send(&[propose_upgrade], &[&members[0]]).await.unwrap();
+
+    let spill = members[0].pubkey();
+    let upgrade_accounts = vec![
+        AccountMeta::new(defai_swap::ID, false),
+        AccountMeta::new(program_data(&defai_swap::ID), false),
+        AccountMeta::new(buffer, false),
+        AccountMeta::new(spill, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+        AccountMeta::new_readonly(sysvar::clock::ID, false),
+        AccountMeta::new_readonly(bpf_loader_upgradeable::ID, false),
+    ];
+
+    // The threshold is met at once, but the timelock still applies
+    let err = env.send(&[execute(&members[0], 2, upgrade_accounts.clone())], &[&members[0]]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(CommonError::TimelockNotExpired.into()));
+
+    env.warp_forward(UPGRADE_TIMELOCK_DURATION).await;
+    let spill_before = env.lamports(&spill).await;
+    let buffer_lamports = env.lamports(&buffer).await;
+    env.send(&[execute(&members[0], 2, upgrade_accounts)], &[&members[0]]).await.unwrap();
+
+    // The loader deployed the buffer and refunded its rent to the spill account
+    assert!(!env.exists(&buffer).await);
+    assert_eq!(env.lamports(&spill).await, spill_before + buffer_lamports);
+    assert!(env.account::<Proposal>(&proposal(2)).await.executed);
+}
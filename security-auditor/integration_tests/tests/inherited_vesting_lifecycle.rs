@@ -0,0 +1,392 @@
+use anchor_lang::{prelude::Pubkey, solana_program::sysvar, system_program};
+use defai_estate::{CLAIM_SEED, COUNTER_SEED, ESTATE_SEED, MIN_GRACE_PERIOD, MIN_INACTIVITY_PERIOD};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use defai_swap::{inheritance::InheritanceError, VestingStateV6};
+use solana_sdk::signature::{Keypair, Signer};
+
+// Mirror of the program's private schedule constant
+const VESTING_DURATION: i64 = 90 * 24 * 60 * 60;
+
+const DEFAI: u64 = 1_000_000;
+const TIER: u8 = 4;
+
+fn swap_pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_swap::ID).0
+}
+
+fn estate_pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_estate::ID).0
+}
+
+// A bonus-NFT holder routes vesting to a wallet that is later lost, puts the
+// NFT in their estate and lapses. The heir claims the NFT from the estate
+// (Token-2022), takes over the vesting position with their claim record and
+// then claims the rest like any holder.
+#[tokio::test]
+async fn inherited_vesting_lifecycle() {
+    let mut env = TestEnv::start().await;
+    let admin = env.payer();
+
+    // Swap setup, as in swap_vesting_lifecycle
+    let old_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let defai_mint = env.create_mint(&TOKEN_2022_PROGRAM_ID, 6).await;
+    let treasury = Keypair::new().pubkey();
+    let prices = [0, 100 * DEFAI, 500 * DEFAI, 800 * DEFAI, 1_000 * DEFAI];
+    let config = swap_pda(&[b"config"]);
+    let escrow = swap_pda(&[b"escrow"]);
+    let randomness_state = swap_pda(&[b"randomness_state"]);
+    let collection_config = swap_pda(&[b"collection_config"]);
+    let treasury_ledger = swap_pda(&[b"treasury_ledger"]);
+    let program_registry = swap_pda(&[b"program_registry"]);
+
+    let ixs = [
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::Initialize {
+                admin,
+                old_mint,
+                new_mint: defai_mint,
+                collection: Keypair::new().pubkey(),
+                treasury,
+                config,
+                escrow,
+                tax_state: swap_pda(&[b"tax_state"]),
+                system_program: system_program::ID,
+            },
+            defai_swap::instruction::Initialize { prices: prices.to_vec() },
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::InitializeRandomness { authority: admin, randomness_state, system_program: system_program::ID },
+            defai_swap::instruction::InitializeRandomnessV2 {},
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::SimpleRandomness {
+                authority: admin,
+                randomness_state,
+                recent_blockhashes: sysvar::recent_blockhashes::ID,
+            },
+            defai_swap::instruction::GenerateSimpleRandomness {},
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::InitializeCollection {
+                authority: admin,
+                collection_mint: Keypair::new().pubkey(),
+                treasury,
+                defai_mint,
+                old_defai_mint: old_mint,
+                collection_config,
+                system_program: system_program::ID,
+            },
+            defai_swap::instruction::InitializeCollection {
+                tier_names: vec!["OG".into(), "Train".into(), "Boat".into(), "Plane".into(), "Rocket".into()],
+                tier_symbols: vec!["OG".into(), "TRN".into(), "BOAT".into(), "PLN".into(), "RKT".into()],
+                tier_prices: prices,
+                tier_supplies: [10; 5],
+                tier_uri_prefixes: vec![String::new(); 5],
+                og_tier_0_merkle_root: [0; 32],
+                airdrop_merkle_root: [0; 32],
+                og_tier_0_supply: 0,
+            },
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::InitializeTreasuryLedger {
+                admin,
+                config,
+                mint: defai_mint,
+                treasury_ledger,
+                system_program: system_program::ID,
+            },
+            defai_swap::instruction::InitializeTreasuryLedger {},
+        ),
+        // Links the main deployment, so claim records must come from defai_estate::ID
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::InitializeProgramRegistry {
+                admin,
+                config,
+                program_registry,
+                system_program: system_program::ID,
+            },
+            defai_swap::instruction::InitializeProgramRegistry { links: None },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+    let treasury_defai_ata = env.create_ata(&treasury, &defai_mint, &TOKEN_2022_PROGRAM_ID).await;
+    let escrow_defai_ata = env.fund_ata(&escrow, &defai_mint, &TOKEN_2022_PROGRAM_ID, 10_000 * DEFAI).await;
+
+    // The holder swaps for a tier NFT, then sends its vesting to another wallet
+    let holder = env.funded_keypair(5 * SOL).await;
+    let holder_defai_ata = env.fund_ata(&holder.pubkey(), &defai_mint, &TOKEN_2022_PROGRAM_ID, 2_000 * DEFAI).await;
+    let nft_mint = env.create_mint(&TOKEN_2022_PROGRAM_ID, 0).await;
+    let holder_nft_ata = env.fund_ata(&holder.pubkey(), &nft_mint, &TOKEN_2022_PROGRAM_ID, 1).await;
+    let user_tax_state = swap_pda(&[b"user_tax", holder.pubkey().as_ref()]);
+    let vesting_state = swap_pda(&[b"vesting_v6", nft_mint.as_ref()]);
+    let lost_wallet = Keypair::new().pubkey();
+
+    let ixs = [
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::InitializeUserTax {
+                user: holder.pubkey(),
+                user_tax_state,
+                system_program: system_program::ID,
+            },
+            defai_swap::instruction::InitializeUserTax {},
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::SwapDefaiForPnftV6 {
+                user: holder.pubkey(),
+                tier_phase: swap_pda(&[b"tier_phase", &[TIER]]),
+                user_defai_ata: holder_defai_ata,
+                randomness_state,
+                treasury_defai_ata,
+                treasury_ledger,
+                buyback_state: swap_pda(&[b"buyback_state"]),
+                buyback_vault: swap_pda(&[b"buyback_vault"]),
+                escrow_defai_ata,
+                defai_mint,
+                config,
+                collection_config,
+                nft_mint,
+                nft_token_account: holder_nft_ata,
+                bonus_state: swap_pda(&[b"bonus_v6", nft_mint.as_ref()]),
+                vesting_state,
+                escrow,
+                user_tax_state,
+                system_program: system_program::ID,
+                token_program_2022: TOKEN_2022_PROGRAM_ID,
+                recent_blockhashes: sysvar::recent_blockhashes::ID,
+                event_authority: event_authority(&defai_swap::ID),
+                program: defai_swap::ID,
+            },
+            defai_swap::instruction::SwapDefaiForPnftV6 {
+                tier: TIER,
+                _metadata_uri: String::new(),
+                _name: String::new(),
+                _symbol: String::new(),
+                presale_proof: vec![],
+            },
+        ),
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::SetVestingPayoutWallet {
+                user: holder.pubkey(),
+                nft_mint,
+                user_nft_ata: holder_nft_ata,
+                vesting_state,
+            },
+            defai_swap::instruction::SetVestingPayoutWallet { payout_wallet: Some(lost_wallet) },
+        ),
+    ];
+    env.send(&ixs, &[&holder]).await.unwrap();
+
+    // The holder's estate leaves everything to the heir
+    let global_counter = estate_pda(&[COUNTER_SEED]);
+    let ix = anchor_ix(
+        defai_estate::ID,
+        defai_estate::accounts::InitializeGlobalCounter { admin, global_counter, system_program: system_program::ID },
+        defai_estate::instruction::InitializeGlobalCounter {},
+    );
+    env.send(&[ix], &[]).await.unwrap();
+    let estate = estate_pda(&[ESTATE_SEED, holder.pubkey().as_ref(), &0u64.to_le_bytes()]);
+    let heir = env.funded_keypair(SOL).await;
+    let ixs = [
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::CreateEstate {
+                owner: holder.pubkey(),
+                estate,
+                global_counter,
+                estate_mint: Keypair::new().pubkey(),
+                system_program: system_program::ID,
+            },
+            defai_estate::instruction::CreateEstate {
+                inactivity_period: MIN_INACTIVITY_PERIOD,
+                grace_period: MIN_GRACE_PERIOD,
+                owner_email_hash: [1; 32],
+            },
+        ),
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::UpdateBeneficiaries {
+                owner: holder.pubkey(),
+                estate,
+                system_program: system_program::ID,
+            },
+            defai_estate::instruction::UpdateBeneficiaries {
+                beneficiaries: vec![defai_estate::Beneficiary {
+                    address: heir.pubkey(),
+                    email_hash: [0; 32],
+                    share_percentage: 100,
+                    claimed: false,
+                    notification_sent: false,
+                }],
+            },
+        ),
+    ];
+    env.send(&ixs, &[&holder]).await.unwrap();
+
+    // The NFT goes into the estate's Token-2022 account
+    env.transfer_sol(&estate, SOL).await;
+    let estate_nft_account = env.create_ata(&estate, &nft_mint, &TOKEN_2022_PROGRAM_ID).await;
+    let ix = spl_token_2022::instruction::transfer_checked(
+        &TOKEN_2022_PROGRAM_ID,
+        &holder_nft_ata,
+        &nft_mint,
+        &estate_nft_account,
+        &holder.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    env.send(&[ix], &[&holder]).await.unwrap();
+
+    // The estate lapses (which also passes the vesting cliff) and the heir claims the NFT
+    env.warp_forward(MIN_INACTIVITY_PERIOD + MIN_GRACE_PERIOD + 1).await;
+    let claim_record = estate_pda(&[CLAIM_SEED, estate.as_ref(), heir.pubkey().as_ref()]);
+    let heir_nft_ata = ata(&heir.pubkey(), &nft_mint, &TOKEN_2022_PROGRAM_ID);
+    let ixs = [
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::TriggerInheritance { authority: heir.pubkey(), estate },
+            defai_estate::instruction::TriggerInheritance {},
+        ),
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::ClaimInheritance {
+                beneficiary: heir.pubkey(),
+                estate,
+                claim_record,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_estate::ID),
+                program: defai_estate::ID,
+            },
+            defai_estate::instruction::ClaimInheritance { beneficiary_index: 0 },
+        ),
+        // No tokens to claim: move straight on to the NFT stage
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::AdvanceClaim { beneficiary: heir.pubkey(), estate, claim_record },
+            defai_estate::instruction::AdvanceClaim {},
+        ),
+        anchor_ix(
+            defai_estate::ID,
+            defai_estate::accounts::ClaimNFT {
+                beneficiary: heir.pubkey(),
+                estate,
+                claim_record,
+                nft_mint,
+                estate_nft_account,
+                beneficiary_nft_account: heir_nft_ata,
+                token_program: TOKEN_2022_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+            },
+            defai_estate::instruction::ClaimNft { beneficiary_index: 0 },
+        ),
+    ];
+    env.send(&ixs, &[&heir]).await.unwrap();
+    assert_eq!(env.token_balance(&heir_nft_ata).await, 1);
+
+    let heir_defai_ata = ata(&heir.pubkey(), &defai_mint, &TOKEN_2022_PROGRAM_ID);
+    let claim_inherited = |heir: Pubkey, heir_nft_ata: Pubkey, claim_record: Pubkey| {
+        anchor_ix(
+            defai_swap::ID,
+            defai_swap::accounts::ClaimInheritedVesting {
+                heir,
+                nft_mint,
+                heir_nft_ata,
+                claim_record,
+                heir_defai_ata: ata(&heir, &defai_mint, &TOKEN_2022_PROGRAM_ID),
+                escrow_defai_ata,
+                defai_mint,
+                config,
+                escrow,
+                vesting_state,
+                program_registry,
+                token_program_2022: TOKEN_2022_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_swap::ID),
+                program: defai_swap::ID,
+            },
+            defai_swap::instruction::ClaimInheritedVesting {},
+        )
+    };
+
+    // Someone else's claim record doesn't work, even when they hold the NFT
+    let stranger = env.funded_keypair(SOL).await;
+    let stranger_nft_ata = env.create_ata(&stranger.pubkey(), &nft_mint, &TOKEN_2022_PROGRAM_ID).await;
+    let ix = spl_token_2022::instruction::transfer_checked(
+        &TOKEN_2022_PROGRAM_ID,
+        &heir_nft_ata,
+        &nft_mint,
+        &stranger_nft_ata,
+        &heir.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    env.send(&[ix], &[&heir]).await.unwrap();
+    let err = env
+        .send(&[claim_inherited(stranger.pubkey(), stranger_nft_ata, claim_record)], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(error_code(&err), Some(InheritanceError::InvalidClaimRecord.into()));
+    let ix = spl_token_2022::instruction::transfer_checked(
+        &TOKEN_2022_PROGRAM_ID,
+        &stranger_nft_ata,
+        &nft_mint,
+        &heir_nft_ata,
+        &stranger.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    env.send(&[ix], &[&stranger]).await.unwrap();
+
+    // The heir takes over: the lost payout wallet is cleared and the vested part paid out
+    env.send_metered("claim_inherited_vesting", claim_inherited(heir.pubkey(), heir_nft_ata, claim_record), &[&heir])
+        .await
+        .unwrap();
+    let vesting: VestingStateV6 = env.account(&vesting_state).await;
+    assert_eq!(vesting.payout_wallet, None);
+    assert!(vesting.released_amount > 0 && vesting.released_amount < vesting.total_amount);
+    assert_eq!(env.token_balance(&heir_defai_ata).await, vesting.released_amount);
+
+    // From here on the heir claims as the holder
+    env.warp_forward(VESTING_DURATION).await;
+    let ix = anchor_ix(
+        defai_swap::ID,
+        defai_swap::accounts::ClaimVestedV6 {
+            user: heir.pubkey(),
+            nft_mint,
+            user_nft_ata: heir_nft_ata,
+            payout_owner: heir.pubkey(),
+            user_defai_ata: heir_defai_ata,
+            escrow_defai_ata,
+            defai_mint,
+            config,
+            escrow,
+            vesting_state,
+            token_program_2022: TOKEN_2022_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_swap::ID),
+            program: defai_swap::ID,
+        },
+        defai_swap::instruction::ClaimVestedV6 {},
+    );
+    env.send(&[ix], &[&heir]).await.unwrap();
+    let vesting: VestingStateV6 = env.account(&vesting_state).await;
+    assert_eq!(vesting.released_amount, vesting.total_amount);
+    assert_eq!(env.token_balance(&heir_defai_ata).await, vesting.total_amount);
+}
@@ -0,0 +1,22 @@
+// The main deployment's ids in defai_common::registry, which registries take
+// when initialized without links, against each program's declared id. Runs
+// without program binaries.
+
+use defai_common::{
+    DEFAULT_APP_FACTORY_PROGRAM_ID, DEFAULT_ESTATE_PROGRAM_ID, DEFAULT_GOVERNANCE_PROGRAM_ID, DEFAULT_SWAP_PROGRAM_ID,
+};
+
+#[test]
+fn default_links_are_the_declared_program_ids() {
+    assert_eq!(DEFAULT_SWAP_PROGRAM_ID, defai_swap::ID);
+    assert_eq!(DEFAULT_ESTATE_PROGRAM_ID, defai_estate::ID);
+    assert_eq!(DEFAULT_APP_FACTORY_PROGRAM_ID, defai_app_factory::ID);
+    assert_eq!(DEFAULT_GOVERNANCE_PROGRAM_ID, defai_governance::ID);
+}
+
+#[test]
+fn every_program_accepts_the_default_links() {
+    assert!(defai_swap::program_registry::ProgramLinks::defaults().validate().is_ok());
+    assert!(defai_estate::ProgramLinks::defaults().validate().is_ok());
+    assert!(defai_app_factory::ProgramLinks::defaults().validate().is_ok());
+}
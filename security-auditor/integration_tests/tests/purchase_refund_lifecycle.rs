@@ -0,0 +1,432 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{accounts, instruction, AppFactoryError, AppRegistration, FactoryStats, RefundError, SplitRecipient, UserAppAccess};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u16 = 1_000;
+const PRICE: u64 = 50 * DEFAI;
+const USER_FUNDS: u64 = 100 * DEFAI;
+const APP_ID: u64 = 0;
+const AFFILIATE_BPS: u16 = 500;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+struct Purchased {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    user: Keypair,
+    app_registration: Pubkey,
+    factory_stats: Pubkey,
+    user_app_access: Pubkey,
+    user_defai_ata: Pubkey,
+    user_sft_ata: Pubkey,
+    creator_defai_ata: Pubkey,
+    treasury_defai_ata: Pubkey,
+    defai_mint: Pubkey,
+    referrer_defai_ata: Option<Pubkey>,
+    refund: Instruction,
+}
+
+// Optional extras for the purchase: a burn of `burn_bps` of the price, an
+// AFFILIATE_BPS commission to `referrer`, and a revenue split sending the
+// whole creator share to `split_wallet`
+#[derive(Default)]
+struct Setup<'a> {
+    burn_bps: u16,
+    referrer: Option<&'a Keypair>,
+    split_wallet: Option<Pubkey>,
+}
+
+// Factory with one registered app that `user` has bought access to
+async fn purchase() -> Purchased {
+    purchase_with(Setup::default()).await
+}
+
+async fn purchase_with(setup: Setup<'_>) -> Purchased {
+    let Setup { burn_bps, referrer, split_wallet } = setup;
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetBurnBps { app_factory, authority },
+            instruction::SetBurnBps { burn_bps },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    // Creator registers app #0; its SFT mint authority moves to the registration PDA
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let ix = anchor_ix(
+        program_id,
+        accounts::RegisterApp {
+            app_factory,
+            factory_stats,
+            app_registration,
+            sft_mint,
+            verified_creator: None,
+            category_registry: None,
+            creator_defai_ata: None,
+            treasury_defai_ata: None,
+            stake_vault: None,
+            treasury_ledger: None,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RegisterApp {
+            price: PRICE,
+            max_supply: 100,
+            metadata_uri: "ipfs://app".into(),
+            categories: vec![],
+            royalty_bps: 0,
+            refund_policy: None,
+        },
+    );
+    env.send(&[ix], &[&creator]).await.unwrap();
+
+    let mut referrer_defai_ata = None;
+    let mut affiliate_stats = None;
+    if let Some(referrer) = referrer {
+        env.transfer_sol(&referrer.pubkey(), SOL).await;
+        let stats = pda(&[b"affiliate_stats", referrer.pubkey().as_ref()]);
+        let ixs = [
+            anchor_ix(
+                program_id,
+                accounts::RegisterAffiliate {
+                    affiliate_stats: stats,
+                    affiliate: referrer.pubkey(),
+                    system_program: system_program::ID,
+                },
+                instruction::RegisterAffiliate {},
+            ),
+            anchor_ix(
+                program_id,
+                accounts::SetAffiliateBps { app_registration, creator: creator.pubkey() },
+                instruction::SetAffiliateBps { app_id: APP_ID, affiliate_bps: AFFILIATE_BPS },
+            ),
+        ];
+        env.send(&ixs, &[referrer, &creator]).await.unwrap();
+        affiliate_stats = Some(stats);
+        referrer_defai_ata = Some(env.create_ata(&referrer.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await);
+    }
+
+    let mut split_atas = vec![];
+    if let Some(wallet) = split_wallet {
+        let ix = anchor_ix(
+            program_id,
+            accounts::SetRevenueSplit {
+                app_registration,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::SetRevenueSplit {
+                app_id: APP_ID,
+                recipients: vec![SplitRecipient { wallet, share_bps: 10_000 }],
+            },
+        );
+        env.send(&[ix], &[&creator]).await.unwrap();
+        split_atas.push(env.create_ata(&wallet, &defai_mint, &TOKEN_PROGRAM_ID).await);
+    }
+
+    let creator_defai_ata = env.create_ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID).await;
+    let treasury_defai_ata = ata(&treasury.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID);
+
+    let user = env.funded_keypair(5 * SOL).await;
+    let user_defai_ata = env.fund_ata(&user.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+    let user_sft_ata = env.create_ata(&user.pubkey(), &sft_mint, &TOKEN_PROGRAM_ID).await;
+    let user_app_access = pda(&[b"user_app_access", user.pubkey().as_ref(), &APP_ID.to_le_bytes()]);
+
+    // The treasury ATA is created by the purchase itself
+    let mut ix = anchor_ix(
+        program_id,
+        accounts::PurchaseAppAccessOptimized {
+            app_factory,
+            factory_stats,
+            app_registration,
+            user: user.pubkey(),
+            defai_mint,
+            creator: creator.pubkey(),
+            treasury: treasury.pubkey(),
+            user_app_access,
+            sft_mint,
+            user_sft_ata,
+            user_defai_ata,
+            creator_defai_ata,
+            creator_vesting: None,
+            vesting_vault: None,
+            charity_defai_ata: None,
+            bonus_state: None,
+            bonus_nft_ata: None,
+            program_registry: None,
+            treasury_defai_ata,
+            revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+            sale: None,
+            affiliate_stats,
+            referrer_defai_ata,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::PurchaseAppAccessV2 { app_id: APP_ID },
+    );
+    ix.accounts.extend(split_atas.iter().map(|ata| AccountMeta::new(*ata, false)));
+    env.send_metered("purchase_app_access_v2", ix, &[&user]).await.unwrap();
+
+    let refund = anchor_ix(
+        program_id,
+        accounts::RefundPurchase {
+            app_factory,
+            factory_stats,
+            app_registration,
+            user_app_access,
+            rent_payer: user.pubkey(),
+            sft_mint,
+            user_sft_ata,
+            user_defai_ata,
+            user_review: pda(&[b"app_review", user.pubkey().as_ref(), &APP_ID.to_le_bytes()]),
+            creator_defai_ata,
+            treasury_defai_ata,
+            creator_vesting: None,
+            vesting_vault: None,
+            user: user.pubkey(),
+            creator: creator.pubkey(),
+            treasury: treasury.pubkey(),
+            defai_mint,
+            token_program: TOKEN_PROGRAM_ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RefundPurchase { app_id: APP_ID, reason: "Not what I expected".into() },
+    );
+
+    Purchased {
+        env,
+        creator,
+        treasury,
+        user,
+        app_registration,
+        factory_stats,
+        user_app_access,
+        user_defai_ata,
+        user_sft_ata,
+        creator_defai_ata,
+        treasury_defai_ata,
+        defai_mint,
+        referrer_defai_ata,
+        refund,
+    }
+}
+
+#[tokio::test]
+async fn purchase_then_refund_in_window() {
+    let Purchased {
+        mut env,
+        creator,
+        treasury,
+        user,
+        app_registration,
+        factory_stats,
+        user_app_access,
+        user_defai_ata,
+        user_sft_ata,
+        creator_defai_ata,
+        treasury_defai_ata,
+        refund,
+        ..
+    } = purchase().await;
+
+    let platform_fee = PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+    assert_eq!(env.token_balance(&user_sft_ata).await, 1);
+    assert_eq!(env.token_balance(&user_defai_ata).await, USER_FUNDS - PRICE);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, PRICE - platform_fee);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, platform_fee);
+    assert_eq!(env.account::<AppRegistration>(&app_registration).await.current_supply, 1);
+
+    // Apps registered without a policy refund in full within 24 hours
+    env.send_metered("refund_purchase", refund, &[&user, &creator, &treasury]).await.unwrap();
+
+    assert_eq!(env.token_balance(&user_defai_ata).await, USER_FUNDS);
+    assert_eq!(env.token_balance(&user_sft_ata).await, 0);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, 0);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, 0);
+    assert!(!env.exists(&user_app_access).await);
+
+    let registration: AppRegistration = env.account(&app_registration).await;
+    assert_eq!(registration.current_supply, 0);
+    assert_eq!(registration.refund_count, 1);
+    assert_eq!(registration.refund_amount, PRICE);
+    let stats: FactoryStats = env.account(&factory_stats).await;
+    assert_eq!(stats.refund_count, 1);
+    assert_eq!(stats.total_refunds, PRICE);
+}
+
+#[tokio::test]
+async fn refund_after_window_is_rejected() {
+    let Purchased { mut env, creator, treasury, user, user_sft_ata, refund, .. } = purchase().await;
+
+    env.warp_forward(24 * 60 * 60 + 1).await;
+    let err = env.send(&[refund], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::RefundWindowExpired.into()));
+    assert_eq!(env.token_balance(&user_sft_ata).await, 1);
+}
+
+// The refund returns what each party received at purchase. The burned DEFAI
+// is gone, so it isn't refunded, and a later fee change doesn't move the split.
+#[tokio::test]
+async fn refund_returns_the_recorded_split() {
+    let Purchased {
+        mut env,
+        creator,
+        treasury,
+        user,
+        user_app_access,
+        user_defai_ata,
+        creator_defai_ata,
+        treasury_defai_ata,
+        refund,
+        ..
+    } = purchase_with(Setup { burn_bps: 400, ..Default::default() }).await;
+
+    let platform_fee = PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+    let burned = PRICE * 400 / 10_000;
+    assert_eq!(env.token_balance(&creator_defai_ata).await, PRICE - platform_fee);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, platform_fee - burned);
+    let paid_out = env.account::<UserAppAccess>(&user_app_access).await.paid_out;
+    assert_eq!(paid_out.burned, burned);
+    assert_eq!(paid_out.treasury, platform_fee - burned);
+    assert_eq!(paid_out.creator, PRICE - platform_fee);
+
+    // Dropping the platform fee to zero after the sale doesn't change who repays what
+    let ix = anchor_ix(
+        defai_app_factory::ID,
+        accounts::UpdatePlatformSettings {
+            app_factory: pda(&[b"app_factory"]),
+            authority: env.payer(),
+            new_treasury: treasury.pubkey(),
+        },
+        instruction::UpdatePlatformSettings { new_platform_fee_bps: Some(0) },
+    );
+    env.send(&[ix], &[]).await.unwrap();
+
+    env.send(&[refund], &[&user, &creator, &treasury]).await.unwrap();
+    assert_eq!(env.token_balance(&user_defai_ata).await, USER_FUNDS - burned);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, 0);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, 0);
+}
+
+// A referred sale pays the affiliate out of the creator share. The commission
+// has already left for the affiliate's wallet, so on a refund the creator
+// returns it along with their own part and the affiliate keeps it.
+#[tokio::test]
+async fn refund_of_referred_sale_is_paid_by_creator() {
+    let referrer = Keypair::new();
+    let Purchased {
+        mut env,
+        creator,
+        treasury,
+        user,
+        user_app_access,
+        user_defai_ata,
+        creator_defai_ata,
+        treasury_defai_ata,
+        defai_mint,
+        referrer_defai_ata,
+        refund,
+        ..
+    } = purchase_with(Setup { referrer: Some(&referrer), ..Default::default() }).await;
+    let referrer_defai_ata = referrer_defai_ata.unwrap();
+
+    let platform_fee = PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+    let commission = PRICE * AFFILIATE_BPS as u64 / 10_000;
+    assert_eq!(env.token_balance(&referrer_defai_ata).await, commission);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, PRICE - platform_fee - commission);
+    let paid_out = env.account::<UserAppAccess>(&user_app_access).await.paid_out;
+    assert_eq!(paid_out.affiliate, commission);
+    assert_eq!(paid_out.creator, PRICE - platform_fee - commission);
+
+    // The creator can't cover the commission out of this sale alone
+    let err = env.send(std::slice::from_ref(&refund), &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(AppFactoryError::InsufficientCreatorBalance.into()));
+
+    env.mint_to(&TOKEN_PROGRAM_ID, &defai_mint, &creator_defai_ata, commission).await;
+    env.send(&[refund], &[&user, &creator, &treasury]).await.unwrap();
+    assert_eq!(env.token_balance(&user_defai_ata).await, USER_FUNDS);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, 0);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, 0);
+    assert_eq!(env.token_balance(&referrer_defai_ata).await, commission);
+}
+
+// Split recipients are paid at purchase and don't sign refunds, and the creator
+// received none of the sale, so a purchase with a revenue split can't be refunded
+#[tokio::test]
+async fn refund_of_split_sale_is_rejected() {
+    let split_wallet = Pubkey::new_unique();
+    let Purchased {
+        mut env,
+        creator,
+        treasury,
+        user,
+        user_app_access,
+        user_sft_ata,
+        creator_defai_ata,
+        defai_mint,
+        refund,
+        ..
+    } = purchase_with(Setup { split_wallet: Some(split_wallet), ..Default::default() }).await;
+
+    let platform_fee = PRICE * PLATFORM_FEE_BPS as u64 / 10_000;
+    let split_ata = ata(&split_wallet, &defai_mint, &TOKEN_PROGRAM_ID);
+    assert_eq!(env.token_balance(&split_ata).await, PRICE - platform_fee);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, 0);
+    let paid_out = env.account::<UserAppAccess>(&user_app_access).await.paid_out;
+    assert_eq!((paid_out.creator, paid_out.split), (0, PRICE - platform_fee));
+
+    // Even a creator with funds to spare isn't charged for the recipients' cut
+    env.mint_to(&TOKEN_PROGRAM_ID, &defai_mint, &creator_defai_ata, PRICE).await;
+    let err = env.send(&[refund], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::ThirdPartyPayout.into()));
+    assert_eq!(env.token_balance(&user_sft_ata).await, 1);
+    assert_eq!(env.token_balance(&creator_defai_ata).await, PRICE);
+}
@@ -0,0 +1,311 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{
+    accounts, instruction, AppRegistration, DisputeError, PurchaseSplit, RefundError, RentalError, UserAppAccess,
+};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u64 = 1_000;
+const BURN_BPS: u64 = 400;
+const PRICE: u64 = 50 * DEFAI;
+const RENTAL_PRICE: u64 = 20 * DEFAI;
+const RENTAL_DAYS: u16 = 10;
+const DAY: i64 = 24 * 60 * 60;
+const USER_FUNDS: u64 = 100 * DEFAI;
+const APP_ID: u64 = 0;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+fn access(user: &Pubkey) -> Pubkey {
+    pda(&[b"user_app_access", user.as_ref(), &APP_ID.to_le_bytes()])
+}
+
+struct Rentable {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    defai_mint: Pubkey,
+    sft_mint: Pubkey,
+    app_registration: Pubkey,
+    creator_defai_ata: Pubkey,
+    treasury_defai_ata: Pubkey,
+}
+
+impl Rentable {
+    fn set_terms(&self, rental_price: u64, rental_days: u16) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::SetRentalTerms { app_registration: self.app_registration, creator: self.creator.pubkey() },
+            instruction::SetRentalTerms { app_id: APP_ID, rental_price, rental_days },
+        )
+    }
+
+    fn rent(&self, user: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::RentAppAccess {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(user, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                creator_defai_ata: self.creator_defai_ata,
+                creator_vesting: None,
+                vesting_vault: None,
+                charity_defai_ata: None,
+                treasury_defai_ata: self.treasury_defai_ata,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                user: *user,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                associated_token_program: spl_associated_token_account::ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RentAppAccess { app_id: APP_ID },
+        )
+    }
+
+    fn refund(&self, user: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::RefundPurchase {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                rent_payer: *user,
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user_defai_ata: ata(user, &self.defai_mint, &TOKEN_PROGRAM_ID),
+                user_review: pda(&[b"app_review", user.as_ref(), &APP_ID.to_le_bytes()]),
+                creator_defai_ata: self.creator_defai_ata,
+                treasury_defai_ata: self.treasury_defai_ata,
+                creator_vesting: None,
+                vesting_vault: None,
+                user: *user,
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RefundPurchase { app_id: APP_ID, reason: "Done with it".into() },
+        )
+    }
+
+    fn expire(&self, user: &Pubkey, caller: &Pubkey) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::ExpireAccess {
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                rent_payer: *user,
+                sft_mint: self.sft_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user: *user,
+                caller: *caller,
+                token_program: TOKEN_PROGRAM_ID,
+            },
+            instruction::ExpireAccess { app_id: APP_ID },
+        )
+    }
+
+    fn open_dispute(&self, user: &Pubkey) -> Instruction {
+        let dispute = pda(&[b"dispute", user.as_ref(), &APP_ID.to_le_bytes()]);
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::OpenDispute {
+                app_factory: pda(&[b"app_factory"]),
+                app_registration: self.app_registration,
+                user_app_access: access(user),
+                dispute,
+                dispute_vault: pda(&[b"dispute_vault", dispute.as_ref()]),
+                creator_vesting: None,
+                vesting_vault: None,
+                sft_mint: self.sft_mint,
+                defai_mint: self.defai_mint,
+                user_sft_ata: ata(user, &self.sft_mint, &TOKEN_PROGRAM_ID),
+                user: *user,
+                token_program: TOKEN_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::OpenDispute { app_id: APP_ID, reason_cid: "bafy-complaint".into() },
+        )
+    }
+
+    // A wallet holding USER_FUNDS DEFAI that rents the app
+    async fn renter(&mut self) -> Keypair {
+        let user = self.env.funded_keypair(5 * SOL).await;
+        self.env.fund_ata(&user.pubkey(), &self.defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+        let ix = self.rent(&user.pubkey());
+        self.env.send(&[ix], &[&user]).await.unwrap();
+        user
+    }
+}
+
+// Factory with a purchase burn and one app, before its rental terms are set
+async fn registered() -> Rentable {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS as u16 },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetBurnBps { app_factory, authority },
+            instruction::SetBurnBps { burn_bps: BURN_BPS as u16 },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let ix = anchor_ix(
+        program_id,
+        accounts::RegisterApp {
+            app_factory,
+            factory_stats,
+            app_registration,
+            sft_mint,
+            verified_creator: None,
+            category_registry: None,
+            creator_defai_ata: None,
+            treasury_defai_ata: None,
+            stake_vault: None,
+            treasury_ledger: None,
+            creator: creator.pubkey(),
+            system_program: system_program::ID,
+            token_program: TOKEN_PROGRAM_ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::RegisterApp {
+            price: PRICE,
+            max_supply: 100,
+            metadata_uri: "ipfs://app".into(),
+            categories: vec![],
+            royalty_bps: 0,
+            refund_policy: None,
+        },
+    );
+    env.send(&[ix], &[&creator]).await.unwrap();
+
+    Rentable {
+        creator_defai_ata: ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID),
+        treasury_defai_ata: ata(&treasury.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID),
+        env,
+        creator,
+        treasury,
+        defai_mint,
+        sft_mint,
+        app_registration,
+    }
+}
+
+// A rental holds a frozen SFT until it expires, refunds the unused part of
+// its period like a subscription, and can't be disputed
+#[tokio::test]
+async fn rental_refund_and_dispute() {
+    let mut r = registered().await;
+    let (creator, treasury) = (r.creator.insecure_clone(), r.treasury.insecure_clone());
+
+    // Rentals cost less than buying, for 1 to 365 days
+    let err = r.env.send(&[r.set_terms(PRICE, RENTAL_DAYS)], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RentalError::RentalPriceTooHigh.into()));
+    let err = r.env.send(&[r.set_terms(RENTAL_PRICE, 0)], &[&creator]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RentalError::InvalidRentalDuration.into()));
+    r.env.send(&[r.set_terms(RENTAL_PRICE, RENTAL_DAYS)], &[&creator]).await.unwrap();
+
+    let user = r.renter().await;
+    let rented_at = r.env.now().await;
+    let user_sft_ata = ata(&user.pubkey(), &r.sft_mint, &TOKEN_PROGRAM_ID);
+    assert_eq!(r.env.token_balance(&user_sft_ata).await, 1);
+    assert!(r.env.token_frozen(&user_sft_ata).await);
+    let record: UserAppAccess = r.env.account(&access(&user.pubkey())).await;
+    assert_eq!(record.expires_at, rented_at + RENTAL_DAYS as i64 * DAY);
+    let fee = RENTAL_PRICE * PLATFORM_FEE_BPS / 10_000;
+    let burned = RENTAL_PRICE * BURN_BPS / 10_000;
+    let paid = PurchaseSplit { burned, treasury: fee - burned, creator: RENTAL_PRICE - fee, ..Default::default() };
+    assert!(record.paid_out == paid);
+
+    // Disputes are for permanent purchases; a rental is refunded instead
+    let err = r.env.send(&[r.open_dispute(&user.pubkey())], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(DisputeError::RentalNotDisputable.into()));
+
+    // Halfway through, half of each recorded part except the burn comes back
+    r.env.warp_forward(RENTAL_DAYS as i64 * DAY / 2).await;
+    r.env.send(&[r.refund(&user.pubkey())], &[&user, &creator, &treasury]).await.unwrap();
+    let user_defai_ata = ata(&user.pubkey(), &r.defai_mint, &TOKEN_PROGRAM_ID);
+    assert_eq!(r.env.token_balance(&user_defai_ata).await, USER_FUNDS - RENTAL_PRICE + (paid.treasury + paid.creator) / 2);
+    assert_eq!(r.env.token_balance(&r.creator_defai_ata).await, paid.creator / 2);
+    assert_eq!(r.env.token_balance(&r.treasury_defai_ata).await, paid.treasury / 2);
+    assert_eq!(r.env.token_balance(&user_sft_ata).await, 0);
+    assert!(!r.env.exists(&access(&user.pubkey())).await);
+    assert_eq!(r.env.account::<AppRegistration>(&r.app_registration).await.current_supply, 0);
+}
+
+// A rental can't be expired early; once it runs out nothing is refundable and
+// anyone can burn the SFT and close the record
+#[tokio::test]
+async fn rental_expiry() {
+    let mut r = registered().await;
+    let (creator, treasury) = (r.creator.insecure_clone(), r.treasury.insecure_clone());
+    r.env.send(&[r.set_terms(RENTAL_PRICE, RENTAL_DAYS)], &[&creator]).await.unwrap();
+    let user = r.renter().await;
+    let cranker = r.env.funded_keypair(SOL).await;
+
+    r.env.warp_forward(RENTAL_DAYS as i64 * DAY - 1).await;
+    let err = r.env.send(&[r.expire(&user.pubkey(), &cranker.pubkey())], &[&cranker]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RentalError::RentalNotExpired.into()));
+
+    r.env.warp_forward(1).await;
+    let err = r.env.send(&[r.refund(&user.pubkey())], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::RefundWindowExpired.into()));
+
+    let user_sft_ata = ata(&user.pubkey(), &r.sft_mint, &TOKEN_PROGRAM_ID);
+    let lamports = r.env.lamports(&user.pubkey()).await;
+    r.env.send(&[r.expire(&user.pubkey(), &cranker.pubkey())], &[&cranker]).await.unwrap();
+    assert_eq!(r.env.token_balance(&user_sft_ata).await, 0);
+    assert!(!r.env.exists(&access(&user.pubkey())).await);
+    assert!(r.env.lamports(&user.pubkey()).await > lamports);
+    assert_eq!(r.env.account::<AppRegistration>(&r.app_registration).await.current_supply, 0);
+}
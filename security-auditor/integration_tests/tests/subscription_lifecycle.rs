@@ -0,0 +1,309 @@
+use anchor_lang::{prelude::Pubkey, system_program};
+use defai_app_factory::{accounts, instruction, PurchaseSplit, RefundError, Subscription, SubscriptionError};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+const DEFAI: u64 = 1_000_000;
+const PLATFORM_FEE_BPS: u64 = 1_000;
+const BURN_BPS: u64 = 400;
+const PRICE: u64 = 30 * DEFAI;
+const PERIOD: i64 = 10 * 24 * 60 * 60;
+const USER_FUNDS: u64 = 200 * DEFAI;
+const APP_ID: u64 = 0;
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_app_factory::ID).0
+}
+
+// Where a payment of `amount` goes: the burn comes out of the platform fee
+fn split(amount: u64) -> PurchaseSplit {
+    let fee = amount * PLATFORM_FEE_BPS / 10_000;
+    let burned = amount * BURN_BPS / 10_000;
+    PurchaseSplit { burned, treasury: fee - burned, creator: amount - fee, ..Default::default() }
+}
+
+struct Subscribed {
+    env: TestEnv,
+    creator: Keypair,
+    treasury: Keypair,
+    user: Keypair,
+    defai_mint: Pubkey,
+    user_defai_ata: Pubkey,
+    creator_defai_ata: Pubkey,
+    treasury_defai_ata: Pubkey,
+}
+
+impl Subscribed {
+    fn subscription(&self) -> Pubkey {
+        pda(&[b"subscription", self.user.pubkey().as_ref(), &APP_ID.to_le_bytes()])
+    }
+
+    fn renew(&self, periods: u16) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::RenewSubscription {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: pda(&[b"app_registration", &APP_ID.to_le_bytes()]),
+                subscription_plan: pda(&[b"subscription_plan", &APP_ID.to_le_bytes()]),
+                subscription: self.subscription(),
+                user_defai_ata: self.user_defai_ata,
+                creator_defai_ata: self.creator_defai_ata,
+                creator_vesting: None,
+                vesting_vault: None,
+                charity_defai_ata: None,
+                treasury_defai_ata: self.treasury_defai_ata,
+                revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+                user: self.user.pubkey(),
+                defai_mint: self.defai_mint,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RenewSubscription { app_id: APP_ID, periods },
+        )
+    }
+
+    fn refund(&self) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::RefundSubscription {
+                app_factory: pda(&[b"app_factory"]),
+                factory_stats: pda(&[b"factory_stats"]),
+                app_registration: pda(&[b"app_registration", &APP_ID.to_le_bytes()]),
+                subscription: self.subscription(),
+                user_defai_ata: self.user_defai_ata,
+                creator_defai_ata: self.creator_defai_ata,
+                treasury_defai_ata: self.treasury_defai_ata,
+                user: self.user.pubkey(),
+                creator: self.creator.pubkey(),
+                treasury: self.treasury.pubkey(),
+                defai_mint: self.defai_mint,
+                creator_vesting: None,
+                vesting_vault: None,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RefundSubscription { app_id: APP_ID },
+        )
+    }
+
+    fn set_plan_active(&self, is_active: bool) -> Instruction {
+        anchor_ix(
+            defai_app_factory::ID,
+            accounts::UpdateSubscriptionPlan {
+                subscription_plan: pda(&[b"subscription_plan", &APP_ID.to_le_bytes()]),
+                creator: self.creator.pubkey(),
+            },
+            instruction::UpdateSubscriptionPlan { app_id: APP_ID, new_price: None, is_active: Some(is_active) },
+        )
+    }
+
+    async fn state(&mut self) -> Subscription {
+        let subscription = self.subscription();
+        self.env.account(&subscription).await
+    }
+}
+
+// Factory with a purchase burn, one app with a PERIOD plan at PRICE, and a
+// user subscribed to it
+async fn subscribed() -> Subscribed {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_app_factory::ID;
+    let authority = env.payer();
+
+    let defai_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let master_collection = env.create_mint(&TOKEN_PROGRAM_ID, 0).await;
+    env.fund_ata(&authority, &master_collection, &TOKEN_PROGRAM_ID, 1).await;
+    let treasury = env.funded_keypair(SOL).await;
+
+    let app_factory = pda(&[b"app_factory"]);
+    let factory_stats = pda(&[b"factory_stats"]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::InitializeAppFactory {
+                app_factory,
+                authority,
+                defai_mint,
+                treasury: treasury.pubkey(),
+                master_collection,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAppFactory { platform_fee_bps: PLATFORM_FEE_BPS as u16 },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeFactoryStats { app_factory, factory_stats, authority, system_program: system_program::ID },
+            instruction::InitializeFactoryStats {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SetBurnBps { app_factory, authority },
+            instruction::SetBurnBps { burn_bps: BURN_BPS as u16 },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let creator = env.funded_keypair(5 * SOL).await;
+    let sft_mint = env.create_mint_with_authority(&TOKEN_PROGRAM_ID, 0, &creator.pubkey()).await;
+    let app_registration = pda(&[b"app_registration", &APP_ID.to_le_bytes()]);
+    let subscription_plan = pda(&[b"subscription_plan", &APP_ID.to_le_bytes()]);
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::RegisterApp {
+                app_factory,
+                factory_stats,
+                app_registration,
+                sft_mint,
+                verified_creator: None,
+                category_registry: None,
+                creator_defai_ata: None,
+                treasury_defai_ata: None,
+                stake_vault: None,
+                treasury_ledger: None,
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+                token_program: TOKEN_PROGRAM_ID,
+                event_authority: event_authority(&defai_app_factory::ID),
+                program: defai_app_factory::ID,
+            },
+            instruction::RegisterApp {
+                price: PRICE,
+                max_supply: 100,
+                metadata_uri: "ipfs://app".into(),
+                categories: vec![],
+                royalty_bps: 0,
+                refund_policy: None,
+            },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::CreateSubscriptionPlan {
+                app_registration,
+                subscription_plan,
+                creator: creator.pubkey(),
+                system_program: system_program::ID,
+            },
+            instruction::CreateSubscriptionPlan { app_id: APP_ID, period_seconds: PERIOD, price: PRICE },
+        ),
+    ];
+    env.send(&ixs, &[&creator]).await.unwrap();
+
+    // The creator and treasury ATAs are created by the subscription itself
+    let user = env.funded_keypair(5 * SOL).await;
+    let user_defai_ata = env.fund_ata(&user.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID, USER_FUNDS).await;
+    let creator_defai_ata = ata(&creator.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID);
+    let treasury_defai_ata = ata(&treasury.pubkey(), &defai_mint, &TOKEN_PROGRAM_ID);
+    let ix = anchor_ix(
+        program_id,
+        accounts::Subscribe {
+            app_factory,
+            factory_stats,
+            app_registration,
+            subscription_plan,
+            subscription: pda(&[b"subscription", user.pubkey().as_ref(), &APP_ID.to_le_bytes()]),
+            user_defai_ata,
+            creator_defai_ata,
+            creator_vesting: None,
+            vesting_vault: None,
+            charity_defai_ata: None,
+            treasury_defai_ata,
+            revenue_split: pda(&[b"revenue_split", &APP_ID.to_le_bytes()]),
+            user: user.pubkey(),
+            creator: creator.pubkey(),
+            treasury: treasury.pubkey(),
+            defai_mint,
+            token_program: TOKEN_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_app_factory::ID),
+            program: defai_app_factory::ID,
+        },
+        instruction::Subscribe { app_id: APP_ID },
+    );
+    env.send(&[ix], &[&user]).await.unwrap();
+
+    Subscribed {
+        env,
+        creator,
+        treasury,
+        user,
+        defai_mint,
+        user_defai_ata,
+        creator_defai_ata,
+        treasury_defai_ata,
+    }
+}
+
+// Renewing an active subscription extends it and adds to its recorded
+// payments; a refund halfway through returns half of each part except the
+// burn, from whoever received it, and ends access
+#[tokio::test]
+async fn renewal_then_refund() {
+    let mut s = subscribed().await;
+    let (user, creator, treasury) = (s.user.insecure_clone(), s.creator.insecure_clone(), s.treasury.insecure_clone());
+
+    let started = s.env.now().await;
+    let state = s.state().await;
+    assert_eq!((state.period_start, state.paid_through, state.paid_amount), (started, started + PERIOD, PRICE));
+    assert!(state.paid_out == split(PRICE));
+    assert_eq!(s.env.token_balance(&s.creator_defai_ata).await, split(PRICE).creator);
+    assert_eq!(s.env.token_balance(&s.treasury_defai_ata).await, split(PRICE).treasury);
+
+    let err = s.env.send(&[s.renew(0)], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(SubscriptionError::InvalidRenewalPeriods.into()));
+
+    // Two more periods while active extend from paid_through, in the same paid span
+    s.env.warp_forward(2 * 24 * 60 * 60).await;
+    s.env.send(&[s.renew(2)], &[&user]).await.unwrap();
+    let state = s.state().await;
+    assert_eq!((state.period_start, state.paid_through, state.paid_amount), (started, started + 3 * PERIOD, 3 * PRICE));
+    assert!(state.paid_out == split(3 * PRICE));
+
+    // Halfway through the span half of each recorded part comes back
+    s.env.warp_forward(3 * PERIOD / 2 - 2 * 24 * 60 * 60).await;
+    let paid = split(3 * PRICE);
+    let (creator_before, treasury_before) =
+        (s.env.token_balance(&s.creator_defai_ata).await, s.env.token_balance(&s.treasury_defai_ata).await);
+    s.env.send(&[s.refund()], &[&user, &creator, &treasury]).await.unwrap();
+    assert_eq!(s.env.token_balance(&s.user_defai_ata).await, USER_FUNDS - 3 * PRICE + (paid.treasury + paid.creator) / 2);
+    assert_eq!(s.env.token_balance(&s.creator_defai_ata).await, creator_before - paid.creator / 2);
+    assert_eq!(s.env.token_balance(&s.treasury_defai_ata).await, treasury_before - paid.treasury / 2);
+
+    let now = s.env.now().await;
+    let state = s.state().await;
+    assert_eq!(state.paid_through, now);
+    assert!(state.cancelled && state.paid_out == PurchaseSplit::default());
+    let err = s.env.send(&[s.refund()], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::NoRecordedPayment.into()));
+}
+
+// A subscription that runs out has nothing left to refund. Renewing it
+// restarts the paid span from now with only the new payment recorded.
+#[tokio::test]
+async fn lapsed_subscription_restarts() {
+    let mut s = subscribed().await;
+    let (user, creator, treasury) = (s.user.insecure_clone(), s.creator.insecure_clone(), s.treasury.insecure_clone());
+
+    s.env.warp_forward(PERIOD + 1).await;
+    let err = s.env.send(&[s.refund()], &[&user, &creator, &treasury]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(RefundError::RefundWindowExpired.into()));
+
+    // Renewals wait for the creator to reopen the plan
+    s.env.send(&[s.set_plan_active(false)], &[&creator]).await.unwrap();
+    let err = s.env.send(&[s.renew(1)], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(SubscriptionError::PlanNotActive.into()));
+    s.env.send(&[s.set_plan_active(true)], &[&creator]).await.unwrap();
+
+    s.env.send(&[s.renew(1)], &[&user]).await.unwrap();
+    let now = s.env.now().await;
+    let state = s.state().await;
+    assert_eq!((state.period_start, state.paid_through, state.paid_amount), (now, now + PERIOD, PRICE));
+    assert!(state.paid_out == split(PRICE));
+}
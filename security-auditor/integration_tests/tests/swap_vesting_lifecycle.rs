@@ -0,0 +1,267 @@
+use anchor_lang::{prelude::Pubkey, solana_program::sysvar, system_program};
+use defai_integration_tests::{anchor_ix, ata, error_code, event_authority, TestEnv, SOL, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use defai_swap::{accounts, instruction, treasury::TreasuryLedger, BonusStateV6, ErrorCode, VestingStateV6};
+use solana_sdk::signature::{Keypair, Signer};
+
+// Mirrors of the program's private schedule constants
+const VESTING_DURATION: i64 = 90 * 24 * 60 * 60;
+const CLIFF_DURATION: i64 = 2 * 24 * 60 * 60;
+const INITIAL_TAX_BPS: u64 = 500;
+const BUYBACK_TAX_SHARE_BPS: u16 = 2_000;
+
+const DEFAI: u64 = 1_000_000;
+const TIER: u8 = 4; // 50-300% bonus, so the vesting never falls under the dust threshold
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_swap::ID).0
+}
+
+// Swap DEFAI for a tier NFT, vest the bonus past the cliff to the end, then
+// redeem the NFT for its base price.
+#[tokio::test]
+async fn swap_vest_redeem_lifecycle() {
+    let mut env = TestEnv::start().await;
+    let program_id = defai_swap::ID;
+    let admin = env.payer();
+
+    let old_mint = env.create_mint(&TOKEN_PROGRAM_ID, 6).await;
+    let defai_mint = env.create_mint(&TOKEN_2022_PROGRAM_ID, 6).await;
+    let treasury = Keypair::new().pubkey();
+    let prices = [0, 100 * DEFAI, 500 * DEFAI, 800 * DEFAI, 1_000 * DEFAI];
+
+    let config = pda(&[b"config"]);
+    let escrow = pda(&[b"escrow"]);
+    let randomness_state = pda(&[b"randomness_state"]);
+    let collection_config = pda(&[b"collection_config"]);
+    let treasury_ledger = pda(&[b"treasury_ledger"]);
+    let buyback_state = pda(&[b"buyback_state"]);
+    let buyback_vault = pda(&[b"buyback_vault"]);
+
+    let ixs = [
+        anchor_ix(
+            program_id,
+            accounts::Initialize {
+                admin,
+                old_mint,
+                new_mint: defai_mint,
+                collection: Keypair::new().pubkey(),
+                treasury,
+                config,
+                escrow,
+                tax_state: pda(&[b"tax_state"]),
+                system_program: system_program::ID,
+            },
+            instruction::Initialize { prices: prices.to_vec() },
+        ),
+        // VRF mode is on by default, so seed the randomness state before swapping
+        anchor_ix(
+            program_id,
+            accounts::InitializeRandomness { authority: admin, randomness_state, system_program: system_program::ID },
+            instruction::InitializeRandomnessV2 {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::SimpleRandomness { authority: admin, randomness_state, recent_blockhashes: sysvar::recent_blockhashes::ID },
+            instruction::GenerateSimpleRandomness {},
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeCollection {
+                authority: admin,
+                collection_mint: Keypair::new().pubkey(),
+                treasury,
+                defai_mint,
+                old_defai_mint: old_mint,
+                collection_config,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeCollection {
+                tier_names: vec!["OG".into(), "Train".into(), "Boat".into(), "Plane".into(), "Rocket".into()],
+                tier_symbols: vec!["OG".into(), "TRN".into(), "BOAT".into(), "PLN".into(), "RKT".into()],
+                tier_prices: prices,
+                tier_supplies: [10; 5],
+                tier_uri_prefixes: vec![String::new(); 5],
+                og_tier_0_merkle_root: [0; 32],
+                airdrop_merkle_root: [0; 32],
+                og_tier_0_supply: 0,
+            },
+        ),
+        anchor_ix(
+            program_id,
+            accounts::InitializeTreasuryLedger {
+                admin,
+                config,
+                mint: defai_mint,
+                treasury_ledger,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeTreasuryLedger {},
+        ),
+        // A fifth of every tax payment goes to the buyback vault
+        anchor_ix(
+            program_id,
+            accounts::InitializeBuyback {
+                admin,
+                config,
+                escrow,
+                buyback_state,
+                defai_mint,
+                buyback_vault,
+                token_program_2022: TOKEN_2022_PROGRAM_ID,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeBuyback { tax_share_bps: BUYBACK_TAX_SHARE_BPS },
+        ),
+    ];
+    env.send(&ixs, &[]).await.unwrap();
+
+    let treasury_defai_ata = env.create_ata(&treasury, &defai_mint, &TOKEN_2022_PROGRAM_ID).await;
+    // Escrow float covering base-price redemptions and bonus payouts
+    let escrow_defai_ata = env.fund_ata(&escrow, &defai_mint, &TOKEN_2022_PROGRAM_ID, 10_000 * DEFAI).await;
+
+    // The holder brings DEFAI and an NFT minted for the swap
+    let user = env.funded_keypair(5 * SOL).await;
+    let user_defai_ata = env.fund_ata(&user.pubkey(), &defai_mint, &TOKEN_2022_PROGRAM_ID, 2_000 * DEFAI).await;
+    let nft_mint = env.create_mint(&TOKEN_2022_PROGRAM_ID, 0).await;
+    let user_nft_ata = env.fund_ata(&user.pubkey(), &nft_mint, &TOKEN_2022_PROGRAM_ID, 1).await;
+    let bonus_state = pda(&[b"bonus_v6", nft_mint.as_ref()]);
+    let vesting_state = pda(&[b"vesting_v6", nft_mint.as_ref()]);
+
+    let ix = anchor_ix(
+        program_id,
+        accounts::InitializeUserTax {
+            user: user.pubkey(),
+            user_tax_state: pda(&[b"user_tax", user.pubkey().as_ref()]),
+            system_program: system_program::ID,
+        },
+        instruction::InitializeUserTax {},
+    );
+    env.send(&[ix], &[&user]).await.unwrap();
+
+    let ix = anchor_ix(
+        program_id,
+        accounts::SwapDefaiForPnftV6 {
+            user: user.pubkey(),
+            tier_phase: pda(&[b"tier_phase", &[TIER]]),
+            user_defai_ata,
+            randomness_state,
+            treasury_defai_ata,
+            treasury_ledger,
+            buyback_state,
+            buyback_vault,
+            escrow_defai_ata,
+            defai_mint,
+            config,
+            collection_config,
+            nft_mint,
+            nft_token_account: user_nft_ata,
+            bonus_state,
+            vesting_state,
+            escrow,
+            user_tax_state: pda(&[b"user_tax", user.pubkey().as_ref()]),
+            system_program: system_program::ID,
+            token_program_2022: TOKEN_2022_PROGRAM_ID,
+            recent_blockhashes: sysvar::recent_blockhashes::ID,
+            event_authority: event_authority(&defai_swap::ID),
+            program: defai_swap::ID,
+        },
+        instruction::SwapDefaiForPnftV6 {
+            tier: TIER,
+            _metadata_uri: String::new(),
+            _name: String::new(),
+            _symbol: String::new(),
+            presale_proof: vec![],
+        },
+    );
+    env.send_metered("swap_defai_for_pnft_v6", ix, &[&user]).await.unwrap();
+
+    let price = prices[TIER as usize];
+    let tax = price * INITIAL_TAX_BPS / 10_000;
+    let buyback = tax * BUYBACK_TAX_SHARE_BPS as u64 / 10_000;
+    assert_eq!(env.token_balance(&user_defai_ata).await, 2_000 * DEFAI - price);
+    assert_eq!(env.token_balance(&treasury_defai_ata).await, tax - buyback);
+    assert_eq!(env.token_balance(&buyback_vault).await, buyback);
+    assert_eq!(env.token_balance(&escrow_defai_ata).await, 10_000 * DEFAI + price - tax);
+
+    // The treasury's part of the tax is booked under RevenueSource::SwapTax
+    let ledger: TreasuryLedger = env.account(&treasury_ledger).await;
+    assert_eq!((ledger.revenue[0], ledger.revenue_entries[0]), (tax - buyback, 1));
+
+    let bonus: BonusStateV6 = env.account(&bonus_state).await;
+    let vesting: VestingStateV6 = env.account(&vesting_state).await;
+    assert_eq!(bonus.tier, TIER);
+    assert!((5_000..=30_000).contains(&bonus.bonus_bps));
+    assert_eq!(vesting.total_amount, price * bonus.bonus_bps as u64 / 10_000);
+    assert_eq!(vesting.released_amount, 0);
+
+    let claim_vested = anchor_ix(
+        program_id,
+        accounts::ClaimVestedV6 {
+            user: user.pubkey(),
+            nft_mint,
+            user_nft_ata,
+            payout_owner: user.pubkey(),
+            user_defai_ata: ata(&user.pubkey(), &defai_mint, &TOKEN_2022_PROGRAM_ID),
+            escrow_defai_ata,
+            defai_mint,
+            config,
+            escrow,
+            vesting_state,
+            token_program_2022: TOKEN_2022_PROGRAM_ID,
+            associated_token_program: spl_associated_token_account::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(&defai_swap::ID),
+            program: defai_swap::ID,
+        },
+        instruction::ClaimVestedV6 {},
+    );
+
+    // Nothing is claimable inside the cliff
+    let err = env.send(std::slice::from_ref(&claim_vested), &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(ErrorCode::StillInCliff.into()));
+
+    // Linear vesting after the cliff
+    env.warp_forward(CLIFF_DURATION + 1).await;
+    let before = env.token_balance(&user_defai_ata).await;
+    env.send_metered("claim_vested_v6", claim_vested.clone(), &[&user]).await.unwrap();
+    let partial = env.token_balance(&user_defai_ata).await - before;
+    assert!(partial > 0 && partial < vesting.total_amount);
+
+    // Everything is released once the schedule ends
+    env.warp_forward(VESTING_DURATION).await;
+    env.send(std::slice::from_ref(&claim_vested), &[&user]).await.unwrap();
+    let vesting: VestingStateV6 = env.account(&vesting_state).await;
+    assert_eq!(vesting.released_amount, vesting.total_amount);
+    assert_eq!(env.token_balance(&user_defai_ata).await, before + vesting.total_amount);
+
+    env.warp_forward(1).await;
+    let err = env.send(&[claim_vested], &[&user]).await.unwrap_err();
+    assert_eq!(error_code(&err), Some(ErrorCode::NothingToClaim.into()));
+
+    // Redeeming returns the base price, burns the NFT and closes its account
+    let before = env.token_balance(&user_defai_ata).await;
+    let ix = anchor_ix(
+        program_id,
+        accounts::RedeemV6 {
+            user: user.pubkey(),
+            nft_mint,
+            user_nft_ata,
+            user_defai_ata,
+            escrow_defai_ata,
+            defai_mint,
+            config,
+            escrow,
+            bonus_state,
+            vesting_state,
+            system_program: system_program::ID,
+            token_program_2022: TOKEN_2022_PROGRAM_ID,
+            event_authority: event_authority(&defai_swap::ID),
+            program: defai_swap::ID,
+        },
+        instruction::RedeemV6 {},
+    );
+    env.send_metered("redeem_v6", ix, &[&user]).await.unwrap();
+    assert_eq!(env.token_balance(&user_defai_ata).await, before + price);
+    assert!(!env.exists(&user_nft_ata).await);
+    assert!(env.account::<BonusStateV6>(&bonus_state).await.claimed);
+}
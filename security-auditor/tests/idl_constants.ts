@@ -0,0 +1,117 @@
+import * as fs from "fs";
+import * as path from "path";
+import { assert } from "chai";
+
+// Drift check between the PDA seeds each program declares in its account
+// constraints and the `*_SEED` constants it exports through the IDL. Clients
+// derive addresses from the exported constants, so a constraint seed without a
+// matching constant (or a renamed one) fails here instead of on-chain.
+//
+// Reads target/idl, so run `anchor build` first (Anchor.toml sets
+// `seeds = true`, which is what puts PDAs in the IDL).
+
+const IDL_DIR = path.resolve(__dirname, "../target/idl");
+
+// Seeds of PDAs owned by other programs (Metaplex token metadata)
+const FOREIGN_SEEDS = ["metadata"];
+
+type IdlConst = { name: string; type: unknown; value: string };
+type IdlSeed = { kind: string; type: unknown; value?: unknown };
+type IdlAccountItem = { name: string; pda?: { seeds: IdlSeed[] }; accounts?: IdlAccountItem[] };
+
+const loadIdl = (program: string) => JSON.parse(fs.readFileSync(path.join(IDL_DIR, `${program}.json`), "utf8"));
+
+const constants = (idl: any): Map<string, IdlConst> =>
+  new Map((idl.constants ?? []).map((c: IdlConst) => [c.name, c]));
+
+// Exported `&[u8]` constants come through as type "bytes" with a "[..]" value
+const exportedSeeds = (idl: any): Map<string, string> => {
+  const seeds = new Map<string, string>();
+  for (const c of constants(idl).values()) {
+    if (c.name.endsWith("_SEED") && c.type === "bytes") {
+      seeds.set(Buffer.from(JSON.parse(c.value)).toString("utf8"), c.name);
+    }
+  }
+  return seeds;
+};
+
+const constraintSeeds = (idl: any): Map<string, string> => {
+  const seeds = new Map<string, string>();
+  const visit = (ix: string, items: IdlAccountItem[]) => {
+    for (const item of items) {
+      if (item.accounts) visit(ix, item.accounts);
+      for (const seed of item.pda?.seeds ?? []) {
+        if (seed.kind === "const" && typeof seed.value === "string") {
+          seeds.set(seed.value, `${ix}.${item.name}`);
+        }
+      }
+    }
+  };
+  for (const ix of idl.instructions) visit(ix.name, ix.accounts);
+  return seeds;
+};
+
+const assertConstant = (idl: any, name: string, value: string | number) => {
+  const c = constants(idl).get(name);
+  assert.isDefined(c, `${name} is not exported`);
+  assert.equal(c!.value, String(value), `${name} value`);
+};
+
+for (const program of ["defai_swap", "defai_estate", "defai_app_factory"]) {
+  describe(`${program} IDL constants`, () => {
+    const idl = loadIdl(program);
+
+    it("declares PDAs", () => {
+      assert.isAbove(constraintSeeds(idl).size, 0);
+    });
+
+    it("exports a constant for every constraint seed", () => {
+      const exported = exportedSeeds(idl);
+      for (const [seed, account] of constraintSeeds(idl)) {
+        if (FOREIGN_SEEDS.includes(seed)) continue;
+        assert.isTrue(exported.has(seed), `${account} uses seed "${seed}" with no exported *_SEED constant`);
+      }
+    });
+  });
+}
+
+describe("Exported protocol constants", () => {
+  it("swap tax, vesting and tier bonus ranges", () => {
+    const idl = loadIdl("defai_swap");
+    assertConstant(idl, "INITIAL_TAX_BPS", 500);
+    assertConstant(idl, "TAX_INCREMENT_BPS", 100);
+    assertConstant(idl, "TAX_CAP_BPS", 3000);
+    assertConstant(idl, "TAX_RESET_DURATION", 86400);
+    assertConstant(idl, "VESTING_DURATION", 7776000);
+    assertConstant(idl, "CLIFF_DURATION", 172800);
+    assertConstant(idl, "DUST_VESTING_THRESHOLD", 10000000);
+
+    const ranges = [[0, 0], [0, 1500], [1500, 5000], [2000, 10000], [5000, 30000]];
+    ranges.forEach(([min, max], tier) => {
+      assertConstant(idl, `TIER_${tier}_MIN_BONUS`, min);
+      assertConstant(idl, `TIER_${tier}_MAX_BONUS`, max);
+    });
+  });
+
+  it("estate fees and periods", () => {
+    const idl = loadIdl("defai_estate");
+    assertConstant(idl, "ESTATE_FEE", 100000000);
+    assertConstant(idl, "RWA_FEE", 10000000);
+    assertConstant(idl, "MIN_INACTIVITY_PERIOD", 86400);
+    assertConstant(idl, "MAX_INACTIVITY_PERIOD", 9460800000);
+    assertConstant(idl, "MIN_GRACE_PERIOD", 86400);
+    assertConstant(idl, "MAX_GRACE_PERIOD", 7776000);
+    assertConstant(idl, "MAX_BENEFICIARIES", 10);
+    assertConstant(idl, "RISK_RESET_INTERVAL", 86400);
+  });
+
+  it("app factory fee ceilings and windows", () => {
+    const idl = loadIdl("defai_app_factory");
+    assertConstant(idl, "MAX_PLATFORM_FEE_BPS", 3000);
+    assertConstant(idl, "MAX_ROYALTY_BPS", 2000);
+    assertConstant(idl, "MAX_AFFILIATE_BPS", 5000);
+    assertConstant(idl, "MIN_REFUND_BPS", 5000);
+    assertConstant(idl, "DISPUTE_WINDOW", 604800);
+    assertConstant(idl, "STAKE_LOCK_PERIOD", 7776000);
+  });
+});